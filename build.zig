@@ -32,6 +32,66 @@ pub fn build(b: *std.Build) void {
         run_cmd.addArgs(args);
     }
 
+    // libbibval for C callers; the header is written by hand, and a test in
+    // capi.zig checks it against the exported functions
+    const lib = b.addLibrary(.{
+        .name = "bibval",
+        .linkage = .dynamic,
+        .root_module = b.createModule(.{
+            .root_source_file = b.path("src/capi.zig"),
+            .target = target,
+            .optimize = optimize,
+            .imports = &.{
+                .{ .name = "bibval", .module = mod },
+            },
+        }),
+    });
+    lib.installHeader(b.path("include/bibval.h"), "bibval.h");
+    lib.root_module.addAnonymousImport("bibval.h", .{ .root_source_file = b.path("include/bibval.h") });
+    const lib_step = b.step("lib", "Build libbibval and its C header");
+    lib_step.dependOn(&b.addInstallArtifact(lib, .{}).step);
+
+    // Parsing, matching and fixes without the network layer, for the browser
+    _ = b.addModule("bibval-core", .{
+        .root_source_file = b.path("src/core.zig"),
+        .target = target,
+    });
+
+    const wasm_target = b.resolveTargetQuery(.{ .cpu_arch = .wasm32, .os_tag = .freestanding });
+    const wasm = b.addExecutable(.{
+        .name = "bibval",
+        .root_module = b.createModule(.{
+            .root_source_file = b.path("src/wasm.zig"),
+            .target = wasm_target,
+            .optimize = .ReleaseSmall,
+            .imports = &.{
+                .{ .name = "bibval-core", .module = b.createModule(.{
+                    .root_source_file = b.path("src/core.zig"),
+                    .target = wasm_target,
+                    .optimize = .ReleaseSmall,
+                }) },
+            },
+        }),
+    });
+    wasm.entry = .disabled;
+    wasm.rdynamic = true;
+    const wasm_step = b.step("wasm", "Build the core as bibval.wasm for browsers");
+    wasm_step.dependOn(&b.addInstallArtifact(wasm, .{}).step);
+
+    const bench_exe = b.addExecutable(.{
+        .name = "bibval-bench",
+        .root_module = b.createModule(.{
+            .root_source_file = b.path("src/bench.zig"),
+            .target = target,
+            .optimize = .ReleaseFast,
+            .imports = &.{
+                .{ .name = "bibval", .module = mod },
+            },
+        }),
+    });
+    const bench_step = b.step("bench", "Run matching benchmarks");
+    bench_step.dependOn(&b.addRunArtifact(bench_exe).step);
+
     const mod_tests = b.addTest(.{
         .root_module = mod,
     });
@@ -42,7 +102,13 @@ pub fn build(b: *std.Build) void {
     });
     const run_exe_tests = b.addRunArtifact(exe_tests);
 
+    const lib_tests = b.addTest(.{
+        .root_module = lib.root_module,
+    });
+    const run_lib_tests = b.addRunArtifact(lib_tests);
+
     const test_step = b.step("test", "Run tests");
     test_step.dependOn(&run_mod_tests.step);
     test_step.dependOn(&run_exe_tests.step);
+    test_step.dependOn(&run_lib_tests.step);
 }
@@ -0,0 +1,56 @@
+/*
+ * bibval C API
+ *
+ * Build with `zig build lib`, which installs libbibval and this header
+ * under zig-out/. Mirrors src/capi.zig, whose tests check that the two
+ * agree.
+ *
+ * Strings are UTF-8 and NUL-terminated. Strings passed to bibval remain
+ * owned by the caller. Strings returned by bibval are owned by the caller
+ * and must be released with bibval_string_free (not free()). Calls share
+ * no state and may be made from several threads at once.
+ */
+#ifndef BIBVAL_H
+#define BIBVAL_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Bumped on any incompatible change to these functions or the request format. */
+#define BIBVAL_ABI_VERSION 1
+
+/* The ABI version of the linked library; compare with BIBVAL_ABI_VERSION. */
+int bibval_abi_version(void);
+
+/*
+ * Validate the references in a JSON request:
+ *
+ *   {"input": "@article{...}",
+ *    "options": {"crossref": true, "dblp": true, "semantic_scholar": true,
+ *                "openalex": true, "open_library": true, "google_books": true,
+ *                "arxiv": true, "pmlr": true, "neurips": true,
+ *                "ieee_api_key": null, "acm": false,
+ *                "springer_api_key": null, "scopus_api_key": null,
+ *                "github": true, "github_token": null,
+ *                "zenodo": true, "figshare": true,
+ *                "web": false,
+ *                "cache": true, "disable": ["BV004"],
+ *                "min_confidence": 0.0, "request_interval_ms": 0,
+ *                "check_funders": false, "open_access": false,
+ *                "suggest_archives": false}}
+ *
+ * "input" is BibTeX, CSL-JSON or RIS (detected); all options are optional.
+ * Returns the JSON report of `bibval check --json`, or {"error": "..."}.
+ * Blocks while the APIs are queried. Returns NULL only if memory runs out.
+ */
+char *bibval_validate_json(const char *request);
+
+/* Release a string returned by bibval. NULL is ignored. */
+void bibval_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BIBVAL_H */
@@ -0,0 +1,135 @@
+//! Matching benchmarks.
+//!
+//! Run with `zig build bench` (ReleaseFast). Entries are generated from a
+//! fixed seed, so timings are comparable between runs.
+
+const std = @import("std");
+const bibval = @import("bibval");
+const Entry = bibval.Entry;
+
+const words = [_][]const u8{
+    "learning", "neural", "networks", "deep", "bayesian", "inference", "graph",
+    "attention", "language", "models", "optimal", "transport", "causal", "discovery",
+    "sparse", "gaussian", "processes", "robust", "estimation", "adversarial", "training",
+    "protein", "structure", "prediction", "stochastic", "gradient", "descent", "variational",
+    "reinforcement", "policy", "search", "quantum", "chemistry", "molecular", "dynamics",
+    "convex", "analysis", "probabilistic", "programming", "kernel", "methods", "time",
+    "series", "forecasting", "vision", "transformers", "efficient", "scalable", "federated",
+};
+
+pub fn main() !void {
+    var gpa = std.heap.GeneralPurposeAllocator(.{}){};
+    defer _ = gpa.deinit();
+    const allocator = gpa.allocator();
+
+    var stdout_buf: [4096]u8 = undefined;
+    var stdout_writer = std.fs.File.stdout().writer(&stdout_buf);
+    const stdout = &stdout_writer.interface;
+    defer stdout.flush() catch {};
+
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const entries = try generate(arena_state.allocator(), 10_000);
+
+    try stdout.print("{s:<36}{s:>12}{s:>14}\n", .{ "BENCHMARK", "RESULT", "TIME" });
+    var timer = try std.time.Timer.start();
+
+    var chars: usize = 0;
+    for (entries) |e| {
+        const normalized = try bibval.entry.normalizeString(allocator, e.title.?);
+        defer allocator.free(normalized);
+        chars += normalized.len;
+    }
+    try row(stdout, "normalizeString (10k)", chars, timer.lap());
+
+    // Title similarity of neighbouring entries with each metric
+    const metrics = [_]struct { name: []const u8, metric: bibval.matcher.Similarity }{
+        .{ .name = "titleSimilarity jaro-winkler (10k)", .metric = bibval.matcher.jaro_winkler },
+        .{ .name = "titleSimilarity token-ratio (10k)", .metric = bibval.matcher.token_ratio },
+        .{ .name = "titleSimilarity levenshtein (10k)", .metric = bibval.matcher.levenshtein },
+        .{ .name = "titleSimilarity trigram (10k)", .metric = bibval.matcher.trigram },
+    };
+    for (metrics) |m| {
+        var similar: usize = 0;
+        for (entries[1..], 0..) |*e, i| {
+            if (try bibval.matcher.titleSimilarityWith(allocator, m.metric, &entries[i], e) >= bibval.matcher.TITLE_MATCH_THRESHOLD) similar += 1;
+        }
+        try row(stdout, m.name, similar, timer.lap());
+    }
+
+    // Best match among 20 search results, as for one API response
+    const config = bibval.matcher.MatcherConfig{};
+    var found: usize = 0;
+    var start: usize = 0;
+    while (start + 20 <= 1_000) : (start += 1) {
+        if (try bibval.matcher.findBestMatch(allocator, &config, &entries[start], entries[start + 1 .. start + 21]) != null) found += 1;
+    }
+    try row(stdout, "findBestMatch 20 candidates (1k)", found, timer.lap());
+
+    // Blocking: index and pair up all 10k entries
+    var index = try bibval.matcher.BlockingIndex.init(allocator, entries);
+    defer index.deinit();
+    try row(stdout, "blocking index build (10k)", entries.len, timer.lap());
+
+    const pairs = try index.pairs(allocator);
+    defer allocator.free(pairs);
+    try row(stdout, "blocking pairs (10k)", pairs.len, timer.lap());
+
+    const normalized = try bibval.matcher.normalizeAll(allocator, entries);
+    defer {
+        for (normalized) |*n| n.deinit();
+        allocator.free(normalized);
+    }
+    try row(stdout, "normalize entries (10k)", normalized.len, timer.lap());
+
+    var matches: usize = 0;
+    for (pairs) |pair| {
+        if (try bibval.matcher.normalizedMatchScore(allocator, &config, &normalized[pair.a], &normalized[pair.b]) > 0) matches += 1;
+    }
+    try row(stdout, "match blocked pairs (10k)", matches, timer.lap());
+
+    // Baseline: every pair of the first 2k entries
+    const naive = normalized[0..2_000];
+    matches = 0;
+    for (naive, 0..) |*a, i| {
+        for (naive[i + 1 ..]) |*b| {
+            if (try bibval.matcher.normalizedMatchScore(allocator, &config, a, b) > 0) matches += 1;
+        }
+    }
+    try row(stdout, "match all pairs (2k)", matches, timer.lap());
+}
+
+fn row(writer: *std.Io.Writer, name: []const u8, result: usize, ns: u64) !void {
+    try writer.print("{s:<36}{d:>12}{d:>11.1} ms\n", .{ name, result, @as(f64, @floatFromInt(ns)) / std.time.ns_per_ms });
+}
+
+/// Random titles of four to ten words; every tenth entry repeats an
+/// earlier title with a typo, as duplicates would.
+fn generate(arena: std.mem.Allocator, n: usize) ![]Entry {
+    var prng = std.Random.DefaultPrng.init(42);
+    const random = prng.random();
+
+    const entries = try arena.alloc(Entry, n);
+    for (entries, 0..) |*e, i| {
+        const year = 1990 + random.intRangeAtMost(i32, 0, 34);
+        if (i > 0 and i % 10 == 0) {
+            const original = entries[random.uintLessThan(usize, i)];
+            const title = try arena.dupe(u8, original.title.?);
+            title[title.len / 2] = 'x';
+            e.* = .{ .key = "dup", .entry_type = "article", .title = title, .year = original.year, .authors = original.authors };
+            continue;
+        }
+
+        var title: std.ArrayList(u8) = .empty;
+        const len = random.intRangeAtMost(usize, 4, 10);
+        for (0..len) |w| {
+            if (w > 0) try title.append(arena, ' ');
+            try title.appendSlice(arena, words[random.uintLessThan(usize, words.len)]);
+        }
+        const author = try std.fmt.allocPrint(arena, "Author{d}, A.", .{random.uintLessThan(usize, 2_000)});
+        const authors = try arena.alloc([]const u8, 1);
+        authors[0] = author;
+        e.* = .{ .key = "gen", .entry_type = "article", .title = title.items, .year = year, .authors = authors };
+    }
+    return entries;
+}
@@ -1,10 +1,13 @@
 //! BibTeX/BibLaTeX parser.
 //!
-//! Parses .bib files into normalized Entry structures.
+//! Parses .bib files into normalized Entry structures. `@string` macros
+//! are expanded where they are used, so `journal = jmlr` reads as the
+//! journal name the macro stands for.
 
 const std = @import("std");
 const entry_mod = @import("entry.zig");
 const Entry = entry_mod.Entry;
+const fix = @import("fix.zig");
 
 pub const ParseError = error{
     InvalidSyntax,
@@ -13,6 +16,65 @@ pub const ParseError = error{
     InvalidCharacter,
 };
 
+/// A problem the parser recovered from.
+pub const Problem = enum {
+    unbalanced_braces,
+    missing_delimiter,
+    missing_comma,
+    missing_equals,
+    trailing_text,
+
+    pub fn describe(self: Problem) []const u8 {
+        return switch (self) {
+            .unbalanced_braces => "unbalanced braces; entry skipped",
+            .missing_delimiter => "expected '{' or '(' after the entry type; entry skipped",
+            .missing_comma => "missing comma between fields",
+            .missing_equals => "field without '='; field skipped",
+            .trailing_text => "text after the end of an entry (extra closing brace?); ignored",
+        };
+    }
+};
+
+/// Location (1-based) of a problem in the input.
+pub const Diagnostic = struct {
+    line: usize,
+    column: usize,
+    problem: Problem,
+};
+
+/// Collects diagnostics while parsing, if the caller wants them.
+const Reporter = struct {
+    allocator: std.mem.Allocator,
+    content: []const u8,
+    diagnostics: ?*std.ArrayList(Diagnostic),
+
+    fn report(self: Reporter, offset: usize, problem: Problem) !void {
+        const list = self.diagnostics orelse return;
+        const loc = location(self.content, offset);
+        try list.append(self.allocator, .{ .line = loc.line, .column = loc.column, .problem = problem });
+    }
+};
+
+/// An `@string` definition.
+pub const Macro = struct {
+    /// Name as written, e.g. "jmlr".
+    name: []const u8,
+    value: []const u8,
+};
+
+/// Macro values by lowercase name; BibTeX macro names ignore case.
+const Macros = std.StringHashMapUnmanaged([]const u8);
+
+/// Line and column (1-based) of byte `offset` in `content`.
+pub fn location(content: []const u8, offset: usize) struct { line: usize, column: usize } {
+    const before = content[0..@min(offset, content.len)];
+    const line_start = if (std.mem.lastIndexOfScalar(u8, before, '\n')) |nl| nl + 1 else 0;
+    return .{
+        .line = std.mem.count(u8, before, "\n") + 1,
+        .column = before.len - line_start + 1,
+    };
+}
+
 /// Parse a BibTeX file and return normalized entries.
 pub fn parseFile(allocator: std.mem.Allocator, path: []const u8) ![]Entry {
     const file = try std.fs.cwd().openFile(path, .{});
@@ -26,12 +88,31 @@ pub fn parseFile(allocator: std.mem.Allocator, path: []const u8) ![]Entry {
 
 /// Parse a BibTeX string and return normalized entries.
 pub fn parseString(allocator: std.mem.Allocator, content: []const u8) ![]Entry {
+    return parseStringDiagnostics(allocator, content, null);
+}
+
+/// Parse a BibTeX string, skipping malformed entries. Each problem
+/// recovered from is appended to `diagnostics` if given.
+pub fn parseStringDiagnostics(allocator: std.mem.Allocator, content: []const u8, diagnostics: ?*std.ArrayList(Diagnostic)) ![]Entry {
     var entries: std.ArrayList(Entry) = .empty;
     errdefer {
         for (entries.items) |*e| e.deinit();
         entries.deinit(allocator);
     }
 
+    const reporter = Reporter{ .allocator = allocator, .content = content, .diagnostics = diagnostics };
+    var macros: Macros = .empty;
+    defer {
+        var it = macros.iterator();
+        while (it.next()) |kv| {
+            allocator.free(kv.key_ptr.*);
+            allocator.free(kv.value_ptr.*);
+        }
+        macros.deinit(allocator);
+    }
+    // Only the first stray field after an entry is reported
+    var after_entry = false;
+
     var pos: usize = 0;
     while (pos < content.len) {
         while (pos < content.len) {
@@ -48,19 +129,106 @@ pub fn parseString(allocator: std.mem.Allocator, content: []const u8) ![]Entry {
 
         if (pos >= content.len) break;
 
-        if (content[pos] == '@') {
-            if (try parseEntry(allocator, content, &pos)) |parsed_entry| {
-                try entries.append(allocator, parsed_entry);
+        if (content[pos] != '@') {
+            if (after_entry and content[pos] == '=') {
+                try reporter.report(pos, .trailing_text);
+                after_entry = false;
             }
-        } else {
             pos += 1;
+            continue;
+        }
+
+        // Skip to the next entry when this one cannot be delimited or its
+        // braces run into the next one
+        const next = nextEntryStart(content, pos + 1) orelse content.len;
+        const end = switch (entryExtent(content, pos)) {
+            .end => |end| end,
+            .text => {
+                pos += 1;
+                continue;
+            },
+            // Mid-line, an `@` is more likely prose than a broken entry
+            .problem => |problem| {
+                if (!atLineStart(content, pos)) {
+                    pos += 1;
+                    continue;
+                }
+                try reporter.report(pos, problem);
+                pos = next;
+                continue;
+            },
+        };
+        if (end > next) {
+            try reporter.report(pos, .unbalanced_braces);
+            pos = next;
+            continue;
         }
+
+        if (try parseEntry(allocator, content[0 .. end + 1], &pos, reporter, &macros)) |parsed_entry| {
+            try entries.append(allocator, parsed_entry);
+        }
+        pos = end + 1;
+        after_entry = true;
     }
 
     return entries.toOwnedSlice(allocator);
 }
 
-fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?Entry {
+/// How far the entry at an `@` extends.
+const Extent = union(enum) {
+    /// Index of the closing delimiter
+    end: usize,
+    /// The `@` is part of comment text, e.g. an email address
+    text,
+    problem: Problem,
+};
+
+fn entryExtent(content: []const u8, at: usize) Extent {
+    var pos = at + 1;
+    const type_start = pos;
+    while (pos < content.len and (std.ascii.isAlphanumeric(content[pos]) or content[pos] == '_')) pos += 1;
+    if (pos == type_start) return .text;
+    // `@comment` without braces comments out the rest of the line
+    const is_comment = std.ascii.eqlIgnoreCase(content[type_start..pos], "comment");
+
+    while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+    const open_char = if (pos < content.len) content[pos] else 0;
+    const close_char: u8 = switch (open_char) {
+        '{' => '}',
+        '(' => ')',
+        else => return if (is_comment) .text else .{ .problem = .missing_delimiter },
+    };
+    const end = findClose(content, pos + 1, close_char) orelse return .{ .problem = .unbalanced_braces };
+    return .{ .end = end };
+}
+
+/// Offset of the next `@type{` or `@type(` that starts a line.
+fn nextEntryStart(content: []const u8, from: usize) ?usize {
+    var pos = from;
+    while (std.mem.indexOfScalarPos(u8, content, pos, '@')) |at| {
+        pos = at + 1;
+        if (!atLineStart(content, at)) continue;
+        var i = at + 1;
+        while (i < content.len and std.ascii.isAlphabetic(content[i])) i += 1;
+        if (i == at + 1) continue;
+        while (i < content.len and (content[i] == ' ' or content[i] == '\t')) i += 1;
+        if (i < content.len and (content[i] == '{' or content[i] == '(')) return at;
+    }
+    return null;
+}
+
+/// Whether only whitespace precedes `pos` on its line.
+fn atLineStart(content: []const u8, pos: usize) bool {
+    var i = pos;
+    while (i > 0) {
+        i -= 1;
+        if (content[i] == '\n') return true;
+        if (content[i] != ' ' and content[i] != '\t' and content[i] != '\r') return false;
+    }
+    return true;
+}
+
+fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize, reporter: Reporter, macros: *Macros) !?Entry {
     pos.* += 1;
 
     const type_start = pos.*;
@@ -78,6 +246,17 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
         break :blk buf[0..len];
     };
 
+    if (std.mem.eql(u8, lower_type, "string")) {
+        if (try parseMacro(allocator, content, pos.*, macros)) |macro| {
+            const gop = try macros.getOrPut(allocator, macro.name);
+            if (gop.found_existing) {
+                allocator.free(macro.name);
+                allocator.free(gop.value_ptr.*);
+            }
+            gop.value_ptr.* = macro.value;
+        }
+    }
+
     if (std.mem.eql(u8, lower_type, "string") or
         std.mem.eql(u8, lower_type, "preamble") or
         std.mem.eql(u8, lower_type, "comment"))
@@ -136,6 +315,21 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
     var authors_list: std.ArrayList([]const u8) = .empty;
     defer authors_list.deinit(allocator);
 
+    // Fields kept as written so that rewriting the entry does not drop them
+    var extra_list: std.ArrayList(entry_mod.ExtraField) = .empty;
+    defer {
+        for (extra_list.items) |f| {
+            allocator.free(f.name);
+            allocator.free(f.value);
+        }
+        extra_list.deinit(allocator);
+    }
+
+    // A note mentioning funding stands in for a missing funding field
+    var funding_note: ?[]u8 = null;
+    defer if (funding_note) |n| allocator.free(n);
+    var has_archive_prefix = false;
+
     while (pos.* < content.len and content[pos.*] != close_char) {
         while (pos.* < content.len and std.ascii.isWhitespace(content[pos.*])) {
             pos.* += 1;
@@ -154,6 +348,7 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
         }
 
         if (pos.* >= content.len or content[pos.*] != '=') {
+            if (pos.* < content.len and content[pos.*] != ',') try reporter.report(field_start, .missing_equals);
             while (pos.* < content.len and content[pos.*] != ',' and content[pos.*] != close_char) {
                 pos.* += 1;
             }
@@ -166,9 +361,15 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
             pos.* += 1;
         }
 
-        const value = try parseFieldValue(allocator, content, pos);
+        const value = try parseFieldValue(allocator, content, pos, macros);
         defer allocator.free(value);
 
+        while (pos.* < content.len and std.ascii.isWhitespace(content[pos.*])) {
+            pos.* += 1;
+        }
+        if (pos.* < content.len and content[pos.*] != ',' and content[pos.*] != close_char) {
+            try reporter.report(pos.*, .missing_comma);
+        }
         while (pos.* < content.len and (std.ascii.isWhitespace(content[pos.*]) or content[pos.*] == ',')) {
             pos.* += 1;
         }
@@ -187,37 +388,106 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
                 }
             }
         } else if (std.ascii.eqlIgnoreCase(field_name, "year")) {
-            result.year = std.fmt.parseInt(i32, value, 10) catch null;
+            result.year = std.fmt.parseInt(i32, value, 10) catch blk: {
+                // "2020a", "to appear"
+                try appendExtra(allocator, &extra_list, field_name, value);
+                break :blk null;
+            };
         } else if (std.ascii.eqlIgnoreCase(field_name, "journal") or std.ascii.eqlIgnoreCase(field_name, "booktitle")) {
             if (result.venue == null) {
                 result.venue = try allocator.dupe(u8, value);
+                result.venue_field = if (std.ascii.eqlIgnoreCase(field_name, "journal")) "journal" else "booktitle";
             }
+            // "arXiv preprint arXiv:2101.00001"
+            try takeArxiv(allocator, &result, findArxiv(value), "journal");
+        } else if (std.ascii.eqlIgnoreCase(field_name, "publisher")) {
+            const new_publisher = try allocator.dupe(u8, value);
+            if (result.publisher) |old| allocator.free(old);
+            result.publisher = new_publisher;
         } else if (std.ascii.eqlIgnoreCase(field_name, "doi")) {
-            const new_doi = try allocator.dupe(u8, value);
+            const new_doi = try std.ascii.allocLowerString(allocator, bareDoi(value));
             if (result.doi) |old| allocator.free(old);
             result.doi = new_doi;
+            // "https://doi.org/10.1145/..." or "10.1002/ANIE..." is not written canonically
+            result.doi_field = if (std.mem.eql(u8, new_doi, value)) null else "doi";
         } else if (std.ascii.eqlIgnoreCase(field_name, "eprint")) {
-            if (isArxivId(value)) {
-                const new_arxiv = try allocator.dupe(u8, value);
+            if (normalizeArxivId(value)) |id| {
+                const new_arxiv = try allocator.dupe(u8, id);
                 if (result.arxiv_id) |old| allocator.free(old);
                 result.arxiv_id = new_arxiv;
+                // "arXiv:2101.00001" still needs tidying
+                result.arxiv_field = if (std.mem.eql(u8, id, value)) null else "eprint";
+            } else {
+                // e.g. a PubMed ID with eprinttype = {pubmed}
+                try appendExtra(allocator, &extra_list, field_name, value);
+            }
+        } else if (std.ascii.eqlIgnoreCase(field_name, "archiveprefix") or std.ascii.eqlIgnoreCase(field_name, "eprinttype")) {
+            if (std.ascii.eqlIgnoreCase(value, "arxiv")) {
+                has_archive_prefix = true;
+            } else {
+                try appendExtra(allocator, &extra_list, field_name, value);
+            }
+        } else if (std.ascii.eqlIgnoreCase(field_name, "primaryclass")) {
+            const new_class = try allocator.dupe(u8, value);
+            if (result.primary_class) |old| allocator.free(old);
+            result.primary_class = new_class;
+        } else if (std.ascii.eqlIgnoreCase(field_name, "isbn")) {
+            var buf: [13]u8 = undefined;
+            if (entry_mod.normalizeIsbn(&buf, value)) |isbn| {
+                const new_isbn = try allocator.dupe(u8, isbn);
+                if (result.isbn) |old| allocator.free(old);
+                result.isbn = new_isbn;
+            } else {
+                try appendExtra(allocator, &extra_list, field_name, value);
+            }
+        } else if (std.ascii.eqlIgnoreCase(field_name, "eid") and isScopusEid(value)) {
+            // biblatex uses eid for article numbers too; only Scopus IDs are taken
+            const new_eid = try allocator.dupe(u8, value);
+            if (result.scopus_eid) |old| allocator.free(old);
+            result.scopus_eid = new_eid;
+        } else if (std.ascii.eqlIgnoreCase(field_name, "funding")) {
+            const new_funding = try allocator.dupe(u8, value);
+            if (result.funding) |old| allocator.free(old);
+            result.funding = new_funding;
+        } else if (std.ascii.eqlIgnoreCase(field_name, "groups")) {
+            const new_groups = try allocator.dupe(u8, value);
+            if (result.groups) |old| allocator.free(old);
+            result.groups = new_groups;
+        } else if (std.ascii.eqlIgnoreCase(field_name, "note")) {
+            if (funding_note == null and (std.ascii.indexOfIgnoreCase(value, "fund") != null or std.ascii.indexOfIgnoreCase(value, "grant") != null)) {
+                funding_note = try allocator.dupe(u8, value);
+            }
+
+            if (result.doi == null) {
+                if (extractDoi(value)) |doi| {
+                    result.doi = try std.ascii.allocLowerString(allocator, doi);
+                    result.doi_field = "note";
+                }
             }
+            try takeArxiv(allocator, &result, findArxiv(value), "note");
+            try appendExtra(allocator, &extra_list, field_name, value);
         } else if (std.ascii.eqlIgnoreCase(field_name, "url")) {
             const new_url = try allocator.dupe(u8, value);
             if (result.url) |old| allocator.free(old);
             result.url = new_url;
 
-            if (result.arxiv_id == null) {
-                if (extractArxivFromUrl(value)) |arxiv| {
-                    result.arxiv_id = try allocator.dupe(u8, arxiv);
-                }
+            if (extractArxivFromUrl(value)) |arxiv| {
+                try takeArxiv(allocator, &result, .{ .id = arxiv }, "url");
+            }
+
+            // Scopus exports link the record, "...record.uri?eid=2-s2.0-85...&doi=..."
+            if (result.scopus_eid == null) {
+                if (extractScopusEid(value)) |eid| result.scopus_eid = try allocator.dupe(u8, eid);
             }
 
             if (result.doi == null) {
-                if (extractDoiFromUrl(value)) |doi| {
-                    result.doi = try allocator.dupe(u8, doi);
+                if (extractDoi(value)) |doi| {
+                    result.doi = try std.ascii.allocLowerString(allocator, doi);
+                    result.doi_field = "url";
                 }
             }
+        } else if (field_name.len > 0) {
+            try appendExtra(allocator, &extra_list, field_name, value);
         }
     }
 
@@ -228,11 +498,141 @@ fn parseEntry(allocator: std.mem.Allocator, content: []const u8, pos: *usize) !?
     if (authors_list.items.len > 0) {
         result.authors = try authors_list.toOwnedSlice(allocator);
     }
+    if (extra_list.items.len > 0) {
+        result.extra = try extra_list.toOwnedSlice(allocator);
+    }
+
+    if (result.funding == null) {
+        result.funding = funding_note;
+        funding_note = null;
+    }
+
+    // biblatex dates and year ranges stay as written, e.g. "2019/2020"
+    if (result.year == null) {
+        for ([_][]const u8{ "year", "date" }) |name| {
+            const years = parseYears(result.extraField(name) orelse continue) orelse continue;
+            result.year = years.start;
+            result.year_end = years.end;
+            result.year_approximate = years.approximate;
+            result.year_field = name;
+            break;
+        }
+    }
 
+    // A bare eprint is only linked to arXiv with an archivePrefix
+    if (result.arxiv_id != null and result.arxiv_field == null and !has_archive_prefix) {
+        result.arxiv_field = "eprint";
+    }
+    if (result.arxiv_id != null and result.primary_class == null) {
+        if (oldStyleClass(result.arxiv_id.?)) |class| result.primary_class = try allocator.dupe(u8, class);
+    }
+
+    return result;
+}
+
+/// Years of a biblatex date or a year that is not a plain number.
+pub const Years = struct {
+    start: i32,
+    /// Last year of a range, if not `start`
+    end: ?i32 = null,
+    approximate: bool = false,
+};
+
+/// Read the years of a biblatex (EDTF) date, "2021-03", "2019/2020",
+/// "2019-05/2020-02", "2019~", or a year range or approximate year written
+/// in `year`, "2019--2020", "2019-2020", "circa 2019". Null for anything
+/// else, such as "2020a" or "to appear".
+pub fn parseYears(value: []const u8) ?Years {
+    var text = std.mem.trim(u8, value, " \t\r\n");
+    var approximate = false;
+    for ([_][]const u8{ "circa ", "ca. ", "ca ", "c. " }) |prefix| {
+        if (std.ascii.startsWithIgnoreCase(text, prefix)) {
+            text = std.mem.trimLeft(u8, text[prefix.len..], " ");
+            approximate = true;
+            break;
+        }
+    }
+
+    var first = text;
+    var last: ?[]const u8 = null;
+    if (std.mem.indexOfScalar(u8, text, '/')) |slash| {
+        first = text[0..slash];
+        // "2019/" and "2019/.." are open-ended
+        const rest = text[slash + 1 ..];
+        if (rest.len > 0 and !std.mem.eql(u8, rest, "..")) last = rest;
+    } else {
+        for ([_][]const u8{ "--", "\u{2013}", "-" }) |dash| {
+            const at = std.mem.indexOf(u8, text, dash) orelse continue;
+            // "2021-03" is a month, "2019-2020" a range
+            if (dash.len == 1 and (at != 4 or text.len != 9)) continue;
+            first = std.mem.trimRight(u8, text[0..at], " ");
+            last = std.mem.trimLeft(u8, text[at + dash.len ..], " ");
+            break;
+        }
+    }
+
+    const start = dateYear(first) orelse return null;
+    var result = Years{ .start = start.year, .approximate = approximate or start.approximate };
+    if (last) |l| {
+        const end = dateYear(l) orelse return null;
+        if (end.year < start.year) return null;
+        if (end.year > start.year) result.end = end.year;
+        result.approximate = result.approximate or end.approximate;
+    }
     return result;
 }
 
-fn parseFieldValue(allocator: std.mem.Allocator, content: []const u8, pos: *usize) ![]u8 {
+/// The year of one EDTF date, "2021-03-05T10:00Z", and whether it carries
+/// an approximate (`~`), uncertain (`?`), or both (`%`) qualifier.
+fn dateYear(text: []const u8) ?struct { year: i32, approximate: bool } {
+    if (text.len < 4) return null;
+    for (text[0..4]) |c| {
+        if (!std.ascii.isDigit(c)) return null;
+    }
+    var approximate = false;
+    var parts: usize = 1;
+    var i: usize = 4;
+    while (i < text.len) {
+        switch (text[i]) {
+            '~', '?', '%' => {
+                approximate = true;
+                i += 1;
+            },
+            '-' => {
+                // Month and day have two digits
+                if (parts == 3 or i + 3 > text.len or !std.ascii.isDigit(text[i + 1]) or !std.ascii.isDigit(text[i + 2])) return null;
+                parts += 1;
+                i += 3;
+            },
+            // The time of day
+            'T' => break,
+            else => return null,
+        }
+    }
+    return .{ .year = std.fmt.parseInt(i32, text[0..4], 10) catch unreachable, .approximate = approximate };
+}
+
+fn appendExtra(allocator: std.mem.Allocator, list: *std.ArrayList(entry_mod.ExtraField), name: []const u8, value: []const u8) !void {
+    const owned_name = try allocator.dupe(u8, name);
+    errdefer allocator.free(owned_name);
+    const owned_value = try allocator.dupe(u8, value);
+    errdefer allocator.free(owned_value);
+    try list.append(allocator, .{ .name = owned_name, .value = owned_value });
+}
+
+/// Take an arXiv ID found in a free-text field, unless the entry already
+/// has one.
+fn takeArxiv(allocator: std.mem.Allocator, result: *Entry, found: ?ArxivRef, field: []const u8) !void {
+    if (result.arxiv_id != null) return;
+    const ref = found orelse return;
+    result.arxiv_id = try allocator.dupe(u8, ref.id);
+    result.arxiv_field = field;
+    if (result.primary_class == null) {
+        if (ref.class) |class| result.primary_class = try allocator.dupe(u8, class);
+    }
+}
+
+fn parseFieldValue(allocator: std.mem.Allocator, content: []const u8, pos: *usize, macros: *const Macros) ![]u8 {
     var result: std.ArrayList(u8) = .empty;
     errdefer result.deinit(allocator);
 
@@ -267,7 +667,10 @@ fn parseFieldValue(allocator: std.mem.Allocator, content: []const u8, pos: *usiz
             while (pos.* < content.len and (std.ascii.isAlphanumeric(content[pos.*]) or content[pos.*] == '_')) {
                 pos.* += 1;
             }
-            try result.appendSlice(allocator, content[start..pos.*]);
+            const word = content[start..pos.*];
+            var name_buf: [64]u8 = undefined;
+            const expansion = if (word.len <= name_buf.len) macros.get(std.ascii.lowerString(&name_buf, word)) else null;
+            try result.appendSlice(allocator, expansion orelse word);
         } else if (c == '#') {
             pos.* += 1;
             while (pos.* < content.len and std.ascii.isWhitespace(content[pos.*])) {
@@ -285,15 +688,234 @@ fn parseFieldValue(allocator: std.mem.Allocator, content: []const u8, pos: *usiz
     return result.toOwnedSlice(allocator);
 }
 
+/// The `@string` definition after the entry type at `start`, with a
+/// lowercase name. Earlier macros in its value are expanded.
+fn parseMacro(allocator: std.mem.Allocator, content: []const u8, start: usize, macros: *const Macros) !?Macro {
+    var pos = start;
+    while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+    if (pos >= content.len or (content[pos] != '{' and content[pos] != '(')) return null;
+    pos += 1;
+    while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+
+    const name_start = pos;
+    while (pos < content.len and (std.ascii.isAlphanumeric(content[pos]) or content[pos] == '_' or content[pos] == '-' or content[pos] == '.' or content[pos] == ':')) pos += 1;
+    const name = content[name_start..pos];
+    while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+    if (name.len == 0 or pos >= content.len or content[pos] != '=') return null;
+    pos += 1;
+    while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+
+    const value = try parseFieldValue(allocator, content, &pos, macros);
+    errdefer allocator.free(value);
+    return .{ .name = try std.ascii.allocLowerString(allocator, name), .value = value };
+}
+
+/// The `@string` definitions of `content`, in order, with lowercase
+/// names. Free with `freeMacros`.
+pub fn parseMacros(allocator: std.mem.Allocator, content: []const u8) ![]Macro {
+    var list: std.ArrayList(Macro) = .empty;
+    errdefer {
+        for (list.items) |m| {
+            allocator.free(m.name);
+            allocator.free(m.value);
+        }
+        list.deinit(allocator);
+    }
+    // Values borrowed from `list`, for macros used in later definitions
+    var expansions: Macros = .empty;
+    defer expansions.deinit(allocator);
+
+    var pos: usize = 0;
+    while (std.mem.indexOfScalarPos(u8, content, pos, '@')) |at| {
+        pos = at + 1;
+        var type_end = pos;
+        while (type_end < content.len and std.ascii.isAlphabetic(content[type_end])) type_end += 1;
+        if (!std.ascii.eqlIgnoreCase(content[pos..type_end], "string")) continue;
+
+        const macro = (try parseMacro(allocator, content, type_end, &expansions)) orelse continue;
+        list.append(allocator, macro) catch |err| {
+            allocator.free(macro.name);
+            allocator.free(macro.value);
+            return err;
+        };
+        try expansions.put(allocator, macro.name, macro.value);
+    }
+    return list.toOwnedSlice(allocator);
+}
+
+pub fn freeMacros(allocator: std.mem.Allocator, macros: []const Macro) void {
+    for (macros) |m| {
+        allocator.free(m.name);
+        allocator.free(m.value);
+    }
+    allocator.free(macros);
+}
+
+/// Byte range in the source text.
+pub const Span = struct {
+    start: usize,
+    end: usize,
+};
+
+/// How a field value is written in the source.
+pub const ValueStyle = enum {
+    braced,
+    quoted,
+    bare,
+    /// Several pieces joined with `#`.
+    concatenated,
+};
+
+/// Location of a field value, including its delimiters.
+pub const FieldSpan = struct {
+    value: Span,
+    style: ValueStyle,
+};
+
+/// Locate the entry with citation key `key`. The span runs from the `@`
+/// to the closing delimiter (exclusive).
+pub fn findEntry(content: []const u8, key: []const u8) ?Span {
+    var pos: usize = 0;
+    while (std.mem.indexOfScalarPos(u8, content, pos, '@')) |at| {
+        pos = at + 1;
+
+        const type_start = pos;
+        while (pos < content.len and (std.ascii.isAlphanumeric(content[pos]) or content[pos] == '_')) {
+            pos += 1;
+        }
+        const entry_type = content[type_start..pos];
+
+        while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+        if (pos >= content.len) return null;
+        const open_char = content[pos];
+        const close_char: u8 = if (open_char == '{') '}' else if (open_char == '(') ')' else continue;
+        const end = findClose(content, pos + 1, close_char) orelse return null;
+
+        if (std.ascii.eqlIgnoreCase(entry_type, "string") or
+            std.ascii.eqlIgnoreCase(entry_type, "preamble") or
+            std.ascii.eqlIgnoreCase(entry_type, "comment"))
+        {
+            pos = end;
+            continue;
+        }
+
+        pos += 1;
+        while (pos < content.len and std.ascii.isWhitespace(content[pos])) pos += 1;
+        const key_start = pos;
+        while (pos < end and content[pos] != ',' and !std.ascii.isWhitespace(content[pos])) {
+            pos += 1;
+        }
+        if (std.mem.eql(u8, content[key_start..pos], key)) {
+            return .{ .start = at, .end = end };
+        }
+        pos = end;
+    }
+    return null;
+}
+
+/// Locate field `name` (case-insensitive) within an entry span returned by `findEntry`.
+pub fn findField(content: []const u8, entry_span: Span, name: []const u8) ?FieldSpan {
+    var pos = std.mem.indexOfScalarPos(u8, content[0..entry_span.end], entry_span.start, ',') orelse return null;
+    pos += 1;
+
+    while (pos < entry_span.end) {
+        while (pos < entry_span.end and (std.ascii.isWhitespace(content[pos]) or content[pos] == ',')) pos += 1;
+
+        const name_start = pos;
+        while (pos < entry_span.end and (std.ascii.isAlphanumeric(content[pos]) or content[pos] == '_' or content[pos] == '-')) {
+            pos += 1;
+        }
+        const field_name = content[name_start..pos];
+        if (field_name.len == 0) return null;
+
+        while (pos < entry_span.end and std.ascii.isWhitespace(content[pos])) pos += 1;
+        if (pos >= entry_span.end or content[pos] != '=') return null;
+        pos += 1;
+        while (pos < entry_span.end and std.ascii.isWhitespace(content[pos])) pos += 1;
+
+        const value_start = pos;
+        var pieces: usize = 0;
+        var style: ValueStyle = .bare;
+        var value_end = pos;
+        while (pos < entry_span.end) {
+            const c = content[pos];
+            if (c == '{') {
+                pos = (findClose(content, pos + 1, '}') orelse return null) + 1;
+                style = .braced;
+            } else if (c == '"') {
+                pos = (findQuote(content, pos + 1) orelse return null) + 1;
+                style = .quoted;
+            } else {
+                while (pos < entry_span.end and (std.ascii.isAlphanumeric(content[pos]) or content[pos] == '_' or content[pos] == '-' or content[pos] == '.')) {
+                    pos += 1;
+                }
+                style = .bare;
+            }
+            pieces += 1;
+            value_end = pos;
+
+            while (pos < entry_span.end and std.ascii.isWhitespace(content[pos])) pos += 1;
+            if (pos < entry_span.end and content[pos] == '#') {
+                pos += 1;
+                while (pos < entry_span.end and std.ascii.isWhitespace(content[pos])) pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        if (std.ascii.eqlIgnoreCase(field_name, name)) {
+            return .{
+                .value = .{ .start = value_start, .end = value_end },
+                .style = if (pieces > 1) .concatenated else style,
+            };
+        }
+    }
+    return null;
+}
+
+/// Index of the delimiter closing a group opened just before `start`,
+/// skipping nested braces.
+fn findClose(content: []const u8, start: usize, close_char: u8) ?usize {
+    var depth: usize = 0;
+    var pos = start;
+    while (pos < content.len) : (pos += 1) {
+        const c = content[pos];
+        if (depth == 0 and c == close_char) return pos;
+        if (c == '{') {
+            depth += 1;
+        } else if (c == '}') {
+            if (depth == 0) return null;
+            depth -= 1;
+        }
+    }
+    return null;
+}
+
+/// Index of the `"` closing a quoted value, ignoring quotes inside braces.
+fn findQuote(content: []const u8, start: usize) ?usize {
+    var depth: usize = 0;
+    var pos = start;
+    while (pos < content.len) : (pos += 1) {
+        switch (content[pos]) {
+            '{' => depth += 1,
+            '}' => depth -|= 1,
+            '"' => if (depth == 0) return pos,
+            else => {},
+        }
+    }
+    return null;
+}
+
 fn isArxivId(s: []const u8) bool {
     const trimmed = std.mem.trim(u8, s, " \t\r\n");
 
     if (std.mem.indexOf(u8, trimmed, "/")) |slash_pos| {
+        // "hep-th/9901001", with a version as in "hep-th/9901001v2"
         const after_slash = trimmed[slash_pos + 1 ..];
-        for (after_slash) |c| {
-            if (!std.ascii.isDigit(c)) return false;
-        }
-        return after_slash.len > 0;
+        const digits = std.mem.indexOfNone(u8, after_slash, "0123456789") orelse after_slash.len;
+        if (digits == 0) return false;
+        const rest = after_slash[digits..];
+        return rest.len == 0 or (rest.len > 1 and rest[0] == 'v' and std.mem.indexOfNone(u8, rest[1..], "0123456789") == null);
     }
 
     if (std.mem.indexOf(u8, trimmed, ".")) |dot_pos| {
@@ -318,6 +940,50 @@ fn isArxivId(s: []const u8) bool {
     return false;
 }
 
+/// An arXiv ID with the primary class given next to it, if any.
+pub const ArxivRef = struct {
+    id: []const u8,
+    class: ?[]const u8 = null,
+};
+
+/// The arXiv ID in an eprint value, without an "arXiv:" prefix.
+pub fn normalizeArxivId(value: []const u8) ?[]const u8 {
+    var id = std.mem.trim(u8, value, " \t\r\n");
+    if (std.ascii.startsWithIgnoreCase(id, "arxiv:")) id = std.mem.trimLeft(u8, id[6..], " ");
+    return if (isArxivId(id)) id else null;
+}
+
+/// The first "arXiv:ID" in free text, with a following "[cs.LG]" class.
+pub fn findArxiv(text: []const u8) ?ArxivRef {
+    var pos: usize = 0;
+    while (std.ascii.indexOfIgnoreCasePos(text, pos, "arxiv:")) |idx| {
+        pos = idx + 6;
+        var start = pos;
+        while (start < text.len and text[start] == ' ') start += 1;
+        var end = start;
+        while (end < text.len and (std.ascii.isAlphanumeric(text[end]) or text[end] == '.' or text[end] == '/' or text[end] == '-')) end += 1;
+        const id = std.mem.trimRight(u8, text[start..end], ".");
+        if (!isArxivId(id)) continue;
+
+        var ref = ArxivRef{ .id = id };
+        const rest = std.mem.trimLeft(u8, text[end..], " ");
+        if (std.mem.startsWith(u8, rest, "[")) {
+            if (std.mem.indexOfScalar(u8, rest, ']')) |close| {
+                const class = rest[1..close];
+                if (class.len > 0 and std.mem.indexOfAny(u8, class, " \t") == null) ref.class = class;
+            }
+        }
+        return ref;
+    }
+    return null;
+}
+
+/// The archive of an old-style ID, e.g. "hep-th" in "hep-th/9901001".
+fn oldStyleClass(id: []const u8) ?[]const u8 {
+    const slash = std.mem.indexOfScalar(u8, id, '/') orelse return null;
+    return if (slash > 0) id[0..slash] else null;
+}
+
 fn extractArxivFromUrl(url: []const u8) ?[]const u8 {
     if (std.mem.indexOf(u8, url, "arxiv.org")) |_| {
         const patterns = [_][]const u8{ "/abs/", "/pdf/" };
@@ -325,7 +991,7 @@ fn extractArxivFromUrl(url: []const u8) ?[]const u8 {
             if (std.mem.indexOf(u8, url, pattern)) |idx| {
                 const start = idx + pattern.len;
                 var end = start;
-                while (end < url.len and (std.ascii.isAlphanumeric(url[end]) or url[end] == '.' or url[end] == '/' or url[end] == 'v')) {
+                while (end < url.len and (std.ascii.isAlphanumeric(url[end]) or url[end] == '.' or url[end] == '/' or url[end] == '-')) {
                     end += 1;
                 }
                 var id = url[start..end];
@@ -341,16 +1007,148 @@ fn extractArxivFromUrl(url: []const u8) ?[]const u8 {
     return null;
 }
 
-fn extractDoiFromUrl(url: []const u8) ?[]const u8 {
-    if (std.mem.indexOf(u8, url, "doi.org/")) |idx| {
-        const doi = url[idx + 8 ..];
-        if (std.mem.startsWith(u8, doi, "10.")) {
-            return doi;
+/// The first DOI in free text: a doi.org link, a `doi:` reference, or a
+/// bare `10.NNNN/suffix`. Trailing punctuation is not part of the DOI.
+pub fn extractDoi(text: []const u8) ?[]const u8 {
+    var pos: usize = 0;
+    while (std.mem.indexOfPos(u8, text, pos, "10.")) |start| {
+        pos = start + 3;
+        // "10." inside a number or word, e.g. "2010.5"
+        if (start > 0 and std.ascii.isAlphanumeric(text[start - 1])) continue;
+
+        var i = pos;
+        while (i < text.len and std.ascii.isDigit(text[i])) i += 1;
+        const registrant = i - pos;
+        if (registrant < 4 or registrant > 9 or i >= text.len or text[i] != '/') continue;
+
+        var end = i + 1;
+        while (end < text.len and !std.ascii.isWhitespace(text[end]) and text[end] != '"' and text[end] != '<') end += 1;
+        while (end > i + 1 and std.mem.indexOfScalar(u8, ".,;:)]", text[end - 1]) != null) {
+            // Old Wiley DOIs contain balanced parentheses, e.g. "(SICI)"
+            const suffix = text[i..end];
+            if (text[end - 1] == ')' and std.mem.count(u8, suffix, "(") >= std.mem.count(u8, suffix, ")")) break;
+            end -= 1;
         }
+        if (end == i + 1) continue;
+        return text[start..end];
     }
     return null;
 }
 
+/// A DOI without a resolver prefix (`https://doi.org/`, `doi:`, ...) or
+/// surrounding whitespace. DOIs are case-insensitive; their canonical form
+/// is this in lower case.
+pub fn bareDoi(doi: []const u8) []const u8 {
+    const prefixes = [_][]const u8{ "https://doi.org/", "http://doi.org/", "https://dx.doi.org/", "http://dx.doi.org/", "doi.org/", "dx.doi.org/", "doi:" };
+    var s = std.mem.trim(u8, doi, " \t\r\n");
+    for (prefixes) |prefix| {
+        if (s.len >= prefix.len and std.ascii.eqlIgnoreCase(s[0..prefix.len], prefix)) {
+            s = std.mem.trimLeft(u8, s[prefix.len..], " ");
+            break;
+        }
+    }
+    return s;
+}
+
+/// Whether a value is a Scopus EID such as "2-s2.0-85012345678".
+pub fn isScopusEid(value: []const u8) bool {
+    const prefix = "2-s2.0-";
+    if (!std.mem.startsWith(u8, value, prefix) or value.len == prefix.len) return false;
+    for (value[prefix.len..]) |c| {
+        if (!std.ascii.isDigit(c)) return false;
+    }
+    return true;
+}
+
+/// The EID in the `eid` parameter of a Scopus record URL.
+fn extractScopusEid(url: []const u8) ?[]const u8 {
+    if (std.mem.indexOf(u8, url, "scopus.com") == null) return null;
+    const marker = "eid=";
+    const start = (std.mem.indexOf(u8, url, marker) orelse return null) + marker.len;
+    const end = std.mem.indexOfScalarPos(u8, url, start, '&') orelse url.len;
+    const eid = url[start..end];
+    return if (isScopusEid(eid)) eid else null;
+}
+
+/// Write an entry as BibTeX, with the venue in the field it was read from
+/// or else the one its entry type names it in: `journal` for articles,
+/// `booktitle` for papers in proceedings, `howpublished` for the rest.
+pub fn writeEntry(w: *std.Io.Writer, e: *const Entry) !void {
+    try w.print("@{s}{{{s},\n", .{ e.entry_type, e.key });
+    if (e.title) |title| try writeField(w, "title", title);
+    if (e.authors.len > 0) {
+        try w.writeAll("  author = {");
+        for (e.authors, 0..) |author, i| {
+            if (i > 0) try w.writeAll(" and ");
+            try writeValue(w, author);
+        }
+        try w.writeAll("},\n");
+    }
+    if (e.venue) |venue| {
+        const field = e.venue_field orelse fix.venueField(e.entry_type) orelse "howpublished";
+        try writeField(w, field, venue);
+    }
+    if (e.publisher) |publisher| try writeField(w, "publisher", publisher);
+    // Otherwise the year is written as given, among the extra fields
+    if (e.year_field == null) {
+        if (e.year) |year| try w.print("  year = {{{d}}},\n", .{year});
+    }
+    if (e.doi) |doi| try writeField(w, "doi", doi);
+    if (e.arxiv_id) |id| {
+        try writeField(w, "eprint", id);
+        try writeField(w, "archiveprefix", "arXiv");
+        if (e.primary_class) |class| try writeField(w, "primaryclass", class);
+    }
+    if (e.scopus_eid) |eid| {
+        // Already in a Scopus record URL
+        const in_url = if (e.url) |url| std.mem.indexOf(u8, url, eid) != null else false;
+        if (!in_url) try writeField(w, "eid", eid);
+    }
+    if (e.isbn) |isbn| try writeField(w, "isbn", isbn);
+    if (e.url) |url| try writeField(w, "url", url);
+    if (e.groups) |groups| try writeField(w, "groups", groups);
+    for (e.extra) |f| try writeField(w, f.name, f.value);
+    try w.writeAll("}\n");
+}
+
+fn writeField(w: *std.Io.Writer, name: []const u8, value: []const u8) !void {
+    try w.print("  {s} = {{", .{name});
+    try writeValue(w, value);
+    try w.writeAll("},\n");
+}
+
+/// Write a value so it reads back as itself: `%` and `&` are escaped, and
+/// braces too unless they already group the value, as in "{GPU} kernels".
+fn writeValue(w: *std.Io.Writer, value: []const u8) !void {
+    const escape_braces = !balancedBraces(value);
+    for (value, 0..) |c, i| {
+        const escaped = i > 0 and value[i - 1] == '\\';
+        const special = switch (c) {
+            '%', '&' => true,
+            '{', '}' => escape_braces,
+            else => false,
+        };
+        if (special and !escaped) try w.writeByte('\\');
+        try w.writeByte(c);
+    }
+}
+
+fn balancedBraces(value: []const u8) bool {
+    var depth: usize = 0;
+    for (value, 0..) |c, i| {
+        if (i > 0 and value[i - 1] == '\\') continue;
+        switch (c) {
+            '{' => depth += 1,
+            '}' => {
+                if (depth == 0) return false;
+                depth -= 1;
+            },
+            else => {},
+        }
+    }
+    return depth == 0;
+}
+
 test "isArxivId" {
     try std.testing.expect(isArxivId("2301.12345"));
     try std.testing.expect(isArxivId("2301.12345v1"));
@@ -386,3 +1184,263 @@ test "parseString simple" {
     try std.testing.expectEqual(@as(i32, 2021), entries[0].year.?);
     try std.testing.expectEqual(@as(usize, 2), entries[0].authors.len);
 }
+
+test "parser recovers from malformed entries" {
+    const allocator = std.testing.allocator;
+    const bib =
+        \\% contact: someone@example.org
+        \\@article{broken,
+        \\    title = {Unclosed {brace},
+        \\    year = {2020}
+        \\}
+        \\
+        \\@article{good,
+        \\    title = {A Good Paper}
+        \\    year = {2021}
+        \\}
+        \\@misc nodelimiter
+        \\@book{last, title = {Last}}}, note = {stray}
+    ;
+
+    var diagnostics: std.ArrayList(Diagnostic) = .empty;
+    defer diagnostics.deinit(allocator);
+    const entries = try parseStringDiagnostics(allocator, bib, &diagnostics);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 2), entries.len);
+    try std.testing.expectEqualStrings("good", entries[0].key);
+    try std.testing.expectEqual(@as(i32, 2021), entries[0].year.?);
+    try std.testing.expectEqualStrings("last", entries[1].key);
+
+    try std.testing.expectEqual(@as(usize, 4), diagnostics.items.len);
+    try std.testing.expectEqual(Diagnostic{ .line = 2, .column = 1, .problem = .unbalanced_braces }, diagnostics.items[0]);
+    try std.testing.expectEqual(Problem.missing_comma, diagnostics.items[1].problem);
+    try std.testing.expectEqual(@as(usize, 9), diagnostics.items[1].line);
+    try std.testing.expectEqual(Problem.missing_delimiter, diagnostics.items[2].problem);
+    try std.testing.expectEqual(Problem.trailing_text, diagnostics.items[3].problem);
+}
+
+test "DOI from url, note, and doi fields" {
+    const allocator = std.testing.allocator;
+    const bib =
+        \\@article{a, title = {A}, url = {https://doi.org/10.1145/3292500.3330701}}
+        \\@article{b, title = {B}, note = {Published as doi:10.1038/nature14539.}}
+        \\@article{c, title = {C}, note = {See 10.1000/x}, doi = {10.1000/y}}
+        \\@article{d, title = {D}, note = {Vol. 10.5, pp. 1--10}}
+        \\@article{e, title = {E}, doi = {https://doi.org/10.1109/CVPR.2016.90}}
+    ;
+
+    const entries = try parseString(allocator, bib);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqualStrings("10.1145/3292500.3330701", entries[0].doi.?);
+    try std.testing.expectEqualStrings("url", entries[0].doi_field.?);
+    try std.testing.expectEqualStrings("10.1038/nature14539", entries[1].doi.?);
+    try std.testing.expectEqualStrings("note", entries[1].doi_field.?);
+    try std.testing.expectEqualStrings("10.1000/y", entries[2].doi.?);
+    try std.testing.expect(entries[2].doi_field == null);
+    try std.testing.expect(entries[3].doi == null);
+    try std.testing.expectEqualStrings("10.1109/cvpr.2016.90", entries[4].doi.?);
+    try std.testing.expectEqualStrings("doi", entries[4].doi_field.?);
+
+    try std.testing.expectEqualStrings("10.1145/3292500", bareDoi(" https://doi.org/10.1145/3292500"));
+    try std.testing.expectEqualStrings("10.1145/3292500", bareDoi("DOI: 10.1145/3292500"));
+    try std.testing.expectEqualStrings("10.1002/(SICI)1097-4571(199806)49:8", extractDoi("doi 10.1002/(SICI)1097-4571(199806)49:8).").?);
+}
+
+test "biblatex dates, year ranges, and approximate years" {
+    const allocator = std.testing.allocator;
+    const bib =
+        \\@article{a, title = {A}, date = {2021-03}}
+        \\@article{b, title = {B}, date = {2019/2020}}
+        \\@article{c, title = {C}, year = {circa 1850}}
+        \\@article{d, title = {D}, date = {2018-05~/2019-02}}
+        \\@article{e, title = {E}, year = {2020a}}
+        \\@article{f, title = {F}, year = {2018}, date = {2017-12-30}}
+    ;
+
+    const entries = try parseString(allocator, bib);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(?i32, 2021), entries[0].year);
+    try std.testing.expect(entries[0].year_end == null);
+    try std.testing.expectEqualStrings("date", entries[0].year_field.?);
+    try std.testing.expectEqual(@as(?i32, 2019), entries[1].year);
+    try std.testing.expectEqual(@as(?i32, 2020), entries[1].year_end);
+    try std.testing.expectEqual(@as(?i32, 1850), entries[2].year);
+    try std.testing.expect(entries[2].year_approximate);
+    try std.testing.expectEqualStrings("year", entries[2].year_field.?);
+    try std.testing.expectEqual(@as(?i32, 2019), entries[3].year_end);
+    try std.testing.expect(entries[3].year_approximate);
+    try std.testing.expect(entries[4].year == null);
+    try std.testing.expectEqual(@as(?i32, 2018), entries[5].year);
+    try std.testing.expect(entries[5].year_field == null);
+
+    // Written back as given
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeEntry(&out.writer, &entries[1]);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "year =") == null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "date = {2019/2020}") != null);
+
+    try std.testing.expectEqual(@as(?i32, 2020), parseYears("2019--2020").?.end);
+    try std.testing.expectEqual(@as(?i32, 2020), parseYears("2019-2020").?.end);
+    try std.testing.expect(parseYears("2020/2019") == null);
+    try std.testing.expect(parseYears("to appear") == null);
+}
+
+test "Scopus EIDs from eid and url fields" {
+    const allocator = std.testing.allocator;
+    const bib =
+        \\@article{a, title = {A}, url = {https://www.scopus.com/inward/record.uri?eid=2-s2.0-85012345678&partnerID=40}}
+        \\@article{b, title = {B}, eid = {2-s2.0-84960980241}}
+        \\@article{c, title = {C}, eid = {e1003}}
+    ;
+
+    const entries = try parseString(allocator, bib);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqualStrings("2-s2.0-85012345678", entries[0].scopus_eid.?);
+    try std.testing.expectEqualStrings("2-s2.0-84960980241", entries[1].scopus_eid.?);
+    try std.testing.expect(entries[2].scopus_eid == null);
+    try std.testing.expectEqualStrings("e1003", entries[2].extraField("eid").?);
+}
+
+test "arXiv IDs from eprint, journal, note and url fields" {
+    const allocator = std.testing.allocator;
+    const bib =
+        \\@article{a, eprint = {2101.00001}, archivePrefix = {arXiv}, primaryClass = {cs.LG}}
+        \\@article{b, journal = {arXiv preprint arXiv:2101.00002}}
+        \\@misc{c, note = {arXiv:2101.00003v2 [stat.ML]}}
+        \\@misc{d, url = {https://arxiv.org/abs/hep-th/9901001}}
+        \\@misc{e, eprint = {arXiv:2101.00005}}
+        \\@misc{f, url = {https://arxiv.org/abs/hep-th/9901001v2}}
+    ;
+
+    const entries = try parseString(allocator, bib);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqualStrings("2101.00001", entries[0].arxiv_id.?);
+    try std.testing.expect(entries[0].arxiv_field == null);
+    try std.testing.expectEqualStrings("cs.LG", entries[0].primary_class.?);
+
+    try std.testing.expectEqualStrings("2101.00002", entries[1].arxiv_id.?);
+    try std.testing.expectEqualStrings("journal", entries[1].arxiv_field.?);
+
+    try std.testing.expectEqualStrings("2101.00003v2", entries[2].arxiv_id.?);
+    try std.testing.expectEqualStrings("note", entries[2].arxiv_field.?);
+    try std.testing.expectEqualStrings("stat.ML", entries[2].primary_class.?);
+
+    try std.testing.expectEqualStrings("hep-th/9901001", entries[3].arxiv_id.?);
+    try std.testing.expectEqualStrings("hep-th", entries[3].primary_class.?);
+
+    try std.testing.expectEqualStrings("2101.00005", entries[4].arxiv_id.?);
+    try std.testing.expectEqualStrings("eprint", entries[4].arxiv_field.?);
+
+    try std.testing.expectEqualStrings("hep-th/9901001v2", entries[5].arxiv_id.?);
+    try std.testing.expectEqualStrings("hep-th", entries[5].primary_class.?);
+}
+
+test "findEntry and findField" {
+    const bib =
+        \\@string{nat = "Nature"}
+        \\@article{smith2021,
+        \\    title = {A {Great} Paper},
+        \\    journal = nat # " Physics",
+        \\    year = 2021
+        \\}
+    ;
+
+    const span = findEntry(bib, "smith2021").?;
+    try std.testing.expectEqual(@as(u8, '@'), bib[span.start]);
+    try std.testing.expectEqual(@as(u8, '}'), bib[span.end]);
+    try std.testing.expect(findEntry(bib, "nat") == null);
+
+    const title = findField(bib, span, "TITLE").?;
+    try std.testing.expectEqual(ValueStyle.braced, title.style);
+    try std.testing.expectEqualStrings("{A {Great} Paper}", bib[title.value.start..title.value.end]);
+
+    const journal = findField(bib, span, "journal").?;
+    try std.testing.expectEqual(ValueStyle.concatenated, journal.style);
+    try std.testing.expectEqualStrings("nat # \" Physics\"", bib[journal.value.start..journal.value.end]);
+
+    const year = findField(bib, span, "year").?;
+    try std.testing.expectEqual(ValueStyle.bare, year.style);
+    try std.testing.expectEqualStrings("2021", bib[year.value.start..year.value.end]);
+
+    try std.testing.expect(findField(bib, span, "doi") == null);
+}
+
+test "writeEntry round trip" {
+    const allocator = std.testing.allocator;
+    const original = Entry{
+        .key = "vaswani2017",
+        .entry_type = "inproceedings",
+        .title = "Attention Is All You Need",
+        .authors = &.{ "Ashish Vaswani", "Noam Shazeer" },
+        .venue = "Advances in Neural Information Processing Systems",
+        .year = 2017,
+        .arxiv_id = "1706.03762",
+        .extra = &.{
+            .{ .name = "pages", .value = "5998--6008" },
+            .{ .name = "editor", .value = "Guyon, I. and von Luxburg, U." },
+        },
+    };
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeEntry(&out.writer, &original);
+
+    const entries = try parseString(allocator, out.written());
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+    try std.testing.expectEqual(@as(usize, 1), entries.len);
+    try std.testing.expectEqualStrings("vaswani2017", entries[0].key);
+    try std.testing.expectEqualStrings(original.venue.?, entries[0].venue.?);
+    try std.testing.expectEqual(@as(usize, 2), entries[0].authors.len);
+    try std.testing.expectEqualStrings("1706.03762", entries[0].arxiv_id.?);
+    // Unrecognized fields survive, in order
+    try std.testing.expectEqual(@as(usize, 2), entries[0].extra.len);
+    try std.testing.expectEqualStrings("pages", entries[0].extra[0].name);
+    try std.testing.expectEqualStrings("5998--6008", entries[0].extraField("Pages").?);
+}
+
+test "writeEntry names venues by entry type and escapes values" {
+    const allocator = std.testing.allocator;
+    const article = Entry{
+        .key = "smith2020",
+        .entry_type = "article",
+        .title = "Profit & Loss: 50% of {GPU} Time",
+        .venue = "Journal of {Unbalanced",
+    };
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeEntry(&out.writer, &article);
+    const written = out.written();
+    try std.testing.expect(std.mem.indexOf(u8, written, "title = {Profit \\& Loss: 50\\% of {GPU} Time}") != null);
+    try std.testing.expect(std.mem.indexOf(u8, written, "journal = {Journal of \\{Unbalanced}") != null);
+    try std.testing.expect(std.mem.indexOf(u8, written, "booktitle") == null);
+
+    const book = Entry{ .key = "doe2019", .entry_type = "book", .venue = "Lecture Notes" };
+    out.clearRetainingCapacity();
+    try writeEntry(&out.writer, &book);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "howpublished = {Lecture Notes}") != null);
+}
@@ -0,0 +1,188 @@
+//! Candidate blocking for offline matching.
+//!
+//! Comparing every pair of entries in a large file is quadratic. The
+//! blocking index groups entries by the words of their titles and by year,
+//! so that only entries sharing a block are compared: a 10k-entry file
+//! needs thousands of comparisons instead of fifty million.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+/// Years per year bucket. Queries look at the neighbouring buckets too,
+/// so entries up to this many years apart always share a block.
+pub const YEAR_BUCKET_SIZE: i32 = 2;
+
+/// Blocks with more entries than this are skipped: a word that common
+/// (after stopwords) does not tell entries apart.
+pub const MAX_BLOCK_SIZE: usize = 500;
+
+/// Title words shorter than this are not indexed.
+const MIN_TOKEN_LEN = 3;
+
+const stopwords = [_][]const u8{
+    "the", "and", "for", "with", "from", "into", "via", "using", "towards", "toward", "through", "over", "under", "its", "our", "are", "not",
+};
+
+/// A pair of entry indices, `a < b`.
+pub const Pair = struct {
+    a: u32,
+    b: u32,
+};
+
+/// Inverted index from (title word, year bucket) to entries. Entries are
+/// borrowed; all other memory is owned by the arena.
+pub const BlockingIndex = struct {
+    entries: []const Entry,
+    /// "word/bucket" (or "word/-" for entries without a year) to entry indices
+    blocks: std.StringHashMapUnmanaged(std.ArrayList(u32)) = .empty,
+    /// Word to entry indices, for queries without a year
+    words: std.StringHashMapUnmanaged(std.ArrayList(u32)) = .empty,
+    arena: std.heap.ArenaAllocator,
+
+    /// Index `entries`, which must outlive the index.
+    pub fn init(allocator: std.mem.Allocator, entries: []const Entry) !BlockingIndex {
+        var index = BlockingIndex{
+            .entries = entries,
+            .arena = std.heap.ArenaAllocator.init(allocator),
+        };
+        errdefer index.deinit();
+        const arena = index.arena.allocator();
+
+        var tokens: std.ArrayList([]const u8) = .empty;
+        for (entries, 0..) |*e, i| {
+            tokens.clearRetainingCapacity();
+            try titleTokens(arena, e, &tokens);
+            for (tokens.items) |token| {
+                const key = try blockKey(arena, token, e.year);
+                try addTo(arena, &index.blocks, key, @intCast(i));
+                try addTo(arena, &index.words, token, @intCast(i));
+            }
+        }
+        return index;
+    }
+
+    pub fn deinit(self: *BlockingIndex) void {
+        self.arena.deinit();
+    }
+
+    /// Indices of indexed entries sharing a block with `e`, ascending.
+    /// The caller frees the slice.
+    pub fn candidates(self: *const BlockingIndex, allocator: std.mem.Allocator, e: *const Entry) ![]u32 {
+        var scratch = std.heap.ArenaAllocator.init(allocator);
+        defer scratch.deinit();
+        const tmp = scratch.allocator();
+
+        var tokens: std.ArrayList([]const u8) = .empty;
+        try titleTokens(tmp, e, &tokens);
+
+        var found: std.ArrayList(u32) = .empty;
+        errdefer found.deinit(allocator);
+
+        for (tokens.items) |token| {
+            const year = e.year orelse {
+                try appendBlock(allocator, &found, self.words.get(token));
+                continue;
+            };
+            const bucket = @divFloor(year, YEAR_BUCKET_SIZE);
+            var b = bucket - 1;
+            while (b <= bucket + 1) : (b += 1) {
+                const key = try std.fmt.allocPrint(tmp, "{s}/{d}", .{ token, b });
+                try appendBlock(allocator, &found, self.blocks.get(key));
+            }
+            try appendBlock(allocator, &found, self.blocks.get(try blockKey(tmp, token, null)));
+        }
+
+        std.sort.pdq(u32, found.items, {}, std.sort.asc(u32));
+        var unique: usize = 0;
+        for (found.items) |i| {
+            if (unique > 0 and found.items[unique - 1] == i) continue;
+            found.items[unique] = i;
+            unique += 1;
+        }
+        found.shrinkRetainingCapacity(unique);
+        return found.toOwnedSlice(allocator);
+    }
+
+    /// All pairs of indexed entries that share a block, each once.
+    /// The caller frees the slice.
+    pub fn pairs(self: *const BlockingIndex, allocator: std.mem.Allocator) ![]Pair {
+        var result: std.ArrayList(Pair) = .empty;
+        errdefer result.deinit(allocator);
+
+        for (self.entries, 0..) |*e, i| {
+            const found = try self.candidates(allocator, e);
+            defer allocator.free(found);
+            for (found) |j| {
+                if (j > i) try result.append(allocator, .{ .a = @intCast(i), .b = j });
+            }
+        }
+        return result.toOwnedSlice(allocator);
+    }
+};
+
+fn blockKey(allocator: std.mem.Allocator, token: []const u8, year: ?i32) ![]const u8 {
+    const y = year orelse return std.fmt.allocPrint(allocator, "{s}/-", .{token});
+    return std.fmt.allocPrint(allocator, "{s}/{d}", .{ token, @divFloor(y, YEAR_BUCKET_SIZE) });
+}
+
+fn addTo(arena: std.mem.Allocator, map: *std.StringHashMapUnmanaged(std.ArrayList(u32)), key: []const u8, i: u32) !void {
+    const gop = try map.getOrPut(arena, key);
+    if (!gop.found_existing) gop.value_ptr.* = .empty;
+    // A title repeating a word adds the entry once
+    const list = gop.value_ptr;
+    if (list.items.len > 0 and list.items[list.items.len - 1] == i) return;
+    try list.append(arena, i);
+}
+
+fn appendBlock(allocator: std.mem.Allocator, found: *std.ArrayList(u32), block: ?std.ArrayList(u32)) !void {
+    const b = block orelse return;
+    if (b.items.len > MAX_BLOCK_SIZE) return;
+    try found.appendSlice(allocator, b.items);
+}
+
+/// Distinct indexable words of the entry's normalized title.
+fn titleTokens(allocator: std.mem.Allocator, e: *const Entry, tokens: *std.ArrayList([]const u8)) !void {
+    const title = e.title orelse return;
+    const normalized = try entry_mod.normalizeString(allocator, title);
+    var iter = std.mem.tokenizeScalar(u8, normalized, ' ');
+    outer: while (iter.next()) |token| {
+        if (token.len < MIN_TOKEN_LEN or isStopword(token)) continue;
+        for (tokens.items) |seen| {
+            if (std.mem.eql(u8, seen, token)) continue :outer;
+        }
+        try tokens.append(allocator, token);
+    }
+}
+
+fn isStopword(token: []const u8) bool {
+    for (stopwords) |word| {
+        if (std.mem.eql(u8, token, word)) return true;
+    }
+    return false;
+}
+
+test "BlockingIndex pairs entries sharing title words and years" {
+    const allocator = std.testing.allocator;
+
+    const entries = [_]Entry{
+        .{ .key = "a", .entry_type = "article", .title = "Attention is all you need", .year = 2017 },
+        .{ .key = "b", .entry_type = "article", .title = "Attention Is All You Need", .year = 2018 },
+        .{ .key = "c", .entry_type = "article", .title = "Attention is all you need", .year = 2030 },
+        .{ .key = "d", .entry_type = "article", .title = "Graph networks for chemistry", .year = 2017 },
+        .{ .key = "e", .entry_type = "misc", .title = "Attention, please" },
+    };
+
+    var index = try BlockingIndex.init(allocator, &entries);
+    defer index.deinit();
+
+    const found = try index.pairs(allocator);
+    defer allocator.free(found);
+
+    // a-b share words and years; e has no year, so it pairs with every "attention"
+    try std.testing.expectEqual(@as(usize, 4), found.len);
+    try std.testing.expectEqual(Pair{ .a = 0, .b = 1 }, found[0]);
+    try std.testing.expectEqual(Pair{ .a = 0, .b = 4 }, found[1]);
+    try std.testing.expectEqual(Pair{ .a = 1, .b = 4 }, found[2]);
+    try std.testing.expectEqual(Pair{ .a = 2, .b = 4 }, found[3]);
+}
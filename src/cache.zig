@@ -8,13 +8,16 @@ pub const CacheError = error{
     OutOfMemory,
 };
 
-const CACHE_TTL_SECS: i64 = 86400 * 7; // 7 days
+pub const CACHE_TTL_SECS: i64 = 86400 * 7; // 7 days
 
 /// Simple file-based cache for API responses.
 pub const Cache = struct {
     cache_dir: []const u8,
     enabled: bool,
     allocator: std.mem.Allocator,
+    /// Lookups answered from the cache, and lookups that were not.
+    hits: u64 = 0,
+    misses: u64 = 0,
 
     pub fn init(allocator: std.mem.Allocator, enabled: bool) !Cache {
         const home = std.process.getEnvVarOwned(allocator, "HOME") catch |err| switch (err) {
@@ -70,7 +73,12 @@ pub const Cache = struct {
     /// Get a cached response if it exists and is not expired.
     pub fn get(self: *Cache, api: []const u8, query: []const u8) ?[]u8 {
         if (!self.enabled) return null;
+        const found = self.readFresh(api, query);
+        if (found != null) self.hits += 1 else self.misses += 1;
+        return found;
+    }
 
+    fn readFresh(self: *Cache, api: []const u8, query: []const u8) ?[]u8 {
         const path = self.cacheKey(api, query) catch return null;
         defer self.allocator.free(path);
 
@@ -79,9 +87,7 @@ pub const Cache = struct {
 
         const stat = file.stat() catch return null;
 
-        const now = std.time.timestamp();
-        const mtime: i64 = @intCast(@divFloor(stat.mtime, std.time.ns_per_s));
-        if (now - mtime > CACHE_TTL_SECS) {
+        if (isExpired(stat)) {
             std.fs.deleteFileAbsolute(path) catch {};
             return null;
         }
@@ -89,6 +95,20 @@ pub const Cache = struct {
         return file.readToEndAlloc(self.allocator, 10 * 1024 * 1024) catch null;
     }
 
+    /// Check whether an unexpired response is cached, without reading it.
+    pub fn contains(self: *Cache, api: []const u8, query: []const u8) bool {
+        if (!self.enabled) return false;
+
+        const path = self.cacheKey(api, query) catch return false;
+        defer self.allocator.free(path);
+
+        const file = std.fs.openFileAbsolute(path, .{}) catch return false;
+        defer file.close();
+
+        const stat = file.stat() catch return false;
+        return !isExpired(stat);
+    }
+
     /// Store a response in the cache.
     pub fn set(self: *Cache, api: []const u8, query: []const u8, value: []const u8) !void {
         if (!self.enabled) return;
@@ -101,4 +121,136 @@ pub const Cache = struct {
 
         try file.writeAll(value);
     }
+
+    /// Every file in the cache, expired ones included, ordered by name.
+    /// Free with `freeRecords`.
+    pub fn list(self: *Cache, allocator: std.mem.Allocator) ![]Record {
+        var records: std.ArrayList(Record) = .empty;
+        defer records.deinit(allocator);
+        errdefer for (records.items) |r| allocator.free(r.name);
+
+        var dir = std.fs.openDirAbsolute(self.cache_dir, .{ .iterate = true }) catch |err| switch (err) {
+            error.FileNotFound => return &.{},
+            else => return CacheError.IoError,
+        };
+        defer dir.close();
+
+        const now = std.time.timestamp();
+        var it = dir.iterate();
+        while (it.next() catch return CacheError.IoError) |item| {
+            if (item.kind != .file) continue;
+            const api = apiOf(item.name) orelse continue;
+            const stat = dir.statFile(item.name) catch continue;
+
+            const name = try allocator.dupe(u8, item.name);
+            errdefer allocator.free(name);
+            try records.append(allocator, .{
+                .name = name,
+                .api = name[0..api.len],
+                .age = now - @as(i64, @intCast(@divFloor(stat.mtime, std.time.ns_per_s))),
+                .size = stat.size,
+            });
+        }
+
+        std.mem.sort(Record, records.items, {}, Record.lessThan);
+        return records.toOwnedSlice(allocator);
+    }
+
+    /// Contents of the cached file `name`, as listed by `list`.
+    pub fn read(self: *Cache, allocator: std.mem.Allocator, name: []const u8) ![]u8 {
+        var dir = try std.fs.openDirAbsolute(self.cache_dir, .{});
+        defer dir.close();
+        return dir.readFileAlloc(allocator, name, 10 * 1024 * 1024);
+    }
+
+    /// Remove every cached file. Returns how many were removed.
+    pub fn clear(self: *Cache) !usize {
+        var dir = std.fs.openDirAbsolute(self.cache_dir, .{ .iterate = true }) catch |err| switch (err) {
+            error.FileNotFound => return 0,
+            else => return CacheError.IoError,
+        };
+        defer dir.close();
+
+        var removed: usize = 0;
+        var it = dir.iterate();
+        while (it.next() catch return CacheError.IoError) |item| {
+            if (item.kind != .file or apiOf(item.name) == null) continue;
+            dir.deleteFile(item.name) catch return CacheError.IoError;
+            removed += 1;
+        }
+        return removed;
+    }
+};
+
+/// A file in the cache directory.
+pub const Record = struct {
+    /// File name, `<api>_<hash>.json`.
+    name: []const u8,
+    /// What produced it: an API lookup such as "crossref_doi", or
+    /// "verdict" for a cached entry verdict. Slices `name`.
+    api: []const u8,
+    /// Seconds since it was written.
+    age: i64,
+    size: u64,
+
+    pub fn isExpired(self: Record) bool {
+        return self.age > CACHE_TTL_SECS;
+    }
+
+    fn lessThan(_: void, a: Record, b: Record) bool {
+        return std.mem.lessThan(u8, a.name, b.name);
+    }
 };
+
+pub fn freeRecords(allocator: std.mem.Allocator, records: []const Record) void {
+    for (records) |r| allocator.free(r.name);
+    allocator.free(records);
+}
+
+/// The API part of a cache file name, or null for files the cache did not write.
+fn apiOf(name: []const u8) ?[]const u8 {
+    if (!std.mem.endsWith(u8, name, ".json")) return null;
+    const stem = name[0 .. name.len - ".json".len];
+    const sep = std.mem.lastIndexOfScalar(u8, stem, '_') orelse return null;
+    const hash = stem[sep + 1 ..];
+    if (sep == 0 or hash.len != 32) return null;
+    for (hash) |c| {
+        if (!std.ascii.isHex(c)) return null;
+    }
+    return stem[0..sep];
+}
+
+fn isExpired(stat: std.fs.File.Stat) bool {
+    const now = std.time.timestamp();
+    const mtime: i64 = @intCast(@divFloor(stat.mtime, std.time.ns_per_s));
+    return now - mtime > CACHE_TTL_SECS;
+}
+
+test "listing and clearing the cache" {
+    const allocator = std.testing.allocator;
+
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir = try tmp.dir.realpathAlloc(allocator, ".");
+    var cache = Cache{ .cache_dir = dir, .enabled = true, .allocator = allocator };
+    defer cache.deinit();
+
+    try cache.set("crossref_doi", "10.1109/5.726791", "{}");
+    try cache.set("verdict", "0123", "{\"key\":\"lecun1998\"}");
+    // Files the cache did not write are left alone
+    try tmp.dir.writeFile(.{ .sub_path = "notes.txt", .data = "" });
+
+    const records = try cache.list(allocator);
+    defer freeRecords(allocator, records);
+    try std.testing.expectEqual(@as(usize, 2), records.len);
+    try std.testing.expectEqualStrings("crossref_doi", records[0].api);
+    try std.testing.expectEqualStrings("verdict", records[1].api);
+    try std.testing.expect(!records[0].isExpired());
+
+    const content = try cache.read(allocator, records[1].name);
+    defer allocator.free(content);
+    try std.testing.expectEqualStrings("{\"key\":\"lecun1998\"}", content);
+
+    try std.testing.expectEqual(@as(usize, 2), try cache.clear());
+    try std.testing.expectEqual(@as(usize, 0), try cache.clear());
+}
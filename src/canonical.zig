@@ -0,0 +1,293 @@
+//! Venue canonicalization.
+//!
+//! Records from OpenAlex and DBLP name the source entity of their venue
+//! (an OpenAlex source ID, a DBLP stream). With canonical venues enabled,
+//! bibval resolves the local venue name to an entity in the same namespace
+//! and compares the two IDs instead of the names, so "NIPS", "NeurIPS", and
+//! "Advances in Neural Information Processing Systems" all agree. Names
+//! that cannot be resolved fall back to the name comparison.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const validators = @import("validators.zig");
+const venues = @import("venues.zig");
+const Entry = entry_mod.Entry;
+const Discrepancy = entry_mod.Discrepancy;
+const FieldComparator = matcher.FieldComparator;
+
+pub const Namespace = enum {
+    openalex,
+    dblp,
+
+    /// Namespace of an ID such as "dblp:conf/nips".
+    pub fn of(id: []const u8) ?Namespace {
+        const colon = std.mem.indexOfScalar(u8, id, ':') orelse return null;
+        return std.meta.stringToEnum(Namespace, id[0..colon]);
+    }
+};
+
+/// A venue name being resolved.
+pub const Query = struct {
+    /// Normalized name
+    norm: []const u8,
+    /// The dictionary entry for the name, whose other names also count
+    known: ?*const venues.Venue = null,
+
+    /// Whether a source name returned by a search names this venue.
+    pub fn isNamed(self: Query, name: []const u8) bool {
+        var buf: [256]u8 = undefined;
+        if (std.mem.eql(u8, venues.normalize(name, &buf), self.norm)) return true;
+        return if (self.known) |known| known.matches(name) else false;
+    }
+};
+
+/// Resolves venue names to source entities, remembering each resolution
+/// for the run and in the response cache.
+pub const Resolver = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    /// "<namespace>:<normalized name>" to entity ID, or null if unresolved
+    resolved: std.StringHashMapUnmanaged(?[]const u8) = .empty,
+    /// Comparator list returned by `comparators`
+    owned: []FieldComparator = &.{},
+    /// Name comparison used when a venue cannot be resolved
+    fallback: FieldComparator = matcher.venue_comparator,
+
+    const OPENALEX_URL = "https://api.openalex.org/sources";
+    const DBLP_URL = "https://dblp.org/search/venue/api";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Resolver {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+        };
+    }
+
+    pub fn deinit(self: *Resolver) void {
+        var it = self.resolved.iterator();
+        while (it.next()) |kv| {
+            self.allocator.free(kv.key_ptr.*);
+            if (kv.value_ptr.*) |id| self.allocator.free(id);
+        }
+        self.resolved.deinit(self.allocator);
+        self.allocator.free(self.owned);
+    }
+
+    /// `base` with its venue check replaced by an entity comparison that
+    /// falls back to it. The resolver must outlive the returned slice.
+    pub fn comparators(self: *Resolver, base: []const FieldComparator) ![]const FieldComparator {
+        const list = try self.allocator.alloc(FieldComparator, base.len);
+        for (base, list) |comparator, *slot| {
+            if (std.mem.eql(u8, comparator.name, "venue")) {
+                self.fallback = comparator;
+                slot.* = .{ .name = "venue", .ctx = self, .compareFn = compareVenue };
+            } else {
+                slot.* = comparator;
+            }
+        }
+        self.allocator.free(self.owned);
+        self.owned = list;
+        return list;
+    }
+
+    /// The entity `name` refers to in `namespace`, or null if no source
+    /// has exactly that name. Request failures are not remembered.
+    pub fn resolve(self: *Resolver, namespace: Namespace, name: []const u8) std.mem.Allocator.Error!?[]const u8 {
+        // Search for the full name of a known venue rather than an acronym
+        const known = venues.lookup(name);
+        const search_name = if (known) |k| k.full else name;
+        var buf: [256]u8 = undefined;
+        const norm = venues.normalize(search_name, &buf);
+        if (norm.len == 0) return null;
+        const query = Query{ .norm = norm, .known = known };
+
+        const key = try std.fmt.allocPrint(self.allocator, "{s}:{s}", .{ @tagName(namespace), norm });
+        if (self.resolved.get(key)) |id| {
+            self.allocator.free(key);
+            return id;
+        }
+        errdefer self.allocator.free(key);
+
+        const cache_api = switch (namespace) {
+            .openalex => "venue_openalex",
+            .dblp => "venue_dblp",
+        };
+        var id: ?[]const u8 = null;
+        if (self.response_cache.get(cache_api, norm)) |cached| {
+            // An empty entry records that nothing matched
+            if (cached.len == 0) self.allocator.free(cached) else id = cached;
+        } else {
+            const found = (switch (namespace) {
+                .openalex => self.searchOpenAlex(search_name, query),
+                .dblp => self.searchDblp(search_name, query),
+            }) catch |err| switch (err) {
+                error.OutOfMemory => return error.OutOfMemory,
+                else => {
+                    self.allocator.free(key);
+                    return null;
+                },
+            };
+            self.response_cache.set(cache_api, norm, found orelse "") catch {};
+            id = found;
+        }
+        errdefer if (id) |i| self.allocator.free(i);
+
+        try self.resolved.put(self.allocator, key, id);
+        return id;
+    }
+
+    fn searchOpenAlex(self: *Resolver, name: []const u8, query: Query) !?[]const u8 {
+        const encoded = try http.urlEncode(self.allocator, name);
+        defer self.allocator.free(encoded);
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?search={s}&per-page=5", .{ OPENALEX_URL, encoded });
+        defer self.allocator.free(url);
+
+        const body = try self.client.get(url);
+        defer self.allocator.free(body);
+        return parseOpenAlex(self.allocator, body, query);
+    }
+
+    fn searchDblp(self: *Resolver, name: []const u8, query: Query) !?[]const u8 {
+        const encoded = try http.urlEncode(self.allocator, name);
+        defer self.allocator.free(encoded);
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?q={s}&format=json&h=5", .{ DBLP_URL, encoded });
+        defer self.allocator.free(url);
+
+        const body = try self.client.get(url);
+        defer self.allocator.free(body);
+        return parseDblp(self.allocator, body, query);
+    }
+};
+
+/// The first OpenAlex source whose display name, abbreviated title, or an
+/// alternate title names the queried venue.
+pub fn parseOpenAlex(allocator: std.mem.Allocator, body: []const u8, query: Query) !?[]const u8 {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, body, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+    const results = parsed.value.object.get("results") orelse return null;
+    if (results != .array) return null;
+
+    for (results.array.items) |source| {
+        if (source != .object) continue;
+        const id = source.object.get("id") orelse continue;
+        if (id != .string) continue;
+
+        var named = isNamed(query, source.object.get("display_name")) or
+            isNamed(query, source.object.get("abbreviated_title"));
+        if (source.object.get("alternate_titles")) |titles| {
+            if (titles == .array) {
+                for (titles.array.items) |title| named = named or isNamed(query, title);
+            }
+        }
+        if (named) return try validators.OpenAlex.openAlexSourceId(allocator, id.string);
+    }
+    return null;
+}
+
+/// The stream of the first DBLP venue whose name or acronym names the
+/// queried venue. DBLP appends the acronym to names, as in "Neural Information
+/// Processing Systems (NeurIPS)".
+pub fn parseDblp(allocator: std.mem.Allocator, body: []const u8, query: Query) !?[]const u8 {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, body, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+    const result = parsed.value.object.get("result") orelse return null;
+    if (result != .object) return null;
+    const hits = result.object.get("hits") orelse return null;
+    if (hits != .object) return null;
+    const hit = hits.object.get("hit") orelse return null;
+    if (hit != .array) return null;
+
+    for (hit.array.items) |item| {
+        if (item != .object) continue;
+        const info = item.object.get("info") orelse continue;
+        if (info != .object) continue;
+        const url = info.object.get("url") orelse continue;
+        if (url != .string) continue;
+
+        var named = isNamed(query, info.object.get("acronym"));
+        if (info.object.get("venue")) |venue| {
+            if (venue == .string) {
+                const paren = std.mem.lastIndexOf(u8, venue.string, " (");
+                const bare = if (paren) |p| venue.string[0..p] else venue.string;
+                named = named or query.isNamed(bare) or query.isNamed(venue.string);
+            }
+        }
+        if (!named) continue;
+
+        // "https://dblp.org/db/conf/nips/" is the stream "conf/nips"
+        const marker = "/db/";
+        const start = (std.mem.indexOf(u8, url.string, marker) orelse continue) + marker.len;
+        const stream = std.mem.trimRight(u8, url.string[start..], "/");
+        if (stream.len == 0) continue;
+        return try std.fmt.allocPrint(allocator, "dblp:{s}", .{stream});
+    }
+    return null;
+}
+
+fn isNamed(query: Query, value: ?std.json.Value) bool {
+    const v = value orelse return false;
+    return v == .string and query.isNamed(v.string);
+}
+
+/// Compare the entities of the two venues when both can be named, and the
+/// names otherwise.
+fn compareVenue(ctx: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const self: *Resolver = @ptrCast(@alignCast(ctx.?));
+    if (local.venue == null or remote.venue == null) return null;
+
+    const remote_id = remote.venue_id orelse return self.fallback.compare(allocator, local, remote);
+    const namespace = Namespace.of(remote_id) orelse return self.fallback.compare(allocator, local, remote);
+    const local_id = (try self.resolve(namespace, local.venue.?)) orelse return self.fallback.compare(allocator, local, remote);
+    if (std.mem.eql(u8, local_id, remote_id)) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Venue is a different source: \"{s}\" ({s}) vs \"{s}\" ({s})", .{ local.venue.?, local_id, remote.venue.?, remote_id });
+    errdefer allocator.free(msg);
+    const local_value = try allocator.dupe(u8, local.venue.?);
+    errdefer allocator.free(local_value);
+    return .{
+        .rule = .venue_mismatch,
+        .field = .venue,
+        .severity = .warning,
+        .local_value = local_value,
+        .remote_value = try allocator.dupe(u8, remote.venue.?),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+test "venue entity resolution responses" {
+    const allocator = std.testing.allocator;
+
+    var buf: [256]u8 = undefined;
+    const neurips = Query{
+        .norm = venues.normalize("Advances in Neural Information Processing Systems", &buf),
+        .known = venues.lookup("NeurIPS"),
+    };
+
+    const openalex_id = (try parseOpenAlex(allocator,
+        \\{"results":[{"id":"https://openalex.org/S1","display_name":"Neural Networks"},
+        \\{"id":"https://openalex.org/S4306420609","display_name":"Neural Information Processing Systems","alternate_titles":["NeurIPS"]}]}
+    , neurips)).?;
+    defer allocator.free(openalex_id);
+    try std.testing.expectEqualStrings("openalex:S4306420609", openalex_id);
+
+    const dblp_id = (try parseDblp(allocator,
+        \\{"result":{"hits":{"hit":[{"info":{"venue":"Neural Information Processing Systems (NeurIPS)","acronym":"NeurIPS","url":"https://dblp.org/db/conf/nips/"}}]}}}
+    , neurips)).?;
+    defer allocator.free(dblp_id);
+    try std.testing.expectEqualStrings("dblp:conf/nips", dblp_id);
+
+    try std.testing.expect(try parseDblp(allocator,
+        \\{"result":{"hits":{"hit":[{"info":{"venue":"Neural Networks","url":"https://dblp.org/db/journals/nn/"}}]}}}
+    , neurips) == null);
+
+    try std.testing.expectEqual(Namespace.dblp, Namespace.of("dblp:conf/nips").?);
+    try std.testing.expect(Namespace.of("S4306420609") == null);
+}
@@ -0,0 +1,227 @@
+//! C API.
+//!
+//! A small, stable surface for embedding bibval in editors and tools that
+//! are not written in Zig; `include/bibval.h` is the contract. Strings are
+//! UTF-8 and NUL-terminated. Strings passed in stay owned by the caller;
+//! strings returned are owned by the caller and released with
+//! `bibval_string_free`. Calls share no state, so they may be made from
+//! several threads at once.
+
+const std = @import("std");
+const bibval = @import("bibval");
+
+const allocator = std.heap.smp_allocator;
+
+/// Bumped on any incompatible change to the functions or to the request
+/// format. Must match BIBVAL_ABI_VERSION in bibval.h, which a test
+/// checks along with the declarations there.
+pub const ABI_VERSION: c_int = 1;
+
+/// A validation request, e.g. `{"input": "@article{...}", "options": {"dblp": false}}`.
+const Request = struct {
+    /// BibTeX, CSL-JSON or RIS; the format is detected.
+    input: []const u8,
+    options: Options = .{},
+};
+
+const Options = struct {
+    crossref: bool = true,
+    dblp: bool = true,
+    semantic_scholar: bool = true,
+    openalex: bool = true,
+    open_library: bool = true,
+    google_books: bool = true,
+    arxiv: bool = true,
+    pmlr: bool = true,
+    neurips: bool = true,
+    /// Publisher and index lookups are made only with a key.
+    ieee_api_key: ?[]const u8 = null,
+    acm: bool = false,
+    springer_api_key: ?[]const u8 = null,
+    scopus_api_key: ?[]const u8 = null,
+    github: bool = true,
+    github_token: ?[]const u8 = null,
+    zenodo: bool = true,
+    figshare: bool = true,
+    /// Off, since it fetches whatever URLs the references give.
+    web: bool = false,
+    cache: bool = true,
+    /// Rule IDs or names, as for `--disable`.
+    disable: []const []const u8 = &.{},
+    min_confidence: f64 = 0,
+    request_interval_ms: u64 = 0,
+    check_funders: bool = false,
+    open_access: bool = false,
+    suggest_archives: bool = false,
+};
+
+const RequestError = error{
+    InvalidRequest,
+    UnknownRule,
+};
+
+export fn bibval_abi_version() c_int {
+    return ABI_VERSION;
+}
+
+/// Validate the references in a JSON request and return the JSON report
+/// of `bibval check --json`, or `{"error": "..."}`. Returns NULL only if
+/// memory runs out.
+export fn bibval_validate_json(request: ?[*:0]const u8) ?[*:0]u8 {
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+
+    validate(if (request) |r| std.mem.span(r) else "", &out.writer) catch |err| {
+        out.clearRetainingCapacity();
+        const message = switch (err) {
+            error.InvalidRequest => "request must be a JSON object with an \"input\" string and optional \"options\"",
+            error.UnknownRule => "unknown rule in \"disable\"",
+            else => @errorName(err),
+        };
+        out.writer.writeAll("{\"error\":") catch return null;
+        bibval.report.writeJsonString(&out.writer, message) catch return null;
+        out.writer.writeAll("}") catch return null;
+    };
+    return allocator.dupeZ(u8, out.written()) catch null;
+}
+
+/// Release a string returned by bibval. NULL is ignored.
+export fn bibval_string_free(s: ?[*:0]u8) void {
+    const str = s orelse return;
+    allocator.free(std.mem.span(str));
+}
+
+fn validate(text: []const u8, w: *std.Io.Writer) !void {
+    const parsed = std.json.parseFromSlice(Request, allocator, text, .{ .ignore_unknown_fields = true }) catch return RequestError.InvalidRequest;
+    defer parsed.deinit();
+    const options = parsed.value.options;
+
+    var disabled: bibval.rules.RuleSet = .initEmpty();
+    for (options.disable) |name| disabled.insert(bibval.rules.Rule.parse(name) orelse return RequestError.UnknownRule);
+
+    var decoded = try bibval.input.decode(allocator, parsed.value.input, null);
+    defer decoded.deinit();
+    const entries = try bibval.input.parse(allocator, decoded.text, bibval.input.detect(decoded.text), null);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    var response_cache = try bibval.cache.Cache.init(allocator, options.cache);
+    defer response_cache.deinit();
+    const tracer = bibval.trace.Tracer{};
+
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
+    client.min_interval_ms = options.request_interval_ms;
+
+    const validators = bibval.validators;
+    var sources = bibval.pipeline.Sources{
+        .crossref = if (options.crossref) validators.CrossRef.init(allocator, &client, &response_cache) else null,
+        .dblp = if (options.dblp) validators.Dblp.init(allocator, &client) else null,
+        .semantic = if (options.semantic_scholar) validators.SemanticScholar.init(allocator, &client) else null,
+        .openalex = if (options.openalex) validators.OpenAlex.init(allocator, &client) else null,
+        .open_library = if (options.open_library) validators.OpenLibrary.init(allocator, &client) else null,
+        .google_books = if (options.google_books) validators.GoogleBooks.init(allocator, &client) else null,
+        .arxiv = if (options.arxiv) validators.Arxiv.init(allocator, &client) else null,
+        .pmlr = if (options.pmlr) bibval.proceedings.Pmlr.init(allocator, &client, &response_cache) else null,
+        .neurips = if (options.neurips) bibval.proceedings.NeurIps.init(allocator, &client, &response_cache) else null,
+        .ieee = if (options.ieee_api_key) |key| validators.Ieee.init(allocator, &client, &response_cache, key) else null,
+        .acm = if (options.acm) validators.Acm.init(allocator, &client) else null,
+        .springer = if (options.springer_api_key) |key| validators.Springer.init(allocator, &client, &response_cache, key) else null,
+        .scopus = if (options.scopus_api_key) |key| validators.Scopus.init(allocator, &client, &response_cache, key) else null,
+        .github = if (options.github) bibval.github.GitHub.init(allocator, &client, &response_cache, options.github_token) else null,
+        .zenodo = if (options.zenodo) bibval.deposits.Zenodo.init(allocator, &client, &response_cache) else null,
+        .figshare = if (options.figshare) bibval.deposits.Figshare.init(allocator, &client, &response_cache) else null,
+        .wayback = if (options.suggest_archives) bibval.wayback.Wayback.init(allocator, &client, &response_cache, false) else null,
+        .web = if (options.web) bibval.webpage.Web.init(allocator, &client, &response_cache) else null,
+    };
+
+    const config = bibval.config.Config{};
+    var report = bibval.report.Report.init(allocator);
+    defer report.deinit();
+    for (entries) |*local_entry| {
+        try report.add(try bibval.pipeline.validateEntry(allocator, local_entry, &sources, &tracer, disabled, options.min_confidence, options.check_funders, options.open_access, &config.screening, &config.matching));
+    }
+
+    var policy = try bibval.policy.evaluate(allocator, &report, config.policy, &config.screening, bibval.ignores.Date.today(), disabled);
+    defer policy.deinit();
+    var duplicates = try bibval.duplicates.check(allocator, entries, &config.matching, disabled);
+    defer duplicates.deinit();
+    var consistency = try bibval.consistency.check(allocator, entries, config.venue_style, config.engine, disabled);
+    defer consistency.deinit();
+
+    try bibval.pipeline.writeJsonReport(allocator, w, &report, &policy, &duplicates, &consistency, null);
+}
+
+test "bibval_validate_json reports bad requests" {
+    const cases = [_]struct { request: [:0]const u8, message: []const u8 }{
+        .{ .request = "not json", .message = "request must be" },
+        .{ .request = "{\"options\":{}}", .message = "request must be" },
+        .{ .request = "{\"input\":\"\",\"options\":{\"disable\":[\"BV999\"]}}", .message = "unknown rule" },
+    };
+    for (cases) |case| {
+        const result = bibval_validate_json(case.request.ptr).?;
+        defer bibval_string_free(result);
+        try std.testing.expect(std.mem.indexOf(u8, std.mem.span(result), case.message) != null);
+    }
+
+    // No entries means no lookups
+    const empty = bibval_validate_json("{\"input\":\"\",\"options\":{\"cache\":false}}").?;
+    defer bibval_string_free(empty);
+    try std.testing.expect(std.mem.startsWith(u8, std.mem.span(empty), "{\"entries\":[]"));
+}
+
+/// The C declaration of `func` named `name`, its parameters named `params`,
+/// as written in bibval.h.
+fn prototype(comptime name: []const u8, comptime func: anytype, comptime params: []const []const u8) []const u8 {
+    const info = @typeInfo(@TypeOf(func)).@"fn";
+    var text: []const u8 = declarator(info.return_type.?, name) ++ "(";
+    if (params.len == 0) text = text ++ "void";
+    for (info.params, params, 0..) |param, param_name, i| {
+        if (i > 0) text = text ++ ", ";
+        text = text ++ declarator(param.type.?, param_name);
+    }
+    return text ++ ");";
+}
+
+/// `T name` in C, without a space after a pointer's `*`.
+fn declarator(comptime T: type, comptime name: []const u8) []const u8 {
+    const c_type = switch (T) {
+        void => "void",
+        c_int => "int",
+        ?[*:0]u8 => "char *",
+        ?[*:0]const u8 => "const char *",
+        else => @compileError("no C type for " ++ @typeName(T)),
+    };
+    return if (std.mem.endsWith(u8, c_type, "*")) c_type ++ name else c_type ++ " " ++ name;
+}
+
+test "bibval.h matches the exported functions" {
+    const header = @embedFile("bibval.h");
+    try std.testing.expect(std.mem.indexOf(u8, header, std.fmt.comptimePrint("#define BIBVAL_ABI_VERSION {d}\n", .{ABI_VERSION})) != null);
+
+    const declarations = comptime [_][]const u8{
+        prototype("bibval_abi_version", bibval_abi_version, &.{}),
+        prototype("bibval_validate_json", bibval_validate_json, &.{"request"}),
+        prototype("bibval_string_free", bibval_string_free, &.{"s"}),
+    };
+    for (declarations) |declaration| {
+        if (std.mem.indexOf(u8, header, declaration) == null) {
+            std.debug.print("bibval.h lacks {s}\n", .{declaration});
+            return error.TestUnexpectedResult;
+        }
+    }
+    // Every function the header declares is one of these
+    var declared: usize = 0;
+    var lines = std.mem.splitScalar(u8, header, '\n');
+    while (lines.next()) |line| {
+        if (std.mem.indexOf(u8, line, "bibval_") != null and std.mem.endsWith(u8, line, ");")) declared += 1;
+    }
+    try std.testing.expectEqual(declarations.len, declared);
+
+    // And it documents every option of a request
+    inline for (std.meta.fields(Options)) |field| {
+        try std.testing.expect(std.mem.indexOf(u8, header, "\"" ++ field.name ++ "\":") != null);
+    }
+}
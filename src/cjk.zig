@@ -0,0 +1,205 @@
+//! Chinese, Japanese, and Korean surnames.
+//!
+//! Local entries often keep an author's name in native script while the
+//! APIs return it romanized, or the other way round. Given names cannot be
+//! romanized without a dictionary, but surnames are few: the common ones,
+//! with their usual romanizations (pinyin, Wade-Giles, Cantonese, Revised
+//! and McCune-Reischauer Korean, Hepburn), are enough to tell whether a
+//! native and a romanized name can be the same person.
+
+const std = @import("std");
+
+pub const Surname = struct {
+    native: []const u8,
+    /// Lowercase romanizations
+    roman: []const []const u8,
+};
+
+pub const surnames = [_]Surname{
+    // Chinese, simplified and traditional forms
+    .{ .native = "王", .roman = &.{ "wang", "wong" } },
+    .{ .native = "李", .roman = &.{ "li", "lee", "lei" } },
+    .{ .native = "张", .roman = &.{ "zhang", "chang", "cheung" } },
+    .{ .native = "張", .roman = &.{ "zhang", "chang", "cheung" } },
+    .{ .native = "刘", .roman = &.{ "liu", "lau", "lew" } },
+    .{ .native = "劉", .roman = &.{ "liu", "lau", "lew" } },
+    .{ .native = "陈", .roman = &.{ "chen", "chan", "tan" } },
+    .{ .native = "陳", .roman = &.{ "chen", "chan", "tan" } },
+    .{ .native = "杨", .roman = &.{ "yang", "yeung", "young" } },
+    .{ .native = "楊", .roman = &.{ "yang", "yeung", "young" } },
+    .{ .native = "黄", .roman = &.{ "huang", "wong", "hwang" } },
+    .{ .native = "黃", .roman = &.{ "huang", "wong", "hwang" } },
+    .{ .native = "赵", .roman = &.{ "zhao", "chao", "chiu" } },
+    .{ .native = "趙", .roman = &.{ "zhao", "chao", "chiu" } },
+    .{ .native = "吴", .roman = &.{ "wu", "ng", "woo" } },
+    .{ .native = "吳", .roman = &.{ "wu", "ng", "woo" } },
+    .{ .native = "周", .roman = &.{ "zhou", "chou", "chow" } },
+    .{ .native = "徐", .roman = &.{ "xu", "hsu", "tsui" } },
+    .{ .native = "孙", .roman = &.{ "sun", "suen" } },
+    .{ .native = "孫", .roman = &.{ "sun", "suen" } },
+    .{ .native = "马", .roman = &.{ "ma", "mah" } },
+    .{ .native = "馬", .roman = &.{ "ma", "mah" } },
+    .{ .native = "朱", .roman = &.{ "zhu", "chu" } },
+    .{ .native = "胡", .roman = &.{ "hu", "wu" } },
+    .{ .native = "郭", .roman = &.{ "guo", "kuo", "kwok" } },
+    .{ .native = "何", .roman = &.{ "he", "ho" } },
+    .{ .native = "林", .roman = &.{ "lin", "lam", "lim" } },
+    .{ .native = "高", .roman = &.{ "gao", "kao", "ko" } },
+    .{ .native = "罗", .roman = &.{ "luo", "lo", "law" } },
+    .{ .native = "羅", .roman = &.{ "luo", "lo", "law" } },
+    .{ .native = "郑", .roman = &.{ "zheng", "cheng" } },
+    .{ .native = "鄭", .roman = &.{ "zheng", "cheng" } },
+    .{ .native = "梁", .roman = &.{ "liang", "leung" } },
+    .{ .native = "谢", .roman = &.{ "xie", "hsieh", "tse" } },
+    .{ .native = "謝", .roman = &.{ "xie", "hsieh", "tse" } },
+    .{ .native = "宋", .roman = &.{ "song", "sung" } },
+    .{ .native = "唐", .roman = &.{ "tang", "tong" } },
+    .{ .native = "许", .roman = &.{ "xu", "hsu", "hui" } },
+    .{ .native = "許", .roman = &.{ "xu", "hsu", "hui" } },
+    .{ .native = "韩", .roman = &.{ "han", "hon" } },
+    .{ .native = "韓", .roman = &.{ "han", "hon" } },
+    .{ .native = "冯", .roman = &.{ "feng", "fung" } },
+    .{ .native = "馮", .roman = &.{ "feng", "fung" } },
+    .{ .native = "邓", .roman = &.{ "deng", "teng", "tang" } },
+    .{ .native = "鄧", .roman = &.{ "deng", "teng", "tang" } },
+    .{ .native = "曹", .roman = &.{ "cao", "tsao", "tso" } },
+    .{ .native = "彭", .roman = &.{ "peng", "pang" } },
+    .{ .native = "曾", .roman = &.{ "zeng", "tseng", "tsang" } },
+    .{ .native = "肖", .roman = &.{ "xiao", "hsiao", "siu" } },
+    .{ .native = "蕭", .roman = &.{ "xiao", "hsiao", "siu" } },
+    .{ .native = "田", .roman = &.{ "tian", "tien" } },
+    .{ .native = "董", .roman = &.{ "dong", "tung" } },
+    .{ .native = "袁", .roman = &.{ "yuan", "yuen" } },
+    .{ .native = "潘", .roman = &.{ "pan", "poon" } },
+    .{ .native = "于", .roman = &.{ "yu", "yue" } },
+    .{ .native = "蒋", .roman = &.{ "jiang", "chiang" } },
+    .{ .native = "蔣", .roman = &.{ "jiang", "chiang" } },
+    .{ .native = "蔡", .roman = &.{ "cai", "tsai", "choi" } },
+    .{ .native = "余", .roman = &.{ "yu", "yee" } },
+    .{ .native = "杜", .roman = &.{ "du", "tu", "to" } },
+    .{ .native = "叶", .roman = &.{ "ye", "yeh", "yip" } },
+    .{ .native = "葉", .roman = &.{ "ye", "yeh", "yip" } },
+    .{ .native = "程", .roman = &.{ "cheng", "ching" } },
+    .{ .native = "苏", .roman = &.{ "su", "so" } },
+    .{ .native = "蘇", .roman = &.{ "su", "so" } },
+    .{ .native = "魏", .roman = &.{ "wei", "ngai" } },
+    .{ .native = "吕", .roman = &.{ "lu", "lv", "lyu", "lui" } },
+    .{ .native = "呂", .roman = &.{ "lu", "lv", "lyu", "lui" } },
+    .{ .native = "丁", .roman = &.{ "ding", "ting" } },
+    .{ .native = "任", .roman = &.{ "ren", "jen", "yam" } },
+    .{ .native = "沈", .roman = &.{ "shen", "sham" } },
+    .{ .native = "姚", .roman = &.{ "yao", "yiu" } },
+    .{ .native = "卢", .roman = &.{ "lu", "lo", "lou" } },
+    .{ .native = "盧", .roman = &.{ "lu", "lo", "lou" } },
+    .{ .native = "钟", .roman = &.{ "zhong", "chung" } },
+    .{ .native = "鍾", .roman = &.{ "zhong", "chung" } },
+    .{ .native = "谭", .roman = &.{ "tan", "tam" } },
+    .{ .native = "譚", .roman = &.{ "tan", "tam" } },
+    .{ .native = "陆", .roman = &.{ "lu", "luk" } },
+    .{ .native = "陸", .roman = &.{ "lu", "luk" } },
+    .{ .native = "范", .roman = &.{ "fan", "faan" } },
+    .{ .native = "金", .roman = &.{ "jin", "chin", "kam", "kim" } },
+    .{ .native = "石", .roman = &.{ "shi", "shih", "shek" } },
+    .{ .native = "方", .roman = &.{ "fang", "fong" } },
+    .{ .native = "欧阳", .roman = &.{ "ouyang", "auyeung" } },
+    .{ .native = "歐陽", .roman = &.{ "ouyang", "auyeung" } },
+    .{ .native = "司马", .roman = &.{"sima"} },
+    .{ .native = "诸葛", .roman = &.{"zhuge"} },
+    .{ .native = "上官", .roman = &.{"shangguan"} },
+    // Korean
+    .{ .native = "김", .roman = &.{ "kim", "gim" } },
+    .{ .native = "이", .roman = &.{ "lee", "yi", "rhee", "rhie" } },
+    .{ .native = "박", .roman = &.{ "park", "pak", "bak" } },
+    .{ .native = "최", .roman = &.{ "choi", "choe" } },
+    .{ .native = "정", .roman = &.{ "jung", "jeong", "chung", "chong" } },
+    .{ .native = "강", .roman = &.{ "kang", "gang" } },
+    .{ .native = "조", .roman = &.{ "cho", "jo", "joh" } },
+    .{ .native = "윤", .roman = &.{ "yoon", "yun" } },
+    .{ .native = "장", .roman = &.{ "jang", "chang" } },
+    .{ .native = "임", .roman = &.{ "lim", "im", "rim" } },
+    .{ .native = "한", .roman = &.{ "han", "hahn" } },
+    .{ .native = "오", .roman = &.{"oh"} },
+    .{ .native = "서", .roman = &.{ "seo", "suh", "so" } },
+    .{ .native = "신", .roman = &.{ "shin", "sin" } },
+    .{ .native = "권", .roman = &.{ "kwon", "gwon" } },
+    .{ .native = "황", .roman = &.{ "hwang", "whang" } },
+    .{ .native = "안", .roman = &.{ "ahn", "an" } },
+    .{ .native = "송", .roman = &.{ "song", "soung" } },
+    .{ .native = "류", .roman = &.{ "ryu", "yoo", "yu", "ryoo" } },
+    .{ .native = "홍", .roman = &.{ "hong", "hung" } },
+    // Japanese
+    .{ .native = "佐藤", .roman = &.{ "sato", "satou", "satoh" } },
+    .{ .native = "鈴木", .roman = &.{"suzuki"} },
+    .{ .native = "高橋", .roman = &.{ "takahashi", "takahasi" } },
+    .{ .native = "田中", .roman = &.{"tanaka"} },
+    .{ .native = "伊藤", .roman = &.{ "ito", "itou", "itoh" } },
+    .{ .native = "渡辺", .roman = &.{"watanabe"} },
+    .{ .native = "渡邊", .roman = &.{"watanabe"} },
+    .{ .native = "山本", .roman = &.{"yamamoto"} },
+    .{ .native = "中村", .roman = &.{"nakamura"} },
+    .{ .native = "小林", .roman = &.{"kobayashi"} },
+    .{ .native = "加藤", .roman = &.{ "kato", "katou", "katoh" } },
+    .{ .native = "吉田", .roman = &.{"yoshida"} },
+    .{ .native = "山田", .roman = &.{"yamada"} },
+    .{ .native = "佐々木", .roman = &.{"sasaki"} },
+    .{ .native = "山口", .roman = &.{"yamaguchi"} },
+    .{ .native = "松本", .roman = &.{"matsumoto"} },
+    .{ .native = "井上", .roman = &.{ "inoue", "inouye" } },
+    .{ .native = "木村", .roman = &.{"kimura"} },
+    .{ .native = "林", .roman = &.{"hayashi"} },
+    .{ .native = "清水", .roman = &.{"shimizu"} },
+};
+
+/// Whether `text` contains Han, kana, or Hangul characters.
+pub fn isNative(text: []const u8) bool {
+    const view = std.unicode.Utf8View.init(text) catch return false;
+    var it = view.iterator();
+    while (it.nextCodepoint()) |cp| {
+        switch (cp) {
+            0x3040...0x30FF, // Hiragana, Katakana
+            0x3400...0x4DBF, // CJK Extension A
+            0x4E00...0x9FFF, // CJK Unified Ideographs
+            0xAC00...0xD7AF, // Hangul syllables
+            0xF900...0xFAFF, // CJK Compatibility Ideographs
+            0x20000...0x2A6DF, // CJK Extension B
+            => return true,
+            else => {},
+        }
+    }
+    return false;
+}
+
+/// Byte length of the surname that starts a name written without a space
+/// ("山田太郎", "张伟"): a known surname of several characters, or else the
+/// first character.
+pub fn surnameLength(name: []const u8) usize {
+    for (surnames) |surname| {
+        if (surname.native.len > 3 and name.len > surname.native.len and std.mem.startsWith(u8, name, surname.native)) {
+            return surname.native.len;
+        }
+    }
+    const first = std.unicode.utf8ByteSequenceLength(name[0]) catch 1;
+    return @min(first, name.len);
+}
+
+/// Whether `roman` is a romanization of the native surname `native`.
+pub fn romanizes(native: []const u8, roman: []const u8) bool {
+    for (surnames) |surname| {
+        if (!std.mem.eql(u8, surname.native, native)) continue;
+        for (surname.roman) |r| {
+            if (std.mem.eql(u8, r, roman)) return true;
+        }
+    }
+    return false;
+}
+
+/// Whether `roman` is a common romanized CJK surname, so that a name
+/// starting with it may be written surname first.
+pub fn isRomanizedSurname(roman: []const u8) bool {
+    for (surnames) |surname| {
+        for (surname.roman) |r| {
+            if (std.mem.eql(u8, r, roman)) return true;
+        }
+    }
+    return false;
+}
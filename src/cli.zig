@@ -0,0 +1,570 @@
+//! The command-line interface: commands and options, described once for
+//! `--help`, shell completion scripts (`bibval completions SHELL`), and the
+//! man page (`bibval man`). Option parsing itself is in main.zig; options
+//! added there belong in `options` too.
+
+const std = @import("std");
+const rules = @import("rules.zig");
+
+pub const version = "26.1.4";
+
+pub const Command = struct {
+    name: []const u8,
+    /// Usage line after "bibval", or null for commands without arguments.
+    synopsis: ?[]const u8 = null,
+    summary: []const u8,
+    /// Words accepted after the command, for completion.
+    arguments: []const []const u8 = &.{},
+};
+
+pub const commands = [_]Command{
+    .{ .name = "check", .synopsis = "[check] [options] <file.bib> [file2.bib ...]", .summary = "Validate entries against academic databases (default)" },
+    .{ .name = "plan", .synopsis = "plan [options] <file.bib> [file2.bib ...]", .summary = "Show lookup strategies and request counts without network access" },
+    .{ .name = "snapshot", .synopsis = "snapshot [--out FILE] <file.bib> [file2.bib ...]", .summary = "Validate and store every matched remote record (see --out)" },
+    .{ .name = "rules", .summary = "List all checks with their IDs and default severities" },
+    .{ .name = "stats", .synopsis = "stats [--author NAME ...] <file.bib> [file2.bib ...]", .summary = "Summarize venues, years, cited authors, and self-citations" },
+    .{ .name = "one", .synopsis = "one --title TITLE [--author NAME ...] [--year YEAR] | --doi DOI", .summary = "Look up a single reference and print its matches and a BibTeX entry" },
+    .{ .name = "serve", .synopsis = "serve [--listen ADDR] [--port N] [--rate-limit N]", .summary = "Serve POST /validate, GET /resolve?doi= and GET /metrics over HTTP with a shared cache" },
+    .{ .name = "history", .synopsis = "history [--history DB] KEY", .summary = "Show when findings of an entry appeared and were fixed across recorded runs" },
+    .{ .name = "cache", .synopsis = "cache stats | clear | show KEY", .summary = "Summarize (stats), empty (clear), or inspect (show KEY) the local cache", .arguments = &.{ "stats", "clear", "show" } },
+    .{ .name = "config", .synopsis = "config check [--config PATH] [--profile NAME]", .summary = "Report unknown keys, type errors, and invalid values in the config file", .arguments = &.{"check"} },
+    .{ .name = "auth", .synopsis = "auth set ieee | springer | scopus | zotero", .summary = "Store an API key in the OS keyring (set), or show where each key comes from (status)", .arguments = &.{ "set", "status" } },
+    .{ .name = "completions", .synopsis = "completions bash | zsh | fish | powershell", .summary = "Print a shell completion script", .arguments = &.{ "bash", "zsh", "fish", "powershell" } },
+    .{ .name = "man", .summary = "Print the man page (roff)" },
+};
+
+/// How the value of an option is completed.
+pub const Complete = union(enum) {
+    none,
+    file,
+    choice: []const []const u8,
+};
+
+pub const Option = struct {
+    long: []const u8,
+    short: ?[]const u8 = null,
+    /// Placeholder of its value, e.g. "FORMAT"; null for flags.
+    value: ?[]const u8 = null,
+    complete: Complete = .none,
+    help: []const u8,
+};
+
+const rule_names = blk: {
+    const all = std.enums.values(rules.Rule);
+    var names: [all.len][]const u8 = undefined;
+    for (all, &names) |rule, *name| name.* = rule.slug();
+    const final = names;
+    break :blk &final;
+};
+
+pub const options = [_]Option{
+    .{ .long = "--help", .short = "-h", .help = "Show this help" },
+    .{ .long = "--version", .short = "-V", .help = "Show version" },
+    .{ .long = "--strict", .short = "-s", .help = "Exit with error if any issues found" },
+    .{ .long = "--max-errors", .value = "N", .help = "Fail only if there are more than N errors (counts warnings with --strict)" },
+    .{ .long = "--max-unmatched-percent", .value = "P", .help = "Fail if more than P% of entries are not found" },
+    .{ .long = "--require-doi-coverage", .value = "P", .help = "Fail if fewer than P% of entries have a DOI" },
+    .{ .long = "--verbose", .short = "-v", .help = "Verbose output (repeat or use -vv to trace HTTP requests)" },
+    .{ .long = "--log-json", .help = "Emit verbose trace events as JSON lines on stderr" },
+    .{ .long = "--quiet", .short = "-q", .help = "Only show errors and failures" },
+    .{ .long = "--no-color", .help = "Plain text output without colors or links (also NO_COLOR)" },
+    .{ .long = "--show-matched", .help = "Print every field of the matched remote records beneath each entry" },
+    .{ .long = "--json", .help = "Output JSON format (same as --format json)" },
+    .{ .long = "--format", .value = "FORMAT", .complete = .{ .choice = &.{ "text", "json", "patch" } }, .help = "Output format: text, json, or patch (unified diff of suggested fixes)" },
+    .{ .long = "--sort", .value = "ORDER", .complete = .{ .choice = &.{ "file", "severity" } }, .help = "Order entries by input position (file, default) or severity" },
+    .{ .long = "--string-macros", .help = "In patch output, define @string macros for venues used by several entries" },
+    .{ .long = "--key", .short = "-k", .value = "KEY", .help = "Only validate entries with these keys (comma-separated; also --only)" },
+    .{ .long = "--key-glob", .value = "PAT", .help = "Only validate entries whose key matches a pattern such as 'smith*'" },
+    .{ .long = "--type", .value = "TYPES", .help = "Only validate entries of these types (e.g. article,inproceedings)" },
+    .{ .long = "--modified-since", .value = "REV", .help = "Only validate entries added or changed since a git revision" },
+    .{ .long = "--group", .value = "NAME", .help = "Only validate entries in this JabRef group or its subgroups (repeatable)" },
+    .{ .long = "--author", .value = "NAME", .help = "Manuscript author for self-citation stats, or the reference's author with one (repeatable)" },
+    .{ .long = "--title", .value = "TITLE", .help = "Title of the reference to look up with one" },
+    .{ .long = "--year", .value = "YEAR", .help = "Year of the reference to look up with one" },
+    .{ .long = "--doi", .value = "DOI", .help = "DOI of the reference to look up with one" },
+    .{ .long = "--listen", .value = "ADDR", .help = "Address for serve to listen on (default: 127.0.0.1)" },
+    .{ .long = "--port", .value = "N", .help = "Port for serve (default: 8080)" },
+    .{ .long = "--rate-limit", .value = "N", .help = "Entries each serve client may look up per minute (default: 600; 0: unlimited)" },
+    .{ .long = "--disable", .value = "RULES", .complete = .{ .choice = rule_names }, .help = "Suppress checks by ID or name (e.g. BV003,missing-doi)" },
+    .{ .long = "--min-confidence", .value = "X", .help = "Suppress discrepancies with confidence below X (0 to 1)" },
+    .{ .long = "--no-crossref", .help = "Disable CrossRef API" },
+    .{ .long = "--no-dblp", .help = "Disable DBLP API" },
+    .{ .long = "--no-semantic", .help = "Disable Semantic Scholar API" },
+    .{ .long = "--no-openalex", .help = "Disable OpenAlex API" },
+    .{ .long = "--no-open-library", .help = "Disable Open Library ISBN lookups" },
+    .{ .long = "--no-google-books", .help = "Disable Google Books ISBN lookups" },
+    .{ .long = "--no-arxiv", .help = "Disable arXiv API lookups" },
+    .{ .long = "--no-pmlr", .help = "Disable PMLR proceedings lookups" },
+    .{ .long = "--no-neurips", .help = "Disable NeurIPS proceedings lookups" },
+    .{ .long = "--no-ieee", .help = "Disable IEEE Xplore lookups (on with IEEE_API_KEY or validators.ieee.api_key)" },
+    .{ .long = "--no-acm", .help = "Disable ACM DL lookups (on with validators.acm.enabled)" },
+    .{ .long = "--no-springer", .help = "Disable Springer Nature lookups (on with SPRINGER_API_KEY or validators.springer.api_key)" },
+    .{ .long = "--no-scopus", .help = "Disable Scopus lookups (on with SCOPUS_API_KEY or validators.scopus.api_key)" },
+    .{ .long = "--no-github", .help = "Disable GitHub repository lookups" },
+    .{ .long = "--no-zenodo", .help = "Disable Zenodo record lookups" },
+    .{ .long = "--no-figshare", .help = "Disable Figshare article lookups" },
+    .{ .long = "--no-web", .help = "Disable reading the metadata of cited web pages" },
+    .{ .long = "--no-external", .help = "Disable validator plugins from the config" },
+    .{ .long = "--canonical-venues", .help = "Compare venues as OpenAlex sources or DBLP streams, not by name" },
+    .{ .long = "--no-cache", .help = "Disable response caching" },
+    .{ .long = "--check-funders", .help = "Verify funders against the Crossref Funder Registry" },
+    .{ .long = "--suggest-archives", .help = "Suggest Wayback Machine snapshots for web citations" },
+    .{ .long = "--archive-urls", .help = "Like --suggest-archives, saving pages that have no snapshot" },
+    .{ .long = "--open-access", .help = "List open-access status and license of each entry (via OpenAlex)" },
+    .{ .long = "--closed-access", .help = "List only entries that are not open access" },
+    .{ .long = "--request-interval", .value = "MS", .help = "Minimum delay between API requests" },
+    .{ .long = "--deadline", .value = "TIME", .help = "Start no more entries after TIME (e.g. 90s, 5m, 1h); the rest is reported as not validated" },
+    .{ .long = "--jobs", .short = "-j", .value = "N", .help = "Threads for snapshot replay (default: one per CPU)" },
+    .{ .long = "--config", .value = "PATH", .complete = .file, .help = "Config file (default: .bibval.toml)" },
+    .{ .long = "--profile", .value = "NAME", .complete = .{ .choice = &.{ "ieee", "apa", "acm" } }, .help = "Check against a citation style (ieee, apa, acm) or a profile file or URL" },
+    .{ .long = "--grobid-url", .value = "URL", .help = "GROBID service used to extract references from PDF inputs" },
+    .{ .long = "--clipboard", .help = "Also read references from the clipboard (format is detected)" },
+    .{ .long = "--zotero", .help = "Also read references from Zotero (local API of the running app)" },
+    .{ .long = "--zotero-url", .value = "URL", .help = "Zotero library to read, e.g. https://api.zotero.org/users/ID" },
+    .{ .long = "--zotero-write", .help = "Write suggested fixes back to Zotero (web API; needs ZOTERO_API_KEY)" },
+    .{ .long = "--resume", .help = "Resume an interrupted run from its journal" },
+    .{ .long = "--journal", .value = "PATH", .complete = .file, .help = "Progress journal location (default: .bibval-journal.jsonl)" },
+    .{ .long = "--snapshot", .value = "FILE", .complete = .file, .help = "Validate against a stored snapshot instead of the live APIs" },
+    .{ .long = "--history", .value = "DB", .complete = .file, .help = "Record each run in an SQLite database (default for history: .bibval-history.sqlite)" },
+    .{ .long = "--out", .short = "-o", .value = "FILE", .complete = .file, .help = "Snapshot output path (default: bibval-snapshot.json)" },
+};
+
+const inputs =
+    \\Inputs may be BibTeX (.bib), RIS (.ris), CSL-JSON (.json), plain-text
+    \\reference lists (.txt), or PDFs. Use - to read stdin; its format is detected.
+    \\Quoted glob patterns ('chapters/**/*.bib') are expanded; all files are checked
+    \\as one project for duplicate keys and works.
+;
+
+const examples = [_][]const u8{
+    "bibval references.bib",
+    "bibval paper.bib thesis.bib --strict",
+    "bibval refs.bib --max-errors 10 --require-doi-coverage 90",
+    "bibval refs.bib -k smith2021,jones2022 --json",
+    "bibval plan refs.bib --request-interval 1000",
+    "bibval refs.bib --format patch | git apply",
+    "bibval paper.pdf --grobid-url http://localhost:8070",
+    "bibval stats refs.bib --author \"Jane Doe\" --author \"John Smith\"",
+    "pbpaste | bibval check -",
+    "bibval check --zotero",
+    "bibval one --title \"Attention is all you need\" --author Vaswani --year 2017",
+    "bibval serve --listen 0.0.0.0 --request-interval 100",
+    "bibval completions fish > ~/.config/fish/completions/bibval.fish",
+};
+
+/// The `--help` text.
+pub fn writeHelp(w: *std.Io.Writer) !void {
+    try w.writeAll("bibval - Citation validator for BibTeX files\n\nUsage:\n");
+    for (commands) |command| {
+        if (command.synopsis) |synopsis| try w.print("  bibval {s}\n", .{synopsis});
+    }
+    try w.print("\n{s}\n\nCommands:\n", .{inputs});
+    for (commands) |command| {
+        try w.print("  {s}", .{command.name});
+        try pad(w, command.name.len, 16);
+        try w.print("{s}\n", .{command.summary});
+    }
+    try w.writeAll("\nOptions:\n");
+    for (options) |option| {
+        try w.writeAll("  ");
+        if (option.short) |short| try w.print("{s}, ", .{short});
+        try w.writeAll(option.long);
+        if (option.value) |value| try w.print(" {s}", .{value});
+        try pad(w, flagsLen(option), 18);
+        try w.print("{s}\n", .{option.help});
+    }
+    try w.writeAll("\nExample:\n");
+    for (examples) |example| try w.print("  {s}\n", .{example});
+}
+
+fn flagsLen(option: Option) usize {
+    var len = option.long.len;
+    if (option.short) |short| len += short.len + 2;
+    if (option.value) |value| len += value.len + 1;
+    return len;
+}
+
+/// Pad `len` columns to `width`, leaving at least one space (two after
+/// text that fills the column).
+fn pad(w: *std.Io.Writer, len: usize, width: usize) !void {
+    try w.splatByteAll(' ', if (len < width) width - len else 2);
+}
+
+pub const Shell = enum { bash, zsh, fish, powershell };
+
+/// The completion script for `shell`.
+pub fn writeCompletions(w: *std.Io.Writer, shell: Shell) !void {
+    switch (shell) {
+        .bash => try writeBash(w),
+        .zsh => try writeZsh(w),
+        .fish => try writeFish(w),
+        .powershell => try writePowerShell(w),
+    }
+}
+
+fn writeBash(w: *std.Io.Writer) !void {
+    try w.writeAll(
+        \\# bash completion for bibval
+        \\_bibval() {
+        \\    local cur="${COMP_WORDS[COMP_CWORD]}"
+        \\    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+        \\    case "$prev" in
+        \\
+    );
+    for (options) |option| {
+        if (option.value == null) continue;
+        try w.writeAll("        ");
+        if (option.short) |short| try w.print("{s}|", .{short});
+        try w.print("{s})\n", .{option.long});
+        switch (option.complete) {
+            .none => try w.writeAll("            return ;;\n"),
+            .file => try w.writeAll("            COMPREPLY=($(compgen -f -- \"$cur\"))\n            return ;;\n"),
+            .choice => |choices| {
+                try w.writeAll("            COMPREPLY=($(compgen -W \"");
+                try writeWords(w, choices);
+                try w.writeAll("\" -- \"$cur\"))\n            return ;;\n");
+            },
+        }
+    }
+    for (commands) |command| {
+        if (command.arguments.len == 0) continue;
+        try w.print("        {s})\n            COMPREPLY=($(compgen -W \"", .{command.name});
+        try writeWords(w, command.arguments);
+        try w.writeAll("\" -- \"$cur\"))\n            return ;;\n");
+    }
+    try w.writeAll(
+        \\    esac
+        \\    if [[ "$cur" == -* ]]; then
+        \\        COMPREPLY=($(compgen -W "
+    );
+    for (options, 0..) |option, i| {
+        if (i > 0) try w.writeAll(" ");
+        if (option.short) |short| try w.print("{s} ", .{short});
+        try w.writeAll(option.long);
+    }
+    try w.writeAll(
+        \\" -- "$cur"))
+        \\    elif [[ $COMP_CWORD -eq 1 ]]; then
+        \\        COMPREPLY=($(compgen -W "
+    );
+    for (commands, 0..) |command, i| {
+        if (i > 0) try w.writeAll(" ");
+        try w.writeAll(command.name);
+    }
+    try w.writeAll(
+        \\" -- "$cur") $(compgen -f -- "$cur"))
+        \\    else
+        \\        COMPREPLY=($(compgen -f -- "$cur"))
+        \\    fi
+        \\}
+        \\complete -o filenames -F _bibval bibval
+        \\
+    );
+}
+
+fn writeZsh(w: *std.Io.Writer) !void {
+    try w.writeAll(
+        \\#compdef bibval
+        \\
+        \\_bibval() {
+        \\  local -a commands
+        \\  commands=(
+        \\
+    );
+    for (commands) |command| {
+        try w.writeAll("    '");
+        try writeQuoted(w, command.name, ":");
+        try w.writeAll(":");
+        try writeQuoted(w, command.summary, ":");
+        try w.writeAll("'\n");
+    }
+    try w.writeAll(
+        \\  )
+        \\  local state
+        \\  _arguments -s \
+        \\
+    );
+    for (options) |option| {
+        try w.writeAll("    ");
+        if (option.short) |short| {
+            try w.print("'({s} {s})'{{{s},{s}}}'[", .{ short, option.long, short, option.long });
+        } else {
+            try w.print("'{s}[", .{option.long});
+        }
+        try writeQuoted(w, option.help, "[]");
+        try w.writeAll("]");
+        if (option.value) |value| {
+            try w.print(":{s}:", .{value});
+            switch (option.complete) {
+                .none => try w.writeAll(" "),
+                .file => try w.writeAll("_files"),
+                .choice => |choices| {
+                    try w.writeAll("(");
+                    try writeWords(w, choices);
+                    try w.writeAll(")");
+                },
+            }
+        }
+        try w.writeAll("' \\\n");
+    }
+    try w.writeAll(
+        \\    '1: :->first' \
+        \\    '*: :->rest' && return
+        \\  case $state in
+        \\    first)
+        \\      _describe -t commands 'bibval command' commands
+        \\      _files
+        \\      ;;
+        \\    rest)
+        \\      case $words[2] in
+        \\
+    );
+    for (commands) |command| {
+        if (command.arguments.len == 0) continue;
+        try w.print("        {s}) _values '{s}' ", .{ command.name, command.name });
+        for (command.arguments, 0..) |argument, i| {
+            if (i > 0) try w.writeAll(" ");
+            try w.print("'{s}'", .{argument});
+        }
+        try w.writeAll(" ;;\n");
+    }
+    try w.writeAll(
+        \\        *) _files ;;
+        \\      esac
+        \\      ;;
+        \\  esac
+        \\}
+        \\
+        \\_bibval "$@"
+        \\
+    );
+}
+
+fn writeFish(w: *std.Io.Writer) !void {
+    try w.writeAll("# fish completion for bibval\n");
+    for (commands) |command| {
+        try w.print("complete -c bibval -n __fish_use_subcommand -a {s} -d '", .{command.name});
+        try writeQuoted(w, command.summary, "");
+        try w.writeAll("'\n");
+        if (command.arguments.len == 0) continue;
+        try w.print("complete -c bibval -n '__fish_seen_subcommand_from {s}' -f -a '", .{command.name});
+        try writeWords(w, command.arguments);
+        try w.writeAll("'\n");
+    }
+    for (options) |option| {
+        try w.print("complete -c bibval -l {s}", .{option.long[2..]});
+        if (option.short) |short| try w.print(" -s {s}", .{short[1..]});
+        if (option.value != null) {
+            switch (option.complete) {
+                .none => try w.writeAll(" -x"),
+                .file => try w.writeAll(" -r -F"),
+                .choice => |choices| {
+                    try w.writeAll(" -x -a '");
+                    try writeWords(w, choices);
+                    try w.writeAll("'");
+                },
+            }
+        }
+        try w.writeAll(" -d '");
+        try writeQuoted(w, option.help, "");
+        try w.writeAll("'\n");
+    }
+}
+
+fn writePowerShell(w: *std.Io.Writer) !void {
+    try w.writeAll(
+        \\# PowerShell completion for bibval
+        \\Register-ArgumentCompleter -Native -CommandName bibval -ScriptBlock {
+        \\    param($wordToComplete, $commandAst, $cursorPosition)
+        \\    $words = @($commandAst.CommandElements | ForEach-Object { $_.ToString() })
+        \\    $previous = if ($wordToComplete) { $words[-2] } else { $words[-1] }
+        \\    $values = switch ($previous) {
+        \\
+    );
+    for (options) |option| {
+        const choices = switch (option.complete) {
+            .choice => |choices| choices,
+            .none, .file => continue,
+        };
+        for ([_]?[]const u8{ option.short, option.long }) |flag| {
+            try w.print("        '{s}' {{ ", .{flag orelse continue});
+            try writePowerShellList(w, choices);
+            try w.writeAll(" }\n");
+        }
+    }
+    for (commands) |command| {
+        if (command.arguments.len == 0) continue;
+        try w.print("        '{s}' {{ ", .{command.name});
+        try writePowerShellList(w, command.arguments);
+        try w.writeAll(" }\n");
+    }
+    try w.writeAll(
+        \\        default { $null }
+        \\    }
+        \\    if ($null -eq $values) {
+        \\        if ($wordToComplete.StartsWith('-')) {
+        \\            $values = @(
+    );
+    var flags: [options.len * 2][]const u8 = undefined;
+    var count: usize = 0;
+    for (options) |option| {
+        if (option.short) |short| {
+            flags[count] = short;
+            count += 1;
+        }
+        flags[count] = option.long;
+        count += 1;
+    }
+    try writePowerShellList(w, flags[0..count]);
+    try w.writeAll(
+        \\)
+        \\        } elseif ($words.Count -le 2) {
+        \\            $values = @(
+    );
+    var names: [commands.len][]const u8 = undefined;
+    for (commands, &names) |command, *name| name.* = command.name;
+    try writePowerShellList(w, &names);
+    try w.writeAll(
+        \\)
+        \\        }
+        \\    }
+        \\    $values | Where-Object { $_ -like "$wordToComplete*" } | ForEach-Object {
+        \\        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+        \\    }
+        \\}
+        \\
+    );
+}
+
+fn writePowerShellList(w: *std.Io.Writer, words: []const []const u8) !void {
+    for (words, 0..) |word, i| {
+        if (i > 0) try w.writeAll(", ");
+        try w.print("'{s}'", .{word});
+    }
+}
+
+fn writeWords(w: *std.Io.Writer, words: []const []const u8) !void {
+    for (words, 0..) |word, i| {
+        if (i > 0) try w.writeAll(" ");
+        try w.writeAll(word);
+    }
+}
+
+/// `text` for a single-quoted shell string, with the characters in
+/// `special` escaped by a backslash.
+fn writeQuoted(w: *std.Io.Writer, text: []const u8, special: []const u8) !void {
+    for (text) |c| {
+        if (c == '\'') {
+            try w.writeAll("'\\''");
+        } else {
+            if (std.mem.indexOfScalar(u8, special, c) != null) try w.writeByte('\\');
+            try w.writeByte(c);
+        }
+    }
+}
+
+/// The man page, in roff.
+pub fn writeMan(w: *std.Io.Writer) !void {
+    try w.print(
+        \\.TH BIBVAL 1 "" "bibval {s}" "User Commands"
+        \\.SH NAME
+        \\bibval \- citation validator for BibTeX files
+        \\.SH SYNOPSIS
+        \\.nf
+        \\
+    , .{version});
+    for (commands) |command| {
+        if (command.synopsis) |synopsis| {
+            try w.writeAll("\\fBbibval\\fR ");
+            try writeRoff(w, synopsis);
+            try w.writeAll("\n");
+        }
+    }
+    try w.writeAll(
+        \\.fi
+        \\.SH DESCRIPTION
+        \\Validates bibliographic entries against academic databases including
+        \\CrossRef, DBLP, arXiv, Semantic Scholar, and OpenAlex.
+        \\.PP
+        \\
+    );
+    try writeRoff(w, inputs);
+    try w.writeAll("\n.SH COMMANDS\n");
+    for (commands) |command| {
+        try w.print(".TP\n.B {s}\n", .{command.name});
+        try writeRoff(w, command.summary);
+        try w.writeAll("\n");
+    }
+    try w.writeAll(".SH OPTIONS\n");
+    for (options) |option| {
+        try w.writeAll(".TP\n");
+        if (option.short) |short| {
+            try w.writeAll("\\fB");
+            try writeRoff(w, short);
+            try w.writeAll("\\fR, ");
+        }
+        try w.writeAll("\\fB");
+        try writeRoff(w, option.long);
+        try w.writeAll("\\fR");
+        if (option.value) |value| try w.print(" \\fI{s}\\fR", .{value});
+        try w.writeAll("\n");
+        try writeRoff(w, option.help);
+        try w.writeAll("\n");
+    }
+    try w.writeAll(".SH EXAMPLES\n.nf\n");
+    for (examples) |example| {
+        try writeRoff(w, example);
+        try w.writeAll("\n");
+    }
+    try w.writeAll(".fi\n");
+}
+
+/// `text` with roff escapes: backslashes and hyphens escaped, and lines
+/// that would start with a control character guarded.
+fn writeRoff(w: *std.Io.Writer, text: []const u8) !void {
+    var line_start = true;
+    for (text) |c| {
+        if (line_start and (c == '.' or c == '\'')) try w.writeAll("\\&");
+        switch (c) {
+            '\\' => try w.writeAll("\\e"),
+            '-' => try w.writeAll("\\-"),
+            else => try w.writeByte(c),
+        }
+        line_start = c == '\n';
+    }
+}
+
+test "help, completions, and man page" {
+    const allocator = std.testing.allocator;
+
+    var help: std.Io.Writer.Allocating = .init(allocator);
+    defer help.deinit();
+    try writeHelp(&help.writer);
+    try std.testing.expect(std.mem.indexOf(u8, help.written(), "\n  check           Validate entries against academic databases (default)\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, help.written(), "\n  -h, --help        Show this help\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, help.written(), "\n  --no-open-library Disable Open Library ISBN lookups\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, help.written(), "\n  --min-confidence X  Suppress") != null);
+
+    var bash: std.Io.Writer.Allocating = .init(allocator);
+    defer bash.deinit();
+    try writeCompletions(&bash.writer, .bash);
+    try std.testing.expect(std.mem.indexOf(u8, bash.written(), "        --format)\n            COMPREPLY=($(compgen -W \"text json patch\" -- \"$cur\"))\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, bash.written(), " crossmark-update ") != null);
+
+    var zsh: std.Io.Writer.Allocating = .init(allocator);
+    defer zsh.deinit();
+    try writeCompletions(&zsh.writer, .zsh);
+    try std.testing.expect(std.mem.indexOf(u8, zsh.written(), "    '(-o --out)'{-o,--out}'[Snapshot output path (default: bibval-snapshot.json)]:FILE:_files' \\\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, zsh.written(), "the reference'\\''s author") != null);
+
+    var fish: std.Io.Writer.Allocating = .init(allocator);
+    defer fish.deinit();
+    try writeCompletions(&fish.writer, .fish);
+    try std.testing.expect(std.mem.indexOf(u8, fish.written(), "complete -c bibval -l sort -x -a 'file severity' -d ") != null);
+
+    var man: std.Io.Writer.Allocating = .init(allocator);
+    defer man.deinit();
+    try writeMan(&man.writer);
+    try std.testing.expect(std.mem.indexOf(u8, man.written(), ".TP\n\\fB\\-j\\fR, \\fB\\-\\-jobs\\fR \\fIN\\fR\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, man.written(), "\n.SH EXAMPLES\n.nf\nbibval references.bib\n") != null);
+}
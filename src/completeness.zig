@@ -0,0 +1,162 @@
+//! Completeness of entries and of the bibliography as a whole.
+//!
+//! Each entry type has recommended fields: an article needs a journal,
+//! volume, pages, and DOI, a book a publisher and ISBN. An entry scores a
+//! point for each recommended field it has and another for each that the
+//! matched record confirms, i.e. has too, with no error or warning about
+//! it. The bibliography's grade is the mean score, a single number to
+//! improve and to track across revisions.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const Entry = entry_mod.Entry;
+const DiscrepancyField = entry_mod.DiscrepancyField;
+const EntryReport = report_mod.EntryReport;
+const Report = report_mod.Report;
+
+pub const Field = enum {
+    title,
+    authors,
+    year,
+    venue,
+    volume,
+    pages,
+    doi,
+    publisher,
+    isbn,
+    url,
+
+    fn has(self: Field, e: *const Entry) bool {
+        return switch (self) {
+            .title => e.title != null,
+            .authors => e.authors.len > 0,
+            .year => e.year != null,
+            .venue => e.venue != null,
+            .volume => e.extraField("volume") != null,
+            .pages => e.extraField("pages") != null,
+            .doi => e.doi != null,
+            .publisher => e.publisher != null,
+            .isbn => e.isbn != null,
+            .url => e.url != null,
+        };
+    }
+
+    /// The field discrepancies about this one are reported under.
+    fn discrepancyField(self: Field) ?DiscrepancyField {
+        return switch (self) {
+            .title => .title,
+            .authors => .authors,
+            .year => .year,
+            .venue => .venue,
+            .volume => .volume,
+            .pages => .pages,
+            .doi => .doi,
+            .publisher => .publisher,
+            .isbn, .url => null,
+        };
+    }
+};
+
+pub const Fields = std.EnumSet(Field);
+
+/// Recommended fields of an entry type.
+pub fn recommended(entry_type: []const u8) Fields {
+    const t = entry_type;
+    if (eql(t, "article")) return .initMany(&.{ .title, .authors, .year, .venue, .volume, .pages, .doi });
+    if (eql(t, "inproceedings") or eql(t, "conference")) return .initMany(&.{ .title, .authors, .year, .venue, .pages, .doi });
+    if (eql(t, "incollection")) return .initMany(&.{ .title, .authors, .year, .venue, .pages, .publisher, .doi });
+    if (eql(t, "book")) return .initMany(&.{ .title, .authors, .year, .publisher, .isbn });
+    if (eql(t, "misc") or eql(t, "online") or eql(t, "software") or eql(t, "techreport")) return .initMany(&.{ .title, .authors, .year, .url });
+    return .initMany(&.{ .title, .authors, .year });
+}
+
+fn eql(a: []const u8, b: []const u8) bool {
+    return std.ascii.eqlIgnoreCase(a, b);
+}
+
+pub const Score = struct {
+    /// Recommended fields of the entry's type.
+    recommended: Fields,
+    /// Recommended fields the entry has.
+    present: Fields,
+    /// Present fields the matched record confirms.
+    verified: Fields,
+
+    /// Score from 0 to 100: half for presence, half for verification.
+    pub fn percent(self: Score) u8 {
+        const total = self.recommended.count();
+        if (total == 0) return 100;
+        const points = self.present.count() + self.verified.count();
+        return @intCast((points * 100 + total) / (2 * total));
+    }
+
+    /// Recommended fields the entry lacks.
+    pub fn missing(self: Score) Fields {
+        return self.recommended.differenceWith(self.present);
+    }
+};
+
+/// Completeness of one entry.
+pub fn score(entry_report: *const EntryReport) Score {
+    const e = &entry_report.entry;
+    const wanted = recommended(e.entry_type);
+    var result = Score{ .recommended = wanted, .present = .initEmpty(), .verified = .initEmpty() };
+
+    var it = wanted.iterator();
+    while (it.next()) |field| {
+        if (!field.has(e)) continue;
+        result.present.insert(field);
+        if (confirmed(entry_report, field)) result.verified.insert(field);
+    }
+    return result;
+}
+
+/// Whether a matched record has `field` and no result disputes it.
+fn confirmed(entry_report: *const EntryReport, field: Field) bool {
+    var matched = false;
+    for (entry_report.validation_results) |result| {
+        const remote = result.matched_entry orelse continue;
+        if (field.has(&remote)) matched = true;
+        const reported = field.discrepancyField() orelse continue;
+        for (result.discrepancies) |d| {
+            if (d.field == reported and d.severity != .info) return false;
+        }
+    }
+    return matched;
+}
+
+pub const Grade = struct {
+    /// Mean entry score, 0 to 100.
+    percent: u8,
+
+    pub fn letter(self: Grade) u8 {
+        return if (self.percent >= 90) 'A' else if (self.percent >= 80) 'B' else if (self.percent >= 70) 'C' else if (self.percent >= 60) 'D' else 'F';
+    }
+};
+
+/// Grade of a bibliography, or null if it has no entries.
+pub fn grade(report: *const Report) ?Grade {
+    if (report.entries.items.len == 0) return null;
+    var sum: usize = 0;
+    for (report.entries.items) |*entry_report| sum += score(entry_report).percent();
+    const n = report.entries.items.len;
+    return .{ .percent = @intCast((sum + n / 2) / n) };
+}
+
+test "completeness score" {
+    const local = Entry{ .key = "a", .entry_type = "article", .title = "T", .authors = &.{"Jane Smith"}, .year = 2020, .venue = "J", .doi = "10.1/x" };
+    const remote = Entry{ .key = "r", .entry_type = "article", .title = "T", .authors = &.{"Jane Smith"}, .year = 2021, .venue = "J" };
+    var discrepancies = [_]entry_mod.Discrepancy{.{ .rule = .year_mismatch, .field = .year, .severity = .@"error", .local_value = "2020", .remote_value = "2021", .message = "" }};
+    var results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .matched_entry = remote, .confidence = 0.9, .discrepancies = &discrepancies }};
+    const entry_report = EntryReport{ .entry = local, .status = .@"error", .validation_results = &results };
+
+    const s = score(&entry_report);
+    // 5 of 7 present; title, authors, venue verified
+    try std.testing.expectEqual(@as(u8, 57), s.percent());
+    try std.testing.expect(s.missing().contains(.volume));
+    try std.testing.expect(!s.verified.contains(.year));
+    try std.testing.expect(!s.verified.contains(.doi));
+
+    try std.testing.expectEqual(@as(u8, 'C'), (Grade{ .percent = 75 }).letter());
+}
@@ -0,0 +1,986 @@
+//! Configuration file support.
+//!
+//! bibval reads `.bibval.toml` from the working directory (or the path given
+//! with `--config`). The file uses a small subset of TOML: `[section]`
+//! headers, `[[array]]` table headers, and `key = value` pairs where a
+//! value is a string, integer, boolean, or array of those.
+//!
+//! Every key bibval reads is listed in `schema`. Keys it does not know,
+//! most often misspelled ones that would otherwise be silently ignored,
+//! are reported as warnings with the known key they most resemble.
+
+const std = @import("std");
+const venues = @import("venues.zig");
+const policy = @import("policy.zig");
+const screening = @import("screening.zig");
+const matcher = @import("matcher.zig");
+const external = @import("external.zig");
+const ignores = @import("ignores.zig");
+const field_rules = @import("field_rules.zig");
+const titlecase = @import("titlecase.zig");
+const punctuation = @import("punctuation.zig");
+const regex = @import("regex.zig");
+const notify = @import("notify.zig");
+const workspace = @import("workspace.zig");
+const rules = @import("rules.zig");
+const profiles = @import("profiles.zig");
+const ApiSource = @import("entry.zig").ApiSource;
+const Severity = @import("entry.zig").Severity;
+
+/// Config location used when `--config` is not given.
+pub const DEFAULT_PATH = ".bibval.toml";
+
+pub const ConfigError = error{
+    InvalidSyntax,
+    InvalidValue,
+};
+
+/// Where a config error occurred.
+pub const Diagnostic = struct {
+    /// Line of the error, or of the key whose value it is about; 0 if unknown
+    line: usize = 0,
+    message: []const u8 = "",
+};
+
+/// A problem `Document.check` found in a config file.
+pub const Problem = struct {
+    line: usize,
+    severity: Severity,
+    message: []const u8,
+};
+
+pub const Value = union(enum) {
+    string: []const u8,
+    integer: i64,
+    boolean: bool,
+    array: []const Value,
+};
+
+/// Parsed key/value pairs, keyed by `section.key`. Keys of the n-th
+/// `[[array]]` table are `array.n.key`, counting from 0.
+pub const Document = struct {
+    arena: std.heap.ArenaAllocator,
+    values: std.StringArrayHashMapUnmanaged(Value),
+    /// Number of tables in each `[[array]]`.
+    tables: std.StringArrayHashMapUnmanaged(usize) = .empty,
+    /// Line each key was set on.
+    lines: std.StringArrayHashMapUnmanaged(usize) = .empty,
+
+    pub fn deinit(self: *Document) void {
+        self.arena.deinit();
+    }
+
+    pub fn get(self: *const Document, name: []const u8) ?Value {
+        return self.values.get(name);
+    }
+
+    pub fn tableCount(self: *const Document, name: []const u8) usize {
+        return self.tables.get(name) orelse 0;
+    }
+
+    /// Point `diag` at the line of `name`, so that an error about its
+    /// value reports where it was set.
+    fn note(self: *const Document, name: []const u8, diag: ?*Diagnostic) void {
+        if (diag) |d| d.line = self.lines.get(name) orelse 0;
+    }
+
+    /// String value of `name`, or `InvalidValue` if it has another type.
+    pub fn getString(self: *const Document, name: []const u8, diag: ?*Diagnostic) !?[]const u8 {
+        self.note(name, diag);
+        const value = self.get(name) orelse return null;
+        if (value != .string) return fail(diag, 0, "expected a string");
+        return value.string;
+    }
+
+    /// Integer value of `name`, or `InvalidValue` if it has another type.
+    pub fn getInteger(self: *const Document, name: []const u8, diag: ?*Diagnostic) !?i64 {
+        self.note(name, diag);
+        const value = self.get(name) orelse return null;
+        if (value != .integer) return fail(diag, 0, "expected an integer");
+        return value.integer;
+    }
+
+    /// Array of strings at `name`, or `InvalidValue` if it has another type.
+    pub fn getStringArray(self: *Document, name: []const u8, diag: ?*Diagnostic) !?[]const []const u8 {
+        self.note(name, diag);
+        const value = self.get(name) orelse return null;
+        if (value != .array) return fail(diag, 0, "expected an array of strings");
+        const strings = try self.arena.allocator().alloc([]const u8, value.array.len);
+        for (value.array, strings) |item, *s| {
+            if (item != .string) return fail(diag, 0, "expected an array of strings");
+            s.* = item.string;
+        }
+        return strings;
+    }
+
+    /// Boolean value of `name`, or `InvalidValue` if it has another type.
+    pub fn getBool(self: *const Document, name: []const u8, diag: ?*Diagnostic) !?bool {
+        self.note(name, diag);
+        const value = self.get(name) orelse return null;
+        if (value != .boolean) return fail(diag, 0, "expected true or false");
+        return value.boolean;
+    }
+
+    /// Take the values of `base` the document does not set. The tables of
+    /// an `[[array]]` in `base` come before the document's own.
+    pub fn inherit(self: *Document, base: *const Document) !void {
+        const arena = self.arena.allocator();
+
+        var values: std.StringArrayHashMapUnmanaged(Value) = .empty;
+        var lines: std.StringArrayHashMapUnmanaged(usize) = .empty;
+        for (self.values.keys(), self.values.values()) |name, value| {
+            const shifted = try shiftTable(arena, name, base);
+            try values.put(arena, shifted, value);
+            if (self.lines.get(name)) |line| try lines.put(arena, shifted, line);
+        }
+        for (base.values.keys(), base.values.values()) |name, value| {
+            const gop = try values.getOrPut(arena, name);
+            if (gop.found_existing) continue;
+            gop.key_ptr.* = try arena.dupe(u8, name);
+            gop.value_ptr.* = try dupeValue(arena, value);
+        }
+        for (base.tables.keys(), base.tables.values()) |name, count| {
+            const gop = try self.tables.getOrPut(arena, name);
+            if (gop.found_existing) {
+                gop.value_ptr.* += count;
+            } else {
+                gop.key_ptr.* = try arena.dupe(u8, name);
+                gop.value_ptr.* = count;
+            }
+        }
+        self.values = values;
+        self.lines = lines;
+    }
+
+    /// Hash of every setting, whether the file or its profile set it.
+    pub fn fingerprint(self: *const Document) u64 {
+        var hasher = std.hash.Wyhash.init(0);
+        for (self.values.keys(), self.values.values()) |name, value| {
+            hasher.update(name);
+            hasher.update(&[_]u8{0});
+            hashValue(&hasher, value);
+        }
+        return hasher.final();
+    }
+
+    fn hashValue(hasher: *std.hash.Wyhash, value: Value) void {
+        std.hash.autoHash(hasher, std.meta.activeTag(value));
+        switch (value) {
+            .string => |s| {
+                hasher.update(s);
+                hasher.update(&[_]u8{0});
+            },
+            .integer => |n| std.hash.autoHash(hasher, n),
+            .boolean => |b| std.hash.autoHash(hasher, b),
+            .array => |items| {
+                std.hash.autoHash(hasher, items.len);
+                for (items) |item| hashValue(hasher, item);
+            },
+        }
+    }
+
+    /// Unknown keys, with the known key they most resemble, deprecated
+    /// keys, and values of the wrong type, in file order.
+    pub fn check(self: *Document) ![]const Problem {
+        const arena = self.arena.allocator();
+        var problems: std.ArrayList(Problem) = .empty;
+        for (self.values.keys(), self.values.values()) |name, value| {
+            const line = self.lines.get(name) orelse 0;
+            const key = for (&schema) |*key| {
+                if (key.matches(name)) break key;
+            } else {
+                const message = if (try suggest(arena, name)) |known|
+                    try std.fmt.allocPrint(arena, "unknown key {s}; did you mean {s}?", .{ name, known })
+                else
+                    try std.fmt.allocPrint(arena, "unknown key {s}", .{name});
+                try problems.append(arena, .{ .line = line, .severity = .warning, .message = message });
+                continue;
+            };
+            if (!key.kind.accepts(value)) {
+                const message = try std.fmt.allocPrint(arena, "{s} must be {s}", .{ name, key.kind.description() });
+                try problems.append(arena, .{ .line = line, .severity = .@"error", .message = message });
+            }
+            if (key.replaced_by) |replacement| {
+                const message = try std.fmt.allocPrint(arena, "{s} is deprecated; use {s}", .{ name, try instantiate(arena, replacement, name) });
+                try problems.append(arena, .{ .line = line, .severity = .warning, .message = message });
+            }
+        }
+        return problems.items;
+    }
+};
+
+const Kind = enum {
+    string,
+    integer,
+    boolean,
+    strings,
+
+    fn accepts(self: Kind, value: Value) bool {
+        return switch (self) {
+            .string => value == .string,
+            .integer => value == .integer,
+            .boolean => value == .boolean,
+            .strings => value == .array and for (value.array) |item| {
+                if (item != .string) break false;
+            } else true,
+        };
+    }
+
+    fn description(self: Kind) []const u8 {
+        return switch (self) {
+            .string => "a string",
+            .integer => "an integer",
+            .boolean => "true or false",
+            .strings => "an array of strings",
+        };
+    }
+};
+
+const Key = struct {
+    /// Full name; `*` stands for the number of an `[[array]]` table.
+    name: []const u8,
+    kind: Kind,
+    /// Key to use instead of this deprecated one.
+    replaced_by: ?[]const u8 = null,
+
+    fn matches(self: *const Key, name: []const u8) bool {
+        var want = std.mem.splitScalar(u8, self.name, '.');
+        var have = std.mem.splitScalar(u8, name, '.');
+        while (want.next()) |part| {
+            const actual = have.next() orelse return false;
+            if (std.mem.eql(u8, part, "*")) {
+                _ = std.fmt.parseInt(usize, actual, 10) catch return false;
+            } else if (!std.mem.eql(u8, part, actual)) return false;
+        }
+        return have.next() == null;
+    }
+};
+
+/// Every key bibval reads.
+const schema = [_]Key{
+    .{ .name = "profile", .kind = .string },
+    .{ .name = "fix.venue_style", .kind = .string },
+    .{ .name = "fix.string_macros", .kind = .boolean },
+    .{ .name = "fix.title_style", .kind = .string },
+    .{ .name = "fix.engine", .kind = .string },
+    .{ .name = "policy.require_year", .kind = .boolean },
+    .{ .name = "policy.max_age", .kind = .integer },
+    .{ .name = "policy.max_old_percent", .kind = .integer },
+    .{ .name = "policy.old_severity", .kind = .string },
+    .{ .name = "policy.require_urldate", .kind = .boolean },
+    .{ .name = "policy.urldate_format", .kind = .string },
+    .{ .name = "policy.max_urldate_age", .kind = .integer },
+    .{ .name = "policy.rules.*.name", .kind = .string },
+    .{ .name = "policy.rules.*.types", .kind = .strings },
+    .{ .name = "policy.rules.*.when", .kind = .strings },
+    .{ .name = "policy.rules.*.require", .kind = .strings },
+    .{ .name = "policy.rules.*.forbid", .kind = .strings },
+    .{ .name = "policy.rules.*.field", .kind = .string },
+    .{ .name = "policy.rules.*.pattern", .kind = .string },
+    .{ .name = "policy.rules.*.message", .kind = .string },
+    .{ .name = "policy.rules.*.severity", .kind = .string },
+    .{ .name = "matching.max_year_distance", .kind = .integer },
+    .{ .name = "matching.canonical_venues", .kind = .boolean },
+    .{ .name = "matching.title_weight", .kind = .integer },
+    .{ .name = "matching.author_weight", .kind = .integer },
+    .{ .name = "matching.year_penalty_percent", .kind = .integer },
+    .{ .name = "matching.first_author_weight", .kind = .integer },
+    .{ .name = "matching.last_author_weight", .kind = .integer },
+    .{ .name = "matching.doi_boost", .kind = .boolean },
+    .{ .name = "matching.filter_title", .kind = .boolean },
+    .{ .name = "matching.filter_year", .kind = .boolean },
+    .{ .name = "matching.filter_authors", .kind = .boolean },
+    .{ .name = "matching.similarity.title", .kind = .string },
+    .{ .name = "matching.similarity.authors", .kind = .string },
+    .{ .name = "matching.similarity.venue", .kind = .string },
+    .{ .name = "screening.blocked_venues", .kind = .strings },
+    .{ .name = "screening.blocked_publishers", .kind = .strings },
+    .{ .name = "screening.allowed_venues", .kind = .strings },
+    .{ .name = "screening.blocklist", .kind = .string },
+    .{ .name = "validators.ieee.api_key", .kind = .string },
+    .{ .name = "validators.acm.enabled", .kind = .boolean },
+    .{ .name = "validators.springer.api_key", .kind = .string },
+    .{ .name = "validators.scopus.api_key", .kind = .string },
+    .{ .name = "validators.github.token", .kind = .string },
+    .{ .name = "validators.external.*.name", .kind = .string },
+    .{ .name = "validators.external.*.command", .kind = .strings },
+    .{ .name = "notify.webhooks.*.url", .kind = .string },
+    .{ .name = "notify.webhooks.*.format", .kind = .string },
+    .{ .name = "ignore.*.key", .kind = .string },
+    .{ .name = "ignore.*.reason", .kind = .string },
+    .{ .name = "ignore.*.until", .kind = .string },
+    .{ .name = "targets.*.files", .kind = .strings },
+    .{ .name = "targets.*.name", .kind = .string },
+    .{ .name = "targets.*.validators", .kind = .strings },
+    .{ .name = "targets.*.min_confidence", .kind = .integer },
+    .{ .name = "targets.*.max_year_distance", .kind = .integer },
+    .{ .name = "targets.*.disable", .kind = .strings },
+    .{ .name = "targets.*.ignore", .kind = .strings },
+};
+
+/// Similarity above which an unknown key is taken for a misspelled one.
+const SUGGESTION_SIMILARITY: f64 = 0.75;
+
+/// The known key most like the unknown key `name`, if any is close.
+fn suggest(arena: std.mem.Allocator, name: []const u8) !?[]const u8 {
+    var best: ?[]const u8 = null;
+    var best_similarity = SUGGESTION_SIMILARITY;
+    for (schema) |key| {
+        const candidate = try instantiate(arena, key.name, name);
+        const similarity = try matcher.levenshteinSimilarity(arena, name, candidate);
+        if (similarity >= best_similarity) {
+            best = candidate;
+            best_similarity = similarity;
+        }
+    }
+    return best;
+}
+
+/// Schema key `pattern` with each `*` replaced by the table number at the
+/// same place in `name`: "policy.rules.*.name" for "policy.rules.2.nmae"
+/// is "policy.rules.2.name".
+fn instantiate(arena: std.mem.Allocator, pattern: []const u8, name: []const u8) ![]const u8 {
+    if (std.mem.indexOfScalar(u8, pattern, '*') == null) return pattern;
+    var result: std.ArrayList(u8) = .empty;
+    var want = std.mem.splitScalar(u8, pattern, '.');
+    var have = std.mem.splitScalar(u8, name, '.');
+    while (want.next()) |part| {
+        const actual = have.next();
+        if (result.items.len > 0) try result.append(arena, '.');
+        if (std.mem.eql(u8, part, "*")) {
+            const number = actual orelse "0";
+            _ = std.fmt.parseInt(usize, number, 10) catch {
+                try result.append(arena, '0');
+                continue;
+            };
+            try result.appendSlice(arena, number);
+        } else {
+            try result.appendSlice(arena, part);
+        }
+    }
+    return result.items;
+}
+
+/// `name` with its table number moved past the tables `base` has of the
+/// same `[[array]]`: "policy.rules.0.name" becomes "policy.rules.3.name".
+fn shiftTable(arena: std.mem.Allocator, name: []const u8, base: *const Document) ![]const u8 {
+    for (base.tables.keys(), base.tables.values()) |table, count| {
+        if (name.len <= table.len or !std.mem.startsWith(u8, name, table) or name[table.len] != '.') continue;
+        const rest = name[table.len + 1 ..];
+        const dot = std.mem.indexOfScalar(u8, rest, '.') orelse continue;
+        const n = std.fmt.parseInt(usize, rest[0..dot], 10) catch continue;
+        return std.fmt.allocPrint(arena, "{s}.{d}{s}", .{ table, n + count, rest[dot..] });
+    }
+    return name;
+}
+
+fn dupeValue(arena: std.mem.Allocator, value: Value) !Value {
+    return switch (value) {
+        .string => |s| .{ .string = try arena.dupe(u8, s) },
+        .integer, .boolean => value,
+        .array => |items| blk: {
+            const copy = try arena.alloc(Value, items.len);
+            for (items, copy) |item, *c| c.* = try dupeValue(arena, item);
+            break :blk .{ .array = copy };
+        },
+    };
+}
+
+/// Settings read from the config file.
+pub const Config = struct {
+    /// Preferred form of venue names in suggested fixes.
+    venue_style: venues.Style = .preserve,
+    /// Write repeated venues as `@string` macros in suggested fixes.
+    string_macros: bool = false,
+    /// Capitalization every title is checked against.
+    title_style: titlecase.Style = .preserve,
+    /// Engine quotes, dashes, and spaces are checked against.
+    engine: punctuation.Engine = .preserve,
+    /// Reference list policies evaluated after matching.
+    policy: policy.Policy = .{},
+    /// Venue and publisher block and allow lists.
+    screening: screening.Screening = .{},
+    /// Candidate matching settings.
+    matching: matcher.MatcherConfig = .{},
+    /// Compare venues as OpenAlex sources or DBLP streams.
+    canonical_venues: bool = false,
+    /// IEEE Xplore API key; IEEE lookups are off without one.
+    ieee_api_key: ?[]const u8 = null,
+    /// Search ACM DL records through Crossref.
+    acm: bool = false,
+    /// Springer Nature Meta API key; Springer lookups are off without one.
+    springer_api_key: ?[]const u8 = null,
+    /// Elsevier API key with Scopus access; Scopus lookups are off without one.
+    scopus_api_key: ?[]const u8 = null,
+    /// GitHub token; GitHub lookups are made without one at a lower rate limit.
+    github_token: ?[]const u8 = null,
+    /// Validator plugins, queried in order after the built-in APIs.
+    external: []const external.Plugin = &.{},
+    /// Entries not expected to be found, skipped until their ignore expires.
+    ignores: []const ignores.Ignore = &.{},
+    /// Webhooks `bibval serve` notifies of new errors.
+    webhooks: []const notify.Sink = &.{},
+    /// Settings for particular input files.
+    targets: []const workspace.Target = &.{},
+    /// Unknown and deprecated keys of the config file.
+    warnings: []const Problem = &.{},
+    /// Hash of the settings in effect: the file's, its profile's, and the
+    /// blocklist's names. Zero without a config file.
+    fingerprint: u64 = 0,
+
+    arena: ?std.heap.ArenaAllocator = null,
+
+    pub fn deinit(self: *Config) void {
+        if (self.arena) |*a| a.deinit();
+    }
+
+    /// Build typed settings from a parsed document, taking ownership of it.
+    pub fn fromDocument(doc: *Document, diag: ?*Diagnostic) !Config {
+        var result = Config{};
+
+        if (try doc.getString("fix.venue_style", diag)) |s| {
+            result.venue_style = std.meta.stringToEnum(venues.Style, s) orelse
+                return fail(diag, 0, "fix.venue_style must be \"preserve\", \"full\", or \"abbreviated\"");
+        }
+        if (try doc.getBool("fix.string_macros", diag)) |b| result.string_macros = b;
+        if (try doc.getString("fix.title_style", diag)) |s| {
+            result.title_style = std.meta.stringToEnum(titlecase.Style, s) orelse
+                return fail(diag, 0, "fix.title_style must be \"preserve\", \"sentence\", or \"title\"");
+        }
+        if (try doc.getString("fix.engine", diag)) |s| {
+            result.engine = std.meta.stringToEnum(punctuation.Engine, s) orelse
+                return fail(diag, 0, "fix.engine must be \"preserve\", \"latex\", or \"unicode\"");
+        }
+
+        if (try doc.getBool("policy.require_year", diag)) |b| result.policy.require_year = b;
+        if (try doc.getInteger("policy.max_age", diag)) |n| {
+            result.policy.max_age = std.math.cast(u32, n) orelse return fail(diag, 0, "policy.max_age must not be negative");
+        }
+        if (try doc.getInteger("policy.max_old_percent", diag)) |n| {
+            if (n < 0 or n > 100) return fail(diag, 0, "policy.max_old_percent must be between 0 and 100");
+            result.policy.max_old_percent = @intCast(n);
+        }
+        if (try doc.getString("policy.old_severity", diag)) |s| {
+            result.policy.old_severity = std.meta.stringToEnum(Severity, s) orelse
+                return fail(diag, 0, "policy.old_severity must be \"error\", \"warning\", or \"info\"");
+        }
+        if (try doc.getBool("policy.require_urldate", diag)) |b| result.policy.require_urldate = b;
+        if (try doc.getString("policy.urldate_format", diag)) |s| {
+            if (std.mem.eql(u8, s, "iso")) {
+                result.policy.urldate_format = policy.ISO_DATE;
+            } else if (std.mem.eql(u8, s, "biblatex")) {
+                result.policy.urldate_format = policy.BIBLATEX_DATE;
+            } else {
+                var re = regex.Regex.compile(doc.arena.allocator(), s) catch |err| switch (err) {
+                    error.InvalidPattern => return fail(diag, 0, "policy.urldate_format must be \"iso\", \"biblatex\", or a regular expression"),
+                    else => |e| return e,
+                };
+                re.deinit();
+                result.policy.urldate_format = s;
+            }
+        }
+        if (try doc.getInteger("policy.max_urldate_age", diag)) |n| {
+            result.policy.max_urldate_age = std.math.cast(u32, n) orelse return fail(diag, 0, "policy.max_urldate_age must not be negative");
+        }
+
+        if (try doc.getInteger("matching.max_year_distance", diag)) |n| {
+            if (n < 0 or n > 100) return fail(diag, 0, "matching.max_year_distance must be between 0 and 100");
+            result.matching.max_year_distance = @intCast(n);
+        }
+        if (try doc.getBool("matching.canonical_venues", diag)) |b| result.canonical_venues = b;
+        if (try doc.getInteger("matching.title_weight", diag)) |n| {
+            if (n < 0 or n > 100) return fail(diag, 0, "matching.title_weight must be between 0 and 100");
+            result.matching.weights.title = @as(f64, @floatFromInt(n)) / 100.0;
+        }
+        if (try doc.getInteger("matching.author_weight", diag)) |n| {
+            if (n < 0 or n > 100) return fail(diag, 0, "matching.author_weight must be between 0 and 100");
+            result.matching.weights.authors = @as(f64, @floatFromInt(n)) / 100.0;
+        }
+        if (result.matching.weights.title + result.matching.weights.authors == 0) {
+            return fail(diag, 0, "matching.title_weight and matching.author_weight must not both be 0");
+        }
+        if (try doc.getInteger("matching.year_penalty_percent", diag)) |n| {
+            if (n < 0 or n > 100) return fail(diag, 0, "matching.year_penalty_percent must be between 0 and 100");
+            result.matching.weights.year_penalty = @as(f64, @floatFromInt(n)) / 100.0;
+        }
+        if (try doc.getInteger("matching.first_author_weight", diag)) |n| {
+            if (n < 1 or n > 10) return fail(diag, 0, "matching.first_author_weight must be between 1 and 10");
+            result.matching.weights.first_author = @floatFromInt(n);
+        }
+        if (try doc.getInteger("matching.last_author_weight", diag)) |n| {
+            if (n < 1 or n > 10) return fail(diag, 0, "matching.last_author_weight must be between 1 and 10");
+            result.matching.weights.last_author = @floatFromInt(n);
+        }
+        if (try doc.getBool("matching.doi_boost", diag)) |b| result.matching.doi_boost = b;
+        if (try doc.getBool("matching.filter_title", diag)) |b| result.matching.filters.title = b;
+        if (try doc.getBool("matching.filter_year", diag)) |b| result.matching.filters.year = b;
+        if (try doc.getBool("matching.filter_authors", diag)) |b| result.matching.filters.authors = b;
+
+        if (try doc.getStringArray("screening.blocked_venues", diag)) |names| result.screening.blocked_venues = names;
+        if (try doc.getStringArray("screening.blocked_publishers", diag)) |names| result.screening.blocked_publishers = names;
+        if (try doc.getStringArray("screening.allowed_venues", diag)) |names| result.screening.allowed_venues = names;
+
+        if (try doc.getString("validators.ieee.api_key", diag)) |key| result.ieee_api_key = key;
+        if (try doc.getBool("validators.acm.enabled", diag)) |b| result.acm = b;
+        if (try doc.getString("validators.springer.api_key", diag)) |key| result.springer_api_key = key;
+        if (try doc.getString("validators.scopus.api_key", diag)) |key| result.scopus_api_key = key;
+        if (try doc.getString("validators.github.token", diag)) |token| result.github_token = token;
+
+        if (try doc.getString("matching.similarity.title", diag)) |name| {
+            result.matching.title_similarity = matcher.metricNamed(name) orelse return fail(diag, 0, metric_error);
+        }
+        if (try doc.getString("matching.similarity.authors", diag)) |name| {
+            result.matching.author_similarity = matcher.metricNamed(name) orelse return fail(diag, 0, metric_error);
+        }
+        var venue_similarity: ?matcher.Similarity = null;
+        if (try doc.getString("matching.similarity.venue", diag)) |name| {
+            venue_similarity = matcher.metricNamed(name) orelse return fail(diag, 0, metric_error);
+        }
+
+        const arena = doc.arena.allocator();
+        result.matching.comparators = try matcher.withSimilarity(arena, result.matching.comparators, result.matching.title_similarity, venue_similarity);
+        result.matching.comparators = try titlecase.withStyle(arena, result.matching.comparators, result.title_style);
+
+        const plugins = try arena.alloc(external.Plugin, doc.tableCount("validators.external"));
+        for (plugins, 0..) |*plugin, i| {
+            const name_key = try std.fmt.allocPrint(arena, "validators.external.{d}.name", .{i});
+            const command_key = try std.fmt.allocPrint(arena, "validators.external.{d}.command", .{i});
+            plugin.name = (try doc.getString(name_key, diag)) orelse return fail(diag, 0, "validators.external needs a name");
+            plugin.command = (try doc.getStringArray(command_key, diag)) orelse return fail(diag, 0, "validators.external needs a command");
+            if (plugin.command.len == 0) return fail(diag, 0, "validators.external command must not be empty");
+        }
+        result.external = plugins;
+
+        const webhooks = try arena.alloc(notify.Sink, doc.tableCount("notify.webhooks"));
+        for (webhooks, 0..) |*sink, i| {
+            const url_key = try std.fmt.allocPrint(arena, "notify.webhooks.{d}.url", .{i});
+            const format_key = try std.fmt.allocPrint(arena, "notify.webhooks.{d}.format", .{i});
+            sink.* = .{ .url = (try doc.getString(url_key, diag)) orelse return fail(diag, 0, "notify.webhooks needs a url") };
+            if (try doc.getString(format_key, diag)) |s| {
+                sink.format = std.meta.stringToEnum(notify.Format, s) orelse
+                    return fail(diag, 0, "notify.webhooks format must be \"json\" or \"slack\"");
+            }
+        }
+        result.webhooks = webhooks;
+
+        const rule_list = try arena.alloc(field_rules.FieldRule, doc.tableCount("policy.rules"));
+        for (rule_list, 0..) |*rule, i| {
+            const prefix = try std.fmt.allocPrint(arena, "policy.rules.{d}.", .{i});
+            rule.* = .{};
+            if (try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "name" }), diag)) |name| rule.name = name;
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "types" }), diag)) |types| rule.types = types;
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "when" }), diag)) |names| rule.when = names;
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "require" }), diag)) |names| rule.require = names;
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "forbid" }), diag)) |names| rule.forbid = names;
+            rule.field = try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "field" }), diag);
+            rule.pattern = try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "pattern" }), diag);
+            rule.message = try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "message" }), diag);
+            if (try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "severity" }), diag)) |severity| {
+                rule.severity = std.meta.stringToEnum(Severity, severity) orelse
+                    return fail(diag, 0, "policy.rules severity must be \"error\", \"warning\", or \"info\"");
+            }
+
+            if ((rule.field == null) != (rule.pattern == null)) return fail(diag, 0, "policy.rules needs both field and pattern");
+            if (rule.require.len == 0 and rule.forbid.len == 0 and rule.field == null) {
+                return fail(diag, 0, "policy.rules needs require, forbid, or field and pattern");
+            }
+            if (rule.pattern) |pattern| {
+                var re = regex.Regex.compile(arena, pattern) catch |err| switch (err) {
+                    error.InvalidPattern => return fail(diag, 0, "policy.rules pattern is not a valid regular expression"),
+                    else => |e| return e,
+                };
+                re.deinit();
+            }
+        }
+        result.policy.field_rules = rule_list;
+
+        const ignore_list = try arena.alloc(ignores.Ignore, doc.tableCount("ignore"));
+        for (ignore_list, 0..) |*ignore, i| {
+            const key_key = try std.fmt.allocPrint(arena, "ignore.{d}.key", .{i});
+            const reason_key = try std.fmt.allocPrint(arena, "ignore.{d}.reason", .{i});
+            const until_key = try std.fmt.allocPrint(arena, "ignore.{d}.until", .{i});
+            ignore.* = .{ .key = (try doc.getString(key_key, diag)) orelse return fail(diag, 0, "ignore needs a key") };
+            if (try doc.getString(reason_key, diag)) |reason| ignore.reason = reason;
+            if (try doc.getString(until_key, diag)) |until| {
+                ignore.until = ignores.Date.parse(until) orelse return fail(diag, 0, "ignore.until must be a date (YYYY-MM-DD)");
+            }
+        }
+        result.ignores = ignore_list;
+
+        const target_list = try arena.alloc(workspace.Target, doc.tableCount("targets"));
+        for (target_list, 0..) |*target, i| {
+            const prefix = try std.fmt.allocPrint(arena, "targets.{d}.", .{i});
+            const files = (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "files" }), diag)) orelse &.{};
+            if (files.len == 0) return fail(diag, 0, "targets needs files");
+            target.* = .{ .files = files };
+            if (try doc.getString(try std.mem.concat(arena, u8, &.{ prefix, "name" }), diag)) |name| target.name = name;
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "validators" }), diag)) |names| {
+                var set = std.EnumSet(ApiSource).initEmpty();
+                for (names) |name| {
+                    set.insert(std.meta.stringToEnum(ApiSource, name) orelse
+                        return fail(diag, 0, "targets validators must name backends, e.g. \"crossref\" or \"open_library\""));
+                }
+                target.validators = set;
+            }
+            if (try doc.getInteger(try std.mem.concat(arena, u8, &.{ prefix, "min_confidence" }), diag)) |n| {
+                if (n < 0 or n > 100) return fail(diag, 0, "targets min_confidence must be a percentage between 0 and 100");
+                target.min_confidence = @as(f64, @floatFromInt(n)) / 100.0;
+            }
+            if (try doc.getInteger(try std.mem.concat(arena, u8, &.{ prefix, "max_year_distance" }), diag)) |n| {
+                if (n < 0 or n > 100) return fail(diag, 0, "targets max_year_distance must be between 0 and 100");
+                target.max_year_distance = @intCast(n);
+            }
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "disable" }), diag)) |names| {
+                for (names) |name| {
+                    target.disabled.insert(rules.Rule.parse(name) orelse return fail(diag, 0, "targets disable must name rules, e.g. \"BV004\" or \"missing-doi\""));
+                }
+            }
+            if (try doc.getStringArray(try std.mem.concat(arena, u8, &.{ prefix, "ignore" }), diag)) |keys| {
+                const reason = if (target.name.len > 0) try std.fmt.allocPrint(arena, "ignored for {s}", .{target.name}) else "";
+                const list = try arena.alloc(ignores.Ignore, keys.len);
+                for (list, keys) |*ignore, key| ignore.* = .{ .key = key, .reason = reason };
+                target.ignores = list;
+            }
+        }
+        result.targets = target_list;
+
+        result.arena = doc.arena;
+        return result;
+    }
+};
+
+/// Load the config at `path` on top of the style profile named by
+/// `profile` or by the file's `profile` key (see `profiles.load`). A
+/// missing file yields the defaults, or the profile's settings.
+pub fn load(allocator: std.mem.Allocator, dir: std.fs.Dir, path: []const u8, profile: ?[]const u8, diag: ?*Diagnostic) !Config {
+    const content = dir.readFileAlloc(allocator, path, 1024 * 1024) catch |err| switch (err) {
+        error.FileNotFound => if (profile == null) return Config{} else try allocator.dupe(u8, ""),
+        else => return err,
+    };
+    defer allocator.free(content);
+
+    var doc = try parse(allocator, content, diag);
+    errdefer doc.deinit();
+    // Type errors are reported as they are read, with the other value errors
+    const warnings = try doc.check();
+
+    const file_profile = try doc.getString("profile", diag);
+    if (profile orelse file_profile) |name| {
+        // A profile path in the file is relative to it, like the blocklist
+        const base_dir = if (profile != null) "." else std.fs.path.dirname(path) orelse ".";
+        var base = profiles.load(allocator, dir, base_dir, name, diag) catch |err| {
+            // Lines of the profile are not lines of this file
+            if (diag) |d| d.line = 0;
+            return err;
+        };
+        defer base.deinit();
+        try doc.inherit(&base);
+    }
+
+    // Blocklist paths are relative to the config directory
+    var blocklist: []const []const u8 = &.{};
+    if (try doc.getString("screening.blocklist", diag)) |list_path| {
+        const arena = doc.arena.allocator();
+        const full_path = if (std.fs.path.isAbsolute(list_path))
+            list_path
+        else
+            try std.fs.path.join(arena, &.{ std.fs.path.dirname(path) orelse ".", list_path });
+        const list = dir.readFileAlloc(arena, full_path, 16 * 1024 * 1024) catch
+            return fail(diag, 0, "cannot read screening.blocklist");
+        blocklist = try screening.parseList(arena, list);
+    }
+    var hasher = std.hash.Wyhash.init(doc.fingerprint());
+    for (blocklist) |name| {
+        hasher.update(name);
+        hasher.update(&[_]u8{0});
+    }
+
+    var config = try Config.fromDocument(&doc, diag);
+    config.fingerprint = hasher.final();
+    config.screening.blocklist = blocklist;
+    config.warnings = warnings;
+    return config;
+}
+
+/// Parse config text into a document.
+pub fn parse(allocator: std.mem.Allocator, content: []const u8, diag: ?*Diagnostic) !Document {
+    var doc = Document{
+        .arena = std.heap.ArenaAllocator.init(allocator),
+        .values = .empty,
+    };
+    errdefer doc.deinit();
+    const arena = doc.arena.allocator();
+
+    var section: []const u8 = "";
+    var line_no: usize = 0;
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        line_no += 1;
+        var line = std.mem.trim(u8, stripComment(raw), " \t\r");
+        if (line.len == 0) continue;
+
+        if (std.mem.startsWith(u8, line, "[[")) {
+            if (!std.mem.endsWith(u8, line, "]]")) return fail(diag, line_no, "unterminated table header");
+            const name = try arena.dupe(u8, std.mem.trim(u8, line[2 .. line.len - 2], " \t"));
+            if (!isKey(name)) return fail(diag, line_no, "invalid table name");
+            const gop = try doc.tables.getOrPut(arena, name);
+            if (!gop.found_existing) gop.value_ptr.* = 0;
+            section = try std.fmt.allocPrint(arena, "{s}.{d}", .{ name, gop.value_ptr.* });
+            gop.value_ptr.* += 1;
+            continue;
+        }
+
+        if (line[0] == '[') {
+            if (line[line.len - 1] != ']') return fail(diag, line_no, "unterminated section header");
+            section = try arena.dupe(u8, std.mem.trim(u8, line[1 .. line.len - 1], " \t"));
+            if (!isKey(section)) return fail(diag, line_no, "invalid section name");
+            continue;
+        }
+
+        const eq = std.mem.indexOfScalar(u8, line, '=') orelse return fail(diag, line_no, "expected key = value");
+        const key = std.mem.trim(u8, line[0..eq], " \t");
+        if (!isKey(key)) return fail(diag, line_no, "invalid key");
+        const key_line = line_no;
+
+        // Arrays may span several lines
+        var text: std.ArrayList(u8) = .empty;
+        try text.appendSlice(arena, std.mem.trim(u8, line[eq + 1 ..], " \t"));
+        while (text.items.len > 0 and text.items[0] == '[' and !bracketsClosed(text.items)) {
+            const next = lines.next() orelse return fail(diag, line_no, "unterminated array");
+            line_no += 1;
+            line = std.mem.trim(u8, stripComment(next), " \t\r");
+            try text.append(arena, ' ');
+            try text.appendSlice(arena, line);
+        }
+
+        var pos: usize = 0;
+        const value = parseValue(arena, text.items, &pos) catch return fail(diag, line_no, "invalid value");
+        if (std.mem.trim(u8, text.items[pos..], " \t").len != 0) return fail(diag, line_no, "unexpected text after value");
+
+        const name = if (section.len > 0) try std.fmt.allocPrint(arena, "{s}.{s}", .{ section, key }) else try arena.dupe(u8, key);
+        const gop = try doc.values.getOrPut(arena, name);
+        if (gop.found_existing) return fail(diag, line_no, "duplicate key");
+        gop.value_ptr.* = value;
+        try doc.lines.put(arena, name, key_line);
+    }
+
+    return doc;
+}
+
+const metric_error = "matching.similarity metrics must be \"jaro-winkler\", \"levenshtein\", \"token-sort\", or \"trigram\"";
+
+pub fn fail(diag: ?*Diagnostic, line: usize, message: []const u8) ConfigError {
+    if (diag) |d| {
+        if (line > 0) d.line = line;
+        d.message = message;
+    }
+    return if (line > 0) ConfigError.InvalidSyntax else ConfigError.InvalidValue;
+}
+
+fn isKey(s: []const u8) bool {
+    if (s.len == 0) return false;
+    for (s) |c| {
+        if (!std.ascii.isAlphanumeric(c) and c != '_' and c != '-' and c != '.') return false;
+    }
+    return true;
+}
+
+/// Remove a trailing `#` comment, ignoring `#` inside strings.
+fn stripComment(line: []const u8) []const u8 {
+    var quote: ?u8 = null;
+    for (line, 0..) |c, i| {
+        if (quote) |q| {
+            if (c == q) quote = null;
+        } else if (c == '"' or c == '\'') {
+            quote = c;
+        } else if (c == '#') {
+            return line[0..i];
+        }
+    }
+    return line;
+}
+
+fn bracketsClosed(s: []const u8) bool {
+    var depth: usize = 0;
+    var quote: ?u8 = null;
+    for (s) |c| {
+        if (quote) |q| {
+            if (c == q) quote = null;
+            continue;
+        }
+        switch (c) {
+            '"', '\'' => quote = c,
+            '[' => depth += 1,
+            ']' => depth -|= 1,
+            else => {},
+        }
+    }
+    return depth == 0;
+}
+
+fn parseValue(arena: std.mem.Allocator, s: []const u8, pos: *usize) (ConfigError || std.mem.Allocator.Error)!Value {
+    skipSpace(s, pos);
+    if (pos.* >= s.len) return error.InvalidValue;
+
+    switch (s[pos.*]) {
+        '"' => return .{ .string = try parseBasicString(arena, s, pos) },
+        '\'' => {
+            const end = std.mem.indexOfScalarPos(u8, s, pos.* + 1, '\'') orelse return error.InvalidValue;
+            const value = try arena.dupe(u8, s[pos.* + 1 .. end]);
+            pos.* = end + 1;
+            return .{ .string = value };
+        },
+        '[' => {
+            pos.* += 1;
+            var items: std.ArrayList(Value) = .empty;
+            while (true) {
+                skipSpace(s, pos);
+                if (pos.* >= s.len) return error.InvalidValue;
+                if (s[pos.*] == ']') break;
+                try items.append(arena, try parseValue(arena, s, pos));
+                skipSpace(s, pos);
+                if (pos.* < s.len and s[pos.*] == ',') pos.* += 1;
+            }
+            pos.* += 1;
+            return .{ .array = try items.toOwnedSlice(arena) };
+        },
+        else => {
+            const start = pos.*;
+            while (pos.* < s.len and (std.ascii.isAlphanumeric(s[pos.*]) or s[pos.*] == '-' or s[pos.*] == '+' or s[pos.*] == '_')) {
+                pos.* += 1;
+            }
+            const word = s[start..pos.*];
+            if (std.mem.eql(u8, word, "true")) return .{ .boolean = true };
+            if (std.mem.eql(u8, word, "false")) return .{ .boolean = false };
+            return .{ .integer = std.fmt.parseInt(i64, word, 10) catch return error.InvalidValue };
+        },
+    }
+}
+
+fn parseBasicString(arena: std.mem.Allocator, s: []const u8, pos: *usize) ![]const u8 {
+    var out: std.ArrayList(u8) = .empty;
+    var i = pos.* + 1;
+    while (i < s.len) : (i += 1) {
+        const c = s[i];
+        if (c == '"') {
+            pos.* = i + 1;
+            return out.toOwnedSlice(arena);
+        }
+        if (c == '\\' and i + 1 < s.len) {
+            i += 1;
+            try out.append(arena, switch (s[i]) {
+                'n' => '\n',
+                't' => '\t',
+                '"' => '"',
+                '\\' => '\\',
+                else => return error.InvalidValue,
+            });
+        } else {
+            try out.append(arena, c);
+        }
+    }
+    return error.InvalidValue;
+}
+
+fn skipSpace(s: []const u8, pos: *usize) void {
+    while (pos.* < s.len and (s[pos.*] == ' ' or s[pos.*] == '\t')) pos.* += 1;
+}
+
+test "parse config" {
+    const allocator = std.testing.allocator;
+
+    var doc = try parse(allocator,
+        \\# bibval settings
+        \\top = 3
+        \\
+        \\[fix]
+        \\venue_style = "abbreviated"  # or "full"
+        \\enabled = true
+        \\names = [
+        \\  "a", 'b#c',
+        \\]
+        \\
+        \\[[validators.external]]
+        \\name = "repo"
+        \\[[validators.external]]
+        \\name = "tracker"
+    , null);
+    defer doc.deinit();
+
+    try std.testing.expectEqual(@as(i64, 3), doc.get("top").?.integer);
+    try std.testing.expectEqualStrings("abbreviated", doc.get("fix.venue_style").?.string);
+    try std.testing.expect(doc.get("fix.enabled").?.boolean);
+    try std.testing.expectEqual(@as(usize, 2), doc.get("fix.names").?.array.len);
+    try std.testing.expectEqualStrings("b#c", doc.get("fix.names").?.array[1].string);
+    try std.testing.expectEqual(@as(usize, 2), doc.tableCount("validators.external"));
+    try std.testing.expectEqualStrings("tracker", doc.get("validators.external.1.name").?.string);
+
+    var diag: Diagnostic = .{};
+    try std.testing.expectError(ConfigError.InvalidSyntax, parse(allocator, "[fix]\nvenue_style\n", &diag));
+    try std.testing.expectEqual(@as(usize, 2), diag.line);
+}
+
+test "settings fingerprints" {
+    const allocator = std.testing.allocator;
+
+    var doc = try parse(allocator, "[fix]\nvenue_style = \"full\"\n", null);
+    defer doc.deinit();
+    var same = try parse(allocator, "[fix]\nvenue_style = \"full\"  # as before\n", null);
+    defer same.deinit();
+    try std.testing.expectEqual(doc.fingerprint(), same.fingerprint());
+
+    // A profile's screening lists count, though the file does not change
+    var profile = try parse(allocator, "[screening]\nblocked_venues = [\"Journal of Everything\"]\n", null);
+    defer profile.deinit();
+    try same.inherit(&profile);
+    try std.testing.expect(doc.fingerprint() != same.fingerprint());
+}
+
+test "unknown keys and type errors" {
+    const allocator = std.testing.allocator;
+
+    var doc = try parse(allocator,
+        \\[fix]
+        \\venue_stlye = "full"
+        \\
+        \\[matching]
+        \\max_year_distance = "3"
+        \\
+        \\[[policy.rules]]
+        \\name = "keywords"
+        \\requrie = ["keywords"]
+        \\
+        \\[colour]
+        \\theme = "dark"
+    , null);
+    defer doc.deinit();
+
+    const problems = try doc.check();
+    try std.testing.expectEqual(@as(usize, 4), problems.len);
+    try std.testing.expectEqual(@as(usize, 2), problems[0].line);
+    try std.testing.expectEqual(Severity.warning, problems[0].severity);
+    try std.testing.expectEqualStrings("unknown key fix.venue_stlye; did you mean fix.venue_style?", problems[0].message);
+    try std.testing.expectEqual(Severity.@"error", problems[1].severity);
+    try std.testing.expectEqualStrings("matching.max_year_distance must be an integer", problems[1].message);
+    try std.testing.expectEqualStrings("unknown key policy.rules.0.requrie; did you mean policy.rules.0.require?", problems[2].message);
+    try std.testing.expectEqualStrings("unknown key colour.theme", problems[3].message);
+
+    var diag: Diagnostic = .{};
+    try std.testing.expectError(ConfigError.InvalidValue, Config.fromDocument(&doc, &diag));
+    try std.testing.expectEqual(@as(usize, 5), diag.line);
+}
+
+test "match weights are percentages" {
+    var doc = try parse(std.testing.allocator,
+        \\[matching]
+        \\title_weight = 50
+        \\year_penalty_percent = 2
+    , null);
+    var config = try Config.fromDocument(&doc, null);
+    defer config.deinit();
+
+    // On the scale of the defaults, so the unset author weight still counts
+    try std.testing.expectEqual(@as(f64, 0.5), config.matching.weights.title);
+    try std.testing.expectEqual(@as(f64, 0.3), config.matching.weights.authors);
+    try std.testing.expectEqual(@as(f64, 0.02), config.matching.weights.year_penalty);
+}
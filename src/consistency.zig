@@ -0,0 +1,604 @@
+//! Consistency of the reference list with itself.
+//!
+//! Entries copied from different sources write the same author as
+//! "Y. LeCun", "Yann LeCun", and "LeCun, Y.". Each is right on its own,
+//! so matching against a database finds nothing, but the bibliography
+//! looks careless. Names that can be the same person are grouped, and the
+//! most complete spelling is suggested for all of them. Venues are grouped
+//! the same way ("NeurIPS", "NIPS", "Advances in Neural Information
+//! Processing Systems"), and `--format patch` rewrites them to one form.
+//! Numeric fields are checked against themselves too: a page range that
+//! ends before it starts, a volume that is not a number, or a year and
+//! page range typed into each other's fields, and quotes and dashes
+//! against the engine of `fix.engine` (see `punctuation`). Nothing here
+//! needs a lookup, so the checks run offline.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const duplicates = @import("duplicates.zig");
+const fix = @import("fix.zig");
+const iso4 = @import("iso4.zig");
+const names = @import("names.zig");
+const policy = @import("policy.zig");
+const punctuation = @import("punctuation.zig");
+const rules = @import("rules.zig");
+const venues = @import("venues.zig");
+const Entry = entry_mod.Entry;
+
+/// Find inconsistently written authors and venues among `entries`. Venue
+/// suggestions follow `venue_style`, punctuation `engine`.
+pub fn check(allocator: std.mem.Allocator, entries: []const Entry, venue_style: venues.Style, engine: punctuation.Engine, disabled: rules.RuleSet) !policy.Evaluation {
+    var result = policy.Evaluation{ .heading = "CONSISTENCY", .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer result.deinit();
+    const arena = result.arena.allocator();
+
+    var violations: std.ArrayList(policy.Violation) = .empty;
+    if (!disabled.contains(.author_variant)) try authorVariants(arena, entries, &violations);
+    if (!disabled.contains(.venue_variant)) try venueVariants(arena, entries, venue_style, &violations);
+    if (!disabled.contains(.numeric_field)) {
+        for (entries) |*e| {
+            var problems: std.ArrayList(NumericProblem) = .empty;
+            try numericProblems(arena, e, &problems);
+            for (problems.items) |problem| {
+                const keys = try arena.alloc([]const u8, 1);
+                keys[0] = try duplicates.label(arena, e);
+                try violations.append(arena, .{
+                    .rule = .numeric_field,
+                    .severity = rules.Rule.numeric_field.defaultSeverity(),
+                    .message = problem.message,
+                    .keys = keys,
+                });
+            }
+        }
+    }
+
+    if (engine != .preserve and !disabled.contains(.punctuation)) {
+        for (entries) |*e| {
+            var changes: std.ArrayList(punctuation.Change) = .empty;
+            try punctuation.changes(arena, e, engine, &changes);
+            for (changes.items) |change| {
+                const keys = try arena.alloc([]const u8, 1);
+                keys[0] = try duplicates.label(arena, e);
+                try violations.append(arena, .{
+                    .rule = .punctuation,
+                    .severity = rules.Rule.punctuation.defaultSeverity(),
+                    .message = try std.fmt.allocPrint(arena, "{s} is not written for {s}; use \"{s}\"", .{ change.field, @tagName(engine), change.converted }),
+                    .keys = keys,
+                });
+            }
+        }
+    }
+
+    result.violations = violations.items;
+    return result;
+}
+
+/// One way an author is written, and the entries (labelled like
+/// duplicates) writing it so.
+const Form = struct {
+    text: []const u8,
+    name: names.Name,
+    keys: std.ArrayList([]const u8) = .empty,
+
+    /// Spelled-out given names, then given names at all: "Yann LeCun"
+    /// beats "Y. LeCun", which beats "LeCun".
+    fn completeness(self: *const Form) usize {
+        var spelled: usize = 0;
+        for (self.name.given) |part| {
+            if (part.len > 1) spelled += 1;
+        }
+        return spelled * 16 + self.name.given.len;
+    }
+
+    fn abbreviated(self: *const Form) bool {
+        for (self.name.given) |part| {
+            if (part.len > 1) return false;
+        }
+        return true;
+    }
+};
+
+fn authorVariants(arena: std.mem.Allocator, entries: []const Entry, violations: *std.ArrayList(policy.Violation)) !void {
+    // Distinct spellings, with whitespace runs collapsed
+    var by_text: std.StringArrayHashMapUnmanaged(Form) = .empty;
+    for (entries) |*e| {
+        for (e.authors) |raw| {
+            const text = try collapse(arena, raw);
+            if (text.len == 0 or std.mem.eql(u8, text, "others")) continue;
+            const gop = try by_text.getOrPut(arena, text);
+            if (!gop.found_existing) {
+                gop.value_ptr.* = .{ .text = text, .name = try names.parse(arena, text) };
+            }
+            const keys = &gop.value_ptr.keys;
+            const entry_label = try duplicates.label(arena, e);
+            if (!contains(keys.items, entry_label)) try keys.append(arena, entry_label);
+        }
+    }
+    const forms = by_text.values();
+
+    // Only forms sharing a surname are compared; a single given name may
+    // be the surname of a name written surname first ("Zhang Wei")
+    var buckets: std.StringArrayHashMapUnmanaged(std.ArrayList(u32)) = .empty;
+    for (forms, 0..) |*form, i| {
+        if (form.name.kind != .person or form.name.family.len == 0) continue;
+        try addToBucket(arena, &buckets, form.name.family, @intCast(i));
+        if (form.name.given.len == 1 and form.name.given[0].len > 1) try addToBucket(arena, &buckets, form.name.given[0], @intCast(i));
+    }
+
+    const parent = try arena.alloc(u32, forms.len);
+    for (parent, 0..) |*p, i| p.* = @intCast(i);
+
+    // Spelled-out names first: "J. Smith" could be John or Jane, and must
+    // not join the two
+    for (buckets.values()) |bucket| {
+        for (bucket.items, 0..) |a, n| {
+            if (forms[a].abbreviated()) continue;
+            for (bucket.items[n + 1 ..]) |b| {
+                if (forms[b].abbreviated()) continue;
+                if (try names.samePerson(arena, &forms[a].name, &forms[b].name)) unite(parent, a, b);
+            }
+        }
+    }
+    // Initials join the one spelled-out person they fit, or other initials
+    for (buckets.values()) |bucket| {
+        for (bucket.items) |a| {
+            if (!forms[a].abbreviated()) continue;
+            var fits: ?u32 = null;
+            var ambiguous = false;
+            for (bucket.items) |b| {
+                if (a == b or forms[b].abbreviated()) continue;
+                if (!try names.samePerson(arena, &forms[a].name, &forms[b].name)) continue;
+                const root = find(parent, b);
+                if (fits != null and fits.? != root) ambiguous = true;
+                fits = root;
+            }
+            if (ambiguous) continue;
+            if (fits) |root| {
+                unite(parent, a, root);
+                continue;
+            }
+            for (bucket.items) |b| {
+                if (a == b or !forms[b].abbreviated()) continue;
+                if (try names.samePerson(arena, &forms[a].name, &forms[b].name)) unite(parent, a, b);
+            }
+        }
+    }
+
+    var groups: std.AutoArrayHashMapUnmanaged(u32, std.ArrayList(u32)) = .empty;
+    for (0..forms.len) |i| {
+        const root = find(parent, @intCast(i));
+        const gop = try groups.getOrPut(arena, root);
+        if (!gop.found_existing) gop.value_ptr.* = .empty;
+        try gop.value_ptr.append(arena, @intCast(i));
+    }
+
+    for (groups.values()) |group| {
+        const members = group.items;
+        if (members.len < 2) continue;
+        // Initials may have joined two people through each other
+        if (!try allSamePerson(arena, forms, members)) continue;
+
+        var best = members[0];
+        for (members[1..]) |m| {
+            const a = forms[m].completeness();
+            const b = forms[best].completeness();
+            if (a > b or (a == b and forms[m].keys.items.len > forms[best].keys.items.len)) best = m;
+        }
+
+        var message: std.Io.Writer.Allocating = .init(arena);
+        const w = &message.writer;
+        try w.print("{s} is written {d} ways (", .{ forms[best].text, members.len });
+        var keys: std.ArrayList([]const u8) = .empty;
+        var listed: usize = 0;
+        for (members) |m| {
+            if (m == best) continue;
+            if (listed > 0) try w.writeAll(", ");
+            try w.print("\"{s}\"", .{forms[m].text});
+            listed += 1;
+            for (forms[m].keys.items) |key| {
+                if (!contains(keys.items, key)) try keys.append(arena, key);
+            }
+        }
+        try w.print("); use \"{s}\"", .{forms[best].text});
+
+        try violations.append(arena, .{
+            .rule = .author_variant,
+            .severity = rules.Rule.author_variant.defaultSeverity(),
+            .message = message.written(),
+            .keys = keys.items,
+        });
+    }
+}
+
+fn venueVariants(arena: std.mem.Allocator, entries: []const Entry, style: venues.Style, violations: *std.ArrayList(policy.Violation)) !void {
+    const canonical = try venueCanonicals(arena, entries, style);
+
+    // The spellings replaced by each suggested one
+    var by_canonical: std.StringArrayHashMapUnmanaged(std.ArrayList([]const u8)) = .empty;
+    var it = canonical.iterator();
+    while (it.next()) |kv| {
+        const gop = try by_canonical.getOrPut(arena, kv.value_ptr.*);
+        if (!gop.found_existing) gop.value_ptr.* = .empty;
+        try gop.value_ptr.append(arena, kv.key_ptr.*);
+    }
+
+    var groups = by_canonical.iterator();
+    while (groups.next()) |group| {
+        const use = group.key_ptr.*;
+        const replaced = group.value_ptr.items;
+        var keys: std.ArrayList([]const u8) = .empty;
+        var used = false;
+        for (entries) |*e| {
+            const venue = try collapse(arena, e.venue orelse continue);
+            if (std.mem.eql(u8, venue, use)) used = true;
+            if (contains(replaced, venue)) try keys.append(arena, try duplicates.label(arena, e));
+        }
+
+        var message: std.Io.Writer.Allocating = .init(arena);
+        const w = &message.writer;
+        try w.print("{s} is written {d} ways (", .{ use, replaced.len + @intFromBool(used) });
+        for (replaced, 0..) |text, i| {
+            if (i > 0) try w.writeAll(", ");
+            try w.print("\"{s}\"", .{text});
+        }
+        try w.print("); use \"{s}\"", .{use});
+
+        try violations.append(arena, .{
+            .rule = .venue_variant,
+            .severity = rules.Rule.venue_variant.defaultSeverity(),
+            .message = message.written(),
+            .keys = keys.items,
+        });
+    }
+}
+
+/// Fixes writing every venue of `entries` in the form `check` suggests.
+pub fn venueFixes(arena: std.mem.Allocator, entries: []const Entry, venue_style: venues.Style, fixes: *std.ArrayList(fix.Fix)) !void {
+    const canonical = try venueCanonicals(arena, entries, venue_style);
+    for (entries) |*e| {
+        const venue = try collapse(arena, e.venue orelse continue);
+        const use = canonical.get(venue) orelse continue;
+        const field = fix.entryFixField(.venue_variant, e) orelse continue;
+        try fixes.append(arena, .{ .key = e.key, .field = field, .value = use });
+    }
+}
+
+/// A numeric field that cannot be right as written.
+const NumericProblem = struct {
+    message: []const u8,
+    /// Values to set; two for swapped fields, none if the right value is
+    /// not known
+    fixes: [2]?fix.Fix = .{ null, null },
+};
+
+fn numericProblems(arena: std.mem.Allocator, e: *const Entry, problems: *std.ArrayList(NumericProblem)) !void {
+    const pages = e.extraField("pages");
+    // Set only when the year is not a number or date
+    const written_year = if (e.year == null) e.extraField("year") else null;
+
+    // "year = {12--19}, pages = {2020}"
+    if (pages != null and written_year != null and yearLike(pages.?) and pageRange(written_year.?) != null) {
+        try problems.append(arena, .{
+            .message = try std.fmt.allocPrint(arena, "year {s} and pages {s} look swapped", .{ written_year.?, pages.? }),
+            .fixes = .{
+                .{ .key = e.key, .field = "year", .value = pages.? },
+                .{ .key = e.key, .field = "pages", .value = written_year.? },
+            },
+        });
+    } else if (pages) |p| {
+        if (pageRange(p)) |range| {
+            // "1234--56" is 1234--1256 written short
+            if (range.first > range.last and range.last_digits >= range.first_digits) {
+                const swapped = try std.fmt.allocPrint(arena, "{d}--{d}", .{ range.last, range.first });
+                try problems.append(arena, .{
+                    .message = try std.fmt.allocPrint(arena, "pages {s} end before they start; use {s}", .{ p, swapped }),
+                    .fixes = .{ .{ .key = e.key, .field = "pages", .value = swapped }, null },
+                });
+            }
+        } else if (e.year != null and yearLike(p) and (std.fmt.parseInt(i32, p, 10) catch 0) == e.year.?) {
+            try problems.append(arena, .{ .message = try std.fmt.allocPrint(arena, "pages {s} is the year of the entry", .{p}) });
+        }
+    }
+
+    // Journals number their volumes and issues
+    if (std.ascii.eqlIgnoreCase(e.entry_type, "article")) {
+        for ([_][]const u8{ "volume", "number" }) |name| {
+            const value = e.extraField(name) orelse continue;
+            if (!isNumbered(value)) {
+                try problems.append(arena, .{ .message = try std.fmt.allocPrint(arena, "{s} {s} is not a number", .{ name, value }) });
+            }
+        }
+    }
+}
+
+/// Fixes for the numeric fields `check` reports, where the right value is
+/// known: swapped page ranges, and years and pages typed into each other.
+pub fn numericFixes(arena: std.mem.Allocator, entries: []const Entry, fixes: *std.ArrayList(fix.Fix)) !void {
+    for (entries) |*e| {
+        var problems: std.ArrayList(NumericProblem) = .empty;
+        try numericProblems(arena, e, &problems);
+        for (problems.items) |problem| {
+            for (problem.fixes) |f| {
+                if (f) |value| try fixes.append(arena, value);
+            }
+        }
+    }
+}
+
+const PageRange = struct {
+    first: u64,
+    last: u64,
+    first_digits: usize,
+    last_digits: usize,
+};
+
+/// The pages of "770--778", "770-778", or "770–778"; null for a single
+/// page, article numbers ("e0123"), and anything else.
+fn pageRange(text: []const u8) ?PageRange {
+    const trimmed = std.mem.trim(u8, text, " ");
+    for ([_][]const u8{ "--", "\u{2013}", "-" }) |dash| {
+        const at = std.mem.indexOf(u8, trimmed, dash) orelse continue;
+        const first = std.mem.trim(u8, trimmed[0..at], " ");
+        const last = std.mem.trim(u8, trimmed[at + dash.len ..], " ");
+        if (!isDigits(first) or !isDigits(last)) return null;
+        return .{
+            .first = std.fmt.parseInt(u64, first, 10) catch return null,
+            .last = std.fmt.parseInt(u64, last, 10) catch return null,
+            .first_digits = first.len,
+            .last_digits = last.len,
+        };
+    }
+    return null;
+}
+
+/// Four digits, like a year: "2020".
+fn yearLike(text: []const u8) bool {
+    return text.len == 4 and isDigits(text);
+}
+
+/// A volume or issue: one with a digit ("12", "3A", "S1", "1-2") or a
+/// Roman numeral ("XIV").
+fn isNumbered(text: []const u8) bool {
+    for (text) |c| {
+        if (std.ascii.isDigit(c)) return true;
+    }
+    if (text.len == 0) return false;
+    for (text) |c| {
+        if (std.mem.indexOfScalar(u8, "IVXLCDMivxlcdm", c) == null) return false;
+    }
+    return true;
+}
+
+fn isDigits(text: []const u8) bool {
+    if (text.len == 0) return false;
+    for (text) |c| {
+        if (!std.ascii.isDigit(c)) return false;
+    }
+    return true;
+}
+
+/// One written form of a venue, and how many entries use it.
+const VenueForm = struct {
+    text: []const u8,
+    uses: usize = 0,
+    /// The dictionary venue it names exactly, if any.
+    known: ?*const venues.Venue,
+};
+
+/// Map from each venue spelling that should change to the one to use.
+/// Spellings naming the same dictionary venue are one venue, as are a
+/// name and its ISO 4 abbreviation; proceedings of different years
+/// ("Proceedings of the 36th ...") are left as they are.
+fn venueCanonicals(arena: std.mem.Allocator, entries: []const Entry, style: venues.Style) !std.StringArrayHashMapUnmanaged([]const u8) {
+    var by_text: std.StringArrayHashMapUnmanaged(VenueForm) = .empty;
+    for (entries) |*e| {
+        const text = try collapse(arena, e.venue orelse continue);
+        if (text.len == 0) continue;
+        const gop = try by_text.getOrPut(arena, text);
+        if (!gop.found_existing) gop.value_ptr.* = .{ .text = text, .known = venues.lookup(text) };
+        gop.value_ptr.uses += 1;
+    }
+    const forms = by_text.values();
+
+    const parent = try arena.alloc(u32, forms.len);
+    for (parent, 0..) |*p, i| p.* = @intCast(i);
+    var buf_a: [256]u8 = undefined;
+    var buf_b: [256]u8 = undefined;
+    for (forms, 0..) |*a, i| {
+        for (forms[i + 1 ..], i + 1..) |*b, j| {
+            const same = if (a.known != null or b.known != null)
+                a.known == b.known
+            else if (std.mem.eql(u8, venues.normalize(a.text, &buf_a), venues.normalize(b.text, &buf_b)))
+                true
+            else if (venues.looksAbbreviated(a.text) != venues.looksAbbreviated(b.text))
+                try iso4.matchesTitle(arena, if (venues.looksAbbreviated(a.text)) a.text else b.text, if (venues.looksAbbreviated(a.text)) b.text else a.text)
+            else
+                false;
+            if (same) unite(parent, @intCast(i), @intCast(j));
+        }
+    }
+
+    var groups: std.AutoArrayHashMapUnmanaged(u32, std.ArrayList(u32)) = .empty;
+    for (0..forms.len) |i| {
+        const gop = try groups.getOrPut(arena, find(parent, @intCast(i)));
+        if (!gop.found_existing) gop.value_ptr.* = .empty;
+        try gop.value_ptr.append(arena, @intCast(i));
+    }
+
+    var canonical: std.StringArrayHashMapUnmanaged([]const u8) = .empty;
+    for (groups.values()) |group| {
+        const members = group.items;
+        // A single spelling is consistent, whatever its style
+        if (members.len < 2) continue;
+        const use = preferredVenue(forms, members, style);
+        for (members) |m| {
+            if (!std.mem.eql(u8, forms[m].text, use)) try canonical.put(arena, forms[m].text, use);
+        }
+    }
+    return canonical;
+}
+
+/// The dictionary form in `style`, or the most used spelling (in `style`
+/// if any is).
+fn preferredVenue(forms: []const VenueForm, members: []const u32, style: venues.Style) []const u8 {
+    if (forms[members[0]].known) |known| {
+        if (style != .preserve) return known.preferred(style, null);
+    }
+    var best = members[0];
+    for (members[1..]) |m| {
+        const fits = fitsStyle(forms[m].text, style);
+        const best_fits = fitsStyle(forms[best].text, style);
+        if ((fits and !best_fits) or (fits == best_fits and forms[m].uses > forms[best].uses)) best = m;
+    }
+    return forms[best].text;
+}
+
+fn fitsStyle(text: []const u8, style: venues.Style) bool {
+    return switch (style) {
+        .preserve => true,
+        .full => !venues.looksAbbreviated(text),
+        .abbreviated => venues.looksAbbreviated(text),
+    };
+}
+
+fn addToBucket(arena: std.mem.Allocator, buckets: *std.StringArrayHashMapUnmanaged(std.ArrayList(u32)), key: []const u8, form: u32) !void {
+    const gop = try buckets.getOrPut(arena, key);
+    if (!gop.found_existing) gop.value_ptr.* = .empty;
+    try gop.value_ptr.append(arena, form);
+}
+
+fn allSamePerson(arena: std.mem.Allocator, forms: []const Form, members: []const u32) !bool {
+    for (members, 0..) |a, n| {
+        for (members[n + 1 ..]) |b| {
+            if (!try names.samePerson(arena, &forms[a].name, &forms[b].name)) return false;
+        }
+    }
+    return true;
+}
+
+fn collapse(arena: std.mem.Allocator, raw: []const u8) ![]const u8 {
+    var out: std.ArrayList(u8) = .empty;
+    var words = std.mem.tokenizeAny(u8, raw, " \t\r\n");
+    while (words.next()) |word| {
+        if (out.items.len > 0) try out.append(arena, ' ');
+        try out.appendSlice(arena, word);
+    }
+    return out.items;
+}
+
+fn contains(keys: []const []const u8, key: []const u8) bool {
+    for (keys) |k| {
+        if (std.mem.eql(u8, k, key)) return true;
+    }
+    return false;
+}
+
+fn find(parent: []u32, i: u32) u32 {
+    var root = i;
+    while (parent[root] != root) root = parent[root];
+    return root;
+}
+
+fn unite(parent: []u32, a: u32, b: u32) void {
+    const ra = find(parent, a);
+    const rb = find(parent, b);
+    if (ra < rb) parent[rb] = ra else parent[ra] = rb;
+}
+
+test "author names written differently" {
+    const entries = [_]Entry{
+        .{ .key = "lecun1998", .entry_type = "article", .authors = &.{ "Y. LeCun", "L. Bottou" } },
+        .{ .key = "lecun2015", .entry_type = "article", .authors = &.{ "Yann LeCun", "Yoshua Bengio", "Geoffrey Hinton" } },
+        .{ .key = "lecun1989", .entry_type = "article", .authors = &.{ "LeCun, Y.", "Bengio, Yoshua" } },
+        .{ .key = "smith2020", .entry_type = "article", .authors = &.{ "John Smith", "J. Smith" } },
+        .{ .key = "smith2021", .entry_type = "article", .authors = &.{"Jane Smith"} },
+    };
+    var result = try check(std.testing.allocator, &entries, .preserve, .preserve, .initEmpty());
+    defer result.deinit();
+
+    // "J. Smith" could be John or Jane, so only LeCun and Bengio are reported
+    try std.testing.expectEqual(@as(usize, 2), result.violations.len);
+    try std.testing.expectEqualStrings("Yann LeCun is written 3 ways (\"Y. LeCun\", \"LeCun, Y.\"); use \"Yann LeCun\"", result.violations[0].message);
+    try std.testing.expectEqual(@as(usize, 2), result.violations[0].keys.len);
+    try std.testing.expectEqualStrings("lecun1998", result.violations[0].keys[0]);
+    try std.testing.expectEqualStrings("Yoshua Bengio is written 2 ways (\"Bengio, Yoshua\"); use \"Yoshua Bengio\"", result.violations[1].message);
+}
+
+test "numeric fields that cannot be right" {
+    const entries = [_]Entry{
+        .{ .key = "backwards", .entry_type = "article", .year = 2016, .extra = &.{.{ .name = "pages", .value = "778--770" }} },
+        .{ .key = "short", .entry_type = "article", .year = 2016, .extra = &.{.{ .name = "pages", .value = "1234--56" }} },
+        .{ .key = "swapped", .entry_type = "article", .extra = &.{ .{ .name = "year", .value = "12--19" }, .{ .name = "pages", .value = "2020" } } },
+        .{ .key = "dated", .entry_type = "inproceedings", .year = 2019, .extra = &.{.{ .name = "pages", .value = "2019" }} },
+        .{ .key = "issue", .entry_type = "article", .year = 2021, .extra = &.{ .{ .name = "volume", .value = "XIV" }, .{ .name = "number", .value = "Spring" } } },
+    };
+    var result = try check(std.testing.allocator, &entries, .preserve, .preserve, .initEmpty());
+    defer result.deinit();
+
+    try std.testing.expectEqual(@as(usize, 4), result.violations.len);
+    try std.testing.expectEqualStrings("pages 778--770 end before they start; use 770--778", result.violations[0].message);
+    try std.testing.expectEqualStrings("year 12--19 and pages 2020 look swapped", result.violations[1].message);
+    try std.testing.expectEqualStrings("pages 2019 is the year of the entry", result.violations[2].message);
+    try std.testing.expectEqualStrings("number Spring is not a number", result.violations[3].message);
+    try std.testing.expectEqualStrings("issue", result.violations[3].keys[0]);
+
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    var fixes: std.ArrayList(fix.Fix) = .empty;
+    try numericFixes(arena.allocator(), &entries, &fixes);
+    try std.testing.expectEqual(@as(usize, 3), fixes.items.len);
+    try std.testing.expectEqualStrings("770--778", fixes.items[0].value);
+    try std.testing.expectEqualStrings("year", fixes.items[1].field);
+    try std.testing.expectEqualStrings("2020", fixes.items[1].value);
+    try std.testing.expectEqualStrings("12--19", fixes.items[2].value);
+}
+
+test "venue names written differently" {
+    const entries = [_]Entry{
+        .{ .key = "a", .entry_type = "inproceedings", .venue = "NeurIPS" },
+        .{ .key = "b", .entry_type = "inproceedings", .venue = "Advances in Neural Information Processing Systems" },
+        .{ .key = "c", .entry_type = "inproceedings", .venue = "NIPS" },
+        .{ .key = "d", .entry_type = "inproceedings", .venue = "NeurIPS" },
+        .{ .key = "e", .entry_type = "article", .venue = "Journal of Applied Widgetry" },
+        .{ .key = "f", .entry_type = "article", .venue = "J. Appl. Widgetry" },
+        .{ .key = "g", .entry_type = "inproceedings", .venue = "Proceedings of the 36th International Conference on Machine Learning" },
+        .{ .key = "h", .entry_type = "inproceedings", .venue = "ICML" },
+    };
+    var result = try check(std.testing.allocator, &entries, .full, .preserve, .initEmpty());
+    defer result.deinit();
+
+    try std.testing.expectEqual(@as(usize, 2), result.violations.len);
+    try std.testing.expectEqualStrings("Advances in Neural Information Processing Systems is written 3 ways (\"NeurIPS\", \"NIPS\"); use \"Advances in Neural Information Processing Systems\"", result.violations[0].message);
+    try std.testing.expectEqual(@as(usize, 3), result.violations[0].keys.len);
+    try std.testing.expectEqualStrings("f", result.violations[1].keys[0]);
+
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    var fixes: std.ArrayList(fix.Fix) = .empty;
+    try venueFixes(arena.allocator(), &entries, .preserve, &fixes);
+    // NeurIPS is the most used form; ICML and its proceedings stay apart
+    try std.testing.expectEqual(@as(usize, 3), fixes.items.len);
+    try std.testing.expectEqualStrings("NeurIPS", fixes.items[0].value);
+    try std.testing.expectEqualStrings("booktitle", fixes.items[0].field);
+    try std.testing.expectEqualStrings("f", fixes.items[2].key);
+    try std.testing.expectEqualStrings("journal", fixes.items[2].field);
+}
+
+test "venue fixes go to the field the venue is in" {
+    const entries = [_]Entry{
+        .{ .key = "a", .entry_type = "article", .venue = "Journal of Applied Widgetry" },
+        .{ .key = "b", .entry_type = "article", .venue = "Journal of Applied Widgetry" },
+        .{ .key = "e", .entry_type = "article", .venue = "Journal of Applied Widgetry" },
+        .{ .key = "c", .entry_type = "misc", .venue = "J. Appl. Widgetry", .venue_field = "journal" },
+        .{ .key = "d", .entry_type = "book", .venue = "J. Appl. Widgetry" },
+    };
+
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    var fixes: std.ArrayList(fix.Fix) = .empty;
+    try venueFixes(arena.allocator(), &entries, .preserve, &fixes);
+    // A @book has no venue field to write
+    try std.testing.expectEqual(@as(usize, 1), fixes.items.len);
+    try std.testing.expectEqualStrings("c", fixes.items[0].key);
+    try std.testing.expectEqualStrings("journal", fixes.items[0].field);
+}
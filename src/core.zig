@@ -0,0 +1,32 @@
+//! bibval core - parsing, matching and fix generation
+//!
+//! Everything here works on strings in memory, without network access,
+//! files or threads, so it compiles for wasm32-freestanding as well (see
+//! wasm.zig). Lookups against the APIs live in the full `bibval` module,
+//! which re-exports these namespaces. Build against one module or the
+//! other: both contain the same source files.
+
+pub const bibtex = @import("bibtex.zig");
+pub const encoding = @import("encoding.zig");
+pub const ris = @import("ris.zig");
+pub const csl = @import("csl.zig");
+pub const freetext = @import("freetext.zig");
+pub const input = @import("input.zig");
+pub const entry = @import("entry.zig");
+pub const matcher = @import("matcher.zig");
+pub const blocking = @import("blocking.zig");
+pub const duplicates = @import("duplicates.zig");
+pub const rules = @import("rules.zig");
+pub const report = @import("report.zig");
+pub const policy = @import("policy.zig");
+pub const screening = @import("screening.zig");
+pub const fix = @import("fix.zig");
+pub const venues = @import("venues.zig");
+pub const iso4 = @import("iso4.zig");
+pub const snapshot = @import("snapshot.zig");
+
+pub const Entry = entry.Entry;
+pub const Discrepancy = entry.Discrepancy;
+pub const ValidationResult = entry.ValidationResult;
+pub const ApiSource = entry.ApiSource;
+pub const Severity = entry.Severity;
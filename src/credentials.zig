@@ -0,0 +1,192 @@
+//! API keys of the services that need one.
+//!
+//! A key is taken from the environment (`IEEE_API_KEY`), then the config
+//! (`validators.ieee.api_key`), then the OS keyring, where
+//! `bibval auth set ieee` stores it: the macOS keychain through `security`,
+//! or the Secret Service (GNOME Keyring, KWallet) through `secret-tool`
+//! elsewhere. Keys are never printed: Scopus takes its key in a header,
+//! and request traces show the URLs of IEEE and Springer with the key's
+//! value cut out (see `http.Client.getWithHeaders`).
+
+const std = @import("std");
+const builtin = @import("builtin");
+const config_mod = @import("config.zig");
+
+/// Keyring service the keys are stored under, one account per service.
+const KEYRING_SERVICE = "bibval";
+
+pub const Service = enum {
+    ieee,
+    springer,
+    scopus,
+    zotero,
+    github,
+
+    pub fn name(self: Service) []const u8 {
+        return switch (self) {
+            .ieee => "IEEE Xplore",
+            .springer => "Springer Nature",
+            .scopus => "Scopus",
+            .zotero => "Zotero",
+            .github => "GitHub",
+        };
+    }
+
+    pub fn envVar(self: Service) []const u8 {
+        return switch (self) {
+            .ieee => "IEEE_API_KEY",
+            .springer => "SPRINGER_API_KEY",
+            .scopus => "SCOPUS_API_KEY",
+            .zotero => "ZOTERO_API_KEY",
+            .github => "GITHUB_TOKEN",
+        };
+    }
+
+    /// Config key holding the key, for services the config can set.
+    pub fn configKey(self: Service) ?[]const u8 {
+        return switch (self) {
+            .ieee => "validators.ieee.api_key",
+            .springer => "validators.springer.api_key",
+            .scopus => "validators.scopus.api_key",
+            .zotero => null,
+            .github => "validators.github.token",
+        };
+    }
+};
+
+pub const Source = enum { environment, config, keyring };
+
+pub const Credential = struct {
+    key: []u8,
+    source: Source,
+};
+
+/// The key of `service` and where it came from: the environment, else
+/// `configured` (the config's key), else the keyring. The caller owns the
+/// key.
+pub fn find(allocator: std.mem.Allocator, service: Service, configured: ?[]const u8) !?Credential {
+    if (std.process.getEnvVarOwned(allocator, service.envVar())) |key| {
+        if (key.len > 0) return .{ .key = key, .source = .environment };
+        allocator.free(key);
+    } else |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => {},
+    }
+    if (configured) |key| return .{ .key = try allocator.dupe(u8, key), .source = .config };
+    const key = (try keyringGet(allocator, service)) orelse return null;
+    return .{ .key = key, .source = .keyring };
+}
+
+/// The key the config file gives for `service`.
+pub fn configured(config: *const config_mod.Config, service: Service) ?[]const u8 {
+    return switch (service) {
+        .ieee => config.ieee_api_key,
+        .springer => config.springer_api_key,
+        .scopus => config.scopus_api_key,
+        .zotero => null,
+        .github => config.github_token,
+    };
+}
+
+/// Set the API keys of `config` for the enabled `services` from the
+/// environment, the config itself, and the keyring, in that order. The
+/// keyring is not asked for the keys of other services.
+pub fn resolve(allocator: std.mem.Allocator, config: *config_mod.Config, services: std.EnumSet(Service)) !void {
+    if (config.arena == null) config.arena = std.heap.ArenaAllocator.init(allocator);
+    const arena = config.arena.?.allocator();
+    var it = services.iterator();
+    while (it.next()) |service| {
+        const field = switch (service) {
+            .ieee => &config.ieee_api_key,
+            .springer => &config.springer_api_key,
+            .scopus => &config.scopus_api_key,
+            .github => &config.github_token,
+            // Not a setting of the config
+            .zotero => continue,
+        };
+        field.* = try keyOf(arena, config, service);
+    }
+}
+
+fn keyOf(arena: std.mem.Allocator, config: *const config_mod.Config, service: Service) !?[]const u8 {
+    const credential = (try find(arena, service, configured(config, service))) orelse return null;
+    return credential.key;
+}
+
+/// The key of `service` in the keyring, or null if it has none or there is
+/// no keyring.
+pub fn keyringGet(allocator: std.mem.Allocator, service: Service) !?[]u8 {
+    const argv: []const []const u8 = switch (builtin.os.tag) {
+        .macos => &.{ "security", "find-generic-password", "-s", KEYRING_SERVICE, "-a", @tagName(service), "-w" },
+        .windows => return null,
+        else => &.{ "secret-tool", "lookup", "service", KEYRING_SERVICE, "account", @tagName(service) },
+    };
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = argv,
+        .max_output_bytes = 64 * 1024,
+    }) catch |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => return null,
+    };
+    allocator.free(result.stderr);
+    defer allocator.free(result.stdout);
+    const key = std.mem.trim(u8, result.stdout, " \t\r\n");
+    if (result.term != .Exited or result.term.Exited != 0 or key.len == 0) return null;
+    return try allocator.dupe(u8, key);
+}
+
+pub const KeyringError = error{KeyringUnavailable};
+
+/// Store `key` for `service` in the keyring, replacing any it has.
+pub fn keyringSet(allocator: std.mem.Allocator, service: Service, key: []const u8) !void {
+    switch (builtin.os.tag) {
+        // With `-w` last, `security` prompts for the key twice instead of
+        // taking it as an argument, which other processes could read
+        .macos => {
+            const input = try std.fmt.allocPrint(allocator, "{s}\n{s}\n", .{ key, key });
+            defer allocator.free(input);
+            try runKeyring(allocator, &.{ "security", "add-generic-password", "-U", "-s", KEYRING_SERVICE, "-a", @tagName(service), "-w" }, input);
+        },
+        .windows => return KeyringError.KeyringUnavailable,
+        else => {
+            const label = try std.fmt.allocPrint(allocator, "bibval {s} API key", .{service.name()});
+            defer allocator.free(label);
+            try runKeyring(allocator, &.{ "secret-tool", "store", "--label", label, "service", KEYRING_SERVICE, "account", @tagName(service) }, key);
+        },
+    }
+}
+
+fn runKeyring(allocator: std.mem.Allocator, argv: []const []const u8, input: ?[]const u8) !void {
+    var child = std.process.Child.init(argv, allocator);
+    child.stdin_behavior = if (input != null) .Pipe else .Ignore;
+    child.stdout_behavior = .Ignore;
+    child.stderr_behavior = .Inherit;
+    child.spawn() catch return KeyringError.KeyringUnavailable;
+    errdefer _ = child.kill() catch {};
+
+    if (input) |text| {
+        child.stdin.?.writeAll(text) catch return KeyringError.KeyringUnavailable;
+        child.stdin.?.close();
+        child.stdin = null;
+    }
+    const term = child.wait() catch return KeyringError.KeyringUnavailable;
+    if (term != .Exited or term.Exited != 0) return KeyringError.KeyringUnavailable;
+}
+
+test "keys from the environment and the config" {
+    const allocator = std.testing.allocator;
+
+    try std.testing.expectEqualStrings("SCOPUS_API_KEY", Service.scopus.envVar());
+    try std.testing.expectEqual(@as(?[]const u8, null), Service.zotero.configKey());
+    for (std.enums.values(Service)) |service| {
+        const key = service.configKey() orelse continue;
+        try std.testing.expect(std.mem.startsWith(u8, key, "validators."));
+    }
+
+    if (std.process.hasEnvVarConstant("SCOPUS_API_KEY")) return error.SkipZigTest;
+    const credential = (try find(allocator, .scopus, "from-config")).?;
+    defer allocator.free(credential.key);
+    try std.testing.expectEqual(Source.config, credential.source);
+    try std.testing.expectEqualStrings("from-config", credential.key);
+}
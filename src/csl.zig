@@ -0,0 +1,178 @@
+//! CSL-JSON parser.
+//!
+//! Parses CSL-JSON items (as exported by Zotero, Mendeley and citeproc
+//! tools) into entries. Accepts an array of items or a single item.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+pub const CslError = error{InvalidCsl};
+
+/// Parse a CSL-JSON file.
+pub fn parseFile(allocator: std.mem.Allocator, path: []const u8) ![]Entry {
+    const file = try std.fs.cwd().openFile(path, .{});
+    defer file.close();
+
+    const content = try file.readToEndAlloc(allocator, 10 * 1024 * 1024);
+    defer allocator.free(content);
+
+    return try parseString(allocator, content);
+}
+
+/// Parse CSL-JSON items.
+pub fn parseString(allocator: std.mem.Allocator, content: []const u8) ![]Entry {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, content, .{}) catch return CslError.InvalidCsl;
+    defer parsed.deinit();
+
+    const items: []const std.json.Value = switch (parsed.value) {
+        .array => |array| array.items,
+        .object => (&parsed.value)[0..1],
+        else => return CslError.InvalidCsl,
+    };
+
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |*e| e.deinit();
+        entries.deinit(allocator);
+    }
+
+    for (items) |item| {
+        if (item != .object) continue;
+        var e = try itemToEntry(allocator, item.object, entries.items);
+        errdefer e.deinit();
+        try entries.append(allocator, e);
+    }
+
+    return entries.toOwnedSlice(allocator);
+}
+
+fn itemToEntry(allocator: std.mem.Allocator, item: std.json.ObjectMap, existing: []const Entry) !Entry {
+    var result = Entry{
+        .key = "",
+        .entry_type = try allocator.dupe(u8, entryType(getString(item, "type") orelse "")),
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+
+    if (getString(item, "title")) |title| result.title = try allocator.dupe(u8, title);
+    if (getString(item, "DOI")) |doi| result.doi = try allocator.dupe(u8, doi);
+    if (getString(item, "URL")) |url| result.url = try allocator.dupe(u8, url);
+    if (getString(item, "ISBN")) |value| {
+        var buf: [13]u8 = undefined;
+        if (entry_mod.normalizeIsbn(&buf, value)) |isbn| result.isbn = try allocator.dupe(u8, isbn);
+    }
+    if (getString(item, "publisher")) |publisher| result.publisher = try allocator.dupe(u8, publisher);
+
+    // container-title is a string, or an array in some exports
+    if (item.get("container-title")) |venue_val| {
+        const venue = switch (venue_val) {
+            .string => |s| s,
+            .array => |a| if (a.items.len > 0 and a.items[0] == .string) a.items[0].string else null,
+            else => null,
+        };
+        if (venue) |v| {
+            if (v.len > 0) result.venue = try allocator.dupe(u8, v);
+        }
+    }
+
+    // "issued": {"date-parts": [[2020, 5, 1]]}; the year may be a string
+    if (item.get("issued")) |issued| {
+        if (issued == .object) {
+            if (issued.object.get("date-parts")) |parts| {
+                if (parts == .array and parts.array.items.len > 0) {
+                    const first = parts.array.items[0];
+                    if (first == .array and first.array.items.len > 0) {
+                        result.year = switch (first.array.items[0]) {
+                            .integer => |y| @intCast(y),
+                            .string => |s| std.fmt.parseInt(i32, s, 10) catch null,
+                            else => null,
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    if (item.get("author")) |author_val| {
+        if (author_val == .array) {
+            var authors: std.ArrayList([]const u8) = .empty;
+            defer authors.deinit(allocator);
+            errdefer for (authors.items) |a| allocator.free(a);
+            for (author_val.array.items) |author| {
+                if (author != .object) continue;
+                if (getString(author.object, "literal")) |literal| {
+                    try authors.append(allocator, try allocator.dupe(u8, literal));
+                    continue;
+                }
+                const family = getString(author.object, "family") orelse continue;
+                const name = if (getString(author.object, "given")) |given|
+                    try std.fmt.allocPrint(allocator, "{s}, {s}", .{ family, given })
+                else
+                    try allocator.dupe(u8, family);
+                try authors.append(allocator, name);
+            }
+            result.authors = try authors.toOwnedSlice(allocator);
+        }
+    }
+
+    result.key = if (getString(item, "id")) |id|
+        try allocator.dupe(u8, id)
+    else
+        try entry_mod.generateKey(allocator, &result, existing);
+
+    return result;
+}
+
+fn getString(obj: std.json.ObjectMap, name: []const u8) ?[]const u8 {
+    const value = obj.get(name) orelse return null;
+    return if (value == .string) value.string else null;
+}
+
+/// BibTeX entry type for a CSL item type.
+fn entryType(ty: []const u8) []const u8 {
+    const types = [_][2][]const u8{
+        .{ "article-journal", "article" },
+        .{ "article-magazine", "article" },
+        .{ "article", "article" },
+        .{ "paper-conference", "inproceedings" },
+        .{ "book", "book" },
+        .{ "chapter", "incollection" },
+        .{ "thesis", "phdthesis" },
+        .{ "report", "techreport" },
+    };
+    for (types) |t| {
+        if (std.mem.eql(u8, ty, t[0])) return t[1];
+    }
+    return "misc";
+}
+
+test "parse CSL-JSON" {
+    const allocator = std.testing.allocator;
+
+    const entries = try parseString(allocator,
+        \\[{"id": "vaswani2017attention", "type": "paper-conference",
+        \\  "title": "Attention is all you need",
+        \\  "author": [{"family": "Vaswani", "given": "Ashish"}, {"literal": "Google Brain"}],
+        \\  "container-title": "Advances in Neural Information Processing Systems",
+        \\  "issued": {"date-parts": [[2017]]}},
+        \\ {"type": "article-journal", "title": "Deep learning",
+        \\  "author": [{"family": "LeCun", "given": "Yann"}],
+        \\  "container-title": ["Nature"], "DOI": "10.1038/nature14539",
+        \\  "issued": {"date-parts": [["2015", 5]]}}]
+    );
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 2), entries.len);
+    try std.testing.expectEqualStrings("vaswani2017attention", entries[0].key);
+    try std.testing.expectEqualStrings("inproceedings", entries[0].entry_type);
+    try std.testing.expectEqualStrings("Vaswani, Ashish", entries[0].authors[0]);
+    try std.testing.expectEqualStrings("Google Brain", entries[0].authors[1]);
+    try std.testing.expectEqual(@as(i32, 2017), entries[0].year.?);
+    try std.testing.expectEqualStrings("lecun2015", entries[1].key);
+    try std.testing.expectEqualStrings("Nature", entries[1].venue.?);
+    try std.testing.expectEqual(@as(i32, 2015), entries[1].year.?);
+}
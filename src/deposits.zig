@@ -0,0 +1,327 @@
+//! Validators for datasets and software deposited on Zenodo and Figshare.
+//!
+//! Both repositories mint a DOI for every version of a deposit and one
+//! "concept" DOI for all of them, which resolves to the latest version.
+//! Their DOIs are registered with DataCite, not CrossRef, so deposits are
+//! looked up on the repository's own API. The records carry the deposit's
+//! `version` and `concept_doi` as extra fields, where the DOI granularity
+//! check compares them with the version the entry cites.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const entry_mod = @import("entry.zig");
+const validators = @import("validators.zig");
+const github = @import("github.zig");
+const Entry = entry_mod.Entry;
+const ValidatorError = validators.ValidatorError;
+
+/// A deposit an entry cites, by DOI or URL.
+pub const Deposit = union(enum) {
+    /// Zenodo record ID, of one version or of the concept
+    zenodo: u64,
+    figshare: Article,
+};
+
+/// A Figshare article and, unless the concept is cited, its version.
+pub const Article = struct {
+    id: u64,
+    version: ?u32 = null,
+};
+
+const ZENODO_PREFIX = "10.5281/zenodo.";
+const FIGSHARE_PREFIX = "10.6084/m9.figshare.";
+
+/// The deposit an entry cites: a Zenodo or Figshare DOI, or a record URL
+/// on zenodo.org or figshare.com.
+pub fn depositFor(e: *const Entry) ?Deposit {
+    if (e.doi) |doi| {
+        if (std.ascii.startsWithIgnoreCase(doi, ZENODO_PREFIX)) {
+            const id = std.fmt.parseInt(u64, doi[ZENODO_PREFIX.len..], 10) catch return null;
+            return .{ .zenodo = id };
+        }
+        if (std.ascii.startsWithIgnoreCase(doi, FIGSHARE_PREFIX)) {
+            return .{ .figshare = figshareDoi(doi[FIGSHARE_PREFIX.len..]) orelse return null };
+        }
+        return null;
+    }
+
+    const url = e.url orelse return null;
+    if (std.mem.indexOf(u8, url, "zenodo.org/record")) |start| {
+        // "zenodo.org/record/123" and "zenodo.org/records/123"
+        var segments = std.mem.tokenizeScalar(u8, url[start..], '/');
+        _ = segments.next();
+        _ = segments.next();
+        const id = segments.next() orelse return null;
+        return .{ .zenodo = std.fmt.parseInt(u64, std.mem.sliceTo(id, '?'), 10) catch return null };
+    }
+    if (std.mem.indexOf(u8, url, "figshare.com/articles/")) |start| {
+        return .{ .figshare = figshareUrl(url[start + "figshare.com/articles/".len ..]) orelse return null };
+    }
+    return null;
+}
+
+/// "123" or "123.v2" after the Figshare DOI prefix.
+fn figshareDoi(suffix: []const u8) ?Article {
+    const dot = std.mem.indexOf(u8, suffix, ".v");
+    const id = std.fmt.parseInt(u64, suffix[0 .. dot orelse suffix.len], 10) catch return null;
+    const version = if (dot) |d| std.fmt.parseInt(u32, suffix[d + 2 ..], 10) catch return null else null;
+    return .{ .id = id, .version = version };
+}
+
+/// "dataset/Some_title/123" or "dataset/Some_title/123/2": the last
+/// numeric segment is the article, or its version if the one before is
+/// numeric too.
+fn figshareUrl(path: []const u8) ?Article {
+    var numbers: [2]?u64 = .{ null, null };
+    var segments = std.mem.tokenizeScalar(u8, std.mem.sliceTo(path, '?'), '/');
+    while (segments.next()) |segment| {
+        const n = std.fmt.parseInt(u64, segment, 10) catch {
+            numbers = .{ null, null };
+            continue;
+        };
+        numbers = .{ numbers[1], n };
+    }
+    const last = numbers[1] orelse return null;
+    if (numbers[0]) |id| return .{ .id = id, .version = std.math.cast(u32, last) };
+    return .{ .id = last };
+}
+
+/// The Zenodo REST API.
+pub const Zenodo = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+
+    const BASE_URL = "https://zenodo.org/api/records";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Zenodo {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+        };
+    }
+
+    /// The record of a version, or the latest version of a concept. `version`
+    /// is the one the entry cites, if any.
+    pub fn searchRecord(self: *Zenodo, id: u64, version: ?[]const u8) !?Entry {
+        var key_buf: [24]u8 = undefined;
+        const key = std.fmt.bufPrint(&key_buf, "{d}", .{id}) catch unreachable;
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ BASE_URL, key });
+        defer self.allocator.free(url);
+        const body = try fetch(self.client, self.response_cache, "zenodo_record", key, url) orelse return null;
+        defer self.allocator.free(body);
+        return parseZenodo(self.allocator, body, id, version);
+    }
+};
+
+/// The Figshare REST API.
+pub const Figshare = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+
+    const BASE_URL = "https://api.figshare.com/v2/articles";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Figshare {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+        };
+    }
+
+    /// The record of an article version, or of the latest version.
+    /// `version` is the one the entry cites, if any.
+    pub fn searchArticle(self: *Figshare, article: Article, version: ?[]const u8) !?Entry {
+        var key_buf: [40]u8 = undefined;
+        const key = if (article.version) |v|
+            std.fmt.bufPrint(&key_buf, "{d}/versions/{d}", .{ article.id, v }) catch unreachable
+        else
+            std.fmt.bufPrint(&key_buf, "{d}", .{article.id}) catch unreachable;
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ BASE_URL, key });
+        defer self.allocator.free(url);
+        const body = try fetch(self.client, self.response_cache, "figshare_article", key, url) orelse return null;
+        defer self.allocator.free(body);
+        return parseFigshare(self.allocator, body, article, version);
+    }
+};
+
+/// A response body, or null on 404.
+fn fetch(client: *http.Client, response_cache: *cache.Cache, api: []const u8, key: []const u8, url: []const u8) ValidatorError!?[]u8 {
+    if (response_cache.get(api, key)) |cached| return cached;
+    const body = client.get(url) catch |err| {
+        return switch (err) {
+            http.HttpError.NotFound => null,
+            http.HttpError.RateLimited => ValidatorError.RateLimited,
+            else => ValidatorError.RequestFailed,
+        };
+    };
+    response_cache.set(api, key, body) catch {};
+    return body;
+}
+
+/// Build a record from a Zenodo record. When the concept was cited, the
+/// record is the latest version, whose year says nothing about the cited
+/// `version` unless it is that version.
+pub fn parseZenodo(allocator: std.mem.Allocator, json: []const u8, cited_id: u64, version: ?[]const u8) !?Entry {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, json, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+    const record = parsed.value.object;
+    const metadata = record.get("metadata") orelse return null;
+    if (metadata != .object) return null;
+
+    var result = Entry{
+        .key = "",
+        .entry_type = try allocator.dupe(u8, entryType(typeName(metadata.object))),
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+
+    result.title = try dupeString(allocator, metadata.object, "title");
+    result.doi = try dupeString(allocator, record, "doi");
+    if (metadata.object.get("creators")) |creators| {
+        if (creators == .array) result.authors = try names(allocator, creators.array.items, "name");
+    }
+
+    const record_version = stringOf(metadata.object, "version");
+    const concept_cited = switch (record.get("conceptrecid") orelse .null) {
+        .string => |s| (std.fmt.parseInt(u64, s, 10) catch 0) == cited_id,
+        .integer => |n| n == cited_id,
+        else => false,
+    };
+    if (!concept_cited or (version != null and github.sameVersion(version.?, record_version))) {
+        result.year = leadingYear(stringOf(metadata.object, "publication_date"));
+    }
+
+    result.extra = try validators.extraFields(allocator, &.{
+        .{ .name = "version", .value = record_version },
+        .{ .name = "concept_doi", .value = stringOf(record, "conceptdoi") },
+    });
+    return result;
+}
+
+/// Build a record from a Figshare article. As on Zenodo, the latest version
+/// stands in for a cited concept.
+pub fn parseFigshare(allocator: std.mem.Allocator, json: []const u8, article: Article, version: ?[]const u8) !?Entry {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, json, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+    const record = parsed.value.object;
+
+    var result = Entry{
+        .key = "",
+        .entry_type = try allocator.dupe(u8, entryType(stringOf(record, "defined_type_name"))),
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+
+    result.title = try dupeString(allocator, record, "title");
+    result.doi = try dupeString(allocator, record, "doi");
+    if (record.get("authors")) |authors| {
+        if (authors == .array) result.authors = try names(allocator, authors.array.items, "full_name");
+    }
+
+    var version_buf: [12]u8 = undefined;
+    const record_version = switch (record.get("version") orelse .null) {
+        .integer => |n| std.fmt.bufPrint(&version_buf, "{d}", .{n}) catch "",
+        else => "",
+    };
+    if (article.version != null or (version != null and github.sameVersion(version.?, record_version))) {
+        result.year = leadingYear(stringOf(record, "published_date"));
+    }
+
+    var concept_buf: [48]u8 = undefined;
+    const concept = std.fmt.bufPrint(&concept_buf, "{s}{d}", .{ FIGSHARE_PREFIX, article.id }) catch "";
+    result.extra = try validators.extraFields(allocator, &.{
+        .{ .name = "version", .value = record_version },
+        .{ .name = "concept_doi", .value = concept },
+    });
+    return result;
+}
+
+/// The BibTeX type of a deposit type such as "dataset" or "software".
+fn entryType(deposit_type: []const u8) []const u8 {
+    if (std.ascii.eqlIgnoreCase(deposit_type, "software")) return "software";
+    if (std.ascii.eqlIgnoreCase(deposit_type, "dataset")) return "dataset";
+    return "misc";
+}
+
+/// Zenodo's resource type, e.g. "software".
+fn typeName(metadata: std.json.ObjectMap) []const u8 {
+    const resource_type = metadata.get("resource_type") orelse return "";
+    if (resource_type != .object) return "";
+    return stringOf(resource_type.object, "type");
+}
+
+fn stringOf(obj: std.json.ObjectMap, name: []const u8) []const u8 {
+    const value = obj.get(name) orelse return "";
+    return if (value == .string) value.string else "";
+}
+
+fn dupeString(allocator: std.mem.Allocator, obj: std.json.ObjectMap, name: []const u8) !?[]const u8 {
+    const value = stringOf(obj, name);
+    return if (value.len > 0) try allocator.dupe(u8, value) else null;
+}
+
+/// Owned author names from the `field` of each object in `people`.
+fn names(allocator: std.mem.Allocator, people: []const std.json.Value, field: []const u8) ![]const []const u8 {
+    var list: std.ArrayList([]const u8) = .empty;
+    errdefer {
+        for (list.items) |n| allocator.free(n);
+        list.deinit(allocator);
+    }
+    for (people) |person| {
+        if (person != .object) continue;
+        const name = stringOf(person.object, field);
+        if (name.len > 0) try list.append(allocator, try allocator.dupe(u8, name));
+    }
+    return list.toOwnedSlice(allocator);
+}
+
+fn leadingYear(date: []const u8) ?i32 {
+    if (date.len < 4) return null;
+    return std.fmt.parseInt(i32, date[0..4], 10) catch null;
+}
+
+test "Zenodo and Figshare deposits" {
+    const allocator = std.testing.allocator;
+
+    const zenodo_doi = Entry{ .key = "a", .entry_type = "software", .doi = "10.5281/zenodo.1234567" };
+    try std.testing.expectEqual(Deposit{ .zenodo = 1234567 }, depositFor(&zenodo_doi).?);
+    const figshare_doi = Entry{ .key = "b", .entry_type = "dataset", .doi = "10.6084/m9.figshare.7654321.v3" };
+    try std.testing.expectEqual(Deposit{ .figshare = .{ .id = 7654321, .version = 3 } }, depositFor(&figshare_doi).?);
+    const figshare_url = Entry{ .key = "c", .entry_type = "dataset", .url = "https://figshare.com/articles/dataset/Some_data/7654321/2" };
+    try std.testing.expectEqual(Deposit{ .figshare = .{ .id = 7654321, .version = 2 } }, depositFor(&figshare_url).?);
+    const zenodo_url = Entry{ .key = "d", .entry_type = "misc", .url = "https://zenodo.org/records/42?preview=1" };
+    try std.testing.expectEqual(Deposit{ .zenodo = 42 }, depositFor(&zenodo_url).?);
+    const other = Entry{ .key = "e", .entry_type = "article", .doi = "10.1000/xyz" };
+    try std.testing.expect(depositFor(&other) == null);
+
+    const latest =
+        \\{"doi": "10.5281/zenodo.1234570", "conceptdoi": "10.5281/zenodo.1234567", "conceptrecid": "1234567",
+        \\ "metadata": {"title": "Example Toolkit", "creators": [{"name": "Doe, Jane"}],
+        \\  "publication_date": "2023-02-01", "version": "v2.0", "resource_type": {"type": "software"}}}
+    ;
+    var concept = (try parseZenodo(allocator, latest, 1234567, "1.0")).?;
+    defer concept.deinit();
+    try std.testing.expectEqualStrings("software", concept.entry_type);
+    try std.testing.expectEqualStrings("Doe, Jane", concept.authors[0]);
+    try std.testing.expectEqualStrings("10.5281/zenodo.1234567", concept.extraField("concept_doi").?);
+    try std.testing.expect(concept.year == null);
+
+    var cited = (try parseZenodo(allocator, latest, 1234570, null)).?;
+    defer cited.deinit();
+    try std.testing.expectEqual(@as(?i32, 2023), cited.year);
+
+    var article = (try parseFigshare(allocator,
+        \\{"title": "Some data", "doi": "10.6084/m9.figshare.7654321.v3", "version": 3,
+        \\ "authors": [{"full_name": "Jane Doe"}], "published_date": "2020-06-01T10:00:00Z", "defined_type_name": "dataset"}
+    , .{ .id = 7654321, .version = 3 }, null)).?;
+    defer article.deinit();
+    try std.testing.expectEqualStrings("3", article.extraField("version").?);
+    try std.testing.expectEqualStrings("10.6084/m9.figshare.7654321", article.extraField("concept_doi").?);
+    try std.testing.expectEqual(@as(?i32, 2020), article.year);
+}
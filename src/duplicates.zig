@@ -0,0 +1,244 @@
+//! Duplicate entries across a project.
+//!
+//! Large projects split their bibliography over several files, and the
+//! same key or the same work easily ends up in two of them. BibTeX keeps
+//! whichever entry it reads first, so the other file's fields are silently
+//! ignored. Findings are reported like policy violations, with each entry
+//! labelled by the file it came from.
+//!
+//! Entries by the same authors with nearly the same title but different
+//! DOIs or years, most often a preprint cited next to its published
+//! version, are reported separately when they are not taken for one work,
+//! so one version can be kept.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const policy = @import("policy.zig");
+const rules = @import("rules.zig");
+const Entry = entry_mod.Entry;
+
+/// Title similarity above which two matching entries are taken to be the
+/// same work. Stricter than matching against search results, since two
+/// papers of one group often share most of a title.
+pub const DUPLICATE_TITLE_SIMILARITY: f64 = 0.95;
+
+/// Title similarity above which two entries that are not the same work
+/// are reported as likely versions of one.
+pub const NEAR_DUPLICATE_TITLE_SIMILARITY: f64 = 0.9;
+
+/// Find duplicate keys and duplicate works among `entries`.
+pub fn check(allocator: std.mem.Allocator, entries: []const Entry, config: *const matcher.MatcherConfig, disabled: rules.RuleSet) !policy.Evaluation {
+    var result = policy.Evaluation{ .heading = "DUPLICATES", .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer result.deinit();
+    const arena = result.arena.allocator();
+
+    var violations: std.ArrayList(policy.Violation) = .empty;
+    if (!disabled.contains(.duplicate_key)) try duplicateKeys(arena, entries, &violations);
+    if (!disabled.contains(.duplicate_work) or !disabled.contains(.near_duplicate)) try duplicateWorks(allocator, arena, entries, config, disabled, &violations);
+
+    result.violations = violations.items;
+    return result;
+}
+
+fn duplicateKeys(arena: std.mem.Allocator, entries: []const Entry, violations: *std.ArrayList(policy.Violation)) !void {
+    var by_key: std.StringArrayHashMapUnmanaged(std.ArrayList([]const u8)) = .empty;
+    for (entries) |*e| {
+        const gop = try by_key.getOrPut(arena, e.key);
+        if (!gop.found_existing) gop.value_ptr.* = .empty;
+        try gop.value_ptr.append(arena, try label(arena, e));
+    }
+
+    var it = by_key.iterator();
+    while (it.next()) |kv| {
+        const labels = kv.value_ptr.items;
+        if (labels.len < 2) continue;
+        try violations.append(arena, .{
+            .rule = .duplicate_key,
+            .severity = rules.Rule.duplicate_key.defaultSeverity(),
+            .message = try std.fmt.allocPrint(arena, "Key {s} is defined {d} times", .{ kv.key_ptr.*, labels.len }),
+            .keys = labels,
+        });
+    }
+}
+
+fn duplicateWorks(allocator: std.mem.Allocator, arena: std.mem.Allocator, entries: []const Entry, config: *const matcher.MatcherConfig, disabled: rules.RuleSet, violations: *std.ArrayList(policy.Violation)) !void {
+    var index = try matcher.BlockingIndex.init(allocator, entries);
+    defer index.deinit();
+    const pairs = try index.pairs(allocator);
+    defer allocator.free(pairs);
+
+    const normalized = try matcher.normalizeAll(allocator, entries);
+    defer {
+        for (normalized) |*n| n.deinit();
+        allocator.free(normalized);
+    }
+
+    // Group entries that are the same work, transitively
+    const parent = try arena.alloc(u32, entries.len);
+    for (parent, 0..) |*p, i| p.* = @intCast(i);
+    for (pairs) |pair| {
+        if (try sameWork(allocator, config, &normalized[pair.a], &normalized[pair.b])) unite(parent, pair.a, pair.b);
+    }
+    // Identical DOIs are the same work whatever the titles say
+    var by_doi: std.StringHashMapUnmanaged(u32) = .empty;
+    for (entries, 0..) |*e, i| {
+        const doi = e.doi orelse continue;
+        const gop = try by_doi.getOrPut(arena, try std.ascii.allocLowerString(arena, doi));
+        if (gop.found_existing) unite(parent, gop.value_ptr.*, @intCast(i)) else gop.value_ptr.* = @intCast(i);
+    }
+
+    var groups: std.AutoArrayHashMapUnmanaged(u32, std.ArrayList(u32)) = .empty;
+    for (0..entries.len) |i| {
+        const root = find(parent, @intCast(i));
+        const gop = try groups.getOrPut(arena, root);
+        if (!gop.found_existing) gop.value_ptr.* = .empty;
+        try gop.value_ptr.append(arena, @intCast(i));
+    }
+
+    if (!disabled.contains(.duplicate_work)) {
+        for (groups.values()) |members| {
+            // Singletons, and the same key twice (a duplicate key), are skipped
+            const first = entries[members.items[0]].key;
+            const distinct_keys = for (members.items[1..]) |m| {
+                if (!std.mem.eql(u8, entries[m].key, first)) break true;
+            } else false;
+            if (!distinct_keys) continue;
+
+            const labels = try arena.alloc([]const u8, members.items.len);
+            for (members.items, labels) |m, *l| l.* = try label(arena, &entries[m]);
+            try violations.append(arena, .{
+                .rule = .duplicate_work,
+                .severity = rules.Rule.duplicate_work.defaultSeverity(),
+                .message = try std.fmt.allocPrint(arena, "The same work is cited under {d} entries", .{labels.len}),
+                .keys = labels,
+            });
+        }
+    }
+
+    if (disabled.contains(.near_duplicate)) return;
+    for (pairs) |pair| {
+        // Already reported as one work
+        if (!disabled.contains(.duplicate_work) and find(parent, pair.a) == find(parent, pair.b)) continue;
+        const a = &entries[pair.a];
+        const b = &entries[pair.b];
+        if (std.mem.eql(u8, a.key, b.key)) continue;
+        if (try nearDuplicate(arena, allocator, config, &normalized[pair.a], &normalized[pair.b])) |message| {
+            const labels = try arena.alloc([]const u8, 2);
+            labels[0] = try label(arena, a);
+            labels[1] = try label(arena, b);
+            try violations.append(arena, .{
+                .rule = .near_duplicate,
+                .severity = rules.Rule.near_duplicate.defaultSeverity(),
+                .message = message,
+                .keys = labels,
+            });
+        }
+    }
+}
+
+/// Why `a` and `b` look like two versions of one work, or null. Short
+/// titles are left alone: "Introduction" is not one work, and neither are
+/// two papers of the same title by other authors.
+fn nearDuplicate(arena: std.mem.Allocator, allocator: std.mem.Allocator, config: *const matcher.MatcherConfig, a: *const matcher.NormalizedEntry, b: *const matcher.NormalizedEntry) !?[]const u8 {
+    const title_a = a.title orelse return null;
+    const title_b = b.title orelse return null;
+    if (a.title_words <= matcher.SHORT_TITLE_WORDS or b.title_words <= matcher.SHORT_TITLE_WORDS) return null;
+    if (try config.title_similarity.compare(allocator, title_a, title_b) < NEAR_DUPLICATE_TITLE_SIMILARITY) return null;
+    if (a.authors.len == 0 or b.authors.len == 0) return null;
+    if (try matcher.normalizedAuthorOverlap(allocator, a, b) < matcher.MIN_AUTHOR_OVERLAP) return null;
+
+    if (a.entry.doi != null and b.entry.doi != null and !std.ascii.eqlIgnoreCase(a.entry.doi.?, b.entry.doi.?)) {
+        return try std.fmt.allocPrint(arena, "Nearly identical titles with different DOIs ({s}, {s}); cite one version", .{ a.entry.doi.?, b.entry.doi.? });
+    }
+    if (a.entry.year != null and b.entry.year != null and a.entry.year.? != b.entry.year.?) {
+        return try std.fmt.allocPrint(arena, "Nearly identical titles with different years ({d}, {d}); cite one version", .{ a.entry.year.?, b.entry.year.? });
+    }
+    return null;
+}
+
+fn sameWork(allocator: std.mem.Allocator, config: *const matcher.MatcherConfig, a: *const matcher.NormalizedEntry, b: *const matcher.NormalizedEntry) !bool {
+    // A match has titles, compatible years, and overlapping authors
+    if (try matcher.normalizedMatchScore(allocator, config, a, b) == 0) return false;
+    return try config.title_similarity.compare(allocator, a.title.?, b.title.?) >= DUPLICATE_TITLE_SIMILARITY;
+}
+
+fn find(parent: []u32, i: u32) u32 {
+    var root = i;
+    while (parent[root] != root) root = parent[root];
+    // Point the path straight at the root
+    var node = i;
+    while (parent[node] != root) {
+        const next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    return root;
+}
+
+fn unite(parent: []u32, a: u32, b: u32) void {
+    const ra = find(parent, a);
+    const rb = find(parent, b);
+    // The lower index stays the root, so groups list entries in input order
+    if (ra < rb) parent[rb] = ra else parent[ra] = rb;
+}
+
+/// "file:key", or the key for entries not read from a file.
+pub fn label(arena: std.mem.Allocator, e: *const Entry) ![]const u8 {
+    const file = e.source_file orelse return e.key;
+    return std.fmt.allocPrint(arena, "{s}:{s}", .{ file, e.key });
+}
+
+test "duplicate keys and works across files" {
+    const entries = [_]Entry{
+        .{ .key = "vaswani2017", .entry_type = "article", .title = "Attention Is All You Need", .year = 2017, .authors = &.{"Vaswani, Ashish"}, .source_file = "ch1.bib" },
+        .{ .key = "he2016", .entry_type = "article", .title = "Deep Residual Learning for Image Recognition", .year = 2016, .source_file = "ch1.bib" },
+        .{ .key = "attention", .entry_type = "article", .title = "Attention is all you need", .year = 2017, .authors = &.{"Vaswani, Ashish"}, .source_file = "ch2.bib" },
+        .{ .key = "he2016", .entry_type = "article", .title = "Identity Mappings in Deep Residual Networks", .year = 2016, .source_file = "ch2.bib" },
+        .{ .key = "lecun", .entry_type = "article", .title = "Gradient-based learning", .doi = "10.1109/5.726791", .source_file = "ch2.bib" },
+        .{ .key = "lecun1998", .entry_type = "article", .title = "Gradient-Based Learning Applied to Document Recognition", .doi = "10.1109/5.726791", .source_file = "ch3.bib" },
+    };
+
+    const config = matcher.MatcherConfig{};
+    var result = try check(std.testing.allocator, &entries, &config, .initEmpty());
+    defer result.deinit();
+
+    try std.testing.expectEqual(@as(usize, 3), result.violations.len);
+    const key = result.violations[0];
+    try std.testing.expectEqual(rules.Rule.duplicate_key, key.rule);
+    try std.testing.expectEqualStrings("ch1.bib:he2016", key.keys[0]);
+    try std.testing.expectEqualStrings("ch2.bib:he2016", key.keys[1]);
+
+    try std.testing.expectEqual(rules.Rule.duplicate_work, result.violations[1].rule);
+    try std.testing.expectEqualStrings("ch2.bib:attention", result.violations[1].keys[1]);
+    try std.testing.expectEqualStrings("ch3.bib:lecun1998", result.violations[2].keys[1]);
+}
+
+test "preprint cited next to its published version" {
+    const entries = [_]Entry{
+        .{ .key = "he2015", .entry_type = "article", .title = "Deep Residual Learning for Image Recognition", .year = 2015, .doi = "10.48550/arXiv.1512.03385", .authors = &.{"He, Kaiming"} },
+        .{ .key = "he2016", .entry_type = "inproceedings", .title = "Deep Residual Learning for Image Recognition", .year = 2016, .doi = "10.1109/CVPR.2016.90", .authors = &.{"Kaiming He"} },
+        .{ .key = "zhang2018", .entry_type = "article", .title = "Deep Residual Learning for Image Recognition", .year = 2018, .doi = "10.1000/other", .authors = &.{"Zhang, Wei"} },
+        .{ .key = "smith2019", .entry_type = "article", .title = "Introduction", .year = 2019 },
+        .{ .key = "jones2020", .entry_type = "article", .title = "Introduction", .year = 2020 },
+    };
+
+    const config = matcher.MatcherConfig{};
+    var disabled = rules.RuleSet.initEmpty();
+    disabled.insert(.duplicate_work);
+    var result = try check(std.testing.allocator, &entries, &config, disabled);
+    defer result.deinit();
+
+    try std.testing.expectEqual(@as(usize, 1), result.violations.len);
+    const near = result.violations[0];
+    try std.testing.expectEqual(rules.Rule.near_duplicate, near.rule);
+    try std.testing.expectEqualStrings("Nearly identical titles with different DOIs (10.48550/arXiv.1512.03385, 10.1109/CVPR.2016.90); cite one version", near.message);
+    try std.testing.expectEqualStrings("he2016", near.keys[1]);
+
+    // Reported as one work instead when that check is on
+    var both = try check(std.testing.allocator, &entries, &config, .initEmpty());
+    defer both.deinit();
+    try std.testing.expectEqual(@as(usize, 1), both.violations.len);
+    try std.testing.expectEqual(rules.Rule.duplicate_work, both.violations[0].rule);
+    try std.testing.expectEqual(@as(usize, 2), both.violations[0].keys.len);
+}
@@ -0,0 +1,62 @@
+//! Book editions.
+//!
+//! Books are reprinted and revised under the same title, so a title search
+//! finds several editions. The `edition` field ("2", "2nd", "Second
+//! Edition") says which one a citation means; matching prefers that
+//! edition and the year is checked against it.
+
+const std = @import("std");
+const Entry = @import("entry.zig").Entry;
+
+/// The edition number in an `edition` value: 2 for "2", "2nd", "2nd ed.",
+/// or "Second Edition".
+pub fn number(text: []const u8) ?u32 {
+    var it = std.mem.tokenizeAny(u8, text, " \t.,");
+    const first = it.next() orelse return null;
+
+    var digits: usize = 0;
+    while (digits < first.len and std.ascii.isDigit(first[digits])) digits += 1;
+    if (digits > 0) {
+        const suffix = first[digits..];
+        const suffixes = [_][]const u8{ "", "st", "nd", "rd", "th" };
+        for (suffixes) |s| {
+            if (std.ascii.eqlIgnoreCase(suffix, s)) return std.fmt.parseInt(u32, first[0..digits], 10) catch null;
+        }
+        return null;
+    }
+
+    const words = [_][]const u8{ "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth" };
+    for (words, 1..) |word, n| {
+        if (std.ascii.eqlIgnoreCase(first, word)) return @intCast(n);
+    }
+    return null;
+}
+
+/// The edition an entry gives, if any.
+pub fn ofEntry(e: *const Entry) ?u32 {
+    return number(e.extraField("edition") orelse return null);
+}
+
+/// "1st", "2nd", "11th", ...
+pub fn ordinal(buf: []u8, n: u32) []const u8 {
+    const suffix = if (n % 100 >= 11 and n % 100 <= 13) "th" else switch (n % 10) {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        else => "th",
+    };
+    return std.fmt.bufPrint(buf, "{d}{s}", .{ n, suffix }) catch "";
+}
+
+test "edition numbers" {
+    try std.testing.expectEqual(@as(?u32, 2), number("2"));
+    try std.testing.expectEqual(@as(?u32, 3), number("3rd ed."));
+    try std.testing.expectEqual(@as(?u32, 2), number("Second Edition"));
+    try std.testing.expectEqual(@as(?u32, null), number("Revised"));
+    try std.testing.expectEqual(@as(?u32, null), number("2a"));
+
+    var buf: [16]u8 = undefined;
+    try std.testing.expectEqualStrings("2nd", ordinal(&buf, 2));
+    try std.testing.expectEqualStrings("12th", ordinal(&buf, 12));
+    try std.testing.expectEqualStrings("21st", ordinal(&buf, 21));
+}
@@ -0,0 +1,251 @@
+//! Input encoding detection.
+//!
+//! Parsers work on UTF-8. Files exported by older tools or copied from
+//! publisher pages are often Windows-1252 (a superset of Latin-1) or
+//! UTF-16, and reading them as UTF-8 garbles accented names. `toUtf8`
+//! detects the encoding, transcodes, and records the byte offsets of
+//! anything it could not decode cleanly.
+
+const std = @import("std");
+
+pub const Encoding = enum {
+    utf8,
+    utf16le,
+    utf16be,
+    windows1252,
+    /// Mostly UTF-8 with stray Windows-1252 bytes, e.g. after pasting from
+    /// a web page into a UTF-8 file.
+    mixed,
+
+    pub fn name(self: Encoding) []const u8 {
+        return switch (self) {
+            .utf8 => "UTF-8",
+            .utf16le => "UTF-16LE",
+            .utf16be => "UTF-16BE",
+            .windows1252 => "Windows-1252",
+            .mixed => "mixed UTF-8 and Windows-1252",
+        };
+    }
+};
+
+pub const IssueKind = enum {
+    /// A byte that is not valid UTF-8 in an otherwise UTF-8 file.
+    /// It was read as Windows-1252.
+    not_utf8,
+    /// A byte with no Windows-1252 character (0x81, 0x8D, 0x8F, 0x90, 0x9D).
+    undefined_byte,
+    /// A UTF-16 surrogate without its pair.
+    unpaired_surrogate,
+    /// A UTF-16 file with an odd number of bytes.
+    truncated,
+
+    pub fn describe(self: IssueKind) []const u8 {
+        return switch (self) {
+            .not_utf8 => "byte is not valid UTF-8; read as Windows-1252",
+            .undefined_byte => "byte is undefined in Windows-1252; replaced",
+            .unpaired_surrogate => "unpaired UTF-16 surrogate; replaced",
+            .truncated => "UTF-16 input ends in half a character; dropped",
+        };
+    }
+};
+
+/// A problem at a byte offset of the original input.
+pub const Issue = struct {
+    offset: usize,
+    kind: IssueKind,
+};
+
+/// Input transcoded to UTF-8.
+pub const Decoded = struct {
+    text: []u8,
+    encoding: Encoding,
+    issues: []Issue,
+    allocator: std.mem.Allocator,
+
+    pub fn deinit(self: *Decoded) void {
+        self.allocator.free(self.text);
+        self.allocator.free(self.issues);
+    }
+};
+
+/// Windows-1252 code points for 0x80-0x9F; 0 marks undefined bytes.
+/// Bytes from 0xA0 up are the same as Latin-1.
+const cp1252_high = [32]u21{
+    0x20AC, 0,      0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021,
+    0x02C6, 0x2030, 0x0160, 0x2039, 0x0152, 0,      0x017D, 0,
+    0,      0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0,      0x017E, 0x0178,
+};
+
+const replacement: u21 = 0xFFFD;
+
+/// Detect the encoding of `bytes` and transcode it to UTF-8, dropping any
+/// byte order mark.
+pub fn toUtf8(allocator: std.mem.Allocator, bytes: []const u8) !Decoded {
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+    var issues: std.ArrayList(Issue) = .empty;
+    errdefer issues.deinit(allocator);
+
+    const encoding: Encoding = if (std.mem.startsWith(u8, bytes, "\xFF\xFE")) blk: {
+        try decodeUtf16(allocator, bytes, 2, .little, &out, &issues);
+        break :blk .utf16le;
+    } else if (std.mem.startsWith(u8, bytes, "\xFE\xFF")) blk: {
+        try decodeUtf16(allocator, bytes, 2, .big, &out, &issues);
+        break :blk .utf16be;
+    } else if (guessUtf16(bytes)) |endian| blk: {
+        try decodeUtf16(allocator, bytes, 0, endian, &out, &issues);
+        break :blk if (endian == .little) .utf16le else .utf16be;
+    } else blk: {
+        const start: usize = if (std.mem.startsWith(u8, bytes, "\xEF\xBB\xBF")) 3 else 0;
+        break :blk try decodeUtf8(allocator, bytes, start, &out, &issues);
+    };
+
+    return .{
+        .text = try out.toOwnedSlice(allocator),
+        .encoding = encoding,
+        .issues = try issues.toOwnedSlice(allocator),
+        .allocator = allocator,
+    };
+}
+
+/// BibTeX is mostly ASCII, so UTF-16 without a byte order mark shows up as
+/// a zero byte in every other position.
+fn guessUtf16(bytes: []const u8) ?std.builtin.Endian {
+    const sample = bytes[0 .. @min(bytes.len, 512) & ~@as(usize, 1)];
+    if (sample.len < 4) return null;
+    var even: usize = 0;
+    var odd: usize = 0;
+    for (sample, 0..) |b, i| {
+        if (b != 0) continue;
+        if (i % 2 == 0) even += 1 else odd += 1;
+    }
+    const pairs = sample.len / 2;
+    if (odd * 4 >= pairs * 3 and even == 0) return .little;
+    if (even * 4 >= pairs * 3 and odd == 0) return .big;
+    return null;
+}
+
+fn decodeUtf16(
+    allocator: std.mem.Allocator,
+    bytes: []const u8,
+    start: usize,
+    endian: std.builtin.Endian,
+    out: *std.ArrayList(u8),
+    issues: *std.ArrayList(Issue),
+) !void {
+    var pos = start;
+    while (pos + 1 < bytes.len) {
+        const unit = std.mem.readInt(u16, bytes[pos..][0..2], endian);
+        const offset = pos;
+        pos += 2;
+
+        var cp: u21 = unit;
+        if (std.unicode.utf16IsHighSurrogate(unit)) {
+            const low = if (pos + 1 < bytes.len) std.mem.readInt(u16, bytes[pos..][0..2], endian) else 0;
+            if (std.unicode.utf16IsLowSurrogate(low)) {
+                cp = 0x10000 + ((@as(u21, unit) - 0xD800) << 10) + (low - 0xDC00);
+                pos += 2;
+            } else {
+                try issues.append(allocator, .{ .offset = offset, .kind = .unpaired_surrogate });
+                cp = replacement;
+            }
+        } else if (std.unicode.utf16IsLowSurrogate(unit)) {
+            try issues.append(allocator, .{ .offset = offset, .kind = .unpaired_surrogate });
+            cp = replacement;
+        }
+        try appendCodepoint(allocator, out, cp);
+    }
+    if (pos < bytes.len) try issues.append(allocator, .{ .offset = pos, .kind = .truncated });
+}
+
+/// Copy valid UTF-8 and read every other byte as Windows-1252. A file with
+/// no valid multi-byte sequence at all is taken to be Windows-1252.
+fn decodeUtf8(allocator: std.mem.Allocator, bytes: []const u8, start: usize, out: *std.ArrayList(u8), issues: *std.ArrayList(Issue)) !Encoding {
+    try out.ensureTotalCapacity(allocator, bytes.len - start);
+    var multibyte = false;
+    var invalid = false;
+    var pos = start;
+    while (pos < bytes.len) {
+        const b = bytes[pos];
+        if (b < 0x80) {
+            try out.append(allocator, b);
+            pos += 1;
+        } else if (sequenceAt(bytes, pos)) |len| {
+            try out.appendSlice(allocator, bytes[pos .. pos + len]);
+            multibyte = true;
+            pos += len;
+        } else {
+            invalid = true;
+            const cp: u21 = if (b >= 0xA0) b else cp1252_high[b - 0x80];
+            try issues.append(allocator, .{ .offset = pos, .kind = if (cp == 0) .undefined_byte else .not_utf8 });
+            try appendCodepoint(allocator, out, if (cp == 0) replacement else cp);
+            pos += 1;
+        }
+    }
+
+    if (!invalid) return .utf8;
+    if (multibyte) return .mixed;
+
+    // In a Windows-1252 file only the undefined bytes are problems
+    var kept: usize = 0;
+    for (issues.items) |issue| {
+        if (issue.kind == .not_utf8) continue;
+        issues.items[kept] = issue;
+        kept += 1;
+    }
+    issues.shrinkRetainingCapacity(kept);
+    return .windows1252;
+}
+
+/// Length of the valid UTF-8 sequence at `pos`, if any.
+fn sequenceAt(bytes: []const u8, pos: usize) ?usize {
+    const len = std.unicode.utf8ByteSequenceLength(bytes[pos]) catch return null;
+    if (pos + len > bytes.len) return null;
+    _ = std.unicode.utf8Decode(bytes[pos .. pos + len]) catch return null;
+    return len;
+}
+
+fn appendCodepoint(allocator: std.mem.Allocator, out: *std.ArrayList(u8), cp: u21) !void {
+    var buf: [4]u8 = undefined;
+    const len = std.unicode.utf8Encode(cp, &buf) catch std.unicode.utf8Encode(replacement, &buf) catch unreachable;
+    try out.appendSlice(allocator, buf[0..len]);
+}
+
+test "toUtf8 detects and transcodes encodings" {
+    const allocator = std.testing.allocator;
+
+    // Windows-1252: "Müller" with curly quotes
+    var latin = try toUtf8(allocator, "title = {\x93M\xFCller\x94}");
+    defer latin.deinit();
+    try std.testing.expectEqual(Encoding.windows1252, latin.encoding);
+    try std.testing.expectEqualStrings("title = {\u{201C}Müller\u{201D}}", latin.text);
+    try std.testing.expectEqual(@as(usize, 0), latin.issues.len);
+
+    // UTF-8 with a byte order mark
+    var utf8 = try toUtf8(allocator, "\xEF\xBB\xBF@article{Gödel}");
+    defer utf8.deinit();
+    try std.testing.expectEqual(Encoding.utf8, utf8.encoding);
+    try std.testing.expectEqualStrings("@article{Gödel}", utf8.text);
+
+    // UTF-16LE without a byte order mark
+    var utf16 = try toUtf8(allocator, "@\x00a\x00{\x00\xE9\x00}\x00");
+    defer utf16.deinit();
+    try std.testing.expectEqual(Encoding.utf16le, utf16.encoding);
+    try std.testing.expectEqualStrings("@a{é}", utf16.text);
+
+    // UTF-8 with a stray Latin-1 byte and an undefined one
+    var mixed = try toUtf8(allocator, "Gödel, Sch\xF6n\x81");
+    defer mixed.deinit();
+    try std.testing.expectEqual(Encoding.mixed, mixed.encoding);
+    try std.testing.expectEqualStrings("Gödel, Schön\u{FFFD}", mixed.text);
+    try std.testing.expectEqual(@as(usize, 2), mixed.issues.len);
+    try std.testing.expectEqual(Issue{ .offset = 11, .kind = .not_utf8 }, mixed.issues[0]);
+    try std.testing.expectEqual(Issue{ .offset = 13, .kind = .undefined_byte }, mixed.issues[1]);
+
+    // UTF-16BE with an unpaired surrogate
+    var broken = try toUtf8(allocator, "\xFE\xFF\x00a\xD8\x00\x00b");
+    defer broken.deinit();
+    try std.testing.expectEqualStrings("a\u{FFFD}b", broken.text);
+    try std.testing.expectEqual(Issue{ .offset = 4, .kind = .unpaired_surrogate }, broken.issues[0]);
+}
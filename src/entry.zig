@@ -4,6 +4,13 @@
 //! across different academic databases.
 
 const std = @import("std");
+const rules = @import("rules.zig");
+
+/// A field bibval does not interpret, kept as written.
+pub const ExtraField = struct {
+    name: []const u8,
+    value: []const u8,
+};
 
 /// Normalized bibliography entry for comparison across different sources.
 pub const Entry = struct {
@@ -15,16 +22,57 @@ pub const Entry = struct {
     title: ?[]const u8 = null,
     /// List of authors
     authors: []const []const u8 = &.{},
-    /// Publication year
+    /// Publication year; the first year of a date range
     year: ?i32 = null,
+    /// Last year of a date range, e.g. 2020 for `date = {2019/2020}`
+    year_end: ?i32 = null,
+    /// The date is approximate or uncertain (`2019~`, `2019?`, "circa 2019")
+    year_approximate: bool = false,
+    /// BibTeX field the year was read from when it is not a plain number
+    /// in `year`, e.g. "date"; the field is kept as written in `extra`
+    year_field: ?[]const u8 = null,
     /// Journal or conference venue
     venue: ?[]const u8 = null,
+    /// BibTeX field the venue was read from, "journal" or "booktitle"
+    venue_field: ?[]const u8 = null,
+    /// Canonical source entity of the venue on a remote record, e.g.
+    /// "openalex:S4306420609" or "dblp:conf/nips"
+    venue_id: ?[]const u8 = null,
+    /// Publisher name
+    publisher: ?[]const u8 = null,
     /// DOI identifier
     doi: ?[]const u8 = null,
+    /// BibTeX field the DOI was found in when there is no `doi` field,
+    /// e.g. "url" or "note"
+    doi_field: ?[]const u8 = null,
     /// ArXiv identifier (e.g., "2301.12345")
     arxiv_id: ?[]const u8 = null,
+    /// BibTeX field the arXiv ID was found in when it is not a tidy
+    /// eprint/archivePrefix pair, e.g. "journal"
+    arxiv_field: ?[]const u8 = null,
+    /// ArXiv primary category (e.g., "cs.LG")
+    primary_class: ?[]const u8 = null,
+    /// Scopus EID (e.g., "2-s2.0-85012345678")
+    scopus_eid: ?[]const u8 = null,
+    /// ISBN-10 or ISBN-13 without hyphens (e.g., "9780262033848")
+    isbn: ?[]const u8 = null,
     /// URL
     url: ?[]const u8 = null,
+    /// Funding acknowledgement: funder names and award numbers
+    funding: ?[]const u8 = null,
+    /// Open-access status of a remote record (gold, green, hybrid, bronze, diamond, closed)
+    oa_status: ?[]const u8 = null,
+    /// License of the best open-access copy, e.g. "cc-by"
+    license: ?[]const u8 = null,
+    /// JabRef groups the entry is assigned to, comma-separated
+    groups: ?[]const u8 = null,
+    /// Other fields (pages, volume, note, ...) in input order, so that
+    /// they survive rewriting and custom comparators can check them
+    extra: []const ExtraField = &.{},
+    /// Input file the entry was read from; borrowed
+    source_file: ?[]const u8 = null,
+    /// Source of each field, for entries merged from several records
+    provenance: Provenance = .initFill(null),
 
     allocator: ?std.mem.Allocator = null,
 
@@ -36,10 +84,160 @@ pub const Entry = struct {
             for (self.authors) |a| alloc.free(a);
             if (self.authors.len > 0) alloc.free(self.authors);
             if (self.venue) |v| alloc.free(v);
+            if (self.venue_id) |v| alloc.free(v);
+            if (self.publisher) |p| alloc.free(p);
             if (self.doi) |d| alloc.free(d);
             if (self.arxiv_id) |a| alloc.free(a);
+            if (self.primary_class) |c| alloc.free(c);
+            if (self.scopus_eid) |s| alloc.free(s);
+            if (self.isbn) |i| alloc.free(i);
             if (self.url) |u| alloc.free(u);
+            if (self.funding) |f| alloc.free(f);
+            if (self.oa_status) |s| alloc.free(s);
+            if (self.license) |l| alloc.free(l);
+            if (self.groups) |g| alloc.free(g);
+            for (self.extra) |f| {
+                alloc.free(f.name);
+                alloc.free(f.value);
+            }
+            if (self.extra.len > 0) alloc.free(self.extra);
+        }
+    }
+
+    /// Earliest and latest year the entry's date allows: the years of a
+    /// range, and a year either side of an approximate date.
+    pub fn yearBounds(self: *const Entry) ?[2]i32 {
+        const year = self.year orelse return null;
+        const slack: i32 = if (self.year_approximate) 1 else 0;
+        return .{ year - slack, (self.year_end orelse year) + slack };
+    }
+
+    /// Value of the extra field `name` (case-insensitive), if present.
+    pub fn extraField(self: *const Entry, name: []const u8) ?[]const u8 {
+        for (self.extra) |f| {
+            if (std.ascii.eqlIgnoreCase(f.name, name)) return f.value;
+        }
+        return null;
+    }
+
+    /// Deep copy of the entry owned by `allocator`.
+    pub fn clone(self: *const Entry, allocator: std.mem.Allocator) !Entry {
+        var copy = Entry{
+            .key = "",
+            .entry_type = "",
+            .year = self.year,
+            .year_end = self.year_end,
+            .year_approximate = self.year_approximate,
+            .year_field = self.year_field,
+            .venue_field = self.venue_field,
+            .doi_field = self.doi_field,
+            .arxiv_field = self.arxiv_field,
+            .source_file = self.source_file,
+            .provenance = self.provenance,
+            .allocator = allocator,
+        };
+        errdefer copy.deinit();
+
+        copy.key = try allocator.dupe(u8, self.key);
+        copy.entry_type = try allocator.dupe(u8, self.entry_type);
+        if (self.title) |t| copy.title = try allocator.dupe(u8, t);
+        if (self.venue) |v| copy.venue = try allocator.dupe(u8, v);
+        if (self.venue_id) |v| copy.venue_id = try allocator.dupe(u8, v);
+        if (self.publisher) |p| copy.publisher = try allocator.dupe(u8, p);
+        if (self.doi) |d| copy.doi = try allocator.dupe(u8, d);
+        if (self.arxiv_id) |a| copy.arxiv_id = try allocator.dupe(u8, a);
+        if (self.primary_class) |c| copy.primary_class = try allocator.dupe(u8, c);
+        if (self.scopus_eid) |s| copy.scopus_eid = try allocator.dupe(u8, s);
+        if (self.isbn) |i| copy.isbn = try allocator.dupe(u8, i);
+        if (self.url) |u| copy.url = try allocator.dupe(u8, u);
+        if (self.funding) |f| copy.funding = try allocator.dupe(u8, f);
+        if (self.oa_status) |s| copy.oa_status = try allocator.dupe(u8, s);
+        if (self.license) |l| copy.license = try allocator.dupe(u8, l);
+        if (self.groups) |g| copy.groups = try allocator.dupe(u8, g);
+
+        if (self.authors.len > 0) {
+            const authors = try allocator.alloc([]const u8, self.authors.len);
+            var filled: usize = 0;
+            errdefer {
+                for (authors[0..filled]) |a| allocator.free(a);
+                allocator.free(authors);
+            }
+            for (self.authors) |a| {
+                authors[filled] = try allocator.dupe(u8, a);
+                filled += 1;
+            }
+            copy.authors = authors;
+        }
+
+        if (self.extra.len > 0) {
+            const extra = try allocator.alloc(ExtraField, self.extra.len);
+            var filled: usize = 0;
+            errdefer {
+                for (extra[0..filled]) |f| {
+                    allocator.free(f.name);
+                    allocator.free(f.value);
+                }
+                allocator.free(extra);
+            }
+            for (self.extra) |f| {
+                const name = try allocator.dupe(u8, f.name);
+                errdefer allocator.free(name);
+                extra[filled] = .{ .name = name, .value = try allocator.dupe(u8, f.value) };
+                filled += 1;
+            }
+            copy.extra = extra;
         }
+
+        return copy;
+    }
+
+    /// Hash of every field read from the input, used to detect edits. The
+    /// input file and provenance are not part of the entry's content.
+    pub fn contentHash(self: *const Entry) u64 {
+        var hasher = std.hash.Wyhash.init(0);
+        hasher.update(self.key);
+        hasher.update(&[_]u8{0});
+        hasher.update(self.entry_type);
+        const fields = [_]?[]const u8{
+            self.title,
+            self.year_field,
+            self.venue,
+            self.venue_field,
+            self.venue_id,
+            self.publisher,
+            self.doi,
+            self.doi_field,
+            self.arxiv_id,
+            self.arxiv_field,
+            self.primary_class,
+            self.scopus_eid,
+            self.isbn,
+            self.url,
+            self.funding,
+            self.oa_status,
+            self.license,
+            self.groups,
+        };
+        for (fields) |field| {
+            // Tells a missing field from an empty one
+            hasher.update(if (field == null) &[_]u8{0} else &[_]u8{1});
+            if (field) |value| hasher.update(value);
+        }
+        for (self.authors) |a| {
+            hasher.update(&[_]u8{0});
+            hasher.update(a);
+        }
+        // Custom comparators may check these
+        for (self.extra) |f| {
+            hasher.update(&[_]u8{0});
+            hasher.update(f.name);
+            hasher.update(&[_]u8{0});
+            hasher.update(f.value);
+        }
+        std.hash.autoHash(&hasher, self.year);
+        std.hash.autoHash(&hasher, self.year_end);
+        std.hash.autoHash(&hasher, self.year_approximate);
+        return hasher.final();
     }
 
     /// Normalize title for comparison (lowercase, remove extra whitespace)
@@ -60,7 +258,7 @@ pub const ValidationResult = struct {
     /// Confidence score (0.0 to 1.0)
     confidence: f64,
     /// List of discrepancies found
-    discrepancies: []const Discrepancy = &.{},
+    discrepancies: []Discrepancy = &.{},
 
     allocator: ?std.mem.Allocator = null,
 
@@ -70,10 +268,7 @@ pub const ValidationResult = struct {
                 var entry_copy = e.*;
                 entry_copy.deinit();
             }
-            for (self.discrepancies) |*d| {
-                var disc = @constCast(d);
-                disc.deinit();
-            }
+            for (self.discrepancies) |*d| d.deinit();
             if (self.discrepancies.len > 0) {
                 alloc.free(self.discrepancies);
             }
@@ -87,6 +282,22 @@ pub const ApiSource = enum {
     dblp,
     semantic_scholar,
     openalex,
+    open_library,
+    google_books,
+    arxiv,
+    pmlr,
+    neurips,
+    ieee,
+    acm,
+    springer,
+    scopus,
+    github,
+    zenodo,
+    figshare,
+    /// The metadata of a cited web page
+    web,
+    /// A configured validator plugin.
+    external,
 
     pub fn name(self: ApiSource) []const u8 {
         return switch (self) {
@@ -94,17 +305,49 @@ pub const ApiSource = enum {
             .dblp => "DBLP",
             .semantic_scholar => "Semantic Scholar",
             .openalex => "OpenAlex",
+            .open_library => "Open Library",
+            .google_books => "Google Books",
+            .arxiv => "arXiv",
+            .pmlr => "PMLR",
+            .neurips => "NeurIPS",
+            .ieee => "IEEE Xplore",
+            .acm => "ACM DL",
+            .springer => "Springer Nature",
+            .scopus => "Scopus",
+            .github => "GitHub",
+            .zenodo => "Zenodo",
+            .figshare => "Figshare",
+            .web => "Web page",
+            .external => "External",
+        };
+    }
+
+    /// How far the source's metadata can be trusted (0.0 to 1.0).
+    /// CrossRef, DBLP, and Scopus are curated, and arXiv, the proceedings
+    /// sites, the publishers, GitHub, Zenodo, and Figshare are the record of their own works; the
+    /// others are harvested or crowd-edited, or a web page's own metadata.
+    pub fn reliability(self: ApiSource) f64 {
+        return switch (self) {
+            .crossref, .dblp, .arxiv, .pmlr, .neurips, .ieee, .acm, .springer, .scopus, .github, .zenodo, .figshare => 1.0,
+            .semantic_scholar, .openalex, .open_library, .google_books, .external => 0.9,
+            // Pages describe themselves, often with the site's name in the title
+            .web => 0.8,
         };
     }
 };
 
 /// Discrepancy between local and remote entries.
 pub const Discrepancy = struct {
+    /// Check that produced this discrepancy
+    rule: rules.Rule,
     field: DiscrepancyField,
     severity: Severity,
     local_value: []const u8,
     remote_value: []const u8,
     message: []const u8,
+    /// How likely the discrepancy is real (0.0 to 1.0), from the match
+    /// confidence, the rule's signal quality and the source's reliability
+    confidence: f64 = 1.0,
 
     allocator: ?std.mem.Allocator = null,
 
@@ -124,6 +367,17 @@ pub const DiscrepancyField = enum {
     year,
     venue,
     doi,
+    eprint,
+    funding,
+    publisher,
+    volume,
+    pages,
+    edition,
+    version,
+    url,
+    language,
+    /// A field only a custom comparator checks.
+    other,
 
     pub fn name(self: DiscrepancyField) []const u8 {
         return switch (self) {
@@ -132,6 +386,16 @@ pub const DiscrepancyField = enum {
             .year => "Year",
             .venue => "Venue",
             .doi => "DOI",
+            .eprint => "Eprint",
+            .funding => "Funding",
+            .publisher => "Publisher",
+            .volume => "Volume",
+            .pages => "Pages",
+            .edition => "Edition",
+            .version => "Version",
+            .url => "URL",
+            .language => "Language",
+            .other => "Other",
         };
     }
 };
@@ -159,6 +423,150 @@ pub const Severity = enum {
     }
 };
 
+/// Which source supplied each field of a merged entry.
+pub const Provenance = std.EnumArray(DiscrepancyField, ?ApiSource);
+
+/// Merge the matched records of `results` into one entry. Each field comes
+/// from the most confident record that has it, and its source is recorded
+/// in `provenance`. Returns null if nothing matched.
+pub fn merge(allocator: std.mem.Allocator, results: []const ValidationResult) !?Entry {
+    const best = mostConfident(results, null) orelse return null;
+    var merged = try best.matched_entry.?.clone(allocator);
+    errdefer merged.deinit();
+    merged.provenance = .initFill(null);
+
+    for (std.enums.values(DiscrepancyField)) |field| {
+        const result = mostConfident(results, field) orelse continue;
+        if (result != best) try copyField(allocator, &merged, &result.matched_entry.?, field);
+        merged.provenance.set(field, result.source);
+    }
+    return merged;
+}
+
+/// The most confident result with a matched record that has `field`, or
+/// with any matched record if `field` is null. Ties go to the first.
+fn mostConfident(results: []const ValidationResult, field: ?DiscrepancyField) ?*const ValidationResult {
+    var best: ?*const ValidationResult = null;
+    for (results) |*result| {
+        const matched = if (result.matched_entry) |*m| m else continue;
+        if (field) |f| {
+            if (!hasField(matched, f)) continue;
+        }
+        if (best == null or result.confidence > best.?.confidence) best = result;
+    }
+    return best;
+}
+
+fn hasField(e: *const Entry, field: DiscrepancyField) bool {
+    return switch (field) {
+        .title => e.title != null,
+        .authors => e.authors.len > 0,
+        .year => e.year != null,
+        .venue => e.venue != null,
+        .doi => e.doi != null,
+        .eprint => e.arxiv_id != null,
+        .funding => e.funding != null,
+        .publisher => e.publisher != null,
+        .volume => e.extraField("volume") != null,
+        .pages => e.extraField("pages") != null,
+        .edition => e.extraField("edition") != null,
+        .version => e.extraField("version") != null,
+        .url => e.url != null,
+        .language => e.extraField("language") != null,
+        .other => false,
+    };
+}
+
+/// Set a field `dst` lacks from `src`.
+fn copyField(allocator: std.mem.Allocator, dst: *Entry, src: *const Entry, field: DiscrepancyField) !void {
+    switch (field) {
+        .title => dst.title = try allocator.dupe(u8, src.title.?),
+        .authors => {
+            const authors = try allocator.alloc([]const u8, src.authors.len);
+            var filled: usize = 0;
+            errdefer {
+                for (authors[0..filled]) |a| allocator.free(a);
+                allocator.free(authors);
+            }
+            for (src.authors) |a| {
+                authors[filled] = try allocator.dupe(u8, a);
+                filled += 1;
+            }
+            dst.authors = authors;
+        },
+        .year => {
+            dst.year = src.year;
+            dst.year_end = src.year_end;
+            dst.year_approximate = src.year_approximate;
+        },
+        .venue => {
+            dst.venue = try allocator.dupe(u8, src.venue.?);
+            if (src.venue_id) |id| {
+                if (dst.venue_id) |old| allocator.free(old);
+                dst.venue_id = try allocator.dupe(u8, id);
+            }
+        },
+        .doi => dst.doi = try allocator.dupe(u8, src.doi.?),
+        .eprint => dst.arxiv_id = try allocator.dupe(u8, src.arxiv_id.?),
+        .funding => dst.funding = try allocator.dupe(u8, src.funding.?),
+        .publisher => dst.publisher = try allocator.dupe(u8, src.publisher.?),
+        .url => dst.url = try allocator.dupe(u8, src.url.?),
+        .volume, .pages, .edition, .version, .language => try appendExtra(allocator, dst, @tagName(field), src.extraField(@tagName(field)).?),
+        .other => {},
+    }
+}
+
+/// Add an extra field to an entry whose fields are owned by `allocator`.
+fn appendExtra(allocator: std.mem.Allocator, e: *Entry, name: []const u8, value: []const u8) !void {
+    const owned_name = try allocator.dupe(u8, name);
+    errdefer allocator.free(owned_name);
+    const owned_value = try allocator.dupe(u8, value);
+    errdefer allocator.free(owned_value);
+    const extra = try allocator.realloc(@constCast(e.extra), e.extra.len + 1);
+    extra[extra.len - 1] = .{ .name = owned_name, .value = owned_value };
+    e.extra = extra;
+}
+
+/// Citation key from the first author's last name and the year, e.g.
+/// "smith2020", with a letter suffix ("smith2020a") if already used by an
+/// entry in `existing`. For inputs that have no keys of their own.
+pub fn generateKey(allocator: std.mem.Allocator, e: *const Entry, existing: []const Entry) ![]const u8 {
+    var base: std.ArrayList(u8) = .empty;
+    defer base.deinit(allocator);
+
+    if (e.authors.len > 0) {
+        const first = e.authors[0];
+        const last = if (std.mem.indexOfScalar(u8, first, ',')) |comma|
+            first[0..comma]
+        else if (std.mem.lastIndexOfScalar(u8, first, ' ')) |space|
+            first[space + 1 ..]
+        else
+            first;
+        for (last) |c| {
+            if (std.ascii.isAlphabetic(c)) try base.append(allocator, std.ascii.toLower(c));
+        }
+    }
+    if (base.items.len == 0) try base.appendSlice(allocator, "ref");
+    if (e.year) |y| try base.print(allocator, "{d}", .{y});
+
+    var suffix: u8 = 0;
+    while (true) : (suffix += 1) {
+        const key = if (suffix == 0)
+            try allocator.dupe(u8, base.items)
+        else
+            try std.fmt.allocPrint(allocator, "{s}{c}", .{ base.items, 'a' + suffix - 1 });
+        if (!keyTaken(existing, key) or suffix == 26) return key;
+        allocator.free(key);
+    }
+}
+
+fn keyTaken(existing: []const Entry, key: []const u8) bool {
+    for (existing) |e| {
+        if (std.mem.eql(u8, e.key, key)) return true;
+    }
+    return false;
+}
+
 /// Normalize a string for comparison: lowercase, collapse whitespace, remove punctuation.
 pub fn normalizeString(allocator: std.mem.Allocator, s: []const u8) ![]u8 {
     var result: std.ArrayList(u8) = .empty;
@@ -181,6 +589,55 @@ pub fn normalizeString(allocator: std.mem.Allocator, s: []const u8) ![]u8 {
     return result.toOwnedSlice(allocator);
 }
 
+/// The first ISBN in `value` without hyphens or spaces, using `buf` for
+/// storage, e.g. "ISBN 978-0-262-03384-8" becomes "9780262033848". Only
+/// ISBNs with a valid check digit are returned.
+pub fn normalizeIsbn(buf: *[13]u8, value: []const u8) ?[]const u8 {
+    var len: usize = 0;
+    for (value) |c| {
+        switch (c) {
+            '0'...'9' => {
+                if (len == 13) break;
+                buf[len] = c;
+                len += 1;
+            },
+            'X', 'x' => {
+                if (len == 9) {
+                    buf[9] = 'X';
+                    len = 10;
+                    break;
+                }
+                len = 0;
+            },
+            '-' => {},
+            // Spaces may group digits or separate two ISBNs
+            ' ' => if (len == 10 and validIsbn(buf[0..10])) break,
+            else => {
+                if (len == 10 or len == 13) break;
+                len = 0;
+            },
+        }
+    }
+    if ((len == 10 or len == 13) and validIsbn(buf[0..len])) return buf[0..len];
+    return null;
+}
+
+fn validIsbn(digits: []const u8) bool {
+    var sum: usize = 0;
+    if (digits.len == 10) {
+        for (digits, 0..) |c, i| {
+            const value: usize = if (c == 'X') 10 else c - '0';
+            sum += value * (10 - i);
+        }
+        return sum % 11 == 0;
+    }
+    for (digits, 0..) |c, i| {
+        if (c == 'X') return false;
+        sum += (c - '0') * @as(usize, if (i % 2 == 0) 1 else 3);
+    }
+    return sum % 10 == 0;
+}
+
 test "normalizeString" {
     const allocator = std.testing.allocator;
 
@@ -188,3 +645,53 @@ test "normalizeString" {
     defer allocator.free(result);
     try std.testing.expectEqualStrings("hello world", result);
 }
+
+test "normalizeIsbn" {
+    var buf: [13]u8 = undefined;
+    try std.testing.expectEqualStrings("9780262033848", normalizeIsbn(&buf, "ISBN 978-0-262-03384-8").?);
+    try std.testing.expectEqualStrings("080442957X", normalizeIsbn(&buf, "0-8044-2957-X").?);
+    try std.testing.expectEqualStrings("0262033844", normalizeIsbn(&buf, "0262033844 9780262033848").?);
+    try std.testing.expectEqualStrings("9780262033848", normalizeIsbn(&buf, "978 0 262 03384 8").?);
+    try std.testing.expect(normalizeIsbn(&buf, "978-0-262-03384-9") == null);
+    try std.testing.expect(normalizeIsbn(&buf, "n/a") == null);
+}
+
+test "merge records field by field" {
+    const allocator = std.testing.allocator;
+
+    const results = [_]ValidationResult{
+        .{ .source = .dblp, .confidence = 0.9, .matched_entry = .{ .key = "d", .entry_type = "inproceedings", .title = "Attention Is All You Need", .year = 2017, .venue = "NeurIPS" } },
+        .{ .source = .crossref, .confidence = 0.95, .matched_entry = .{ .key = "c", .entry_type = "article", .title = "Attention is all you need", .doi = "10.5555/3295222.3295349" } },
+        .{ .source = .openalex, .confidence = 1.0 },
+    };
+
+    var merged = (try merge(allocator, &results)).?;
+    defer merged.deinit();
+
+    try std.testing.expectEqualStrings("Attention is all you need", merged.title.?);
+    try std.testing.expectEqual(@as(?i32, 2017), merged.year);
+    try std.testing.expectEqualStrings("NeurIPS", merged.venue.?);
+    try std.testing.expectEqual(@as(?ApiSource, .crossref), merged.provenance.get(.title));
+    try std.testing.expectEqual(@as(?ApiSource, .dblp), merged.provenance.get(.year));
+    try std.testing.expectEqual(@as(?ApiSource, null), merged.provenance.get(.authors));
+
+    try std.testing.expect((try merge(allocator, results[2..])) == null);
+}
+
+test "contentHash sees every field" {
+    const base = Entry{ .key = "smith2020", .entry_type = "article", .title = "A Paper", .year = 2020 };
+    var edited = base;
+    edited.primary_class = "cs.LG";
+    try std.testing.expect(edited.contentHash() != base.contentHash());
+    edited = base;
+    edited.year_end = 2021;
+    try std.testing.expect(edited.contentHash() != base.contentHash());
+    edited = base;
+    edited.groups = "";
+    try std.testing.expect(edited.contentHash() != base.contentHash());
+
+    // Where it was read from is not content
+    edited = base;
+    edited.source_file = "refs.bib";
+    try std.testing.expectEqual(base.contentHash(), edited.contentHash());
+}
@@ -0,0 +1,135 @@
+//! External validator plugins.
+//!
+//! A plugin is a command configured under `[[validators.external]]`. When
+//! an entry has no identifier match, bibval runs each plugin with the entry
+//! as a JSON query on stdin and matches the records it prints like the
+//! results of a title search. This wires in institutional repositories or
+//! internal paper trackers without changing bibval.
+//!
+//! Protocol version 1. The request is a single JSON object on stdin,
+//! followed by end of input:
+//!
+//!     {"protocol":1,"query":{"key":"smith2020","entry_type":"article","title":"...","authors":["Smith, Jane"],"year":2020,"doi":"..."}}
+//!
+//! The plugin answers on stdout and exits with status 0:
+//!
+//!     {"protocol":1,"entries":[{"entry_type":"article","title":"...","authors":["Smith, Jane"],"year":2020,"venue":"..."}]}
+//!
+//! Query and records use the snapshot entry format (`key`, `entry_type`,
+//! `title`, `authors`, `year`, `venue`, `publisher`, `doi`, `arxiv_id`,
+//! `url`, and `extra`, an object of other BibTeX fields such as `number`);
+//! absent fields are omitted and unknown fields ignored. A plugin
+//! that finds nothing returns an empty array. Its stderr is passed through.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const snapshot = @import("snapshot.zig");
+const Entry = entry_mod.Entry;
+
+/// Version of the plugin protocol; a response with another version is
+/// rejected.
+pub const PROTOCOL_VERSION: i64 = 1;
+
+/// Largest response read from a plugin.
+pub const MAX_RESPONSE_SIZE = 10 * 1024 * 1024;
+
+pub const PluginError = error{
+    /// The command could not be run or exited with an error.
+    PluginFailed,
+    InvalidResponse,
+    UnsupportedProtocol,
+};
+
+/// A configured plugin.
+pub const Plugin = struct {
+    /// Shown in traces, e.g. "institutional-repository".
+    name: []const u8,
+    /// Program and arguments.
+    command: []const []const u8,
+};
+
+/// Run `plugin` for `query` and return the records it found. Caller owns
+/// the entries.
+pub fn search(allocator: std.mem.Allocator, plugin: Plugin, query: *const Entry) ![]Entry {
+    var request: std.Io.Writer.Allocating = .init(allocator);
+    defer request.deinit();
+    try writeRequest(&request.writer, query);
+
+    const output = try run(allocator, plugin.command, request.written());
+    defer allocator.free(output);
+    return parseResponse(allocator, output);
+}
+
+pub fn writeRequest(w: *std.Io.Writer, query: *const Entry) !void {
+    try w.print("{{\"protocol\":{d},\"query\":", .{PROTOCOL_VERSION});
+    try snapshot.writeEntry(w, query);
+    try w.writeAll("}\n");
+}
+
+pub fn parseResponse(allocator: std.mem.Allocator, output: []const u8) ![]Entry {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, output, .{}) catch return PluginError.InvalidResponse;
+    defer parsed.deinit();
+
+    const root = parsed.value;
+    if (root != .object) return PluginError.InvalidResponse;
+    const version = root.object.get("protocol") orelse return PluginError.InvalidResponse;
+    if (version != .integer or version.integer != PROTOCOL_VERSION) return PluginError.UnsupportedProtocol;
+    const items = root.object.get("entries") orelse return PluginError.InvalidResponse;
+    if (items != .array) return PluginError.InvalidResponse;
+
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |*e| e.deinit();
+        entries.deinit(allocator);
+    }
+    for (items.array.items) |item| {
+        var record = (try snapshot.parseEntry(allocator, item)) orelse continue;
+        errdefer record.deinit();
+        try entries.append(allocator, record);
+    }
+    return entries.toOwnedSlice(allocator);
+}
+
+fn run(allocator: std.mem.Allocator, argv: []const []const u8, input: []const u8) ![]u8 {
+    var child = std.process.Child.init(argv, allocator);
+    child.stdin_behavior = .Pipe;
+    child.stdout_behavior = .Pipe;
+    child.stderr_behavior = .Inherit;
+    child.spawn() catch return PluginError.PluginFailed;
+    errdefer _ = child.kill() catch {};
+
+    // Queries are small, so writing all of it before reading cannot block
+    child.stdin.?.writeAll(input) catch return PluginError.PluginFailed;
+    child.stdin.?.close();
+    child.stdin = null;
+
+    const output = child.stdout.?.readToEndAlloc(allocator, MAX_RESPONSE_SIZE) catch return PluginError.PluginFailed;
+    errdefer allocator.free(output);
+
+    const term = child.wait() catch return PluginError.PluginFailed;
+    if (term != .Exited or term.Exited != 0) return PluginError.PluginFailed;
+    return output;
+}
+
+test "plugin protocol" {
+    const allocator = std.testing.allocator;
+
+    var request: std.Io.Writer.Allocating = .init(allocator);
+    defer request.deinit();
+    const query = Entry{ .key = "smith2020", .entry_type = "article", .title = "Deep Learning", .year = 2020 };
+    try writeRequest(&request.writer, &query);
+    try std.testing.expect(std.mem.startsWith(u8, request.written(), "{\"protocol\":1,\"query\":{\"key\":\"smith2020\""));
+
+    const entries = try parseResponse(allocator,
+        \\{"protocol":1,"entries":[{"entry_type":"article","title":"Deep Learning","authors":["Smith, Jane"],"year":2020,"repository_id":42}]}
+    );
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+    try std.testing.expectEqual(@as(usize, 1), entries.len);
+    try std.testing.expectEqualStrings("Smith, Jane", entries[0].authors[0]);
+
+    try std.testing.expectError(PluginError.UnsupportedProtocol, parseResponse(allocator, "{\"protocol\":2,\"entries\":[]}"));
+    try std.testing.expectError(PluginError.InvalidResponse, parseResponse(allocator, "not json"));
+}
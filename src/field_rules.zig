@@ -0,0 +1,196 @@
+//! House-style field rules from the config.
+//!
+//! Labs and venues have conventions no generic check knows about: every
+//! `@article` needs a DOI, no `url` next to a DOI, keywords in lowercase
+//! separated by semicolons. Each `[[policy.rules]]` table of the config
+//! states one such rule; entries breaking it are reported with the other
+//! policy findings, under BV021 `field-rule`.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const regex = @import("regex.zig");
+const Entry = entry_mod.Entry;
+const Severity = entry_mod.Severity;
+
+pub const FieldRule = struct {
+    /// Shown before the message, e.g. "article-doi".
+    name: []const u8 = "",
+    /// Entry types the rule applies to ("article"); all if empty.
+    types: []const []const u8 = &.{},
+    /// The rule only applies to entries that have all of these fields.
+    when: []const []const u8 = &.{},
+    /// Fields every entry must have.
+    require: []const []const u8 = &.{},
+    /// Fields no entry may have.
+    forbid: []const []const u8 = &.{},
+    /// Field whose value must match `pattern`, when the entry has it.
+    field: ?[]const u8 = null,
+    /// Regular expression (see `regex`), matched anywhere in the value
+    /// unless anchored with `^` and `$`.
+    pattern: ?[]const u8 = null,
+    severity: Severity = .warning,
+    /// Replaces the generated description in the report.
+    message: ?[]const u8 = null,
+
+    /// Whether the rule applies to `e` at all.
+    pub fn appliesTo(self: *const FieldRule, e: *const Entry) bool {
+        if (self.types.len > 0) {
+            const listed = for (self.types) |t| {
+                if (std.ascii.eqlIgnoreCase(std.mem.trimLeft(u8, t, "@"), e.entry_type)) break true;
+            } else false;
+            if (!listed) return false;
+        }
+        for (self.when) |name| {
+            if (!has(e, name)) return false;
+        }
+        return true;
+    }
+
+    /// Report text: the custom message, or one generated from the rule,
+    /// e.g. "@article entries must have doi".
+    pub fn describe(self: *const FieldRule, allocator: std.mem.Allocator) ![]u8 {
+        var text: std.Io.Writer.Allocating = .init(allocator);
+        defer text.deinit();
+        const w = &text.writer;
+
+        if (self.name.len > 0) try w.print("{s}: ", .{self.name});
+        if (self.message) |message| {
+            try w.writeAll(message);
+            return text.toOwnedSlice();
+        }
+
+        if (self.types.len == 0) try w.writeAll("Entries");
+        for (self.types, 0..) |t, i| {
+            if (i > 0) try w.writeAll(if (i + 1 == self.types.len) " and " else ", ");
+            try w.print("@{s}", .{std.mem.trimLeft(u8, t, "@")});
+        }
+        if (self.types.len > 0) try w.writeAll(" entries");
+        if (self.when.len > 0) try writeList(w, " with ", self.when);
+
+        var clauses: usize = 0;
+        if (self.require.len > 0) {
+            try writeList(w, " must have ", self.require);
+            clauses += 1;
+        }
+        if (self.forbid.len > 0) {
+            if (clauses > 0) try w.writeAll(" and");
+            try writeList(w, " must not have ", self.forbid);
+            clauses += 1;
+        }
+        if (self.field) |field| {
+            if (clauses > 0) try w.writeAll(" and");
+            try w.print(" must have {s} matching {s}", .{ field, self.pattern orelse "" });
+        }
+        return text.toOwnedSlice();
+    }
+};
+
+fn writeList(w: *std.Io.Writer, prefix: []const u8, names: []const []const u8) !void {
+    try w.writeAll(prefix);
+    for (names, 0..) |name, i| {
+        if (i > 0) try w.writeAll(", ");
+        try w.writeAll(name);
+    }
+}
+
+/// Keys of the entries among `entries` that break `rule`.
+pub fn check(allocator: std.mem.Allocator, rule: *const FieldRule, entries: []const *const Entry) ![]const []const u8 {
+    var compiled: ?regex.Regex = if (rule.field != null and rule.pattern != null)
+        try regex.Regex.compile(allocator, rule.pattern.?)
+    else
+        null;
+    defer if (compiled) |*re| re.deinit();
+
+    var keys: std.ArrayList([]const u8) = .empty;
+    errdefer keys.deinit(allocator);
+    for (entries) |e| {
+        if (!rule.appliesTo(e)) continue;
+        if (!try satisfies(rule, e, if (compiled) |*re| re else null)) try keys.append(allocator, e.key);
+    }
+    return keys.toOwnedSlice(allocator);
+}
+
+fn satisfies(rule: *const FieldRule, e: *const Entry, pattern: ?*const regex.Regex) !bool {
+    for (rule.require) |name| {
+        if (!has(e, name)) return false;
+    }
+    for (rule.forbid) |name| {
+        if (has(e, name)) return false;
+    }
+    const re = pattern orelse return true;
+    const name = rule.field.?;
+    // Every author must match a pattern on authors
+    if (std.ascii.eqlIgnoreCase(name, "author")) {
+        for (e.authors) |author| {
+            if (!try re.isMatch(author)) return false;
+        }
+        return true;
+    }
+    var buf: [16]u8 = undefined;
+    const value = fieldValue(e, name, &buf) orelse return true;
+    return re.isMatch(value);
+}
+
+fn has(e: *const Entry, name: []const u8) bool {
+    if (std.ascii.eqlIgnoreCase(name, "author")) return e.authors.len > 0;
+    var buf: [16]u8 = undefined;
+    const value = fieldValue(e, name, &buf) orelse return false;
+    return std.mem.trim(u8, value, " \t\r\n").len > 0;
+}
+
+/// Value of the BibTeX field `name` of an entry. Fields bibval reads into
+/// structured form are looked up there; `journal` and `booktitle` are the
+/// venue, and a DOI or arXiv ID found in another field does not count as
+/// a `doi` or `eprint` field.
+fn fieldValue(e: *const Entry, name: []const u8, buf: *[16]u8) ?[]const u8 {
+    const eql = std.ascii.eqlIgnoreCase;
+    if (eql(name, "title")) return e.title;
+    if (eql(name, "year")) {
+        const year = e.year orelse return null;
+        return std.fmt.bufPrint(buf, "{d}", .{year}) catch null;
+    }
+    if (eql(name, "journal") or eql(name, "booktitle") or eql(name, "venue")) return e.venue;
+    if (eql(name, "publisher")) return e.publisher;
+    if (eql(name, "doi")) return if (e.doi_field == null or eql(e.doi_field.?, "doi")) e.doi else null;
+    if (eql(name, "eprint")) return if (e.arxiv_field == null) e.arxiv_id else null;
+    if (eql(name, "primaryclass")) return e.primary_class;
+    if (eql(name, "eid")) return e.scopus_eid orelse e.extraField(name);
+    if (eql(name, "isbn")) return e.isbn;
+    if (eql(name, "url")) return e.url;
+    if (eql(name, "groups")) return e.groups;
+    return e.extraField(name);
+}
+
+test "field rules" {
+    const allocator = std.testing.allocator;
+
+    const article = Entry{ .key = "a", .entry_type = "article", .url = "https://example.org", .doi = "10.1000/x", .extra = &.{.{ .name = "keywords", .value = "Deep Learning" }} };
+    const no_doi = Entry{ .key = "b", .entry_type = "article", .extra = &.{.{ .name = "keywords", .value = "deep-learning; vision" }} };
+    const book = Entry{ .key = "c", .entry_type = "book" };
+    const entries = [_]*const Entry{ &article, &no_doi, &book };
+
+    const doi_rule = FieldRule{ .name = "article-doi", .types = &.{"@article"}, .require = &.{"doi"}, .severity = .@"error" };
+    const missing = try check(allocator, &doi_rule, &entries);
+    defer allocator.free(missing);
+    try std.testing.expectEqual(@as(usize, 1), missing.len);
+    try std.testing.expectEqualStrings("b", missing[0]);
+
+    const url_rule = FieldRule{ .when = &.{"doi"}, .forbid = &.{"url"} };
+    const with_url = try check(allocator, &url_rule, &entries);
+    defer allocator.free(with_url);
+    try std.testing.expectEqual(@as(usize, 1), with_url.len);
+    try std.testing.expectEqualStrings("a", with_url[0]);
+
+    const keyword_rule = FieldRule{ .field = "keywords", .pattern = "^[a-z-]+(; [a-z-]+)*$" };
+    const bad_keywords = try check(allocator, &keyword_rule, &entries);
+    defer allocator.free(bad_keywords);
+    try std.testing.expectEqual(@as(usize, 1), bad_keywords.len);
+    try std.testing.expectEqualStrings("a", bad_keywords[0]);
+
+    const text = try doi_rule.describe(allocator);
+    defer allocator.free(text);
+    try std.testing.expectEqualStrings("article-doi: @article entries must have doi", text);
+    const url_text = try url_rule.describe(allocator);
+    defer allocator.free(url_text);
+    try std.testing.expectEqualStrings("Entries with doi must not have url", url_text);
+}
@@ -0,0 +1,579 @@
+//! Suggested fixes and patch output.
+//!
+//! Turns discrepancies into field edits on the original .bib source and
+//! renders them as a unified diff, leaving the file itself untouched.
+
+const std = @import("std");
+const bibtex = @import("bibtex.zig");
+const report_mod = @import("report.zig");
+const venues = @import("venues.zig");
+const iso4 = @import("iso4.zig");
+const wayback = @import("wayback.zig");
+const punctuation = @import("punctuation.zig");
+const Rule = @import("rules.zig").Rule;
+const EntryReport = report_mod.EntryReport;
+const Entry = @import("entry.zig").Entry;
+
+/// Lines of unchanged context around each hunk.
+pub const CONTEXT_LINES: usize = 3;
+
+/// Preferences for suggested fixes.
+pub const Options = struct {
+    venue_style: venues.Style = .preserve,
+    /// Define `@string` macros for venues used by several entries.
+    string_macros: bool = false,
+    /// Engine quotes, dashes, and spaces are written for.
+    engine: punctuation.Engine = .preserve,
+};
+
+/// A field value to set on an entry.
+pub const Fix = struct {
+    key: []const u8,
+    /// BibTeX field name, e.g. "year".
+    field: []const u8,
+    value: []const u8,
+    /// `value` names an `@string` macro and is written bare.
+    macro: bool = false,
+};
+
+/// A replacement of `content[start..end]` with `text`.
+pub const Edit = struct {
+    start: usize,
+    end: usize,
+    text: []const u8,
+};
+
+/// The BibTeX field a rule's fix is written to, or null if the rule has no
+/// automatic fix.
+pub fn fixField(rule: Rule, entry_type: []const u8) ?[]const u8 {
+    return switch (rule) {
+        .title_difference, .title_style => "title",
+        .year_mismatch => "year",
+        .missing_doi, .misplaced_doi, .wrong_doi, .doi_granularity => "doi",
+        .misplaced_arxiv => "eprint",
+        .venue_mismatch, .venue_variant, .workshop_venue => venueField(entry_type),
+        .missing_award => "funding",
+        .archive_url => wayback.FIELD,
+        .urldate => "urldate",
+        .missing_author => "author",
+        .volume_mismatch => "volume",
+        .pages_mismatch => "pages",
+        // A very different title or author list usually means a wrong match
+        .title_mismatch, .author_count, .first_author, .unknown_funder => null,
+        // Year and venue both change, and the venue's form is the author's
+        .wrong_edition => null,
+        // Which edition is meant is the author's call
+        .book_edition => null,
+        // The release a result came from cannot be told from the entry
+        .unknown_version => null,
+        // Whether the repository moved or was deleted is not known
+        .missing_repository => null,
+        // Policy findings apply to the reference list, not to a field
+        .missing_year, .old_references, .field_rule => null,
+        // A correction is cited alongside the work, if at all
+        .erratum => null,
+        // Whether to cite a new version or drop a retracted work is the
+        // author's call
+        .crossmark_update => null,
+        // Which duplicate or spelling to keep is the author's call
+        .duplicate_key, .duplicate_work, .near_duplicate, .author_variant => null,
+        // Fixed field by field with `consistency.numericFixes`
+        .numeric_field => null,
+        // Fixed field by field with `punctuation.fixes`
+        .punctuation => null,
+        // Screening findings need a different reference, not an edit
+        .blocked_venue, .blocked_publisher, .unlisted_venue => null,
+        // Custom checks do not say which field to write
+        .custom_check => null,
+        // Punctuation and spacing of a title are the author's style
+        .title_punctuation => null,
+        // Which language, and which edition of a translated work, is cited
+        // is the author's call
+        .language_mismatch, .mixed_translation => null,
+    };
+}
+
+/// The field `fixField` writes for `rule` on the entry `e`: a venue or a
+/// year is fixed in the field it was read from.
+pub fn entryFixField(rule: Rule, e: *const Entry) ?[]const u8 {
+    return switch (rule) {
+        .venue_mismatch, .venue_variant, .workshop_venue => e.venue_field orelse fixField(rule, e.entry_type),
+        .year_mismatch => e.year_field orelse fixField(rule, e.entry_type),
+        else => fixField(rule, e.entry_type),
+    };
+}
+
+/// The field an entry type names its venue in, or null for types without
+/// one, such as @book, @misc, or @techreport.
+pub fn venueField(entry_type: []const u8) ?[]const u8 {
+    for ([_][]const u8{ "article", "periodical" }) |t| {
+        if (std.ascii.eqlIgnoreCase(entry_type, t)) return "journal";
+    }
+    for ([_][]const u8{ "inproceedings", "conference", "incollection", "inbook" }) |t| {
+        if (std.ascii.eqlIgnoreCase(entry_type, t)) return "booktitle";
+    }
+    return null;
+}
+
+/// A value for an entry's field and the confidence of the match it comes from.
+pub const Suggestion = struct {
+    value: []const u8,
+    confidence: f64,
+};
+
+/// The DOI to add to an entry without one: that of the most confident match
+/// that has one.
+pub fn suggestedDoi(entry_report: *const EntryReport) ?Suggestion {
+    var best: ?Suggestion = null;
+    for (entry_report.validation_results) |result| {
+        for (result.discrepancies) |d| {
+            if (d.rule != .missing_doi) continue;
+            if (best == null or result.confidence > best.?.confidence) {
+                best = .{ .value = d.remote_value, .confidence = result.confidence };
+            }
+        }
+    }
+    return best;
+}
+
+/// Append the fixes suggested for an entry. Values borrow from `entry_report`
+/// or are allocated with `allocator`. When several sources disagree, the
+/// first result wins, except for a missing DOI, which comes from the most
+/// confident match.
+pub fn suggest(allocator: std.mem.Allocator, entry_report: *const EntryReport, options: Options, fixes: *std.ArrayList(Fix)) !void {
+    const first = fixes.items.len;
+    for (entry_report.validation_results) |result| {
+        for (result.discrepancies) |d| {
+            // A year read from a biblatex date, or a venue from `booktitle`
+            // in an @article, is fixed in that field
+            const field = entryFixField(d.rule, &entry_report.entry) orelse continue;
+            if (containsField(fixes.items[first..], field)) continue;
+            // Only add award numbers to entries without a funding field
+            if (d.rule == .missing_award and entry_report.entry.funding != null) continue;
+            // A wrong DOI is only replaced if a title search found the right
+            // one, a deposit DOI if the record is the cited version, and an
+            // archive URL is only added if the page has a snapshot
+            if ((d.rule == .wrong_doi or d.rule == .doi_granularity or d.rule == .archive_url) and d.remote_value.len == 0) continue;
+
+            var value = switch (d.rule) {
+                .venue_mismatch => try venueFix(allocator, d.remote_value, entry_report.entry.venue, options.venue_style),
+                .missing_doi => suggestedDoi(entry_report).?.value,
+                else => d.remote_value,
+            };
+            // DOIs are written in their canonical form, whatever the source's
+            if (std.mem.eql(u8, field, "doi")) value = try std.ascii.allocLowerString(allocator, bibtex.bareDoi(value));
+
+            try fixes.append(allocator, .{ .key = entry_report.entry.key, .field = field, .value = value });
+            if (d.rule == .misplaced_arxiv) try arxivFields(allocator, &entry_report.entry, fixes);
+        }
+    }
+}
+
+/// The fields that go with an eprint for arXiv to be linked.
+fn arxivFields(allocator: std.mem.Allocator, e: *const Entry, fixes: *std.ArrayList(Fix)) !void {
+    try fixes.append(allocator, .{ .key = e.key, .field = "archiveprefix", .value = "arXiv" });
+    if (e.primary_class) |class| {
+        try fixes.append(allocator, .{ .key = e.key, .field = "primaryclass", .value = class });
+    }
+}
+
+/// The remote venue in the preferred style: the dictionary form for known
+/// venues, otherwise the remote name or its ISO 4 abbreviation.
+fn venueFix(allocator: std.mem.Allocator, remote: []const u8, local: ?[]const u8, style: venues.Style) ![]const u8 {
+    if (venues.lookup(remote)) |known| return known.preferred(style, local);
+
+    const abbreviated = switch (style) {
+        .full => false,
+        .abbreviated => true,
+        .preserve => local != null and venues.looksAbbreviated(local.?),
+    };
+    if (abbreviated) return iso4.abbreviate(allocator, remote);
+    return remote;
+}
+
+fn balancedBraces(value: []const u8) bool {
+    var depth: usize = 0;
+    for (value) |c| {
+        switch (c) {
+            '{' => depth += 1,
+            '}' => {
+                if (depth == 0) return false;
+                depth -= 1;
+            },
+            else => {},
+        }
+    }
+    return depth == 0;
+}
+
+fn containsField(fixes: []const Fix, field: []const u8) bool {
+    for (fixes) |f| {
+        if (std.mem.eql(u8, f.field, field)) return true;
+    }
+    return false;
+}
+
+/// Translate fixes into edits on `content`, sorted by position. Fixes for
+/// entries not present in `content` are skipped. Edit text is allocated
+/// with `allocator`; use an arena.
+pub fn editsFor(allocator: std.mem.Allocator, content: []const u8, fixes: []const Fix) ![]Edit {
+    var edits: std.ArrayList(Edit) = .empty;
+    errdefer edits.deinit(allocator);
+
+    for (fixes) |f| {
+        // An unbalanced brace in the value would break the entry
+        if (!balancedBraces(f.value)) continue;
+
+        const entry_span = bibtex.findEntry(content, f.key) orelse continue;
+        if (bibtex.findField(content, entry_span, f.field)) |field| {
+            try edits.append(allocator, .{
+                .start = field.value.start,
+                .end = field.value.end,
+                .text = if (f.macro) f.value else try formatValue(allocator, f.value, field.style),
+            });
+        } else {
+            try edits.append(allocator, try insertField(allocator, content, entry_span, f));
+        }
+    }
+
+    std.mem.sort(Edit, edits.items, {}, lessThan);
+    return edits.toOwnedSlice(allocator);
+}
+
+fn lessThan(_: void, a: Edit, b: Edit) bool {
+    return a.start < b.start;
+}
+
+/// Format a value in the style of the one it replaces.
+fn formatValue(allocator: std.mem.Allocator, value: []const u8, style: bibtex.ValueStyle) ![]const u8 {
+    return switch (style) {
+        .quoted => if (std.mem.indexOfScalar(u8, value, '"') == null)
+            std.fmt.allocPrint(allocator, "\"{s}\"", .{value})
+        else
+            std.fmt.allocPrint(allocator, "{{{s}}}", .{value}),
+        .bare => if (isNumber(value))
+            allocator.dupe(u8, value)
+        else
+            std.fmt.allocPrint(allocator, "{{{s}}}", .{value}),
+        .braced, .concatenated => std.fmt.allocPrint(allocator, "{{{s}}}", .{value}),
+    };
+}
+
+fn isNumber(s: []const u8) bool {
+    if (s.len == 0) return false;
+    for (s) |c| {
+        if (!std.ascii.isDigit(c)) return false;
+    }
+    return true;
+}
+
+/// Add a new field after the last field of an entry, matching its indentation.
+fn insertField(allocator: std.mem.Allocator, content: []const u8, entry_span: bibtex.Span, f: Fix) !Edit {
+    var last = entry_span.end;
+    while (last > entry_span.start and std.ascii.isWhitespace(content[last - 1])) last -= 1;
+    const trailing_comma = content[last - 1] == ',';
+    const value = if (f.macro) f.value else try std.fmt.allocPrint(allocator, "{{{s}}}", .{f.value});
+
+    const body = content[entry_span.start..entry_span.end];
+    const text = if (std.mem.indexOfScalar(u8, body, '\n')) |nl| blk: {
+        const line = body[nl + 1 ..];
+        var indent_len: usize = 0;
+        while (indent_len < line.len and (line[indent_len] == ' ' or line[indent_len] == '\t')) indent_len += 1;
+        const indent = if (indent_len > 0) line[0..indent_len] else "  ";
+        break :blk try std.fmt.allocPrint(allocator, "{s}\n{s}{s} = {s}{s}", .{
+            if (trailing_comma) "" else ",",
+            indent,
+            f.field,
+            value,
+            if (trailing_comma) "," else "",
+        });
+    } else try std.fmt.allocPrint(allocator, "{s} {s} = {s}", .{ if (trailing_comma) "" else ",", f.field, value });
+
+    return .{ .start = last, .end = last, .text = text };
+}
+
+/// Apply sorted, non-overlapping edits and return the new content.
+pub fn apply(allocator: std.mem.Allocator, content: []const u8, edits: []const Edit) ![]u8 {
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+
+    var pos: usize = 0;
+    for (edits) |edit| {
+        try out.appendSlice(allocator, content[pos..edit.start]);
+        try out.appendSlice(allocator, edit.text);
+        pos = edit.end;
+    }
+    try out.appendSlice(allocator, content[pos..]);
+    return out.toOwnedSlice(allocator);
+}
+
+/// A run of changed lines: old lines `[first_line, end_line)` are replaced
+/// by the edits `[first_edit, end_edit)`.
+const Change = struct {
+    first_line: usize,
+    end_line: usize,
+    first_edit: usize,
+    end_edit: usize,
+};
+
+/// Write sorted, non-overlapping edits to `content` as a unified diff
+/// against `path`. Nothing is written when there are no edits.
+pub fn writePatch(allocator: std.mem.Allocator, w: *std.Io.Writer, path: []const u8, content: []const u8, edits: []const Edit) !void {
+    if (edits.len == 0) return;
+
+    var line_starts: std.ArrayList(usize) = .empty;
+    defer line_starts.deinit(allocator);
+    try line_starts.append(allocator, 0);
+    for (content, 0..) |c, i| {
+        if (c == '\n' and i + 1 < content.len) try line_starts.append(allocator, i + 1);
+    }
+    const lines = Lines{ .content = content, .starts = line_starts.items };
+
+    // Group edits touching the same lines
+    var changes: std.ArrayList(Change) = .empty;
+    defer changes.deinit(allocator);
+    for (edits, 0..) |edit, i| {
+        const first_line = lines.lineOf(edit.start);
+        const end_line = lines.lineOf(if (edit.end > edit.start) edit.end - 1 else edit.start) + 1;
+        if (changes.items.len > 0 and first_line < changes.items[changes.items.len - 1].end_line) {
+            const last = &changes.items[changes.items.len - 1];
+            last.end_line = @max(last.end_line, end_line);
+            last.end_edit = i + 1;
+        } else {
+            try changes.append(allocator, .{ .first_line = first_line, .end_line = end_line, .first_edit = i, .end_edit = i + 1 });
+        }
+    }
+
+    try w.print("--- a/{s}\n+++ b/{s}\n", .{ path, path });
+
+    var aw: std.Io.Writer.Allocating = .init(allocator);
+    defer aw.deinit();
+
+    // Line offset of the new file relative to the old one
+    var delta: isize = 0;
+    var i: usize = 0;
+    while (i < changes.items.len) {
+        // Merge changes whose context would overlap into one hunk
+        var j = i + 1;
+        while (j < changes.items.len and changes.items[j].first_line - changes.items[j - 1].end_line <= 2 * CONTEXT_LINES) j += 1;
+        const hunk = changes.items[i..j];
+
+        const old_start = hunk[0].first_line -| CONTEXT_LINES;
+        const old_end = @min(lines.count(), hunk[hunk.len - 1].end_line + CONTEXT_LINES);
+
+        aw.clearRetainingCapacity();
+        var new_count: usize = 0;
+        var line = old_start;
+        for (hunk) |change| {
+            while (line < change.first_line) : (line += 1) {
+                try writeLine(&aw.writer, ' ', lines.get(line));
+            }
+            while (line < change.end_line) : (line += 1) {
+                try writeLine(&aw.writer, '-', lines.get(line));
+            }
+
+            const new_text = try changedText(allocator, lines, change, edits);
+            defer allocator.free(new_text);
+            var rest: []const u8 = new_text;
+            while (rest.len > 0) {
+                const len = if (std.mem.indexOfScalar(u8, rest, '\n')) |nl| nl + 1 else rest.len;
+                try writeLine(&aw.writer, '+', rest[0..len]);
+                rest = rest[len..];
+                new_count += 1;
+            }
+        }
+        while (line < old_end) : (line += 1) {
+            try writeLine(&aw.writer, ' ', lines.get(line));
+        }
+
+        var changed_old: usize = 0;
+        for (hunk) |change| changed_old += change.end_line - change.first_line;
+        const old_count = old_end - old_start;
+        const new_total = old_count - changed_old + new_count;
+        const new_start: usize = @intCast(@as(isize, @intCast(old_start)) + delta);
+
+        try w.print("@@ -{d},{d} +{d},{d} @@\n", .{ old_start + 1, old_count, new_start + 1, new_total });
+        try w.writeAll(aw.written());
+
+        delta += @as(isize, @intCast(new_count)) - @as(isize, @intCast(changed_old));
+        i = j;
+    }
+}
+
+/// New text for the lines covered by `change`.
+fn changedText(allocator: std.mem.Allocator, lines: Lines, change: Change, edits: []const Edit) ![]u8 {
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+
+    var pos = lines.starts[change.first_line];
+    for (edits[change.first_edit..change.end_edit]) |edit| {
+        try out.appendSlice(allocator, lines.content[pos..edit.start]);
+        try out.appendSlice(allocator, edit.text);
+        pos = edit.end;
+    }
+    try out.appendSlice(allocator, lines.content[pos..lines.end(change.end_line - 1)]);
+    return out.toOwnedSlice(allocator);
+}
+
+const Lines = struct {
+    content: []const u8,
+    starts: []const usize,
+
+    fn count(self: Lines) usize {
+        return self.starts.len;
+    }
+
+    /// Index of the line containing byte `pos`.
+    fn lineOf(self: Lines, pos: usize) usize {
+        var lo: usize = 0;
+        var hi: usize = self.starts.len;
+        while (hi - lo > 1) {
+            const mid = lo + (hi - lo) / 2;
+            if (self.starts[mid] <= pos) lo = mid else hi = mid;
+        }
+        return lo;
+    }
+
+    /// End of line `n`, including its newline.
+    fn end(self: Lines, n: usize) usize {
+        return if (n + 1 < self.starts.len) self.starts[n + 1] else self.content.len;
+    }
+
+    fn get(self: Lines, n: usize) []const u8 {
+        return self.content[self.starts[n]..self.end(n)];
+    }
+};
+
+fn writeLine(w: *std.Io.Writer, prefix: u8, line: []const u8) !void {
+    try w.writeByte(prefix);
+    try w.writeAll(line);
+    if (line.len == 0 or line[line.len - 1] != '\n') {
+        try w.writeAll("\n\\ No newline at end of file\n");
+    }
+}
+
+test "patch replaces and inserts fields" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    const bib =
+        \\@article{smith2021,
+        \\  title = {A Great Paper},
+        \\  year = {2021}
+        \\}
+        \\
+    ;
+
+    const fixes = [_]Fix{
+        .{ .key = "smith2021", .field = "year", .value = "2020" },
+        .{ .key = "smith2021", .field = "doi", .value = "10.1234/example" },
+        .{ .key = "missing", .field = "year", .value = "1999" },
+    };
+    const edits = try editsFor(allocator, bib, &fixes);
+    try std.testing.expectEqual(@as(usize, 2), edits.len);
+
+    const fixed = try apply(allocator, bib, edits);
+    try std.testing.expectEqualStrings(
+        \\@article{smith2021,
+        \\  title = {A Great Paper},
+        \\  year = {2020},
+        \\  doi = {10.1234/example}
+        \\}
+        \\
+    , fixed);
+
+    var aw: std.Io.Writer.Allocating = .init(allocator);
+    try writePatch(allocator, &aw.writer, "refs.bib", bib, edits);
+    try std.testing.expectEqualStrings(
+        \\--- a/refs.bib
+        \\+++ b/refs.bib
+        \\@@ -1,4 +1,5 @@
+        \\ @article{smith2021,
+        \\   title = {A Great Paper},
+        \\-  year = {2021}
+        \\+  year = {2020},
+        \\+  doi = {10.1234/example}
+        \\ }
+        \\
+    , aw.written());
+}
+
+test "missing DOI comes from the most confident match, written canonically" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+    const entry_mod = @import("entry.zig");
+
+    var weak = [_]entry_mod.Discrepancy{.{ .rule = .missing_doi, .field = .doi, .severity = .warning, .local_value = "(none)", .remote_value = "10.1/preprint", .message = "Missing DOI in local entry" }};
+    var strong = [_]entry_mod.Discrepancy{.{ .rule = .missing_doi, .field = .doi, .severity = .warning, .local_value = "(none)", .remote_value = "https://doi.org/10.1/Paper", .message = "Missing DOI in local entry" }};
+    var results = [_]entry_mod.ValidationResult{
+        .{ .source = .semantic_scholar, .confidence = 0.8, .discrepancies = &weak },
+        .{ .source = .crossref, .confidence = 0.97, .discrepancies = &strong },
+    };
+    const entry_report = EntryReport{ .entry = .{ .key = "smith2021", .entry_type = "article" }, .status = .warning, .validation_results = &results };
+
+    const doi = suggestedDoi(&entry_report).?;
+    try std.testing.expectEqualStrings("https://doi.org/10.1/Paper", doi.value);
+    try std.testing.expectEqual(@as(f64, 0.97), doi.confidence);
+
+    var fixes: std.ArrayList(Fix) = .empty;
+    try suggest(allocator, &entry_report, .{}, &fixes);
+    try std.testing.expectEqual(@as(usize, 1), fixes.items.len);
+    try std.testing.expectEqualStrings("10.1/paper", fixes.items[0].value);
+}
+
+test "venue fixes go to the field the venue was read from" {
+    const article = Entry{ .key = "a", .entry_type = "article", .venue = "NeurIPS" };
+    try std.testing.expectEqualStrings("journal", entryFixField(.venue_mismatch, &article).?);
+    const misfiled = Entry{ .key = "b", .entry_type = "article", .venue = "NeurIPS", .venue_field = "booktitle" };
+    try std.testing.expectEqualStrings("booktitle", entryFixField(.venue_mismatch, &misfiled).?);
+    try std.testing.expectEqualStrings("booktitle", entryFixField(.workshop_venue, &misfiled).?);
+    const paper = Entry{ .key = "c", .entry_type = "InProceedings", .venue = "NeurIPS" };
+    try std.testing.expectEqualStrings("booktitle", entryFixField(.venue_mismatch, &paper).?);
+    const report = Entry{ .key = "d", .entry_type = "techreport" };
+    try std.testing.expect(entryFixField(.venue_mismatch, &report) == null);
+    try std.testing.expect(entryFixField(.venue_variant, &Entry{ .key = "e", .entry_type = "misc" }) == null);
+}
+
+test "wrong DOI is replaced only by the one a title search found" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+    const entry_mod = @import("entry.zig");
+
+    const bib =
+        \\@article{lee2019,
+        \\  title = {Graph Attention Networks},
+        \\  doi = {10.1/other}
+        \\}
+        \\
+    ;
+
+    var unresolved = [_]entry_mod.Discrepancy{.{ .rule = .wrong_doi, .field = .doi, .severity = .@"error", .local_value = "10.1/other", .remote_value = "", .message = "DOI points to a different work: \"Other\"" }};
+    var unresolved_results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .confidence = 1.0, .discrepancies = &unresolved }};
+    const unresolved_report = EntryReport{ .entry = .{ .key = "lee2019", .entry_type = "article", .doi = "10.1/other" }, .status = .@"error", .validation_results = &unresolved_results };
+    var fixes: std.ArrayList(Fix) = .empty;
+    try suggest(allocator, &unresolved_report, .{}, &fixes);
+    try std.testing.expectEqual(@as(usize, 0), fixes.items.len);
+
+    var found = [_]entry_mod.Discrepancy{.{ .rule = .wrong_doi, .field = .doi, .severity = .@"error", .local_value = "10.1/other", .remote_value = "https://doi.org/10.1/GAT", .message = "DOI points to a different work (\"Other\"); the matched record has 10.1/GAT" }};
+    var found_results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .confidence = 1.0, .discrepancies = &found }};
+    const found_report = EntryReport{ .entry = .{ .key = "lee2019", .entry_type = "article", .doi = "10.1/other" }, .status = .@"error", .validation_results = &found_results };
+    try suggest(allocator, &found_report, .{}, &fixes);
+    try std.testing.expectEqual(@as(usize, 1), fixes.items.len);
+
+    var aw: std.Io.Writer.Allocating = .init(allocator);
+    try writePatch(allocator, &aw.writer, "refs.bib", bib, try editsFor(allocator, bib, fixes.items));
+    try std.testing.expectEqualStrings(
+        \\--- a/refs.bib
+        \\+++ b/refs.bib
+        \\@@ -1,4 +1,4 @@
+        \\ @article{lee2019,
+        \\   title = {Graph Attention Networks},
+        \\-  doi = {10.1/other}
+        \\+  doi = {10.1/gat}
+        \\ }
+        \\
+    , aw.written());
+}
@@ -0,0 +1,392 @@
+//! Plain-text reference parser.
+//!
+//! Heuristically splits formatted reference strings, as pasted from a
+//! paper's reference list, into entries. APA-style ("Smith, J. (2020).
+//! Title. Journal, 12(3), 1–10."), IEEE-style ("[1] J. Smith, "Title,"
+//! Journal, 2020.") and similar layouts are recognized well enough to find
+//! the title, authors, year, venue, and DOI for matching.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+/// Parse a file with one reference per line or per numbered item.
+pub fn parseFile(allocator: std.mem.Allocator, path: []const u8) ![]Entry {
+    const file = try std.fs.cwd().openFile(path, .{});
+    defer file.close();
+
+    const content = try file.readToEndAlloc(allocator, 10 * 1024 * 1024);
+    defer allocator.free(content);
+
+    return try parseString(allocator, content);
+}
+
+/// Parse a reference list. References start at numbered lines ("[1]",
+/// "1.") when present; otherwise blank lines separate references, or
+/// each line is one reference.
+pub fn parseString(allocator: std.mem.Allocator, content: []const u8) ![]Entry {
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |*e| e.deinit();
+        entries.deinit(allocator);
+    }
+
+    var numbered = false;
+    var blank_separated = false;
+    var scan = std.mem.splitScalar(u8, content, '\n');
+    var previous_blank = false;
+    while (scan.next()) |raw| {
+        const line = std.mem.trim(u8, raw, " \t\r");
+        if (line.len == 0) {
+            previous_blank = true;
+            continue;
+        }
+        if (numberEnd(line) != null) numbered = true;
+        if (previous_blank) blank_separated = true;
+        previous_blank = false;
+    }
+
+    var current: std.ArrayList(u8) = .empty;
+    defer current.deinit(allocator);
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        const line = std.mem.trim(u8, raw, " \t\r");
+        const starts_new = if (numbered)
+            numberEnd(line) != null
+        else if (blank_separated)
+            line.len == 0
+        else
+            true;
+
+        if (starts_new) try flush(allocator, &entries, &current);
+        if (line.len == 0) continue;
+        if (current.items.len > 0) try current.append(allocator, ' ');
+        try current.appendSlice(allocator, line);
+    }
+    try flush(allocator, &entries, &current);
+
+    return entries.toOwnedSlice(allocator);
+}
+
+fn flush(allocator: std.mem.Allocator, entries: *std.ArrayList(Entry), current: *std.ArrayList(u8)) !void {
+    defer current.clearRetainingCapacity();
+    if (current.items.len == 0) return;
+
+    var parsed = try parseReference(allocator, current.items, entries.items);
+    errdefer parsed.deinit();
+    if (parsed.title == null) {
+        parsed.deinit();
+        return;
+    }
+    try entries.append(allocator, parsed);
+}
+
+/// Parse a single reference string. `existing` is used to keep keys unique.
+pub fn parseReference(allocator: std.mem.Allocator, reference: []const u8, existing: []const Entry) !Entry {
+    var result = Entry{ .key = "", .entry_type = "", .allocator = allocator };
+    errdefer result.deinit();
+
+    var rest = std.mem.trim(u8, reference, " \t");
+    if (numberEnd(rest)) |end| rest = std.mem.trimLeft(u8, rest[end..], " \t");
+
+    if (findDoi(rest)) |doi| result.doi = try allocator.dupe(u8, doi);
+    if (findArxiv(rest)) |id| result.arxiv_id = try allocator.dupe(u8, id);
+
+    var authors_text: []const u8 = "";
+    var title: ?[]const u8 = null;
+    var after_title: []const u8 = "";
+
+    if (findQuoted(rest)) |quoted| {
+        // IEEE/MLA: authors, "Title," Venue, ...
+        authors_text = rest[0..quoted.start];
+        title = quoted.text;
+        after_title = rest[quoted.end..];
+    } else if (findParenYear(rest)) |paren| {
+        // APA: Authors (2020). Title. Venue, ...
+        authors_text = rest[0..paren.start];
+        result.year = paren.year;
+        const tail = std.mem.trimLeft(u8, rest[paren.end..], " .");
+        const end = sentenceEnd(tail);
+        title = tail[0..end];
+        after_title = tail[end..];
+    } else {
+        // Authors. Title. Venue ... Year
+        const first = sentenceEnd(rest);
+        authors_text = rest[0..first];
+        const tail = std.mem.trimLeft(u8, rest[first..], " .");
+        const end = sentenceEnd(tail);
+        title = tail[0..end];
+        after_title = tail[end..];
+    }
+
+    if (title) |t| {
+        const trimmed = std.mem.trim(u8, t, " .,;:\"\u{201C}\u{201D}");
+        if (trimmed.len > 0) result.title = try allocator.dupe(u8, trimmed);
+    }
+
+    if (result.year == null) result.year = lastYear(rest);
+
+    const venue = venueOf(after_title);
+    if (venue.len > 0) result.venue = try allocator.dupe(u8, venue);
+    result.entry_type = try allocator.dupe(u8, if (result.venue != null) "article" else "misc");
+
+    result.authors = try splitAuthors(allocator, authors_text);
+    result.key = try entry_mod.generateKey(allocator, &result, existing);
+
+    return result;
+}
+
+/// End of a leading "[12]", "12." or "12)" marker. Longer numbers are
+/// taken to be years.
+fn numberEnd(line: []const u8) ?usize {
+    if (line.len > 2 and line[0] == '[') {
+        const close = std.mem.indexOfScalar(u8, line, ']') orelse return null;
+        for (line[1..close]) |c| {
+            if (!std.ascii.isDigit(c)) return null;
+        }
+        return if (close > 1) close + 1 else null;
+    }
+    var i: usize = 0;
+    while (i < line.len and i < 3 and std.ascii.isDigit(line[i])) i += 1;
+    if (i == 0 or i >= line.len) return null;
+    if ((line[i] == '.' or line[i] == ')') and i + 1 < line.len and line[i + 1] == ' ') return i + 1;
+    return null;
+}
+
+const Quoted = struct {
+    start: usize,
+    end: usize,
+    text: []const u8,
+};
+
+/// The first "double-quoted" or “curly-quoted” span.
+fn findQuoted(s: []const u8) ?Quoted {
+    const pairs = [_][2][]const u8{ .{ "\"", "\"" }, .{ "\u{201C}", "\u{201D}" } };
+    for (pairs) |pair| {
+        const open = std.mem.indexOf(u8, s, pair[0]) orelse continue;
+        const text_start = open + pair[0].len;
+        const close = std.mem.indexOfPos(u8, s, text_start, pair[1]) orelse continue;
+        if (close - text_start < 4) continue;
+        return .{ .start = open, .end = close + pair[1].len, .text = s[text_start..close] };
+    }
+    return null;
+}
+
+const ParenYear = struct {
+    start: usize,
+    end: usize,
+    year: i32,
+};
+
+/// An APA-style "(2020)" or "(2020a)" or "(2020, May 3)".
+fn findParenYear(s: []const u8) ?ParenYear {
+    var pos: usize = 0;
+    while (std.mem.indexOfScalarPos(u8, s, pos, '(')) |open| {
+        pos = open + 1;
+        if (open + 5 >= s.len) return null;
+        const year = parseYear(s[open + 1 .. open + 5]) orelse continue;
+        const close = std.mem.indexOfScalarPos(u8, s, open, ')') orelse return null;
+        if (close - open > 20) continue;
+        return .{ .start = open, .end = close + 1, .year = year };
+    }
+    return null;
+}
+
+fn parseYear(s: []const u8) ?i32 {
+    if (s.len != 4 or !(std.mem.startsWith(u8, s, "19") or std.mem.startsWith(u8, s, "20"))) return null;
+    return std.fmt.parseInt(i32, s, 10) catch null;
+}
+
+/// The last standalone 19xx/20xx number.
+fn lastYear(s: []const u8) ?i32 {
+    var year: ?i32 = null;
+    var i: usize = 0;
+    while (i + 4 <= s.len) : (i += 1) {
+        const before_ok = i == 0 or !std.ascii.isAlphanumeric(s[i - 1]);
+        const after_ok = i + 4 == s.len or !std.ascii.isDigit(s[i + 4]);
+        if (before_ok and after_ok) {
+            if (parseYear(s[i .. i + 4])) |y| year = y;
+        }
+    }
+    return year;
+}
+
+/// Offset of the first ". " that ends a sentence rather than an initial
+/// ("J. Smith") or abbreviation ("Proc. ").
+fn sentenceEnd(s: []const u8) usize {
+    var i: usize = 0;
+    while (i < s.len) : (i += 1) {
+        if (s[i] != '.' and s[i] != '?' and s[i] != '!') continue;
+        if (i + 1 < s.len and s[i + 1] != ' ') continue;
+        var word_start = i;
+        while (word_start > 0 and s[word_start - 1] != ' ' and s[word_start - 1] != '.') word_start -= 1;
+        if (s[i] == '.' and i - word_start <= 1) continue;
+        return i;
+    }
+    return s.len;
+}
+
+/// The venue: text after the title up to the first comma or period that
+/// is followed by volume, page, or year information.
+fn venueOf(after_title: []const u8) []const u8 {
+    var s = std.mem.trimLeft(u8, after_title, " .,;\"\u{201D}");
+    if (std.ascii.startsWithIgnoreCase(s, "in ")) s = s[3..];
+    if (std.ascii.startsWithIgnoreCase(s, "doi") or std.ascii.startsWithIgnoreCase(s, "http")) return "";
+
+    var end: usize = 0;
+    while (end < s.len) : (end += 1) {
+        const c = s[end];
+        if (c == ',' or c == '(' or c == ':') break;
+        if (c == '.' and end + 1 < s.len and s[end + 1] == ' ' and !isAbbreviation(s, end)) break;
+        if (std.ascii.isDigit(c) and end > 0 and s[end - 1] == ' ') break;
+    }
+    const venue = std.mem.trim(u8, s[0..end], " .;");
+    return if (venue.len >= 3 and std.ascii.isUpper(venue[0])) venue else "";
+}
+
+/// Whether the period at `dot` ends a short abbreviated word ("Proc.", "J.").
+fn isAbbreviation(s: []const u8, dot: usize) bool {
+    var start = dot;
+    while (start > 0 and s[start - 1] != ' ') start -= 1;
+    return dot - start <= 6 and std.ascii.isUpper(s[start]);
+}
+
+fn findDoi(s: []const u8) ?[]const u8 {
+    var pos: usize = 0;
+    while (std.mem.indexOfPos(u8, s, pos, "10.")) |start| {
+        pos = start + 3;
+        if (start > 0 and std.ascii.isDigit(s[start - 1])) continue;
+        var end = start;
+        while (end < s.len and !std.ascii.isWhitespace(s[end])) end += 1;
+        const doi = std.mem.trimRight(u8, s[start..end], ".,;)");
+        if (std.mem.indexOfScalar(u8, doi, '/') != null and doi.len > 7) return doi;
+    }
+    return null;
+}
+
+fn findArxiv(s: []const u8) ?[]const u8 {
+    const idx = std.ascii.indexOfIgnoreCase(s, "arXiv:") orelse return null;
+    var end = idx + 6;
+    while (end < s.len and (std.ascii.isAlphanumeric(s[end]) or s[end] == '.' or s[end] == '/' or s[end] == '-')) end += 1;
+    const id = std.mem.trimRight(u8, s[idx + 6 .. end], ".");
+    return if (id.len > 0) id else null;
+}
+
+/// Split "Smith, J., Doe, A., & Lee, K." or "J. Smith, A. Doe, and K. Lee".
+fn splitAuthors(allocator: std.mem.Allocator, text: []const u8) ![]const []const u8 {
+    const trimmed = std.mem.trim(u8, text, " ,");
+    if (trimmed.len == 0) return &.{};
+
+    var parts: std.ArrayList([]const u8) = .empty;
+    defer parts.deinit(allocator);
+
+    var it = std.mem.tokenizeAny(u8, trimmed, ",&;");
+    while (it.next()) |raw| {
+        var part = std.mem.trim(u8, raw, " ");
+        if (std.mem.startsWith(u8, part, "and ")) part = std.mem.trimLeft(u8, part[4..], " ");
+        if (std.mem.eql(u8, part, "et al.") or std.mem.eql(u8, part, "et al") or part.len == 0) continue;
+        // "A and B" without commas
+        if (std.mem.indexOf(u8, part, " and ")) |and_pos| {
+            try parts.append(allocator, part[0..and_pos]);
+            part = part[and_pos + 5 ..];
+        }
+        try parts.append(allocator, part);
+    }
+
+    var authors: std.ArrayList([]const u8) = .empty;
+    errdefer {
+        for (authors.items) |a| allocator.free(a);
+        authors.deinit(allocator);
+    }
+
+    var i: usize = 0;
+    while (i < parts.items.len) : (i += 1) {
+        const part = parts.items[i];
+        // "Smith" followed by "J." is one "Last, Initials" name
+        if (i + 1 < parts.items.len and !isInitials(part) and isInitials(parts.items[i + 1]) and std.mem.indexOfScalar(u8, part, ' ') == null) {
+            try authors.append(allocator, try std.fmt.allocPrint(allocator, "{s}, {s}", .{ part, parts.items[i + 1] }));
+            i += 1;
+        } else if (!isInitials(part)) {
+            try authors.append(allocator, try allocator.dupe(u8, part));
+        }
+    }
+
+    return authors.toOwnedSlice(allocator);
+}
+
+/// Whether a name part consists only of initials, e.g. "J.", "J.-P.", "A. B.",
+/// "JP". Short surnames such as "Li" or "Ng" are not: without periods,
+/// initials must be uppercase.
+fn isInitials(s: []const u8) bool {
+    if (s.len == 0) return false;
+    var letters: usize = 0;
+    var lower = false;
+    for (s) |c| {
+        if (std.ascii.isAlphabetic(c)) {
+            letters += 1;
+            if (letters > 2) return false;
+            if (std.ascii.isLower(c)) lower = true;
+        } else if (c == '.' or c == ' ' or c == '-') {
+            letters = 0;
+        } else {
+            return false;
+        }
+    }
+    if (std.mem.endsWith(u8, s, ".")) return true;
+    return !lower and s.len <= 2;
+}
+
+test "parse APA and IEEE references" {
+    const allocator = std.testing.allocator;
+
+    const entries = try parseString(allocator,
+        \\[1] A. Vaswani, N. Shazeer, and N. Parmar, "Attention is all you need," in Advances in Neural Information Processing Systems, 2017, pp. 5998-6008.
+        \\[2] Smith, J., & Doe, A. (2020). Learning to parse references. Journal of Machine Learning Research, 21(3), 1-10. https://doi.org/10.1234/jmlr.2020.42
+        \\[3] Smith, J. (2020). Another paper. Neural Computation, 4, 1-2.
+    );
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 3), entries.len);
+
+    try std.testing.expectEqualStrings("Attention is all you need", entries[0].title.?);
+    try std.testing.expectEqual(@as(usize, 3), entries[0].authors.len);
+    try std.testing.expectEqualStrings("N. Parmar", entries[0].authors[2]);
+    try std.testing.expectEqualStrings("Advances in Neural Information Processing Systems", entries[0].venue.?);
+    try std.testing.expectEqual(@as(i32, 2017), entries[0].year.?);
+    try std.testing.expectEqualStrings("vaswani2017", entries[0].key);
+
+    try std.testing.expectEqualStrings("Learning to parse references", entries[1].title.?);
+    try std.testing.expectEqualStrings("Smith, J.", entries[1].authors[0]);
+    try std.testing.expectEqualStrings("Doe, A.", entries[1].authors[1]);
+    try std.testing.expectEqualStrings("Journal of Machine Learning Research", entries[1].venue.?);
+    try std.testing.expectEqualStrings("10.1234/jmlr.2020.42", entries[1].doi.?);
+    try std.testing.expectEqual(@as(i32, 2020), entries[1].year.?);
+
+    try std.testing.expectEqualStrings("smith2020a", entries[2].key);
+}
+
+test "short surnames are not initials" {
+    const allocator = std.testing.allocator;
+
+    const authors = try splitAuthors(allocator, "Li, Wu, Xu, and Ng");
+    defer {
+        for (authors) |a| allocator.free(a);
+        allocator.free(authors);
+    }
+    try std.testing.expectEqual(@as(usize, 4), authors.len);
+    try std.testing.expectEqualStrings("Li", authors[0]);
+    try std.testing.expectEqualStrings("Ng", authors[3]);
+
+    const named = try splitAuthors(allocator, "Li, X., Wu, JP");
+    defer {
+        for (named) |a| allocator.free(a);
+        allocator.free(named);
+    }
+    try std.testing.expectEqual(@as(usize, 2), named.len);
+    try std.testing.expectEqualStrings("Li, X.", named[0]);
+    try std.testing.expectEqualStrings("Wu, JP", named[1]);
+}
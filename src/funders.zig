@@ -0,0 +1,182 @@
+//! Funding metadata checks.
+//!
+//! Verifies funder names in an entry's funding acknowledgement against the
+//! Crossref Funder Registry, and reports award numbers that the matched
+//! Crossref record lists but the entry omits.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const validators = @import("validators.zig");
+const Entry = entry_mod.Entry;
+const Discrepancy = entry_mod.Discrepancy;
+
+/// Minimum similarity between a local funder name and a registry name.
+pub const FUNDER_MATCH_THRESHOLD: f64 = 0.92;
+
+/// A funder named in a funding acknowledgement. Slices point into the
+/// parsed text.
+pub const Funder = struct {
+    name: []const u8,
+    awards: []const []const u8,
+};
+
+/// Words that end a funder name and introduce award numbers.
+const award_words = [_][]const u8{ "grant", "grants", "award", "awards", "under", "no", "nos", "number", "numbers", "contract", "project" };
+
+/// Split funding text such as "Supported by the National Science
+/// Foundation grant CCF-1234567; European Research Council" into funders.
+pub fn parse(allocator: std.mem.Allocator, text: []const u8) ![]Funder {
+    var funders: std.ArrayList(Funder) = .empty;
+    errdefer {
+        for (funders.items) |f| allocator.free(f.awards);
+        funders.deinit(allocator);
+    }
+
+    var segments = std.mem.tokenizeAny(u8, text, ";\n");
+    while (segments.next()) |raw| {
+        var segment = std.mem.trim(u8, raw, " \t\r");
+
+        // Drop a leading "supported by" / "funded by" phrase
+        if (std.ascii.indexOfIgnoreCase(segment, " by ")) |by| {
+            const lead = segment[0..by];
+            if (std.ascii.indexOfIgnoreCase(lead, "support") != null or std.ascii.indexOfIgnoreCase(lead, "fund") != null) {
+                segment = segment[by + 4 ..];
+            }
+        }
+
+        var name_end = segment.len;
+        var awards: std.ArrayList([]const u8) = .empty;
+        errdefer awards.deinit(allocator);
+
+        var tokens = std.mem.tokenizeAny(u8, segment, " \t\r");
+        while (tokens.next()) |token| {
+            const word = std.mem.trim(u8, token, ",.:()#");
+            const offset = @intFromPtr(token.ptr) - @intFromPtr(segment.ptr);
+            if (containsDigit(word)) {
+                name_end = @min(name_end, offset);
+                try awards.append(allocator, word);
+            } else if (isAwardWord(word) or token[0] == '(' or token[0] == '#') {
+                name_end = @min(name_end, offset);
+            }
+        }
+
+        var name = std.mem.trim(u8, segment[0..name_end], " \t,.:-");
+        if (std.ascii.startsWithIgnoreCase(name, "the ")) name = name[4..];
+        if (name.len < 3) {
+            awards.deinit(allocator);
+            continue;
+        }
+
+        try funders.append(allocator, .{ .name = name, .awards = try awards.toOwnedSlice(allocator) });
+    }
+
+    return funders.toOwnedSlice(allocator);
+}
+
+pub fn free(allocator: std.mem.Allocator, funders: []const Funder) void {
+    for (funders) |f| allocator.free(f.awards);
+    allocator.free(funders);
+}
+
+/// Check an entry's funding against the registry and, when available, the
+/// funding listed on its matched Crossref record.
+pub fn check(allocator: std.mem.Allocator, crossref: *validators.CrossRef, local: *const Entry, remote: ?*const Entry) ![]Discrepancy {
+    var discrepancies: std.ArrayList(Discrepancy) = .empty;
+    errdefer {
+        for (discrepancies.items) |*d| d.deinit();
+        discrepancies.deinit(allocator);
+    }
+
+    if (local.funding) |text| {
+        const funders = try parse(allocator, text);
+        defer free(allocator, funders);
+
+        for (funders) |f| {
+            if (try isRegistered(allocator, crossref, f.name)) continue;
+            try discrepancies.append(allocator, .{
+                .rule = .unknown_funder,
+                .field = .funding,
+                .severity = .warning,
+                .local_value = try allocator.dupe(u8, f.name),
+                .remote_value = try allocator.dupe(u8, "(not in registry)"),
+                .message = try std.fmt.allocPrint(allocator, "Funder not found in Crossref Funder Registry: {s}", .{f.name}),
+                .allocator = allocator,
+            });
+        }
+    }
+
+    if (remote) |r| {
+        if (r.funding) |remote_text| {
+            const funders = try parse(allocator, remote_text);
+            defer free(allocator, funders);
+
+            for (funders) |f| {
+                for (f.awards) |award| {
+                    if (local.funding) |text| {
+                        if (std.ascii.indexOfIgnoreCase(text, award) != null) continue;
+                    }
+                    try discrepancies.append(allocator, .{
+                        .rule = .missing_award,
+                        .field = .funding,
+                        .severity = .info,
+                        .local_value = try allocator.dupe(u8, local.funding orelse "(none)"),
+                        .remote_value = try allocator.dupe(u8, remote_text),
+                        .message = try std.fmt.allocPrint(allocator, "Crossref lists award {s} from {s}", .{ award, f.name }),
+                        .allocator = allocator,
+                    });
+                }
+            }
+        }
+    }
+
+    return discrepancies.toOwnedSlice(allocator);
+}
+
+/// Whether the registry knows a funder by this name. Lookup failures
+/// count as registered so that network problems do not produce warnings.
+fn isRegistered(allocator: std.mem.Allocator, crossref: *validators.CrossRef, name: []const u8) !bool {
+    const names = crossref.searchFunders(name) catch return true;
+    defer {
+        for (names) |n| allocator.free(n);
+        allocator.free(names);
+    }
+
+    const local_norm = try entry_mod.normalizeString(allocator, name);
+    defer allocator.free(local_norm);
+
+    for (names) |candidate| {
+        const norm = try entry_mod.normalizeString(allocator, candidate);
+        defer allocator.free(norm);
+        if (try matcher.jaroWinklerSimilarity(allocator, local_norm, norm) >= FUNDER_MATCH_THRESHOLD) return true;
+    }
+    return false;
+}
+
+fn containsDigit(s: []const u8) bool {
+    for (s) |c| {
+        if (std.ascii.isDigit(c)) return true;
+    }
+    return false;
+}
+
+fn isAwardWord(word: []const u8) bool {
+    for (award_words) |w| {
+        if (std.ascii.eqlIgnoreCase(word, w)) return true;
+    }
+    return false;
+}
+
+test "parse funding text" {
+    const allocator = std.testing.allocator;
+
+    const funders = try parse(allocator, "Supported by the National Science Foundation grant CCF-1234567; European Research Council (ERC-2019-StG 850529)");
+    defer free(allocator, funders);
+
+    try std.testing.expectEqual(@as(usize, 2), funders.len);
+    try std.testing.expectEqualStrings("National Science Foundation", funders[0].name);
+    try std.testing.expectEqual(@as(usize, 1), funders[0].awards.len);
+    try std.testing.expectEqualStrings("CCF-1234567", funders[0].awards[0]);
+    try std.testing.expectEqualStrings("European Research Council", funders[1].name);
+    try std.testing.expectEqual(@as(usize, 2), funders[1].awards.len);
+}
@@ -0,0 +1,108 @@
+//! Quality gates over a whole run.
+//!
+//! Per-entry findings decide whether a single reference is fine; gates
+//! decide whether the reference list as a whole is good enough, e.g. "no
+//! more than 10 errors" or "at least 90% of references have a DOI". CI
+//! pipelines tighten the thresholds over time as a bibliography is cleaned
+//! up, without failing on every remaining error at once.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const Report = report_mod.Report;
+
+pub const Gates = struct {
+    /// Errors allowed before the run fails; by default any error fails it.
+    max_errors: ?usize = null,
+    /// Largest allowed percentage of entries no source found.
+    max_unmatched_percent: ?u32 = null,
+    /// Smallest allowed percentage of entries with a DOI.
+    require_doi_coverage: ?u32 = null,
+
+    pub fn isEmpty(self: Gates) bool {
+        return self.max_errors == null and self.max_unmatched_percent == null and self.require_doi_coverage == null;
+    }
+};
+
+pub const Evaluation = struct {
+    /// One message per gate the run fails.
+    failures: []const []const u8 = &.{},
+    arena: std.heap.ArenaAllocator,
+
+    pub fn deinit(self: *Evaluation) void {
+        self.arena.deinit();
+    }
+
+    pub fn passed(self: *const Evaluation) bool {
+        return self.failures.len == 0;
+    }
+
+    pub fn print(self: *const Evaluation, writer: anytype) !void {
+        if (self.failures.len == 0) return;
+
+        try writer.print("GATES ({d} failed)\n", .{self.failures.len});
+        for (self.failures) |message| try writer.print("  {s}\n", .{message});
+        try writer.writeAll("\n");
+    }
+};
+
+/// Check `gates` against a finished run. `errors` counts every error of
+/// the run, including policy and duplicate findings, so the caller
+/// decides what counts (e.g. warnings too under `--strict`).
+pub fn evaluate(allocator: std.mem.Allocator, report: *const Report, gates: Gates, errors: usize) !Evaluation {
+    var result = Evaluation{ .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer result.deinit();
+    const arena = result.arena.allocator();
+
+    var failures: std.ArrayList([]const u8) = .empty;
+    const total = report.entries.items.len;
+
+    if (gates.max_errors) |max_errors| {
+        if (errors > max_errors) {
+            try failures.append(arena, try std.fmt.allocPrint(arena, "{d} errors (at most {d} allowed)", .{ errors, max_errors }));
+        }
+    }
+
+    if (gates.max_unmatched_percent) |max_percent| {
+        const unmatched = report.countNotFound();
+        if (unmatched * 100 > @as(usize, max_percent) * total) {
+            try failures.append(arena, try std.fmt.allocPrint(arena, "{d}% of entries were not found (at most {d}% allowed)", .{ unmatched * 100 / total, max_percent }));
+        }
+    }
+
+    if (gates.require_doi_coverage) |min_percent| {
+        var with_doi: usize = 0;
+        for (report.entries.items) |entry_report| {
+            if (entry_report.entry.doi != null) with_doi += 1;
+        }
+        if (with_doi * 100 < @as(usize, min_percent) * total) {
+            try failures.append(arena, try std.fmt.allocPrint(arena, "{d}% of entries have a DOI (at least {d}% required)", .{ with_doi * 100 / total, min_percent }));
+        }
+    }
+
+    result.failures = failures.items;
+    return result;
+}
+
+test "gates over a whole run" {
+    const allocator = std.testing.allocator;
+
+    var report = Report.init(allocator);
+    defer report.deinit();
+    var none = [_]entry_mod.ValidationResult{};
+    try report.add(.{ .entry = .{ .key = "a", .entry_type = "article", .doi = "10.1000/a" }, .status = .{ .ok = .crossref }, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "b", .entry_type = "article", .doi = "10.1000/b" }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "c", .entry_type = "misc" }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "d", .entry_type = "misc" }, .status = .{ .ok = .dblp }, .validation_results = &none });
+
+    var strict = try evaluate(allocator, &report, .{ .max_errors = 2, .max_unmatched_percent = 40, .require_doi_coverage = 60 }, 3);
+    defer strict.deinit();
+    try std.testing.expectEqual(@as(usize, 3), strict.failures.len);
+    try std.testing.expectEqualStrings("3 errors (at most 2 allowed)", strict.failures[0]);
+    try std.testing.expectEqualStrings("50% of entries were not found (at most 40% allowed)", strict.failures[1]);
+    try std.testing.expectEqualStrings("50% of entries have a DOI (at least 60% required)", strict.failures[2]);
+
+    var lenient = try evaluate(allocator, &report, .{ .max_errors = 3, .max_unmatched_percent = 50, .require_doi_coverage = 50 }, 3);
+    defer lenient.deinit();
+    try std.testing.expect(lenient.passed());
+}
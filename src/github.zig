@@ -0,0 +1,397 @@
+//! Validator for software cited by its GitHub repository.
+//!
+//! A repository URL is an identifier like a DOI: the repository either
+//! exists or it does not. Its record is built from the repository's
+//! CITATION.cff when it has one (title, authors, release date), else from
+//! the repository's name. When the entry cites a version, its tag is looked
+//! up, and if there is none, the latest release, so that the record says
+//! whether the version exists and which is current. Requests are made with
+//! the GitHub token if there is one, which raises the API's rate limit
+//! from 60 to 5000 requests an hour.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const entry_mod = @import("entry.zig");
+const validators = @import("validators.zig");
+const Entry = entry_mod.Entry;
+const ValidatorError = validators.ValidatorError;
+
+/// A repository named by a github.com URL.
+pub const Repository = struct {
+    owner: []const u8,
+    name: []const u8,
+    /// Tag or branch of a /tree/ or /releases/tag/ URL
+    ref: ?[]const u8 = null,
+};
+
+/// The repository a URL points at, e.g. "https://github.com/owner/repo",
+/// "github.com/owner/repo.git", or ".../owner/repo/releases/tag/v1.2".
+pub fn repository(url: []const u8) ?Repository {
+    var rest = url;
+    for ([_][]const u8{ "https://", "http://" }) |scheme| {
+        if (std.ascii.startsWithIgnoreCase(rest, scheme)) rest = rest[scheme.len..];
+    }
+    if (std.ascii.startsWithIgnoreCase(rest, "www.")) rest = rest["www.".len..];
+    if (!std.ascii.startsWithIgnoreCase(rest, "github.com/")) return null;
+    rest = rest["github.com/".len..];
+    if (std.mem.indexOfAny(u8, rest, "?#")) |end| rest = rest[0..end];
+
+    var parts = std.mem.tokenizeScalar(u8, rest, '/');
+    const owner = parts.next() orelse return null;
+    var name = parts.next() orelse return null;
+    if (std.mem.endsWith(u8, name, ".git")) name = name[0 .. name.len - ".git".len];
+    if (name.len == 0) return null;
+
+    var result = Repository{ .owner = owner, .name = name };
+    if (parts.next()) |kind| {
+        if (std.mem.eql(u8, kind, "tree")) {
+            result.ref = parts.next();
+        } else if (std.mem.eql(u8, kind, "releases") and std.mem.eql(u8, parts.next() orelse "", "tag")) {
+            result.ref = parts.next();
+        }
+    }
+    return result;
+}
+
+/// The version an entry cites: its `version` field, or the tag its URL
+/// points at. A /tree/ URL of a branch ("main") cites no version.
+pub fn citedVersion(e: *const Entry) ?[]const u8 {
+    if (e.extraField("version")) |version| return version;
+    const repo = repository(e.url orelse return null) orelse return null;
+    const ref = repo.ref orelse return null;
+    return if (std.mem.indexOfAny(u8, ref, "0123456789") != null) ref else null;
+}
+
+/// Whether two version strings name the same release: "v1.2", "1.2", and
+/// "version 1.2" do.
+pub fn sameVersion(a: []const u8, b: []const u8) bool {
+    return std.ascii.eqlIgnoreCase(bareVersion(a), bareVersion(b));
+}
+
+fn bareVersion(version: []const u8) []const u8 {
+    var v = std.mem.trim(u8, version, " ");
+    if (std.ascii.startsWithIgnoreCase(v, "version ")) v = v["version ".len..];
+    if (v.len > 1 and (v[0] == 'v' or v[0] == 'V') and std.ascii.isDigit(v[1])) v = v[1..];
+    return v;
+}
+
+/// Longest tag tried with a leading "v" added.
+pub const MAX_TAG_LEN = 64;
+
+/// The tags a cited `version` may be under, in the order they are tried:
+/// as cited, with a leading "v", and without one. Repeats and tags that
+/// cannot go in a URL are left out; `buf` holds the "v" form.
+pub fn tagCandidates(version: []const u8, buf: *[MAX_TAG_LEN]u8, out: *[3][]const u8) []const []const u8 {
+    const bare = bareVersion(version);
+    const prefixed = std.fmt.bufPrint(buf, "v{s}", .{bare}) catch "";
+    var len: usize = 0;
+    next: for ([_][]const u8{ std.mem.trim(u8, version, " "), prefixed, bare }) |tag| {
+        if (tag.len == 0 or std.mem.indexOfAny(u8, tag, " ?#%") != null) continue;
+        for (out[0..len]) |tried| {
+            if (std.mem.eql(u8, tag, tried)) continue :next;
+        }
+        out[len] = tag;
+        len += 1;
+    }
+    return out[0..len];
+}
+
+/// The GitHub REST API and the repositories' CITATION.cff files.
+pub const GitHub = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    /// Personal access token, sent to the API only
+    token: ?[]const u8,
+
+    const API_URL = "https://api.github.com/repos";
+    const RAW_URL = "https://raw.githubusercontent.com";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache, token: ?[]const u8) GitHub {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+            .token = token,
+        };
+    }
+
+    /// The record of a repository, or null if it does not exist. When a
+    /// `version` is cited that is not a tag, the record's "untagged_version"
+    /// field gives it and "latest_release" the tag of the latest release.
+    pub fn searchRepository(self: *GitHub, repo: Repository, version: ?[]const u8) !?Entry {
+        const key = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ repo.owner, repo.name });
+        defer self.allocator.free(key);
+
+        const api_url = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ API_URL, key });
+        defer self.allocator.free(api_url);
+        const body = try self.fetch("github_repo", key, api_url) orelse return null;
+        defer self.allocator.free(body);
+
+        const cff_url = try std.fmt.allocPrint(self.allocator, "{s}/{s}/HEAD/CITATION.cff", .{ RAW_URL, key });
+        defer self.allocator.free(cff_url);
+        // Most repositories have no CITATION.cff; the record falls back to the name
+        const cff = self.fetch("github_cff", key, cff_url) catch null;
+        defer if (cff) |c| self.allocator.free(c);
+
+        // A version that cannot be checked is not reported as untagged
+        const untagged = if (version) |v| (try self.hasTag(key, api_url, v)) == false else false;
+        var latest: ?[]u8 = null;
+        defer if (latest) |l| self.allocator.free(l);
+        if (untagged) {
+            const latest_url = try std.fmt.allocPrint(self.allocator, "{s}/releases/latest", .{api_url});
+            defer self.allocator.free(latest_url);
+            // A repository without releases has only its tags
+            latest = self.fetch("github_latest", key, latest_url) catch null;
+        }
+
+        return try parseRepository(self.allocator, body, cff, if (untagged) version else null, latest, version);
+    }
+
+    /// Whether `version` is a tag of the repository `key`, as cited, with a
+    /// leading "v", or without one; null if GitHub could not be asked.
+    fn hasTag(self: *GitHub, key: []const u8, api_url: []const u8, version: []const u8) !?bool {
+        var buf: [MAX_TAG_LEN]u8 = undefined;
+        var candidates: [3][]const u8 = undefined;
+        for (tagCandidates(version, &buf, &candidates)) |tag| {
+            const ref_key = try std.fmt.allocPrint(self.allocator, "{s}@{s}", .{ key, tag });
+            defer self.allocator.free(ref_key);
+            const ref_url = try std.fmt.allocPrint(self.allocator, "{s}/git/ref/tags/{s}", .{ api_url, tag });
+            defer self.allocator.free(ref_url);
+            const ref = self.fetch("github_ref", ref_key, ref_url) catch return null;
+            if (ref) |body| {
+                self.allocator.free(body);
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /// A response body, or null on 404.
+    fn fetch(self: *GitHub, api: []const u8, key: []const u8, url: []const u8) !?[]u8 {
+        if (self.response_cache.get(api, key)) |cached| return cached;
+        var authorization: ?[]u8 = null;
+        defer if (authorization) |a| self.allocator.free(a);
+        var header: [1]std.http.Header = undefined;
+        var headers: []const std.http.Header = &.{};
+        if (self.token != null and std.mem.startsWith(u8, url, API_URL)) {
+            authorization = try std.fmt.allocPrint(self.allocator, "Bearer {s}", .{self.token.?});
+            header[0] = .{ .name = "Authorization", .value = authorization.? };
+            headers = &header;
+        }
+        const body = self.client.getWithHeaders(url, headers) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        self.response_cache.set(api, key, body) catch {};
+        return body;
+    }
+};
+
+/// Build a record from the repository JSON, its CITATION.cff if any, the
+/// cited version if it is `untagged`, and the JSON of the latest release if
+/// fetched. The release date in CITATION.cff is that of the version it
+/// describes, so the record has a year only if that is the cited `version`.
+pub fn parseRepository(allocator: std.mem.Allocator, repo_json: []const u8, cff: ?[]const u8, untagged: ?[]const u8, latest_json: ?[]const u8, version: ?[]const u8) !?Entry {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, repo_json, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+    const repo = parsed.value.object;
+
+    var result = Entry{
+        .key = "",
+        .entry_type = try allocator.dupe(u8, "software"),
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+
+    if (repo.get("html_url")) |url| {
+        if (url == .string) result.url = try allocator.dupe(u8, url.string);
+    }
+
+    var citation = if (cff) |text| try Citation.parse(allocator, text) else Citation{};
+    defer citation.deinit(allocator);
+
+    if (citation.title) |title| {
+        result.title = try allocator.dupe(u8, title);
+    } else if (repo.get("name")) |name| {
+        if (name == .string) result.title = try allocator.dupe(u8, name.string);
+    }
+    if (citation.authors.items.len > 0) {
+        result.authors = try citation.authors.toOwnedSlice(allocator);
+    }
+    if (citation.date != null and citation.version != null and version != null and sameVersion(citation.version.?, version.?)) {
+        const date = citation.date.?;
+        if (date.len >= 4) result.year = std.fmt.parseInt(i32, date[0..4], 10) catch null;
+    }
+
+    var latest: ?std.json.Parsed(std.json.Value) = null;
+    defer if (latest) |l| l.deinit();
+    var latest_tag: []const u8 = "";
+    if (latest_json) |json| {
+        latest = std.json.parseFromSlice(std.json.Value, allocator, json, .{}) catch null;
+        if (latest) |release| {
+            if (release.value == .object) {
+                if (release.value.object.get("tag_name")) |tag| {
+                    if (tag == .string) latest_tag = tag.string;
+                }
+            }
+        }
+    }
+
+    result.extra = try validators.extraFields(allocator, &.{
+        .{ .name = "version", .value = citation.version orelse "" },
+        .{ .name = "untagged_version", .value = untagged orelse "" },
+        .{ .name = "latest_release", .value = latest_tag },
+    });
+    return result;
+}
+
+/// The fields of a CITATION.cff that describe the software itself; the
+/// `preferred-citation` block is left alone.
+const Citation = struct {
+    title: ?[]const u8 = null,
+    version: ?[]const u8 = null,
+    date: ?[]const u8 = null,
+    /// Owned names, "Given Family"
+    authors: std.ArrayList([]const u8) = .empty,
+
+    fn deinit(self: *Citation, allocator: std.mem.Allocator) void {
+        for (self.authors.items) |a| allocator.free(a);
+        self.authors.deinit(allocator);
+    }
+
+    /// Read the top-level keys of a CITATION.cff. Values borrow from `text`.
+    fn parse(allocator: std.mem.Allocator, text: []const u8) !Citation {
+        var result = Citation{};
+        errdefer result.deinit(allocator);
+
+        var in_authors = false;
+        var given: ?[]const u8 = null;
+        var family: ?[]const u8 = null;
+        var lines = std.mem.splitScalar(u8, text, '\n');
+        while (lines.next()) |raw| {
+            const line = std.mem.trimRight(u8, raw, " \r");
+            if (line.len == 0 or std.mem.trimLeft(u8, line, " ")[0] == '#') continue;
+
+            if (line[0] != ' ' and line[0] != '-') {
+                if (in_authors) try result.addAuthor(allocator, &given, &family);
+                in_authors = false;
+                const key, const value = keyValue(line) orelse continue;
+                if (std.mem.eql(u8, key, "title")) result.title = value;
+                if (std.mem.eql(u8, key, "version")) result.version = value;
+                if (std.mem.eql(u8, key, "date-released")) result.date = value;
+                if (std.mem.eql(u8, key, "authors")) in_authors = true;
+                continue;
+            }
+            if (!in_authors) continue;
+
+            var item = std.mem.trimLeft(u8, line, " ");
+            if (std.mem.startsWith(u8, item, "- ")) {
+                try result.addAuthor(allocator, &given, &family);
+                item = std.mem.trimLeft(u8, item[2..], " ");
+            }
+            const key, const value = keyValue(item) orelse continue;
+            if (std.mem.eql(u8, key, "given-names")) given = value;
+            if (std.mem.eql(u8, key, "family-names") or std.mem.eql(u8, key, "name")) family = value;
+        }
+        if (in_authors) try result.addAuthor(allocator, &given, &family);
+        return result;
+    }
+
+    /// Add the author read so far, if any, and start the next.
+    fn addAuthor(self: *Citation, allocator: std.mem.Allocator, given: *?[]const u8, family: *?[]const u8) !void {
+        defer {
+            given.* = null;
+            family.* = null;
+        }
+        const last = family.* orelse return;
+        const name = if (given.*) |first|
+            try std.fmt.allocPrint(allocator, "{s} {s}", .{ first, last })
+        else
+            try allocator.dupe(u8, last);
+        errdefer allocator.free(name);
+        try self.authors.append(allocator, name);
+    }
+};
+
+/// "key: value" with the value unquoted; null for a line without a colon.
+fn keyValue(line: []const u8) ?struct { []const u8, []const u8 } {
+    const colon = std.mem.indexOfScalar(u8, line, ':') orelse return null;
+    const key = std.mem.trim(u8, line[0..colon], " ");
+    var value = std.mem.trim(u8, line[colon + 1 ..], " \t");
+    if (value.len >= 2 and (value[0] == '"' or value[0] == '\'') and value[value.len - 1] == value[0]) {
+        value = value[1 .. value.len - 1];
+    }
+    return .{ key, value };
+}
+
+test "GitHub repositories" {
+    const allocator = std.testing.allocator;
+
+    const repo = repository("https://github.com/pytorch/pytorch/releases/tag/v2.1.0").?;
+    try std.testing.expectEqualStrings("pytorch", repo.owner);
+    try std.testing.expectEqualStrings("pytorch", repo.name);
+    try std.testing.expectEqualStrings("v2.1.0", repo.ref.?);
+    try std.testing.expectEqualStrings("numpy", repository("github.com/numpy/numpy.git").?.name);
+    try std.testing.expect(repository("https://gitlab.com/a/b") == null);
+    try std.testing.expect(repository("https://github.com/numpy") == null);
+
+    try std.testing.expect(sameVersion("1.2.0", "v1.2.0"));
+    try std.testing.expect(sameVersion("version 0.9", "0.9"));
+    try std.testing.expect(!sameVersion("1.2", "v1.2.0"));
+
+    const cff =
+        \\cff-version: 1.2.0
+        \\message: "If you use this software, please cite it as below."
+        \\title: "Example Toolkit"
+        \\version: 1.2.0
+        \\date-released: 2021-05-04
+        \\authors:
+        \\  - family-names: Doe
+        \\    given-names: Jane
+        \\  - name: "The Example Project"
+        \\preferred-citation:
+        \\  type: article
+        \\  title: "A Paper About It"
+        \\  authors:
+        \\    - family-names: Roe
+    ;
+    var record = (try parseRepository(allocator,
+        \\{"name": "toolkit", "html_url": "https://github.com/example/toolkit"}
+    , cff, null, null, "1.2.0")).?;
+    defer record.deinit();
+    try std.testing.expectEqualStrings("Example Toolkit", record.title.?);
+    try std.testing.expectEqual(@as(usize, 2), record.authors.len);
+    try std.testing.expectEqualStrings("Jane Doe", record.authors[0]);
+    try std.testing.expectEqualStrings("The Example Project", record.authors[1]);
+    try std.testing.expectEqual(@as(?i32, 2021), record.year);
+    try std.testing.expect(record.extraField("untagged_version") == null);
+
+    var bare = (try parseRepository(allocator,
+        \\{"name": "toolkit"}
+    , cff, null, null, null)).?;
+    defer bare.deinit();
+    try std.testing.expectEqualStrings("Example Toolkit", bare.title.?);
+    try std.testing.expect(bare.year == null);
+
+    var untagged = (try parseRepository(allocator,
+        \\{"name": "toolkit"}
+    , cff, "1.4",
+        \\{"tag_name": "v1.3.0", "name": "Release 1.3.0"}
+    , "1.4")).?;
+    defer untagged.deinit();
+    try std.testing.expectEqualStrings("1.4", untagged.extraField("untagged_version").?);
+    try std.testing.expectEqualStrings("v1.3.0", untagged.extraField("latest_release").?);
+    try std.testing.expect(untagged.year == null);
+
+    var plain = (try parseRepository(allocator,
+        \\{"name": "toolkit"}
+    , null, null, null, null)).?;
+    defer plain.deinit();
+    try std.testing.expectEqualStrings("toolkit", plain.title.?);
+}
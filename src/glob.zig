@@ -0,0 +1,145 @@
+//! Glob patterns for input files.
+//!
+//! Shells expand `chapters/*.bib` before bibval sees it, but not when the
+//! pattern is quoted, on Windows, or for `**`. Patterns support `*` and `?`
+//! within a path component and `**` for any number of directories.
+
+const std = @import("std");
+
+/// Whether `path` contains glob wildcards.
+pub fn hasWildcards(path: []const u8) bool {
+    return std.mem.indexOfAny(u8, path, "*?") != null;
+}
+
+/// Append the paths under `dir` that match `pattern`, sorted, allocated
+/// with `allocator`. Hidden files and directories only match a pattern
+/// component that starts with a dot.
+pub fn expand(allocator: std.mem.Allocator, dir: std.fs.Dir, pattern: []const u8, out: *std.ArrayList([]const u8)) !void {
+    var components: std.ArrayList([]const u8) = .empty;
+    defer components.deinit(allocator);
+    var iter = std.mem.tokenizeAny(u8, pattern, "/\\");
+    while (iter.next()) |component| try components.append(allocator, component);
+
+    const absolute = std.fs.path.isAbsolute(pattern);
+    var root = if (absolute) try std.fs.openDirAbsolute("/", .{ .iterate = true }) else dir;
+    defer if (absolute) root.close();
+    const prefix = if (absolute) "/" else "";
+
+    const first = out.items.len;
+    try expandFrom(allocator, root, prefix, components.items, out);
+    std.sort.pdq([]const u8, out.items[first..], {}, lessThan);
+}
+
+fn expandFrom(allocator: std.mem.Allocator, dir: std.fs.Dir, prefix: []const u8, components: []const []const u8, out: *std.ArrayList([]const u8)) !void {
+    if (components.len == 0) return;
+    const component = components[0];
+    const rest = components[1..];
+
+    if (std.mem.eql(u8, component, "**")) {
+        // Zero directories, then every subdirectory with the same pattern
+        try expandFrom(allocator, dir, prefix, rest, out);
+        var it = dir.iterate();
+        while (try it.next()) |child| {
+            if (child.kind != .directory or child.name[0] == '.') continue;
+            try descend(allocator, dir, prefix, child.name, components, out);
+        }
+        return;
+    }
+
+    if (!hasWildcards(component)) {
+        if (rest.len == 0) {
+            dir.access(component, .{}) catch return;
+            try out.append(allocator, try join(allocator, prefix, component));
+        } else {
+            try descend(allocator, dir, prefix, component, rest, out);
+        }
+        return;
+    }
+
+    var it = dir.iterate();
+    while (try it.next()) |child| {
+        if (child.name[0] == '.' and component[0] != '.') continue;
+        if (!match(component, child.name)) continue;
+        if (rest.len == 0) {
+            if (child.kind != .directory) try out.append(allocator, try join(allocator, prefix, child.name));
+        } else if (child.kind == .directory) {
+            try descend(allocator, dir, prefix, child.name, rest, out);
+        }
+    }
+}
+
+fn descend(allocator: std.mem.Allocator, dir: std.fs.Dir, prefix: []const u8, name: []const u8, components: []const []const u8, out: *std.ArrayList([]const u8)) !void {
+    var sub = dir.openDir(name, .{ .iterate = true }) catch return;
+    defer sub.close();
+    const sub_prefix = try join(allocator, prefix, name);
+    defer allocator.free(sub_prefix);
+    try expandFrom(allocator, sub, sub_prefix, components, out);
+}
+
+fn join(allocator: std.mem.Allocator, prefix: []const u8, name: []const u8) ![]const u8 {
+    if (prefix.len == 0) return allocator.dupe(u8, name);
+    if (std.mem.endsWith(u8, prefix, "/")) return std.mem.concat(allocator, u8, &.{ prefix, name });
+    return std.mem.concat(allocator, u8, &.{ prefix, "/", name });
+}
+
+/// Match one path component against a pattern with `*` and `?`.
+pub fn match(pattern: []const u8, name: []const u8) bool {
+    var p: usize = 0;
+    var n: usize = 0;
+    // Where to resume after the last `*` if the rest fails to match
+    var star: ?usize = null;
+    var star_n: usize = 0;
+    while (n < name.len) {
+        if (p < pattern.len and (pattern[p] == '?' or pattern[p] == name[n])) {
+            p += 1;
+            n += 1;
+        } else if (p < pattern.len and pattern[p] == '*') {
+            star = p;
+            star_n = n;
+            p += 1;
+        } else if (star) |s| {
+            p = s + 1;
+            star_n += 1;
+            n = star_n;
+        } else {
+            return false;
+        }
+    }
+    while (p < pattern.len and pattern[p] == '*') p += 1;
+    return p == pattern.len;
+}
+
+fn lessThan(_: void, a: []const u8, b: []const u8) bool {
+    return std.mem.order(u8, a, b) == .lt;
+}
+
+test "glob expansion" {
+    const allocator = std.testing.allocator;
+
+    try std.testing.expect(match("*.bib", "refs.bib"));
+    try std.testing.expect(match("ch?-*.bib", "ch1-intro.bib"));
+    try std.testing.expect(!match("*.bib", "refs.bib.bak"));
+    try std.testing.expect(!match("ch?.bib", "ch10.bib"));
+
+    var tmp = std.testing.tmpDir(.{ .iterate = true });
+    defer tmp.cleanup();
+    try tmp.dir.makePath("thesis/chapters/appendix");
+    for ([_][]const u8{ "thesis/main.bib", "thesis/chapters/b.bib", "thesis/chapters/a.bib", "thesis/chapters/notes.txt", "thesis/chapters/appendix/c.bib" }) |path| {
+        try tmp.dir.writeFile(.{ .sub_path = path, .data = "" });
+    }
+
+    var found: std.ArrayList([]const u8) = .empty;
+    defer {
+        for (found.items) |path| allocator.free(path);
+        found.deinit(allocator);
+    }
+
+    try expand(allocator, tmp.dir, "thesis/chapters/*.bib", &found);
+    try std.testing.expectEqual(@as(usize, 2), found.items.len);
+    try std.testing.expectEqualStrings("thesis/chapters/a.bib", found.items[0]);
+
+    try expand(allocator, tmp.dir, "thesis/**/*.bib", &found);
+    try std.testing.expectEqual(@as(usize, 6), found.items.len);
+    try std.testing.expectEqualStrings("thesis/chapters/appendix/c.bib", found.items[3]);
+    try std.testing.expectEqualStrings("thesis/main.bib", found.items[5]);
+}
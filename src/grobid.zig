@@ -0,0 +1,314 @@
+//! Reference extraction from PDFs with GROBID.
+//!
+//! Sends a PDF to a user-hosted GROBID service and converts the TEI
+//! reference list it returns into entries, so that a paper can be checked
+//! without its BibTeX source.
+
+const std = @import("std");
+const http = @import("http.zig");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+pub const GrobidError = error{
+    ServiceUnavailable,
+    InvalidResponse,
+};
+
+/// Multipart boundary for uploads. Unlikely to occur in a PDF.
+const BOUNDARY = "----bibval-grobid-7f3c2a91e8d4";
+
+/// Largest PDF accepted for upload.
+pub const MAX_PDF_SIZE = 64 * 1024 * 1024;
+
+pub const Grobid = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    /// Service root, e.g. "http://localhost:8070".
+    base_url: []const u8,
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, base_url: []const u8) Grobid {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .base_url = std.mem.trimRight(u8, base_url, "/"),
+        };
+    }
+
+    /// Extract the reference list of a PDF.
+    pub fn processReferences(self: *Grobid, pdf: []const u8, file_name: []const u8) ![]Entry {
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/api/processReferences", .{self.base_url});
+        defer self.allocator.free(url);
+
+        const body = try std.fmt.allocPrint(self.allocator, "--{s}\r\n" ++
+            "Content-Disposition: form-data; name=\"input\"; filename=\"{s}\"\r\n" ++
+            "Content-Type: application/pdf\r\n\r\n{s}\r\n" ++
+            "--{s}\r\n" ++
+            "Content-Disposition: form-data; name=\"includeRawCitations\"\r\n\r\n1\r\n" ++
+            "--{s}--\r\n", .{ BOUNDARY, std.fs.path.basename(file_name), pdf, BOUNDARY, BOUNDARY });
+        defer self.allocator.free(body);
+
+        const tei = self.client.post(url, "multipart/form-data; boundary=" ++ BOUNDARY, body) catch
+            return GrobidError.ServiceUnavailable;
+        defer self.allocator.free(tei);
+
+        return parseTei(self.allocator, tei);
+    }
+};
+
+/// Convert the `<biblStruct>` elements of a TEI document into entries.
+pub fn parseTei(allocator: std.mem.Allocator, tei: []const u8) ![]Entry {
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |*e| e.deinit();
+        entries.deinit(allocator);
+    }
+
+    var pos: usize = 0;
+    while (findElement(tei, pos, "biblStruct")) |bibl| {
+        pos = bibl.end;
+        var result = try parseBiblStruct(allocator, bibl, entries.items.len);
+        errdefer result.deinit();
+        if (result.title == null) {
+            result.deinit();
+            continue;
+        }
+        try entries.append(allocator, result);
+    }
+
+    if (entries.items.len == 0 and std.mem.indexOf(u8, tei, "<TEI") == null) return GrobidError.InvalidResponse;
+    return entries.toOwnedSlice(allocator);
+}
+
+fn parseBiblStruct(allocator: std.mem.Allocator, bibl: Element, index: usize) !Entry {
+    var result = Entry{ .key = "", .entry_type = "", .allocator = allocator };
+    errdefer result.deinit();
+
+    result.key = if (attribute(bibl.attrs, "xml:id")) |id|
+        try allocator.dupe(u8, id)
+    else
+        try std.fmt.allocPrint(allocator, "ref{d}", .{index + 1});
+
+    const analytic = findElement(bibl.content, 0, "analytic");
+    const monogr = findElement(bibl.content, 0, "monogr");
+
+    // Articles and papers have an analytic part; books only a monograph
+    var entry_type: []const u8 = "misc";
+    if (analytic) |a| {
+        if (findTitle(a.content, "a")) |t| result.title = try text(allocator, t);
+        if (monogr) |m| {
+            if (findTitle(m.content, "j")) |j| {
+                result.venue = try text(allocator, j);
+                entry_type = "article";
+            } else if (findTitle(m.content, "m")) |book| {
+                result.venue = try text(allocator, book);
+                entry_type = "inproceedings";
+            }
+        }
+    } else if (monogr) |m| {
+        if (findTitle(m.content, "m")) |t| {
+            result.title = try text(allocator, t);
+            entry_type = "book";
+        }
+    }
+    result.entry_type = try allocator.dupe(u8, entry_type);
+
+    // Authors of the cited work, not the editors of its container
+    const author_scope = if (analytic) |a| a.content else if (monogr) |m| m.content else bibl.content;
+    var authors: std.ArrayList([]const u8) = .empty;
+    errdefer {
+        for (authors.items) |a| allocator.free(a);
+        authors.deinit(allocator);
+    }
+    var author_pos: usize = 0;
+    while (findElement(author_scope, author_pos, "author")) |author| {
+        author_pos = author.end;
+        if (try personName(allocator, author.content)) |name| try authors.append(allocator, name);
+    }
+    if (authors.items.len > 0) result.authors = try authors.toOwnedSlice(allocator);
+
+    var date_pos: usize = 0;
+    while (findElement(bibl.content, date_pos, "date")) |date| {
+        date_pos = date.end;
+        const when = attribute(date.attrs, "when") orelse continue;
+        if (when.len >= 4) {
+            result.year = std.fmt.parseInt(i32, when[0..4], 10) catch continue;
+            break;
+        }
+    }
+
+    var idno_pos: usize = 0;
+    while (findElement(bibl.content, idno_pos, "idno")) |idno| {
+        idno_pos = idno.end;
+        const id_type = attribute(idno.attrs, "type") orelse continue;
+        if (std.ascii.eqlIgnoreCase(id_type, "DOI") and result.doi == null) {
+            result.doi = try text(allocator, idno.content);
+        } else if (std.ascii.eqlIgnoreCase(id_type, "arXiv") and result.arxiv_id == null) {
+            const id = try text(allocator, idno.content);
+            defer allocator.free(id);
+            const bare = if (std.ascii.startsWithIgnoreCase(id, "arXiv:")) id[6..] else id;
+            result.arxiv_id = try allocator.dupe(u8, bare);
+        }
+    }
+
+    return result;
+}
+
+/// "Forename Surname" from a `<persName>`.
+fn personName(allocator: std.mem.Allocator, author: []const u8) !?[]const u8 {
+    const pers = findElement(author, 0, "persName") orelse return null;
+    const surname = findElement(pers.content, 0, "surname") orelse return null;
+
+    var name: std.ArrayList(u8) = .empty;
+    errdefer name.deinit(allocator);
+
+    var pos: usize = 0;
+    while (findElement(pers.content, pos, "forename")) |forename| {
+        pos = forename.end;
+        const part = try text(allocator, forename.content);
+        defer allocator.free(part);
+        try name.appendSlice(allocator, part);
+        // Initials are returned without a period
+        if (part.len == 1) try name.append(allocator, '.');
+        try name.append(allocator, ' ');
+    }
+    const last = try text(allocator, surname.content);
+    defer allocator.free(last);
+    try name.appendSlice(allocator, last);
+
+    return try name.toOwnedSlice(allocator);
+}
+
+const Element = struct {
+    attrs: []const u8,
+    content: []const u8,
+    /// Offset just past the element in the searched text.
+    end: usize,
+};
+
+/// Find the next `<tag ...>...</tag>` (or `<tag .../>`) at or after `start`.
+/// Elements of the same name are assumed not to nest.
+fn findElement(xml: []const u8, start: usize, tag: []const u8) ?Element {
+    var pos = start;
+    while (std.mem.indexOfPos(u8, xml, pos, "<")) |lt| {
+        pos = lt + 1;
+        if (!std.mem.startsWith(u8, xml[pos..], tag)) continue;
+        const after = pos + tag.len;
+        if (after >= xml.len or (xml[after] != ' ' and xml[after] != '>' and xml[after] != '/' and xml[after] != '\n')) continue;
+
+        const gt = std.mem.indexOfScalarPos(u8, xml, after, '>') orelse return null;
+        if (xml[gt - 1] == '/') {
+            return .{ .attrs = xml[after .. gt - 1], .content = "", .end = gt + 1 };
+        }
+
+        var close_buf: [64]u8 = undefined;
+        const close = std.fmt.bufPrint(&close_buf, "</{s}>", .{tag}) catch return null;
+        const close_start = std.mem.indexOfPos(u8, xml, gt + 1, close) orelse return null;
+        return .{ .attrs = xml[after..gt], .content = xml[gt + 1 .. close_start], .end = close_start + close.len };
+    }
+    return null;
+}
+
+/// The `<title level="...">` with the given level.
+fn findTitle(xml: []const u8, level: []const u8) ?[]const u8 {
+    var pos: usize = 0;
+    while (findElement(xml, pos, "title")) |title| {
+        pos = title.end;
+        const title_level = attribute(title.attrs, "level") orelse continue;
+        if (std.mem.eql(u8, title_level, level)) return title.content;
+    }
+    return null;
+}
+
+fn attribute(attrs: []const u8, name: []const u8) ?[]const u8 {
+    var pos: usize = 0;
+    while (std.mem.indexOfPos(u8, attrs, pos, name)) |i| {
+        pos = i + name.len;
+        if (i > 0 and attrs[i - 1] != ' ' and attrs[i - 1] != '\n') continue;
+        if (pos + 1 >= attrs.len or attrs[pos] != '=') continue;
+        const quote = attrs[pos + 1];
+        if (quote != '"' and quote != '\'') continue;
+        const end = std.mem.indexOfScalarPos(u8, attrs, pos + 2, quote) orelse return null;
+        return attrs[pos + 2 .. end];
+    }
+    return null;
+}
+
+/// Element content with nested tags removed, entities decoded, and
+/// whitespace collapsed.
+fn text(allocator: std.mem.Allocator, content: []const u8) ![]u8 {
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+
+    var i: usize = 0;
+    var space = false;
+    while (i < content.len) : (i += 1) {
+        const c = content[i];
+        if (c == '<') {
+            i = std.mem.indexOfScalarPos(u8, content, i, '>') orelse content.len;
+            continue;
+        }
+        if (std.ascii.isWhitespace(c)) {
+            space = out.items.len > 0;
+            continue;
+        }
+        if (space) try out.append(allocator, ' ');
+        space = false;
+
+        if (c == '&') {
+            const entities = [_][2][]const u8{ .{ "&amp;", "&" }, .{ "&lt;", "<" }, .{ "&gt;", ">" }, .{ "&quot;", "\"" }, .{ "&apos;", "'" } };
+            for (entities) |entity| {
+                if (std.mem.startsWith(u8, content[i..], entity[0])) {
+                    try out.appendSlice(allocator, entity[1]);
+                    i += entity[0].len - 1;
+                    break;
+                }
+            } else try out.append(allocator, c);
+            continue;
+        }
+        try out.append(allocator, c);
+    }
+
+    return out.toOwnedSlice(allocator);
+}
+
+test "parse TEI reference list" {
+    const allocator = std.testing.allocator;
+
+    const tei =
+        \\<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><back><listBibl>
+        \\<biblStruct xml:id="b0">
+        \\  <analytic>
+        \\    <title level="a" type="main">Attention Is All You Need</title>
+        \\    <author><persName><forename type="first">Ashish</forename><surname>Vaswani</surname></persName></author>
+        \\    <author><persName><forename type="first">N</forename><surname>Shazeer</surname></persName></author>
+        \\    <idno type="arXiv">arXiv:1706.03762</idno>
+        \\  </analytic>
+        \\  <monogr>
+        \\    <title level="m">Advances in Neural Information Processing Systems</title>
+        \\    <imprint><date type="published" when="2017" /></imprint>
+        \\  </monogr>
+        \\</biblStruct>
+        \\<biblStruct xml:id="b1">
+        \\  <monogr><title level="m">Deep Learning &amp; Beyond</title>
+        \\  <imprint><date type="published" when="2016-11" /></imprint></monogr>
+        \\</biblStruct>
+        \\</listBibl></back></text></TEI>
+    ;
+
+    const entries = try parseTei(allocator, tei);
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 2), entries.len);
+    try std.testing.expectEqualStrings("b0", entries[0].key);
+    try std.testing.expectEqualStrings("inproceedings", entries[0].entry_type);
+    try std.testing.expectEqualStrings("Attention Is All You Need", entries[0].title.?);
+    try std.testing.expectEqualStrings("N. Shazeer", entries[0].authors[1]);
+    try std.testing.expectEqualStrings("1706.03762", entries[0].arxiv_id.?);
+    try std.testing.expectEqual(@as(i32, 2017), entries[0].year.?);
+    try std.testing.expectEqualStrings("book", entries[1].entry_type);
+    try std.testing.expectEqualStrings("Deep Learning & Beyond", entries[1].title.?);
+    try std.testing.expectEqual(@as(i32, 2016), entries[1].year.?);
+}
@@ -0,0 +1,275 @@
+//! Validation history in an SQLite database.
+//!
+//! With `--history PATH`, every run appends the status and findings of each
+//! entry to the database, and `bibval history KEY` shows, run by run, when
+//! findings of an entry appeared and when they were fixed. A finding is
+//! identified by its rule and field, so a changed message (e.g. a different
+//! remote year) is not counted as fixed. The database is written and read
+//! through the `sqlite3` command-line tool, so bibval links no SQLite.
+
+const std = @import("std");
+const Report = @import("report.zig").Report;
+
+/// Database used by `bibval history` when `--history` is not given.
+pub const DEFAULT_PATH = ".bibval-history.sqlite";
+
+pub const HistoryError = error{
+    /// `sqlite3` is not installed.
+    SqliteUnavailable,
+    /// `sqlite3` failed, e.g. because the file is not a database.
+    SqliteFailed,
+};
+
+const SCHEMA =
+    \\CREATE TABLE IF NOT EXISTS runs (id INTEGER PRIMARY KEY, started TEXT NOT NULL, files TEXT NOT NULL);
+    \\CREATE TABLE IF NOT EXISTS results (run INTEGER NOT NULL REFERENCES runs (id), key TEXT NOT NULL, status TEXT NOT NULL, PRIMARY KEY (run, key));
+    \\CREATE TABLE IF NOT EXISTS findings (run INTEGER NOT NULL REFERENCES runs (id), key TEXT NOT NULL, rule TEXT NOT NULL, field TEXT NOT NULL, severity TEXT NOT NULL, message TEXT NOT NULL, PRIMARY KEY (run, key, rule, field));
+    \\
+;
+
+/// Id of the run being recorded, within the statements of `writeRun`.
+const CURRENT_RUN = "(SELECT max(id) FROM runs)";
+
+/// One entry in one run, with one of its findings if it had any.
+pub const Row = struct {
+    run: i64,
+    started: []const u8,
+    status: []const u8,
+    rule: ?[]const u8 = null,
+    field: ?[]const u8 = null,
+    severity: ?[]const u8 = null,
+    message: ?[]const u8 = null,
+
+    fn sameFinding(a: Row, b: Row) bool {
+        return std.mem.eql(u8, a.rule.?, b.rule.?) and std.mem.eql(u8, a.field.?, b.field.?);
+    }
+};
+
+/// Rows of one entry, ordered by run.
+pub const History = struct {
+    rows: []const Row,
+    parsed: ?std.json.Parsed([]Row) = null,
+
+    pub fn deinit(self: *History) void {
+        if (self.parsed) |p| p.deinit();
+    }
+
+    /// Number of runs that recorded the entry.
+    pub fn runCount(self: History) usize {
+        var count: usize = 0;
+        for (self.rows, 0..) |row, i| {
+            if (i == 0 or self.rows[i - 1].run != row.run) count += 1;
+        }
+        return count;
+    }
+};
+
+/// Append `report` as a new run over `files` to the database at `path`,
+/// creating it if needed.
+pub fn record(allocator: std.mem.Allocator, path: []const u8, report: *const Report, files: []const []const u8) !void {
+    var sql: std.Io.Writer.Allocating = .init(allocator);
+    defer sql.deinit();
+    try writeRun(&sql.writer, report, files);
+
+    var child = std.process.Child.init(&.{ "sqlite3", "-bail", path }, allocator);
+    child.stdin_behavior = .Pipe;
+    child.stdout_behavior = .Ignore;
+    child.stderr_behavior = .Inherit;
+    child.spawn() catch return HistoryError.SqliteUnavailable;
+    errdefer _ = child.kill() catch {};
+
+    child.stdin.?.writeAll(sql.written()) catch return HistoryError.SqliteFailed;
+    child.stdin.?.close();
+    child.stdin = null;
+    const term = child.wait() catch return HistoryError.SqliteFailed;
+    if (term != .Exited or term.Exited != 0) return HistoryError.SqliteFailed;
+}
+
+/// Load the recorded runs of the entry `key` from the database at `path`.
+pub fn load(allocator: std.mem.Allocator, path: []const u8, key: []const u8) !History {
+    try std.fs.cwd().access(path, .{});
+
+    var query: std.Io.Writer.Allocating = .init(allocator);
+    defer query.deinit();
+    try query.writer.writeAll(
+        \\SELECT runs.id AS run, runs.started AS started, results.status AS status,
+        \\  findings.rule AS rule, findings.field AS field, findings.severity AS severity, findings.message AS message
+        \\FROM results JOIN runs ON runs.id = results.run
+        \\LEFT JOIN findings ON findings.run = results.run AND findings.key = results.key
+        \\WHERE results.key =
+    );
+    try writeSqlString(&query.writer, key);
+    try query.writer.writeAll(" ORDER BY runs.id, findings.rule, findings.field;");
+
+    // As in `record`, sqlite3 reports its own errors on stderr
+    var child = std.process.Child.init(&.{ "sqlite3", "-json", "-readonly", path, query.written() }, allocator);
+    child.stdin_behavior = .Ignore;
+    child.stdout_behavior = .Pipe;
+    child.stderr_behavior = .Inherit;
+    child.spawn() catch return HistoryError.SqliteUnavailable;
+    errdefer _ = child.kill() catch {};
+
+    const output = child.stdout.?.readToEndAlloc(allocator, 64 * 1024 * 1024) catch return HistoryError.SqliteFailed;
+    defer allocator.free(output);
+    const term = child.wait() catch return HistoryError.SqliteFailed;
+    if (term != .Exited or term.Exited != 0) return HistoryError.SqliteFailed;
+
+    // sqlite3 prints nothing at all when no row matches
+    if (std.mem.trim(u8, output, " \t\r\n").len == 0) return .{ .rows = &.{} };
+    const parsed = std.json.parseFromSlice([]Row, allocator, output, .{
+        .ignore_unknown_fields = true,
+        .allocate = .alloc_always,
+    }) catch return HistoryError.SqliteFailed;
+    return .{ .rows = parsed.value, .parsed = parsed };
+}
+
+/// Write the SQL statements that record `report` as a new run.
+pub fn writeRun(writer: anytype, report: *const Report, files: []const []const u8) !void {
+    try writer.writeAll(SCHEMA);
+    try writer.writeAll("BEGIN;\nINSERT INTO runs (started, files) VALUES (datetime('now'), '");
+    for (files, 0..) |file, i| {
+        if (i > 0) try writer.writeAll(", ");
+        try writeSqlText(writer, file);
+    }
+    try writer.writeAll("');\n");
+
+    for (report.entries.items) |e| {
+        try writer.writeAll("INSERT OR REPLACE INTO results VALUES (" ++ CURRENT_RUN ++ ", ");
+        try writeSqlString(writer, e.entry.key);
+        try writer.print(", '{s}');\n", .{@tagName(e.status)});
+        for (e.validation_results) |result| {
+            for (result.discrepancies) |d| {
+                try writer.writeAll("INSERT OR IGNORE INTO findings VALUES (" ++ CURRENT_RUN ++ ", ");
+                try writeSqlString(writer, e.entry.key);
+                try writer.print(", '{s}', '{s}', '{s}', ", .{ d.rule.id(), @tagName(d.field), d.severity.name() });
+                try writeSqlString(writer, d.message);
+                try writer.writeAll(");\n");
+            }
+        }
+    }
+    try writer.writeAll("COMMIT;\n");
+}
+
+/// Print the runs of `history`: the entry's status in each, the findings
+/// that appeared (`+`) and the ones that were fixed (`-`) since the
+/// previous run that found the entry. Runs whose lookups failed or found
+/// nothing are marked as such and left out of the comparison.
+pub fn printTimeline(writer: anytype, key: []const u8, history: History) !void {
+    const count = history.runCount();
+    try writer.print("History of {s} ({d} run{s})\n", .{ key, count, if (count == 1) "" else "s" });
+
+    // Last run that found the entry; null before the first
+    var previous: ?[]const Row = null;
+    var start: usize = 0;
+    while (start < history.rows.len) {
+        var end = start + 1;
+        while (end < history.rows.len and history.rows[end].run == history.rows[start].run) end += 1;
+        const run = history.rows[start..end];
+
+        try writer.print("  {s}  {s}", .{ run[0].started, run[0].status });
+        // Without a match there are no findings to compare
+        if (lookupFailed(run[0].status)) {
+            try writer.writeAll("  (lookup failed)\n");
+            start = end;
+            continue;
+        }
+        var changed = false;
+        for (run) |row| {
+            if (row.rule == null or containsFinding(previous orelse &.{}, row)) continue;
+            if (!changed) try writer.writeAll("\n");
+            changed = true;
+            try writer.print("    + {s} [{s}] {s}\n", .{ row.rule.?, row.severity.?, row.message.? });
+        }
+        for (previous orelse &.{}) |row| {
+            if (row.rule == null or containsFinding(run, row)) continue;
+            if (!changed) try writer.writeAll("\n");
+            changed = true;
+            try writer.print("    - {s} [{s}] {s}\n", .{ row.rule.?, row.severity.?, row.message.? });
+        }
+        if (!changed) try writer.writeAll(if (previous == null) "\n" else "  (no change)\n");
+
+        previous = run;
+        start = end;
+    }
+}
+
+/// Whether a recorded status is of an entry no source matched.
+fn lookupFailed(status: []const u8) bool {
+    return std.mem.eql(u8, status, "failed") or std.mem.eql(u8, status, "not_found");
+}
+
+fn containsFinding(rows: []const Row, finding: Row) bool {
+    for (rows) |row| {
+        if (row.rule != null and row.sameFinding(finding)) return true;
+    }
+    return false;
+}
+
+/// Write `text` as an SQL string literal.
+fn writeSqlString(writer: anytype, text: []const u8) !void {
+    try writer.writeByte('\'');
+    try writeSqlText(writer, text);
+    try writer.writeByte('\'');
+}
+
+/// Write `text` for use inside an SQL string literal, doubling quotes.
+fn writeSqlText(writer: anytype, text: []const u8) !void {
+    for (text) |c| {
+        if (c == '\'') try writer.writeByte('\'');
+        try writer.writeByte(c);
+    }
+}
+
+test "runs are recorded and compared" {
+    const allocator = std.testing.allocator;
+    const Discrepancy = @import("entry.zig").Discrepancy;
+    const ValidationResult = @import("entry.zig").ValidationResult;
+
+    var discrepancies = [_]Discrepancy{.{
+        .rule = .year_mismatch,
+        .field = .year,
+        .severity = .@"error",
+        .local_value = "2019",
+        .remote_value = "2018",
+        .message = "Year mismatch: 2019 vs 2018",
+    }};
+    var results = [_]ValidationResult{.{ .source = .dblp, .confidence = 0.9, .discrepancies = &discrepancies }};
+    var report = Report.init(allocator);
+    defer report.entries.deinit(allocator);
+    try report.add(.{
+        .entry = .{ .key = "o'brien2019", .entry_type = "article" },
+        .status = .@"error",
+        .validation_results = &results,
+    });
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeRun(&out.writer, &report, &.{"refs.bib"});
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "VALUES ((SELECT max(id) FROM runs), 'o''brien2019', 'error');\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "'o''brien2019', 'BV003', 'year', 'ERROR', 'Year mismatch: 2019 vs 2018');\n") != null);
+    try std.testing.expect(std.mem.endsWith(u8, out.written(), "COMMIT;\n"));
+
+    const rows = [_]Row{
+        .{ .run = 1, .started = "2026-09-01 10:00:00", .status = "error", .rule = "BV003", .field = "year", .severity = "ERROR", .message = "Year mismatch: 2019 vs 2018" },
+        .{ .run = 1, .started = "2026-09-01 10:00:00", .status = "error", .rule = "BV004", .field = "doi", .severity = "WARN", .message = "Missing DOI" },
+        .{ .run = 2, .started = "2026-09-08 10:00:00", .status = "warning", .rule = "BV004", .field = "doi", .severity = "WARN", .message = "Missing DOI" },
+        .{ .run = 3, .started = "2026-09-15 10:00:00", .status = "warning", .rule = "BV004", .field = "doi", .severity = "WARN", .message = "Missing DOI" },
+        .{ .run = 4, .started = "2026-09-18 10:00:00", .status = "failed" },
+        .{ .run = 5, .started = "2026-09-22 10:00:00", .status = "ok" },
+    };
+    out.clearRetainingCapacity();
+    try printTimeline(&out.writer, "smith2019", .{ .rows = &rows });
+    try std.testing.expectEqualStrings(
+        \\History of smith2019 (5 runs)
+        \\  2026-09-01 10:00:00  error
+        \\    + BV003 [ERROR] Year mismatch: 2019 vs 2018
+        \\    + BV004 [WARN] Missing DOI
+        \\  2026-09-08 10:00:00  warning
+        \\    - BV003 [ERROR] Year mismatch: 2019 vs 2018
+        \\  2026-09-15 10:00:00  warning  (no change)
+        \\  2026-09-18 10:00:00  failed  (lookup failed)
+        \\  2026-09-22 10:00:00  ok
+        \\    - BV004 [WARN] Missing DOI
+        \\
+    , out.written());
+}
@@ -1,11 +1,15 @@
 //! HTTP client wrapper for API requests.
 
 const std = @import("std");
+const trace = @import("trace.zig");
+const metrics = @import("metrics.zig");
 
 pub const HttpError = error{
     RequestFailed,
     RateLimited,
     NotFound,
+    /// Another client error (4xx), such as 401, 403, or 410
+    Unavailable,
     ParseError,
     OutOfMemory,
     ConnectionRefused,
@@ -13,30 +17,196 @@ pub const HttpError = error{
     InvalidUrl,
 };
 
+/// Pluggable transport used in place of the network (e.g. canned responses in tests).
+pub const Transport = struct {
+    ctx: *anyopaque,
+    /// Serves GET requests.
+    getFn: *const fn (ctx: *anyopaque, allocator: std.mem.Allocator, url: []const u8) HttpError![]u8,
+    /// Serves requests with a body (POST and PATCH).
+    postFn: ?*const fn (ctx: *anyopaque, allocator: std.mem.Allocator, url: []const u8, body: []const u8) HttpError![]u8 = null,
+
+    pub fn get(self: Transport, allocator: std.mem.Allocator, url: []const u8) HttpError![]u8 {
+        return self.getFn(self.ctx, allocator, url);
+    }
+
+    pub fn post(self: Transport, allocator: std.mem.Allocator, url: []const u8, body: []const u8) HttpError![]u8 {
+        const postFn = self.postFn orelse return HttpError.RequestFailed;
+        return postFn(self.ctx, allocator, url, body);
+    }
+};
+
+/// Response to a GET that does not follow redirects.
+pub const Fetched = union(enum) {
+    body: []u8,
+    /// Target of a redirect as its `Location` header gives it, possibly
+    /// relative (see `resolveUrl`).
+    redirect: []u8,
+};
+
 /// HTTP client for making API requests.
+///
+/// A single instance is shared by all validators so that connections are
+/// pooled and retry/rate-limit behaviour is applied in one place.
 pub const Client = struct {
     allocator: std.mem.Allocator,
     user_agent: []const u8,
+    /// Underlying std client; owns the connection pool.
+    inner: std.http.Client,
+    /// When set, requests are served by this transport instead of the network.
+    transport: ?Transport = null,
+    /// Number of retries after a rate-limited or timed-out request.
+    max_retries: u8 = 2,
+    /// Minimum delay between consecutive requests in milliseconds.
+    min_interval_ms: u64 = 0,
+    /// Timestamp of the last request in milliseconds.
+    last_request_ms: i64 = 0,
+    /// Total time spent waiting on rate limits and backoff, in milliseconds.
+    waited_ms: u64 = 0,
+    /// Receives a debug event for every request and response.
+    tracer: ?*const trace.Tracer = null,
+    /// Receives the latency and outcome of every GET request.
+    metrics: ?*metrics.Metrics = null,
 
     pub fn init(allocator: std.mem.Allocator, user_agent: []const u8) Client {
         return .{
             .allocator = allocator,
             .user_agent = user_agent,
+            .inner = .{ .allocator = allocator },
         };
     }
 
+    pub fn deinit(self: *Client) void {
+        self.inner.deinit();
+    }
+
     /// Make a GET request and return the response body.
-    pub fn get(self: *Client, url: []const u8) ![]u8 {
-        const uri = std.Uri.parse(url) catch return HttpError.InvalidUrl;
+    pub fn get(self: *Client, url: []const u8) HttpError![]u8 {
+        return self.getWithHeaders(url, &.{});
+    }
+
+    /// Make a GET request with extra headers, e.g. for an API key that
+    /// should not appear in the URL, and return the response body.
+    pub fn getWithHeaders(self: *Client, url: []const u8, headers: []const std.http.Header) HttpError![]u8 {
+        return switch (try self.fetch(url, headers, true)) {
+            .body => |body| body,
+            // Followed by the std client
+            .redirect => |location| {
+                self.allocator.free(location);
+                return HttpError.RequestFailed;
+            },
+        };
+    }
+
+    /// Make a GET request, returning a redirect instead of following it so
+    /// the caller can check where it leads. Caller owns the body or target.
+    pub fn getUnredirected(self: *Client, url: []const u8) HttpError!Fetched {
+        return self.fetch(url, &.{}, false);
+    }
+
+    fn fetch(self: *Client, url: []const u8, headers: []const std.http.Header, follow_redirects: bool) HttpError!Fetched {
+        var attempt: u8 = 0;
+        while (true) : (attempt += 1) {
+            self.throttle();
+            if (self.tracer) |t| {
+                // API keys in the query are secrets, so their values are cut
+                const shown = redactApiKey(url);
+                t.event(.debug, "http", "GET {s}{s}{s}", .{ shown.head, if (shown.redacted) "<key redacted>" else "", shown.tail });
+            }
+
+            const start_ms = std.time.milliTimestamp();
+            // Transports serve bodies only; they never redirect
+            const result: HttpError!Fetched = if (self.transport) |t|
+                (if (t.get(self.allocator, url)) |body| .{ .body = body } else |err| err)
+            else
+                self.send(.GET, url, null, null, headers, follow_redirects);
+            const elapsed_ms = std.time.milliTimestamp() - start_ms;
+            if (self.metrics) |m| m.request(@intCast(@max(elapsed_ms, 0)), if (result) |_| true else |_| false);
+
+            if (result) |fetched| {
+                if (self.tracer) |t| switch (fetched) {
+                    .body => |body| t.event(.debug, "http", "{d} bytes in {d}ms", .{ body.len, elapsed_ms }),
+                    .redirect => |location| t.event(.debug, "http", "redirect to {s} in {d}ms", .{ location, elapsed_ms }),
+                };
+                return fetched;
+            } else |err| switch (err) {
+                HttpError.RateLimited, HttpError.Timeout => {
+                    if (self.tracer) |t| t.event(.debug, "http", "{s} after {d}ms (attempt {d})", .{ @errorName(err), elapsed_ms, attempt + 1 });
+                    if (attempt >= self.max_retries) return err;
+                    // Exponential backoff: 1s, 2s, 4s, ...
+                    self.wait(std.time.ms_per_s * (@as(u64, 1) << @intCast(attempt)));
+                },
+                else => {
+                    if (self.tracer) |t| t.event(.debug, "http", "{s} after {d}ms", .{ @errorName(err), elapsed_ms });
+                    return err;
+                },
+            }
+        }
+    }
+
+    /// Make a POST request and return the response body. POST requests are
+    /// not retried, since they may not be idempotent.
+    pub fn post(self: *Client, url: []const u8, content_type: []const u8, body: []u8) HttpError![]u8 {
+        return self.upload(.POST, url, content_type, body, &.{});
+    }
+
+    /// Make a PATCH request with extra headers and return the response
+    /// body. Not retried.
+    pub fn patch(self: *Client, url: []const u8, content_type: []const u8, body: []u8, headers: []const std.http.Header) HttpError![]u8 {
+        return self.upload(.PATCH, url, content_type, body, headers);
+    }
+
+    fn upload(self: *Client, method: std.http.Method, url: []const u8, content_type: []const u8, body: []u8, headers: []const std.http.Header) HttpError![]u8 {
+        self.throttle();
+        // Webhook URLs are secrets, so only the origin is traced
+        if (self.tracer) |t| t.event(.debug, "http", "{s} {s} ({d} bytes)", .{ @tagName(method), origin(url), body.len });
+
+        const start_ms = std.time.milliTimestamp();
+        const result = if (self.transport) |t|
+            t.post(self.allocator, url, body)
+        else if (self.send(method, url, content_type, body, headers, true)) |fetched|
+            fetched.body
+        else |err|
+            err;
+        const elapsed_ms = std.time.milliTimestamp() - start_ms;
+
+        if (result) |response| {
+            if (self.tracer) |t| t.event(.debug, "http", "{d} bytes in {d}ms", .{ response.len, elapsed_ms });
+        } else |err| {
+            if (self.tracer) |t| t.event(.debug, "http", "{s} after {d}ms", .{ @errorName(err), elapsed_ms });
+        }
+        return result;
+    }
+
+    /// Wait until `min_interval_ms` has elapsed since the previous request.
+    fn throttle(self: *Client) void {
+        if (self.min_interval_ms > 0 and self.last_request_ms > 0) {
+            const elapsed = std.time.milliTimestamp() - self.last_request_ms;
+            const interval: i64 = @intCast(self.min_interval_ms);
+            if (elapsed < interval) self.wait(@intCast(interval - elapsed));
+        }
+        self.last_request_ms = std.time.milliTimestamp();
+    }
+
+    /// Sleep for `ms` milliseconds, counted in `waited_ms`.
+    pub fn wait(self: *Client, ms: u64) void {
+        if (self.tracer) |t| t.event(.debug, "wait", "{d}ms", .{ms});
+        self.waited_ms += ms;
+        std.Thread.sleep(ms * std.time.ns_per_ms);
+    }
 
-        var client: std.http.Client = .{ .allocator = self.allocator };
-        defer client.deinit();
+    /// Send a request, with `body` unless it is null. Without
+    /// `follow_redirects`, a redirect is returned rather than followed.
+    fn send(self: *Client, method: std.http.Method, url: []const u8, content_type: ?[]const u8, body: ?[]u8, headers: []const std.http.Header, follow_redirects: bool) HttpError!Fetched {
+        const uri = std.Uri.parse(url) catch return HttpError.InvalidUrl;
 
         // Create request
-        var req = client.request(.GET, uri, .{
+        var req = self.inner.request(method, uri, .{
             .headers = .{
                 .user_agent = .{ .override = self.user_agent },
+                .content_type = if (content_type) |ct| .{ .override = ct } else .default,
             },
+            .extra_headers = headers,
+            .redirect_behavior = if (follow_redirects) .init(3) else .unhandled,
         }) catch |err| {
             return switch (err) {
                 error.ConnectionRefused => HttpError.ConnectionRefused,
@@ -46,33 +216,90 @@ pub const Client = struct {
         };
         defer req.deinit();
 
-        // Send request (no body for GET)
-        req.sendBodiless() catch return HttpError.RequestFailed;
+        if (body) |b| {
+            req.sendBodyComplete(b) catch return HttpError.RequestFailed;
+        } else {
+            req.sendBodiless() catch return HttpError.RequestFailed;
+        }
 
         // Receive response headers
         var redirect_buf: [8 * 1024]u8 = undefined;
         var response = req.receiveHead(&redirect_buf) catch return HttpError.RequestFailed;
 
         // Check status code
+        if (!follow_redirects and response.head.status.class() == .redirect) {
+            const location = response.head.location orelse return HttpError.RequestFailed;
+            return .{ .redirect = try self.allocator.dupe(u8, location) };
+        }
         if (response.head.status == .not_found) {
             return HttpError.NotFound;
         }
         if (response.head.status == .too_many_requests) {
             return HttpError.RateLimited;
         }
-        if (@intFromEnum(response.head.status) >= 400) {
+        if (@intFromEnum(response.head.status) >= 500) {
             return HttpError.RequestFailed;
         }
+        if (@intFromEnum(response.head.status) >= 400) {
+            return HttpError.Unavailable;
+        }
 
         // Read response body
         var transfer_buf: [4096]u8 = undefined;
         const reader = response.reader(&transfer_buf);
 
         const body = reader.allocRemaining(self.allocator, .limited(10 * 1024 * 1024)) catch return HttpError.RequestFailed;
-        return body;
+        return .{ .body = body };
     }
 };
 
+/// The scheme and host of a URL, e.g. "https://hooks.slack.com" for a
+/// Slack webhook, without the path that holds its token.
+pub fn origin(url: []const u8) []const u8 {
+    const host_start = if (std.mem.indexOf(u8, url, "://")) |i| i + 3 else 0;
+    const end = std.mem.indexOfAnyPos(u8, url, host_start, "/?#") orelse url.len;
+    return url[0..end];
+}
+
+/// The absolute URL a redirect from `base` to `location` leads to.
+/// Caller owns the result.
+pub fn resolveUrl(allocator: std.mem.Allocator, base: []const u8, location: []const u8) ![]u8 {
+    if (std.mem.indexOf(u8, location, "://") != null) return allocator.dupe(u8, location);
+    const scheme_end = std.mem.indexOf(u8, base, "://") orelse return HttpError.InvalidUrl;
+    // Scheme-relative, "//example.org/page"
+    if (std.mem.startsWith(u8, location, "//")) return std.fmt.allocPrint(allocator, "{s}:{s}", .{ base[0..scheme_end], location });
+    const site = origin(base);
+    if (std.mem.startsWith(u8, location, "/")) return std.fmt.allocPrint(allocator, "{s}{s}", .{ site, location });
+    // Relative to the directory of the base path
+    const path = base[site.len .. std.mem.indexOfAnyPos(u8, base, site.len, "?#") orelse base.len];
+    const dir = path[0 .. if (std.mem.lastIndexOfScalar(u8, path, '/')) |i| i + 1 else 0];
+    return std.fmt.allocPrint(allocator, "{s}{s}{s}{s}", .{ site, if (dir.len == 0) "/" else "", dir, location });
+}
+
+/// A URL split around the value of its API key parameter (`apikey`,
+/// `apiKey`, or `api_key`).
+const Redacted = struct {
+    head: []const u8,
+    tail: []const u8 = "",
+    redacted: bool = false,
+};
+
+fn redactApiKey(url: []const u8) Redacted {
+    const query = std.mem.indexOfScalar(u8, url, '?') orelse return .{ .head = url };
+    var start = query + 1;
+    while (start < url.len) {
+        const end = std.mem.indexOfScalarPos(u8, url, start, '&') orelse url.len;
+        const param = url[start..end];
+        for ([_][]const u8{ "apikey=", "api_key=" }) |name| {
+            if (std.ascii.startsWithIgnoreCase(param, name)) {
+                return .{ .head = url[0 .. start + name.len], .tail = url[end..], .redacted = true };
+            }
+        }
+        start = end + 1;
+    }
+    return .{ .head = url };
+}
+
 /// URL encode a string.
 pub fn urlEncode(allocator: std.mem.Allocator, input: []const u8) ![]u8 {
     var result: std.ArrayList(u8) = .empty;
@@ -102,3 +329,69 @@ test "urlEncode" {
     defer allocator.free(result);
     try std.testing.expectEqualStrings("hello+world%21", result);
 }
+
+test "redirect targets" {
+    const allocator = std.testing.allocator;
+    const cases = [_]struct { []const u8, []const u8 }{
+        .{ "http://169.254.169.254/latest/meta-data/", "http://169.254.169.254/latest/meta-data/" },
+        .{ "//localhost/admin", "https://localhost/admin" },
+        .{ "/login?next=%2F", "https://example.org/login?next=%2F" },
+        .{ "page2.html", "https://example.org/docs/page2.html" },
+    };
+    for (cases) |case| {
+        const resolved = try resolveUrl(allocator, "https://example.org/docs/page1.html?x=1", case[0]);
+        defer allocator.free(resolved);
+        try std.testing.expectEqualStrings(case[1], resolved);
+    }
+    const from_root = try resolveUrl(allocator, "https://example.org", "about");
+    defer allocator.free(from_root);
+    try std.testing.expectEqualStrings("https://example.org/about", from_root);
+}
+
+test "Client uses injected transport" {
+    const allocator = std.testing.allocator;
+
+    const Echo = struct {
+        fn get(_: *anyopaque, alloc: std.mem.Allocator, url: []const u8) HttpError![]u8 {
+            return alloc.dupe(u8, url);
+        }
+    };
+
+    var ctx: u8 = 0;
+    var client = Client.init(allocator, "bibval-test");
+    defer client.deinit();
+    client.transport = .{ .ctx = &ctx, .getFn = Echo.get };
+
+    const body = try client.get("https://example.org/works");
+    defer allocator.free(body);
+    try std.testing.expectEqualStrings("https://example.org/works", body);
+}
+
+test "traced requests do not show API keys" {
+    const allocator = std.testing.allocator;
+
+    const Echo = struct {
+        fn get(_: *anyopaque, alloc: std.mem.Allocator, url: []const u8) HttpError![]u8 {
+            return alloc.dupe(u8, url);
+        }
+    };
+
+    var events: std.Io.Writer.Allocating = .init(allocator);
+    defer events.deinit();
+    const tracer = trace.Tracer{ .level = .debug, .out = &events.writer };
+
+    var ctx: u8 = 0;
+    var client = Client.init(allocator, "bibval-test");
+    defer client.deinit();
+    client.transport = .{ .ctx = &ctx, .getFn = Echo.get };
+    client.tracer = &tracer;
+
+    const body = try client.get("https://api.springernature.com/meta/v2/json?api_key=s3cr3t&q=doi%3A10.1007%2Fx");
+    defer allocator.free(body);
+    try std.testing.expect(std.mem.indexOf(u8, events.written(), "s3cr3t") == null);
+    try std.testing.expect(std.mem.indexOf(u8, events.written(), "GET https://api.springernature.com/meta/v2/json?api_key=<key redacted>&q=doi%3A10.1007%2Fx\n") != null);
+
+    const unkeyed = redactApiKey("https://api.crossref.org/works?query=keyboard");
+    try std.testing.expect(!unkeyed.redacted);
+    try std.testing.expectEqualStrings("https://api.crossref.org/works?query=keyboard", unkeyed.head);
+}
@@ -0,0 +1,94 @@
+//! Entries known not to be in any database.
+//!
+//! Lecture notes, personal communications, and unpublished manuscripts
+//! will never be found, and should not fail a run every time. Each is
+//! listed in an `[[ignore]]` table of the config file, optionally with a
+//! date until which it is skipped; once that date has passed the entry is
+//! validated again, in case it has been published in the meantime.
+
+const std = @import("std");
+
+/// A calendar date, as written in the config ("2026-06-30").
+pub const Date = struct {
+    year: i32,
+    month: u8,
+    day: u8,
+
+    /// Parse an ISO 8601 date (`YYYY-MM-DD`).
+    pub fn parse(text: []const u8) ?Date {
+        if (text.len != 10 or text[4] != '-' or text[7] != '-') return null;
+        const year = std.fmt.parseInt(i32, text[0..4], 10) catch return null;
+        const month = std.fmt.parseInt(u8, text[5..7], 10) catch return null;
+        const day = std.fmt.parseInt(u8, text[8..10], 10) catch return null;
+        if (month < 1 or month > 12 or day < 1 or day > 31) return null;
+        return .{ .year = year, .month = month, .day = day };
+    }
+
+    /// Current date in UTC.
+    pub fn today() Date {
+        const secs: u64 = @intCast(@max(0, std.time.timestamp()));
+        const epoch = std.time.epoch.EpochSeconds{ .secs = secs };
+        const year_day = epoch.getEpochDay().calculateYearDay();
+        const month_day = year_day.calculateMonthDay();
+        return .{ .year = year_day.year, .month = month_day.month.numeric(), .day = month_day.day_index + 1 };
+    }
+
+    pub fn order(a: Date, b: Date) std.math.Order {
+        if (a.year != b.year) return std.math.order(a.year, b.year);
+        if (a.month != b.month) return std.math.order(a.month, b.month);
+        return std.math.order(a.day, b.day);
+    }
+};
+
+/// An entry that is not expected to be found.
+pub const Ignore = struct {
+    key: []const u8,
+    /// Why, e.g. "lecture notes"; shown in the report.
+    reason: []const u8 = "",
+    /// Last day the entry is skipped; it is skipped forever without one.
+    until: ?Date = null,
+
+    /// Whether the entry is still skipped on `date`.
+    pub fn isActive(self: Ignore, date: Date) bool {
+        const until = self.until orelse return true;
+        return date.order(until) != .gt;
+    }
+
+    /// Text shown for the entry in the report, e.g. "lecture notes (until
+    /// 2026-06-30)".
+    pub fn describe(self: Ignore, allocator: std.mem.Allocator) ![]u8 {
+        const reason = if (self.reason.len > 0) self.reason else "known unmatched";
+        if (self.until) |until| return std.fmt.allocPrint(allocator, "{s} (until {d:0>4}-{d:0>2}-{d:0>2})", .{ reason, until.year, until.month, until.day });
+        return allocator.dupe(u8, reason);
+    }
+};
+
+/// The ignore for `key`, if any.
+pub fn find(ignores: []const Ignore, key: []const u8) ?*const Ignore {
+    for (ignores) |*ignore| {
+        if (std.mem.eql(u8, ignore.key, key)) return ignore;
+    }
+    return null;
+}
+
+test "ignores expire after their date" {
+    const ignores = [_]Ignore{
+        .{ .key = "notes", .reason = "lecture notes", .until = Date.parse("2026-06-30") },
+        .{ .key = "pc", .reason = "personal communication" },
+    };
+    const before = Date.parse("2026-06-30").?;
+    const after = Date.parse("2026-07-01").?;
+
+    const notes = find(&ignores, "notes").?;
+    try std.testing.expect(notes.isActive(before));
+    try std.testing.expect(!notes.isActive(after));
+    try std.testing.expect(find(&ignores, "pc").?.isActive(after));
+    try std.testing.expect(find(&ignores, "smith2020") == null);
+
+    try std.testing.expect(Date.parse("2026-13-01") == null);
+    try std.testing.expect(Date.parse("30.06.2026") == null);
+
+    const text = try notes.describe(std.testing.allocator);
+    defer std.testing.allocator.free(text);
+    try std.testing.expectEqualStrings("lecture notes (until 2026-06-30)", text);
+}
@@ -0,0 +1,155 @@
+//! Input formats.
+//!
+//! Picks a parser for a reference file by extension, or by looking at the
+//! content when there is none (stdin, the clipboard).
+
+const std = @import("std");
+const builtin = @import("builtin");
+const entry_mod = @import("entry.zig");
+const bibtex = @import("bibtex.zig");
+const ris = @import("ris.zig");
+const csl = @import("csl.zig");
+const freetext = @import("freetext.zig");
+const encoding = @import("encoding.zig");
+const Entry = entry_mod.Entry;
+
+/// Largest input read from a file, stdin or the clipboard.
+pub const MAX_INPUT_SIZE = 10 * 1024 * 1024;
+
+pub const Format = enum {
+    bibtex,
+    ris,
+    csl_json,
+    freetext,
+
+    pub fn name(self: Format) []const u8 {
+        return switch (self) {
+            .bibtex => "BibTeX",
+            .ris => "RIS",
+            .csl_json => "CSL-JSON",
+            .freetext => "plain text",
+        };
+    }
+};
+
+/// Format implied by a file extension, if any.
+pub fn fromPath(path: []const u8) ?Format {
+    const ext = std.fs.path.extension(path);
+    if (std.ascii.eqlIgnoreCase(ext, ".bib")) return .bibtex;
+    if (std.ascii.eqlIgnoreCase(ext, ".ris")) return .ris;
+    if (std.ascii.eqlIgnoreCase(ext, ".json")) return .csl_json;
+    if (std.ascii.eqlIgnoreCase(ext, ".txt")) return .freetext;
+    return null;
+}
+
+/// Guess the format of `content`: JSON objects or arrays of objects are
+/// CSL-JSON, a leading `TY  -` tag is RIS, any line starting an `@type{`
+/// entry is BibTeX, and anything else is a plain-text reference list.
+pub fn detect(content: []const u8) Format {
+    const trimmed = std.mem.trimLeft(u8, content, " \t\r\n\u{FEFF}");
+    if (std.mem.startsWith(u8, trimmed, "{")) return .csl_json;
+    if (std.mem.startsWith(u8, trimmed, "[")) {
+        // "[1] Smith, J. ..." is a numbered reference, not a JSON array
+        const inner = std.mem.trimLeft(u8, trimmed[1..], " \t\r\n");
+        if (inner.len == 0 or inner[0] == '{' or inner[0] == ']') return .csl_json;
+    }
+    if (ris.looksLikeRis(content)) return .ris;
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        const line = std.mem.trimLeft(u8, raw, " \t");
+        if (line.len < 2 or line[0] != '@') continue;
+        var i: usize = 1;
+        while (i < line.len and std.ascii.isAlphabetic(line[i])) i += 1;
+        const rest = std.mem.trimLeft(u8, line[i..], " \t");
+        if (i > 1 and rest.len > 0 and (rest[0] == '{' or rest[0] == '(')) return .bibtex;
+    }
+    return .freetext;
+}
+
+/// What was found wrong with an input while reading it.
+pub const Diagnostics = struct {
+    encoding: encoding.Encoding = .utf8,
+    /// Byte offsets that could not be decoded cleanly.
+    encoding_issues: std.ArrayList(encoding.Issue) = .empty,
+    /// Malformed BibTeX the parser skipped over.
+    parse: std.ArrayList(bibtex.Diagnostic) = .empty,
+
+    pub fn deinit(self: *Diagnostics, allocator: std.mem.Allocator) void {
+        self.encoding_issues.deinit(allocator);
+        self.parse.deinit(allocator);
+    }
+};
+
+/// Transcode raw input to UTF-8, recording its encoding and any bytes
+/// that could not be decoded in `diagnostics` if given.
+pub fn decode(allocator: std.mem.Allocator, raw: []const u8, diagnostics: ?*Diagnostics) !encoding.Decoded {
+    var decoded = try encoding.toUtf8(allocator, raw);
+    errdefer decoded.deinit();
+    if (diagnostics) |d| {
+        d.encoding = decoded.encoding;
+        try d.encoding_issues.appendSlice(allocator, decoded.issues);
+    }
+    return decoded;
+}
+
+/// Parse UTF-8 `content` in the given format. Malformed BibTeX is
+/// recorded in `diagnostics` if given.
+pub fn parse(allocator: std.mem.Allocator, content: []const u8, format: Format, diagnostics: ?*Diagnostics) ![]Entry {
+    return switch (format) {
+        .bibtex => try bibtex.parseStringDiagnostics(allocator, content, if (diagnostics) |d| &d.parse else null),
+        .ris => try ris.parseString(allocator, content),
+        .csl_json => try csl.parseString(allocator, content),
+        .freetext => try freetext.parseString(allocator, content),
+    };
+}
+
+/// Parse a reference file. Files without a known extension are
+/// detected from their content, defaulting to BibTeX.
+pub fn parseFile(allocator: std.mem.Allocator, path: []const u8, diagnostics: ?*Diagnostics) ![]Entry {
+    const raw = try std.fs.cwd().readFileAlloc(allocator, path, MAX_INPUT_SIZE);
+    defer allocator.free(raw);
+    var decoded = try decode(allocator, raw, diagnostics);
+    defer decoded.deinit();
+    const content = decoded.text;
+
+    const format = fromPath(path) orelse switch (detect(content)) {
+        .freetext => .bibtex,
+        else => |f| f,
+    };
+    return parse(allocator, content, format, diagnostics);
+}
+
+/// Read the system clipboard with the platform's paste command.
+pub fn readClipboard(allocator: std.mem.Allocator) ![]u8 {
+    const commands: []const []const []const u8 = switch (builtin.os.tag) {
+        .macos => &.{&.{"pbpaste"}},
+        .windows => &.{&.{ "powershell.exe", "-NoProfile", "-Command", "Get-Clipboard" }},
+        else => &.{
+            &.{ "wl-paste", "--no-newline" },
+            &.{ "xclip", "-selection", "clipboard", "-o" },
+            &.{ "xsel", "--clipboard", "--output" },
+        },
+    };
+
+    for (commands) |argv| {
+        const result = std.process.Child.run(.{
+            .allocator = allocator,
+            .argv = argv,
+            .max_output_bytes = MAX_INPUT_SIZE,
+        }) catch continue;
+        allocator.free(result.stderr);
+        if (result.term == .Exited and result.term.Exited == 0) return result.stdout;
+        allocator.free(result.stdout);
+    }
+    return error.ClipboardUnavailable;
+}
+
+test "detect input format" {
+    try std.testing.expectEqual(Format.bibtex, detect("% refs\n@article{a,\n  title = {A}\n}\n"));
+    try std.testing.expectEqual(Format.ris, detect("\nTY  - JOUR\nTI  - A\nER  - \n"));
+    try std.testing.expectEqual(Format.csl_json, detect("  [{\"id\": \"a\"}]"));
+    try std.testing.expectEqual(Format.freetext, detect("[1] J. Smith, \"A title,\" Nature, 2020. Contact @smith"));
+    try std.testing.expectEqual(Format.ris, fromPath("refs.RIS").?);
+    try std.testing.expectEqual(@as(?Format, null), fromPath("refs"));
+}
@@ -0,0 +1,234 @@
+//! JabRef metadata.
+//!
+//! JabRef keeps library settings in `@Comment{jabref-meta: ...}` blocks at
+//! the end of a .bib file: the group tree, file directories, save actions,
+//! and so on. The parser skips these blocks and patches only touch entry
+//! fields, so they survive a round trip untouched. This module reads the
+//! group tree so that a run can be limited to the entries of a group.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+const META_PREFIX = "jabref-meta:";
+
+/// A node of the group tree. Level 0 is the "All Entries" root.
+pub const Group = struct {
+    level: u32,
+    /// Group type, e.g. "StaticGroup" or "KeywordGroup".
+    kind: []const u8,
+    name: []const u8,
+};
+
+/// A `jabref-meta` setting, e.g. "fileDirectory" = "papers".
+pub const Setting = struct {
+    name: []const u8,
+    value: []const u8,
+};
+
+/// Metadata of a JabRef library. All memory is owned by the arena.
+pub const Metadata = struct {
+    groups: []const Group = &.{},
+    /// Other settings, with their raw values.
+    settings: []const Setting = &.{},
+    arena: std.heap.ArenaAllocator,
+
+    pub fn deinit(self: *Metadata) void {
+        self.arena.deinit();
+    }
+
+    pub fn get(self: *const Metadata, name: []const u8) ?[]const u8 {
+        for (self.settings) |setting| {
+            if (std.mem.eql(u8, setting.name, name)) return setting.value;
+        }
+        return null;
+    }
+
+    /// Directory for linked files, preferring the user-specific setting.
+    pub fn fileDirectory(self: *const Metadata) ?[]const u8 {
+        for (self.settings) |setting| {
+            if (std.mem.startsWith(u8, setting.name, "fileDirectory-")) return setting.value;
+        }
+        return self.get("fileDirectory");
+    }
+
+    /// Append `name` and the names of all its subgroups to `names`.
+    pub fn collectSubgroups(self: *const Metadata, allocator: std.mem.Allocator, name: []const u8, names: *std.ArrayList([]const u8)) !void {
+        var i: usize = 0;
+        while (i < self.groups.len) : (i += 1) {
+            const group = self.groups[i];
+            if (!std.mem.eql(u8, group.name, name)) continue;
+            try names.append(allocator, group.name);
+            var j = i + 1;
+            while (j < self.groups.len and self.groups[j].level > group.level) : (j += 1) {
+                try names.append(allocator, self.groups[j].name);
+            }
+        }
+    }
+};
+
+/// Read the `jabref-meta` blocks of a .bib file.
+pub fn parseMetadata(allocator: std.mem.Allocator, content: []const u8) !Metadata {
+    var metadata = Metadata{ .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer metadata.deinit();
+    const arena = metadata.arena.allocator();
+
+    var groups: std.ArrayList(Group) = .empty;
+    var settings: std.ArrayList(Setting) = .empty;
+
+    var pos: usize = 0;
+    while (std.mem.indexOfScalarPos(u8, content, pos, '@')) |at| {
+        pos = at + 1;
+        const comment = findComment(content, at) orelse continue;
+        pos = comment.end;
+
+        const meta = std.mem.trimLeft(u8, comment.body, " \t\r\n");
+        if (!std.mem.startsWith(u8, meta, META_PREFIX)) continue;
+        const rest = std.mem.trimLeft(u8, meta[META_PREFIX.len..], " ");
+        const colon = std.mem.indexOfScalar(u8, rest, ':') orelse continue;
+        const name = std.mem.trim(u8, rest[0..colon], " ");
+        const value = rest[colon + 1 ..];
+
+        if (std.mem.eql(u8, name, "grouping") or std.mem.eql(u8, name, "groupstree")) {
+            try parseGroups(arena, value, &groups);
+        } else {
+            const unescaped = try unescape(arena, std.mem.trim(u8, value, " \t\r\n"));
+            try settings.append(arena, .{
+                .name = try arena.dupe(u8, name),
+                .value = std.mem.trimRight(u8, unescaped, ";"),
+            });
+        }
+    }
+
+    metadata.groups = groups.items;
+    metadata.settings = settings.items;
+    return metadata;
+}
+
+const Comment = struct {
+    /// Text between the braces.
+    body: []const u8,
+    /// Offset just past the closing brace.
+    end: usize,
+};
+
+/// The `@comment{...}` at `at`, or null if the entry there is not a comment.
+fn findComment(content: []const u8, at: usize) ?Comment {
+    const keyword = "comment";
+    const start = at + 1;
+    if (start + keyword.len > content.len) return null;
+    if (!std.ascii.eqlIgnoreCase(content[start .. start + keyword.len], keyword)) return null;
+
+    var i = start + keyword.len;
+    while (i < content.len and std.ascii.isWhitespace(content[i])) i += 1;
+    if (i >= content.len or content[i] != '{') return null;
+
+    var depth: usize = 1;
+    var j = i + 1;
+    while (j < content.len) : (j += 1) {
+        switch (content[j]) {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if (depth == 0) return .{ .body = content[i + 1 .. j], .end = j + 1 };
+            },
+            else => {},
+        }
+    }
+    return null;
+}
+
+/// Parse group lines such as `1 StaticGroup:Reviewed\;0\;1\;\;\;\;;`.
+fn parseGroups(arena: std.mem.Allocator, value: []const u8, groups: *std.ArrayList(Group)) !void {
+    var start: usize = 0;
+    var i: usize = 0;
+    while (i < value.len) : (i += 1) {
+        if (value[i] == '\\') {
+            i += 1;
+            continue;
+        }
+        if (value[i] != ';') continue;
+        const line = std.mem.trim(u8, value[start..i], " \t\r\n");
+        start = i + 1;
+
+        const space = std.mem.indexOfScalar(u8, line, ' ') orelse continue;
+        const level = std.fmt.parseInt(u32, line[0..space], 10) catch continue;
+        const spec = line[space + 1 ..];
+        const colon = std.mem.indexOfScalar(u8, spec, ':') orelse continue;
+
+        // The name runs to the first escaped separator
+        const fields = spec[colon + 1 ..];
+        const name_end = std.mem.indexOf(u8, fields, "\\;") orelse fields.len;
+        try groups.append(arena, .{
+            .level = level,
+            .kind = try arena.dupe(u8, spec[0..colon]),
+            .name = try unescape(arena, fields[0..name_end]),
+        });
+    }
+}
+
+/// Copy of `s` with backslash escapes removed.
+fn unescape(arena: std.mem.Allocator, s: []const u8) ![]const u8 {
+    var out: std.ArrayList(u8) = .empty;
+    var i: usize = 0;
+    while (i < s.len) : (i += 1) {
+        if (s[i] == '\\' and i + 1 < s.len) i += 1;
+        try out.append(arena, s[i]);
+    }
+    return out.items;
+}
+
+/// Whether an entry is assigned to any of `names` in its `groups` field.
+pub fn inAnyGroup(e: *const Entry, names: []const []const u8) bool {
+    const groups = e.groups orelse return false;
+    var iter = std.mem.splitScalar(u8, groups, ',');
+    while (iter.next()) |raw| {
+        const group = std.mem.trim(u8, raw, " \t\r\n");
+        for (names) |name| {
+            if (std.mem.eql(u8, group, name)) return true;
+        }
+    }
+    return false;
+}
+
+test "JabRef groups and settings" {
+    const allocator = std.testing.allocator;
+
+    const bib =
+        \\@article{a, title = {A}, groups = {Reviewed}}
+        \\@article{b, title = {B}, groups = {To Read, Methods}}
+        \\@article{c, title = {C}}
+        \\
+        \\@Comment{jabref-meta: databaseType:bibtex;}
+        \\
+        \\@Comment{jabref-meta: fileDirectory:papers;}
+        \\
+        \\@Comment{jabref-meta: grouping:
+        \\0 AllEntriesGroup:;
+        \\1 StaticGroup:Reviewed\;0\;1\;0x8a8a8aff\;\;\;;
+        \\2 StaticGroup:Methods\;0\;1\;\;\;\;;
+        \\1 StaticGroup:To Read\;0\;1\;\;\;\;;
+        \\}
+    ;
+
+    var metadata = try parseMetadata(allocator, bib);
+    defer metadata.deinit();
+
+    try std.testing.expectEqual(@as(usize, 4), metadata.groups.len);
+    try std.testing.expectEqualStrings("Methods", metadata.groups[2].name);
+    try std.testing.expectEqual(@as(u32, 2), metadata.groups[2].level);
+    try std.testing.expectEqualStrings("papers", metadata.fileDirectory().?);
+    try std.testing.expectEqualStrings("bibtex", metadata.get("databaseType").?);
+
+    var names: std.ArrayList([]const u8) = .empty;
+    defer names.deinit(allocator);
+    try metadata.collectSubgroups(allocator, "Reviewed", &names);
+    try std.testing.expectEqual(@as(usize, 2), names.items.len);
+
+    const a = Entry{ .key = "a", .entry_type = "article", .groups = "Reviewed" };
+    const b = Entry{ .key = "b", .entry_type = "article", .groups = "To Read, Methods" };
+    const c = Entry{ .key = "c", .entry_type = "article" };
+    try std.testing.expect(inAnyGroup(&a, names.items));
+    try std.testing.expect(inAnyGroup(&b, names.items));
+    try std.testing.expect(!inAnyGroup(&c, names.items));
+}
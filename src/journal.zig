@@ -0,0 +1,372 @@
+//! Progress journal for resumable runs.
+//!
+//! Each completed entry is appended to the journal as one JSON line. An
+//! interrupted run leaves the journal behind, and `--resume` replays it so
+//! completed entries are not validated again. Entries whose content changed
+//! since they were journaled are validated afresh. The journal is removed
+//! once a run completes.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const snapshot = @import("snapshot.zig");
+const Entry = entry_mod.Entry;
+const ApiSource = entry_mod.ApiSource;
+const Discrepancy = entry_mod.Discrepancy;
+const DiscrepancyField = entry_mod.DiscrepancyField;
+const Severity = entry_mod.Severity;
+const ValidationResult = entry_mod.ValidationResult;
+const EntryReport = report_mod.EntryReport;
+const EntryStatus = report_mod.EntryStatus;
+const writeJsonString = report_mod.writeJsonString;
+const Rule = @import("rules.zig").Rule;
+
+/// Journal location used when `--journal` is not given.
+pub const DEFAULT_PATH = ".bibval-journal.jsonl";
+
+pub const Journal = struct {
+    allocator: std.mem.Allocator,
+    dir: std.fs.Dir,
+    path: []const u8,
+    file: ?std.fs.File,
+    /// Lines of the previous journal keyed by citation key.
+    completed: std.StringHashMap([]const u8),
+    /// Raw contents of the previous journal; `completed` values slice into it.
+    previous: []u8,
+
+    /// Open the journal at `path`. When `resuming`, entries recorded by an
+    /// earlier run are loaded and new records are appended; otherwise the
+    /// journal starts empty.
+    pub fn open(allocator: std.mem.Allocator, dir: std.fs.Dir, path: []const u8, resuming: bool) !Journal {
+        var previous: []u8 = &.{};
+        if (resuming) {
+            if (dir.openFile(path, .{})) |file| {
+                defer file.close();
+                previous = try file.readToEndAlloc(allocator, 100 * 1024 * 1024);
+            } else |err| switch (err) {
+                error.FileNotFound => {},
+                else => return err,
+            }
+        }
+        errdefer allocator.free(previous);
+
+        var completed = std.StringHashMap([]const u8).init(allocator);
+        errdefer {
+            var keys = completed.keyIterator();
+            while (keys.next()) |k| allocator.free(k.*);
+            completed.deinit();
+        }
+
+        var lines = std.mem.tokenizeScalar(u8, previous, '\n');
+        while (lines.next()) |line| {
+            const key = try recordKey(allocator, line) orelse continue;
+            const gop = try completed.getOrPut(key);
+            if (gop.found_existing) {
+                allocator.free(key);
+            }
+            gop.value_ptr.* = line;
+        }
+
+        const file = try dir.createFile(path, .{ .truncate = !resuming });
+        errdefer file.close();
+        try file.seekFromEnd(0);
+        // A run killed mid-write leaves a partial line; end it so the
+        // next record starts a line of its own
+        if (previous.len > 0 and previous[previous.len - 1] != '\n') try file.writeAll("\n");
+
+        return .{
+            .allocator = allocator,
+            .dir = dir,
+            .path = path,
+            .file = file,
+            .completed = completed,
+            .previous = previous,
+        };
+    }
+
+    pub fn deinit(self: *Journal) void {
+        if (self.file) |f| f.close();
+        var keys = self.completed.keyIterator();
+        while (keys.next()) |k| self.allocator.free(k.*);
+        self.completed.deinit();
+        self.allocator.free(self.previous);
+    }
+
+    /// Number of entries recorded by the previous run.
+    pub fn restorableCount(self: *const Journal) usize {
+        return self.completed.count();
+    }
+
+    /// Rebuild the report of an entry completed by a previous run, or null
+    /// if it was not journaled or has been edited since.
+    pub fn restore(self: *Journal, local: *const Entry) !?EntryReport {
+        const line = self.completed.get(local.key) orelse return null;
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, line, .{}) catch return null;
+        defer parsed.deinit();
+
+        return parseRecord(self.allocator, parsed.value, local);
+    }
+
+    /// Append a completed entry to the journal.
+    pub fn record(self: *Journal, entry_report: *const EntryReport) !void {
+        const file = self.file orelse return;
+
+        var aw: std.Io.Writer.Allocating = .init(self.allocator);
+        defer aw.deinit();
+
+        try writeRecord(&aw.writer, entry_report);
+        try aw.writer.writeByte('\n');
+        // One write per record, so an interruption seldom leaves a partial
+        // line behind (see `open`)
+        try file.writeAll(aw.written());
+    }
+
+    /// Close and remove the journal after a completed run.
+    pub fn finish(self: *Journal) void {
+        if (self.file) |f| f.close();
+        self.file = null;
+        self.dir.deleteFile(self.path) catch {};
+    }
+};
+
+fn recordKey(allocator: std.mem.Allocator, line: []const u8) !?[]u8 {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, line, .{}) catch return null;
+    defer parsed.deinit();
+
+    if (parsed.value != .object) return null;
+    const key = getString(parsed.value.object, "key") orelse return null;
+    return try allocator.dupe(u8, key);
+}
+
+/// Write one entry report as a single-line JSON record.
+pub fn writeRecord(w: *std.Io.Writer, entry_report: *const EntryReport) !void {
+    try w.writeAll("{\"key\":");
+    try writeJsonString(w, entry_report.entry.key);
+    try w.print(",\"hash\":\"{x:0>16}\",\"status\":\"{s}\"", .{ entry_report.entry.contentHash(), @tagName(entry_report.status) });
+    switch (entry_report.status) {
+        .ok => |source| try w.print(",\"source\":\"{s}\"", .{@tagName(source)}),
+        .failed, .known_unmatched => |reason| {
+            try w.writeAll(",\"reason\":");
+            try writeJsonString(w, reason);
+        },
+        else => {},
+    }
+
+    try w.writeAll(",\"results\":[");
+    for (entry_report.validation_results, 0..) |result, i| {
+        if (i > 0) try w.writeByte(',');
+        try w.print("{{\"source\":\"{s}\",\"confidence\":{d},", .{ @tagName(result.source), result.confidence });
+        if (result.matched_entry) |*matched| {
+            try w.writeAll("\"matched\":");
+            try snapshot.writeEntry(w, matched);
+            try w.writeByte(',');
+        }
+        try w.writeAll("\"discrepancies\":[");
+        for (result.discrepancies, 0..) |d, j| {
+            if (j > 0) try w.writeByte(',');
+            try w.print("{{\"rule\":\"{s}\",\"field\":\"{s}\",\"severity\":\"{s}\",\"confidence\":{d},\"local\":", .{ d.rule.id(), @tagName(d.field), @tagName(d.severity), d.confidence });
+            try writeJsonString(w, d.local_value);
+            try w.writeAll(",\"remote\":");
+            try writeJsonString(w, d.remote_value);
+            try w.writeAll(",\"message\":");
+            try writeJsonString(w, d.message);
+            try w.writeByte('}');
+        }
+        try w.writeAll("]}");
+    }
+    try w.writeAll("]}");
+}
+
+/// Rebuild an entry report from a record, or null if the record is
+/// malformed or `local` was edited since it was written.
+pub fn parseRecord(allocator: std.mem.Allocator, value: std.json.Value, local: *const Entry) !?EntryReport {
+    if (value != .object) return null;
+    const obj = value.object;
+
+    const hash_str = getString(obj, "hash") orelse return null;
+    const hash = std.fmt.parseInt(u64, hash_str, 16) catch return null;
+    if (hash != local.contentHash()) return null;
+
+    const tag = std.meta.stringToEnum(std.meta.Tag(EntryStatus), getString(obj, "status") orelse return null) orelse return null;
+    const source: ?ApiSource = if (getString(obj, "source")) |s| std.meta.stringToEnum(ApiSource, s) else null;
+    if (tag == .ok and source == null) return null;
+
+    var results: std.ArrayList(ValidationResult) = .empty;
+    errdefer {
+        for (results.items) |*r| r.deinit();
+        results.deinit(allocator);
+    }
+
+    if (obj.get("results")) |results_val| {
+        if (results_val == .array) {
+            for (results_val.array.items) |item| {
+                if (try parseResult(allocator, item)) |r| {
+                    try results.append(allocator, r);
+                }
+            }
+        }
+    }
+
+    const validation_results = try results.toOwnedSlice(allocator);
+    errdefer {
+        for (validation_results) |*r| r.deinit();
+        allocator.free(validation_results);
+    }
+
+    var entry_copy = try local.clone(allocator);
+    errdefer entry_copy.deinit();
+
+    const status: EntryStatus = switch (tag) {
+        .ok => .{ .ok = source.? },
+        .warning => .warning,
+        .@"error" => .@"error",
+        .not_found => .not_found,
+        .failed => .{ .failed = try allocator.dupe(u8, getString(obj, "reason") orelse "unknown") },
+        .known_unmatched => .{ .known_unmatched = try allocator.dupe(u8, getString(obj, "reason") orelse "") },
+    };
+
+    return EntryReport{
+        .entry = entry_copy,
+        .status = status,
+        .validation_results = validation_results,
+        .allocator = allocator,
+    };
+}
+
+fn parseResult(allocator: std.mem.Allocator, value: std.json.Value) !?ValidationResult {
+    if (value != .object) return null;
+    const obj = value.object;
+
+    const source = std.meta.stringToEnum(ApiSource, getString(obj, "source") orelse return null) orelse return null;
+    const confidence: f64 = if (obj.get("confidence")) |c| switch (c) {
+        .float => |f| f,
+        .integer => |i| @as(f64, @floatFromInt(i)),
+        else => 0.0,
+    } else 0.0;
+
+    var discrepancies: std.ArrayList(Discrepancy) = .empty;
+    errdefer {
+        for (discrepancies.items) |*d| d.deinit();
+        discrepancies.deinit(allocator);
+    }
+
+    if (obj.get("discrepancies")) |list| {
+        if (list == .array) {
+            for (list.array.items) |item| {
+                if (try parseDiscrepancy(allocator, item)) |d| {
+                    try discrepancies.append(allocator, d);
+                }
+            }
+        }
+    }
+
+    var matched: ?Entry = if (obj.get("matched")) |m| try snapshot.parseEntry(allocator, m) else null;
+    errdefer if (matched) |*m| m.deinit();
+
+    return ValidationResult{
+        .source = source,
+        .matched_entry = matched,
+        .confidence = confidence,
+        .discrepancies = try discrepancies.toOwnedSlice(allocator),
+        .allocator = allocator,
+    };
+}
+
+fn parseDiscrepancy(allocator: std.mem.Allocator, value: std.json.Value) !?Discrepancy {
+    if (value != .object) return null;
+    const obj = value.object;
+
+    const rule = Rule.parse(getString(obj, "rule") orelse return null) orelse return null;
+    const field = std.meta.stringToEnum(DiscrepancyField, getString(obj, "field") orelse return null) orelse return null;
+    const severity = std.meta.stringToEnum(Severity, getString(obj, "severity") orelse return null) orelse return null;
+    const confidence: f64 = if (obj.get("confidence")) |c| switch (c) {
+        .float => |f| f,
+        .integer => |i| @as(f64, @floatFromInt(i)),
+        else => 1.0,
+    } else 1.0;
+
+    const local_value = try allocator.dupe(u8, getString(obj, "local") orelse "");
+    errdefer allocator.free(local_value);
+    const remote_value = try allocator.dupe(u8, getString(obj, "remote") orelse "");
+    errdefer allocator.free(remote_value);
+
+    return Discrepancy{
+        .rule = rule,
+        .field = field,
+        .severity = severity,
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = try allocator.dupe(u8, getString(obj, "message") orelse ""),
+        .confidence = confidence,
+        .allocator = allocator,
+    };
+}
+
+fn getString(obj: std.json.ObjectMap, name: []const u8) ?[]const u8 {
+    const value = obj.get(name) orelse return null;
+    return if (value == .string) value.string else null;
+}
+
+test "journal round trip" {
+    const allocator = std.testing.allocator;
+
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+
+    const local = Entry{ .key = "smith2021", .entry_type = "article", .title = "A Great Paper", .year = 2021 };
+
+    {
+        var journal = try Journal.open(allocator, tmp.dir, "journal.jsonl", false);
+        defer journal.deinit();
+
+        var discrepancies = [_]Discrepancy{.{
+            .rule = .year_mismatch,
+            .field = .year,
+            .severity = .@"error",
+            .local_value = "2021",
+            .remote_value = "2020",
+            .message = "Year mismatch: 2021 vs 2020",
+        }};
+        var results = [_]ValidationResult{.{ .source = .dblp, .confidence = 0.9, .discrepancies = &discrepancies }};
+        const entry_report = EntryReport{ .entry = local, .status = .@"error", .validation_results = &results };
+        try journal.record(&entry_report);
+    }
+
+    var journal = try Journal.open(allocator, tmp.dir, "journal.jsonl", true);
+    defer journal.deinit();
+    try std.testing.expectEqual(@as(usize, 1), journal.restorableCount());
+
+    var restored = (try journal.restore(&local)).?;
+    defer restored.deinit();
+    try std.testing.expect(restored.status == .@"error");
+    try std.testing.expectEqual(@as(usize, 1), restored.validation_results.len);
+    try std.testing.expectEqualStrings("2020", restored.validation_results[0].discrepancies[0].remote_value);
+
+    // An edited entry is validated again
+    const edited = Entry{ .key = "smith2021", .entry_type = "article", .title = "A Great Paper", .year = 2020 };
+    try std.testing.expect((try journal.restore(&edited)) == null);
+}
+
+test "records after an interrupted write start a new line" {
+    const allocator = std.testing.allocator;
+
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    try tmp.dir.writeFile(.{ .sub_path = "journal.jsonl", .data = "{\"key\":\"jones2019\",\"ha" });
+
+    const local = Entry{ .key = "smith2021", .entry_type = "article", .title = "A Great Paper", .year = 2021 };
+    {
+        var journal = try Journal.open(allocator, tmp.dir, "journal.jsonl", true);
+        defer journal.deinit();
+        try std.testing.expectEqual(@as(usize, 0), journal.restorableCount());
+        try journal.record(&.{ .entry = local, .status = .not_found, .validation_results = &.{} });
+    }
+
+    var journal = try Journal.open(allocator, tmp.dir, "journal.jsonl", true);
+    defer journal.deinit();
+    try std.testing.expectEqual(@as(usize, 1), journal.restorableCount());
+    var restored = (try journal.restore(&local)).?;
+    defer restored.deinit();
+    try std.testing.expect(restored.status == .not_found);
+}
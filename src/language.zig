@@ -0,0 +1,184 @@
+//! Languages of entries and titles.
+//!
+//! Humanities and non-English references are often cited by a translated
+//! title, and the databases record the work in its own language, or give
+//! both: CrossRef's `original-title` next to `title`. The entry's
+//! `langid` or `language` field says which language it is cited in; where
+//! it has none, the language of a title is told from its script or, for
+//! Latin script, from its function words ("der", "de la", "the").
+
+const std = @import("std");
+const Entry = @import("entry.zig").Entry;
+
+/// Extra field of a matched record holding its language, as the source
+/// gives it ("en", "deu").
+pub const FIELD = "language";
+
+/// Extra field of a matched record holding the title in the original
+/// language, when `title` is a translation. Named as in biblatex.
+pub const ORIGINAL_TITLE_FIELD = "origtitle";
+
+/// Languages that can be named in an entry or told from a title. Tags are
+/// babel names, as used in `langid`.
+pub const Language = enum {
+    english,
+    german,
+    french,
+    spanish,
+    italian,
+    portuguese,
+    dutch,
+    russian,
+    chinese,
+    japanese,
+    korean,
+
+    pub fn name(self: Language) []const u8 {
+        return switch (self) {
+            .english => "English",
+            .german => "German",
+            .french => "French",
+            .spanish => "Spanish",
+            .italian => "Italian",
+            .portuguese => "Portuguese",
+            .dutch => "Dutch",
+            .russian => "Russian",
+            .chinese => "Chinese",
+            .japanese => "Japanese",
+            .korean => "Korean",
+        };
+    }
+};
+
+/// ISO 639-1 and 639-2 codes, babel and polyglossia names, and endonyms.
+const aliases = [_]struct { Language, []const []const u8 }{
+    .{ .english, &.{ "en", "eng", "english", "american", "british", "canadian", "australian", "newzealand", "ukenglish", "usenglish" } },
+    .{ .german, &.{ "de", "deu", "ger", "german", "ngerman", "austrian", "naustrian", "swissgerman", "nswissgerman", "deutsch" } },
+    .{ .french, &.{ "fr", "fra", "fre", "french", "francais", "français", "acadian", "canadien" } },
+    .{ .spanish, &.{ "es", "spa", "spanish", "español", "espanol" } },
+    .{ .italian, &.{ "it", "ita", "italian", "italiano" } },
+    .{ .portuguese, &.{ "pt", "por", "portuguese", "portuges", "brazil", "brazilian", "português", "portugues" } },
+    .{ .dutch, &.{ "nl", "nld", "dut", "dutch", "nederlands" } },
+    .{ .russian, &.{ "ru", "rus", "russian" } },
+    .{ .chinese, &.{ "zh", "zho", "chi", "chinese" } },
+    .{ .japanese, &.{ "ja", "jpn", "japanese" } },
+    .{ .korean, &.{ "ko", "kor", "korean" } },
+};
+
+/// Short function words, lowercase. Words several languages share count
+/// for each of them, so only the others decide.
+const stopwords = [_]struct { Language, []const []const u8 }{
+    .{ .english, &.{ "the", "of", "and", "for", "with", "to", "from", "by", "an", "is", "are", "towards", "using", "its", "on" } },
+    .{ .german, &.{ "der", "die", "das", "und", "für", "mit", "von", "zur", "zum", "den", "des", "dem", "ein", "eine", "einer", "im", "über", "auf", "bei", "ist", "als" } },
+    .{ .french, &.{ "le", "la", "les", "de", "des", "du", "d", "l", "à", "et", "pour", "une", "un", "dans", "sur", "au", "aux", "est", "par", "avec", "entre" } },
+    .{ .spanish, &.{ "el", "la", "los", "las", "de", "del", "y", "para", "con", "una", "un", "por", "sobre", "al", "como", "entre" } },
+    .{ .italian, &.{ "il", "lo", "la", "gli", "di", "del", "della", "dei", "delle", "e", "per", "con", "una", "un", "nel", "nella", "sul", "tra" } },
+    .{ .portuguese, &.{ "o", "os", "as", "de", "do", "da", "dos", "das", "e", "para", "com", "em", "uma", "um", "no", "na", "por", "sobre", "entre" } },
+    .{ .dutch, &.{ "de", "het", "een", "en", "van", "voor", "met", "op", "bij", "naar", "over", "uit", "tussen" } },
+};
+
+/// The language `value` names: "en", "en-US", "ngerman", "French", ...
+pub fn parse(value: []const u8) ?Language {
+    var text = std.mem.trim(u8, value, " \t{}");
+    // Region and script subtags: "en-GB", "zh_Hans"
+    if (std.mem.indexOfAny(u8, text, "-_")) |end| text = text[0..end];
+    for (aliases) |alias| {
+        for (alias[1]) |a| {
+            if (std.ascii.eqlIgnoreCase(text, a)) return alias[0];
+        }
+    }
+    return null;
+}
+
+/// The language an entry says it is in, from `langid` or else `language`.
+pub fn ofEntry(e: *const Entry) ?Language {
+    if (e.extraField("langid")) |langid| {
+        if (parse(langid)) |lang| return lang;
+    }
+    return parse(e.extraField(FIELD) orelse return null);
+}
+
+/// The language `text` is written in, or null if it cannot be told: a
+/// title without function words ("Deep Residual Learning") or with as
+/// many of one language as of another.
+pub fn detect(text: []const u8) ?Language {
+    if (scriptLanguage(text)) |lang| return lang;
+
+    var counts = std.EnumArray(Language, usize).initFill(0);
+    var words = std.mem.tokenizeAny(u8, text, " \t\r\n.,;:!?()[]{}\"'`-/");
+    while (words.next()) |word| {
+        var buf: [16]u8 = undefined;
+        if (word.len > buf.len) continue;
+        const lower = std.ascii.lowerString(&buf, word);
+        for (stopwords) |list| {
+            for (list[1]) |stopword| {
+                if (std.mem.eql(u8, lower, stopword)) counts.getPtr(list[0]).* += 1;
+            }
+        }
+    }
+
+    var best: ?Language = null;
+    var best_count: usize = 0;
+    var runner_up: usize = 0;
+    for (std.enums.values(Language)) |lang| {
+        const count = counts.get(lang);
+        if (count > best_count) {
+            runner_up = best_count;
+            best = lang;
+            best_count = count;
+        } else if (count > runner_up) {
+            runner_up = count;
+        }
+    }
+    return if (best_count > runner_up) best else null;
+}
+
+/// The language of a title mostly written in Cyrillic, kana, Hangul, or
+/// Han characters.
+fn scriptLanguage(text: []const u8) ?Language {
+    var latin: usize = 0;
+    var cyrillic: usize = 0;
+    var kana: usize = 0;
+    var hangul: usize = 0;
+    var han: usize = 0;
+    const view = std.unicode.Utf8View.init(text) catch return null;
+    var it = view.iterator();
+    while (it.nextCodepoint()) |cp| {
+        switch (cp) {
+            'A'...'Z', 'a'...'z', 0xC0...0x24F => latin += 1,
+            0x400...0x4FF => cyrillic += 1,
+            0x3040...0x30FF => kana += 1,
+            0x1100...0x11FF, 0x3130...0x318F, 0xAC00...0xD7AF => hangul += 1,
+            0x3400...0x4DBF, 0x4E00...0x9FFF, 0xF900...0xFAFF => han += 1,
+            else => {},
+        }
+    }
+    // Han characters count for several each: "深層学習" is one word
+    if (kana + hangul + han * 2 + cyrillic <= latin) return null;
+    // Japanese mixes kana into Han, Korean rarely uses Han at all
+    if (kana > 0) return .japanese;
+    if (hangul > 0) return .korean;
+    if (han > 0) return .chinese;
+    return .russian;
+}
+
+test "languages from fields and titles" {
+    try std.testing.expectEqual(Language.english, parse("en-US").?);
+    try std.testing.expectEqual(Language.german, parse("ngerman").?);
+    try std.testing.expectEqual(Language.french, parse("French").?);
+    try std.testing.expectEqual(Language.portuguese, parse("por").?);
+    try std.testing.expectEqual(@as(?Language, null), parse("klingon"));
+
+    const e = Entry{ .key = "k", .entry_type = "article", .extra = &.{.{ .name = "language", .value = "Deutsch" }} };
+    try std.testing.expectEqual(Language.german, ofEntry(&e).?);
+
+    try std.testing.expectEqual(Language.english, detect("On the Origin of Species").?);
+    try std.testing.expectEqual(Language.german, detect("Die protestantische Ethik und der Geist des Kapitalismus").?);
+    try std.testing.expectEqual(Language.french, detect("Les mots et les choses").?);
+    try std.testing.expectEqual(Language.french, detect("Histoire de la folie à l'âge classique").?);
+    try std.testing.expectEqual(Language.spanish, detect("Cien años de soledad y el realismo mágico").?);
+    try std.testing.expectEqual(Language.russian, detect("Война и мир").?);
+    try std.testing.expectEqual(Language.japanese, detect("深層学習による画像認識").?);
+    try std.testing.expectEqual(Language.chinese, detect("深度学习综述").?);
+    try std.testing.expectEqual(@as(?Language, null), detect("Deep Residual Learning"));
+}
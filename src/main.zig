@@ -4,6 +4,7 @@
 //! CrossRef, DBLP, Semantic Scholar, and OpenAlex.
 
 const std = @import("std");
+const builtin = @import("builtin");
 const bibval = @import("bibval");
 
 const Entry = bibval.Entry;
@@ -14,41 +15,232 @@ const Discrepancy = bibval.Discrepancy;
 const EntryReport = bibval.report.EntryReport;
 const EntryStatus = bibval.report.EntryStatus;
 const Report = bibval.report.Report;
+const Tracer = bibval.trace.Tracer;
+const writeJsonString = bibval.report.writeJsonString;
+
+const Command = enum {
+    check,
+    plan,
+    snapshot,
+    rules,
+    stats,
+    one,
+    serve,
+    cache,
+    history,
+    completions,
+    man,
+    config,
+    auth,
+};
+
+const Format = enum {
+    text,
+    json,
+    patch,
+};
 
 const Args = struct {
+    command: Command = .check,
     files: []const []const u8 = &.{},
     no_crossref: bool = false,
     no_dblp: bool = false,
     no_semantic: bool = false,
     no_openalex: bool = false,
+    no_open_library: bool = false,
+    no_google_books: bool = false,
+    no_arxiv: bool = false,
+    no_pmlr: bool = false,
+    no_neurips: bool = false,
+    no_ieee: bool = false,
+    no_acm: bool = false,
+    no_springer: bool = false,
+    no_scopus: bool = false,
+    no_github: bool = false,
+    no_zenodo: bool = false,
+    no_figshare: bool = false,
+    no_web: bool = false,
+    no_external: bool = false,
+    canonical_venues: bool = false,
     no_cache: bool = false,
     strict: bool = false,
-    verbose: bool = false,
-    json: bool = false,
+    gates: bibval.gates.Gates = .{},
+    verbosity: u8 = 0,
+    log_json: bool = false,
+    format: Format = .text,
+    /// Order of entries and discrepancies in the output.
+    sort: bibval.report.Order = .file,
+    /// Write repeated venues as `@string` macros in patch output.
+    string_macros: bool = false,
+    quiet: bool = false,
+    /// Print the matched remote records beneath each entry.
+    show_matched: bool = false,
+    no_color: bool = false,
     keys: []const []const u8 = &.{},
+    key_globs: []const []const u8 = &.{},
+    types: []const []const u8 = &.{},
+    /// Git revision; only entries changed since are validated.
+    modified_since: ?[]const u8 = null,
+    authors: []const []const u8 = &.{},
+    groups: []const []const u8 = &.{},
+    request_interval_ms: u64 = 0,
+    /// Time budget of the whole run; entries not reached in time are skipped.
+    deadline_ms: ?u64 = null,
+    /// Threads for local passes; 0 uses one per CPU.
+    jobs: usize = 0,
+    resume_run: bool = false,
+    journal_path: []const u8 = bibval.journal.DEFAULT_PATH,
+    snapshot_out: []const u8 = bibval.snapshot.DEFAULT_PATH,
+    snapshot_in: ?[]const u8 = null,
+    /// SQLite database each run is recorded in.
+    history_path: ?[]const u8 = null,
+    disabled: bibval.rules.RuleSet = .initEmpty(),
+    min_confidence: f64 = 0,
+    config_path: []const u8 = bibval.config.DEFAULT_PATH,
+    /// Style profile the config is loaded on top of
+    profile: ?[]const u8 = null,
+    check_funders: bool = false,
+    access: bibval.report.AccessFilter = .none,
+    suggest_archives: bool = false,
+    /// Save pages without a snapshot; implies suggest_archives
+    archive_urls: bool = false,
+    grobid_url: ?[]const u8 = null,
+    clipboard: bool = false,
+    zotero: bool = false,
+    zotero_url: []const u8 = bibval.zotero.DEFAULT_URL,
+    zotero_write: bool = false,
+    /// From ZOTERO_API_KEY or the keyring; owned by main.
+    zotero_api_key: ?[]const u8 = null,
+    title: ?[]const u8 = null,
+    year: ?i32 = null,
+    doi: ?[]const u8 = null,
+    listen: []const u8 = bibval.serve.DEFAULT_ADDRESS,
+    port: u16 = bibval.serve.DEFAULT_PORT,
+    /// Entries each client of `serve` may look up per minute; 0 is unlimited.
+    rate_limit: u32 = bibval.serve.DEFAULT_RATE_LIMIT,
+    /// Whether `serve` reads cited web pages.
+    serve_web: bool = false,
     help: bool = false,
     version: bool = false,
 
     allocator: std.mem.Allocator,
     files_list: std.ArrayList([]const u8),
+    /// Paths expanded from glob patterns.
+    paths: std.heap.ArenaAllocator,
     keys_list: std.ArrayList([]const u8),
+    key_globs_list: std.ArrayList([]const u8),
+    types_list: std.ArrayList([]const u8),
+    authors_list: std.ArrayList([]const u8),
+    groups_list: std.ArrayList([]const u8),
 
     fn init(allocator: std.mem.Allocator) Args {
         return .{
             .allocator = allocator,
             .files_list = .empty,
+            .paths = std.heap.ArenaAllocator.init(allocator),
             .keys_list = .empty,
+            .key_globs_list = .empty,
+            .types_list = .empty,
+            .authors_list = .empty,
+            .groups_list = .empty,
         };
     }
 
     fn deinit(self: *Args, allocator: std.mem.Allocator) void {
         self.files_list.deinit(allocator);
+        self.paths.deinit();
         self.keys_list.deinit(allocator);
+        self.key_globs_list.deinit(allocator);
+        self.types_list.deinit(allocator);
+        self.authors_list.deinit(allocator);
+        self.groups_list.deinit(allocator);
     }
 
     fn finalize(self: *Args) void {
         self.files = self.files_list.items;
         self.keys = self.keys_list.items;
+        self.key_globs = self.key_globs_list.items;
+        self.types = self.types_list.items;
+        self.authors = self.authors_list.items;
+        self.groups = self.groups_list.items;
+    }
+
+    fn backends(self: *const Args, config: *const bibval.config.Config) bibval.plan.Backends {
+        return .{
+            .crossref = !self.no_crossref,
+            .dblp = !self.no_dblp,
+            .semantic_scholar = !self.no_semantic,
+            .openalex = !self.no_openalex,
+            .open_library = !self.no_open_library,
+            .google_books = !self.no_google_books,
+            .arxiv = !self.no_arxiv,
+            .pmlr = !self.no_pmlr,
+            .neurips = !self.no_neurips,
+            .ieee = self.ieeeApiKey(config) != null,
+            .acm = !self.no_acm and config.acm,
+            .springer = self.springerApiKey(config) != null,
+            .scopus = self.scopusApiKey(config) != null,
+            .github = !self.no_github,
+            .zenodo = !self.no_zenodo,
+            .figshare = !self.no_figshare,
+            .web = !self.no_web,
+            .wayback = self.suggest_archives or self.archive_urls,
+            .save_pages = self.archive_urls,
+        };
+    }
+
+    /// The enabled lookup backends, all sharing `client`.
+    fn sources(self: *const Args, allocator: std.mem.Allocator, client: *bibval.http.Client, response_cache: *bibval.cache.Cache, config: *const bibval.config.Config) bibval.pipeline.Sources {
+        const validators = bibval.validators;
+        return .{
+            .crossref = if (!self.no_crossref) validators.CrossRef.init(allocator, client, response_cache) else null,
+            .dblp = if (!self.no_dblp) validators.Dblp.init(allocator, client) else null,
+            .semantic = if (!self.no_semantic) validators.SemanticScholar.init(allocator, client) else null,
+            .openalex = if (!self.no_openalex) validators.OpenAlex.init(allocator, client) else null,
+            .open_library = if (!self.no_open_library) validators.OpenLibrary.init(allocator, client) else null,
+            .google_books = if (!self.no_google_books) validators.GoogleBooks.init(allocator, client) else null,
+            .arxiv = if (!self.no_arxiv) validators.Arxiv.init(allocator, client) else null,
+            .pmlr = if (!self.no_pmlr) bibval.proceedings.Pmlr.init(allocator, client, response_cache) else null,
+            .neurips = if (!self.no_neurips) bibval.proceedings.NeurIps.init(allocator, client, response_cache) else null,
+            .ieee = if (self.ieeeApiKey(config)) |key| validators.Ieee.init(allocator, client, response_cache, key) else null,
+            .acm = if (!self.no_acm and config.acm) validators.Acm.init(allocator, client) else null,
+            .springer = if (self.springerApiKey(config)) |key| validators.Springer.init(allocator, client, response_cache, key) else null,
+            .scopus = if (self.scopusApiKey(config)) |key| validators.Scopus.init(allocator, client, response_cache, key) else null,
+            .github = if (!self.no_github) bibval.github.GitHub.init(allocator, client, response_cache, config.github_token) else null,
+            .zenodo = if (!self.no_zenodo) bibval.deposits.Zenodo.init(allocator, client, response_cache) else null,
+            .figshare = if (!self.no_figshare) bibval.deposits.Figshare.init(allocator, client, response_cache) else null,
+            .web = if (!self.no_web) bibval.webpage.Web.init(allocator, client, response_cache) else null,
+            .wayback = if (self.suggest_archives or self.archive_urls) bibval.wayback.Wayback.init(allocator, client, response_cache, self.archive_urls) else null,
+            .plugins = self.plugins(config),
+        };
+    }
+
+    /// The IEEE Xplore key (see `credentials.resolve`), unless IEEE
+    /// lookups are disabled.
+    fn ieeeApiKey(self: *const Args, config: *const bibval.config.Config) ?[]const u8 {
+        if (self.no_ieee) return null;
+        return config.ieee_api_key;
+    }
+
+    fn springerApiKey(self: *const Args, config: *const bibval.config.Config) ?[]const u8 {
+        if (self.no_springer) return null;
+        return config.springer_api_key;
+    }
+
+    fn scopusApiKey(self: *const Args, config: *const bibval.config.Config) ?[]const u8 {
+        if (self.no_scopus) return null;
+        return config.scopus_api_key;
+    }
+
+    fn plugins(self: *const Args, config: *const bibval.config.Config) []const bibval.external.Plugin {
+        return if (self.no_external) &.{} else config.external;
+    }
+
+    /// Compare venues by source entity if enabled, resolving names with
+    /// `resolver`.
+    fn canonicalVenues(self: *const Args, config: *bibval.config.Config, resolver: *bibval.canonical.Resolver) !void {
+        if (!self.canonical_venues and !config.canonical_venues) return;
+        config.matching.comparators = try resolver.comparators(config.matching.comparators);
     }
 };
 
@@ -69,393 +261,1699 @@ pub fn main() !void {
     var stdout_writer = std.fs.File.stdout().writer(&stdout_buf);
     const stdout = &stdout_writer.interface;
     defer stdout.flush() catch {};
-    const use_color = std.fs.File.stdout().supportsAnsiEscapeCodes();
+    // Plain text when piped, with --no-color, or with NO_COLOR set (https://no-color.org)
+    const use_color = std.fs.File.stdout().supportsAnsiEscapeCodes() and !args.no_color and !noColorEnv(allocator);
 
     if (args.version) {
-        try stdout.writeAll("bibval 26.1.4\n");
+        try stdout.print("bibval {s}\n", .{bibval.cli.version});
         return;
     }
 
-    if (args.files.len == 0) {
+    if (args.command == .rules) {
+        try bibval.rules.printRules(stdout);
+        return;
+    }
+
+    if (args.command == .completions) {
+        const shell = if (args.files.len == 1) std.meta.stringToEnum(bibval.cli.Shell, args.files[0]) else null;
+        try bibval.cli.writeCompletions(stdout, shell orelse {
+            std.debug.print("Usage: bibval completions bash | zsh | fish | powershell\n", .{});
+            std.process.exit(1);
+        });
+        return;
+    }
+
+    if (args.command == .man) {
+        try bibval.cli.writeMan(stdout);
+        return;
+    }
+
+    if (args.command == .auth) {
+        const exit_code = try runAuth(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.command == .config) {
+        const exit_code = try runConfig(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.command == .one) {
+        const exit_code = try runOne(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.command == .cache) {
+        const exit_code = try runCache(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.command == .history) {
+        const exit_code = try runHistory(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.command == .serve) {
+        const exit_code = try runServe(allocator, &args, stdout);
+        if (exit_code != 0) {
+            stdout.flush() catch {};
+            std.process.exit(exit_code);
+        }
+        return;
+    }
+
+    if (args.files.len == 0 and !args.clipboard and !args.zotero) {
         std.debug.print("Error: No input files specified\n", .{});
         printUsage();
         std.process.exit(1);
     }
 
-    // Parse all input files
-    var all_entries: std.ArrayList(Entry) = .empty;
+    if (args.zotero) {
+        if (try bibval.credentials.find(allocator, .zotero, null)) |credential| args.zotero_api_key = credential.key;
+    }
+    defer if (args.zotero_api_key) |key| allocator.free(key);
+    if (args.zotero_write and (!args.zotero or args.zotero_api_key == null)) {
+        std.debug.print("Error: --zotero-write needs --zotero, a web API --zotero-url, and a Zotero API key (ZOTERO_API_KEY or bibval auth set zotero)\n", .{});
+        std.process.exit(1);
+    }
+
+    var library: ?bibval.zotero.Library = if (args.zotero) try loadZotero(allocator, &args) else null;
+    defer if (library) |*l| l.deinit();
+    const library_ptr: ?*const bibval.zotero.Library = if (library) |*l| l else null;
+
+    var all_entries = try loadEntries(allocator, &args, library_ptr, stdout);
     defer {
         for (all_entries.items) |*e| e.deinit();
         all_entries.deinit(allocator);
     }
 
-    for (args.files) |file_path| {
+    if (all_entries.items.len == 0) return;
+
+    const exit_code: u8 = switch (args.command) {
+        .check, .snapshot, .stats => try runCheck(allocator, &args, all_entries.items, library_ptr, stdout, use_color),
+        .plan => try runPlan(allocator, &args, all_entries.items, stdout),
+        .rules, .one, .serve, .cache, .history, .completions, .man, .config, .auth => unreachable,
+    };
+
+    if (exit_code != 0) {
+        stdout.flush() catch {};
+        std.process.exit(exit_code);
+    }
+}
+
+/// Parse all input files and apply the key, type, revision, and group filters.
+fn loadEntries(allocator: std.mem.Allocator, args: *const Args, library: ?*const bibval.zotero.Library, stdout: *std.Io.Writer) !std.ArrayList(Entry) {
+    var all_entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (all_entries.items) |*e| e.deinit();
+        all_entries.deinit(allocator);
+    }
+
+    for (args.files, 0..) |file_path, i| {
+        // A file matched by two patterns is read once
+        if (containsPath(args.files[0..i], file_path)) continue;
+
+        if (isStdin(file_path)) {
+            const content = std.fs.File.stdin().readToEndAlloc(allocator, bibval.input.MAX_INPUT_SIZE) catch |err| {
+                std.debug.print("Error: Failed to read stdin: {s}\n", .{@errorName(err)});
+                std.process.exit(1);
+            };
+            defer allocator.free(content);
+            try appendDetected(allocator, args, "stdin", content, &all_entries, stdout);
+            continue;
+        }
+
         // Check file exists
         std.fs.cwd().access(file_path, .{}) catch {
             std.debug.print("Error: File not found: {s}\n", .{file_path});
             std.process.exit(1);
         };
 
-        if (!args.json) {
+        if (args.format == .text and !args.quiet) {
             try stdout.print("Parsing {s}...\n", .{file_path});
         }
 
-        const entries = bibval.bibtex.parseFile(allocator, file_path) catch |err| {
-            std.debug.print("Error: Failed to parse {s}: {s}\n", .{ file_path, @errorName(err) });
-            std.process.exit(1);
-        };
+        var diagnostics: bibval.input.Diagnostics = .{};
+        defer diagnostics.deinit(allocator);
+        const entries = if (isPdf(file_path))
+            try extractReferences(allocator, args, file_path)
+        else
+            bibval.input.parseFile(allocator, file_path, &diagnostics) catch |err| {
+                std.debug.print("Error: Failed to parse {s}: {s}\n", .{ file_path, @errorName(err) });
+                std.process.exit(1);
+            };
+        printDiagnostics(file_path, &diagnostics);
 
-        if (!args.json) {
+        if (args.format == .text and !args.quiet) {
             try stdout.print("  Found {d} entries\n", .{entries.len});
         }
 
-        for (entries) |e| {
-            try all_entries.append(allocator, e);
+        for (entries) |*e| {
+            e.source_file = file_path;
+            try all_entries.append(allocator, e.*);
         }
         allocator.free(entries);
     }
 
+    if (args.clipboard) {
+        const content = bibval.input.readClipboard(allocator) catch {
+            std.debug.print("Error: Failed to read the clipboard (is pbpaste, wl-paste, xclip or xsel installed?)\n", .{});
+            std.process.exit(1);
+        };
+        defer allocator.free(content);
+        try appendDetected(allocator, args, "clipboard", content, &all_entries, stdout);
+    }
+
+    if (library) |lib| {
+        if (args.format == .text and !args.quiet) {
+            try stdout.print("Read {d} entries from Zotero\n", .{lib.entries.len});
+        }
+        for (lib.entries) |*e| {
+            try all_entries.append(allocator, try e.clone(allocator));
+        }
+    }
+
     if (all_entries.items.len == 0) {
-        if (!args.json) {
+        if (args.format == .text) {
             try stdout.writeAll("No entries found to validate.\n");
         }
-        return;
+        return all_entries;
     }
 
-    // Apply key filtering if requested
-    if (args.keys.len > 0) {
+    // Apply key, type, and revision filters if requested
+    var unchanged: bibval.selection.Hashes = .empty;
+    defer unchanged.deinit(allocator);
+    if (args.modified_since) |rev| {
+        bibval.selection.verifyRevision(allocator, rev) catch |err| {
+            std.debug.print("Error: --modified-since {s}: {s}\n", .{ rev, switch (err) {
+                error.UnknownRevision => "not a git revision",
+                error.GitUnavailable => "git could not be run",
+                else => @errorName(err),
+            } });
+            std.process.exit(1);
+        };
+        for (args.files) |file_path| {
+            if (isStdin(file_path) or isPdf(file_path)) continue;
+            try bibval.selection.addHashesAt(allocator, rev, file_path, &unchanged);
+        }
+    }
+    const selection = bibval.selection.Selection{
+        .keys = args.keys,
+        .key_globs = args.key_globs,
+        .types = args.types,
+        .unchanged = if (args.modified_since != null) &unchanged else null,
+    };
+    if (!selection.isEmpty()) {
         var i: usize = 0;
         while (i < all_entries.items.len) {
-            var found = false;
-            for (args.keys) |key| {
-                if (std.mem.eql(u8, all_entries.items[i].key, key)) {
-                    found = true;
-                    break;
-                }
-            }
-            if (!found) {
+            if (selection.matches(&all_entries.items[i])) {
+                i += 1;
+            } else {
                 var removed = all_entries.orderedRemove(i);
                 removed.deinit();
-            } else {
-                i += 1;
             }
         }
 
-        if (all_entries.items.len == 0) {
-            if (!args.json) {
-                try stdout.writeAll("No entries matched the provided keys.\n");
+        if (all_entries.items.len == 0 and args.format == .text) {
+            try stdout.writeAll("No entries matched the provided keys, types, or revision.\n");
+        }
+    }
+
+    // Limit to JabRef groups and their subgroups
+    if (args.groups.len > 0) {
+        var arena = std.heap.ArenaAllocator.init(allocator);
+        defer arena.deinit();
+        const names = try groupNames(arena.allocator(), args);
+
+        var i: usize = 0;
+        while (i < all_entries.items.len) {
+            if (bibval.jabref.inAnyGroup(&all_entries.items[i], names)) {
+                i += 1;
+            } else {
+                var removed = all_entries.orderedRemove(i);
+                removed.deinit();
             }
-            return;
+        }
+
+        if (all_entries.items.len == 0 and args.format == .text) {
+            try stdout.writeAll("No entries are in the selected groups.\n");
         }
     }
 
-    if (!args.json) {
-        try stdout.writeAll("\n");
-        try stdout.print("Validating {d} entries...\n\n", .{all_entries.items.len});
+    return all_entries;
+}
+
+/// The requested JabRef groups together with their subgroups from the
+/// group trees of the BibTeX inputs.
+fn groupNames(arena: std.mem.Allocator, args: *const Args) ![]const []const u8 {
+    var names: std.ArrayList([]const u8) = .empty;
+    try names.appendSlice(arena, args.groups);
+
+    for (args.files) |file_path| {
+        if (isStdin(file_path) or isPdf(file_path) or !isBibtex(file_path)) continue;
+        const content = try std.fs.cwd().readFileAlloc(arena, file_path, bibval.input.MAX_INPUT_SIZE);
+        const metadata = try bibval.jabref.parseMetadata(arena, content);
+        for (args.groups) |group| {
+            try metadata.collectSubgroups(arena, group, &names);
+        }
     }
+    return names.items;
+}
 
-    // Initialize cache
-    var response_cache = try bibval.cache.Cache.init(allocator, !args.no_cache);
-    defer response_cache.deinit();
+/// Read the entries of a Zotero library.
+fn loadZotero(allocator: std.mem.Allocator, args: *const Args) !bibval.zotero.Library {
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
 
-    // Initialize validators
-    var crossref = if (!args.no_crossref) bibval.validators.CrossRef.init(allocator, &response_cache) else null;
-    var dblp = if (!args.no_dblp) bibval.validators.Dblp.init(allocator) else null;
-    var semantic = if (!args.no_semantic) bibval.validators.SemanticScholar.init(allocator) else null;
-    var openalex = if (!args.no_openalex) bibval.validators.OpenAlex.init(allocator) else null;
+    var zotero = bibval.zotero.Zotero.init(allocator, &client, args.zotero_url, args.zotero_api_key);
+    return zotero.fetchLibrary() catch |err| {
+        std.debug.print("Error: Failed to read Zotero library at {s}: {s} (is Zotero running?)\n", .{ args.zotero_url, @errorName(err) });
+        std.process.exit(1);
+    };
+}
 
-    // Validate entries
-    var report = Report.init(allocator);
-    defer report.deinit();
+/// Write the suggested fixes back to the Zotero items the entries came from.
+fn writeZotero(allocator: std.mem.Allocator, args: *const Args, library: *const bibval.zotero.Library, report: *const Report, client: *bibval.http.Client, options: bibval.fix.Options, stdout: *std.Io.Writer) !void {
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const arena_allocator = arena.allocator();
 
-    for (all_entries.items) |*local_entry| {
-        const entry_report = try validateEntry(allocator, local_entry, &crossref, &dblp, &semantic, &openalex, args.verbose);
-        try report.add(entry_report);
+    var zotero = bibval.zotero.Zotero.init(allocator, client, args.zotero_url, args.zotero_api_key);
+    var updated: usize = 0;
+    for (report.entries.items) |*entry_report| {
+        const item = library.find(entry_report.entry.key) orelse continue;
+
+        var fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+        try bibval.fix.suggest(arena_allocator, entry_report, options, &fixes);
+        if (fixes.items.len == 0) continue;
+
+        const changed = zotero.updateItem(item, fixes.items) catch |err| {
+            std.debug.print("Warning: Failed to update Zotero item {s} [{s}]: {s}\n", .{ item.item_key, item.key, @errorName(err) });
+            continue;
+        };
+        if (changed > 0) updated += 1;
     }
 
-    // Output report
-    if (args.json) {
-        try printJsonReport(allocator, stdout, &report);
-    } else {
-        try report.print(stdout, use_color);
+    if (args.format == .text) {
+        try stdout.print("Updated {d} Zotero items\n", .{updated});
+    }
+}
+
+/// Parse references read from stdin or the clipboard, detecting the format.
+fn appendDetected(allocator: std.mem.Allocator, args: *const Args, source: []const u8, raw: []const u8, all_entries: *std.ArrayList(Entry), stdout: *std.Io.Writer) !void {
+    var diagnostics: bibval.input.Diagnostics = .{};
+    defer diagnostics.deinit(allocator);
+    var decoded = try bibval.input.decode(allocator, raw, &diagnostics);
+    defer decoded.deinit();
+    const content = decoded.text;
+
+    const format = bibval.input.detect(content);
+    if (args.format == .text and !args.quiet) {
+        try stdout.print("Parsing {s} as {s}...\n", .{ source, format.name() });
     }
 
-    // Determine exit code
-    if (args.strict and (report.countErrors() > 0 or report.countWarnings() > 0)) {
+    const entries = bibval.input.parse(allocator, content, format, &diagnostics) catch |err| {
+        std.debug.print("Error: Failed to parse {s}: {s}\n", .{ source, @errorName(err) });
+        std.process.exit(1);
+    };
+    defer allocator.free(entries);
+    printDiagnostics(source, &diagnostics);
+
+    if (args.format == .text and !args.quiet) {
+        try stdout.print("  Found {d} entries\n", .{entries.len});
+    }
+    for (entries) |*e| e.source_file = source;
+    try all_entries.appendSlice(allocator, entries);
+}
+
+/// Encoding problems listed per input; the rest are counted.
+const MAX_ENCODING_ISSUES = 10;
+
+/// Warn about input that was not UTF-8 or that the parser skipped over.
+fn printDiagnostics(source: []const u8, diagnostics: *const bibval.input.Diagnostics) void {
+    if (diagnostics.encoding != .utf8) {
+        std.debug.print("Warning: {s}: read as {s}; consider converting it to UTF-8\n", .{ source, diagnostics.encoding.name() });
+    }
+    const issues = diagnostics.encoding_issues.items;
+    for (issues[0..@min(issues.len, MAX_ENCODING_ISSUES)]) |issue| {
+        std.debug.print("Warning: {s}: byte {d}: {s}\n", .{ source, issue.offset, issue.kind.describe() });
+    }
+    if (issues.len > MAX_ENCODING_ISSUES) {
+        std.debug.print("Warning: {s}: {d} more encoding problems\n", .{ source, issues.len - MAX_ENCODING_ISSUES });
+    }
+    for (diagnostics.parse.items) |d| {
+        std.debug.print("Warning: {s}:{d}:{d}: {s}\n", .{ source, d.line, d.column, d.problem.describe() });
+    }
+}
+
+fn containsPath(paths: []const []const u8, path: []const u8) bool {
+    for (paths) |p| {
+        if (std.mem.eql(u8, p, path)) return true;
+    }
+    return false;
+}
+
+/// `-` reads references from stdin.
+fn isStdin(path: []const u8) bool {
+    return std.mem.eql(u8, path, "-");
+}
+
+fn isPdf(path: []const u8) bool {
+    return std.ascii.endsWithIgnoreCase(path, ".pdf");
+}
+
+/// Inputs that can be patched; files without a known extension are
+/// assumed to be BibTeX.
+fn isBibtex(path: []const u8) bool {
+    return (bibval.input.fromPath(path) orelse .bibtex) == .bibtex;
+}
+
+/// Extract the reference list of a PDF with GROBID.
+fn extractReferences(allocator: std.mem.Allocator, args: *const Args, file_path: []const u8) ![]Entry {
+    const url = args.grobid_url orelse {
+        std.debug.print("Error: {s} is a PDF; pass --grobid-url to extract its references\n", .{file_path});
+        std.process.exit(1);
+    };
+
+    const pdf = std.fs.cwd().readFileAlloc(allocator, file_path, bibval.grobid.MAX_PDF_SIZE) catch |err| {
+        std.debug.print("Error: Failed to read {s}: {s}\n", .{ file_path, @errorName(err) });
         std.process.exit(1);
-    } else if (report.countErrors() > 0) {
+    };
+    defer allocator.free(pdf);
+
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
+
+    var grobid = bibval.grobid.Grobid.init(allocator, &client, url);
+    return grobid.processReferences(pdf, file_path) catch |err| {
+        std.debug.print("Error: GROBID at {s} could not process {s}: {s}\n", .{ url, file_path, @errorName(err) });
         std.process.exit(1);
+    };
+}
+
+/// Load the config, with API keys from the environment and the keyring,
+/// and print its warnings; or print why it cannot be loaded and return null.
+fn loadConfig(allocator: std.mem.Allocator, args: *const Args) ?bibval.config.Config {
+    var diag: bibval.config.Diagnostic = .{};
+    var config = bibval.config.load(allocator, std.fs.cwd(), args.config_path, args.profile, &diag) catch |err| {
+        printConfigError(args.config_path, &diag, err);
+        return null;
+    };
+    // Only the keys of enabled backends, so the keyring is not asked needlessly
+    var services = std.EnumSet(bibval.credentials.Service).initEmpty();
+    services.setPresent(.ieee, !args.no_ieee);
+    services.setPresent(.springer, !args.no_springer);
+    services.setPresent(.scopus, !args.no_scopus);
+    services.setPresent(.github, !args.no_github);
+    bibval.credentials.resolve(allocator, &config, services) catch |err| {
+        std.debug.print("Error: Failed to read API keys: {s}\n", .{@errorName(err)});
+        config.deinit();
+        return null;
+    };
+    for (config.warnings) |warning| {
+        std.debug.print("Warning: {s}:{d}: {s}\n", .{ args.config_path, warning.line, warning.message });
     }
+    return config;
 }
 
-fn validateEntry(
-    allocator: std.mem.Allocator,
-    local_entry: *const Entry,
-    crossref: *?bibval.validators.CrossRef,
-    dblp: *?bibval.validators.Dblp,
-    semantic: *?bibval.validators.SemanticScholar,
-    openalex: *?bibval.validators.OpenAlex,
-    verbose: bool,
-) !EntryReport {
-    var validation_results: std.ArrayList(ValidationResult) = .empty;
-    defer validation_results.deinit(allocator);
+/// Store an API key in the OS keyring (`auth set SERVICE`), or show where
+/// the key of each service comes from (`auth status`). Returns the process
+/// exit code.
+fn runAuth(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    const Service = bibval.credentials.Service;
+    const action = if (args.files.len > 0) args.files[0] else "status";
 
-    // Try DOI-based lookup first (most reliable)
-    if (local_entry.doi != null and crossref.* != null) {
-        if (crossref.*.?.searchByDoi(local_entry.doi.?)) |remote| {
-            if (remote) |r| {
-                var result = r;
-                defer result.deinit();
-
-                // Validate match
-                const title_sim = try bibval.matcher.titleSimilarity(allocator, local_entry, &result);
-                if (title_sim >= 0.75 and bibval.matcher.yearsCompatible(local_entry, &result)) {
-                    const discrepancies = try bibval.matcher.compareEntries(allocator, local_entry, &result);
-                    const confidence: f64 = if (discrepancies.len == 0) 1.0 else 0.8;
-
-                    try validation_results.append(allocator, .{
-                        .source = .crossref,
-                        .matched_entry = null, // Don't copy entry
-                        .confidence = confidence,
-                        .discrepancies = discrepancies,
-                        .allocator = allocator,
-                    });
-                }
-            }
-        } else |err| {
-            if (verbose) {
-                std.debug.print("  [{s}] CrossRef lookup failed: {}\n", .{ local_entry.key, err });
+    if (std.mem.eql(u8, action, "status") and args.files.len <= 1) {
+        var diag: bibval.config.Diagnostic = .{};
+        var config = bibval.config.load(allocator, std.fs.cwd(), args.config_path, args.profile, &diag) catch |err| {
+            printConfigError(args.config_path, &diag, err);
+            return 1;
+        };
+        defer config.deinit();
+        for (std.enums.values(Service)) |service| {
+            try stdout.print("{s} ({s}): ", .{ service.name(), @tagName(service) });
+            const credential = (try bibval.credentials.find(allocator, service, bibval.credentials.configured(&config, service))) orelse {
+                try stdout.writeAll("not set\n");
+                continue;
+            };
+            defer allocator.free(credential.key);
+            switch (credential.source) {
+                .environment => try stdout.print("set by {s}\n", .{service.envVar()}),
+                .config => try stdout.print("set in {s} ({s})\n", .{ args.config_path, service.configKey().? }),
+                .keyring => try stdout.writeAll("stored in the keyring\n"),
             }
         }
+        return 0;
     }
 
-    // Try title search if no DOI match
-    if (validation_results.items.len == 0 and local_entry.title != null) {
-        // Try DBLP
-        if (dblp.* != null) {
-            if (dblp.*.?.searchByTitle(local_entry.title.?)) |results| {
-                defer {
-                    for (results) |*r| {
-                        var result = @constCast(r);
-                        result.deinit();
-                    }
-                    allocator.free(results);
-                }
+    const service = if (std.mem.eql(u8, action, "set") and args.files.len == 2) std.meta.stringToEnum(Service, args.files[1]) else null;
+    if (service == null) {
+        std.debug.print("Usage: bibval auth set ieee | springer | scopus | zotero | github\n       bibval auth status\n", .{});
+        return 1;
+    }
+    const prompt = try std.fmt.allocPrint(allocator, "{s} API key: ", .{service.?.name()});
+    defer allocator.free(prompt);
+    const line = try readSecret(allocator, prompt);
+    defer allocator.free(line);
+    const key = std.mem.trim(u8, line, " \t\r");
+    if (key.len == 0) {
+        std.debug.print("Error: No key given\n", .{});
+        return 1;
+    }
+    bibval.credentials.keyringSet(allocator, service.?, key) catch |err| switch (err) {
+        error.KeyringUnavailable => {
+            std.debug.print("Error: Could not store the key; is a keyring available (secret-tool on Linux)? Set {s} instead\n", .{service.?.envVar()});
+            return 1;
+        },
+        else => return err,
+    };
+    try stdout.print("Stored the {s} API key in the keyring\n", .{service.?.name()});
+    return 0;
+}
 
-                if (try bibval.matcher.findBestMatch(allocator, local_entry, results)) |match| {
-                    const discrepancies = try bibval.matcher.compareEntries(allocator, local_entry, match.entry);
-                    try validation_results.append(allocator, .{
-                        .source = .dblp,
-                        .matched_entry = null,
-                        .confidence = match.score,
-                        .discrepancies = discrepancies,
-                        .allocator = allocator,
-                    });
-                }
-            } else |err| {
-                if (verbose) {
-                    std.debug.print("  [{s}] DBLP lookup failed: {}\n", .{ local_entry.key, err });
-                }
-            }
+/// One line from stdin, not echoed if stdin is a terminal.
+fn readSecret(allocator: std.mem.Allocator, prompt: []const u8) ![]u8 {
+    const stdin = std.fs.File.stdin();
+    var original: ?std.posix.termios = null;
+    if (builtin.os.tag != .windows and stdin.isTty()) {
+        std.debug.print("{s}", .{prompt});
+        if (std.posix.tcgetattr(stdin.handle)) |attrs| {
+            var quiet = attrs;
+            quiet.lflag.ECHO = false;
+            std.posix.tcsetattr(stdin.handle, .FLUSH, quiet) catch {};
+            original = attrs;
+        } else |_| {}
+    }
+    defer {
+        if (original) |attrs| {
+            std.posix.tcsetattr(stdin.handle, .FLUSH, attrs) catch {};
+            std.debug.print("\n", .{});
         }
+    }
 
-        // Try Semantic Scholar
-        if (semantic.* != null) {
-            if (semantic.*.?.searchByTitle(local_entry.title.?)) |results| {
-                defer {
-                    for (results) |*r| {
-                        var result = @constCast(r);
-                        result.deinit();
-                    }
-                    allocator.free(results);
-                }
+    var line: std.ArrayList(u8) = .empty;
+    errdefer line.deinit(allocator);
+    var byte: [1]u8 = undefined;
+    while ((try stdin.read(&byte)) == 1 and byte[0] != '\n') try line.append(allocator, byte[0]);
+    return line.toOwnedSlice(allocator);
+}
 
-                if (try bibval.matcher.findBestMatch(allocator, local_entry, results)) |match| {
-                    const discrepancies = try bibval.matcher.compareEntries(allocator, local_entry, match.entry);
-                    try validation_results.append(allocator, .{
-                        .source = .semantic_scholar,
-                        .matched_entry = null,
-                        .confidence = match.score,
-                        .discrepancies = discrepancies,
-                        .allocator = allocator,
-                    });
-                }
-            } else |err| {
-                if (verbose) {
-                    std.debug.print("  [{s}] Semantic Scholar lookup failed: {}\n", .{ local_entry.key, err });
-                }
-            }
-        }
+fn printConfigError(path: []const u8, diag: *const bibval.config.Diagnostic, err: anyerror) void {
+    if (diag.line > 0) {
+        std.debug.print("Error: {s}:{d}: {s}\n", .{ path, diag.line, diag.message });
+    } else {
+        std.debug.print("Error: Failed to load config {s}: {s}\n", .{ path, if (diag.message.len > 0) diag.message else @errorName(err) });
+    }
+}
 
-        // Try OpenAlex
-        if (openalex.* != null) {
-            if (openalex.*.?.searchByTitle(local_entry.title.?)) |results| {
-                defer {
-                    for (results) |*r| {
-                        var result = @constCast(r);
-                        result.deinit();
-                    }
-                    allocator.free(results);
-                }
+/// Report every problem of the config file: unknown and deprecated keys,
+/// type errors, and values bibval rejects. Returns the process exit code.
+fn runConfig(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    if (args.files.len != 1 or !std.mem.eql(u8, args.files[0], "check")) {
+        std.debug.print("Usage: bibval config check [--config PATH] [--profile NAME]\n", .{});
+        return 1;
+    }
+    const path = args.config_path;
+    const content = std.fs.cwd().readFileAlloc(allocator, path, 1024 * 1024) catch |err| {
+        std.debug.print("Error: Cannot read {s}: {s}\n", .{ path, @errorName(err) });
+        return 1;
+    };
+    defer allocator.free(content);
 
-                if (try bibval.matcher.findBestMatch(allocator, local_entry, results)) |match| {
-                    const discrepancies = try bibval.matcher.compareEntries(allocator, local_entry, match.entry);
-                    try validation_results.append(allocator, .{
-                        .source = .openalex,
-                        .matched_entry = null,
-                        .confidence = match.score,
-                        .discrepancies = discrepancies,
-                        .allocator = allocator,
-                    });
-                }
-            } else |err| {
-                if (verbose) {
-                    std.debug.print("  [{s}] OpenAlex lookup failed: {}\n", .{ local_entry.key, err });
-                }
-            }
-        }
+    var diag: bibval.config.Diagnostic = .{};
+    var doc = bibval.config.parse(allocator, content, &diag) catch |err| {
+        printConfigError(path, &diag, err);
+        return 1;
+    };
+    defer doc.deinit();
+
+    const problems = try doc.check();
+    var typed = true;
+    for (problems) |problem| {
+        try stdout.print("{s}:{d}: {s}: {s}\n", .{ path, problem.line, @tagName(problem.severity), problem.message });
+        if (problem.severity == .@"error") typed = false;
+    }
+    // Ranges, patterns, and the profile are checked by loading it
+    if (typed) {
+        var config = bibval.config.load(allocator, std.fs.cwd(), path, args.profile, &diag) catch |err| {
+            stdout.flush() catch {};
+            printConfigError(path, &diag, err);
+            return 1;
+        };
+        config.deinit();
     }
+    if (problems.len > 0) return 1;
+    try stdout.print("{s}: no problems found\n", .{path});
+    return 0;
+}
 
-    // Determine status
-    const status = determineStatus(&validation_results);
+/// Validate entries against the enabled APIs and print the report.
+/// Returns the process exit code.
+fn runCheck(allocator: std.mem.Allocator, args: *const Args, entries: []Entry, library: ?*const bibval.zotero.Library, stdout: *std.Io.Writer, use_color: bool) !u8 {
+    const run_start_ms = std.time.milliTimestamp();
+    if (args.format == .text and !args.quiet) {
+        try stdout.writeAll("\n");
+        try stdout.print("Validating {d} entries...\n\n", .{entries.len});
+    }
 
-    // Clone entry for report
-    var entry_copy = Entry{
-        .key = try allocator.dupe(u8, local_entry.key),
-        .entry_type = try allocator.dupe(u8, local_entry.entry_type),
-        .title = if (local_entry.title) |t| try allocator.dupe(u8, t) else null,
-        .year = local_entry.year,
-        .venue = if (local_entry.venue) |v| try allocator.dupe(u8, v) else null,
-        .doi = if (local_entry.doi) |d| try allocator.dupe(u8, d) else null,
-        .arxiv_id = if (local_entry.arxiv_id) |a| try allocator.dupe(u8, a) else null,
-        .url = if (local_entry.url) |u| try allocator.dupe(u8, u) else null,
-        .allocator = allocator,
+    var config = loadConfig(allocator, args) orelse return 1;
+    defer config.deinit();
+
+    // Initialize cache
+    var response_cache = try bibval.cache.Cache.init(allocator, !args.no_cache);
+    defer response_cache.deinit();
+
+    // Latency and failures of each backend, for the summary
+    var metrics: bibval.metrics.Metrics = .{};
+    var tracer = Tracer{
+        .level = @enumFromInt(@min(args.verbosity, 2)),
+        .json = args.log_json,
+        .metrics = &metrics,
     };
 
-    // Clone authors
-    if (local_entry.authors.len > 0) {
-        var authors = try allocator.alloc([]const u8, local_entry.authors.len);
-        for (local_entry.authors, 0..) |a, i| {
-            authors[i] = try allocator.dupe(u8, a);
+    // Shared HTTP client so all validators reuse pooled connections
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
+    client.tracer = &tracer;
+    client.metrics = &metrics;
+    client.min_interval_ms = args.request_interval_ms;
+
+    // Initialize validators
+    var sources = args.sources(allocator, &client, &response_cache, &config);
+    var venue_resolver = bibval.canonical.Resolver.init(allocator, &client, &response_cache);
+    defer venue_resolver.deinit();
+    try args.canonicalVenues(&config, &venue_resolver);
+
+    // Replay a committed snapshot instead of querying the APIs
+    var replay: ?bibval.snapshot.Snapshot = null;
+    if (args.snapshot_in) |path| {
+        replay = bibval.snapshot.Snapshot.load(allocator, std.fs.cwd(), path) catch |err| {
+            std.debug.print("Error: Failed to load snapshot {s}: {s}\n", .{ path, @errorName(err) });
+            return 1;
+        };
+    }
+    defer if (replay) |*snap| snap.deinit();
+
+    // Progress journal so an interrupted run can be resumed
+    var journal = try bibval.journal.Journal.open(allocator, std.fs.cwd(), args.journal_path, args.resume_run);
+    defer journal.deinit();
+
+    if (args.resume_run and args.format == .text and !args.quiet) {
+        try stdout.print("Resuming: {d} entries recorded in {s}\n\n", .{ journal.restorableCount(), args.journal_path });
+    }
+
+    // Settings per [[targets]] table of the config, then the run's own
+    const runs = try allocator.alloc(TargetRun, config.targets.len + 1);
+    defer allocator.free(runs);
+    for (runs, 0..) |*run, t| {
+        run.* = .{ .sources = sources, .disabled = args.disabled, .min_confidence = args.min_confidence, .matching = config.matching, .verdicts = undefined };
+        if (t < config.targets.len) {
+            const target = &config.targets[t];
+            if (target.validators) |allowed| run.sources = sources.restrict(allowed);
+            run.disabled.setUnion(target.disabled);
+            if (target.min_confidence) |c| run.min_confidence = c;
+            if (target.max_year_distance) |d| run.matching.max_year_distance = d;
+            run.ignores = target.ignores;
         }
-        entry_copy.authors = authors;
+        // Finished reports of unchanged entries, valid while the settings stay the same
+        run.verdicts = bibval.verdicts.Verdicts.init(allocator, &response_cache, .{
+            .backends = run.sources.backends(),
+            .disabled = run.disabled,
+            .min_confidence = run.min_confidence,
+            .check_funders = args.check_funders,
+            .open_access = args.access != .none,
+            .matching = &run.matching,
+            .config = config.fingerprint,
+        });
     }
 
-    return EntryReport{
-        .entry = entry_copy,
-        .status = status,
-        .validation_results = try validation_results.toOwnedSlice(allocator),
-        .allocator = allocator,
-    };
-}
+    // Known-unmatched entries are skipped until their ignore expires
+    const today = bibval.ignores.Date.today();
+    var expired: usize = 0;
+    for (entries) |*local_entry| {
+        const run = &runs[bibval.workspace.forFile(config.targets, local_entry.source_file) orelse config.targets.len];
+        const ignore = bibval.ignores.find(run.ignores, local_entry.key) orelse bibval.ignores.find(config.ignores, local_entry.key) orelse continue;
+        if (!ignore.isActive(today)) expired += 1;
+    }
+    if (expired > 0 and args.format == .text and !args.quiet) {
+        try stdout.print("Checking {d} known-unmatched entries again: their ignore has expired\n\n", .{expired});
+    }
+
+    // Replaying a snapshot is purely local, so it runs on all threads up front
+    var replayed: []?EntryReport = &.{};
+    defer {
+        for (replayed) |*r| if (r.*) |*entry_report| entry_report.deinit();
+        allocator.free(replayed);
+    }
+    if (replay) |*snap| {
+        replayed = try replayAll(allocator, entries, snap, config.targets, runs, args.jobs, &config.screening);
+    }
 
-fn determineStatus(results: *const std.ArrayList(ValidationResult)) EntryStatus {
-    if (results.items.len == 0) {
-        return .not_found;
+    // Progress line on stderr, for interactive runs only
+    var progress: ?bibval.progress.Progress = null;
+    if (!args.quiet and args.verbosity == 0 and !args.log_json and std.fs.File.stdout().isTty() and std.fs.File.stderr().isTty()) {
+        try stdout.flush();
+        progress = .init(entries.len, terminalWidth(allocator));
+        progress.?.waited_ms = &client.waited_ms;
+        tracer.progress = &progress.?;
     }
 
-    var has_errors = false;
-    var has_warnings = false;
-    var best_source: ApiSource = .crossref;
-    var best_confidence: f64 = 0;
+    // Validate entries
+    var report = Report.init(allocator);
+    defer report.deinit();
+    // Entries skipped because the deadline passed
+    var skipped: usize = 0;
+
+    for (entries, 0..) |*local_entry, i| {
+        tracer.entry_key = local_entry.key;
+        defer metrics.endEntry();
+        defer if (progress) |*p| p.advance();
+        const run = &runs[bibval.workspace.forFile(config.targets, local_entry.source_file) orelse config.targets.len];
 
-    for (results.items) |result| {
-        if (result.confidence > best_confidence) {
-            best_confidence = result.confidence;
-            best_source = result.source;
+        if (bibval.ignores.find(run.ignores, local_entry.key) orelse bibval.ignores.find(config.ignores, local_entry.key)) |ignore| {
+            if (ignore.isActive(today)) {
+                tracer.event(.info, "ignore", "skipped as known unmatched", .{});
+                try report.add(try knownUnmatched(allocator, local_entry, ignore));
+                continue;
+            }
+            tracer.event(.info, "ignore", "expired, checking again", .{});
         }
 
-        for (result.discrepancies) |d| {
-            if (d.severity == .@"error") has_errors = true;
-            if (d.severity == .warning) has_warnings = true;
-        }
-    }
-
-    if (has_errors) return .@"error";
-    if (has_warnings) return .warning;
-    return .{ .ok = best_source };
-}
-
-fn printJsonReport(allocator: std.mem.Allocator, writer: anytype, report: *const Report) !void {
-    try writer.writeAll("{\"entries\":[");
-
-    var first = true;
-    for (report.entries.items) |entry_report| {
-        if (!first) try writer.writeAll(",");
-        first = false;
-
-        try writer.writeAll("{\"key\":");
-        try writeJsonString(writer, entry_report.entry.key);
-        try writer.writeAll(",\"title\":");
-        if (entry_report.entry.title) |t| {
-            try writeJsonString(writer, t);
-        } else {
-            try writer.writeAll("null");
-        }
-        try writer.writeAll(",\"status\":\"");
-        switch (entry_report.status) {
-            .ok => |source| try writer.print("ok:{s}", .{source.name()}),
-            .warning => try writer.writeAll("warning"),
-            .@"error" => try writer.writeAll("error"),
-            .not_found => try writer.writeAll("not_found"),
-            .failed => |msg| try writer.print("failed:{s}", .{msg}),
-        }
-        try writer.writeAll("\",\"discrepancies\":[");
-
-        var disc_first = true;
-        for (entry_report.validation_results) |result| {
-            for (result.discrepancies) |d| {
-                if (!disc_first) try writer.writeAll(",");
-                disc_first = false;
-
-                try writer.writeAll("{\"field\":\"");
-                try writer.writeAll(d.field.name());
-                try writer.writeAll("\",\"severity\":\"");
-                try writer.writeAll(d.severity.name());
-                try writer.writeAll("\",\"message\":");
-                try writeJsonString(writer, d.message);
-                try writer.writeAll("}");
+        if (try journal.restore(local_entry)) |restored| {
+            tracer.event(.info, "journal", "restored {s}", .{@tagName(restored.status)});
+            try report.add(restored);
+            continue;
+        }
+
+        if (replay == null) {
+            if (try run.verdicts.restore(local_entry)) |cached| {
+                tracer.event(.info, "verdict", "cached {s}", .{@tagName(cached.status)});
+                try journal.record(&cached);
+                try report.add(cached);
+                continue;
             }
         }
-        try writer.writeAll("]}");
-    }
-
-    try writer.writeAll("],\"summary\":{");
-    try writer.print("\"total\":{d},\"ok\":{d},\"warnings\":{d},\"errors\":{d},\"not_found\":{d}", .{
-        report.entries.items.len,
-        report.countOk(),
-        report.countWarnings(),
-        report.countErrors(),
-        report.countNotFound(),
-    });
-    try writer.writeAll("}}\n");
-    _ = allocator;
-}
-
-fn writeJsonString(writer: anytype, s: []const u8) !void {
-    try writer.writeByte('"');
-    for (s) |c| {
-        switch (c) {
-            '"' => try writer.writeAll("\\\""),
-            '\\' => try writer.writeAll("\\\\"),
-            '\n' => try writer.writeAll("\\n"),
-            '\r' => try writer.writeAll("\\r"),
-            '\t' => try writer.writeAll("\\t"),
-            0x08 => try writer.writeAll("\\b"), // backspace
-            0x0C => try writer.writeAll("\\f"), // form feed
-            else => {
-                if (c < 0x20) {
-                    try writer.print("\\u{x:0>4}", .{c});
-                } else {
-                    try writer.writeByte(c);
-                }
-            },
+
+        const start_ms = std.time.milliTimestamp();
+        if (replay == null and args.deadline_ms != null and @as(u64, @intCast(start_ms - run_start_ms)) >= args.deadline_ms.?) {
+            // Left out of the journal, so --resume picks them up
+            try report.add(try notValidated(allocator, local_entry, "not validated (deadline)"));
+            skipped += 1;
+            continue;
         }
+        const entry_report = if (replay != null) replayed[i].? else try bibval.pipeline.validateEntry(allocator, local_entry, &run.sources, &tracer, run.disabled, run.min_confidence, args.check_funders, args.access != .none, &config.screening, &run.matching);
+        if (replay != null) replayed[i] = null else try run.verdicts.store(&entry_report);
+        tracer.event(.info, "entry", "{s} in {d}ms", .{ @tagName(entry_report.status), std.time.milliTimestamp() - start_ms });
+        try journal.record(&entry_report);
+        try report.add(entry_report);
+    }
+    tracer.entry_key = null;
+    tracer.progress = null;
+    if (progress) |*p| p.finish();
+    if (skipped > 0) {
+        std.debug.print("Deadline reached: {d} of {d} entries not validated; rerun with --resume to continue\n", .{ skipped, entries.len });
+    } else {
+        journal.finish();
     }
-    try writer.writeByte('"');
-}
 
-fn parseArgs(allocator: std.mem.Allocator) !Args {
+    if (args.command == .snapshot) {
+        var snap = try bibval.snapshot.Snapshot.fromReport(allocator, &report);
+        defer snap.deinit();
+        try snap.save(std.fs.cwd(), args.snapshot_out);
+        if (args.format == .text) {
+            try stdout.print("Wrote {d} records for {d} entries to {s}\n", .{ snap.recordCount(), snap.records.count(), args.snapshot_out });
+        }
+    }
+
+    if (args.history_path) |path| {
+        bibval.history.record(allocator, path, &report, args.files) catch |err| {
+            std.debug.print("Warning: Failed to record the run in {s}: {s}\n", .{ path, @errorName(err) });
+        };
+    }
+
+    report.sort(args.sort);
+
+    var policy = try bibval.policy.evaluate(allocator, &report, config.policy, &config.screening, bibval.ignores.Date.today(), args.disabled);
+    defer policy.deinit();
+
+    var duplicates = try bibval.duplicates.check(allocator, entries, &config.matching, args.disabled);
+    defer duplicates.deinit();
+    var consistency = try bibval.consistency.check(allocator, entries, config.venue_style, config.engine, args.disabled);
+    defer consistency.deinit();
+    // Rules disabled by the [[targets]] of all the entries involved
+    for ([_]*bibval.policy.Evaluation{ &policy, &duplicates, &consistency }) |evaluation| {
+        try bibval.workspace.dropDisabled(evaluation, config.targets, entries);
+    }
+
+    if (args.command == .stats) {
+        var stats = try bibval.stats.compute(allocator, &report, args.authors);
+        defer stats.deinit();
+        switch (args.format) {
+            .json => try stats.writeJson(stdout),
+            else => try stats.print(stdout, bibval.stats.DEFAULT_TOP, args.authors.len),
+        }
+        return 0;
+    }
+
+    // Output report
+    switch (args.format) {
+        .text => {
+            try report.print(stdout, .{ .use_color = use_color, .width = terminalWidth(allocator), .quiet = args.quiet, .access = args.access, .show_matched = args.show_matched, .metrics = &metrics });
+            try policy.print(stdout);
+            try duplicates.print(stdout);
+            try consistency.print(stdout);
+        },
+        .json => try bibval.pipeline.writeJsonReport(allocator, stdout, &report, &policy, &duplicates, &consistency, &metrics),
+        .patch => try printPatch(allocator, args.files, entries, &report, .{ .venue_style = config.venue_style, .string_macros = args.string_macros or config.string_macros, .engine = config.engine }, config.policy, config.targets, args.disabled, stdout),
+    }
+
+    if (args.zotero_write) {
+        if (library) |lib| try writeZotero(allocator, args, lib, &report, &client, .{ .venue_style = config.venue_style }, stdout);
+    }
+
+    // Determine exit code
+    var errors = report.countErrors() + policy.count(.@"error") + duplicates.count(.@"error") + consistency.count(.@"error");
+    if (args.strict) errors += report.countWarnings() + policy.count(.warning) + duplicates.count(.warning) + consistency.count(.warning);
+
+    var gates = try bibval.gates.evaluate(allocator, &report, args.gates, errors);
+    defer gates.deinit();
+    if (args.format == .text) {
+        try gates.print(stdout);
+    } else {
+        for (gates.failures) |message| std.debug.print("Gate failed: {s}\n", .{message});
+    }
+
+    if (errors > (args.gates.max_errors orelse 0) or !gates.passed()) return 1;
+    if (skipped > 0) return 2;
+    return 0;
+}
+
+/// Settings an entry is validated with, per `[[targets]]` table.
+const TargetRun = struct {
+    sources: bibval.pipeline.Sources,
+    disabled: bibval.rules.RuleSet,
+    min_confidence: f64,
+    matching: bibval.matcher.MatcherConfig,
+    ignores: []const bibval.ignores.Ignore = &.{},
+    verdicts: bibval.verdicts.Verdicts,
+};
+
+/// Report for an entry skipped by an `[[ignore]]` of the config.
+fn knownUnmatched(allocator: std.mem.Allocator, local: *const Entry, ignore: *const bibval.ignores.Ignore) !EntryReport {
+    var entry_copy = try local.clone(allocator);
+    errdefer entry_copy.deinit();
+    const reason = try ignore.describe(allocator);
+    errdefer allocator.free(reason);
+    return .{
+        .entry = entry_copy,
+        .status = .{ .known_unmatched = reason },
+        .validation_results = try allocator.alloc(ValidationResult, 0),
+        .allocator = allocator,
+    };
+}
+
+/// Report for an entry that was not validated, with the reason.
+fn notValidated(allocator: std.mem.Allocator, local: *const Entry, reason: []const u8) !EntryReport {
+    var entry_copy = try local.clone(allocator);
+    errdefer entry_copy.deinit();
+    const owned = try allocator.dupe(u8, reason);
+    errdefer allocator.free(owned);
+    return .{
+        .entry = entry_copy,
+        .status = .{ .failed = owned },
+        .validation_results = try allocator.alloc(ValidationResult, 0),
+        .allocator = allocator,
+    };
+}
+
+/// Print the request plan for a run without touching the network.
+fn runPlan(allocator: std.mem.Allocator, args: *const Args, entries: []const Entry, stdout: *std.Io.Writer) !u8 {
+    var config = loadConfig(allocator, args) orelse return 1;
+    defer config.deinit();
+
+    var response_cache = try bibval.cache.Cache.init(allocator, !args.no_cache);
+    defer response_cache.deinit();
+
+    var backends = args.backends(&config);
+    backends.external = args.plugins(&config).len;
+    // Entries of a [[targets]] table query only its validators
+    var plan = bibval.plan.Plan{};
+    for (entries) |*e| {
+        const allowed = if (bibval.workspace.forFile(config.targets, e.source_file)) |t| config.targets[t].validators else null;
+        plan.add(e, if (allowed) |a| backends.restrict(a) else backends, &response_cache);
+    }
+    const estimate_ms = plan.estimatedMs(bibval.plan.DEFAULT_LATENCY_MS, args.request_interval_ms);
+
+    if (args.format == .json) {
+        try stdout.print("{{\"entries\":{d},\"strategies\":{{", .{plan.entries});
+        for (std.enums.values(bibval.plan.Strategy), 0..) |strategy, i| {
+            if (i > 0) try stdout.writeAll(",");
+            try stdout.print("\"{s}\":{d}", .{ @tagName(strategy), plan.strategies.get(strategy) });
+        }
+        try stdout.writeAll("},\"backends\":{");
+        for (std.enums.values(ApiSource), 0..) |source, i| {
+            if (i > 0) try stdout.writeAll(",");
+            try stdout.print("\"{s}\":{{\"enabled\":{s},\"requests\":{d},\"cached\":{d}}}", .{
+                @tagName(source),
+                if (backends.isEnabled(source)) "true" else "false",
+                plan.requests.get(source),
+                plan.cached.get(source),
+            });
+        }
+        try stdout.print("}},\"network_requests\":{d},\"estimated_ms\":{d}}}\n", .{ plan.networkRequests(), estimate_ms });
+        return 0;
+    }
+
+    try stdout.writeAll("\nbibval Plan\n");
+    try stdout.writeAll("==================================================\n\n");
+
+    try stdout.print("Entries: {d}\n", .{plan.entries});
+    for (std.enums.values(bibval.plan.Strategy)) |strategy| {
+        try stdout.print("  {s:<20}{d}\n", .{ strategy.name(), plan.strategies.get(strategy) });
+    }
+
+    try stdout.writeAll("\nRequests per backend:\n");
+    for (std.enums.values(ApiSource)) |source| {
+        if (!backends.isEnabled(source)) {
+            try stdout.print("  {s:<18}disabled\n", .{source.name()});
+            continue;
+        }
+        try stdout.print("  {s:<18}{d}", .{ source.name(), plan.requests.get(source) });
+        if (plan.cached.get(source) > 0) {
+            try stdout.print(" ({d} cached)", .{plan.cached.get(source)});
+        }
+        try stdout.writeAll("\n");
+    }
+
+    try stdout.print("\nNetwork requests: {d}\n", .{plan.networkRequests()});
+    try stdout.writeAll("Estimated time: ~");
+    try printDuration(stdout, estimate_ms);
+    try stdout.print(" ({d}ms per request, {d}ms interval)\n\n", .{ bibval.plan.DEFAULT_LATENCY_MS, args.request_interval_ms });
+    return 0;
+}
+
+/// Show when the findings of an entry appeared and were fixed across the
+/// runs recorded with `--history`. The key is the first argument after
+/// `history`.
+fn runHistory(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    if (args.files.len == 0) {
+        std.debug.print("Error: bibval history needs a citation key\n", .{});
+        return 1;
+    }
+    const key = args.files[0];
+    const path = args.history_path orelse bibval.history.DEFAULT_PATH;
+
+    var history = bibval.history.load(allocator, path, key) catch |err| {
+        switch (err) {
+            error.FileNotFound => std.debug.print("Error: No history at {s}; record runs with --history {s}\n", .{ path, path }),
+            error.SqliteUnavailable => std.debug.print("Error: bibval history needs the sqlite3 command\n", .{}),
+            else => std.debug.print("Error: Failed to read {s}: {s}\n", .{ path, @errorName(err) }),
+        }
+        return 1;
+    };
+    defer history.deinit();
+
+    if (history.rows.len == 0) {
+        std.debug.print("No runs in {s} recorded {s}\n", .{ path, key });
+        return 1;
+    }
+    try bibval.history.printTimeline(stdout, key, history);
+    return 0;
+}
+
+/// Inspect or clear the response and verdict cache. The action is the
+/// first argument after `cache`.
+fn runCache(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    var response_cache = try bibval.cache.Cache.init(allocator, false);
+    defer response_cache.deinit();
+
+    const action = if (args.files.len > 0) args.files[0] else "stats";
+    if (std.mem.eql(u8, action, "clear")) {
+        const removed = response_cache.clear() catch |err| {
+            std.debug.print("Error: Failed to clear {s}: {s}\n", .{ response_cache.cache_dir, @errorName(err) });
+            return 1;
+        };
+        try stdout.print("Removed {d} cached records from {s}\n", .{ removed, response_cache.cache_dir });
+        return 0;
+    }
+    if (!std.mem.eql(u8, action, "stats") and !std.mem.eql(u8, action, "show")) {
+        std.debug.print("Error: Unknown cache command: {s} (use stats, clear, or show)\n", .{action});
+        return 1;
+    }
+
+    const records = response_cache.list(allocator) catch |err| {
+        std.debug.print("Error: Failed to read {s}: {s}\n", .{ response_cache.cache_dir, @errorName(err) });
+        return 1;
+    };
+    defer bibval.cache.freeRecords(allocator, records);
+
+    if (std.mem.eql(u8, action, "show")) {
+        if (args.files.len < 2) {
+            std.debug.print("Error: bibval cache show needs a citation key or cache file name\n", .{});
+            return 1;
+        }
+        return showCached(allocator, &response_cache, records, args.files[1], stdout);
+    }
+
+    // Records and bytes per API, in name order
+    const Usage = struct { records: usize = 0, bytes: u64 = 0 };
+    var by_api: std.StringArrayHashMapUnmanaged(Usage) = .empty;
+    defer by_api.deinit(allocator);
+    var total: Usage = .{};
+    var expired: usize = 0;
+    var oldest: i64 = 0;
+    for (records) |r| {
+        const gop = try by_api.getOrPut(allocator, r.api);
+        if (!gop.found_existing) gop.value_ptr.* = .{};
+        gop.value_ptr.records += 1;
+        gop.value_ptr.bytes += r.size;
+        total.records += 1;
+        total.bytes += r.size;
+        if (r.isExpired()) expired += 1;
+        oldest = @max(oldest, r.age);
+    }
+
+    if (args.format == .json) {
+        try stdout.writeAll("{\"dir\":");
+        try writeJsonString(stdout, response_cache.cache_dir);
+        try stdout.print(",\"records\":{d},\"bytes\":{d},\"expired\":{d},\"oldest_secs\":{d},\"apis\":{{", .{ total.records, total.bytes, expired, oldest });
+        for (by_api.keys(), by_api.values(), 0..) |api, usage, i| {
+            if (i > 0) try stdout.writeAll(",");
+            try stdout.print("\"{s}\":{{\"records\":{d},\"bytes\":{d}}}", .{ api, usage.records, usage.bytes });
+        }
+        try stdout.writeAll("}}\n");
+        return 0;
+    }
+
+    try stdout.print("\nCache: {s}\n\n", .{response_cache.cache_dir});
+    for (by_api.keys(), by_api.values()) |api, usage| {
+        try stdout.print("  {s:<20}{d:>7} records  ", .{ api, usage.records });
+        try printSize(stdout, usage.bytes);
+        try stdout.writeAll("\n");
+    }
+    try stdout.print("  {s:<20}{d:>7} records  ", .{ "Total", total.records });
+    try printSize(stdout, total.bytes);
+    try stdout.writeAll("\n");
+    if (records.len > 0) {
+        try stdout.print("\n{d} expired; oldest written ", .{expired});
+        try printDuration(stdout, @intCast(oldest * 1000));
+        try stdout.writeAll(" ago\n");
+    }
+    try stdout.writeAll("\n");
+    return 0;
+}
+
+/// Print the cached records named by `query`: verdicts of the entry with
+/// that citation key, or the file with that name or hash prefix.
+fn showCached(allocator: std.mem.Allocator, response_cache: *bibval.cache.Cache, records: []const bibval.cache.Record, query: []const u8, stdout: *std.Io.Writer) !u8 {
+    var shown: usize = 0;
+    for (records) |r| {
+        const hash = r.name[r.api.len + 1 .. r.name.len - ".json".len];
+        const by_name = std.mem.eql(u8, r.name, query) or (query.len >= 4 and std.mem.startsWith(u8, hash, query));
+        const is_verdict = std.mem.eql(u8, r.api, "verdict");
+        if (!by_name and !is_verdict) continue;
+
+        const content = response_cache.read(allocator, r.name) catch continue;
+        defer allocator.free(content);
+
+        var parsed: ?std.json.Parsed(std.json.Value) = null;
+        defer if (parsed) |*p| p.deinit();
+        if (is_verdict) {
+            parsed = std.json.parseFromSlice(std.json.Value, allocator, content, .{}) catch null;
+        }
+        const record: ?std.json.ObjectMap = if (parsed) |p| (if (p.value == .object) p.value.object else null) else null;
+        const key: ?[]const u8 = if (record) |obj| jsonString(obj, "key") else null;
+        if (!by_name and !(key != null and std.mem.eql(u8, key.?, query))) continue;
+
+        shown += 1;
+        try stdout.print("\n{s}\n", .{r.name});
+        try stdout.writeAll("  Age:     ");
+        try printDuration(stdout, @intCast(@max(r.age, 0) * 1000));
+        if (r.isExpired()) try stdout.writeAll(" (expired)");
+        try stdout.writeAll("\n");
+
+        const obj = record orelse {
+            try stdout.print("  Backend: {s}\n  Size:    ", .{r.api});
+            try printSize(stdout, r.size);
+            try stdout.print("\n\n{s}\n", .{content});
+            continue;
+        };
+        try stdout.print("  Entry:   {s}\n  Status:  {s}\n", .{ key orelse "?", jsonString(obj, "status") orelse "?" });
+        try stdout.writeAll("  Backend: ");
+        const results: []const std.json.Value = if (obj.get("results")) |v| (if (v == .array) v.array.items else &.{}) else &.{};
+        var sources: usize = 0;
+        for (results) |result| {
+            if (result != .object) continue;
+            const source_tag = jsonString(result.object, "source") orelse continue;
+            const source = std.meta.stringToEnum(ApiSource, source_tag) orelse continue;
+            if (sources > 0) try stdout.writeAll(", ");
+            sources += 1;
+            try stdout.writeAll(source.name());
+            if (result.object.get("discrepancies")) |d| {
+                if (d == .array and d.array.items.len > 0) try stdout.print(" ({d} discrepancies)", .{d.array.items.len});
+            }
+        }
+        if (sources == 0) try stdout.writeAll("none (not matched)");
+        try stdout.writeAll("\n");
+    }
+
+    if (shown == 0) {
+        std.debug.print("Error: Nothing cached for {s}\n", .{query});
+        return 1;
+    }
+    try stdout.writeAll("\n");
+    return 0;
+}
+
+fn jsonString(obj: std.json.ObjectMap, name: []const u8) ?[]const u8 {
+    const value = obj.get(name) orelse return null;
+    return if (value == .string) value.string else null;
+}
+
+fn printSize(writer: *std.Io.Writer, bytes: u64) !void {
+    if (bytes >= 1024 * 1024) {
+        try writer.print("{d:.1} MB", .{@as(f64, @floatFromInt(bytes)) / (1024 * 1024)});
+    } else if (bytes >= 1024) {
+        try writer.print("{d:.1} KB", .{@as(f64, @floatFromInt(bytes)) / 1024});
+    } else {
+        try writer.print("{d} B", .{bytes});
+    }
+}
+
+/// Validate a single reference given on the command line, then print its
+/// matches with scores and a BibTeX entry built from the best one.
+fn runOne(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    if (args.title == null and args.doi == null) {
+        std.debug.print("Error: bibval one needs --title or --doi\n", .{});
+        return 1;
+    }
+
+    var config = loadConfig(allocator, args) orelse return 1;
+    defer config.deinit();
+
+    var response_cache = try bibval.cache.Cache.init(allocator, !args.no_cache);
+    defer response_cache.deinit();
+
+    var tracer = Tracer{
+        .level = @enumFromInt(@min(args.verbosity, 2)),
+        .json = args.log_json,
+    };
+
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
+    client.tracer = &tracer;
+    client.min_interval_ms = args.request_interval_ms;
+
+    var sources = args.sources(allocator, &client, &response_cache, &config);
+    var venue_resolver = bibval.canonical.Resolver.init(allocator, &client, &response_cache);
+    defer venue_resolver.deinit();
+    try args.canonicalVenues(&config, &venue_resolver);
+
+    var query = Entry{
+        .key = "query",
+        .entry_type = "misc",
+        .title = args.title,
+        .authors = args.authors,
+        .year = args.year,
+        .doi = args.doi,
+    };
+
+    // With only a DOI, take the title from its record so the title
+    // searches can run too
+    var resolved: ?Entry = null;
+    defer if (resolved) |*r| r.deinit();
+    if (query.title == null) {
+        resolved = bibval.pipeline.resolveDoi(args.doi.?, &sources);
+        const record = if (resolved) |*r| r else {
+            try stdout.print("No record found for DOI {s}\n", .{args.doi.?});
+            return 1;
+        };
+        query.title = record.title;
+        if (query.authors.len == 0) query.authors = record.authors;
+        if (query.year == null) query.year = record.year;
+    }
+
+    var entry_report = try bibval.pipeline.validateEntry(allocator, &query, &sources, &tracer, args.disabled, args.min_confidence, false, false, &config.screening, &config.matching);
+    defer entry_report.deinit();
+
+    try stdout.print("\n{s}", .{query.title.?});
+    if (query.year) |y| try stdout.print(" ({d})", .{y});
+    try stdout.writeAll("\n\n");
+
+    // Best matches first
+    const ranked = try allocator.alloc(*const ValidationResult, entry_report.validation_results.len);
+    defer allocator.free(ranked);
+    for (entry_report.validation_results, ranked) |*result, *slot| slot.* = result;
+    std.sort.pdq(*const ValidationResult, ranked, {}, higherConfidence);
+
+    if (ranked.len == 0) {
+        try stdout.writeAll("No matches found.\n");
+        for (entry_report.lookups) |lookup| {
+            try stdout.print("  {s:<18}{s}\n", .{ lookup.strategy.name(), lookup.outcome.describe() });
+        }
+        if (entry_report.candidates.len > 0) try stdout.writeAll("\nClosest candidates:\n");
+        for (entry_report.candidates) |candidate| {
+            try stdout.print("  {s:<18}{d:.2}  {s}", .{ candidate.source.name(), candidate.score, candidate.title });
+            if (candidate.year) |y| try stdout.print(" ({d})", .{y});
+            try stdout.print("\n{s:<26}{s}\n", .{ "", candidate.reason.describe() });
+        }
+        return 1;
+    }
+
+    try stdout.print("Matches ({d}):\n", .{ranked.len});
+    for (ranked) |result| {
+        try stdout.print("  {s:<18}{d:.2}  ", .{ result.source.name(), result.confidence });
+        const matched = result.matched_entry orelse {
+            try stdout.writeAll("(no record)\n");
+            continue;
+        };
+        try stdout.writeAll(matched.title orelse "(untitled)");
+        if (matched.year) |y| try stdout.print(" ({d})", .{y});
+        try stdout.writeAll("\n");
+        if (matched.venue) |venue| try stdout.print("{s:<26}{s}\n", .{ "", venue });
+        if (matched.doi) |doi| try stdout.print("{s:<26}doi:{s}\n", .{ "", doi });
+        for (result.discrepancies) |d| {
+            try stdout.print("{s:<26}{s} {s} {s}\n", .{ "", d.severity.name(), d.rule.id(), d.message });
+        }
+    }
+
+    var best = (try bestRecord(allocator, entry_report.validation_results)) orelse return 1;
+    defer best.deinit();
+
+    try stdout.writeAll("\nBibTeX:");
+    try bibval.pipeline.printProvenance(stdout, &best.provenance);
+    try stdout.writeAll("\n\n");
+    try bibval.bibtex.writeEntry(stdout, &best);
+    return 0;
+}
+
+/// Serve validation over HTTP until the process is stopped. All requests
+/// share one HTTP client, response cache and set of validators, so upstream
+/// rate limits and cached responses apply across clients.
+fn runServe(allocator: std.mem.Allocator, args: *const Args, stdout: *std.Io.Writer) !u8 {
+    var config = loadConfig(allocator, args) orelse return 1;
+    defer config.deinit();
+
+    var response_cache = try bibval.cache.Cache.init(allocator, !args.no_cache);
+    defer response_cache.deinit();
+
+    // Exposed at GET /metrics
+    var metrics: bibval.metrics.Metrics = .{};
+    var stats = bibval.serve.Stats.init(allocator);
+    defer stats.deinit();
+    var tracer = Tracer{
+        .level = @enumFromInt(@min(args.verbosity, 2)),
+        .json = args.log_json,
+        .metrics = &metrics,
+    };
+
+    var client = bibval.http.Client.init(allocator, bibval.validators.USER_AGENT);
+    defer client.deinit();
+    client.tracer = &tracer;
+    client.metrics = &metrics;
+    client.min_interval_ms = args.request_interval_ms;
+
+    var sources = args.sources(allocator, &client, &response_cache, &config);
+    // Clients would have the server fetch any URL they cite
+    if (!args.serve_web) sources.web = null;
+    var venue_resolver = bibval.canonical.Resolver.init(allocator, &client, &response_cache);
+    defer venue_resolver.deinit();
+    try args.canonicalVenues(&config, &venue_resolver);
+
+    var limiter = bibval.serve.RateLimiter.init(allocator, args.rate_limit);
+    defer limiter.deinit();
+    var notifier = bibval.notify.Notifier.init(allocator, config.webhooks);
+    defer notifier.deinit();
+
+    const address = std.net.Address.parseIp(args.listen, args.port) catch {
+        std.debug.print("Error: Invalid listen address: {s}\n", .{args.listen});
+        return 1;
+    };
+    var listener = address.listen(.{ .reuse_address = true }) catch |err| {
+        std.debug.print("Error: Failed to listen on {s}:{d}: {s}\n", .{ args.listen, args.port, @errorName(err) });
+        return 1;
+    };
+    defer listener.deinit();
+
+    var server = Server{
+        .allocator = allocator,
+        .args = args,
+        .config = &config,
+        .tracer = &tracer,
+        .limiter = &limiter,
+        .sources = &sources,
+        .client = &client,
+        .notifier = &notifier,
+        .response_cache = &response_cache,
+        .metrics = &metrics,
+        .stats = &stats,
+    };
+
+    try stdout.print("Listening on http://{s}:{d}\n", .{ args.listen, args.port });
+    try stdout.flush();
+
+    // One request at a time: the validators and the client's rate limit
+    // are not shared between threads
+    while (true) {
+        const connection = listener.accept() catch |err| {
+            tracer.event(.info, "serve", "accept failed: {s}", .{@errorName(err)});
+            continue;
+        };
+        defer connection.stream.close();
+        // Otherwise a client that stops sending holds the only worker
+        const timeout = std.posix.timeval{ .sec = bibval.serve.READ_TIMEOUT_S, .usec = 0 };
+        std.posix.setsockopt(connection.stream.handle, std.posix.SOL.SOCKET, std.posix.SO.RCVTIMEO, std.mem.asBytes(&timeout)) catch |err| {
+            tracer.event(.info, "serve", "setting read timeout failed: {s}", .{@errorName(err)});
+            continue;
+        };
+        server.handleConnection(connection) catch |err| {
+            tracer.event(.info, "serve", "request failed: {s}", .{@errorName(err)});
+        };
+    }
+}
+
+/// State shared by all requests of `bibval serve`.
+const Server = struct {
+    allocator: std.mem.Allocator,
+    args: *const Args,
+    config: *const bibval.config.Config,
+    tracer: *Tracer,
+    limiter: *bibval.serve.RateLimiter,
+    sources: *bibval.pipeline.Sources,
+    client: *bibval.http.Client,
+    notifier: *bibval.notify.Notifier,
+    response_cache: *const bibval.cache.Cache,
+    metrics: *const bibval.metrics.Metrics,
+    stats: *bibval.serve.Stats,
+
+    const Response = struct {
+        status: std.http.Status = .ok,
+        content_type: []const u8 = "application/json",
+        /// Seconds, for rate-limited requests.
+        retry_after: ?u64 = null,
+    };
+
+    /// Answer a single request and close the connection, so that a slow
+    /// client cannot hold the server.
+    fn handleConnection(self: *Server, connection: std.net.Server.Connection) !void {
+        var recv_buf: [8192]u8 = undefined;
+        var send_buf: [8192]u8 = undefined;
+        var conn_reader = connection.stream.reader(&recv_buf);
+        var conn_writer = connection.stream.writer(&send_buf);
+        var http_server = std.http.Server.init(conn_reader.interface(), &conn_writer.interface);
+
+        var request = http_server.receiveHead() catch |err| switch (err) {
+            error.HttpConnectionClosing => return,
+            else => return err,
+        };
+        const client = bibval.serve.clientId(connection.address);
+        const method = request.head.method;
+        const target = bibval.serve.Target.parse(request.head.target);
+        // Reading the body reuses the buffer holding the head
+        var path_buf: [256]u8 = undefined;
+        const path = path_buf[0..@min(target.path.len, path_buf.len)];
+        @memcpy(path, target.path[0..path.len]);
+
+        var body: std.Io.Writer.Allocating = .init(self.allocator);
+        defer body.deinit();
+
+        const response: Response = if (std.mem.eql(u8, path, "/validate")) blk: {
+            if (method != .POST) break :blk try writeError(&body.writer, .method_not_allowed, "Use POST /validate", .{});
+            break :blk try self.validate(&request, target, client, &body.writer);
+        } else if (std.mem.eql(u8, path, "/resolve")) blk: {
+            if (method != .GET) break :blk try writeError(&body.writer, .method_not_allowed, "Use GET /resolve?doi=DOI", .{});
+            break :blk try self.resolve(target, client, &body.writer);
+        } else if (std.mem.eql(u8, path, "/metrics")) blk: {
+            if (method != .GET) break :blk try writeError(&body.writer, .method_not_allowed, "Use GET /metrics", .{});
+            break :blk try self.writeMetrics(&body.writer);
+        } else try writeError(&body.writer, .not_found, "Unknown endpoint {s}; use POST /validate, GET /resolve?doi=DOI or GET /metrics", .{path});
+
+        self.tracer.event(.info, "serve", "{s} {s} {d}", .{ @tagName(method), path, @intFromEnum(response.status) });
+        try self.stats.record(bibval.serve.Endpoint.fromPath(path), response.status);
+
+        var retry_buf: [20]u8 = undefined;
+        var headers_buf = [2]std.http.Header{
+            .{ .name = "content-type", .value = response.content_type },
+            undefined,
+        };
+        var headers: []const std.http.Header = headers_buf[0..1];
+        if (response.retry_after) |secs| {
+            headers_buf[1] = .{ .name = "retry-after", .value = std.fmt.bufPrint(&retry_buf, "{d}", .{secs}) catch unreachable };
+            headers = headers_buf[0..2];
+        }
+        try request.respond(body.written(), .{ .status = response.status, .keep_alive = false, .extra_headers = headers });
+    }
+
+    /// POST /validate: validate the BibTeX, CSL-JSON or RIS body and write
+    /// the same JSON report as `bibval check --json`. With `?name=`, new
+    /// errors since the last report of that name are sent to the webhooks.
+    fn validate(self: *Server, request: *std.http.Server.Request, target: bibval.serve.Target, client: u64, out: *std.Io.Writer) !Response {
+        // Before reading the body, which reuses the buffer holding the head
+        const declared = bibval.serve.formatFromContentType(request.head.content_type);
+        const name = try target.param(self.allocator, "name");
+        defer if (name) |n| self.allocator.free(n);
+        var transfer_buf: [4096]u8 = undefined;
+        const reader = try request.readerExpectContinue(&transfer_buf);
+        const raw = reader.allocRemaining(self.allocator, .limited(bibval.input.MAX_INPUT_SIZE)) catch |err| switch (err) {
+            error.StreamTooLong => return writeError(out, .payload_too_large, "Body is larger than {d} bytes", .{bibval.input.MAX_INPUT_SIZE}),
+            else => return err,
+        };
+        defer self.allocator.free(raw);
+
+        var decoded = try bibval.input.decode(self.allocator, raw, null);
+        defer decoded.deinit();
+        const format = declared orelse bibval.input.detect(decoded.text);
+        const entries = bibval.input.parse(self.allocator, decoded.text, format, null) catch |err| {
+            return writeError(out, .bad_request, "Could not parse body as {s}: {s}", .{ format.name(), @errorName(err) });
+        };
+        defer {
+            for (entries) |*e| e.deinit();
+            self.allocator.free(entries);
+        }
+
+        if (try self.limit(client, entries.len, out)) |refused| return refused;
+        self.stats.entries += entries.len;
+
+        var report = Report.init(self.allocator);
+        defer report.deinit();
+        for (entries) |*local_entry| {
+            self.tracer.entry_key = local_entry.key;
+            defer self.tracer.entry_key = null;
+            defer if (self.tracer.metrics) |m| m.endEntry();
+            try report.add(try bibval.pipeline.validateEntry(self.allocator, local_entry, self.sources, self.tracer, self.args.disabled, self.args.min_confidence, self.args.check_funders, self.args.access != .none, &self.config.screening, &self.config.matching));
+        }
+        report.sort(self.args.sort);
+
+        var policy = try bibval.policy.evaluate(self.allocator, &report, self.config.policy, &self.config.screening, bibval.ignores.Date.today(), self.args.disabled);
+        defer policy.deinit();
+        var duplicates = try bibval.duplicates.check(self.allocator, entries, &self.config.matching, self.args.disabled);
+        defer duplicates.deinit();
+        var consistency = try bibval.consistency.check(self.allocator, entries, self.config.venue_style, self.config.engine, self.args.disabled);
+        defer consistency.deinit();
+
+        try bibval.pipeline.writeJsonReport(self.allocator, out, &report, &policy, &duplicates, &consistency, null);
+
+        if (name != null and self.notifier.sinks.len > 0) {
+            const fresh = try self.notifier.newErrors(client, name.?, &report);
+            defer self.allocator.free(fresh);
+            try self.notifier.send(self.client, self.tracer, name.?, fresh);
+        }
+        return .{};
+    }
+
+    /// GET /resolve?doi=DOI: the record for a DOI, from CrossRef or OpenAlex.
+    fn resolve(self: *Server, target: bibval.serve.Target, client: u64, out: *std.Io.Writer) !Response {
+        const doi = (try target.param(self.allocator, "doi")) orelse {
+            return writeError(out, .bad_request, "Missing doi parameter", .{});
+        };
+        defer self.allocator.free(doi);
+
+        if (try self.limit(client, 1, out)) |refused| return refused;
+        self.stats.entries += 1;
+
+        var record = bibval.pipeline.resolveDoi(doi, self.sources) orelse {
+            return writeError(out, .not_found, "No record found for DOI {s}", .{doi});
+        };
+        defer record.deinit();
+
+        try out.writeAll("{\"doi\":");
+        try writeJsonString(out, doi);
+        try out.writeAll(",\"entry\":");
+        try bibval.snapshot.writeEntry(out, &record);
+        try out.writeAll("}\n");
+        return .{};
+    }
+
+    /// GET /metrics: request counts, the cache hit rate, upstream latencies
+    /// and rate-limit waits, for Prometheus. Not rate limited.
+    fn writeMetrics(self: *Server, out: *std.Io.Writer) !Response {
+        try bibval.serve.writeMetrics(out, self.stats, .{
+            .backends = self.metrics,
+            .cache_hits = self.response_cache.hits,
+            .cache_misses = self.response_cache.misses,
+            .waited_ms = self.client.waited_ms,
+        });
+        return .{ .content_type = "text/plain; version=0.0.4" };
+    }
+
+    /// Charge `cost` lookups to the client, or write why they were refused.
+    fn limit(self: *Server, client: u64, cost: usize, out: *std.Io.Writer) !?Response {
+        switch (try self.limiter.take(client, cost, std.time.milliTimestamp())) {
+            .allow => return null,
+            .wait => {
+                const secs = self.limiter.retryAfter(client, cost);
+                var response = try writeError(out, .too_many_requests, "Rate limit of {d} entries per minute exceeded; retry in {d}s", .{ self.limiter.per_minute, secs });
+                response.retry_after = secs;
+                return response;
+            },
+            .too_large => return try writeError(out, .payload_too_large, "{d} entries exceed the limit of {d} per minute", .{ cost, self.limiter.per_minute }),
+        }
+    }
+
+    fn writeError(out: *std.Io.Writer, status: std.http.Status, comptime fmt: []const u8, args: anytype) !Response {
+        var buf: [512]u8 = undefined;
+        // Messages quote request input; cut a long one short, marked "..."
+        var w: std.Io.Writer = .fixed(buf[0 .. buf.len - 3]);
+        const len = if (w.print(fmt, args)) |_| w.end else |_| blk: {
+            @memcpy(buf[w.end..][0..3], "...");
+            break :blk w.end + 3;
+        };
+        try out.writeAll("{\"error\":");
+        try writeJsonString(out, buf[0..len]);
+        try out.writeAll("}\n");
+        return .{ .status = status };
+    }
+};
+
+fn higherConfidence(_: void, a: *const ValidationResult, b: *const ValidationResult) bool {
+    return a.confidence > b.confidence;
+}
+
+/// The matched records merged field by field, with a generated key.
+fn bestRecord(allocator: std.mem.Allocator, results: []const ValidationResult) !?Entry {
+    var best = (try bibval.entry.merge(allocator, results)) orelse return null;
+    errdefer best.deinit();
+
+    if (best.arxiv_id == null) {
+        for (results) |result| {
+            const matched = result.matched_entry orelse continue;
+            const id = matched.arxiv_id orelse continue;
+            best.arxiv_id = try allocator.dupe(u8, id);
+            break;
+        }
+    }
+
+    const key = try bibval.entry.generateKey(allocator, &best, &.{});
+    if (best.key.len > 0) allocator.free(best.key);
+    best.key = key;
+    return best;
+}
+
+fn printDuration(writer: *std.Io.Writer, ms: u64) !void {
+    const secs = (ms + 999) / 1000;
+    if (secs >= 86400) {
+        try writer.print("{d}d {d}h", .{ secs / 86400, (secs % 86400) / 3600 });
+    } else if (secs >= 3600) {
+        try writer.print("{d}h {d}m", .{ secs / 3600, (secs % 3600) / 60 });
+    } else if (secs >= 60) {
+        try writer.print("{d}m {d}s", .{ secs / 60, secs % 60 });
+    } else {
+        try writer.print("{d}s", .{secs});
+    }
+}
+
+/// Replay the snapshot for every entry on the thread pool.
+fn replayAll(
+    allocator: std.mem.Allocator,
+    entries: []const Entry,
+    snap: *const bibval.snapshot.Snapshot,
+    targets: []const bibval.workspace.Target,
+    runs: []const TargetRun,
+    jobs: usize,
+    screening: *const bibval.screening.Screening,
+) ![]?EntryReport {
+    const reports = try allocator.alloc(?EntryReport, entries.len);
+    @memset(reports, null);
+    errdefer {
+        for (reports) |*r| if (r.*) |*entry_report| entry_report.deinit();
+        allocator.free(reports);
+    }
+
+    const Pass = struct {
+        allocator: std.mem.Allocator,
+        entries: []const Entry,
+        snap: *const bibval.snapshot.Snapshot,
+        targets: []const bibval.workspace.Target,
+        runs: []const TargetRun,
+        screening: *const bibval.screening.Screening,
+        reports: []?EntryReport,
+        mutex: std.Thread.Mutex = .{},
+        failure: ?anyerror = null,
+
+        fn run(self: *@This(), i: usize) void {
+            const t = bibval.workspace.forFile(self.targets, self.entries[i].source_file);
+            const allowed = if (t) |index| self.targets[index].validators else null;
+            const target_run = &self.runs[t orelse self.targets.len];
+            self.reports[i] = replaySnapshot(self.allocator, &self.entries[i], self.snap, allowed, target_run.disabled, target_run.min_confidence, self.screening) catch |err| {
+                self.mutex.lock();
+                defer self.mutex.unlock();
+                self.failure = err;
+                return;
+            };
+        }
+    };
+
+    var pass = Pass{
+        .allocator = allocator,
+        .entries = entries,
+        .snap = snap,
+        .targets = targets,
+        .runs = runs,
+        .screening = screening,
+        .reports = reports,
+    };
+    try bibval.parallel.forEach(allocator, entries.len, if (jobs > 0) jobs else bibval.parallel.defaultJobs(), &pass, Pass.run);
+    if (pass.failure) |err| return err;
+    return reports;
+}
+
+/// Build an entry report from the records of a committed snapshot.
+fn replaySnapshot(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    snap: *const bibval.snapshot.Snapshot,
+    /// Backends of the entry's target; null keeps every record.
+    allowed: ?std.EnumSet(ApiSource),
+    disabled: bibval.rules.RuleSet,
+    min_confidence: f64,
+    screening: *const bibval.screening.Screening,
+) !EntryReport {
+    const records = snap.get(local_entry.key) orelse {
+        return EntryReport{
+            .entry = try local_entry.clone(allocator),
+            .status = .{ .failed = try allocator.dupe(u8, "Missing from snapshot") },
+            .validation_results = &.{},
+            .allocator = allocator,
+        };
+    };
+
+    var validation_results: std.ArrayList(ValidationResult) = .empty;
+    defer validation_results.deinit(allocator);
+
+    for (records) |*record| {
+        if (allowed) |a| {
+            if (!a.contains(record.source)) continue;
+        }
+        const discrepancies = try bibval.matcher.compareEntries(allocator, local_entry, &record.entry);
+        try validation_results.append(allocator, .{
+            .source = record.source,
+            .matched_entry = try record.entry.clone(allocator),
+            .confidence = record.confidence,
+            .discrepancies = discrepancies,
+            .allocator = allocator,
+        });
+    }
+
+    return bibval.pipeline.finishReport(allocator, local_entry, &validation_results, disabled, min_confidence, screening);
+}
+
+/// Print suggested fixes for every input file as a unified diff.
+fn printPatch(allocator: std.mem.Allocator, files: []const []const u8, entries: []const Entry, report: *const Report, options: bibval.fix.Options, policy: bibval.policy.Policy, targets: []const bibval.workspace.Target, disabled: bibval.rules.RuleSet, stdout: *std.Io.Writer) !void {
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const arena_allocator = arena.allocator();
+
+    var fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+    for (report.entries.items) |*entry_report| {
+        try bibval.fix.suggest(arena_allocator, entry_report, options, &fixes);
+    }
+    if (!disabled.contains(.venue_variant)) {
+        // A venue fixed from the matched record already has its value
+        var venue_fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+        try bibval.consistency.venueFixes(arena_allocator, entries, options.venue_style, &venue_fixes);
+        dropDisabledFixes(&venue_fixes, targets, entries, .venue_variant);
+        try appendUnfixed(arena_allocator, &fixes, venue_fixes.items);
+    }
+    if (!disabled.contains(.numeric_field)) {
+        var numeric_fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+        try bibval.consistency.numericFixes(arena_allocator, entries, &numeric_fixes);
+        dropDisabledFixes(&numeric_fixes, targets, entries, .numeric_field);
+        try appendUnfixed(arena_allocator, &fixes, numeric_fixes.items);
+    }
+    if (!disabled.contains(.urldate)) {
+        var urldate_fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+        try bibval.policy.urldateFixes(arena_allocator, entries, policy, bibval.ignores.Date.today(), &urldate_fixes);
+        dropDisabledFixes(&urldate_fixes, targets, entries, .urldate);
+        try fixes.appendSlice(arena_allocator, urldate_fixes.items);
+    }
+    if (!disabled.contains(.punctuation)) {
+        var punctuation_fixes: std.ArrayList(bibval.fix.Fix) = .empty;
+        try bibval.punctuation.fixes(arena_allocator, entries, options.engine, &punctuation_fixes);
+        dropDisabledFixes(&punctuation_fixes, targets, entries, .punctuation);
+        try appendUnfixed(arena_allocator, &fixes, punctuation_fixes.items);
+        for (fixes.items) |*f| {
+            if (bibval.workspace.isDisabled(targets, entries, .initEmpty(), .punctuation, f.key)) continue;
+            try bibval.punctuation.convertFixes(arena_allocator, f[0..1], options.engine);
+        }
+    }
+    if (fixes.items.len == 0 and !options.string_macros) return;
+
+    for (files) |file_path| {
+        // Only BibTeX sources can be patched
+        if (isStdin(file_path) or isPdf(file_path) or !isBibtex(file_path)) continue;
+        const file = try std.fs.cwd().openFile(file_path, .{});
+        defer file.close();
+        const content = try file.readToEndAlloc(arena_allocator, 10 * 1024 * 1024);
+        const edits = if (options.string_macros)
+            try bibval.string_macros.editsFor(arena_allocator, content, fixes.items)
+        else
+            try bibval.fix.editsFor(arena_allocator, content, fixes.items);
+        try bibval.fix.writePatch(arena_allocator, stdout, file_path, content, edits);
+    }
+}
+
+/// Drop the fixes of entries whose target disables `rule`.
+fn dropDisabledFixes(fixes: *std.ArrayList(bibval.fix.Fix), targets: []const bibval.workspace.Target, entries: []const Entry, rule: bibval.rules.Rule) void {
+    var kept: usize = 0;
+    for (fixes.items) |f| {
+        if (bibval.workspace.isDisabled(targets, entries, .initEmpty(), rule, f.key)) continue;
+        fixes.items[kept] = f;
+        kept += 1;
+    }
+    fixes.shrinkRetainingCapacity(kept);
+}
+
+/// Append the fixes of `more` for fields `fixes` does not already set.
+fn appendUnfixed(allocator: std.mem.Allocator, fixes: *std.ArrayList(bibval.fix.Fix), more: []const bibval.fix.Fix) !void {
+    for (more) |extra| {
+        const fixed = for (fixes.items) |f| {
+            if (std.mem.eql(u8, f.key, extra.key) and std.mem.eql(u8, f.field, extra.field)) break true;
+        } else false;
+        if (!fixed) try fixes.append(allocator, extra);
+    }
+}
+
+fn parseArgs(allocator: std.mem.Allocator) !Args {
     var args = Args.init(allocator);
     errdefer args.deinit(allocator);
 
@@ -464,6 +1962,7 @@ fn parseArgs(allocator: std.mem.Allocator) !Args {
 
     _ = arg_iter.next(); // Skip program name
 
+    var saw_positional = false;
     while (arg_iter.next()) |arg| {
         if (std.mem.eql(u8, arg, "--help") or std.mem.eql(u8, arg, "-h")) {
             args.help = true;
@@ -477,15 +1976,79 @@ fn parseArgs(allocator: std.mem.Allocator) !Args {
             args.no_semantic = true;
         } else if (std.mem.eql(u8, arg, "--no-openalex")) {
             args.no_openalex = true;
+        } else if (std.mem.eql(u8, arg, "--no-open-library")) {
+            args.no_open_library = true;
+        } else if (std.mem.eql(u8, arg, "--no-google-books")) {
+            args.no_google_books = true;
+        } else if (std.mem.eql(u8, arg, "--no-arxiv")) {
+            args.no_arxiv = true;
+        } else if (std.mem.eql(u8, arg, "--no-pmlr")) {
+            args.no_pmlr = true;
+        } else if (std.mem.eql(u8, arg, "--no-neurips")) {
+            args.no_neurips = true;
+        } else if (std.mem.eql(u8, arg, "--no-ieee")) {
+            args.no_ieee = true;
+        } else if (std.mem.eql(u8, arg, "--no-acm")) {
+            args.no_acm = true;
+        } else if (std.mem.eql(u8, arg, "--no-springer")) {
+            args.no_springer = true;
+        } else if (std.mem.eql(u8, arg, "--no-scopus")) {
+            args.no_scopus = true;
+        } else if (std.mem.eql(u8, arg, "--no-github")) {
+            args.no_github = true;
+        } else if (std.mem.eql(u8, arg, "--no-zenodo")) {
+            args.no_zenodo = true;
+        } else if (std.mem.eql(u8, arg, "--no-figshare")) {
+            args.no_figshare = true;
+        } else if (std.mem.eql(u8, arg, "--no-web")) {
+            args.no_web = true;
+        } else if (std.mem.eql(u8, arg, "--no-external")) {
+            args.no_external = true;
+        } else if (std.mem.eql(u8, arg, "--canonical-venues")) {
+            args.canonical_venues = true;
         } else if (std.mem.eql(u8, arg, "--no-cache")) {
             args.no_cache = true;
         } else if (std.mem.eql(u8, arg, "--strict") or std.mem.eql(u8, arg, "-s")) {
             args.strict = true;
+        } else if (std.mem.eql(u8, arg, "--max-errors")) {
+            const value = arg_iter.next() orelse "";
+            args.gates.max_errors = std.fmt.parseInt(usize, value, 10) catch {
+                std.debug.print("Invalid value for --max-errors: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--max-unmatched-percent")) {
+            args.gates.max_unmatched_percent = parsePercent(arg, arg_iter.next() orelse "");
+        } else if (std.mem.eql(u8, arg, "--require-doi-coverage")) {
+            args.gates.require_doi_coverage = parsePercent(arg, arg_iter.next() orelse "");
         } else if (std.mem.eql(u8, arg, "--verbose") or std.mem.eql(u8, arg, "-v")) {
-            args.verbose = true;
+            args.verbosity +|= 1;
+        } else if (std.mem.eql(u8, arg, "-vv")) {
+            args.verbosity +|= 2;
+        } else if (std.mem.eql(u8, arg, "--log-json")) {
+            args.log_json = true;
         } else if (std.mem.eql(u8, arg, "--json")) {
-            args.json = true;
-        } else if (std.mem.eql(u8, arg, "-k") or std.mem.eql(u8, arg, "--key")) {
+            args.format = .json;
+        } else if (std.mem.eql(u8, arg, "--format")) {
+            const value = arg_iter.next() orelse "";
+            args.format = std.meta.stringToEnum(Format, value) orelse {
+                std.debug.print("Invalid value for --format: {s} (expected text, json or patch)\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--sort")) {
+            const value = arg_iter.next() orelse "";
+            args.sort = std.meta.stringToEnum(bibval.report.Order, value) orelse {
+                std.debug.print("Invalid value for --sort: {s} (expected file or severity)\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--string-macros")) {
+            args.string_macros = true;
+        } else if (std.mem.eql(u8, arg, "--quiet") or std.mem.eql(u8, arg, "-q")) {
+            args.quiet = true;
+        } else if (std.mem.eql(u8, arg, "--no-color")) {
+            args.no_color = true;
+        } else if (std.mem.eql(u8, arg, "--show-matched")) {
+            args.show_matched = true;
+        } else if (std.mem.eql(u8, arg, "-k") or std.mem.eql(u8, arg, "--key") or std.mem.eql(u8, arg, "--only")) {
             if (arg_iter.next()) |key| {
                 // Handle comma-separated keys
                 var iter = std.mem.splitScalar(u8, key, ',');
@@ -493,7 +2056,182 @@ fn parseArgs(allocator: std.mem.Allocator) !Args {
                     try args.keys_list.append(allocator, k);
                 }
             }
+        } else if (std.mem.eql(u8, arg, "--key-glob")) {
+            if (arg_iter.next()) |patterns| {
+                var iter = std.mem.splitScalar(u8, patterns, ',');
+                while (iter.next()) |p| {
+                    try args.key_globs_list.append(allocator, p);
+                }
+            }
+        } else if (std.mem.eql(u8, arg, "--type")) {
+            if (arg_iter.next()) |types| {
+                var iter = std.mem.splitScalar(u8, types, ',');
+                while (iter.next()) |t| {
+                    try args.types_list.append(allocator, std.mem.trim(u8, t, " @"));
+                }
+            }
+        } else if (std.mem.eql(u8, arg, "--modified-since")) {
+            if (arg_iter.next()) |rev| {
+                args.modified_since = rev;
+            }
+        } else if (std.mem.eql(u8, arg, "--author")) {
+            if (arg_iter.next()) |names| {
+                // Accept BibTeX-style "A and B" lists
+                var iter = std.mem.splitSequence(u8, names, " and ");
+                while (iter.next()) |name| {
+                    try args.authors_list.append(allocator, std.mem.trim(u8, name, " "));
+                }
+            }
+        } else if (std.mem.eql(u8, arg, "--group")) {
+            if (arg_iter.next()) |group| {
+                try args.groups_list.append(allocator, group);
+            }
+        } else if (std.mem.eql(u8, arg, "--grobid-url")) {
+            if (arg_iter.next()) |url| {
+                args.grobid_url = url;
+            }
+        } else if (std.mem.eql(u8, arg, "--config")) {
+            if (arg_iter.next()) |path| {
+                args.config_path = path;
+            }
+        } else if (std.mem.eql(u8, arg, "--profile")) {
+            if (arg_iter.next()) |name| {
+                args.profile = name;
+            }
+        } else if (std.mem.eql(u8, arg, "--check-funders")) {
+            args.check_funders = true;
+        } else if (std.mem.eql(u8, arg, "--suggest-archives")) {
+            args.suggest_archives = true;
+        } else if (std.mem.eql(u8, arg, "--archive-urls")) {
+            args.archive_urls = true;
+        } else if (std.mem.eql(u8, arg, "--open-access")) {
+            args.access = .all;
+        } else if (std.mem.eql(u8, arg, "--closed-access")) {
+            args.access = .closed;
+        } else if (std.mem.eql(u8, arg, "--resume")) {
+            args.resume_run = true;
+        } else if (std.mem.eql(u8, arg, "--journal")) {
+            if (arg_iter.next()) |path| {
+                args.journal_path = path;
+            }
+        } else if (std.mem.eql(u8, arg, "--out") or std.mem.eql(u8, arg, "-o")) {
+            if (arg_iter.next()) |path| {
+                args.snapshot_out = path;
+            }
+        } else if (std.mem.eql(u8, arg, "--snapshot")) {
+            if (arg_iter.next()) |path| {
+                args.snapshot_in = path;
+            }
+        } else if (std.mem.eql(u8, arg, "--history")) {
+            if (arg_iter.next()) |path| {
+                args.history_path = path;
+            }
+        } else if (std.mem.eql(u8, arg, "--disable")) {
+            if (arg_iter.next()) |list| {
+                var iter = std.mem.splitScalar(u8, list, ',');
+                while (iter.next()) |name| {
+                    const rule = bibval.rules.Rule.parse(std.mem.trim(u8, name, " ")) orelse {
+                        std.debug.print("Unknown rule: {s} (see `bibval rules`)\n", .{name});
+                        std.process.exit(1);
+                    };
+                    args.disabled.insert(rule);
+                }
+            }
+        } else if (std.mem.eql(u8, arg, "--min-confidence")) {
+            const value = arg_iter.next() orelse "";
+            args.min_confidence = std.fmt.parseFloat(f64, value) catch {
+                std.debug.print("Invalid value for --min-confidence: {s}\n", .{value});
+                std.process.exit(1);
+            };
+            if (args.min_confidence < 0 or args.min_confidence > 1) {
+                std.debug.print("--min-confidence must be between 0 and 1\n", .{});
+                std.process.exit(1);
+            }
+        } else if (std.mem.eql(u8, arg, "--jobs") or std.mem.eql(u8, arg, "-j")) {
+            const value = arg_iter.next() orelse "";
+            args.jobs = std.fmt.parseInt(usize, value, 10) catch {
+                std.debug.print("Invalid value for --jobs: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--deadline")) {
+            const value = arg_iter.next() orelse "";
+            args.deadline_ms = parseDuration(value) orelse {
+                std.debug.print("Invalid value for --deadline: {s} (use e.g. 90s, 5m, or 1h)\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--request-interval")) {
+            const value = arg_iter.next() orelse "";
+            args.request_interval_ms = std.fmt.parseInt(u64, value, 10) catch {
+                std.debug.print("Invalid value for --request-interval: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--title")) {
+            if (arg_iter.next()) |title| {
+                args.title = title;
+            }
+        } else if (std.mem.eql(u8, arg, "--year")) {
+            const value = arg_iter.next() orelse "";
+            args.year = std.fmt.parseInt(i32, value, 10) catch {
+                std.debug.print("Invalid value for --year: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--doi")) {
+            if (arg_iter.next()) |doi| {
+                args.doi = doi;
+            }
+        } else if (std.mem.eql(u8, arg, "--listen")) {
+            if (arg_iter.next()) |address| {
+                args.listen = address;
+            }
+        } else if (std.mem.eql(u8, arg, "--port")) {
+            const value = arg_iter.next() orelse "";
+            args.port = std.fmt.parseInt(u16, value, 10) catch {
+                std.debug.print("Invalid value for --port: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--web")) {
+            args.serve_web = true;
+        } else if (std.mem.eql(u8, arg, "--rate-limit")) {
+            const value = arg_iter.next() orelse "";
+            args.rate_limit = std.fmt.parseInt(u32, value, 10) catch {
+                std.debug.print("Invalid value for --rate-limit: {s}\n", .{value});
+                std.process.exit(1);
+            };
+        } else if (std.mem.eql(u8, arg, "--zotero")) {
+            args.zotero = true;
+        } else if (std.mem.eql(u8, arg, "--zotero-url")) {
+            if (arg_iter.next()) |url| {
+                args.zotero_url = url;
+            }
+        } else if (std.mem.eql(u8, arg, "--zotero-write")) {
+            args.zotero_write = true;
+        } else if (std.mem.eql(u8, arg, "--clipboard")) {
+            args.clipboard = true;
+        } else if (isStdin(arg)) {
+            saw_positional = true;
+            try args.files_list.append(allocator, arg);
         } else if (arg.len > 0 and arg[0] != '-') {
+            // The first positional argument may name a subcommand
+            if (!saw_positional) {
+                saw_positional = true;
+                if (std.meta.stringToEnum(Command, arg)) |command| {
+                    args.command = command;
+                    continue;
+                }
+            }
+            if (bibval.glob.hasWildcards(arg)) {
+                var matches: std.ArrayList([]const u8) = .empty;
+                bibval.glob.expand(args.paths.allocator(), std.fs.cwd(), arg, &matches) catch |err| {
+                    std.debug.print("Error: Failed to expand {s}: {s}\n", .{ arg, @errorName(err) });
+                    std.process.exit(1);
+                };
+                if (matches.items.len == 0) {
+                    std.debug.print("Error: No files match {s}\n", .{arg});
+                    std.process.exit(1);
+                }
+                try args.files_list.appendSlice(allocator, matches.items);
+                continue;
+            }
             try args.files_list.append(allocator, arg);
         } else if (std.mem.startsWith(u8, arg, "--")) {
             std.debug.print("Unknown option: {s}\n", .{arg});
@@ -505,30 +2243,73 @@ fn parseArgs(allocator: std.mem.Allocator) !Args {
     return args;
 }
 
+/// Whether NO_COLOR is set to a non-empty value.
+fn noColorEnv(allocator: std.mem.Allocator) bool {
+    const value = std.process.getEnvVarOwned(allocator, "NO_COLOR") catch return false;
+    defer allocator.free(value);
+    return value.len > 0;
+}
+
+/// Columns of the terminal on stdout: COLUMNS if set, else the terminal's
+/// size, else 80.
+fn terminalWidth(allocator: std.mem.Allocator) usize {
+    if (std.process.getEnvVarOwned(allocator, "COLUMNS")) |columns| {
+        defer allocator.free(columns);
+        const n = std.fmt.parseInt(usize, columns, 10) catch 0;
+        if (n > 0) return n;
+    } else |_| {}
+    if (builtin.os.tag != .windows) {
+        var size: std.posix.winsize = undefined;
+        const rc = std.posix.system.ioctl(std.fs.File.stdout().handle, std.posix.T.IOCGWINSZ, @intFromPtr(&size));
+        if (std.posix.errno(rc) == .SUCCESS and size.col > 0) return size.col;
+    }
+    return 80;
+}
+
+/// Milliseconds in a duration such as "90s", "5m", or "1h"; plain numbers
+/// are seconds.
+fn parseDuration(value: []const u8) ?u64 {
+    if (value.len == 0) return null;
+    const unit: u64 = switch (value[value.len - 1]) {
+        's' => std.time.ms_per_s,
+        'm' => std.time.ms_per_min,
+        'h' => std.time.ms_per_hour,
+        '0'...'9' => return std.math.mul(u64, std.fmt.parseInt(u64, value, 10) catch return null, std.time.ms_per_s) catch null,
+        else => return null,
+    };
+    const n = std.fmt.parseInt(u64, value[0 .. value.len - 1], 10) catch return null;
+    return std.math.mul(u64, n, unit) catch null;
+}
+
+/// A percentage argument of `flag` between 0 and 100.
+fn parsePercent(flag: []const u8, value: []const u8) u32 {
+    const percent = std.fmt.parseInt(u32, std.mem.trimRight(u8, value, "%"), 10) catch {
+        std.debug.print("Invalid value for {s}: {s}\n", .{ flag, value });
+        std.process.exit(1);
+    };
+    if (percent > 100) {
+        std.debug.print("{s} must be between 0 and 100\n", .{flag});
+        std.process.exit(1);
+    }
+    return percent;
+}
+
 fn printUsage() void {
-    std.debug.print(
-        \\bibval - Citation validator for BibTeX files
-        \\
-        \\Usage:
-        \\  bibval [options] <file.bib> [file2.bib ...]
-        \\
-        \\Options:
-        \\  -h, --help        Show this help
-        \\  -V, --version     Show version
-        \\  -s, --strict      Exit with error if any issues found
-        \\  -v, --verbose     Verbose output
-        \\  --json            Output JSON format
-        \\  -k, --key KEY     Only validate entries with these keys (comma-separated)
-        \\  --no-crossref     Disable CrossRef API
-        \\  --no-dblp         Disable DBLP API
-        \\  --no-semantic     Disable Semantic Scholar API
-        \\  --no-openalex     Disable OpenAlex API
-        \\  --no-cache        Disable response caching
-        \\
-        \\Example:
-        \\  bibval references.bib
-        \\  bibval paper.bib thesis.bib --strict
-        \\  bibval refs.bib -k smith2021,jones2022 --json
-        \\
-    , .{});
+    var buffer: [4096]u8 = undefined;
+    const stderr = std.debug.lockStderrWriter(&buffer);
+    defer std.debug.unlockStderrWriter();
+    bibval.cli.writeHelp(stderr) catch {};
+}
+
+test "parseDuration" {
+    try std.testing.expectEqual(@as(?u64, 90 * std.time.ms_per_s), parseDuration("90s"));
+    try std.testing.expectEqual(@as(?u64, 5 * std.time.ms_per_min), parseDuration("5m"));
+    try std.testing.expectEqual(@as(?u64, std.time.ms_per_hour), parseDuration("1h"));
+    // Bare numbers are seconds
+    try std.testing.expectEqual(@as(?u64, 30 * std.time.ms_per_s), parseDuration("30"));
+    try std.testing.expectEqual(@as(?u64, null), parseDuration(""));
+    try std.testing.expectEqual(@as(?u64, null), parseDuration("m"));
+    try std.testing.expectEqual(@as(?u64, null), parseDuration("5d"));
+    try std.testing.expectEqual(@as(?u64, null), parseDuration("-5s"));
+    try std.testing.expectEqual(@as(?u64, null), parseDuration("99999999999999999999h"));
 }
@@ -0,0 +1,173 @@
+//! HTML and MathML in remote metadata.
+//!
+//! Publishers deposit titles as they typeset them, and CrossRef, OpenAlex,
+//! IEEE, Springer, and Scopus pass them on: "The <i>Drosophila</i> genome",
+//! "CO<sub>2</sub> capture", "Q&amp;A", or a whole MathML formula. Compared
+//! as they are, such titles differ from the entry's plain ones and look
+//! like mismatches. `plainText` reduces them to the text they show.
+
+const std = @import("std");
+
+/// Elements whose content repeats the formula around them, e.g. its TeX
+/// source in a MathML `<annotation>`.
+const hidden_elements = [_][]const u8{ "annotation", "annotation-xml" };
+
+const named_entities = [_]struct { []const u8, []const u8 }{
+    .{ "&amp;", "&" },
+    .{ "&quot;", "\"" },
+    .{ "&apos;", "'" },
+    .{ "&lt;", "<" },
+    .{ "&gt;", ">" },
+    .{ "&nbsp;", " " },
+    .{ "&ndash;", "\u{2013}" },
+    .{ "&mdash;", "\u{2014}" },
+};
+
+/// `raw` with tags removed, entities decoded, and whitespace runs
+/// collapsed to single spaces. Text that only looks like a tag, as in
+/// "a < b", is kept.
+pub fn plainText(allocator: std.mem.Allocator, raw: []const u8) ![]u8 {
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+
+    var space = false;
+    var i: usize = 0;
+    while (i < raw.len) {
+        const c = raw[i];
+        if (c == '<') {
+            if (tagEnd(raw, i)) |end| {
+                const name = tagName(raw[i..end]);
+                const opening = raw[i + 1] != '/' and raw[end - 2] != '/';
+                i = if (opening and isHidden(name)) closingEnd(raw, end, name) else end;
+                continue;
+            }
+        }
+        if (std.ascii.isWhitespace(c)) {
+            space = out.items.len > 0;
+            i += 1;
+            continue;
+        }
+        if (space) try out.append(allocator, ' ');
+        space = false;
+
+        var buf: [4]u8 = undefined;
+        if (entityAt(raw[i..], &buf)) |entity| {
+            try out.appendSlice(allocator, entity.text);
+            i += entity.len;
+            continue;
+        }
+        try out.append(allocator, c);
+        i += 1;
+    }
+    return out.toOwnedSlice(allocator);
+}
+
+/// Replace the character references in `text`, such as an attribute value.
+/// Returns `text` itself if it has none.
+pub fn decodeEntities(arena: std.mem.Allocator, text: []const u8) ![]const u8 {
+    if (std.mem.indexOfScalar(u8, text, '&') == null) return text;
+
+    var out: std.ArrayList(u8) = .empty;
+    var i: usize = 0;
+    while (i < text.len) {
+        var buf: [4]u8 = undefined;
+        if (entityAt(text[i..], &buf)) |entity| {
+            try out.appendSlice(arena, entity.text);
+            i += entity.len;
+            continue;
+        }
+        try out.append(arena, text[i]);
+        i += 1;
+    }
+    return out.items;
+}
+
+const Entity = struct {
+    /// Length of the reference, "&amp;" included.
+    len: usize,
+    text: []const u8,
+};
+
+/// The character reference `text` starts with, if any. Numeric ones are
+/// encoded into `buf`.
+fn entityAt(text: []const u8, buf: *[4]u8) ?Entity {
+    if (text.len == 0 or text[0] != '&') return null;
+    for (named_entities) |entity| {
+        if (std.mem.startsWith(u8, text, entity[0])) return .{ .len = entity[0].len, .text = entity[1] };
+    }
+    if (!std.mem.startsWith(u8, text, "&#")) return null;
+    const semi = std.mem.indexOfScalar(u8, text, ';') orelse return null;
+    const digits = text[2..semi];
+    const code = if (digits.len > 0 and (digits[0] == 'x' or digits[0] == 'X'))
+        std.fmt.parseInt(u21, digits[1..], 16) catch return null
+    else
+        std.fmt.parseInt(u21, digits, 10) catch return null;
+    const len = std.unicode.utf8Encode(code, buf) catch return null;
+    return .{ .len = semi + 1, .text = buf[0..len] };
+}
+
+/// End of the tag starting at `raw[start]` ('<'), or null if no tag starts
+/// there: the name must start with a letter, and the tag must close
+/// before the next '<'.
+fn tagEnd(raw: []const u8, start: usize) ?usize {
+    var i = start + 1;
+    if (i < raw.len and raw[i] == '/') i += 1;
+    if (i >= raw.len or !std.ascii.isAlphabetic(raw[i])) return null;
+    while (i < raw.len) : (i += 1) {
+        switch (raw[i]) {
+            '>' => return i + 1,
+            '<' => return null,
+            else => {},
+        }
+    }
+    return null;
+}
+
+/// Element name of a tag without its namespace prefix: "annotation" for
+/// `<mml:annotation encoding="...">`.
+fn tagName(tag: []const u8) []const u8 {
+    var start: usize = 1;
+    if (tag[start] == '/') start += 1;
+    var end = start;
+    while (end < tag.len and !std.ascii.isWhitespace(tag[end]) and tag[end] != '>' and tag[end] != '/') end += 1;
+    const name = tag[start..end];
+    return if (std.mem.lastIndexOfScalar(u8, name, ':')) |colon| name[colon + 1 ..] else name;
+}
+
+fn isHidden(name: []const u8) bool {
+    for (hidden_elements) |hidden| {
+        if (std.ascii.eqlIgnoreCase(name, hidden)) return true;
+    }
+    return false;
+}
+
+/// End of the closing tag of `name` after `from`, or the end of `raw` if
+/// it is never closed.
+fn closingEnd(raw: []const u8, from: usize, name: []const u8) usize {
+    var pos = from;
+    while (std.mem.indexOfPos(u8, raw, pos, "</")) |at| {
+        const end = tagEnd(raw, at) orelse {
+            pos = at + 2;
+            continue;
+        };
+        if (std.ascii.eqlIgnoreCase(tagName(raw[at..end]), name)) return end;
+        pos = end;
+    }
+    return raw.len;
+}
+
+test "plainText strips markup from remote titles" {
+    const allocator = std.testing.allocator;
+
+    const cases = [_][2][]const u8{
+        .{ "The <i>Drosophila</i> genome: CO<sub>2</sub> &amp; H<sub>2</sub>O", "The Drosophila genome: CO2 & H2O" },
+        .{ "Bounds for <mml:math xmlns:mml=\"http://www.w3.org/1998/Math/MathML\"><mml:semantics><mml:mrow><mml:mi>k</mml:mi><mml:mo>&#x2212;</mml:mo><mml:mn>1</mml:mn></mml:mrow><mml:annotation encoding=\"application/x-tex\">k-1</mml:annotation></mml:semantics></mml:math> colorings", "Bounds for k\u{2212}1 colorings" },
+        .{ "When a < b and\n  c > d", "When a < b and c > d" },
+        .{ "Plain title", "Plain title" },
+    };
+    for (cases) |case| {
+        const text = try plainText(allocator, case[0]);
+        defer allocator.free(text);
+        try std.testing.expectEqualStrings(case[1], text);
+    }
+}
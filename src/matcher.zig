@@ -9,20 +9,95 @@ const Discrepancy = entry.Discrepancy;
 const DiscrepancyField = entry.DiscrepancyField;
 const Severity = entry.Severity;
 const normalizeString = entry.normalizeString;
+const venues = @import("venues.zig");
+const iso4 = @import("iso4.zig");
+const names = @import("names.zig");
+const editions = @import("editions.zig");
+const github = @import("github.zig");
+const language = @import("language.zig");
 
-/// Threshold for title similarity (0.0 to 1.0)
+/// Inverted index limiting pairwise comparisons in large files.
+pub const BlockingIndex = @import("blocking.zig").BlockingIndex;
+
+/// Threshold for title similarity (0.0 to 1.0) of average-length titles
 pub const TITLE_MATCH_THRESHOLD: f64 = 0.85;
 pub const TITLE_WARNING_THRESHOLD: f64 = 0.90;
 
+/// Titles of at most this many words need a near-exact match and
+/// confirming authors
+pub const SHORT_TITLE_WORDS: usize = 2;
+
+/// Titles of more than this many words tolerate more edit noise
+pub const LONG_TITLE_WORDS: usize = 12;
+
+/// Author overlap needed to confirm a match on a short title
+pub const SHORT_TITLE_AUTHOR_OVERLAP: f64 = 0.5;
+
 /// Threshold for author name similarity
 pub const AUTHOR_MATCH_THRESHOLD: f64 = 0.80;
 
-/// Maximum year difference for a valid match
-pub const MAX_YEAR_DIFFERENCE: i32 = 2;
+/// Default year difference beyond which a candidate is rejected
+pub const MAX_YEAR_DISTANCE: i32 = 10;
+
+/// Fraction of the match score lost per year of difference
+pub const YEAR_PENALTY: f64 = 0.05;
+
+/// Fraction of the match score lost by another edition of the cited book
+pub const EDITION_PENALTY: f64 = 0.1;
+
+/// Minimum similarity of venue names under a configured venue metric
+pub const VENUE_MATCH_THRESHOLD: f64 = 0.7;
 
 /// Minimum author overlap ratio for a valid match
 pub const MIN_AUTHOR_OVERLAP: f64 = 0.3;
 
+/// Matching settings, read from the `[matching]` section of the config.
+pub const MatcherConfig = struct {
+    /// Year difference beyond which a candidate is rejected outright.
+    /// Smaller differences only lower the score, so later editions and
+    /// journal versions of conference papers can still match.
+    max_year_distance: i32 = MAX_YEAR_DISTANCE,
+    /// Metric comparing candidate titles.
+    title_similarity: Similarity = jaro_winkler,
+    /// Metric comparing author names that cannot be split into parts.
+    author_similarity: Similarity = jaro_winkler,
+    /// Checks run against each matched record.
+    comparators: []const FieldComparator = &default_comparators,
+    /// How title and author similarity combine into the match score.
+    weights: Weights = .{},
+    /// A candidate with the same DOI scores 1.0 whatever its title and
+    /// authors; off for sources whose DOIs are unreliable.
+    doi_boost: bool = true,
+    /// Hard filters rejecting a candidate whatever its score.
+    filters: Filters = .{},
+};
+
+/// Relative weights of the match score components. Title and author
+/// weights are divided by their sum, so only their ratio matters.
+pub const Weights = struct {
+    title: f64 = 0.7,
+    authors: f64 = 0.3,
+    /// Fraction of the score lost per year of difference.
+    year_penalty: f64 = YEAR_PENALTY,
+    /// Weight of the first local author in the author overlap, relative
+    /// to the others.
+    first_author: f64 = 1.0,
+    /// Weight of the last local author, who leads the lab in biomedical
+    /// fields.
+    last_author: f64 = 1.0,
+};
+
+/// Which hard filters of `matchScore` apply. OCR'd references with
+/// garbled titles may need the title filter off and rely on the score.
+pub const Filters = struct {
+    /// Reject titles below the length-scaled threshold.
+    title: bool = true,
+    /// Reject years further apart than `max_year_distance`.
+    year: bool = true,
+    /// Reject low author overlap, and short titles not confirmed by authors.
+    authors: bool = true,
+};
+
 /// Calculate Jaro similarity between two strings.
 /// Uses dynamic allocation to support strings of any length.
 pub fn jaroSimilarity(allocator: std.mem.Allocator, s1: []const u8, s2: []const u8) !f64 {
@@ -96,8 +171,201 @@ pub fn jaroWinklerSimilarity(allocator: std.mem.Allocator, s1: []const u8, s2: [
     return jaro + l * p * (1.0 - jaro);
 }
 
+/// A string similarity metric over normalized strings (0.0 to 1.0).
+///
+/// The built-in metrics are `jaro_winkler`, `levenshtein`, `token_ratio`,
+/// and `trigram`; library users can supply their own.
+pub const Similarity = struct {
+    ctx: ?*anyopaque = null,
+    compareFn: *const fn (ctx: ?*anyopaque, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64,
+
+    pub fn compare(self: Similarity, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64 {
+        return self.compareFn(self.ctx, allocator, a, b);
+    }
+};
+
+/// Jaro-Winkler similarity, the default.
+pub const jaro_winkler = Similarity{ .compareFn = jaroWinklerCompare };
+
+/// Token-sort ratio: words are sorted before comparing, so reordered
+/// titles ("Learning, Deep" / "Deep Learning") still match. Faster than
+/// Jaro-Winkler on long strings, but the title thresholds are tuned for
+/// Jaro-Winkler scores.
+pub const token_ratio = Similarity{ .compareFn = tokenRatioCompare };
+
+/// Edit distance relative to the longer string. No prefix bias, so
+/// venues differing in their first word ("Proc." / "Proceedings") are not
+/// favored over ones differing at the end.
+pub const levenshtein = Similarity{ .compareFn = levenshteinCompare };
+
+/// Jaccard similarity of character trigrams. Insensitive to word order
+/// and robust to OCR errors in long titles.
+pub const trigram = Similarity{ .compareFn = trigramCompare };
+
+/// Config names of the built-in metrics.
+pub const metric_names = [_]struct { name: []const u8, metric: Similarity }{
+    .{ .name = "jaro-winkler", .metric = jaro_winkler },
+    .{ .name = "levenshtein", .metric = levenshtein },
+    .{ .name = "token-sort", .metric = token_ratio },
+    .{ .name = "trigram", .metric = trigram },
+};
+
+/// The built-in metric called `name` in the config.
+pub fn metricNamed(name: []const u8) ?Similarity {
+    for (metric_names) |m| {
+        if (std.mem.eql(u8, m.name, name)) return m.metric;
+    }
+    return null;
+}
+
+fn jaroWinklerCompare(_: ?*anyopaque, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64 {
+    return jaroWinklerSimilarity(allocator, a, b);
+}
+
+fn tokenRatioCompare(_: ?*anyopaque, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64 {
+    return tokenSortRatio(allocator, a, b);
+}
+
+fn levenshteinCompare(_: ?*anyopaque, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64 {
+    return levenshteinSimilarity(allocator, a, b);
+}
+
+fn trigramCompare(_: ?*anyopaque, allocator: std.mem.Allocator, a: []const u8, b: []const u8) std.mem.Allocator.Error!f64 {
+    return trigramSimilarity(allocator, a, b);
+}
+
+/// 1 - Levenshtein distance / length of the longer string.
+pub fn levenshteinSimilarity(allocator: std.mem.Allocator, a: []const u8, b: []const u8) !f64 {
+    if (a.len == 0 and b.len == 0) return 1.0;
+    if (a.len == 0 or b.len == 0) return 0.0;
+
+    // Two rows of the edit distance table
+    const row = try allocator.alloc(usize, 2 * (b.len + 1));
+    defer allocator.free(row);
+    var prev = row[0 .. b.len + 1];
+    var cur = row[b.len + 1 ..];
+    for (prev, 0..) |*d, j| d.* = j;
+
+    for (a, 1..) |ca, i| {
+        cur[0] = i;
+        for (b, 1..) |cb, j| {
+            const substitution = prev[j - 1] + @intFromBool(ca != cb);
+            cur[j] = @min(substitution, @min(prev[j], cur[j - 1]) + 1);
+        }
+        std.mem.swap([]usize, &prev, &cur);
+    }
+
+    const distance: f64 = @floatFromInt(prev[b.len]);
+    return 1.0 - distance / @as(f64, @floatFromInt(@max(a.len, b.len)));
+}
+
+/// Jaccard similarity of the character trigram sets of two strings, each
+/// padded with a space so short words still have trigrams.
+pub fn trigramSimilarity(allocator: std.mem.Allocator, a: []const u8, b: []const u8) !f64 {
+    if (std.mem.eql(u8, a, b)) return 1.0;
+
+    var set_a = try trigrams(allocator, a);
+    defer set_a.deinit(allocator);
+    var set_b = try trigrams(allocator, b);
+    defer set_b.deinit(allocator);
+    if (set_a.count() == 0 or set_b.count() == 0) return 0.0;
+
+    var shared: usize = 0;
+    var it = set_a.keyIterator();
+    while (it.next()) |t| {
+        if (set_b.contains(t.*)) shared += 1;
+    }
+    const all = set_a.count() + set_b.count() - shared;
+    return @as(f64, @floatFromInt(shared)) / @as(f64, @floatFromInt(all));
+}
+
+fn trigrams(allocator: std.mem.Allocator, s: []const u8) !std.AutoHashMapUnmanaged(u24, void) {
+    var set: std.AutoHashMapUnmanaged(u24, void) = .empty;
+    errdefer set.deinit(allocator);
+    if (s.len == 0) return set;
+
+    var prev: [2]u8 = .{ ' ', ' ' };
+    for (s) |c| {
+        try set.put(allocator, @as(u24, prev[0]) << 16 | @as(u24, prev[1]) << 8 | c, {});
+        prev = .{ prev[1], c };
+    }
+    try set.put(allocator, @as(u24, prev[0]) << 16 | @as(u24, prev[1]) << 8 | ' ', {});
+    return set;
+}
+
+/// Indel similarity of two strings after sorting their words:
+/// 2 * LCS / (len a + len b).
+pub fn tokenSortRatio(allocator: std.mem.Allocator, a: []const u8, b: []const u8) !f64 {
+    if (a.len == 0 and b.len == 0) return 1.0;
+
+    const sorted_a = try sortTokens(allocator, a);
+    defer allocator.free(sorted_a);
+    const sorted_b = try sortTokens(allocator, b);
+    defer allocator.free(sorted_b);
+
+    const lcs = try lcsLength(allocator, sorted_a, sorted_b);
+    return 2.0 * @as(f64, @floatFromInt(lcs)) / @as(f64, @floatFromInt(sorted_a.len + sorted_b.len));
+}
+
+fn sortTokens(allocator: std.mem.Allocator, s: []const u8) ![]u8 {
+    var tokens: std.ArrayList([]const u8) = .empty;
+    defer tokens.deinit(allocator);
+    var iter = std.mem.tokenizeScalar(u8, s, ' ');
+    while (iter.next()) |token| try tokens.append(allocator, token);
+
+    std.sort.pdq([]const u8, tokens.items, {}, lessThanString);
+    return std.mem.join(allocator, " ", tokens.items);
+}
+
+fn lessThanString(_: void, a: []const u8, b: []const u8) bool {
+    return std.mem.lessThan(u8, a, b);
+}
+
+/// Length of the longest common subsequence, computed 64 characters of
+/// `a` at a time with bit-parallel arithmetic (Hyyrö, 2004).
+fn lcsLength(allocator: std.mem.Allocator, a: []const u8, b: []const u8) !usize {
+    if (a.len == 0 or b.len == 0) return 0;
+    const words = (a.len + 63) / 64;
+
+    // Bit i of match[c * words + i / 64] is set if a[i] == c
+    const match = try allocator.alloc(u64, 256 * words);
+    defer allocator.free(match);
+    @memset(match, 0);
+    for (a, 0..) |c, i| match[@as(usize, c) * words + i / 64] |= @as(u64, 1) << @intCast(i % 64);
+
+    const v = try allocator.alloc(u64, words);
+    defer allocator.free(v);
+    @memset(v, std.math.maxInt(u64));
+
+    for (b) |c| {
+        const row = match[@as(usize, c) * words ..][0..words];
+        var carry: u1 = 0;
+        for (v, row) |*word, m| {
+            const u = word.* & m;
+            const sum1 = @addWithOverflow(word.*, u);
+            const sum2 = @addWithOverflow(sum1[0], @as(u64, carry));
+            carry = sum1[1] | sum2[1];
+            word.* = sum2[0] | (word.* & ~m);
+        }
+    }
+
+    // Zero bits of v within the length of a mark the LCS
+    var ones: usize = 0;
+    for (v, 0..) |word, k| {
+        const bits = @min(64, a.len - k * 64);
+        const mask = if (bits == 64) std.math.maxInt(u64) else (@as(u64, 1) << @intCast(bits)) - 1;
+        ones += @popCount(word & mask);
+    }
+    return a.len - ones;
+}
+
 /// Calculate title similarity between two entries.
 pub fn titleSimilarity(allocator: std.mem.Allocator, a: *const Entry, b: *const Entry) !f64 {
+    return titleSimilarityWith(allocator, jaro_winkler, a, b);
+}
+
+/// Title similarity between two entries using `metric`.
+pub fn titleSimilarityWith(allocator: std.mem.Allocator, metric: Similarity, a: *const Entry, b: *const Entry) !f64 {
     if (a.title == null or b.title == null) return 0.0;
 
     const norm_a = try normalizeString(allocator, a.title.?);
@@ -105,159 +373,1152 @@ pub fn titleSimilarity(allocator: std.mem.Allocator, a: *const Entry, b: *const
     const norm_b = try normalizeString(allocator, b.title.?);
     defer allocator.free(norm_b);
 
-    return jaroWinklerSimilarity(allocator, norm_a, norm_b);
+    return metric.compare(allocator, norm_a, norm_b);
+}
+
+/// An entry with the strings used for matching normalized once, so that
+/// comparing it against many candidates does not normalize them again.
+pub const NormalizedEntry = struct {
+    entry: *const Entry,
+    /// Normalized title
+    title: ?[]const u8 = null,
+    /// Words in the original title
+    title_words: usize = 0,
+    /// Normalized title in the original language, for a matched record
+    /// whose `title` is a translation
+    original_title: ?[]const u8 = null,
+    /// Normalized author names
+    authors: []const []const u8 = &.{},
+    /// Parsed author names, in the same order
+    names: []names.Name = &.{},
+    /// Normalized venue
+    venue: ?[]const u8 = null,
+
+    allocator: std.mem.Allocator,
+
+    /// Normalize `e`, which must outlive the result.
+    pub fn init(allocator: std.mem.Allocator, e: *const Entry) !NormalizedEntry {
+        var result = NormalizedEntry{ .entry = e, .allocator = allocator };
+        errdefer result.deinit();
+
+        if (e.title) |title| {
+            result.title = try normalizeString(allocator, title);
+            result.title_words = titleWords(title);
+        }
+        if (e.extraField(language.ORIGINAL_TITLE_FIELD)) |original| {
+            result.original_title = try normalizeString(allocator, original);
+        }
+        if (e.venue) |venue| result.venue = try normalizeString(allocator, venue);
+
+        var authors: std.ArrayList([]const u8) = .empty;
+        errdefer {
+            for (authors.items) |a| allocator.free(a);
+            authors.deinit(allocator);
+        }
+        for (e.authors) |author| {
+            const normalized = try normalizeString(allocator, author);
+            errdefer allocator.free(normalized);
+            try authors.append(allocator, normalized);
+        }
+        result.authors = try authors.toOwnedSlice(allocator);
+
+        var parsed: std.ArrayList(names.Name) = .empty;
+        errdefer {
+            for (parsed.items) |*n| n.deinit(allocator);
+            parsed.deinit(allocator);
+        }
+        for (e.authors) |author| {
+            var name = try names.parse(allocator, author);
+            errdefer name.deinit(allocator);
+            try parsed.append(allocator, name);
+        }
+        result.names = try parsed.toOwnedSlice(allocator);
+
+        return result;
+    }
+
+    pub fn deinit(self: *NormalizedEntry) void {
+        if (self.title) |t| self.allocator.free(t);
+        if (self.original_title) |t| self.allocator.free(t);
+        if (self.venue) |v| self.allocator.free(v);
+        for (self.authors) |a| self.allocator.free(a);
+        if (self.authors.len > 0) self.allocator.free(self.authors);
+        for (self.names) |*n| n.deinit(self.allocator);
+        if (self.names.len > 0) self.allocator.free(self.names);
+    }
+};
+
+/// Normalize every entry of `entries`. The caller deinits each result and
+/// frees the slice.
+pub fn normalizeAll(allocator: std.mem.Allocator, entries: []const Entry) ![]NormalizedEntry {
+    var result: std.ArrayList(NormalizedEntry) = .empty;
+    errdefer {
+        for (result.items) |*n| n.deinit();
+        result.deinit(allocator);
+    }
+    for (entries) |*e| {
+        var normalized = try NormalizedEntry.init(allocator, e);
+        errdefer normalized.deinit();
+        try result.append(allocator, normalized);
+    }
+    return result.toOwnedSlice(allocator);
+}
+
+/// Title similarity thresholds for a title of a given length.
+pub const TitleThresholds = struct {
+    /// Below this the titles name different works
+    match: f64,
+    /// Below this the titles differ enough to warn
+    warning: f64,
+};
+
+/// Number of words in a title.
+pub fn titleWords(title: []const u8) usize {
+    var count: usize = 0;
+    var iter = std.mem.tokenizeAny(u8, title, " \t\r\n");
+    while (iter.next()) |_| count += 1;
+    return count;
+}
+
+/// Thresholds scaled by title length: one typo is a large share of a short
+/// title ("Deep Learning" vs "Deep Learners"), while long titles collect
+/// more harmless edit noise (subtitles, punctuation, hyphenation).
+pub fn titleThresholds(title: []const u8) TitleThresholds {
+    return thresholdsForWords(titleWords(title));
+}
+
+fn thresholdsForWords(words: usize) TitleThresholds {
+    if (words <= SHORT_TITLE_WORDS) return .{ .match = 0.97, .warning = 1.0 };
+    if (words <= 4) return .{ .match = 0.92, .warning = 0.96 };
+    if (words > LONG_TITLE_WORDS) return .{ .match = 0.80, .warning = 0.86 };
+    return .{ .match = TITLE_MATCH_THRESHOLD, .warning = TITLE_WARNING_THRESHOLD };
+}
+
+/// Years between two entries, or null if either has no year. Date ranges
+/// and approximate dates are as close as their nearest years.
+pub fn yearDistance(a: *const Entry, b: *const Entry) ?i32 {
+    const x = a.yearBounds() orelse return null;
+    const y = b.yearBounds() orelse return null;
+    return @max(0, @max(x[0], y[0]) - @min(x[1], y[1]));
+}
+
+/// The date as the entry gives it: "2019", or "2019/2020" as written in
+/// its `date` field.
+fn writtenYear(allocator: std.mem.Allocator, e: *const Entry) ![]u8 {
+    if (e.year_field) |name| {
+        if (e.extraField(name)) |value| return allocator.dupe(u8, value);
+    }
+    return std.fmt.allocPrint(allocator, "{d}", .{e.year.?});
 }
 
 /// Check if years are within acceptable range.
-pub fn yearsCompatible(a: *const Entry, b: *const Entry) bool {
-    if (a.year == null or b.year == null) return true;
-    const diff = if (a.year.? > b.year.?) a.year.? - b.year.? else b.year.? - a.year.?;
-    return diff <= MAX_YEAR_DIFFERENCE;
+pub fn yearsCompatible(config: *const MatcherConfig, a: *const Entry, b: *const Entry) bool {
+    const diff = yearDistance(a, b) orelse return true;
+    return diff <= config.max_year_distance;
+}
+
+/// Score multiplier for the year difference between two entries.
+fn yearFactor(config: *const MatcherConfig, a: *const Entry, b: *const Entry) f64 {
+    const diff = yearDistance(a, b) orelse return 1.0;
+    return @max(0.0, 1.0 - config.weights.year_penalty * @as(f64, @floatFromInt(diff)));
 }
 
 /// Calculate author overlap ratio.
 pub fn authorOverlap(allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) !f64 {
-    if (local.authors.len == 0 or remote.authors.len == 0) return 1.0;
-
-    var matches: usize = 0;
-    for (local.authors) |local_author| {
-        const local_norm = try normalizeString(allocator, local_author);
-        defer allocator.free(local_norm);
+    var local_norm = try NormalizedEntry.init(allocator, local);
+    defer local_norm.deinit();
+    var remote_norm = try NormalizedEntry.init(allocator, remote);
+    defer remote_norm.deinit();
+    return normalizedAuthorOverlap(allocator, &local_norm, &remote_norm);
+}
 
-        for (remote.authors) |remote_author| {
-            const remote_norm = try normalizeString(allocator, remote_author);
-            defer allocator.free(remote_norm);
+/// Author overlap ratio of two normalized entries.
+pub fn normalizedAuthorOverlap(allocator: std.mem.Allocator, local: *const NormalizedEntry, remote: *const NormalizedEntry) !f64 {
+    return authorOverlapWith(allocator, jaro_winkler, local, remote);
+}
 
-            const full_sim = try jaroWinklerSimilarity(allocator, local_norm, remote_norm);
+/// Author overlap ratio, comparing unsplittable names with `metric`.
+fn authorOverlapWith(allocator: std.mem.Allocator, metric: Similarity, local: *const NormalizedEntry, remote: *const NormalizedEntry) !f64 {
+    return weightedAuthorOverlap(allocator, metric, .{}, local, remote);
+}
 
-            // Also check last names
-            const local_last = lastWord(local_norm);
-            const remote_last = lastWord(remote_norm);
-            const last_name_sim = try jaroWinklerSimilarity(allocator, local_last, remote_last);
+/// Author overlap ratio, with the first and last local authors weighted
+/// by `weights`.
+fn weightedAuthorOverlap(allocator: std.mem.Allocator, metric: Similarity, weights: Weights, local: *const NormalizedEntry, remote: *const NormalizedEntry) !f64 {
+    if (local.authors.len == 0 or remote.authors.len == 0) return 1.0;
 
-            if (full_sim >= AUTHOR_MATCH_THRESHOLD or last_name_sim >= 0.9) {
-                matches += 1;
+    var matched: f64 = 0;
+    var total: f64 = 0;
+    const last = local.authors.len - 1;
+    for (local.authors, local.names, 0..) |local_norm, *local_name, i| {
+        const weight = if (i == 0) weights.first_author else if (i == last) weights.last_author else 1.0;
+        total += weight;
+        for (remote.authors, remote.names) |remote_norm, *remote_name| {
+            if (try sameAuthor(allocator, metric, local_norm, local_name, remote_norm, remote_name)) {
+                matched += weight;
                 break;
             }
         }
     }
 
-    return @as(f64, @floatFromInt(matches)) / @as(f64, @floatFromInt(local.authors.len));
+    return if (total > 0) matched / total else 1.0;
+}
+
+/// Names compared part by part, or as strings if one has no recognizable
+/// family name.
+fn sameAuthor(allocator: std.mem.Allocator, metric: Similarity, a_norm: []const u8, a: *const names.Name, b_norm: []const u8, b: *const names.Name) !bool {
+    if (a.family.len > 0 and b.family.len > 0) return names.samePerson(allocator, a, b);
+    return try metric.compare(allocator, a_norm, b_norm) >= AUTHOR_MATCH_THRESHOLD;
+}
+
+/// Why a candidate fails the hard filters of `matchScore`.
+pub const Rejection = enum {
+    title_below_threshold,
+    year_incompatible,
+    author_overlap_low,
+    /// Short title without enough shared authors to confirm it
+    author_unconfirmed,
+
+    pub fn describe(self: Rejection) []const u8 {
+        return switch (self) {
+            .title_below_threshold => "title below threshold",
+            .year_incompatible => "year incompatible",
+            .author_overlap_low => "author overlap too low",
+            .author_unconfirmed => "short title not confirmed by authors",
+        };
+    }
+};
+
+/// The components of a match score, for tuning weights and explaining
+/// matches.
+pub const ScoreBreakdown = struct {
+    /// Title similarity under `title_similarity`.
+    title: f64,
+    /// Share of the local authors found among the candidate's.
+    authors: f64,
+    /// Multiplier for the year difference.
+    year_factor: f64,
+    /// Multiplier for another edition of the cited book.
+    edition_factor: f64 = 1.0,
+    /// Whether both have the same DOI.
+    doi_match: bool,
+    /// Whether both have the same ISBN, which names one edition of a book.
+    isbn_match: bool = false,
+    /// Weighted score, or 1.0 for a boosted DOI or ISBN match.
+    score: f64,
+    /// The hard filter that rejects the candidate, if any.
+    rejection: ?Rejection,
+
+    /// The match score: `score`, or 0 if a filter rejects the candidate.
+    pub fn final(self: ScoreBreakdown) f64 {
+        return if (self.rejection == null) self.score else 0.0;
+    }
+};
+
+/// The components of the match score of `candidate` for `target`.
+pub fn scoreBreakdown(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const Entry, candidate: *const Entry) !ScoreBreakdown {
+    var target_norm = try NormalizedEntry.init(allocator, target);
+    defer target_norm.deinit();
+    var candidate_norm = try NormalizedEntry.init(allocator, candidate);
+    defer candidate_norm.deinit();
+    return scoreCandidate(allocator, config, &target_norm, &candidate_norm);
 }
 
-fn lastWord(s: []const u8) []const u8 {
-    var i = s.len;
-    while (i > 0 and s[i - 1] != ' ') {
-        i -= 1;
+fn scoreCandidate(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const NormalizedEntry, candidate: *const NormalizedEntry) !ScoreBreakdown {
+    var title_sim = if (target.title != null and candidate.title != null)
+        try config.title_similarity.compare(allocator, target.title.?, candidate.title.?)
+    else
+        0.0;
+    // A translated work matches by either of its titles
+    if (target.title != null and candidate.original_title != null) {
+        title_sim = @max(title_sim, try config.title_similarity.compare(allocator, target.title.?, candidate.original_title.?));
     }
-    return s[i..];
+    const author_sim = try weightedAuthorOverlap(allocator, config.author_similarity, config.weights, target, candidate);
+    const year_factor = yearFactor(config, target.entry, candidate.entry);
+
+    // Combined score: title and authors by weight (70/30 by default),
+    // decaying with the year difference
+    const weights = config.weights;
+    const total = weights.title + weights.authors;
+    const weighted = if (total > 0) (title_sim * weights.title + author_sim * weights.authors) / total else title_sim;
+    // Of several editions of a book, the one the entry names wins
+    const stated = editions.ofEntry(target.entry);
+    const found = editions.ofEntry(candidate.entry);
+    const edition_factor: f64 = if (stated != null and found != null and stated.? != found.?) 1.0 - EDITION_PENALTY else 1.0;
+    var score = weighted * year_factor * edition_factor;
+
+    // Boost if DOIs or ISBNs match exactly (case-insensitive)
+    const doi_match = target.entry.doi != null and candidate.entry.doi != null and
+        std.ascii.eqlIgnoreCase(target.entry.doi.?, candidate.entry.doi.?);
+    const isbn_match = target.entry.isbn != null and candidate.entry.isbn != null and
+        std.ascii.eqlIgnoreCase(target.entry.isbn.?, candidate.entry.isbn.?);
+    if ((doi_match or isbn_match) and config.doi_boost) score = 1.0;
+
+    const short = target.title_words <= SHORT_TITLE_WORDS;
+    const have_authors = target.authors.len > 0 and candidate.authors.len > 0;
+    const filters = config.filters;
+
+    const rejection: ?Rejection = if (filters.title and title_sim < thresholdsForWords(target.title_words).match)
+        .title_below_threshold
+    else if (filters.year and !yearsCompatible(config, target.entry, candidate.entry))
+        .year_incompatible
+    else if (filters.authors and have_authors and author_sim < MIN_AUTHOR_OVERLAP)
+        .author_overlap_low
+    else if (filters.authors and short and (!have_authors or author_sim < SHORT_TITLE_AUTHOR_OVERLAP))
+        .author_unconfirmed
+    else
+        null;
+
+    return .{
+        .title = title_sim,
+        .authors = author_sim,
+        .year_factor = year_factor,
+        .edition_factor = edition_factor,
+        .doi_match = doi_match,
+        .isbn_match = isbn_match,
+        .score = score,
+        .rejection = rejection,
+    };
 }
 
 /// Calculate a combined match score.
-pub fn matchScore(allocator: std.mem.Allocator, target: *const Entry, candidate: *const Entry) !f64 {
-    const title_sim = try titleSimilarity(allocator, target, candidate);
+pub fn matchScore(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const Entry, candidate: *const Entry) !f64 {
+    var target_norm = try NormalizedEntry.init(allocator, target);
+    defer target_norm.deinit();
+    var candidate_norm = try NormalizedEntry.init(allocator, candidate);
+    defer candidate_norm.deinit();
+    return normalizedMatchScore(allocator, config, &target_norm, &candidate_norm);
+}
 
-    if (title_sim < TITLE_MATCH_THRESHOLD) return 0.0;
-    if (!yearsCompatible(target, candidate)) return 0.0;
+/// Match score of two normalized entries, for comparing many pairs.
+pub fn normalizedMatchScore(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const NormalizedEntry, candidate: *const NormalizedEntry) !f64 {
+    const scored = try scoreCandidate(allocator, config, target, candidate);
+    return scored.final();
+}
 
-    const author_sim = try authorOverlap(allocator, target, candidate);
+/// A field-level check of a local entry against its matched record,
+/// producing at most one discrepancy.
+///
+/// The built-in checks are listed in `default_comparators`. Library users
+/// can set `MatcherConfig.comparators` to a list of their own to add checks
+/// (reported under the `custom-check` rule) or to leave built-ins out.
+pub const FieldComparator = struct {
+    /// Identifies the check, e.g. "title".
+    name: []const u8,
+    ctx: ?*anyopaque = null,
+    compareFn: *const fn (ctx: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy,
 
-    if (target.authors.len > 0 and candidate.authors.len > 0 and author_sim < MIN_AUTHOR_OVERLAP) {
-        return 0.0;
+    pub fn compare(self: FieldComparator, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+        return self.compareFn(self.ctx, allocator, local, remote);
     }
+};
 
-    // Combined score: title 70%, authors 30%
-    var base_score = title_sim * 0.7 + author_sim * 0.3;
+pub const title_comparator = FieldComparator{ .name = "title", .compareFn = compareTitle };
+pub const year_comparator = FieldComparator{ .name = "year", .compareFn = compareYear };
+pub const edition_comparator = FieldComparator{ .name = "edition", .compareFn = compareEdition };
+pub const doi_field_comparator = FieldComparator{ .name = "doi-field", .compareFn = compareDoiField };
+pub const arxiv_field_comparator = FieldComparator{ .name = "arxiv-field", .compareFn = compareArxivField };
+pub const missing_doi_comparator = FieldComparator{ .name = "missing-doi", .compareFn = compareMissingDoi };
+pub const erratum_comparator = FieldComparator{ .name = "erratum", .compareFn = compareErratum };
+pub const updates_comparator = FieldComparator{ .name = "crossmark-update", .compareFn = compareUpdates };
+pub const author_count_comparator = FieldComparator{ .name = "author-count", .compareFn = compareAuthorCount };
+pub const first_author_comparator = FieldComparator{ .name = "first-author", .compareFn = compareFirstAuthor };
+pub const missing_author_comparator = FieldComparator{ .name = "missing-author", .compareFn = compareMissingAuthors };
+pub const venue_comparator = FieldComparator{ .name = "venue", .compareFn = compareVenue };
+pub const workshop_comparator = FieldComparator{ .name = "workshop", .compareFn = compareWorkshop };
+pub const book_edition_comparator = FieldComparator{ .name = "book-edition", .compareFn = compareBookEdition };
+pub const version_comparator = FieldComparator{ .name = "version", .compareFn = compareVersion };
+pub const granularity_comparator = FieldComparator{ .name = "doi-granularity", .compareFn = compareGranularity };
+pub const volume_comparator = FieldComparator{ .name = "volume", .compareFn = compareVolume };
+pub const pages_comparator = FieldComparator{ .name = "pages", .compareFn = comparePages };
+pub const language_comparator = FieldComparator{ .name = "language", .compareFn = compareLanguage };
+pub const translation_comparator = FieldComparator{ .name = "translation", .compareFn = compareTranslation };
 
-    // Boost if DOIs match exactly (case-insensitive)
-    if (target.doi != null and candidate.doi != null) {
-        if (std.ascii.eqlIgnoreCase(target.doi.?, candidate.doi.?)) {
-            base_score = 1.0;
-        }
-    }
+/// The built-in checks, in the order their findings are reported.
+pub const default_comparators = [_]FieldComparator{
+    title_comparator,
+    year_comparator,
+    edition_comparator,
+    doi_field_comparator,
+    arxiv_field_comparator,
+    missing_doi_comparator,
+    erratum_comparator,
+    updates_comparator,
+    author_count_comparator,
+    first_author_comparator,
+    missing_author_comparator,
+    venue_comparator,
+    workshop_comparator,
+    book_edition_comparator,
+    version_comparator,
+    granularity_comparator,
+    volume_comparator,
+    pages_comparator,
+    language_comparator,
+    translation_comparator,
+};
 
-    return base_score;
+/// `base` with the title check comparing by `title` and the venue check
+/// falling back to `venue` for venues the dictionary does not know.
+/// Allocates with `arena`, which must outlive the returned slice.
+pub fn withSimilarity(arena: std.mem.Allocator, base: []const FieldComparator, title: Similarity, venue: ?Similarity) ![]const FieldComparator {
+    const list = try arena.alloc(FieldComparator, base.len);
+    for (base, list) |comparator, *slot| {
+        slot.* = comparator;
+        const metric = if (std.mem.eql(u8, comparator.name, "title") and comparator.compareFn == &compareTitle)
+            title
+        else if (std.mem.eql(u8, comparator.name, "venue") and comparator.compareFn == &compareVenue)
+            venue orelse continue
+        else
+            continue;
+        const ctx = try arena.create(Similarity);
+        ctx.* = metric;
+        slot.ctx = ctx;
+    }
+    return list;
 }
 
-/// Compare two entries and return a list of discrepancies.
+/// Compare two entries with the built-in checks and return a list of
+/// discrepancies.
 pub fn compareEntries(allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) ![]Discrepancy {
+    return compareWith(allocator, &default_comparators, local, remote);
+}
+
+/// Compare two entries with `comparators`, in order.
+pub fn compareWith(allocator: std.mem.Allocator, comparators: []const FieldComparator, local: *const Entry, remote: *const Entry) ![]Discrepancy {
     var discrepancies: std.ArrayList(Discrepancy) = .empty;
     errdefer {
         for (discrepancies.items) |*d| d.deinit();
         discrepancies.deinit(allocator);
     }
 
-    // Compare titles
-    if (local.title != null and remote.title != null) {
-        const local_norm = try normalizeString(allocator, local.title.?);
-        defer allocator.free(local_norm);
-        const remote_norm = try normalizeString(allocator, remote.title.?);
-        defer allocator.free(remote_norm);
-
-        const similarity = try jaroWinklerSimilarity(allocator, local_norm, remote_norm);
-
-        if (similarity < TITLE_MATCH_THRESHOLD) {
-            const msg = try std.fmt.allocPrint(allocator, "Title significantly different (similarity: {d:.0}%)", .{similarity * 100.0});
-            try discrepancies.append(allocator, .{
-                .field = .title,
-                .severity = .@"error",
-                .local_value = try allocator.dupe(u8, local.title.?),
-                .remote_value = try allocator.dupe(u8, remote.title.?),
-                .message = msg,
-                .allocator = allocator,
-            });
-        } else if (similarity < TITLE_WARNING_THRESHOLD) {
-            const msg = try std.fmt.allocPrint(allocator, "Title slightly different (similarity: {d:.0}%)", .{similarity * 100.0});
-            try discrepancies.append(allocator, .{
-                .field = .title,
-                .severity = .warning,
-                .local_value = try allocator.dupe(u8, local.title.?),
-                .remote_value = try allocator.dupe(u8, remote.title.?),
-                .message = msg,
-                .allocator = allocator,
-            });
-        }
+    for (comparators) |comparator| {
+        var found = (try comparator.compare(allocator, local, remote)) orelse continue;
+        errdefer found.deinit();
+        try discrepancies.append(allocator, found);
     }
 
-    // Compare years
-    if (local.year != null and remote.year != null and local.year.? != remote.year.?) {
-        const msg = try std.fmt.allocPrint(allocator, "Year mismatch: {d} vs {d}", .{ local.year.?, remote.year.? });
-        try discrepancies.append(allocator, .{
-            .field = .year,
+    return discrepancies.toOwnedSlice(allocator);
+}
+
+/// The metric a comparator was given by `withSimilarity`.
+fn comparatorMetric(ctx: ?*anyopaque) ?Similarity {
+    const metric: *const Similarity = @ptrCast(@alignCast(ctx orelse return null));
+    return metric.*;
+}
+
+fn compareTitle(ctx: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (local.title == null or remote.title == null) return null;
+
+    const local_norm = try normalizeString(allocator, local.title.?);
+    defer allocator.free(local_norm);
+    const metric = comparatorMetric(ctx) orelse jaro_winkler;
+    const closer = try closerTitle(allocator, metric, local_norm, remote);
+    defer allocator.free(closer.normalized);
+    const remote_title = closer.title;
+    const remote_norm = closer.normalized;
+    const similarity = closer.similarity;
+    const thresholds = titleThresholds(local.title.?);
+
+    // Only punctuation, dashes, or spacing differ ("Self-Supervised" and
+    // "Self Supervised"); case is already normalized away
+    if (similarity < thresholds.warning and sameLetters(local_norm, remote_norm)) {
+        return .{
+            .rule = .title_punctuation,
+            .field = .title,
+            .severity = .info,
+            .local_value = try allocator.dupe(u8, local.title.?),
+            .remote_value = try allocator.dupe(u8, remote_title),
+            .message = try allocator.dupe(u8, "Title differs only in punctuation or spacing"),
+            .allocator = allocator,
+        };
+    }
+    if (similarity < thresholds.match) {
+        const msg = try std.fmt.allocPrint(allocator, "Title significantly different (similarity: {d:.0}%)", .{similarity * 100.0});
+        return .{
+            .rule = .title_mismatch,
+            .field = .title,
             .severity = .@"error",
-            .local_value = try std.fmt.allocPrint(allocator, "{d}", .{local.year.?}),
-            .remote_value = try std.fmt.allocPrint(allocator, "{d}", .{remote.year.?}),
+            .local_value = try allocator.dupe(u8, local.title.?),
+            .remote_value = try allocator.dupe(u8, remote_title),
+            .message = msg,
+            .allocator = allocator,
+        };
+    } else if (similarity < thresholds.warning) {
+        const msg = try std.fmt.allocPrint(allocator, "Title slightly different (similarity: {d:.0}%)", .{similarity * 100.0});
+        return .{
+            .rule = .title_difference,
+            .field = .title,
+            .severity = .warning,
+            .local_value = try allocator.dupe(u8, local.title.?),
+            .remote_value = try allocator.dupe(u8, remote_title),
             .message = msg,
             .allocator = allocator,
-        });
+        };
     }
+    return null;
+}
 
-    // Check for missing DOI
-    if (local.doi == null and remote.doi != null) {
-        try discrepancies.append(allocator, .{
-            .field = .doi,
-            .severity = .warning,
-            .local_value = try allocator.dupe(u8, "(none)"),
-            .remote_value = try allocator.dupe(u8, remote.doi.?),
-            .message = try allocator.dupe(u8, "Missing DOI in local entry"),
+/// A title of a matched record and its similarity to the entry's.
+const CloserTitle = struct {
+    title: []const u8,
+    /// Owned by the caller
+    normalized: []u8,
+    similarity: f64,
+    /// Whether `title` is the original-language one
+    original: bool,
+};
+
+/// The title of `remote` closest to the normalized title `local_norm`:
+/// its `title`, or its title in the original language if the entry cites
+/// the work by that one.
+fn closerTitle(allocator: std.mem.Allocator, metric: Similarity, local_norm: []const u8, remote: *const Entry) std.mem.Allocator.Error!CloserTitle {
+    const remote_norm = try normalizeString(allocator, remote.title.?);
+    errdefer allocator.free(remote_norm);
+    const similarity = try metric.compare(allocator, local_norm, remote_norm);
+    const closer = CloserTitle{ .title = remote.title.?, .normalized = remote_norm, .similarity = similarity, .original = false };
+
+    const original = remote.extraField(language.ORIGINAL_TITLE_FIELD) orelse return closer;
+    const original_norm = try normalizeString(allocator, original);
+    errdefer allocator.free(original_norm);
+    const original_similarity = try metric.compare(allocator, local_norm, original_norm);
+    if (original_similarity <= similarity) {
+        allocator.free(original_norm);
+        return closer;
+    }
+    allocator.free(remote_norm);
+    return .{ .title = original, .normalized = original_norm, .similarity = original_similarity, .original = true };
+}
+
+/// Whether two normalized strings have the same letters and digits,
+/// ignoring spaces.
+fn sameLetters(a: []const u8, b: []const u8) bool {
+    var i: usize = 0;
+    var j: usize = 0;
+    while (true) {
+        while (i < a.len and a[i] == ' ') i += 1;
+        while (j < b.len and b[j] == ' ') j += 1;
+        if (i == a.len or j == b.len) return i == a.len and j == b.len;
+        if (a[i] != b[j]) return false;
+        i += 1;
+        j += 1;
+    }
+}
+
+fn compareYear(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if ((yearDistance(local, remote) orelse 0) == 0) return null;
+    // Reported as the wrong edition instead
+    if (differentEdition(local, remote) or sameBookEdition(local, remote)) return null;
+
+    const local_value = try writtenYear(allocator, local);
+    errdefer allocator.free(local_value);
+    const remote_value = try writtenYear(allocator, remote);
+    errdefer allocator.free(remote_value);
+    const msg = try std.fmt.allocPrint(allocator, "Year mismatch: {s} vs {s}", .{ local_value, remote_value });
+    return .{
+        .rule = .year_mismatch,
+        .field = .year,
+        .severity = .@"error",
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// Whether a conference paper cites another edition of the conference
+/// it appeared in, e.g. ICML 2019 for a paper from ICML 2020, by the year
+/// or edition number in both venue names. The years of the entries alone
+/// are a year mismatch.
+fn differentEdition(local: *const Entry, remote: *const Entry) bool {
+    if (std.ascii.eqlIgnoreCase(local.entry_type, "article") or std.ascii.eqlIgnoreCase(local.entry_type, "book")) return false;
+    const local_venue = local.venue orelse return false;
+    const remote_venue = remote.venue orelse return false;
+    if (!venues.sameSeries(local_venue, remote_venue)) return false;
+
+    const local_edition = venues.edition(local_venue);
+    const remote_edition = venues.edition(remote_venue);
+    if (local_edition.year != null and remote_edition.year != null and local_edition.year.? != remote_edition.year.?) return true;
+    return local_edition.number != null and remote_edition.number != null and local_edition.number.? != remote_edition.number.?;
+}
+
+fn compareEdition(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (!differentEdition(local, remote)) return null;
+    const local_value = try describeEdition(allocator, local);
+    errdefer allocator.free(local_value);
+    const remote_value = try describeEdition(allocator, remote);
+    errdefer allocator.free(remote_value);
+    return .{
+        .rule = .wrong_edition,
+        .field = .venue,
+        .severity = .@"error",
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = try std.fmt.allocPrint(allocator, "Wrong edition of the venue: {s} vs {s}", .{ local_value, remote_value }),
+        .allocator = allocator,
+    };
+}
+
+/// "ICML 2019" with its year, if the venue name does not give it.
+fn describeEdition(allocator: std.mem.Allocator, e: *const Entry) ![]u8 {
+    const venue = e.venue.?;
+    if (e.year) |y| {
+        if (venues.edition(venue).year == null) return std.fmt.allocPrint(allocator, "{s} ({d})", .{ venue, y });
+    }
+    return allocator.dupe(u8, venue);
+}
+
+/// Whether the entry and the matched record name the same edition of a
+/// book, whose year the edition comparator checks.
+fn sameBookEdition(local: *const Entry, remote: *const Entry) bool {
+    const stated = editions.ofEntry(local) orelse return false;
+    return editions.ofEntry(remote) == stated;
+}
+
+/// The edition a book is cited as against the matched record's, and the
+/// year against the year of that edition.
+fn compareBookEdition(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const stated = editions.ofEntry(local) orelse return null;
+    const found = editions.ofEntry(remote) orelse return null;
+    var stated_buf: [16]u8 = undefined;
+    var found_buf: [16]u8 = undefined;
+
+    if (stated != found) {
+        const msg = try std.fmt.allocPrint(allocator, "Edition differs: {s} vs {s}", .{ editions.ordinal(&stated_buf, stated), editions.ordinal(&found_buf, found) });
+        return .{
+            .rule = .book_edition,
+            .field = .edition,
+            .severity = .@"error",
+            .local_value = try allocator.dupe(u8, local.extraField("edition").?),
+            .remote_value = try allocator.dupe(u8, remote.extraField("edition").?),
+            .message = msg,
             .allocator = allocator,
-        });
+        };
+    }
+
+    if ((yearDistance(local, remote) orelse 0) == 0) return null;
+    const msg = try std.fmt.allocPrint(allocator, "The {s} edition appeared in {d}; {d} is the year of another edition", .{ editions.ordinal(&stated_buf, stated), remote.year.?, local.year.? });
+    return .{
+        .rule = .book_edition,
+        .field = .year,
+        .severity = .@"error",
+        .local_value = try std.fmt.allocPrint(allocator, "{d}", .{local.year.?}),
+        .remote_value = try std.fmt.allocPrint(allocator, "{d}", .{remote.year.?}),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// A cited software version that is not a tag of the matched repository,
+/// which GitHub records give as the "untagged_version" extra field, with
+/// the tag of the latest release as "latest_release".
+fn compareVersion(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const untagged = remote.extraField("untagged_version") orelse return null;
+    const version = github.citedVersion(local) orelse return null;
+    if (!github.sameVersion(version, untagged)) return null;
+
+    const latest = remote.extraField("latest_release");
+    return .{
+        .rule = .unknown_version,
+        .field = .version,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, version),
+        .remote_value = try allocator.dupe(u8, latest orelse ""),
+        .message = if (latest) |l|
+            try std.fmt.allocPrint(allocator, "Version {s} is not a tag of the repository (latest release: {s})", .{ version, l })
+        else
+            try std.fmt.allocPrint(allocator, "Version {s} is not a tag of the repository", .{version}),
+        .allocator = allocator,
+    };
+}
+
+/// A Zenodo or Figshare DOI that does not name the cited version: the
+/// concept DOI, which resolves to the latest version, or the DOI of another
+/// version. Deposit records carry `version` and `concept_doi` extra fields.
+fn compareGranularity(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const concept = remote.extraField("concept_doi") orelse return null;
+    const doi = local.doi orelse return null;
+    const version = local.extraField("version") orelse return null;
+    const found = remote.extraField("version") orelse return null;
+    const same_version = github.sameVersion(version, found);
+
+    var remote_value: []const u8 = "";
+    const msg = if (std.ascii.eqlIgnoreCase(doi, concept)) blk: {
+        if (!same_version) break :blk try std.fmt.allocPrint(allocator, "DOI {s} names all versions of the deposit, not version {s}", .{ doi, version });
+        remote_value = remote.doi orelse "";
+        break :blk try std.fmt.allocPrint(allocator, "DOI {s} names all versions of the deposit; version {s} has its own DOI {s}", .{ doi, version, remote_value });
+    } else if (!same_version and remote.doi != null and std.ascii.eqlIgnoreCase(doi, remote.doi.?))
+        try std.fmt.allocPrint(allocator, "DOI {s} is of version {s}, not version {s}", .{ doi, found, version })
+    else
+        return null;
+
+    return .{
+        .rule = .doi_granularity,
+        .field = .doi,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, doi),
+        .remote_value = try allocator.dupe(u8, remote_value),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// A DOI recovered from the url or note belongs in a doi field, and one in
+/// the doi field should be written bare and in lower case.
+fn compareDoiField(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const field = local.doi_field orelse return null;
+    if (std.mem.eql(u8, field, "doi")) return .{
+        .rule = .misplaced_doi,
+        .field = .doi,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, local.doi.?),
+        .remote_value = try allocator.dupe(u8, local.doi.?),
+        .message = try std.fmt.allocPrint(allocator, "DOI should be written bare and in lower case: {s}", .{local.doi.?}),
+        .allocator = allocator,
+    };
+    const doi = remote.doi orelse local.doi.?;
+    const msg = if (std.ascii.eqlIgnoreCase(doi, local.doi.?))
+        try std.fmt.allocPrint(allocator, "DOI is in the {s} field; move it to a doi field", .{field})
+    else
+        try std.fmt.allocPrint(allocator, "DOI in the {s} field differs from the matched record", .{field});
+    return .{
+        .rule = .misplaced_doi,
+        .field = .doi,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, local.doi.?),
+        .remote_value = try allocator.dupe(u8, doi),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+fn compareArxivField(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, _: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const field = local.arxiv_field orelse return null;
+    const msg = if (std.mem.eql(u8, field, "eprint"))
+        try allocator.dupe(u8, "arXiv eprint needs a bare ID and archivePrefix")
+    else
+        try std.fmt.allocPrint(allocator, "arXiv ID is in the {s} field; use eprint and archivePrefix", .{field});
+    return .{
+        .rule = .misplaced_arxiv,
+        .field = .eprint,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, local.arxiv_id.?),
+        .remote_value = try allocator.dupe(u8, local.arxiv_id.?),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+fn compareMissingDoi(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (local.doi != null or remote.doi == null) return null;
+    return .{
+        .rule = .missing_doi,
+        .field = .doi,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, "(none)"),
+        .remote_value = try allocator.dupe(u8, remote.doi.?),
+        .message = try allocator.dupe(u8, "Missing DOI in local entry"),
+        .allocator = allocator,
+    };
+}
+
+/// Corrections published for the matched work, which CrossRef records
+/// list as the "erratum" extra field.
+fn compareErratum(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const errata = remote.extraField("erratum") orelse return null;
+    // The entry cites the correction itself
+    if (local.doi) |doi| {
+        if (std.ascii.indexOfIgnoreCase(errata, doi) != null) return null;
     }
+    const several = std.mem.indexOf(u8, errata, ", ") != null;
+    return .{
+        .rule = .erratum,
+        .field = .doi,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, local.doi orelse "(none)"),
+        .remote_value = try allocator.dupe(u8, errata),
+        .message = try std.fmt.allocPrint(allocator, "{s} published for this work: {s}", .{ if (several) "Corrections were" else "A correction was", errata }),
+        .allocator = allocator,
+    };
+}
+
+/// Crossmark notices for the matched work other than corrections, which
+/// CrossRef records list as the "updates" extra field: new versions,
+/// addenda, expressions of concern, retractions.
+fn compareUpdates(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const updates = remote.extraField("updates") orelse return null;
+
+    var listed: std.ArrayList(u8) = .empty;
+    defer listed.deinit(allocator);
+    var count: usize = 0;
+    var retracted = false;
+    var notices = std.mem.splitSequence(u8, updates, ", ");
+    while (notices.next()) |notice| {
+        const space = std.mem.indexOfScalar(u8, notice, ' ') orelse continue;
+        const kind = notice[0..space];
+        const doi = notice[space + 1 ..];
+        // The entry cites the update itself, e.g. the new version
+        if (local.doi) |cited| {
+            if (std.ascii.eqlIgnoreCase(cited, doi)) continue;
+        }
+        for ([_][]const u8{ "retraction", "partial_retraction", "withdrawal", "removal" }) |name| {
+            if (std.mem.eql(u8, kind, name)) retracted = true;
+        }
+        if (count > 0) try listed.appendSlice(allocator, "; ");
+        for (kind) |c| try listed.append(allocator, if (c == '_') ' ' else c);
+        try listed.print(allocator, " https://doi.org/{s}", .{doi});
+        count += 1;
+    }
+    if (count == 0) return null;
+
+    return .{
+        .rule = .crossmark_update,
+        .field = .doi,
+        .severity = if (retracted) .@"error" else .info,
+        .local_value = try allocator.dupe(u8, local.doi orelse "(none)"),
+        .remote_value = try allocator.dupe(u8, updates),
+        .message = try std.fmt.allocPrint(allocator, "Crossmark lists {s} to this work: {s}", .{ if (count > 1) "updates" else "an update", listed.items }),
+        .allocator = allocator,
+    };
+}
 
-    // Compare author counts
-    if (local.authors.len > 0 and remote.authors.len > 0 and local.authors.len != remote.authors.len) {
-        const msg = try std.fmt.allocPrint(allocator, "Author count differs: {d} (local) vs {d} (remote)", .{ local.authors.len, remote.authors.len });
-        try discrepancies.append(allocator, .{
-            .field = .authors,
+fn compareAuthorCount(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (local.authors.len == 0 or remote.authors.len == 0 or local.authors.len == remote.authors.len) return null;
+    // Reported as missing authors instead
+    if (try lacksAuthors(allocator, local, remote)) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Author count differs: {d} (local) vs {d} (remote)", .{ local.authors.len, remote.authors.len });
+    return .{
+        .rule = .author_count,
+        .field = .authors,
+        .severity = .warning,
+        .local_value = try std.fmt.allocPrint(allocator, "{d} authors", .{local.authors.len}),
+        .remote_value = try std.fmt.allocPrint(allocator, "{d} authors", .{remote.authors.len}),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// A wrong first author is reported even when the author lists overlap
+/// enough to match: "Smith et al." is cited by its first author.
+fn compareFirstAuthor(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (local.authors.len == 0 or remote.authors.len == 0) return null;
+
+    var local_norm = try NormalizedEntry.init(allocator, local);
+    defer local_norm.deinit();
+    var remote_norm = try NormalizedEntry.init(allocator, remote);
+    defer remote_norm.deinit();
+
+    // Where the local first author is in the remote list, if anywhere
+    const position = for (remote_norm.authors, remote_norm.names, 0..) |remote_author, *remote_name, i| {
+        if (try sameAuthor(allocator, jaro_winkler, local_norm.authors[0], &local_norm.names[0], remote_author, remote_name)) break i;
+    } else null;
+    if (position == 0) return null;
+
+    const msg = if (position) |i|
+        try std.fmt.allocPrint(allocator, "First author differs: {s} is author {d} of the matched record", .{ local.authors[0], i + 1 })
+    else
+        try std.fmt.allocPrint(allocator, "First author differs: {s} vs {s}", .{ local.authors[0], remote.authors[0] });
+    return .{
+        .rule = .first_author,
+        .field = .authors,
+        .severity = .@"error",
+        .local_value = try allocator.dupe(u8, local.authors[0]),
+        .remote_value = try allocator.dupe(u8, remote.authors[0]),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// Remote authors the local entry leaves out, as when an entry was copied
+/// before authors were added for the camera-ready version. The suggested
+/// author list keeps the local names and order, with each missing author
+/// inserted after the one the remote record lists before them.
+fn compareMissingAuthors(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (!try lacksAuthors(allocator, local, remote)) return null;
+
+    var local_norm = try NormalizedEntry.init(allocator, local);
+    defer local_norm.deinit();
+    var remote_norm = try NormalizedEntry.init(allocator, remote);
+    defer remote_norm.deinit();
+
+    var merged: std.ArrayList([]const u8) = .empty;
+    defer merged.deinit(allocator);
+    var missing: std.ArrayList([]const u8) = .empty;
+    defer missing.deinit(allocator);
+    var emitted: usize = 0;
+    for (remote_norm.authors, remote_norm.names, remote.authors) |remote_author, *remote_name, raw| {
+        const found = for (local_norm.authors, local_norm.names, 0..) |local_author, *local_name, i| {
+            if (try sameAuthor(allocator, jaro_winkler, local_author, local_name, remote_author, remote_name)) break i;
+        } else null;
+        if (found) |i| {
+            if (i >= emitted) {
+                try merged.appendSlice(allocator, local.authors[emitted .. i + 1]);
+                emitted = i + 1;
+            }
+        } else {
+            try merged.append(allocator, raw);
+            try missing.append(allocator, raw);
+        }
+    }
+    if (missing.items.len == 0) return null;
+    try merged.appendSlice(allocator, local.authors[emitted..]);
+
+    const listed = try std.mem.join(allocator, ", ", missing.items);
+    defer allocator.free(listed);
+    const msg = if (missing.items.len == 1)
+        try std.fmt.allocPrint(allocator, "Author missing from the local entry: {s}", .{listed})
+    else
+        try std.fmt.allocPrint(allocator, "{d} authors missing from the local entry: {s}", .{ missing.items.len, listed });
+    errdefer allocator.free(msg);
+    const local_value = try std.mem.join(allocator, " and ", local.authors);
+    errdefer allocator.free(local_value);
+    return .{
+        .rule = .missing_author,
+        .field = .authors,
+        .severity = .warning,
+        .local_value = local_value,
+        .remote_value = try std.mem.join(allocator, " and ", merged.items),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// Whether the remote record lists every local author and more: the local
+/// list leaves authors out rather than spelling one differently, which
+/// would put both spellings in the suggested list.
+fn lacksAuthors(allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!bool {
+    if (local.authors.len == 0 or remote.authors.len <= local.authors.len) return false;
+    // "and others" shortens the list on purpose
+    if (std.ascii.eqlIgnoreCase(local.authors[local.authors.len - 1], "others")) return false;
+
+    var local_norm = try NormalizedEntry.init(allocator, local);
+    defer local_norm.deinit();
+    var remote_norm = try NormalizedEntry.init(allocator, remote);
+    defer remote_norm.deinit();
+
+    for (local_norm.authors, local_norm.names) |local_author, *local_name| {
+        const found = for (remote_norm.authors, remote_norm.names) |remote_author, *remote_name| {
+            if (try sameAuthor(allocator, jaro_winkler, local_author, local_name, remote_author, remote_name)) break true;
+        } else false;
+        if (!found) return false;
+    }
+    return true;
+}
+
+/// Compare venues the dictionary knows, offering both forms of the name.
+/// An abbreviated journal name must abbreviate the remote title. Other
+/// venue names are compared only with a configured metric.
+fn compareVenue(ctx: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (local.venue == null or remote.venue == null) return null;
+    if (differentEdition(local, remote) or workshopSwapped(local, remote)) return null;
+
+    if (venues.lookup(remote.venue.?)) |known| {
+        if (known.matches(local.venue.?)) return null;
+        const msg = try std.fmt.allocPrint(allocator, "Venue differs: expected \"{s}\" or \"{s}\"", .{ known.full, known.abbrev });
+        return .{
+            .rule = .venue_mismatch,
+            .field = .venue,
             .severity = .warning,
-            .local_value = try std.fmt.allocPrint(allocator, "{d} authors", .{local.authors.len}),
-            .remote_value = try std.fmt.allocPrint(allocator, "{d} authors", .{remote.authors.len}),
+            .local_value = try allocator.dupe(u8, local.venue.?),
+            .remote_value = try allocator.dupe(u8, remote.venue.?),
             .message = msg,
             .allocator = allocator,
-        });
+        };
     }
 
-    return discrepancies.toOwnedSlice(allocator);
+    if (!std.ascii.eqlIgnoreCase(local.entry_type, "article") or
+        !venues.looksAbbreviated(local.venue.?) or venues.looksAbbreviated(remote.venue.?))
+    {
+        return compareVenueNames(allocator, comparatorMetric(ctx) orelse return null, local, remote);
+    }
+    if (try iso4.matchesTitle(allocator, local.venue.?, remote.venue.?)) return null;
+
+    const expected = try iso4.abbreviate(allocator, remote.venue.?);
+    defer allocator.free(expected);
+    const msg = try std.fmt.allocPrint(allocator, "Journal abbreviation does not match \"{s}\" (expected \"{s}\")", .{ remote.venue.?, expected });
+    return .{
+        .rule = .venue_mismatch,
+        .field = .venue,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, local.venue.?),
+        .remote_value = try allocator.dupe(u8, remote.venue.?),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// Whether one venue is a workshop of the conference the other names: a
+/// workshop paper cited as a main-conference paper, or the reverse. Only
+/// matched records of proceedings papers are taken to be either; a journal
+/// article or a book whose venue names a workshop is neither.
+fn workshopSwapped(local: *const Entry, remote: *const Entry) bool {
+    if (!proceedingsRecord(remote)) return false;
+    const local_venue = local.venue orelse return false;
+    const remote_venue = remote.venue orelse return false;
+    return venues.workshopOf(local_venue, remote_venue) or venues.workshopOf(remote_venue, local_venue);
+}
+
+/// Whether a matched record is a paper in conference or workshop
+/// proceedings, by the type its source gives: "proceedings-article" in
+/// CrossRef, "Conference and Workshop Papers" in DBLP.
+fn proceedingsRecord(e: *const Entry) bool {
+    for ([_][]const u8{ "inproceedings", "conference", "proceedings-article" }) |t| {
+        if (std.ascii.eqlIgnoreCase(e.entry_type, t)) return true;
+    }
+    return std.ascii.indexOfIgnoreCase(e.entry_type, "conference") != null;
+}
+
+fn compareWorkshop(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    if (!workshopSwapped(local, remote)) return null;
+    const msg = if (venues.isWorkshop(local.venue.?))
+        try std.fmt.allocPrint(allocator, "Cited as a workshop paper, but the matched record appeared in the main conference: {s}", .{remote.venue.?})
+    else
+        try std.fmt.allocPrint(allocator, "Cited as a main-conference paper, but the matched record appeared in a workshop: {s}", .{remote.venue.?});
+    return .{
+        .rule = .workshop_venue,
+        .field = .venue,
+        .severity = .@"error",
+        .local_value = try allocator.dupe(u8, local.venue.?),
+        .remote_value = try allocator.dupe(u8, remote.venue.?),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+fn compareVenueNames(allocator: std.mem.Allocator, metric: Similarity, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const local_norm = try normalizeString(allocator, local.venue.?);
+    defer allocator.free(local_norm);
+    const remote_norm = try normalizeString(allocator, remote.venue.?);
+    defer allocator.free(remote_norm);
+
+    const similarity = try metric.compare(allocator, local_norm, remote_norm);
+    if (similarity >= VENUE_MATCH_THRESHOLD) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Venue differs (similarity: {d:.0}%)", .{similarity * 100.0});
+    return .{
+        .rule = .venue_mismatch,
+        .field = .venue,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, local.venue.?),
+        .remote_value = try allocator.dupe(u8, remote.venue.?),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+fn compareVolume(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const local_volume = local.extraField("volume") orelse return null;
+    const remote_volume = remote.extraField("volume") orelse return null;
+    if (std.ascii.eqlIgnoreCase(std.mem.trim(u8, local_volume, " "), std.mem.trim(u8, remote_volume, " "))) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Volume mismatch: {s} vs {s}", .{ local_volume, remote_volume });
+    return .{
+        .rule = .volume_mismatch,
+        .field = .volume,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, local_volume),
+        .remote_value = try allocator.dupe(u8, remote_volume),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// Page ranges agree if they differ only in spacing and dashes, e.g.
+/// "1-10" and "1--10".
+fn comparePages(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const local_pages = local.extraField("pages") orelse return null;
+    const remote_pages = remote.extraField("pages") orelse return null;
+    if (samePages(local_pages, remote_pages)) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Pages mismatch: {s} vs {s}", .{ local_pages, remote_pages });
+    return .{
+        .rule = .pages_mismatch,
+        .field = .pages,
+        .severity = .warning,
+        .local_value = try allocator.dupe(u8, local_pages),
+        .remote_value = try allocator.dupe(u8, remote_pages),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+pub fn samePages(a: []const u8, b: []const u8) bool {
+    var ia = pageTokens(a);
+    var ib = pageTokens(b);
+    while (true) {
+        const ta = ia.next();
+        const tb = ib.next();
+        if (ta == null or tb == null) return ta == null and tb == null;
+        if (!std.ascii.eqlIgnoreCase(ta.?, tb.?)) return false;
+    }
+}
+
+/// Page numbers of a range, skipping spaces and ASCII or Unicode dashes.
+fn pageTokens(pages: []const u8) std.mem.TokenIterator(u8, .any) {
+    // en dash and em dash are E2 80 93 and E2 80 94
+    return std.mem.tokenizeAny(u8, pages, " -\xe2\x80\x93\x94");
+}
+
+/// The entry's `langid` or `language` against the language its title is
+/// written in, or the matched record's if the title does not tell.
+fn compareLanguage(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const stated = language.ofEntry(local) orelse return null;
+    const written = written: {
+        if (local.title) |title| {
+            if (language.detect(title)) |lang| break :written lang;
+        }
+        // A record with two titles does not say which one its language is of
+        if (remote.extraField(language.ORIGINAL_TITLE_FIELD) != null) return null;
+        break :written language.parse(remote.extraField(language.FIELD) orelse return null) orelse return null;
+    };
+    if (stated == written) return null;
+
+    const msg = try std.fmt.allocPrint(allocator, "Language is {s}, but the title is in {s}", .{ stated.name(), written.name() });
+    return .{
+        .rule = .language_mismatch,
+        .field = .language,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, stated.name()),
+        .remote_value = try allocator.dupe(u8, written.name()),
+        .message = msg,
+        .allocator = allocator,
+    };
+}
+
+/// An entry citing a translated work by its translated title, but with
+/// the venue the original appeared in.
+fn compareTranslation(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    const title = local.title orelse return null;
+    const venue = local.venue orelse return null;
+    if (remote.title == null) return null;
+    const original = remote.extraField(language.ORIGINAL_TITLE_FIELD) orelse return null;
+    // biblatex entries of translations give both titles on purpose
+    if (local.extraField(language.ORIGINAL_TITLE_FIELD) != null) return null;
+
+    const local_norm = try normalizeString(allocator, title);
+    defer allocator.free(local_norm);
+    const closer = try closerTitle(allocator, jaro_winkler, local_norm, remote);
+    defer allocator.free(closer.normalized);
+    if (closer.original) return null;
+
+    const original_language = language.detect(original) orelse return null;
+    const venue_language = language.detect(venue) orelse return null;
+    if (venue_language != original_language) return null;
+    if (language.detect(title)) |lang| {
+        if (lang == original_language) return null;
+    }
+
+    const msg = try std.fmt.allocPrint(allocator, "Translated title cited with the {s} venue of the original", .{original_language.name()});
+    return .{
+        .rule = .mixed_translation,
+        .field = .title,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, title),
+        .remote_value = try allocator.dupe(u8, original),
+        .message = msg,
+        .allocator = allocator,
+    };
 }
 
 /// Result of finding a best match.
@@ -267,11 +1528,17 @@ pub const MatchResult = struct {
 };
 
 /// Find the best matching entry from a list of candidates.
-pub fn findBestMatch(allocator: std.mem.Allocator, target: *const Entry, candidates: []const Entry) !?MatchResult {
+pub fn findBestMatch(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const Entry, candidates: []const Entry) !?MatchResult {
     var best: ?MatchResult = null;
 
+    var target_norm = try NormalizedEntry.init(allocator, target);
+    defer target_norm.deinit();
+
     for (candidates) |*candidate| {
-        const score = try matchScore(allocator, target, candidate);
+        var candidate_norm = try NormalizedEntry.init(allocator, candidate);
+        defer candidate_norm.deinit();
+
+        const score = try normalizedMatchScore(allocator, config, &target_norm, &candidate_norm);
         if (score > 0.0) {
             if (best == null or score > best.?.score) {
                 best = .{ .entry = candidate, .score = score };
@@ -282,6 +1549,521 @@ pub fn findBestMatch(allocator: std.mem.Allocator, target: *const Entry, candida
     return best;
 }
 
+/// A candidate rejected by `matchScore`, with the score it would have had.
+pub const RejectedCandidate = struct {
+    entry: *const Entry,
+    score: f64,
+    reason: Rejection,
+};
+
+/// The `limit` best-scoring candidates that `matchScore` rejects, best
+/// first. Entries are borrowed from `candidates`; the caller frees the slice.
+pub fn topRejected(allocator: std.mem.Allocator, config: *const MatcherConfig, target: *const Entry, candidates: []const Entry, limit: usize) ![]RejectedCandidate {
+    var rejected: std.ArrayList(RejectedCandidate) = .empty;
+    errdefer rejected.deinit(allocator);
+
+    var target_norm = try NormalizedEntry.init(allocator, target);
+    defer target_norm.deinit();
+
+    for (candidates) |*candidate| {
+        var candidate_norm = try NormalizedEntry.init(allocator, candidate);
+        defer candidate_norm.deinit();
+
+        const scored = try scoreCandidate(allocator, config, &target_norm, &candidate_norm);
+        const reason = scored.rejection orelse continue;
+        try rejected.append(allocator, .{ .entry = candidate, .score = scored.score, .reason = reason });
+    }
+
+    std.sort.pdq(RejectedCandidate, rejected.items, {}, higherScore);
+    rejected.shrinkRetainingCapacity(@min(rejected.items.len, limit));
+    return rejected.toOwnedSlice(allocator);
+}
+
+fn higherScore(_: void, a: RejectedCandidate, b: RejectedCandidate) bool {
+    return a.score > b.score;
+}
+
+test "topRejected explains near misses" {
+    const allocator = std.testing.allocator;
+
+    const target = Entry{ .key = "t", .entry_type = "article", .title = "Attention is all you need", .year = 2017 };
+    const candidates = [_]Entry{
+        .{ .key = "a", .entry_type = "article", .title = "Attention is all you need", .year = 2030 },
+        .{ .key = "b", .entry_type = "article", .title = "Graph networks for chemistry", .year = 2017 },
+        .{ .key = "c", .entry_type = "article", .title = "Attention is all you need", .year = 2017 },
+    };
+
+    const rejected = try topRejected(allocator, &MatcherConfig{}, &target, &candidates, 3);
+    defer allocator.free(rejected);
+
+    try std.testing.expectEqual(@as(usize, 2), rejected.len);
+    try std.testing.expectEqualStrings("a", rejected[0].entry.key);
+    try std.testing.expectEqual(Rejection.year_incompatible, rejected[0].reason);
+    try std.testing.expectEqual(Rejection.title_below_threshold, rejected[1].reason);
+}
+
+test "compareWith runs custom comparators" {
+    const allocator = std.testing.allocator;
+
+    const Publisher = struct {
+        fn compare(_: ?*anyopaque, alloc: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+            const a = local.publisher orelse return null;
+            const b = remote.publisher orelse return null;
+            if (std.mem.eql(u8, a, b)) return null;
+            return .{
+                .rule = .custom_check,
+                .field = .publisher,
+                .severity = .warning,
+                .local_value = try alloc.dupe(u8, a),
+                .remote_value = try alloc.dupe(u8, b),
+                .message = try alloc.dupe(u8, "Publisher differs"),
+                .allocator = alloc,
+            };
+        }
+    };
+
+    const local = Entry{ .key = "l", .entry_type = "article", .title = "Deep Learning", .year = 2015, .publisher = "Springer" };
+    const remote = Entry{ .key = "r", .entry_type = "article", .title = "Deep Learning", .year = 2016, .publisher = "Nature" };
+
+    // The year check is left out
+    const comparators = [_]FieldComparator{ title_comparator, .{ .name = "publisher", .compareFn = Publisher.compare } };
+    const found = try compareWith(allocator, &comparators, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .custom_check);
+
+    const defaults = try compareEntries(allocator, &local, &remote);
+    defer {
+        for (defaults) |*d| d.deinit();
+        allocator.free(defaults);
+    }
+    try std.testing.expectEqual(@as(usize, 1), defaults.len);
+    try std.testing.expect(defaults[0].rule == .year_mismatch);
+}
+
+test "another edition or a workshop of a conference" {
+    const allocator = std.testing.allocator;
+    const local = Entry{ .key = "l", .entry_type = "inproceedings", .title = "Deep Sets", .year = 2019, .venue = "ICML 2019" };
+    const remote = Entry{ .key = "r", .entry_type = "inproceedings", .title = "Deep Sets", .year = 2020, .venue = "Proceedings of the 37th International Conference on Machine Learning, ICML 2020" };
+
+    const found = try compareEntries(allocator, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .wrong_edition);
+    try std.testing.expectEqualStrings("ICML 2019", found[0].local_value);
+
+    // Without the year in the matched venue, only the entry years differ
+    const undated = Entry{ .key = "u", .entry_type = "inproceedings", .title = "Deep Sets", .year = 2020, .venue = "Proceedings of the International Conference on Machine Learning" };
+    const year_only = try compareEntries(allocator, &local, &undated);
+    defer {
+        for (year_only) |*d| d.deinit();
+        allocator.free(year_only);
+    }
+    for (year_only) |d| try std.testing.expect(d.rule != .wrong_edition);
+    try std.testing.expect(year_only[0].rule == .year_mismatch);
+
+    const workshop = Entry{ .key = "w", .entry_type = "inproceedings", .title = "Deep Sets", .year = 2020, .venue = "ICML 2020 Workshop on Uncertainty and Robustness" };
+    const swapped = try compareEntries(allocator, &workshop, &remote);
+    defer {
+        for (swapped) |*d| d.deinit();
+        allocator.free(swapped);
+    }
+    try std.testing.expectEqual(@as(usize, 1), swapped.len);
+    try std.testing.expect(swapped[0].rule == .workshop_venue);
+
+    // A journal article is no main-conference paper, whatever the names
+    const article = Entry{ .key = "a", .entry_type = "journal-article", .title = "Deep Sets", .year = 2020, .venue = "Proceedings of the 37th International Conference on Machine Learning, ICML 2020" };
+    const not_swapped = try compareEntries(allocator, &workshop, &article);
+    defer {
+        for (not_swapped) |*d| d.deinit();
+        allocator.free(not_swapped);
+    }
+    for (not_swapped) |d| try std.testing.expect(d.rule != .workshop_venue);
+}
+
+test "volume and page checks" {
+    const allocator = std.testing.allocator;
+    try std.testing.expect(samePages("1-10", "1--10"));
+    try std.testing.expect(samePages("1 \xe2\x80\x93 10", "1-10"));
+    try std.testing.expect(!samePages("1-10", "1-11"));
+    try std.testing.expect(!samePages("1-10", "1"));
+
+    const local = Entry{ .key = "a", .entry_type = "inproceedings", .extra = &.{ .{ .name = "volume", .value = "139" }, .{ .name = "pages", .value = "1-10" } } };
+    const remote = Entry{ .key = "b", .entry_type = "inproceedings", .extra = &.{ .{ .name = "volume", .value = "119" }, .{ .name = "pages", .value = "1--10" } } };
+    const found = try compareEntries(allocator, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expectEqual(DiscrepancyField.volume, found[0].field);
+}
+
+test "titles differing only in punctuation" {
+    const allocator = std.testing.allocator;
+    try std.testing.expect(sameLetters("self supervised learning", "selfsupervised learning"));
+    try std.testing.expect(!sameLetters("self supervised learning", "self supervised learner"));
+
+    // Short titles must match exactly, so this would be an error
+    const local = Entry{ .key = "a", .entry_type = "article", .title = "Self-Attention Networks" };
+    const remote = Entry{ .key = "b", .entry_type = "article", .title = "Self Attention Networks" };
+    const found = try compareEntries(allocator, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .title_punctuation);
+    try std.testing.expectEqual(Severity.info, found[0].severity);
+}
+
+test "translated works match by either title" {
+    const allocator = std.testing.allocator;
+
+    const remote = Entry{
+        .key = "r",
+        .entry_type = "article",
+        .title = "The Protestant Ethic and the Spirit of Capitalism",
+        .venue = "Archiv für Sozialwissenschaft und Sozialpolitik",
+        .extra = &.{
+            .{ .name = "language", .value = "de" },
+            .{ .name = "origtitle", .value = "Die protestantische Ethik und der Geist des Kapitalismus" },
+        },
+    };
+
+    // Cited by the original title, but marked as English
+    const original = Entry{
+        .key = "a",
+        .entry_type = "article",
+        .title = "Die protestantische Ethik und der Geist des Kapitalismus",
+        .venue = "Archiv für Sozialwissenschaft und Sozialpolitik",
+        .extra = &.{.{ .name = "langid", .value = "english" }},
+    };
+    const config = MatcherConfig{};
+    const breakdown = try scoreBreakdown(allocator, &config, &original, &remote);
+    try std.testing.expectApproxEqAbs(@as(f64, 1.0), breakdown.title, 1e-9);
+    const found = try compareEntries(allocator, &original, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .language_mismatch);
+    try std.testing.expectEqualStrings("German", found[0].remote_value);
+
+    // Cited by the translated title, with the venue of the original
+    const mixed = Entry{
+        .key = "b",
+        .entry_type = "article",
+        .title = "The Protestant Ethic and the Spirit of Capitalism",
+        .venue = "Archiv für Sozialwissenschaft und Sozialpolitik",
+    };
+    const mixed_found = try compareEntries(allocator, &mixed, &remote);
+    defer {
+        for (mixed_found) |*d| d.deinit();
+        allocator.free(mixed_found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), mixed_found.len);
+    try std.testing.expect(mixed_found[0].rule == .mixed_translation);
+    try std.testing.expectEqual(Severity.info, mixed_found[0].severity);
+}
+
+test "year difference lowers the match score" {
+    const allocator = std.testing.allocator;
+    const config = MatcherConfig{};
+
+    const book = Entry{ .key = "b", .entry_type = "book", .title = "Pattern recognition and machine learning", .year = 2006 };
+    const same = Entry{ .key = "s", .entry_type = "book", .title = "Pattern recognition and machine learning", .year = 2006 };
+    const reprint = Entry{ .key = "r", .entry_type = "book", .title = "Pattern recognition and machine learning", .year = 2011 };
+
+    const exact = try matchScore(allocator, &config, &book, &same);
+    const later = try matchScore(allocator, &config, &book, &reprint);
+    try std.testing.expect(later > 0.0);
+    try std.testing.expect(later < exact);
+
+    const strict = MatcherConfig{ .max_year_distance = 2 };
+    try std.testing.expectEqual(@as(f64, 0.0), try matchScore(allocator, &strict, &book, &reprint));
+}
+
+test "date ranges and approximate years" {
+    const allocator = std.testing.allocator;
+
+    const range = Entry{ .key = "r", .entry_type = "article", .year = 2019, .year_end = 2020, .year_field = "date", .extra = &.{.{ .name = "date", .value = "2019/2020" }} };
+    const circa = Entry{ .key = "c", .entry_type = "article", .year = 2019, .year_approximate = true };
+    const within = Entry{ .key = "w", .entry_type = "article", .year = 2020 };
+    const later = Entry{ .key = "l", .entry_type = "article", .year = 2022 };
+
+    try std.testing.expectEqual(@as(?i32, 0), yearDistance(&range, &within));
+    try std.testing.expectEqual(@as(?i32, 0), yearDistance(&circa, &within));
+    try std.testing.expectEqual(@as(?i32, 2), yearDistance(&range, &later));
+    try std.testing.expect(try compareYear(null, allocator, &range, &within) == null);
+
+    var d = (try compareYear(null, allocator, &range, &later)).?;
+    defer d.deinit();
+    try std.testing.expectEqualStrings("2019/2020", d.local_value);
+    try std.testing.expectEqualStrings("Year mismatch: 2019/2020 vs 2022", d.message);
+}
+
+test "score breakdown follows weights and filters" {
+    const allocator = std.testing.allocator;
+
+    const local = Entry{ .key = "a", .entry_type = "article", .title = "Attention is all you need", .authors = &.{ "Ashish Vaswani", "Noam Shazeer" }, .year = 2017, .doi = "10.5555/3295222" };
+    const remote = Entry{ .key = "b", .entry_type = "article", .title = "Attention is all you need", .authors = &.{"Someone Else"}, .year = 2017, .doi = "10.5555/3295222" };
+
+    const defaults = MatcherConfig{};
+    const boosted = try scoreBreakdown(allocator, &defaults, &local, &remote);
+    try std.testing.expect(boosted.doi_match);
+    try std.testing.expectEqual(@as(f64, 1.0), boosted.score);
+    try std.testing.expectEqual(@as(f64, 0.0), boosted.authors);
+    try std.testing.expect(boosted.rejection.? == .author_overlap_low);
+    try std.testing.expectEqual(@as(f64, 0.0), boosted.final());
+
+    // Title only, no DOI boost, no author filter
+    const title_only = MatcherConfig{ .weights = .{ .title = 1, .authors = 0 }, .doi_boost = false, .filters = .{ .authors = false } };
+    const weighted = try scoreBreakdown(allocator, &title_only, &local, &remote);
+    try std.testing.expect(weighted.rejection == null);
+    try std.testing.expectApproxEqAbs(weighted.title, weighted.final(), 1e-9);
+    try std.testing.expectEqual(weighted.final(), try matchScore(allocator, &title_only, &local, &remote));
+}
+
+test "the cited edition of a book" {
+    const allocator = std.testing.allocator;
+    const authors: []const []const u8 = &.{ "Richard S. Sutton", "Andrew G. Barto" };
+    const local = Entry{ .key = "l", .entry_type = "book", .title = "Reinforcement Learning: An Introduction", .authors = authors, .year = 2018, .extra = &.{.{ .name = "edition", .value = "2nd" }} };
+    const candidates = [_]Entry{
+        .{ .key = "first", .entry_type = "book", .title = "Reinforcement Learning: An Introduction", .authors = authors, .year = 2017, .extra = &.{.{ .name = "edition", .value = "1" }} },
+        .{ .key = "second", .entry_type = "book", .title = "Reinforcement Learning: An Introduction", .authors = authors, .year = 2020, .extra = &.{.{ .name = "edition", .value = "2" }} },
+    };
+
+    // The second edition wins although its year is further off
+    const best = (try findBestMatch(allocator, &MatcherConfig{}, &local, &candidates)).?;
+    try std.testing.expectEqualStrings("second", best.entry.key);
+
+    const found = try compareEntries(allocator, &local, &candidates[1]);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .book_edition);
+    try std.testing.expect(found[0].field == .year);
+}
+
+test "title thresholds scale with length" {
+    const allocator = std.testing.allocator;
+    const config = MatcherConfig{};
+
+    try std.testing.expect(titleThresholds("Attention").match > titleThresholds("Attention is all you need").match);
+    try std.testing.expect(titleThresholds("A very long title with many words that goes on and on about things").match < TITLE_MATCH_THRESHOLD);
+
+    // A short title alone is not enough
+    const local = Entry{ .key = "l", .entry_type = "book", .title = "Deep Learning", .year = 2016, .authors = &.{"Goodfellow, Ian"} };
+    const anonymous = Entry{ .key = "a", .entry_type = "book", .title = "Deep Learning", .year = 2016 };
+    const confirmed = Entry{ .key = "c", .entry_type = "book", .title = "Deep Learning", .year = 2016, .authors = &.{ "Goodfellow, Ian", "Bengio, Yoshua" } };
+    try std.testing.expectEqual(@as(f64, 0.0), try matchScore(allocator, &config, &local, &anonymous));
+    try std.testing.expect(try matchScore(allocator, &config, &local, &confirmed) > 0.0);
+}
+
+test "NormalizedEntry matches like the entries it normalizes" {
+    const allocator = std.testing.allocator;
+    const config = MatcherConfig{};
+
+    const a = Entry{ .key = "a", .entry_type = "article", .title = "Attention Is All You Need!", .year = 2017, .authors = &.{ "Vaswani, Ashish", "Shazeer, Noam" } };
+    const b = Entry{ .key = "b", .entry_type = "article", .title = "Attention is all you need", .year = 2017, .authors = &.{"A. Vaswani"} };
+
+    var norm_a = try NormalizedEntry.init(allocator, &a);
+    defer norm_a.deinit();
+    var norm_b = try NormalizedEntry.init(allocator, &b);
+    defer norm_b.deinit();
+
+    try std.testing.expectEqualStrings("attention is all you need", norm_a.title.?);
+    try std.testing.expectEqualStrings("vaswani ashish", norm_a.authors[0]);
+    try std.testing.expectEqual(@as(usize, 5), norm_a.title_words);
+    try std.testing.expectEqual(try matchScore(allocator, &config, &a, &b), try normalizedMatchScore(allocator, &config, &norm_a, &norm_b));
+}
+
+test "author overlap compares names part by part" {
+    const allocator = std.testing.allocator;
+
+    const local = Entry{ .key = "l", .entry_type = "article", .authors = &.{ "Dupont, Jean-Pierre", "King, Jr., Martin Luther" } };
+    const remote = Entry{ .key = "r", .entry_type = "article", .authors = &.{ "Martin Luther King Jr.", "J. P. Dupont" } };
+    try std.testing.expectEqual(@as(f64, 1.0), try authorOverlap(allocator, &local, &remote));
+
+    const john = Entry{ .key = "j", .entry_type = "article", .authors = &.{"John Smith"} };
+    const jane = Entry{ .key = "k", .entry_type = "article", .authors = &.{"Smith, Jane"} };
+    try std.testing.expectEqual(@as(f64, 0.0), try authorOverlap(allocator, &john, &jane));
+}
+
+test "tokenSortRatio" {
+    const allocator = std.testing.allocator;
+
+    try std.testing.expectEqual(@as(f64, 1.0), try tokenSortRatio(allocator, "learning deep", "deep learning"));
+    // LCS of "abcd" and "acbd" is 3
+    try std.testing.expectApproxEqAbs(@as(f64, 0.75), try tokenSortRatio(allocator, "abcd", "acbd"), 1e-9);
+    try std.testing.expectEqual(@as(f64, 0.0), try tokenSortRatio(allocator, "abc", "xyz"));
+
+    // Strings longer than one 64-bit word
+    const long_a = "a" ** 70 ++ "b" ** 10;
+    const long_b = "a" ** 70 ++ "c" ** 10;
+    try std.testing.expectEqual(@as(usize, 70), try lcsLength(allocator, long_a, long_b));
+}
+
+test "software version not among the repository tags" {
+    const allocator = std.testing.allocator;
+    const local = Entry{ .key = "l", .entry_type = "software", .title = "toolkit", .url = "https://github.com/example/toolkit/tree/v1.4" };
+    const remote = Entry{ .key = "r", .entry_type = "software", .title = "toolkit", .extra = &.{
+        .{ .name = "untagged_version", .value = "v1.4" },
+        .{ .name = "latest_release", .value = "v1.3.0" },
+    } };
+
+    const found = try compareEntries(allocator, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .unknown_version);
+    try std.testing.expectEqualStrings("v1.3.0", found[0].remote_value);
+
+    const tagged = Entry{ .key = "t", .entry_type = "software", .title = "toolkit", .extra = &.{.{ .name = "version", .value = "1.2.0" }} };
+    const none = try compareEntries(allocator, &tagged, &remote);
+    defer allocator.free(none);
+    try std.testing.expectEqual(@as(usize, 0), none.len);
+}
+
+test "deposit DOI of the wrong granularity" {
+    const allocator = std.testing.allocator;
+    const remote = Entry{ .key = "r", .entry_type = "software", .title = "Example Toolkit", .doi = "10.5281/zenodo.1234570", .extra = &.{
+        .{ .name = "version", .value = "v2.0" },
+        .{ .name = "concept_doi", .value = "10.5281/zenodo.1234567" },
+    } };
+    const concept = Entry{ .key = "c", .entry_type = "software", .title = "Example Toolkit", .doi = "10.5281/zenodo.1234567", .extra = &.{.{ .name = "version", .value = "2.0" }} };
+
+    const found = try compareEntries(allocator, &concept, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .doi_granularity);
+    try std.testing.expectEqualStrings("10.5281/zenodo.1234570", found[0].remote_value);
+
+    const other = Entry{ .key = "o", .entry_type = "software", .title = "Example Toolkit", .doi = "10.5281/zenodo.1234570", .extra = &.{.{ .name = "version", .value = "1.0" }} };
+    const wrong = try compareEntries(allocator, &other, &remote);
+    defer {
+        for (wrong) |*d| d.deinit();
+        allocator.free(wrong);
+    }
+    try std.testing.expectEqual(@as(usize, 1), wrong.len);
+    try std.testing.expectEqualStrings("", wrong[0].remote_value);
+}
+
+test "Crossmark updates of the matched work" {
+    const allocator = std.testing.allocator;
+
+    const local = Entry{ .key = "a", .entry_type = "article", .doi = "10.1/paper" };
+    const updated = Entry{ .key = "r", .entry_type = "article", .extra = &.{.{ .name = "updates", .value = "new_version 10.1/paper.v2, addendum 10.1/add" }} };
+    var d = (try compareUpdates(null, allocator, &local, &updated)).?;
+    defer d.deinit();
+    try std.testing.expectEqual(Severity.info, d.severity);
+    try std.testing.expectEqualStrings("Crossmark lists updates to this work: new version https://doi.org/10.1/paper.v2; addendum https://doi.org/10.1/add", d.message);
+
+    const retracted = Entry{ .key = "r", .entry_type = "article", .extra = &.{.{ .name = "updates", .value = "retraction 10.1/notice" }} };
+    var r = (try compareUpdates(null, allocator, &local, &retracted)).?;
+    defer r.deinit();
+    try std.testing.expectEqual(Severity.@"error", r.severity);
+
+    const cites_update = Entry{ .key = "b", .entry_type = "article", .doi = "10.1/paper.v2" };
+    const only_version = Entry{ .key = "r", .entry_type = "article", .extra = &.{.{ .name = "updates", .value = "new_version 10.1/paper.v2" }} };
+    try std.testing.expect(try compareUpdates(null, allocator, &cites_update, &only_version) == null);
+}
+
+test "first author is weighted and checked" {
+    const allocator = std.testing.allocator;
+
+    const local = Entry{ .key = "a", .entry_type = "article", .authors = &.{ "Jane Smith", "Bob Jones", "Carol White", "Dan Black" } };
+    const swapped = Entry{ .key = "b", .entry_type = "article", .authors = &.{ "Bob Jones", "Jane Smith", "Carol White" } };
+    const other_first = Entry{ .key = "c", .entry_type = "article", .authors = &.{ "Eve Brown", "Bob Jones", "Carol White", "Dan Black" } };
+
+    var local_norm = try NormalizedEntry.init(allocator, &local);
+    defer local_norm.deinit();
+    var other_norm = try NormalizedEntry.init(allocator, &other_first);
+    defer other_norm.deinit();
+    try std.testing.expectApproxEqAbs(@as(f64, 0.75), try weightedAuthorOverlap(allocator, jaro_winkler, .{}, &local_norm, &other_norm), 1e-9);
+    try std.testing.expectApproxEqAbs(@as(f64, 0.5), try weightedAuthorOverlap(allocator, jaro_winkler, .{ .first_author = 3 }, &local_norm, &other_norm), 1e-9);
+
+    var reordered = (try compareFirstAuthor(null, allocator, &local, &swapped)).?;
+    defer reordered.deinit();
+    try std.testing.expect(reordered.rule == .first_author);
+    try std.testing.expectEqualStrings("First author differs: Jane Smith is author 2 of the matched record", reordered.message);
+
+    var replaced = (try compareFirstAuthor(null, allocator, &local, &other_first)).?;
+    defer replaced.deinit();
+    try std.testing.expectEqualStrings("First author differs: Jane Smith vs Eve Brown", replaced.message);
+
+    try std.testing.expect((try compareFirstAuthor(null, allocator, &local, &local)) == null);
+}
+
+test "remote authors missing locally" {
+    const allocator = std.testing.allocator;
+
+    const local = Entry{ .key = "a", .entry_type = "article", .authors = &.{ "Jane Smith", "Carol White" } };
+    const remote = Entry{ .key = "b", .entry_type = "article", .authors = &.{ "J. Smith", "Bob Jones", "C. White", "Dan Black" } };
+    var found = (try compareMissingAuthors(null, allocator, &local, &remote)).?;
+    defer found.deinit();
+    try std.testing.expectEqualStrings("2 authors missing from the local entry: Bob Jones, Dan Black", found.message);
+    try std.testing.expectEqualStrings("Jane Smith and Bob Jones and Carol White and Dan Black", found.remote_value);
+
+    const truncated = Entry{ .key = "c", .entry_type = "article", .authors = &.{ "Jane Smith", "others" } };
+    try std.testing.expect((try compareMissingAuthors(null, allocator, &truncated, &remote)) == null);
+
+    // One finding, not an author count as well
+    try std.testing.expect((try compareAuthorCount(null, allocator, &local, &remote)) == null);
+
+    // A misspelled author is not missing, so both spellings are not offered
+    const misspelled = Entry{ .key = "d", .entry_type = "article", .authors = &.{ "Jane Smith", "Carol Whitfield-Brown" } };
+    try std.testing.expect((try compareMissingAuthors(null, allocator, &misspelled, &remote)) == null);
+    var count = (try compareAuthorCount(null, allocator, &misspelled, &remote)).?;
+    count.deinit();
+}
+
+test "alternate similarity metrics" {
+    const allocator = std.testing.allocator;
+
+    try std.testing.expectApproxEqAbs(@as(f64, 0.8), try levenshteinSimilarity(allocator, "hello", "hallo"), 1e-9);
+    try std.testing.expectEqual(@as(f64, 1.0), try levenshteinSimilarity(allocator, "", ""));
+    try std.testing.expectEqual(@as(f64, 0.0), try levenshteinSimilarity(allocator, "abc", ""));
+
+    try std.testing.expectEqual(@as(f64, 1.0), try trigramSimilarity(allocator, "deep learning", "deep learning"));
+    try std.testing.expectEqual(@as(f64, 0.0), try trigramSimilarity(allocator, "abc", "xyz"));
+    try std.testing.expect(try trigramSimilarity(allocator, "learning deep", "deep learning") > 0.6);
+
+    try std.testing.expect(metricNamed("trigram") != null);
+    try std.testing.expect(metricNamed("soundex") == null);
+
+    // A venue metric flags venues the dictionary does not know
+    const comparators = try withSimilarity(allocator, &default_comparators, jaro_winkler, levenshtein);
+    defer {
+        for (comparators) |c| if (c.ctx) |ctx| allocator.destroy(@as(*Similarity, @ptrCast(@alignCast(ctx))));
+        allocator.free(comparators);
+    }
+    const local = Entry{ .key = "a", .entry_type = "inproceedings", .venue = "Workshop on Obscure Topics" };
+    const remote = Entry{ .key = "b", .entry_type = "inproceedings", .venue = "Symposium on Unrelated Things" };
+    const found = try compareWith(allocator, comparators, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 1), found.len);
+    try std.testing.expect(found[0].rule == .venue_mismatch);
+}
+
 test "jaroWinklerSimilarity" {
     const allocator = std.testing.allocator;
 
@@ -0,0 +1,193 @@
+//! Request latency and failures per lookup backend.
+//!
+//! Like the progress line, the metrics learn which backend a request
+//! belongs to from the pipeline's trace events; the HTTP client then
+//! reports the latency and outcome of every request it sends. Answers
+//! from the cache never reach the client, so they are not counted. The
+//! summary, the JSON report and `bibval serve` show the totals, which is
+//! what tuning `--request-interval` or finding a slow backend needs; the
+//! server's `/metrics` adds a latency histogram per backend.
+
+const std = @import("std");
+const ApiSource = @import("entry.zig").ApiSource;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+pub const BUCKETS_MS = [_]u64{ 50, 100, 250, 500, 1000, 2500, 5000, 10000 };
+
+/// Requests sent to one backend.
+pub const Backend = struct {
+    requests: u64 = 0,
+    /// Requests that failed, rate-limited and timed-out attempts included.
+    errors: u64 = 0,
+    total_ms: u64 = 0,
+    max_ms: u64 = 0,
+    /// Requests per latency bucket (see `BUCKETS_MS`); slower ones are
+    /// only in `requests`.
+    buckets: [BUCKETS_MS.len]u64 = @splat(0),
+
+    pub fn averageMs(self: Backend) u64 {
+        return if (self.requests == 0) 0 else self.total_ms / self.requests;
+    }
+};
+
+pub const Metrics = struct {
+    backends: std.EnumArray(ApiSource, Backend) = .initFill(.{}),
+    /// Backend of the lookup in flight.
+    active: ?ApiSource = null,
+
+    /// Note a pipeline event. Events scoped to a backend start a lookup of
+    /// it; the requests that follow are counted for that backend until an
+    /// event of another step, such as matching or a webhook, ends it.
+    pub fn event(self: *Metrics, scope: []const u8) void {
+        if (std.meta.stringToEnum(ApiSource, scope)) |source| {
+            self.active = source;
+        } else if (!isRequestScope(scope)) {
+            self.active = null;
+        }
+    }
+
+    /// End the lookup in flight once an entry is done.
+    pub fn endEntry(self: *Metrics) void {
+        self.active = null;
+    }
+
+    /// Count a request of the lookup in flight. Requests outside a lookup,
+    /// such as webhooks, are not counted.
+    pub fn request(self: *Metrics, elapsed_ms: u64, ok: bool) void {
+        const backend = self.backends.getPtr(self.active orelse return);
+        backend.requests += 1;
+        if (!ok) backend.errors += 1;
+        backend.total_ms += elapsed_ms;
+        backend.max_ms = @max(backend.max_ms, elapsed_ms);
+        for (BUCKETS_MS, 0..) |bound, i| {
+            if (elapsed_ms <= bound) {
+                backend.buckets[i] += 1;
+                break;
+            }
+        }
+    }
+
+    /// Requests sent to all backends.
+    pub fn totalRequests(self: *const Metrics) u64 {
+        var total: u64 = 0;
+        for (self.backends.values) |b| total += b.requests;
+        return total;
+    }
+
+    /// Print a table of the backends that were sent requests.
+    pub fn print(self: *const Metrics, writer: anytype) !void {
+        try writer.print("  {s:<18}{s:>8}{s:>8}{s:>8}{s:>8}\n", .{ "Backend", "Requests", "Errors", "Avg ms", "Max ms" });
+        for (std.enums.values(ApiSource)) |source| {
+            const b = self.backends.get(source);
+            if (b.requests == 0) continue;
+            try writer.print("  {s:<18}{d:>8}{d:>8}{d:>8}{d:>8}\n", .{ source.name(), b.requests, b.errors, b.averageMs(), b.max_ms });
+        }
+    }
+
+    /// Write the backends that were sent requests as a JSON object, e.g.
+    /// `{"crossref":{"requests":12,"errors":0,"avg_ms":230,"max_ms":810}}`.
+    pub fn writeJson(self: *const Metrics, writer: anytype) !void {
+        try writer.writeAll("{");
+        var first = true;
+        for (std.enums.values(ApiSource)) |source| {
+            const b = self.backends.get(source);
+            if (b.requests == 0) continue;
+            if (!first) try writer.writeAll(",");
+            first = false;
+            try writer.print("\"{s}\":{{\"requests\":{d},\"errors\":{d},\"avg_ms\":{d},\"max_ms\":{d}}}", .{ @tagName(source), b.requests, b.errors, b.averageMs(), b.max_ms });
+        }
+        try writer.writeAll("}");
+    }
+
+    /// Write the counters in the Prometheus text exposition format, as
+    /// served at `bibval serve`'s `GET /metrics`.
+    pub fn writePrometheus(self: *const Metrics, writer: anytype) !void {
+        try writer.writeAll(
+            \\# HELP bibval_backend_requests_total Requests sent to each lookup backend.
+            \\# TYPE bibval_backend_requests_total counter
+            \\
+        );
+        for (std.enums.values(ApiSource)) |source| {
+            try writer.print("bibval_backend_requests_total{{backend=\"{s}\"}} {d}\n", .{ @tagName(source), self.backends.get(source).requests });
+        }
+        try writer.writeAll(
+            \\# HELP bibval_backend_errors_total Requests to each lookup backend that failed.
+            \\# TYPE bibval_backend_errors_total counter
+            \\
+        );
+        for (std.enums.values(ApiSource)) |source| {
+            try writer.print("bibval_backend_errors_total{{backend=\"{s}\"}} {d}\n", .{ @tagName(source), self.backends.get(source).errors });
+        }
+        try writer.writeAll(
+            \\# HELP bibval_backend_request_duration_seconds Latency of requests to each lookup backend.
+            \\# TYPE bibval_backend_request_duration_seconds histogram
+            \\
+        );
+        for (std.enums.values(ApiSource)) |source| {
+            const b = self.backends.get(source);
+            var cumulative: u64 = 0;
+            for (BUCKETS_MS, b.buckets) |bound, count| {
+                cumulative += count;
+                try writer.print("bibval_backend_request_duration_seconds_bucket{{backend=\"{s}\",le=\"{d}.{d:0>3}\"}} {d}\n", .{ @tagName(source), bound / 1000, bound % 1000, cumulative });
+            }
+            try writer.print("bibval_backend_request_duration_seconds_bucket{{backend=\"{s}\",le=\"+Inf\"}} {d}\n", .{ @tagName(source), b.requests });
+            try writer.print("bibval_backend_request_duration_seconds_sum{{backend=\"{s}\"}} ", .{@tagName(source)});
+            try writeSeconds(writer, b.total_ms);
+            try writer.print("\nbibval_backend_request_duration_seconds_count{{backend=\"{s}\"}} {d}\n", .{ @tagName(source), b.requests });
+        }
+    }
+};
+
+/// Whether events of `scope` belong to the requests of a lookup rather
+/// than to a step of their own.
+fn isRequestScope(scope: []const u8) bool {
+    for ([_][]const u8{ "http", "wait", "robots" }) |s| {
+        if (std.mem.eql(u8, scope, s)) return true;
+    }
+    return false;
+}
+
+/// Write milliseconds as seconds with three decimals, e.g. "1.250".
+fn writeSeconds(writer: anytype, ms: u64) !void {
+    try writer.print("{d}.{d:0>3}", .{ ms / 1000, ms % 1000 });
+}
+
+test "requests are counted for the backend being looked up" {
+    var metrics: Metrics = .{};
+    // Before any lookup
+    metrics.request(50, true);
+    metrics.event("crossref");
+    metrics.event("http");
+    metrics.request(200, true);
+    metrics.request(600, false);
+    metrics.event("matcher");
+    // After matching, until the next lookup
+    metrics.request(300, true);
+    metrics.event("dblp");
+    metrics.event("wait");
+    metrics.request(100, true);
+    metrics.endEntry();
+    metrics.request(700, true);
+
+    const crossref = metrics.backends.get(.crossref);
+    try std.testing.expectEqual(@as(u64, 2), crossref.requests);
+    try std.testing.expectEqual(@as(u64, 1), crossref.errors);
+    try std.testing.expectEqual(@as(u64, 400), crossref.averageMs());
+    try std.testing.expectEqual(@as(u64, 600), crossref.max_ms);
+    try std.testing.expectEqual(@as(u64, 3), metrics.totalRequests());
+
+    var out: std.Io.Writer.Allocating = .init(std.testing.allocator);
+    defer out.deinit();
+    try metrics.writeJson(&out.writer);
+    try std.testing.expectEqualStrings(
+        \\{"crossref":{"requests":2,"errors":1,"avg_ms":400,"max_ms":600},"dblp":{"requests":1,"errors":0,"avg_ms":100,"max_ms":100}}
+    , out.written());
+
+    out.clearRetainingCapacity();
+    try metrics.writePrometheus(&out.writer);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "\nbibval_backend_requests_total{backend=\"crossref\"} 2\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "\nbibval_backend_request_duration_seconds_bucket{backend=\"crossref\",le=\"0.250\"} 1\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "\nbibval_backend_request_duration_seconds_bucket{backend=\"crossref\",le=\"1.000\"} 2\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "\nbibval_backend_request_duration_seconds_sum{backend=\"crossref\"} 0.800\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "\nbibval_backend_errors_total{backend=\"github\"} 0\n") != null);
+}
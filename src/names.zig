@@ -0,0 +1,638 @@
+//! Personal names in structured form.
+//!
+//! Sources spell the same author many ways: "Dupont, Jean-Pierre",
+//! "J.-P. Dupont", "Jean Pierre Dupont Jr.", "Dupont J.P.". Comparing the
+//! whole strings fails on word order, hyphens, initials, and suffixes, so
+//! names are split into given names and a family name and compared part
+//! by part.
+//!
+//! Mononyms ("Plato") and corporate authors ("{OpenAI}", "ATLAS
+//! Collaboration") are not split: they are compared as whole names.
+//!
+//! Chinese, Japanese, and Korean names come in native script ("张伟"),
+//! romanized in either order ("Wei Zhang", "Zhang Wei"), or both
+//! ("Wei Zhang (张伟)"). Native and romanized forms are compared by the
+//! romanizations of the surname, and a two-syllable given name matches
+//! its initials ("Xiaoming" and "X.-M.").
+
+const std = @import("std");
+const cjk = @import("cjk.zig");
+const matcher = @import("matcher.zig");
+
+/// Family names at least this similar are the same name spelled with or
+/// without diacritics, or with a typo.
+pub const FAMILY_MATCH_THRESHOLD: f64 = 0.9;
+
+/// Given names beyond this many are not split into syllables.
+const MAX_GIVEN = 8;
+
+pub const Kind = enum {
+    person,
+    /// A single name, e.g. "Plato"
+    mononym,
+    /// An organization, braced in BibTeX ("{OpenAI}") or named as one
+    /// ("ATLAS Collaboration")
+    corporate,
+};
+
+/// A parsed author name.
+pub const Name = struct {
+    kind: Kind = .person,
+    /// Given and middle names or their initials, lowercased, e.g. "jean", "p"
+    given: []const []const u8 = &.{},
+    /// Family name without particles, suffixes, or separators, e.g.
+    /// "dupont" or "garcialopez"; the whole name, lowercased, for mononyms
+    /// and corporate authors
+    family: []const u8 = "",
+    /// Storage of the strings above
+    buf: []u8 = &.{},
+    /// Family and given names are in Chinese, Japanese, or Korean script
+    native: bool = false,
+    /// The same name in another script, from "Wei Zhang (张伟)"
+    alias: ?*Name = null,
+
+    pub fn deinit(self: *Name, allocator: std.mem.Allocator) void {
+        if (self.alias) |alias| {
+            alias.deinit(allocator);
+            allocator.destroy(alias);
+        }
+        allocator.free(self.given);
+        allocator.free(self.buf);
+    }
+};
+
+/// Generational suffixes, dropped from names.
+const suffixes = [_][]const u8{ "jr", "sr", "ii", "iii", "iv" };
+
+/// Words that make a name an organization's.
+const corporate_words = [_][]const u8{ "collaboration", "consortium", "team", "group", "project", "committee", "council", "association", "society", "institute", "foundation", "organization", "organisation", "initiative", "network", "laboratory", "university", "corporation", "inc", "ltd", "llc" };
+
+/// Lowercase words that join a family name, as in "Ludwig van Beethoven".
+const particles = [_][]const u8{ "von", "van", "der", "den", "de", "del", "della", "di", "da", "du", "la", "le", "dos", "das", "ter", "ten", "zu" };
+
+/// Parse a name in any of the BibTeX forms ("First Last", "Last, First",
+/// "Last, Jr, First") or the surname-first form with initials that
+/// indexes use ("Dupont J.P."). A name followed by the same name in
+/// another script in parentheses keeps both.
+pub fn parse(allocator: std.mem.Allocator, raw: []const u8) !Name {
+    const name = std.mem.trim(u8, raw, " \t\r\n");
+    if (name.len > 0 and name[name.len - 1] == ')') {
+        if (std.mem.lastIndexOfScalar(u8, name, '(')) |open| {
+            const primary = name[0..open];
+            const other = name[open + 1 .. name.len - 1];
+            // "World Health Organization (WHO)" is one name
+            if (isNativeScript(primary) != isNativeScript(other)) {
+                var result = try parseForm(allocator, primary);
+                errdefer result.deinit(allocator);
+                const alias = try allocator.create(Name);
+                errdefer allocator.destroy(alias);
+                alias.* = try parseForm(allocator, other);
+                result.alias = alias;
+                return result;
+            }
+        }
+    }
+    return parseForm(allocator, name);
+}
+
+fn parseForm(allocator: std.mem.Allocator, raw: []const u8) !Name {
+    if (isNativeScript(raw)) return parseNative(allocator, raw);
+    if (literalKind(raw)) |kind| return parseLiteral(allocator, raw, kind);
+
+    var family_words: std.ArrayList([]const u8) = .empty;
+    defer family_words.deinit(allocator);
+    var given_words: std.ArrayList([]const u8) = .empty;
+    defer given_words.deinit(allocator);
+
+    var parts: [3][]const u8 = undefined;
+    const part_count = splitCommas(raw, &parts);
+    if (part_count >= 2) {
+        try appendWords(allocator, &family_words, parts[0]);
+        // The middle part of "Last, Jr, First" is the suffix
+        if (!isSuffix(parts[part_count - 1])) try appendWords(allocator, &given_words, parts[part_count - 1]);
+    } else {
+        var words: std.ArrayList([]const u8) = .empty;
+        defer words.deinit(allocator);
+        try appendWords(allocator, &words, raw);
+        while (words.items.len > 1 and isSuffix(words.items[words.items.len - 1])) _ = words.pop();
+
+        const n = words.items.len;
+        if (n >= 2 and isInitialsWord(words.items[n - 1], raw) and !isInitialsWord(words.items[0], raw)) {
+            // "Dupont J.P."
+            try family_words.appendSlice(allocator, words.items[0 .. n - 1]);
+            try given_words.append(allocator, words.items[n - 1]);
+        } else if (n > 0) {
+            var family_start = n - 1;
+            for (words.items[0..family_start], 0..) |word, i| {
+                if (i > 0 and isParticle(word)) {
+                    family_start = i;
+                    break;
+                }
+            }
+            try given_words.appendSlice(allocator, words.items[0..family_start]);
+            try family_words.appendSlice(allocator, words.items[family_start..]);
+        }
+    }
+    // "King Jr., Martin Luther"
+    while (family_words.items.len > 1 and isSuffix(family_words.items[family_words.items.len - 1])) _ = family_words.pop();
+
+    // Folding never makes a word longer
+    const buf = try allocator.alloc(u8, raw.len);
+    errdefer allocator.free(buf);
+    var len: usize = 0;
+
+    for (family_words.items) |word| {
+        if (!isParticle(word)) fold(buf, &len, word);
+    }
+    const family_len = len;
+
+    var given: std.ArrayList([]const u8) = .empty;
+    errdefer given.deinit(allocator);
+    for (given_words.items) |word| {
+        // "Hans van der" in "Berg, Hans van der"
+        if (isParticle(word)) continue;
+        var pieces = std.mem.tokenizeAny(u8, word, "-.");
+        while (pieces.next()) |piece| {
+            if (isInitials(piece, raw)) {
+                // "JP" is two initials
+                for (piece) |c| {
+                    buf[len] = std.ascii.toLower(c);
+                    try given.append(allocator, buf[len .. len + 1]);
+                    len += 1;
+                }
+                continue;
+            }
+            const start = len;
+            fold(buf, &len, piece);
+            if (len > start) try given.append(allocator, buf[start..len]);
+        }
+    }
+
+    return .{
+        .given = try given.toOwnedSlice(allocator),
+        .family = buf[0..family_len],
+        .buf = buf,
+    };
+}
+
+/// Whether two names can be the same person: the family names agree, and
+/// the given names of one are, in order, among those of the other, each
+/// spelled out or as an initial. A missing middle name or missing given
+/// names do not count against a match. Corporate authors only match the
+/// same name, and a mononym also matches a person of that family name.
+///
+/// When both names have a native-script form, only those are compared.
+/// Otherwise a native name matches a romanized one whose family name, or
+/// first name if written surname first, romanizes its surname.
+pub fn samePerson(allocator: std.mem.Allocator, a: *const Name, b: *const Name) !bool {
+    if (nativeForm(a)) |native_a| {
+        if (nativeForm(b)) |native_b| return sameForm(allocator, native_a, native_b);
+    }
+    if (try sameForm(allocator, a, b)) return true;
+    if (a.alias) |alias_a| {
+        if (try sameForm(allocator, alias_a, b)) return true;
+    }
+    if (b.alias) |alias_b| {
+        if (try sameForm(allocator, a, alias_b)) return true;
+        if (a.alias) |alias_a| return sameForm(allocator, alias_a, alias_b);
+    }
+    return false;
+}
+
+fn nativeForm(name: *const Name) ?*const Name {
+    if (name.native) return name;
+    if (name.alias) |alias| {
+        if (alias.native) return alias;
+    }
+    return null;
+}
+
+fn sameForm(allocator: std.mem.Allocator, a: *const Name, b: *const Name) !bool {
+    if (a.family.len == 0 or b.family.len == 0) return false;
+    if (a.native and b.native) return std.mem.eql(u8, a.family, b.family) and givenCompatible(a.given, b.given);
+    if (a.native or b.native) {
+        const native = if (a.native) a else b;
+        const roman = if (a.native) b else a;
+        // Given names cannot be romanized without a dictionary
+        if (roman.kind == .corporate) return false;
+        if (cjk.romanizes(native.family, roman.family)) return true;
+        return roman.kind == .person and roman.given.len == 1 and cjk.romanizes(native.family, roman.given[0]);
+    }
+    if (a.kind != .person or b.kind != .person) {
+        if ((a.kind == .corporate or b.kind == .corporate) and (a.kind == .person or b.kind == .person)) return false;
+        return std.mem.eql(u8, a.family, b.family);
+    }
+    if (try samePersonInOrder(allocator, a, b)) return true;
+
+    // "Zhang Wei" against "Wei Zhang"
+    var a_storage: [1][]const u8 = undefined;
+    if (surnameFirst(a, &a_storage)) |a_swapped| {
+        if (try samePersonInOrder(allocator, &a_swapped, b)) return true;
+    }
+    var b_storage: [1][]const u8 = undefined;
+    if (surnameFirst(b, &b_storage)) |b_swapped| {
+        if (try samePersonInOrder(allocator, a, &b_swapped)) return true;
+    }
+    return false;
+}
+
+fn samePersonInOrder(allocator: std.mem.Allocator, a: *const Name, b: *const Name) !bool {
+    if (!givenCompatible(a.given, b.given)) return false;
+    if (std.mem.eql(u8, a.family, b.family)) return true;
+    return try matcher.jaroWinklerSimilarity(allocator, a.family, b.family) >= FAMILY_MATCH_THRESHOLD;
+}
+
+/// The name read surname first, when its one given name is a common
+/// romanized CJK surname: "Zhang Wei" as family "zhang", given "wei".
+fn surnameFirst(name: *const Name, storage: *[1][]const u8) ?Name {
+    if (name.given.len != 1) return null;
+    const first = name.given[0];
+    if (first.len < 2 or !cjk.isRomanizedSurname(first)) return null;
+    storage[0] = name.family;
+    return .{ .given = storage, .family = first };
+}
+
+fn givenCompatible(a_parts: []const []const u8, b_parts: []const []const u8) bool {
+    // "Xiaoming" against "Xiao-Ming" or "X.-M."
+    var a_buf: [MAX_GIVEN][]const u8 = undefined;
+    var b_buf: [MAX_GIVEN][]const u8 = undefined;
+    const a = splitSyllables(a_parts, b_parts, &a_buf);
+    const b = splitSyllables(b_parts, a_parts, &b_buf);
+
+    const shorter = if (a.len <= b.len) a else b;
+    const longer = if (a.len <= b.len) b else a;
+    var j: usize = 0;
+    for (shorter) |part| {
+        while (j < longer.len and !partsAgree(part, longer[j])) j += 1;
+        if (j == longer.len) return false;
+        j += 1;
+    }
+    return true;
+}
+
+/// Equal, or one is the initial of the other.
+fn partsAgree(x: []const u8, y: []const u8) bool {
+    if (std.mem.eql(u8, x, y)) return true;
+    if (x.len == 1) return std.mem.startsWith(u8, y, x);
+    if (y.len == 1) return std.mem.startsWith(u8, x, y);
+    return false;
+}
+
+/// Split given names written as one word ("xiaoming") where two
+/// consecutive given names of the other name ("xiao", "ming", or "x",
+/// "m") spell them.
+fn splitSyllables(parts: []const []const u8, other: []const []const u8, buf: *[MAX_GIVEN][]const u8) []const []const u8 {
+    var n: usize = 0;
+    for (parts) |part| {
+        if (n + 2 > buf.len) return parts;
+        const split_at: ?usize = for (0..other.len -| 1) |i| {
+            if (syllableSplit(part, other[i], other[i + 1])) |at| break at;
+        } else null;
+        if (split_at) |split| {
+            buf[n] = part[0..split];
+            buf[n + 1] = part[split..];
+            n += 2;
+        } else {
+            buf[n] = part;
+            n += 1;
+        }
+    }
+    return buf[0..n];
+}
+
+/// Where `part` splits into two syllables agreeing with `first` and
+/// `second`. Initials only split `part` after a vowel, "n", "g", or "r"
+/// and before a consonant, as between pinyin and Korean syllables.
+fn syllableSplit(part: []const u8, first: []const u8, second: []const u8) ?usize {
+    if (part.len < 4) return null;
+    for (part) |c| {
+        if (!std.ascii.isLower(c)) return null;
+    }
+    const initials = first.len == 1 or second.len == 1;
+    var at: usize = 2;
+    while (at + 2 <= part.len) : (at += 1) {
+        const head = part[0..at];
+        const tail = part[at..];
+        if (!pieceAgrees(first, head) or !pieceAgrees(second, tail)) continue;
+        if (initials and !syllableBoundary(part, at)) continue;
+        return at;
+    }
+    return null;
+}
+
+/// A given name or its initial matches a syllable.
+fn pieceAgrees(given: []const u8, piece: []const u8) bool {
+    if (given.len == 1) return piece[0] == given[0];
+    return std.mem.eql(u8, given, piece);
+}
+
+fn syllableBoundary(part: []const u8, at: usize) bool {
+    const prev = part[at - 1];
+    return (isVowel(prev) or std.mem.indexOfScalar(u8, "ngr", prev) != null) and !isVowel(part[at]);
+}
+
+fn isVowel(c: u8) bool {
+    return std.mem.indexOfScalar(u8, "aeiouv", c) != null;
+}
+
+/// Written in Chinese, Japanese, or Korean characters, without Latin
+/// letters.
+fn isNativeScript(raw: []const u8) bool {
+    for (raw) |c| {
+        if (std.ascii.isAlphabetic(c)) return false;
+    }
+    return cjk.isNative(raw);
+}
+
+/// A native name is written family name first, with or without a space:
+/// "张伟", "김 민준", "山田太郎".
+fn parseNative(allocator: std.mem.Allocator, raw: []const u8) !Name {
+    const buf = try allocator.dupe(u8, raw);
+    errdefer allocator.free(buf);
+
+    var words: std.ArrayList([]const u8) = .empty;
+    defer words.deinit(allocator);
+    var it = std.mem.tokenizeAny(u8, buf, " \t\r\n~");
+    while (it.next()) |word| {
+        const bare = std.mem.trim(u8, word, "{}");
+        if (bare.len > 0) try words.append(allocator, bare);
+    }
+    if (words.items.len == 0) return .{ .native = true, .buf = buf };
+
+    var given: std.ArrayList([]const u8) = .empty;
+    errdefer given.deinit(allocator);
+    var family = words.items[0];
+    if (words.items.len > 1) {
+        try given.appendSlice(allocator, words.items[1..]);
+    } else {
+        const len = cjk.surnameLength(family);
+        if (len < family.len) try given.append(allocator, family[len..]);
+        family = family[0..len];
+    }
+
+    return .{
+        .given = try given.toOwnedSlice(allocator),
+        .family = family,
+        .buf = buf,
+        .native = true,
+    };
+}
+
+/// Whether a name is a mononym or an organization's rather than a person's.
+fn literalKind(raw: []const u8) ?Kind {
+    const name = std.mem.trim(u8, raw, " \t\r\n");
+    if (isBraced(name)) return .corporate;
+    if (std.mem.indexOfScalar(u8, name, ',') != null) return null;
+
+    var count: usize = 0;
+    var words = std.mem.tokenizeAny(u8, name, " \t\r\n~");
+    while (words.next()) |word| {
+        count += 1;
+        const bare = std.mem.trim(u8, word, "{}.()");
+        for (corporate_words) |corporate| {
+            if (std.ascii.eqlIgnoreCase(bare, corporate)) return .corporate;
+        }
+    }
+    return if (count == 1) .mononym else null;
+}
+
+/// Wrapped in one pair of braces, as in "{Google Brain}".
+fn isBraced(name: []const u8) bool {
+    if (name.len < 2 or name[0] != '{' or name[name.len - 1] != '}') return false;
+    var depth: usize = 0;
+    for (name[0 .. name.len - 1]) |c| {
+        switch (c) {
+            '{' => depth += 1,
+            '}' => {
+                depth -|= 1;
+                // "{Le} Cun, {Y}ann" closes before the end
+                if (depth == 0) return false;
+            },
+            else => {},
+        }
+    }
+    return true;
+}
+
+/// The whole name folded, with single spaces between words.
+fn parseLiteral(allocator: std.mem.Allocator, raw: []const u8, kind: Kind) !Name {
+    const buf = try allocator.alloc(u8, raw.len);
+    errdefer allocator.free(buf);
+    var len: usize = 0;
+    var words = std.mem.tokenizeAny(u8, raw, " \t\r\n~");
+    while (words.next()) |word| {
+        const start = len;
+        if (len > 0) {
+            buf[len] = ' ';
+            len += 1;
+        }
+        const folded_start = len;
+        fold(buf, &len, word);
+        if (len == folded_start) len = start;
+    }
+    return .{
+        .kind = kind,
+        .family = buf[0..len],
+        .buf = buf,
+    };
+}
+
+/// Split at commas outside braces into at most three parts.
+fn splitCommas(raw: []const u8, parts: *[3][]const u8) usize {
+    var count: usize = 0;
+    var depth: usize = 0;
+    var start: usize = 0;
+    for (raw, 0..) |c, i| {
+        switch (c) {
+            '{' => depth += 1,
+            '}' => depth -|= 1,
+            ',' => if (depth == 0 and count < 2) {
+                parts[count] = std.mem.trim(u8, raw[start..i], " \t\r\n");
+                count += 1;
+                start = i + 1;
+            },
+            else => {},
+        }
+    }
+    parts[count] = std.mem.trim(u8, raw[start..], " \t\r\n");
+    return count + 1;
+}
+
+fn appendWords(allocator: std.mem.Allocator, list: *std.ArrayList([]const u8), text: []const u8) !void {
+    var words = std.mem.tokenizeAny(u8, text, " \t\r\n~");
+    while (words.next()) |word| try list.append(allocator, word);
+}
+
+fn isSuffix(word: []const u8) bool {
+    var buf: [8]u8 = undefined;
+    var len: usize = 0;
+    for (word) |c| {
+        if (!std.ascii.isAlphabetic(c)) continue;
+        if (len == buf.len) return false;
+        buf[len] = std.ascii.toLower(c);
+        len += 1;
+    }
+    for (suffixes) |suffix| {
+        if (std.mem.eql(u8, buf[0..len], suffix)) return true;
+    }
+    return false;
+}
+
+/// A lowercase particle; "Le" in "Le Cun, Yann" is part of the family name.
+fn isParticle(word: []const u8) bool {
+    if (word.len == 0 or !std.ascii.isLower(word[0])) return false;
+    for (particles) |particle| {
+        if (std.mem.eql(u8, word, particle)) return true;
+    }
+    return false;
+}
+
+/// Two or three capitals such as "JP", unless the whole name is written
+/// in capitals.
+fn isInitials(piece: []const u8, raw: []const u8) bool {
+    if (piece.len < 2 or piece.len > 3) return false;
+    for (piece) |c| {
+        if (!std.ascii.isUpper(c)) return false;
+    }
+    for (raw) |c| {
+        if (std.ascii.isLower(c)) return true;
+    }
+    return false;
+}
+
+/// A word made only of initials: "Y.", "J.P.", "J.-P.", or "JP".
+fn isInitialsWord(word: []const u8, raw: []const u8) bool {
+    var pieces = std.mem.tokenizeAny(u8, word, "-.");
+    var any = false;
+    while (pieces.next()) |piece| {
+        const single = piece.len == 1 and std.ascii.isUpper(piece[0]);
+        if (!single and !isInitials(piece, raw)) return false;
+        any = true;
+    }
+    return any;
+}
+
+/// Append `text` lowercased, without punctuation or LaTeX accent commands
+/// (`{\"u}`, `\c{c}`). Other UTF-8 bytes are kept as they are.
+fn fold(buf: []u8, len: *usize, text: []const u8) void {
+    var i: usize = 0;
+    while (i < text.len) : (i += 1) {
+        const c = text[i];
+        if (c == '\\') {
+            // Skip the accent symbol or one-letter accent command
+            if (i + 1 < text.len and !std.ascii.isAlphabetic(text[i + 1])) {
+                i += 1;
+            } else if (i + 2 < text.len and std.mem.indexOfScalar(u8, "cvuHkrdbt", text[i + 1]) != null and (text[i + 2] == '{' or text[i + 2] == ' ')) {
+                i += 1;
+            }
+            continue;
+        }
+        if (c >= 0x80 or std.ascii.isAlphanumeric(c)) {
+            buf[len.*] = std.ascii.toLower(c);
+            len.* += 1;
+        }
+    }
+}
+
+test "names match across spellings" {
+    const allocator = std.testing.allocator;
+    const same = [_][2][]const u8{
+        .{ "Jean-Pierre Dupont", "Jean Pierre Dupont" },
+        .{ "Dupont, Jean-Pierre", "J.-P. Dupont" },
+        .{ "Jean-Pierre Dupont", "JP Dupont" },
+        .{ "John F. Kennedy", "John Kennedy" },
+        .{ "Martin Luther King Jr.", "King, Jr., Martin Luther" },
+        .{ "King Jr., Martin Luther", "Martin L. King" },
+        .{ "LeCun Y.", "Yann LeCun" },
+        .{ "Le Cun, Yann", "Yann LeCun" },
+        .{ "Ludwig van Beethoven", "Beethoven, Ludwig van" },
+        .{ "Kurt G{\\\"o}del", "Kurt Godel" },
+    };
+    for (same) |pair| {
+        var a = try parse(allocator, pair[0]);
+        defer a.deinit(allocator);
+        var b = try parse(allocator, pair[1]);
+        defer b.deinit(allocator);
+        try std.testing.expect(try samePerson(allocator, &a, &b));
+    }
+
+    var john = try parse(allocator, "John Smith");
+    defer john.deinit(allocator);
+    var jane = try parse(allocator, "Smith, Jane");
+    defer jane.deinit(allocator);
+    try std.testing.expect(!try samePerson(allocator, &john, &jane));
+    try std.testing.expectEqualStrings("smith", jane.family);
+}
+
+test "mononyms and corporate authors are whole names" {
+    const allocator = std.testing.allocator;
+
+    var openai = try parse(allocator, "{OpenAI}");
+    defer openai.deinit(allocator);
+    try std.testing.expectEqual(Kind.corporate, openai.kind);
+    var atlas = try parse(allocator, "ATLAS Collaboration");
+    defer atlas.deinit(allocator);
+    try std.testing.expectEqual(Kind.corporate, atlas.kind);
+    try std.testing.expectEqualStrings("atlas collaboration", atlas.family);
+    var atlas_lower = try parse(allocator, "{Atlas collaboration}");
+    defer atlas_lower.deinit(allocator);
+    try std.testing.expect(try samePerson(allocator, &atlas, &atlas_lower));
+    try std.testing.expect(!try samePerson(allocator, &atlas, &openai));
+
+    // Not "Collaboration" with the given name "ATLAS"
+    var collins = try parse(allocator, "Atlas Collins");
+    defer collins.deinit(allocator);
+    try std.testing.expect(!try samePerson(allocator, &atlas, &collins));
+
+    var plato = try parse(allocator, "Plato");
+    defer plato.deinit(allocator);
+    try std.testing.expectEqual(Kind.mononym, plato.kind);
+    var vaswani = try parse(allocator, "Vaswani");
+    defer vaswani.deinit(allocator);
+    var ashish = try parse(allocator, "Ashish Vaswani");
+    defer ashish.deinit(allocator);
+    try std.testing.expect(try samePerson(allocator, &vaswani, &ashish));
+    try std.testing.expect(!try samePerson(allocator, &plato, &ashish));
+
+    // Braces around part of a name only protect its case
+    var le_cun = try parse(allocator, "{Le Cun}, Yann");
+    defer le_cun.deinit(allocator);
+    try std.testing.expectEqual(Kind.person, le_cun.kind);
+}
+
+test "CJK names" {
+    const allocator = std.testing.allocator;
+    const same = [_][2][]const u8{
+        .{ "张伟", "Wei Zhang" },
+        .{ "张伟", "Zhang Wei" },
+        .{ "Zhang Wei", "Wei Zhang" },
+        .{ "Xiaoming Zhang", "Xiao-Ming Zhang" },
+        .{ "Xiaoming Zhang", "Zhang X.-M." },
+        .{ "김민준", "Minjun Kim" },
+        .{ "Minjun Kim", "M.-J. Kim" },
+        .{ "佐藤太郎", "Taro Sato" },
+        .{ "歐陽修", "Xiu Ouyang" },
+        .{ "Wei Zhang (张伟)", "张伟" },
+    };
+    for (same) |pair| {
+        var a = try parse(allocator, pair[0]);
+        defer a.deinit(allocator);
+        var b = try parse(allocator, pair[1]);
+        defer b.deinit(allocator);
+        try std.testing.expect(try samePerson(allocator, &a, &b));
+    }
+
+    var wang = try parse(allocator, "王伟");
+    defer wang.deinit(allocator);
+    try std.testing.expectEqualStrings("王", wang.family);
+    var zhang = try parse(allocator, "Wei Zhang");
+    defer zhang.deinit(allocator);
+    try std.testing.expect(!try samePerson(allocator, &wang, &zhang));
+
+    // Both native forms known: the given names must agree too
+    var both = try parse(allocator, "Wei Zhang (张伟)");
+    defer both.deinit(allocator);
+    var jun = try parse(allocator, "张军");
+    defer jun.deinit(allocator);
+    try std.testing.expect(!try samePerson(allocator, &both, &jun));
+}
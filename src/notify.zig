@@ -0,0 +1,213 @@
+//! Notifications when a shared bibliography regresses.
+//!
+//! `bibval serve` remembers the errors of the last report of each
+//! bibliography (named by the `name` parameter of POST /validate). When a
+//! later report has errors the previous one did not, every configured
+//! webhook is sent the new ones, as plain JSON or as a Slack-compatible
+//! message. The first report of a bibliography only sets the baseline.
+//! Names are scoped to the client sending them, so another client reusing
+//! a name starts its own baseline instead of resetting the first one.
+
+const std = @import("std");
+const http = @import("http.zig");
+const report_mod = @import("report.zig");
+const trace = @import("trace.zig");
+const Report = report_mod.Report;
+
+pub const Format = enum {
+    /// `{"bibliography": ..., "errors": [...]}`
+    json,
+    /// `{"text": ...}`, as Slack, Mattermost, and Discord's Slack endpoint accept.
+    slack,
+};
+
+/// A webhook, from a `[[notify.webhooks]]` table of the config.
+pub const Sink = struct {
+    url: []const u8,
+    format: Format = .json,
+};
+
+/// An error-severity discrepancy of a report.
+pub const Finding = struct {
+    key: []const u8,
+    rule: []const u8,
+    message: []const u8,
+
+    fn hash(self: Finding) u64 {
+        var hasher = std.hash.Wyhash.init(0);
+        hasher.update(self.key);
+        hasher.update(&[_]u8{0});
+        hasher.update(self.rule);
+        return hasher.final();
+    }
+};
+
+/// Most errors listed in one notification.
+const MAX_LISTED = 20;
+
+/// Most bibliographies remembered; reports of further ones are not
+/// compared, so clients cannot grow the server's memory without bound.
+pub const MAX_BIBLIOGRAPHIES = 1000;
+
+pub const Notifier = struct {
+    allocator: std.mem.Allocator,
+    sinks: []const Sink,
+    /// Errors of the last report of each bibliography, by client and name
+    /// (see `bibliographyId`).
+    seen: std.AutoHashMapUnmanaged(u64, std.AutoHashMapUnmanaged(u64, void)) = .empty,
+
+    pub fn init(allocator: std.mem.Allocator, sinks: []const Sink) Notifier {
+        return .{ .allocator = allocator, .sinks = sinks };
+    }
+
+    pub fn deinit(self: *Notifier) void {
+        var it = self.seen.valueIterator();
+        while (it.next()) |hashes| hashes.deinit(self.allocator);
+        self.seen.deinit(self.allocator);
+    }
+
+    /// Errors of `report` that the previous report of `name` from `client`
+    /// did not have, remembering the report's errors for next time.
+    /// Strings borrow from `report`; free the slice with `allocator`.
+    pub fn newErrors(self: *Notifier, client: u64, name: []const u8, report: *const Report) ![]Finding {
+        const id = bibliographyId(client, name);
+        if (!self.seen.contains(id) and self.seen.count() >= MAX_BIBLIOGRAPHIES) return self.allocator.alloc(Finding, 0);
+
+        var current: std.ArrayList(Finding) = .empty;
+        defer current.deinit(self.allocator);
+        for (report.entries.items) |*entry_report| {
+            for (entry_report.validation_results) |result| {
+                for (result.discrepancies) |d| {
+                    if (d.severity != .@"error") continue;
+                    const finding = Finding{ .key = entry_report.entry.key, .rule = d.rule.id(), .message = d.message };
+                    const duplicate = for (current.items) |f| {
+                        if (f.hash() == finding.hash()) break true;
+                    } else false;
+                    if (!duplicate) try current.append(self.allocator, finding);
+                }
+            }
+        }
+
+        var hashes: std.AutoHashMapUnmanaged(u64, void) = .empty;
+        errdefer hashes.deinit(self.allocator);
+        for (current.items) |f| try hashes.put(self.allocator, f.hash(), {});
+
+        var fresh: std.ArrayList(Finding) = .empty;
+        errdefer fresh.deinit(self.allocator);
+        if (self.seen.getPtr(id)) |previous| {
+            for (current.items) |f| {
+                if (!previous.contains(f.hash())) try fresh.append(self.allocator, f);
+            }
+            previous.deinit(self.allocator);
+            previous.* = hashes;
+        } else {
+            try self.seen.put(self.allocator, id, hashes);
+        }
+        return fresh.toOwnedSlice(self.allocator);
+    }
+
+    fn bibliographyId(client: u64, name: []const u8) u64 {
+        var hasher = std.hash.Wyhash.init(client);
+        hasher.update(name);
+        return hasher.final();
+    }
+
+    /// Send `findings` to every sink. A sink that fails is traced and does
+    /// not stop the others.
+    pub fn send(self: *Notifier, client: *http.Client, tracer: *trace.Tracer, name: []const u8, findings: []const Finding) !void {
+        if (findings.len == 0) return;
+        var body: std.Io.Writer.Allocating = .init(self.allocator);
+        defer body.deinit();
+        for (self.sinks) |sink| {
+            body.clearRetainingCapacity();
+            try writePayload(self.allocator, &body.writer, sink.format, name, findings);
+            // The URL's path is the webhook's secret
+            const shown = http.origin(sink.url);
+            const response = client.post(sink.url, "application/json", body.written()) catch |err| {
+                tracer.event(.info, "notify", "webhook {s} failed: {s}", .{ shown, @errorName(err) });
+                continue;
+            };
+            self.allocator.free(response);
+            tracer.event(.info, "notify", "sent {d} new errors of {s} to {s}", .{ findings.len, name, shown });
+        }
+    }
+};
+
+/// The notification body for `findings` in `format`.
+pub fn writePayload(allocator: std.mem.Allocator, w: *std.Io.Writer, format: Format, name: []const u8, findings: []const Finding) !void {
+    const listed = findings[0..@min(findings.len, MAX_LISTED)];
+    switch (format) {
+        .json => {
+            try w.writeAll("{\"bibliography\":");
+            try report_mod.writeJsonString(w, name);
+            try w.print(",\"new_errors\":{d},\"errors\":[", .{findings.len});
+            for (listed, 0..) |f, i| {
+                if (i > 0) try w.writeAll(",");
+                try w.writeAll("{\"key\":");
+                try report_mod.writeJsonString(w, f.key);
+                try w.print(",\"rule\":\"{s}\",\"message\":", .{f.rule});
+                try report_mod.writeJsonString(w, f.message);
+                try w.writeAll("}");
+            }
+            try w.writeAll("]}");
+        },
+        .slack => {
+            var text: std.Io.Writer.Allocating = .init(allocator);
+            defer text.deinit();
+            try text.writer.print("bibval: {d} new error{s} in {s}", .{ findings.len, if (findings.len == 1) "" else "s", name });
+            for (listed) |f| try text.writer.print("\n\u{2022} {s}: {s} {s}", .{ f.key, f.rule, f.message });
+            if (findings.len > listed.len) try text.writer.print("\n... and {d} more", .{findings.len - listed.len});
+            try w.writeAll("{\"text\":");
+            try report_mod.writeJsonString(w, text.written());
+            try w.writeAll("}");
+        },
+    }
+}
+
+test "only new errors are notified" {
+    const allocator = std.testing.allocator;
+    const entry_mod = @import("entry.zig");
+
+    var year = [_]entry_mod.Discrepancy{.{ .rule = .year_mismatch, .field = .year, .severity = .@"error", .local_value = "2019", .remote_value = "2018", .message = "Year mismatch: 2019 vs 2018" }};
+    var doi = [_]entry_mod.Discrepancy{.{ .rule = .missing_doi, .field = .doi, .severity = .warning, .local_value = "(none)", .remote_value = "10.1/x", .message = "Missing DOI in local entry" }};
+    var first_results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .confidence = 0.9, .discrepancies = &doi }};
+    var second_results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .confidence = 0.9, .discrepancies = &year }};
+
+    var notifier = Notifier.init(allocator, &.{});
+    defer notifier.deinit();
+
+    var report = Report.init(allocator);
+    defer report.entries.deinit(allocator);
+    try report.entries.append(allocator, .{ .entry = .{ .key = "smith2020", .entry_type = "article" }, .status = .warning, .validation_results = &first_results });
+
+    // The first report sets the baseline
+    const baseline = try notifier.newErrors(1, "thesis", &report);
+    defer allocator.free(baseline);
+    try std.testing.expectEqual(@as(usize, 0), baseline.len);
+
+    report.entries.items[0].validation_results = &second_results;
+    const fresh = try notifier.newErrors(1, "thesis", &report);
+    defer allocator.free(fresh);
+    try std.testing.expectEqual(@as(usize, 1), fresh.len);
+
+    // Reported once only
+    const again = try notifier.newErrors(1, "thesis", &report);
+    defer allocator.free(again);
+    try std.testing.expectEqual(@as(usize, 0), again.len);
+
+    // Another client sending the same name has a baseline of its own
+    const other = try notifier.newErrors(2, "thesis", &report);
+    defer allocator.free(other);
+    try std.testing.expectEqual(@as(usize, 0), other.len);
+    try std.testing.expectEqual(@as(u32, 2), notifier.seen.count());
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writePayload(allocator, &out.writer, .slack, "thesis", fresh);
+    try std.testing.expectEqualStrings("{\"text\":\"bibval: 1 new error in thesis\\n\u{2022} smith2020: BV003 Year mismatch: 2019 vs 2018\"}", out.written());
+}
+
+test "webhook URLs are traced without their path" {
+    try std.testing.expectEqualStrings("https://hooks.slack.com", http.origin("https://hooks.slack.com/services/T0/B0/secret"));
+    try std.testing.expectEqualStrings("http://localhost:9000", http.origin("http://localhost:9000?token=x"));
+}
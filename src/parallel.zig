@@ -0,0 +1,66 @@
+//! Parallel execution of local passes.
+//!
+//! Network lookups stay sequential so that rate limits and the response
+//! cache behave. Snapshot replay, which compares every entry with stored
+//! records and needs no network, is spread over a thread pool; the other
+//! local passes (lint, duplicates, consistency) still run on one thread.
+
+const std = @import("std");
+
+/// Number of worker threads to use when none is given: one per CPU.
+pub fn defaultJobs() usize {
+    return std.Thread.getCpuCount() catch 1;
+}
+
+/// Call `func(context, i)` for every `i` in `0..n` on up to `jobs`
+/// threads, returning when all calls are done. `func` must be safe to call
+/// concurrently for different indices; with `jobs <= 1` the calls run in
+/// order on the calling thread.
+pub fn forEach(
+    allocator: std.mem.Allocator,
+    n: usize,
+    jobs: usize,
+    context: anytype,
+    comptime func: fn (@TypeOf(context), usize) void,
+) !void {
+    if (jobs <= 1 or n <= 1) {
+        for (0..n) |i| func(context, i);
+        return;
+    }
+
+    const Chunk = struct {
+        fn run(ctx: @TypeOf(context), start: usize, end: usize) void {
+            for (start..end) |i| func(ctx, i);
+        }
+    };
+
+    var pool: std.Thread.Pool = undefined;
+    try pool.init(.{ .allocator = allocator, .n_jobs = @min(jobs, n) });
+    defer pool.deinit();
+
+    // A few chunks per thread balance uneven work without scheduling every index
+    const chunk = @max(1, n / (jobs * 4));
+    var wait_group: std.Thread.WaitGroup = .{};
+    var start: usize = 0;
+    while (start < n) : (start += chunk) {
+        pool.spawnWg(&wait_group, Chunk.run, .{ context, start, @min(start + chunk, n) });
+    }
+    pool.waitAndWork(&wait_group);
+}
+
+test "forEach visits every index once" {
+    const Counter = struct {
+        hits: []std.atomic.Value(u32),
+
+        fn visit(self: *const @This(), i: usize) void {
+            _ = self.hits[i].fetchAdd(1, .monotonic);
+        }
+    };
+
+    var hits: [1000]std.atomic.Value(u32) = undefined;
+    for (&hits) |*h| h.* = .init(0);
+    const counter = Counter{ .hits = &hits };
+
+    try forEach(std.testing.allocator, hits.len, 4, &counter, Counter.visit);
+    for (hits) |h| try std.testing.expectEqual(@as(u32, 1), h.load(.monotonic));
+}
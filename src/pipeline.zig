@@ -0,0 +1,1149 @@
+//! The validation pipeline for a single entry.
+//!
+//! Looks an entry up on Zenodo or Figshare, by DOI, ISBN, arXiv ID or
+//! Scopus EID, software by its GitHub repository, a web citation by its
+//! URL, on the PMLR or NeurIPS proceedings site, then by title on each
+//! enabled API and validator plugin, compares it with the best match of
+//! each, and turns the findings into an entry report. Shared by the
+//! command line, `bibval serve` and the C API.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const validators = @import("validators.zig");
+const external = @import("external.zig");
+const proceedings = @import("proceedings.zig");
+const webpage = @import("webpage.zig");
+const github = @import("github.zig");
+const deposits = @import("deposits.zig");
+const wayback = @import("wayback.zig");
+const ignores = @import("ignores.zig");
+const report_mod = @import("report.zig");
+const rules = @import("rules.zig");
+const screening_mod = @import("screening.zig");
+const funders = @import("funders.zig");
+const policy_mod = @import("policy.zig");
+const trace = @import("trace.zig");
+const plan = @import("plan.zig");
+const completeness = @import("completeness.zig");
+const fix = @import("fix.zig");
+const Metrics = @import("metrics.zig").Metrics;
+const Entry = entry_mod.Entry;
+const ApiSource = entry_mod.ApiSource;
+const ValidationResult = entry_mod.ValidationResult;
+const Discrepancy = entry_mod.Discrepancy;
+const EntryReport = report_mod.EntryReport;
+const Report = report_mod.Report;
+const Tracer = trace.Tracer;
+const writeJsonString = report_mod.writeJsonString;
+
+/// The lookup backends of a run; a null validator is disabled.
+pub const Sources = struct {
+    crossref: ?validators.CrossRef = null,
+    dblp: ?validators.Dblp = null,
+    semantic: ?validators.SemanticScholar = null,
+    openalex: ?validators.OpenAlex = null,
+    open_library: ?validators.OpenLibrary = null,
+    google_books: ?validators.GoogleBooks = null,
+    arxiv: ?validators.Arxiv = null,
+    pmlr: ?proceedings.Pmlr = null,
+    neurips: ?proceedings.NeurIps = null,
+    ieee: ?validators.Ieee = null,
+    acm: ?validators.Acm = null,
+    springer: ?validators.Springer = null,
+    scopus: ?validators.Scopus = null,
+    github: ?github.GitHub = null,
+    zenodo: ?deposits.Zenodo = null,
+    figshare: ?deposits.Figshare = null,
+    web: ?webpage.Web = null,
+    /// Null unless archive snapshots are suggested
+    wayback: ?wayback.Wayback = null,
+    plugins: []const external.Plugin = &.{},
+
+    pub fn backends(self: *const Sources) plan.Backends {
+        return .{
+            .crossref = self.crossref != null,
+            .dblp = self.dblp != null,
+            .semantic_scholar = self.semantic != null,
+            .openalex = self.openalex != null,
+            .open_library = self.open_library != null,
+            .google_books = self.google_books != null,
+            .arxiv = self.arxiv != null,
+            .pmlr = self.pmlr != null,
+            .neurips = self.neurips != null,
+            .ieee = self.ieee != null,
+            .acm = self.acm != null,
+            .springer = self.springer != null,
+            .scopus = self.scopus != null,
+            .github = self.github != null,
+            .zenodo = self.zenodo != null,
+            .figshare = self.figshare != null,
+            .web = self.web != null,
+            .wayback = self.wayback != null,
+            .save_pages = if (self.wayback) |w| w.save else false,
+            .external = self.plugins.len,
+        };
+    }
+
+    /// These sources without the backends `allowed` leaves out.
+    pub fn restrict(self: Sources, allowed: std.EnumSet(ApiSource)) Sources {
+        var result = self;
+        if (!allowed.contains(.crossref)) result.crossref = null;
+        if (!allowed.contains(.dblp)) result.dblp = null;
+        if (!allowed.contains(.semantic_scholar)) result.semantic = null;
+        if (!allowed.contains(.openalex)) result.openalex = null;
+        if (!allowed.contains(.open_library)) result.open_library = null;
+        if (!allowed.contains(.google_books)) result.google_books = null;
+        if (!allowed.contains(.arxiv)) result.arxiv = null;
+        if (!allowed.contains(.pmlr)) result.pmlr = null;
+        if (!allowed.contains(.neurips)) result.neurips = null;
+        if (!allowed.contains(.ieee)) result.ieee = null;
+        if (!allowed.contains(.acm)) result.acm = null;
+        if (!allowed.contains(.springer)) result.springer = null;
+        if (!allowed.contains(.scopus)) result.scopus = null;
+        if (!allowed.contains(.github)) result.github = null;
+        if (!allowed.contains(.zenodo)) result.zenodo = null;
+        if (!allowed.contains(.figshare)) result.figshare = null;
+        if (!allowed.contains(.web)) result.web = null;
+        if (!allowed.contains(.external)) result.plugins = &.{};
+        return result;
+    }
+};
+
+/// Look up a DOI, trying CrossRef before OpenAlex.
+pub fn resolveDoi(doi: []const u8, sources: *Sources) ?Entry {
+    if (sources.crossref) |*c| {
+        if (c.searchByDoi(doi) catch null) |record| return record;
+    }
+    if (sources.openalex) |*o| {
+        if (o.searchByDoi(doi) catch null) |record| return record;
+    }
+    return null;
+}
+
+/// Print the source of each merged field, e.g. " Title from CrossRef, Year from DBLP".
+pub fn printProvenance(writer: *std.Io.Writer, provenance: *const entry_mod.Provenance) !void {
+    var first = true;
+    for (std.enums.values(entry_mod.DiscrepancyField)) |field| {
+        const source = provenance.get(field) orelse continue;
+        try writer.print("{s} {s} from {s}", .{ if (first) "" else ",", field.name(), source.name() });
+        first = false;
+    }
+}
+
+/// Validate one entry against the enabled sources. The entry's strategies
+/// (see `plan.strategiesFor`) are tried in order until one matches: DOI
+/// and ISBN lookups, the proceedings site, lookups by the other
+/// identifiers, then a title search on each API and plugin that keeps the
+/// best match of each. Every strategy tried is
+/// recorded in the report.
+pub fn validateEntry(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    sources: *Sources,
+    tracer: *const Tracer,
+    disabled: rules.RuleSet,
+    min_confidence: f64,
+    check_funders: bool,
+    open_access: bool,
+    screening: *const screening_mod.Screening,
+    matching: *const matcher.MatcherConfig,
+) !EntryReport {
+    const start_ms = std.time.milliTimestamp();
+    var validation_results: std.ArrayList(ValidationResult) = .empty;
+    defer validation_results.deinit(allocator);
+    var candidates: std.ArrayList(report_mod.Candidate) = .empty;
+    defer {
+        for (candidates.items) |c| allocator.free(c.title);
+        candidates.deinit(allocator);
+    }
+    var lookups: std.ArrayList(report_mod.Lookup) = .empty;
+    defer lookups.deinit(allocator);
+    // Title of the record the entry's DOI resolved to, if another work's
+    var other_work: ?[]const u8 = null;
+    defer if (other_work) |t| allocator.free(t);
+
+    const strategies = plan.strategiesFor(local_entry, sources.backends());
+    var it = strategies.iterator();
+    while (it.next()) |strategy| {
+        const outcome: Outcome = switch (strategy) {
+            .deposit_lookup => try lookupDeposit(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .doi_lookup => try lookupDoi(allocator, matching, local_entry, sources, &validation_results, &other_work, tracer),
+            .isbn_lookup => try lookupIsbn(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .arxiv_lookup => try lookupArxiv(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .scopus_lookup => try lookupScopus(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .repo_lookup => try lookupRepository(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .url_lookup => try lookupUrl(allocator, matching, local_entry, sources, &validation_results, tracer),
+            .proceedings_lookup => try lookupProceedings(allocator, matching, local_entry, sources, &validation_results, &candidates, tracer),
+            .title_search => try searchTitle(allocator, matching, local_entry, sources, &validation_results, &candidates, tracer),
+            .skip => unreachable,
+        };
+        try lookups.append(allocator, .{ .strategy = strategy, .outcome = outcome });
+        if (outcome == .matched) break;
+        tracer.event(.info, "pipeline", "{s}: {s}", .{ strategy.name(), outcome.describe() });
+    }
+
+    // A DOI of another work is wrong even if the title search finds the entry
+    for (lookups.items) |lookup| {
+        if (lookup.strategy != .doi_lookup or lookup.outcome != .mismatch) continue;
+        if (disabled.contains(.wrong_doi)) break;
+        if (other_work) |title| try flagWrongDoi(allocator, local_entry, title, &validation_results);
+    }
+
+    // A repository URL that leads nowhere is wrong whatever the title search finds
+    for (lookups.items) |lookup| {
+        if (lookup.strategy != .repo_lookup or lookup.outcome != .not_found) continue;
+        if (!disabled.contains(.missing_repository)) try flagMissingRepository(allocator, local_entry, &validation_results);
+    }
+
+    // Funding checks only apply to entries that were found
+    if (check_funders and sources.crossref != null and validation_results.items.len > 0) {
+        try checkFunding(allocator, local_entry, &sources.crossref.?, &validation_results, tracer);
+    }
+
+    if (open_access and sources.openalex != null and validation_results.items.len > 0) {
+        try lookupAccess(allocator, matching, local_entry, &sources.openalex.?, &validation_results, tracer);
+    }
+
+    if (sources.wayback != null and !disabled.contains(.archive_url) and webpage.isWebEntry(local_entry) and !wayback.isArchived(local_entry)) {
+        try suggestArchive(allocator, local_entry, &sources.wayback.?, &validation_results, tracer);
+    }
+
+    if (tracer.enabled(.info) and validation_results.items.len > 1) {
+        if (try entry_mod.merge(allocator, validation_results.items)) |merged| {
+            var m = merged;
+            defer m.deinit();
+            var buf: [256]u8 = undefined;
+            var w = std.Io.Writer.fixed(&buf);
+            printProvenance(&w, &m.provenance) catch {};
+            tracer.event(.info, "merge", "fields:{s}", .{w.buffered()});
+        }
+    }
+
+    var entry_report = try finishReport(allocator, local_entry, &validation_results, disabled, min_confidence, screening);
+    if (entry_report.status == .not_found and candidates.items.len > 0) {
+        std.sort.pdq(report_mod.Candidate, candidates.items, {}, higherCandidateScore);
+        const kept = @min(candidates.items.len, MAX_CANDIDATES);
+        entry_report.candidates = try allocator.dupe(report_mod.Candidate, candidates.items[0..kept]);
+        // The report owns the kept titles now
+        candidates.replaceRangeAssumeCapacity(0, kept, &.{});
+    }
+    entry_report.lookups = try lookups.toOwnedSlice(allocator);
+    entry_report.elapsed_ms = @intCast(@max(std.time.milliTimestamp() - start_ms, 0));
+    return entry_report;
+}
+
+const Outcome = report_mod.Lookup.Outcome;
+
+/// Look up a DOI on CrossRef, and IEEE and Springer DOIs also on IEEE
+/// Xplore and Springer Nature, whose records carry the publisher's page
+/// numbers. If CrossRef resolves the DOI to a different work, its title is
+/// stored in `other_work`.
+fn lookupDoi(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    other_work: *?[]const u8,
+    tracer: *const Tracer,
+) !Outcome {
+    const doi = local_entry.doi.?;
+    var outcome: Outcome = .failed;
+
+    tracer.lookup("crossref", "DOI lookup {s}", .{doi});
+    if (sources.crossref.?.searchByDoi(doi)) |remote| {
+        if (remote) |r| {
+            const title = try allocator.dupe(u8, r.title orelse "(untitled)");
+            errdefer allocator.free(title);
+            const found = try appendIdentifierMatch(allocator, matching, local_entry, .crossref, r, validation_results, tracer);
+            if (found == .mismatch) other_work.* = title else allocator.free(title);
+            outcome = outcome.combine(found);
+        } else outcome = outcome.combine(.not_found);
+    } else |err| {
+        tracer.lookupFailed("crossref", err);
+    }
+
+    if (sources.ieee) |*ieee| {
+        if (validators.Ieee.isIeeeDoi(doi)) {
+            tracer.lookup("ieee", "DOI lookup {s}", .{doi});
+            if (ieee.searchByDoi(doi)) |remote| {
+                outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .ieee, r, validation_results, tracer) else .not_found);
+            } else |err| {
+                tracer.lookupFailed("ieee", err);
+            }
+        }
+    }
+
+    if (sources.springer) |*springer| {
+        if (validators.Springer.isSpringerDoi(doi)) {
+            tracer.lookup("springer", "DOI lookup {s}", .{doi});
+            if (springer.searchByDoi(doi)) |remote| {
+                outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .springer, r, validation_results, tracer) else .not_found);
+            } else |err| {
+                tracer.lookupFailed("springer", err);
+            }
+        }
+    }
+    return outcome;
+}
+
+/// Look up the Scopus record an entry exported from Scopus came from.
+fn lookupScopus(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const eid = local_entry.scopus_eid.?;
+    tracer.lookup("scopus", "EID lookup {s}", .{eid});
+    const remote = sources.scopus.?.searchByEid(eid) catch |err| {
+        tracer.lookupFailed("scopus", err);
+        return .failed;
+    } orelse return .not_found;
+    return appendIdentifierMatch(allocator, matching, local_entry, .scopus, remote, validation_results, tracer);
+}
+
+/// Look up a dataset or software deposit on Zenodo or Figshare.
+fn lookupDeposit(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const version = local_entry.extraField("version");
+    const deposit = deposits.depositFor(local_entry).?;
+    const source: ApiSource = switch (deposit) {
+        .zenodo => .zenodo,
+        .figshare => .figshare,
+    };
+    tracer.lookup(@tagName(source), "deposit {s}", .{local_entry.doi orelse local_entry.url.?});
+    const found: anyerror!?Entry = switch (deposit) {
+        .zenodo => |id| sources.zenodo.?.searchRecord(id, version),
+        .figshare => |article| sources.figshare.?.searchArticle(article, version),
+    };
+    const remote = found catch |err| {
+        tracer.lookupFailed(@tagName(source), err);
+        return .failed;
+    } orelse return .not_found;
+    return appendIdentifierMatch(allocator, matching, local_entry, source, remote, validation_results, tracer);
+}
+
+/// Look up the GitHub repository software is cited by, with the tag of the
+/// version it cites.
+fn lookupRepository(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const repo = github.repository(local_entry.url.?).?;
+    tracer.lookup("github", "repository {s}/{s}", .{ repo.owner, repo.name });
+    const remote = sources.github.?.searchRepository(repo, github.citedVersion(local_entry)) catch |err| {
+        tracer.lookupFailed("github", err);
+        return .failed;
+    } orelse return .not_found;
+    return appendIdentifierMatch(allocator, matching, local_entry, .github, remote, validation_results, tracer);
+}
+
+/// Read the metadata of the page a web citation links to.
+fn lookupUrl(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const url = local_entry.url.?;
+    tracer.lookup("web", "page {s}", .{url});
+    const remote = sources.web.?.searchByUrl(url) catch |err| {
+        tracer.lookupFailed("web", err);
+        return .failed;
+    } orelse return .not_found;
+    return appendIdentifierMatch(allocator, matching, local_entry, .web, remote, validation_results, tracer);
+}
+
+/// Look up a book on Open Library, then on Google Books.
+fn lookupIsbn(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const isbn = local_entry.isbn.?;
+    var outcome: Outcome = .failed;
+
+    if (sources.open_library) |*open_library| {
+        tracer.lookup("open_library", "ISBN lookup {s}", .{isbn});
+        if (open_library.searchByIsbn(isbn)) |remote| {
+            outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .open_library, r, validation_results, tracer) else .not_found);
+        } else |err| {
+            tracer.lookupFailed("open_library", err);
+        }
+    }
+    if (outcome == .matched) return outcome;
+
+    if (sources.google_books) |*google_books| {
+        tracer.lookup("google_books", "ISBN lookup {s}", .{isbn});
+        if (google_books.searchByIsbn(isbn)) |remote| {
+            outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .google_books, r, validation_results, tracer) else .not_found);
+        } else |err| {
+            tracer.lookupFailed("google_books", err);
+        }
+    }
+    return outcome;
+}
+
+/// Look up a preprint on arXiv, then on Semantic Scholar.
+fn lookupArxiv(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    const arxiv_id = local_entry.arxiv_id.?;
+    var outcome: Outcome = .failed;
+
+    if (sources.arxiv) |*arxiv| {
+        tracer.lookup("arxiv", "arXiv lookup {s}", .{arxiv_id});
+        if (arxiv.searchById(arxiv_id)) |remote| {
+            outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .arxiv, r, validation_results, tracer) else .not_found);
+        } else |err| {
+            tracer.lookupFailed("arxiv", err);
+        }
+    }
+    if (outcome == .matched) return outcome;
+
+    if (sources.semantic) |*semantic| {
+        tracer.lookup("semantic_scholar", "arXiv lookup {s}", .{arxiv_id});
+        if (semantic.searchByArxiv(arxiv_id)) |remote| {
+            outcome = outcome.combine(if (remote) |r| try appendIdentifierMatch(allocator, matching, local_entry, .semantic_scholar, r, validation_results, tracer) else .not_found);
+        } else |err| {
+            tracer.lookupFailed("semantic_scholar", err);
+        }
+    }
+    return outcome;
+}
+
+/// Match the entry against its PMLR volume or NeurIPS year.
+fn lookupProceedings(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    candidates: *std.ArrayList(report_mod.Candidate),
+    tracer: *const Tracer,
+) !Outcome {
+    switch (proceedings.siteFor(local_entry).?) {
+        .pmlr => |volume| {
+            tracer.lookup("pmlr", "volume {d}", .{volume});
+            const results = sources.pmlr.?.volumeEntries(volume) catch |err| {
+                tracer.lookupFailed("pmlr", err);
+                return .failed;
+            };
+            defer freeResults(allocator, results);
+            return takeBestMatch(allocator, matching, local_entry, .pmlr, results, validation_results, candidates, tracer);
+        },
+        .neurips => |year| {
+            tracer.lookup("neurips", "proceedings {d}", .{year});
+            const results = sources.neurips.?.search(year, local_entry.title.?) catch |err| {
+                tracer.lookupFailed("neurips", err);
+                return .failed;
+            };
+            defer freeResults(allocator, results);
+            return takeBestMatch(allocator, matching, local_entry, .neurips, results, validation_results, candidates, tracer);
+        },
+    }
+}
+
+/// Search each API and plugin by title, keeping the best match of each.
+fn searchTitle(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    sources: *Sources,
+    validation_results: *std.ArrayList(ValidationResult),
+    candidates: *std.ArrayList(report_mod.Candidate),
+    tracer: *const Tracer,
+) !Outcome {
+    const title = local_entry.title.?;
+    var outcome: Outcome = .failed;
+
+    if (sources.dblp) |*dblp| {
+        tracer.lookup("dblp", "title search", .{});
+        if (dblp.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .dblp, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("dblp", err);
+        }
+    }
+
+    if (sources.semantic) |*semantic| {
+        tracer.lookup("semantic_scholar", "title search", .{});
+        if (semantic.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .semantic_scholar, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("semantic_scholar", err);
+        }
+    }
+
+    if (sources.openalex) |*openalex| {
+        tracer.lookup("openalex", "title search", .{});
+        if (openalex.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .openalex, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("openalex", err);
+        }
+    }
+
+    if (sources.ieee) |*ieee| {
+        tracer.lookup("ieee", "title search", .{});
+        if (ieee.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .ieee, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("ieee", err);
+        }
+    }
+
+    if (sources.acm) |*acm| {
+        tracer.lookup("acm", "title search", .{});
+        if (acm.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .acm, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("acm", err);
+        }
+    }
+
+    if (sources.springer) |*springer| {
+        tracer.lookup("springer", "title search", .{});
+        if (springer.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .springer, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("springer", err);
+        }
+    }
+
+    if (sources.scopus) |*scopus| {
+        tracer.lookup("scopus", "title search", .{});
+        if (scopus.searchByTitle(title)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .scopus, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.lookupFailed("scopus", err);
+        }
+    }
+
+    for (sources.plugins) |plugin| {
+        tracer.lookup(plugin.name, "plugin search", .{});
+        if (external.search(allocator, plugin, local_entry)) |results| {
+            defer freeResults(allocator, results);
+            outcome = outcome.combine(try takeBestMatch(allocator, matching, local_entry, .external, results, validation_results, candidates, tracer));
+        } else |err| {
+            tracer.event(.info, plugin.name, "plugin failed: {s}", .{@errorName(err)});
+        }
+    }
+
+    return outcome;
+}
+
+fn freeResults(allocator: std.mem.Allocator, results: []Entry) void {
+    for (results) |*r| r.deinit();
+    allocator.free(results);
+}
+
+/// Add the best match among search results, or keep the closest rejected
+/// candidates if none passes the match filters.
+fn takeBestMatch(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    source: ApiSource,
+    results: []const Entry,
+    validation_results: *std.ArrayList(ValidationResult),
+    candidates: *std.ArrayList(report_mod.Candidate),
+    tracer: *const Tracer,
+) !Outcome {
+    tracer.event(.info, "matcher", "{d} candidates", .{results.len});
+    const match = (try matcher.findBestMatch(allocator, matching, local_entry, results)) orelse {
+        try collectRejected(allocator, matching, local_entry, source, results, candidates);
+        return .not_found;
+    };
+    tracer.event(.info, "matcher", "best match {s} (score {d:.2})", .{ match.entry.key, match.score });
+    const discrepancies = try matcher.compareWith(allocator, matching.comparators, local_entry, match.entry);
+    try validation_results.append(allocator, .{
+        .source = source,
+        .matched_entry = try match.entry.clone(allocator),
+        .confidence = match.score,
+        .discrepancies = discrepancies,
+        .allocator = allocator,
+    });
+    return .matched;
+}
+
+/// Accept a record looked up by an identifier, taking ownership of it, if
+/// its title and year agree with the entry. A mistyped identifier would
+/// otherwise validate the entry against a different work.
+fn appendIdentifierMatch(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    source: ApiSource,
+    remote: Entry,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !Outcome {
+    var result = remote;
+    defer result.deinit();
+
+    const title_sim = try matcher.titleSimilarity(allocator, local_entry, &result);
+    tracer.event(.info, @tagName(source), "identifier record title similarity {d:.2}", .{title_sim});
+    if (title_sim < 0.75 or !matcher.yearsCompatible(matching, local_entry, &result)) return .mismatch;
+
+    const discrepancies = try matcher.compareWith(allocator, matching.comparators, local_entry, &result);
+    // Notes about where the identifier was written do not weaken the match
+    const agrees = for (discrepancies) |d| {
+        if (d.severity != .info) break false;
+    } else true;
+    try validation_results.append(allocator, .{
+        .source = source,
+        .matched_entry = try result.clone(allocator),
+        .confidence = if (agrees) 1.0 else 0.8,
+        .discrepancies = discrepancies,
+        .allocator = allocator,
+    });
+    return .matched;
+}
+
+/// Flag a GitHub URL whose repository does not exist.
+fn flagMissingRepository(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    validation_results: *std.ArrayList(ValidationResult),
+) !void {
+    const repo = github.repository(local_entry.url.?).?;
+    const message = try std.fmt.allocPrint(allocator, "Repository {s}/{s} does not exist on GitHub; it may have been renamed, moved, or deleted", .{ repo.owner, repo.name });
+    errdefer allocator.free(message);
+    const local_value = try allocator.dupe(u8, local_entry.url.?);
+    errdefer allocator.free(local_value);
+    const remote_value = try allocator.dupe(u8, "");
+    errdefer allocator.free(remote_value);
+
+    const discrepancies = try allocator.alloc(Discrepancy, 1);
+    errdefer allocator.free(discrepancies);
+    discrepancies[0] = .{
+        .rule = .missing_repository,
+        .field = .url,
+        .severity = rules.Rule.missing_repository.defaultSeverity(),
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = message,
+        .allocator = allocator,
+    };
+    // Not tied to a match: the repository was looked up directly
+    try validation_results.append(allocator, .{
+        .source = .github,
+        .confidence = 1.0,
+        .discrepancies = discrepancies,
+        .allocator = allocator,
+    });
+}
+
+/// Flag a DOI that resolves to a different work, suggesting the DOI of the
+/// record a title search matched instead, if it has one.
+fn flagWrongDoi(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    other_title: []const u8,
+    validation_results: *std.ArrayList(ValidationResult),
+) !void {
+    const doi = local_entry.doi.?;
+    var correct: ?[]const u8 = null;
+    for (validation_results.items) |result| {
+        const matched = result.matched_entry orelse continue;
+        const remote_doi = matched.doi orelse continue;
+        if (std.ascii.eqlIgnoreCase(remote_doi, doi)) continue;
+        correct = remote_doi;
+        break;
+    }
+
+    const message = if (correct) |c|
+        try std.fmt.allocPrint(allocator, "DOI points to a different work (\"{s}\"); the matched record has {s}", .{ other_title, c })
+    else
+        try std.fmt.allocPrint(allocator, "DOI points to a different work: \"{s}\"", .{other_title});
+    errdefer allocator.free(message);
+    const local_value = try allocator.dupe(u8, doi);
+    errdefer allocator.free(local_value);
+    const remote_value = try allocator.dupe(u8, correct orelse "");
+    errdefer allocator.free(remote_value);
+
+    const discrepancies = try allocator.alloc(Discrepancy, 1);
+    errdefer allocator.free(discrepancies);
+    discrepancies[0] = .{
+        .rule = .wrong_doi,
+        .field = .doi,
+        .severity = rules.Rule.wrong_doi.defaultSeverity(),
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = message,
+        .allocator = allocator,
+    };
+    // Not tied to a match: the DOI was resolved directly
+    try validation_results.append(allocator, .{
+        .source = .crossref,
+        .confidence = 1.0,
+        .discrepancies = discrepancies,
+        .allocator = allocator,
+    });
+}
+
+/// Rejected candidates listed for a not-found entry.
+const MAX_CANDIDATES = 3;
+
+/// Record the best candidates of a search that produced no match, so a
+/// not-found entry can say what came close and why it was rejected.
+fn collectRejected(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    source: ApiSource,
+    results: []const Entry,
+    candidates: *std.ArrayList(report_mod.Candidate),
+) !void {
+    const rejected = try matcher.topRejected(allocator, matching, local_entry, results, MAX_CANDIDATES);
+    defer allocator.free(rejected);
+
+    for (rejected) |r| {
+        const title = try allocator.dupe(u8, r.entry.title orelse "(untitled)");
+        errdefer allocator.free(title);
+        try candidates.append(allocator, .{
+            .source = source,
+            .title = title,
+            .year = r.entry.year,
+            .score = r.score,
+            .reason = r.reason,
+        });
+    }
+}
+
+fn higherCandidateScore(_: void, a: report_mod.Candidate, b: report_mod.Candidate) bool {
+    return a.score > b.score;
+}
+
+/// Verify funder names and award numbers, attaching findings to the
+/// CrossRef result (added without a match if the entry was found elsewhere).
+fn checkFunding(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    crossref: *validators.CrossRef,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !void {
+    var target: ?*ValidationResult = null;
+    for (validation_results.items) |*result| {
+        if (result.source == .crossref) {
+            target = result;
+            break;
+        }
+    }
+
+    var remote: ?*const Entry = null;
+    if (target) |t| {
+        if (t.matched_entry) |*m| remote = m;
+    }
+
+    const found = try funders.check(allocator, crossref, local_entry, remote);
+    tracer.event(.info, "funders", "{d} findings", .{found.len});
+    if (found.len == 0) {
+        allocator.free(found);
+        return;
+    }
+
+    if (target) |t| {
+        const merged = try std.mem.concat(allocator, Discrepancy, &.{ t.discrepancies, found });
+        allocator.free(t.discrepancies);
+        allocator.free(found);
+        t.discrepancies = merged;
+    } else {
+        try validation_results.append(allocator, .{
+            .source = .crossref,
+            .confidence = 0.0,
+            .discrepancies = found,
+            .allocator = allocator,
+        });
+    }
+}
+
+/// Fetch open-access status from OpenAlex by DOI unless a matched record
+/// already carries it.
+fn lookupAccess(
+    allocator: std.mem.Allocator,
+    matching: *const matcher.MatcherConfig,
+    local_entry: *const Entry,
+    openalex: *validators.OpenAlex,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !void {
+    var doi = local_entry.doi;
+    for (validation_results.items) |result| {
+        const matched = result.matched_entry orelse continue;
+        if (matched.oa_status != null) return;
+        if (doi == null) doi = matched.doi;
+    }
+    const d = doi orelse return;
+
+    tracer.lookup("openalex", "DOI lookup {s}", .{d});
+    const remote = openalex.searchByDoi(d) catch |err| {
+        tracer.lookupFailed("openalex", err);
+        return;
+    } orelse return;
+    var result = remote;
+    defer result.deinit();
+
+    const title_sim = try matcher.titleSimilarity(allocator, local_entry, &result);
+    tracer.event(.info, "openalex", "DOI record title similarity {d:.2}", .{title_sim});
+    if (title_sim < 0.75 or !matcher.yearsCompatible(matching, local_entry, &result)) return;
+
+    const discrepancies = try matcher.compareWith(allocator, matching.comparators, local_entry, &result);
+    try validation_results.append(allocator, .{
+        .source = .openalex,
+        .matched_entry = try result.clone(allocator),
+        .confidence = if (discrepancies.len == 0) 1.0 else 0.8,
+        .discrepancies = discrepancies,
+        .allocator = allocator,
+    });
+}
+
+/// Screen the venue, score and filter discrepancies, determine the entry
+/// status, and attach a copy of the entry.
+pub fn finishReport(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    validation_results: *std.ArrayList(ValidationResult),
+    disabled: rules.RuleSet,
+    min_confidence: f64,
+    screening: *const screening_mod.Screening,
+) !EntryReport {
+    if (!screening.isEmpty()) try screenVenue(allocator, local_entry, screening, validation_results);
+
+    for (validation_results.items) |*result| {
+        // Findings without a matched record (funders) do not depend on the match
+        const match_confidence = if (result.matched_entry != null) result.confidence else 1.0;
+        for (result.discrepancies) |*d| {
+            d.confidence = match_confidence * d.rule.signalQuality() * result.source.reliability();
+        }
+        result.discrepancies = try rules.removeDisabled(allocator, result.discrepancies, disabled);
+        result.discrepancies = try rules.removeBelowConfidence(allocator, result.discrepancies, min_confidence);
+    }
+
+    return EntryReport{
+        .entry = try local_entry.clone(allocator),
+        .status = report_mod.statusOf(validation_results.items),
+        .validation_results = try validation_results.toOwnedSlice(allocator),
+        .allocator = allocator,
+    };
+}
+
+/// Suggest a Wayback Machine snapshot of a web citation's page, the one
+/// closest to its `urldate`, saving the page first with --archive-urls if
+/// it was never archived. The finding is attached to the first matched
+/// record, or for an entry that was not found, to a result of its own,
+/// which leaves the entry not found.
+fn suggestArchive(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    archive: *wayback.Wayback,
+    validation_results: *std.ArrayList(ValidationResult),
+    tracer: *const Tracer,
+) !void {
+    const url = local_entry.url.?;
+    const accessed = if (local_entry.extraField("urldate")) |d| ignores.Date.parse(d) else null;
+    tracer.lookup("wayback", "snapshot of {s}", .{url});
+    var snapshot = archive.snapshot(url, accessed) catch |err| {
+        tracer.lookupFailed("wayback", err);
+        return;
+    };
+    defer if (snapshot) |s| allocator.free(s);
+    var saved = false;
+    if (snapshot == null and archive.save) {
+        tracer.event(.info, "wayback", "saving {s}", .{url});
+        snapshot = archive.archive(url) catch |err| blk: {
+            tracer.event(.info, "wayback", "save failed: {s}", .{@errorName(err)});
+            break :blk null;
+        };
+        saved = snapshot != null;
+    }
+
+    const message = if (snapshot) |s|
+        try std.fmt.allocPrint(allocator, "{s}: {s}", .{ if (saved) "No archived copy is cited; the page was archived" else "No archived copy is cited; the Wayback Machine has", s })
+    else
+        try allocator.dupe(u8, "No archived copy is cited, and the Wayback Machine has none (archive it with --archive-urls)");
+    errdefer allocator.free(message);
+    const local_value = try allocator.dupe(u8, url);
+    errdefer allocator.free(local_value);
+    const remote_value = try allocator.dupe(u8, snapshot orelse "");
+    errdefer allocator.free(remote_value);
+
+    const finding = Discrepancy{
+        .rule = .archive_url,
+        .field = .url,
+        .severity = rules.Rule.archive_url.defaultSeverity(),
+        .local_value = local_value,
+        .remote_value = remote_value,
+        .message = message,
+        .allocator = allocator,
+    };
+    const target = for (validation_results.items) |*result| {
+        if (result.matched_entry != null) break result;
+    } else {
+        const discrepancies = try allocator.alloc(Discrepancy, 1);
+        errdefer allocator.free(discrepancies);
+        discrepancies[0] = finding;
+        try validation_results.append(allocator, .{
+            .source = .web,
+            .confidence = 1.0,
+            .discrepancies = discrepancies,
+            .allocator = allocator,
+        });
+        return;
+    };
+    const merged = try std.mem.concat(allocator, Discrepancy, &.{ target.discrepancies, &[_]Discrepancy{finding} });
+    allocator.free(target.discrepancies);
+    target.discrepancies = merged;
+}
+
+/// Check the venue and publisher of the first matched record against the
+/// screening lists, attaching findings to that result.
+fn screenVenue(
+    allocator: std.mem.Allocator,
+    local_entry: *const Entry,
+    screening: *const screening_mod.Screening,
+    validation_results: *std.ArrayList(ValidationResult),
+) !void {
+    for (validation_results.items) |*result| {
+        const remote = if (result.matched_entry) |*m| m else continue;
+        const found = try screening_mod.check(allocator, screening, local_entry, remote);
+        if (found.len == 0) {
+            allocator.free(found);
+            return;
+        }
+        const merged = try std.mem.concat(allocator, Discrepancy, &.{ result.discrepancies, found });
+        allocator.free(result.discrepancies);
+        allocator.free(found);
+        result.discrepancies = merged;
+        return;
+    }
+}
+
+/// The Scopus EID of an entry, or of the Scopus record it matched.
+fn scopusEid(entry_report: *const EntryReport) ?[]const u8 {
+    if (entry_report.entry.scopus_eid) |eid| return eid;
+    for (entry_report.validation_results) |result| {
+        const matched = result.matched_entry orelse continue;
+        if (matched.scopus_eid) |eid| return eid;
+    }
+    return null;
+}
+
+/// Write a report as JSON, as for `bibval check --json`, with the findings
+/// of the reference list policies and the duplicate check, and the backend
+/// latencies of the run if `metrics` is given.
+pub fn writeJsonReport(allocator: std.mem.Allocator, writer: anytype, report: *const Report, policy: *const policy_mod.Evaluation, duplicates: *const policy_mod.Evaluation, consistency: *const policy_mod.Evaluation, metrics: ?*const Metrics) !void {
+    try writer.writeAll("{\"entries\":[");
+
+    var first = true;
+    for (report.entries.items) |entry_report| {
+        if (!first) try writer.writeAll(",");
+        first = false;
+
+        try writer.writeAll("{\"key\":");
+        try writeJsonString(writer, entry_report.entry.key);
+        try writer.writeAll(",\"title\":");
+        if (entry_report.entry.title) |t| {
+            try writeJsonString(writer, t);
+        } else {
+            try writer.writeAll("null");
+        }
+        if (entry_report.entry.extra.len > 0) {
+            try writer.writeAll(",\"extra\":{");
+            for (entry_report.entry.extra, 0..) |f, i| {
+                if (i > 0) try writer.writeAll(",");
+                try writeJsonString(writer, f.name);
+                try writer.writeAll(":");
+                try writeJsonString(writer, f.value);
+            }
+            try writer.writeAll("}");
+        }
+        try writer.writeAll(",\"status\":\"");
+        switch (entry_report.status) {
+            .ok => |source| try writer.print("ok:{s}", .{source.name()}),
+            .warning => try writer.writeAll("warning"),
+            .@"error" => try writer.writeAll("error"),
+            .not_found => try writer.writeAll("not_found"),
+            .failed => |msg| try writer.print("failed:{s}", .{msg}),
+            .known_unmatched => try writer.writeAll("known_unmatched"),
+        }
+        try writer.writeAll("\"");
+        if (entry_report.access()) |a| {
+            try writer.writeAll(",\"oa_status\":");
+            try writeJsonString(writer, a.oa_status);
+            try writer.writeAll(",\"license\":");
+            if (a.license) |l| {
+                try writeJsonString(writer, l);
+            } else {
+                try writer.writeAll("null");
+            }
+        }
+        if (entry_report.elapsed_ms) |ms| try writer.print(",\"elapsed_ms\":{d}", .{ms});
+        if (scopusEid(&entry_report)) |eid| {
+            try writer.writeAll(",\"scopus_eid\":");
+            try writeJsonString(writer, eid);
+        }
+        if (try entry_mod.merge(allocator, entry_report.validation_results)) |merged| {
+            var m = merged;
+            defer m.deinit();
+            try writer.writeAll(",\"provenance\":");
+            try writeProvenanceJson(writer, &m.provenance);
+        }
+        const complete = completeness.score(&entry_report);
+        try writer.print(",\"completeness\":{{\"score\":{d},\"missing\":[", .{complete.percent()});
+        var missing = complete.missing().iterator();
+        var missing_first = true;
+        while (missing.next()) |field| {
+            if (!missing_first) try writer.writeAll(",");
+            missing_first = false;
+            try writer.print("\"{s}\"", .{@tagName(field)});
+        }
+        try writer.writeAll("]}");
+        if (entry_report.candidates.len > 0) {
+            try writer.writeAll(",\"candidates\":[");
+            for (entry_report.candidates, 0..) |candidate, i| {
+                if (i > 0) try writer.writeAll(",");
+                try writer.print("{{\"source\":\"{s}\",\"title\":", .{@tagName(candidate.source)});
+                try writeJsonString(writer, candidate.title);
+                if (candidate.year) |y| try writer.print(",\"year\":{d}", .{y});
+                try writer.print(",\"score\":{d:.2},\"reason\":\"{s}\"}}", .{ candidate.score, @tagName(candidate.reason) });
+            }
+            try writer.writeAll("]");
+        }
+        if (entry_report.lookups.len > 0) {
+            try writer.writeAll(",\"lookups\":[");
+            for (entry_report.lookups, 0..) |lookup, i| {
+                if (i > 0) try writer.writeAll(",");
+                try writer.print("{{\"strategy\":\"{s}\",\"outcome\":\"{s}\"}}", .{ @tagName(lookup.strategy), @tagName(lookup.outcome) });
+            }
+            try writer.writeAll("]");
+        }
+        try writer.writeAll(",\"discrepancies\":[");
+
+        var disc_first = true;
+        for (entry_report.validation_results) |result| {
+            for (result.discrepancies) |d| {
+                if (!disc_first) try writer.writeAll(",");
+                disc_first = false;
+
+                try writer.print("{{\"rule\":\"{s}\",\"rule_name\":\"{s}\",\"source\":\"{s}\",\"field\":\"", .{ d.rule.id(), d.rule.slug(), @tagName(result.source) });
+                try writer.writeAll(d.field.name());
+                try writer.writeAll("\",\"severity\":\"");
+                try writer.writeAll(d.severity.name());
+                try writer.print("\",\"confidence\":{d:.2},\"message\":", .{d.confidence});
+                try writeJsonString(writer, d.message);
+                if (d.rule == .missing_doi) {
+                    if (fix.suggestedDoi(&entry_report)) |doi| {
+                        try writer.writeAll(",\"suggestion\":{\"field\":\"doi\",\"value\":");
+                        try writeJsonString(writer, doi.value);
+                        try writer.print(",\"confidence\":{d:.2}}}", .{doi.confidence});
+                    }
+                }
+                try writer.writeAll("}");
+            }
+        }
+        try writer.writeAll("]}");
+    }
+
+    try writer.writeAll("],\"policy\":");
+    try policy.writeJson(writer);
+    try writer.writeAll(",\"duplicates\":");
+    try duplicates.writeJson(writer);
+    try writer.writeAll(",\"consistency\":");
+    try consistency.writeJson(writer);
+    try writer.writeAll(",\"summary\":{");
+    try writer.print("\"total\":{d},\"ok\":{d},\"warnings\":{d},\"errors\":{d},\"not_found\":{d}", .{
+        report.entries.items.len,
+        report.countOk(),
+        report.countWarnings(),
+        report.countErrors(),
+        report.countNotFound(),
+    });
+    if (completeness.grade(report)) |g| {
+        try writer.print(",\"completeness\":{d},\"grade\":\"{c}\"", .{ g.percent, g.letter() });
+    }
+    if (report.entryTiming()) |t| {
+        try writer.print(",\"timing\":{{\"entries\":{d},\"avg_ms\":{d},\"max_ms\":{d},\"slowest\":", .{ t.count, t.averageMs(), t.max_ms });
+        try writeJsonString(writer, t.slowest);
+        try writer.writeAll("}");
+    }
+    if (metrics) |m| {
+        try writer.writeAll(",\"backends\":");
+        try m.writeJson(writer);
+    }
+    try writer.writeAll("}}\n");
+}
+
+/// Write the source of each merged field as a JSON object, e.g.
+/// `{"title":"crossref","year":"dblp"}`.
+fn writeProvenanceJson(writer: anytype, provenance: *const entry_mod.Provenance) !void {
+    try writer.writeAll("{");
+    var first = true;
+    for (std.enums.values(entry_mod.DiscrepancyField)) |field| {
+        const source = provenance.get(field) orelse continue;
+        if (!first) try writer.writeAll(",");
+        first = false;
+        try writer.print("\"{s}\":\"{s}\"", .{ @tagName(field), @tagName(source) });
+    }
+    try writer.writeAll("}");
+}
+
+test "JSON reports carry the access status and license" {
+    const allocator = std.testing.allocator;
+    var open = [_]ValidationResult{.{ .source = .openalex, .confidence = 1.0, .matched_entry = .{ .key = "W1", .entry_type = "article", .oa_status = "gold", .license = "cc-by" } }};
+    var closed = [_]ValidationResult{.{ .source = .openalex, .confidence = 1.0, .matched_entry = .{ .key = "W2", .entry_type = "article", .oa_status = "closed" } }};
+
+    var report = Report.init(allocator);
+    defer report.entries.deinit(allocator);
+    try report.add(.{ .entry = .{ .key = "open2020", .entry_type = "article" }, .status = .{ .ok = .openalex }, .validation_results = &open });
+    try report.add(.{ .entry = .{ .key = "closed2015", .entry_type = "article" }, .status = .{ .ok = .openalex }, .validation_results = &closed });
+    try report.add(.{ .entry = .{ .key = "missing", .entry_type = "article" }, .status = .not_found, .validation_results = &.{} });
+
+    var policy = policy_mod.Evaluation{ .arena = std.heap.ArenaAllocator.init(allocator) };
+    defer policy.deinit();
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeJsonReport(allocator, &out.writer, &report, &policy, &policy, &policy, null);
+
+    const parsed = try std.json.parseFromSlice(std.json.Value, allocator, out.written(), .{});
+    defer parsed.deinit();
+    const entries = parsed.value.object.get("entries").?.array.items;
+    try std.testing.expectEqualStrings("gold", entries[0].object.get("oa_status").?.string);
+    try std.testing.expectEqualStrings("cc-by", entries[0].object.get("license").?.string);
+    try std.testing.expectEqualStrings("closed", entries[1].object.get("oa_status").?.string);
+    try std.testing.expect(entries[1].object.get("license").? == .null);
+    try std.testing.expect(entries[2].object.get("oa_status") == null);
+}
@@ -0,0 +1,319 @@
+//! Dry-run query planning.
+//!
+//! Predicts, without any network calls, which lookup strategy each entry
+//! will use and how many requests each backend will receive. The
+//! validation pipeline tries the same strategies in the same order.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const cache = @import("cache.zig");
+const proceedings = @import("proceedings.zig");
+const validators = @import("validators.zig");
+const webpage = @import("webpage.zig");
+const github = @import("github.zig");
+const deposits = @import("deposits.zig");
+const Entry = entry_mod.Entry;
+const ApiSource = entry_mod.ApiSource;
+
+/// Assumed round-trip time of a single API request, used for estimates.
+pub const DEFAULT_LATENCY_MS: u64 = 500;
+
+/// Lookup strategy chosen for an entry.
+pub const Strategy = enum {
+    deposit_lookup,
+    doi_lookup,
+    isbn_lookup,
+    proceedings_lookup,
+    arxiv_lookup,
+    scopus_lookup,
+    repo_lookup,
+    url_lookup,
+    title_search,
+    skip,
+
+    pub fn name(self: Strategy) []const u8 {
+        return switch (self) {
+            .deposit_lookup => "Deposit lookup",
+            .doi_lookup => "DOI lookup",
+            .isbn_lookup => "ISBN lookup",
+            .arxiv_lookup => "arXiv lookup",
+            .scopus_lookup => "Scopus lookup",
+            .repo_lookup => "GitHub lookup",
+            .url_lookup => "URL lookup",
+            .proceedings_lookup => "Proceedings lookup",
+            .title_search => "Title search",
+            .skip => "Skipped",
+        };
+    }
+};
+
+/// Which backends are enabled for a run.
+pub const Backends = struct {
+    crossref: bool = true,
+    dblp: bool = true,
+    semantic_scholar: bool = true,
+    openalex: bool = true,
+    open_library: bool = true,
+    google_books: bool = true,
+    arxiv: bool = true,
+    pmlr: bool = true,
+    neurips: bool = true,
+    github: bool = true,
+    zenodo: bool = true,
+    figshare: bool = true,
+    web: bool = true,
+    /// Off unless an API key is configured.
+    ieee: bool = false,
+    acm: bool = false,
+    springer: bool = false,
+    scopus: bool = false,
+    /// Wayback Machine snapshots are suggested for web citations
+    /// (--suggest-archives); not a lookup backend.
+    wayback: bool = false,
+    /// Pages without a snapshot are saved (--archive-urls)
+    save_pages: bool = false,
+    /// Number of configured validator plugins.
+    external: usize = 0,
+
+    pub fn isEnabled(self: Backends, source: ApiSource) bool {
+        return switch (source) {
+            .crossref => self.crossref,
+            .dblp => self.dblp,
+            .semantic_scholar => self.semantic_scholar,
+            .openalex => self.openalex,
+            .open_library => self.open_library,
+            .google_books => self.google_books,
+            .arxiv => self.arxiv,
+            .pmlr => self.pmlr,
+            .neurips => self.neurips,
+            .ieee => self.ieee,
+            .acm => self.acm,
+            .springer => self.springer,
+            .scopus => self.scopus,
+            .github => self.github,
+            .zenodo => self.zenodo,
+            .figshare => self.figshare,
+            .web => self.web,
+            .external => self.external > 0,
+        };
+    }
+
+    /// The backends of `self` that `allowed` contains.
+    pub fn restrict(self: Backends, allowed: std.EnumSet(ApiSource)) Backends {
+        var result = self;
+        inline for (comptime std.enums.values(ApiSource)) |source| {
+            if (source != .external and !allowed.contains(source)) @field(result, @tagName(source)) = false;
+        }
+        if (!allowed.contains(.external)) result.external = 0;
+        return result;
+    }
+
+    /// Backends queried by title search.
+    pub fn titleSearchCount(self: Backends) usize {
+        var count: usize = 0;
+        inline for (.{ ApiSource.dblp, ApiSource.semantic_scholar, ApiSource.openalex, ApiSource.ieee, ApiSource.acm, ApiSource.springer, ApiSource.scopus }) |source| {
+            if (self.isEnabled(source)) count += 1;
+        }
+        return count + self.external;
+    }
+};
+
+/// Lookup strategies for an entry, tried in declaration order until one
+/// matches: a Zenodo or Figshare deposit, whose DataCite DOIs CrossRef
+/// does not have, the DOI and ISBN, the proceedings site of PMLR and
+/// NeurIPS papers (ahead of their arXiv preprint, which lacks the volume
+/// and pages), the other identifiers, the GitHub repository of software,
+/// the page a web citation links to, then a title search. Empty if the
+/// entry cannot be looked up at all.
+pub fn strategiesFor(e: *const Entry, backends: Backends) std.EnumSet(Strategy) {
+    var result = std.EnumSet(Strategy).initEmpty();
+    if (deposits.depositFor(e)) |deposit| {
+        const enabled = switch (deposit) {
+            .zenodo => backends.zenodo,
+            .figshare => backends.figshare,
+        };
+        if (enabled and e.title != null) result.insert(.deposit_lookup);
+    }
+    if (e.doi != null and backends.crossref) result.insert(.doi_lookup);
+    if (e.isbn != null and (backends.open_library or backends.google_books)) result.insert(.isbn_lookup);
+    if (e.arxiv_id != null and (backends.arxiv or backends.semantic_scholar)) result.insert(.arxiv_lookup);
+    if (e.scopus_eid != null and backends.scopus) result.insert(.scopus_lookup);
+    if (e.url != null and github.repository(e.url.?) != null and e.title != null and backends.github) result.insert(.repo_lookup);
+    if (webpage.isWebEntry(e) and e.title != null and backends.web) result.insert(.url_lookup);
+    if (proceedings.siteFor(e)) |site| {
+        const enabled = switch (site) {
+            .pmlr => backends.pmlr,
+            .neurips => e.title != null and backends.neurips,
+        };
+        if (enabled) result.insert(.proceedings_lookup);
+    }
+    if (e.title != null and backends.titleSearchCount() > 0) result.insert(.title_search);
+    return result;
+}
+
+/// Choose the initial lookup strategy for an entry.
+pub fn strategyFor(e: *const Entry, backends: Backends) Strategy {
+    var strategies = strategiesFor(e, backends).iterator();
+    return strategies.next() orelse .skip;
+}
+
+/// Aggregated request plan for a set of entries.
+pub const Plan = struct {
+    entries: usize = 0,
+    strategies: std.EnumArray(Strategy, usize) = .initFill(0),
+    requests: std.EnumArray(ApiSource, usize) = .initFill(0),
+    /// Requests that will be served from the local cache.
+    cached: std.EnumArray(ApiSource, usize) = .initFill(0),
+
+    /// Number of requests that will actually hit the network.
+    pub fn networkRequests(self: *const Plan) usize {
+        var total: usize = 0;
+        for (std.enums.values(ApiSource)) |source| {
+            total += self.requests.get(source) - self.cached.get(source);
+        }
+        return total;
+    }
+
+    /// Estimated wall time in milliseconds for a sequential run.
+    pub fn estimatedMs(self: *const Plan, latency_ms: u64, interval_ms: u64) u64 {
+        return @as(u64, @intCast(self.networkRequests())) * @max(latency_ms, interval_ms);
+    }
+
+    /// Count the requests for `e` with `backends`.
+    pub fn add(self: *Plan, e: *const Entry, backends: Backends, response_cache: ?*cache.Cache) void {
+        self.entries += 1;
+        const strategy = strategyFor(e, backends);
+        self.strategies.getPtr(strategy).* += 1;
+
+        switch (strategy) {
+            .deposit_lookup => switch (deposits.depositFor(e).?) {
+                .zenodo => self.requests.getPtr(.zenodo).* += 1,
+                .figshare => self.requests.getPtr(.figshare).* += 1,
+            },
+            .doi_lookup => {
+                self.requests.getPtr(.crossref).* += 1;
+                if (response_cache) |c| {
+                    if (c.contains("crossref_doi", e.doi.?)) self.cached.getPtr(.crossref).* += 1;
+                }
+                if (backends.ieee and validators.Ieee.isIeeeDoi(e.doi.?)) {
+                    self.requests.getPtr(.ieee).* += 1;
+                    if (response_cache) |c| {
+                        if (c.contains("ieee_doi", e.doi.?)) self.cached.getPtr(.ieee).* += 1;
+                    }
+                }
+                if (backends.springer and validators.Springer.isSpringerDoi(e.doi.?)) {
+                    self.requests.getPtr(.springer).* += 1;
+                    if (response_cache) |c| {
+                        if (c.contains("springer_doi", e.doi.?)) self.cached.getPtr(.springer).* += 1;
+                    }
+                }
+            },
+            .isbn_lookup => {
+                self.requests.getPtr(if (backends.open_library) .open_library else .google_books).* += 1;
+            },
+            .arxiv_lookup => {
+                self.requests.getPtr(if (backends.arxiv) .arxiv else .semantic_scholar).* += 1;
+            },
+            .scopus_lookup => {
+                self.requests.getPtr(.scopus).* += 1;
+                if (response_cache) |c| {
+                    if (c.contains("scopus_eid", e.scopus_eid.?)) self.cached.getPtr(.scopus).* += 1;
+                }
+            },
+            .repo_lookup => {
+                const repo = github.repository(e.url.?).?;
+                var key_buf: [256]u8 = undefined;
+                const key = std.fmt.bufPrint(&key_buf, "{s}/{s}", .{ repo.owner, repo.name }) catch "";
+                // The repository and its CITATION.cff
+                _ = self.addGitHub("github_repo", key, response_cache);
+                _ = self.addGitHub("github_cff", key, response_cache);
+                // Each form of a cited version's tag until one is known to
+                // exist, and the latest release if none is: at most all of them
+                const version = github.citedVersion(e) orelse return;
+                var buf: [github.MAX_TAG_LEN]u8 = undefined;
+                var candidates: [3][]const u8 = undefined;
+                for (github.tagCandidates(version, &buf, &candidates)) |tag| {
+                    var ref_buf: [320]u8 = undefined;
+                    const ref_key = std.fmt.bufPrint(&ref_buf, "{s}@{s}", .{ key, tag }) catch "";
+                    if (self.addGitHub("github_ref", ref_key, response_cache)) return;
+                }
+                _ = self.addGitHub("github_latest", key, response_cache);
+            },
+            .url_lookup => {
+                self.requests.getPtr(.web).* += 1;
+                if (response_cache) |c| {
+                    if (c.contains("web_page", e.url.?)) self.cached.getPtr(.web).* += 1;
+                }
+            },
+            .proceedings_lookup => switch (proceedings.siteFor(e).?) {
+                .pmlr => self.requests.getPtr(.pmlr).* += 1,
+                // The year page, then the BibTeX of each close title
+                .neurips => self.requests.getPtr(.neurips).* += 2,
+            },
+            .title_search => {
+                inline for (.{ ApiSource.dblp, ApiSource.semantic_scholar, ApiSource.openalex, ApiSource.ieee, ApiSource.acm, ApiSource.springer, ApiSource.scopus }) |source| {
+                    if (backends.isEnabled(source)) self.requests.getPtr(source).* += 1;
+                }
+                self.requests.getPtr(.external).* += backends.external;
+            },
+            .skip => {},
+        }
+    }
+
+    /// Count a GitHub request for `key` under `api`; true if the response
+    /// is cached. Only found records are cached.
+    fn addGitHub(self: *Plan, api: []const u8, key: []const u8, response_cache: ?*cache.Cache) bool {
+        self.requests.getPtr(.github).* += 1;
+        const c = response_cache orelse return false;
+        if (!c.contains(api, key)) return false;
+        self.cached.getPtr(.github).* += 1;
+        return true;
+    }
+};
+
+/// Build a plan for `entries`. When `response_cache` is given, cached
+/// DOI lookups are counted separately.
+pub fn build(entries: []const Entry, backends: Backends, response_cache: ?*cache.Cache) Plan {
+    var result = Plan{};
+    for (entries) |*e| result.add(e, backends, response_cache);
+    return result;
+}
+
+test "plan counts requests per backend" {
+    const entries = [_]Entry{
+        .{ .key = "a", .entry_type = "article", .title = "A", .doi = "10.1/a" },
+        .{ .key = "b", .entry_type = "article", .title = "B" },
+        .{ .key = "c", .entry_type = "misc" },
+        .{ .key = "d", .entry_type = "book", .title = "D", .isbn = "9780262033848" },
+    };
+
+    const p = build(&entries, .{ .semantic_scholar = false, .external = 2 }, null);
+    try std.testing.expectEqual(@as(usize, 1), p.strategies.get(.doi_lookup));
+    try std.testing.expectEqual(@as(usize, 1), p.strategies.get(.title_search));
+    try std.testing.expectEqual(@as(usize, 1), p.strategies.get(.isbn_lookup));
+    try std.testing.expectEqual(@as(usize, 1), p.strategies.get(.skip));
+    try std.testing.expectEqual(@as(usize, 1), p.requests.get(.open_library));
+    try std.testing.expectEqual(@as(usize, 1), p.requests.get(.crossref));
+    try std.testing.expectEqual(@as(usize, 1), p.requests.get(.dblp));
+    try std.testing.expectEqual(@as(usize, 0), p.requests.get(.semantic_scholar));
+    try std.testing.expectEqual(@as(usize, 2), p.requests.get(.external));
+    try std.testing.expectEqual(@as(u64, 6 * DEFAULT_LATENCY_MS), p.estimatedMs(DEFAULT_LATENCY_MS, 0));
+
+    const arxiv_only = Entry{ .key = "e", .entry_type = "article", .title = "E", .arxiv_id = "2101.00001" };
+    const strategies = strategiesFor(&arxiv_only, .{});
+    try std.testing.expect(strategies.contains(.arxiv_lookup) and strategies.contains(.title_search));
+    try std.testing.expectEqual(Strategy.arxiv_lookup, strategyFor(&arxiv_only, .{}));
+
+    const blog_post = Entry{ .key = "f", .entry_type = "online", .title = "F", .url = "https://example.org/f" };
+    try std.testing.expectEqual(Strategy.url_lookup, strategyFor(&blog_post, .{}));
+    try std.testing.expectEqual(Strategy.title_search, strategyFor(&blog_post, .{ .web = false }));
+
+    // The repository, its CITATION.cff, "2.1", "v2.1", and the latest release
+    const software = Entry{ .key = "h", .entry_type = "software", .title = "H", .url = "https://github.com/owner/h", .extra = &.{.{ .name = "version", .value = "2.1" }} };
+    const repo_plan = build(&.{software}, .{}, null);
+    try std.testing.expectEqual(Strategy.repo_lookup, strategyFor(&software, .{}));
+    try std.testing.expectEqual(@as(usize, 5), repo_plan.requests.get(.github));
+
+    const pmlr_preprint = Entry{ .key = "g", .entry_type = "inproceedings", .title = "G", .arxiv_id = "2101.00002", .url = "https://proceedings.mlr.press/v139/g21a.html" };
+    try std.testing.expectEqual(Strategy.proceedings_lookup, strategyFor(&pmlr_preprint, .{}));
+}
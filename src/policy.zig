@@ -0,0 +1,380 @@
+//! Reference list policies.
+//!
+//! Some venues and funders restrict the references a manuscript may use,
+//! e.g. requiring a year on every reference or limiting how many may be
+//! older than a given age, style guides want an access date on web
+//! citations, and labs have house-style rules for fields (see
+//! `field_rules`). Policies are set in the `[policy]` section of the config
+//! file and evaluated on the whole report after matching.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const rules = @import("rules.zig");
+const field_rules = @import("field_rules.zig");
+const regex = @import("regex.zig");
+const webpage = @import("webpage.zig");
+const fix = @import("fix.zig");
+const screening_mod = @import("screening.zig");
+const Date = @import("ignores.zig").Date;
+const Severity = entry_mod.Severity;
+const Report = report_mod.Report;
+
+pub const Policy = struct {
+    /// Report references without a year.
+    require_year: bool = false,
+    /// Age in years beyond which a reference counts as old.
+    max_age: ?u32 = null,
+    /// Largest allowed percentage of old references.
+    max_old_percent: u32 = 0,
+    /// Severity of the old-references finding.
+    old_severity: Severity = .warning,
+    /// Report web citations without a `urldate`.
+    require_urldate: bool = false,
+    /// Pattern `urldate` values must match: `ISO_DATE`, `BIBLATEX_DATE`, or
+    /// a regular expression from the config.
+    urldate_format: ?[]const u8 = null,
+    /// Age in months beyond which a `urldate` is too old.
+    max_urldate_age: ?u32 = null,
+    /// House-style rules for the fields of each entry.
+    field_rules: []const field_rules.FieldRule = &.{},
+
+    pub fn isEmpty(self: Policy) bool {
+        return !self.require_year and self.max_age == null and !self.checksUrldate() and self.field_rules.len == 0;
+    }
+
+    fn checksUrldate(self: Policy) bool {
+        return self.require_urldate or self.urldate_format != null or self.max_urldate_age != null;
+    }
+};
+
+/// `urldate_format = "iso"`: a calendar date, "2024-03-01".
+pub const ISO_DATE = "^\\d\\d\\d\\d-\\d\\d-\\d\\d$";
+/// `urldate_format = "biblatex"`: a year, year and month, or date,
+/// optionally with a time, as biblatex reads them.
+pub const BIBLATEX_DATE = "^\\d\\d\\d\\d(-\\d\\d(-\\d\\d(T\\d\\d:\\d\\d(:\\d\\d)?(Z|[+-]\\d\\d:\\d\\d)?)?)?)?$";
+
+/// What is wrong with the access dates of a reference list.
+const UrldateFindings = struct {
+    missing: std.ArrayList([]const u8) = .empty,
+    malformed: std.ArrayList([]const u8) = .empty,
+    stale: std.ArrayList([]const u8) = .empty,
+};
+
+/// Sort the web citations among `entries` by what is wrong with their
+/// `urldate`. Allocates with `arena`.
+fn checkUrldates(arena: std.mem.Allocator, entries: []const *const entry_mod.Entry, policy: Policy, today: Date) !UrldateFindings {
+    var result = UrldateFindings{};
+    var format: ?regex.Regex = if (policy.urldate_format) |pattern| try regex.Regex.compile(arena, pattern) else null;
+    defer if (format) |*re| re.deinit();
+
+    for (entries) |e| {
+        if (!webpage.isWebEntry(e)) continue;
+        const date = e.extraField("urldate") orelse {
+            if (policy.require_urldate) try result.missing.append(arena, e.key);
+            continue;
+        };
+        if (format) |*re| {
+            if (!try re.isMatch(date)) {
+                try result.malformed.append(arena, e.key);
+                continue;
+            }
+        }
+        if (policy.max_urldate_age) |max_age| {
+            const month = monthIndex(date) orelse continue;
+            const now = @as(i64, today.year) * 12 + today.month - 1;
+            if (now - month > @as(i64, max_age)) try result.stale.append(arena, e.key);
+        }
+    }
+    return result;
+}
+
+/// Months since year 0 of a date that starts "YYYY-MM".
+fn monthIndex(date: []const u8) ?i64 {
+    if (date.len < 7 or date[4] != '-') return null;
+    const year = std.fmt.parseInt(i64, date[0..4], 10) catch return null;
+    const month = std.fmt.parseInt(i64, date[5..7], 10) catch return null;
+    if (month < 1 or month > 12) return null;
+    return year * 12 + month - 1;
+}
+
+/// "2024-03-01"
+fn isoDate(date: Date) [10]u8 {
+    var buf: [10]u8 = undefined;
+    _ = std.fmt.bufPrint(&buf, "{d:0>4}-{d:0>2}-{d:0>2}", .{ @as(u32, @intCast(@max(0, date.year))), date.month, date.day }) catch unreachable;
+    return buf;
+}
+
+/// Fixes setting today's date as the `urldate` of web citations that lack
+/// one or whose access date is too old; the pages should be checked again
+/// before such a fix is applied. Allocates with `arena`.
+pub fn urldateFixes(arena: std.mem.Allocator, entries: []const entry_mod.Entry, policy: Policy, today: Date, fixes: *std.ArrayList(fix.Fix)) !void {
+    if (!policy.checksUrldate()) return;
+    const pointers = try arena.alloc(*const entry_mod.Entry, entries.len);
+    for (entries, pointers) |*e, *p| p.* = e;
+    const found = try checkUrldates(arena, pointers, policy, today);
+    const value = try arena.dupe(u8, &isoDate(today));
+    for ([_][]const []const u8{ found.missing.items, found.stale.items }) |keys| {
+        for (keys) |key| try fixes.append(arena, .{ .key = key, .field = "urldate", .value = value });
+    }
+}
+
+/// A policy the reference list breaks.
+pub const Violation = struct {
+    rule: rules.Rule,
+    severity: Severity,
+    message: []const u8,
+    /// Entries that contribute to the violation.
+    keys: []const []const u8,
+};
+
+pub const Evaluation = struct {
+    /// Section title in the text report.
+    heading: []const u8 = "POLICY",
+    violations: []const Violation = &.{},
+    arena: std.heap.ArenaAllocator,
+
+    pub fn deinit(self: *Evaluation) void {
+        self.arena.deinit();
+    }
+
+    pub fn count(self: *const Evaluation, severity: Severity) usize {
+        var n: usize = 0;
+        for (self.violations) |v| {
+            if (v.severity == severity) n += 1;
+        }
+        return n;
+    }
+
+    pub fn print(self: *const Evaluation, writer: anytype) !void {
+        if (self.violations.len == 0) return;
+
+        try writer.print("{s} ({d})\n", .{ self.heading, self.violations.len });
+        for (self.violations) |v| {
+            try writer.print("  {s} {s} {s}\n", .{ v.severity.name(), v.rule.id(), v.message });
+            for (v.keys) |key| try writer.print("    [{s}]\n", .{key});
+        }
+        try writer.writeAll("\n");
+    }
+
+    pub fn writeJson(self: *const Evaluation, writer: anytype) !void {
+        try writer.writeAll("[");
+        for (self.violations, 0..) |v, i| {
+            if (i > 0) try writer.writeAll(",");
+            try writer.print("{{\"rule\":\"{s}\",\"rule_name\":\"{s}\",\"severity\":\"{s}\",\"message\":", .{ v.rule.id(), v.rule.slug(), v.severity.name() });
+            try report_mod.writeJsonString(writer, v.message);
+            try writer.writeAll(",\"keys\":[");
+            for (v.keys, 0..) |key, j| {
+                if (j > 0) try writer.writeAll(",");
+                try report_mod.writeJsonString(writer, key);
+            }
+            try writer.writeAll("]}");
+        }
+        try writer.writeAll("]");
+    }
+};
+
+/// Evaluate `policy` against a report. Years missing locally are taken
+/// from the first matched record for the age check. The venues of entries
+/// without a matched record, which the pipeline cannot screen against one,
+/// are screened here as written.
+pub fn evaluate(allocator: std.mem.Allocator, report: *const Report, policy: Policy, screening: *const screening_mod.Screening, today: Date, disabled: rules.RuleSet) !Evaluation {
+    var result = Evaluation{ .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer result.deinit();
+    const arena = result.arena.allocator();
+
+    var violations: std.ArrayList(Violation) = .empty;
+    var missing: std.ArrayList([]const u8) = .empty;
+    var old: std.ArrayList([]const u8) = .empty;
+    var dated: usize = 0;
+
+    for (report.entries.items) |*entry_report| {
+        const local = &entry_report.entry;
+        if (local.year == null) try missing.append(arena, local.key);
+
+        const year = local.year orelse matchedYear(entry_report) orelse continue;
+        dated += 1;
+        if (policy.max_age) |max_age| {
+            if (today.year - year > @as(i32, @intCast(max_age))) try old.append(arena, local.key);
+        }
+    }
+
+    if (policy.require_year and missing.items.len > 0 and !disabled.contains(.missing_year)) {
+        try violations.append(arena, .{
+            .rule = .missing_year,
+            .severity = rules.Rule.missing_year.defaultSeverity(),
+            .message = try std.fmt.allocPrint(arena, "{d} references have no year", .{missing.items.len}),
+            .keys = missing.items,
+        });
+    }
+
+    if (policy.max_age) |max_age| {
+        const percent = if (dated == 0) 0 else old.items.len * 100 / dated;
+        if (percent > policy.max_old_percent and !disabled.contains(.old_references)) {
+            try violations.append(arena, .{
+                .rule = .old_references,
+                .severity = policy.old_severity,
+                .message = try std.fmt.allocPrint(arena, "{d}% of references are older than {d} years (at most {d}% allowed)", .{ percent, max_age, policy.max_old_percent }),
+                .keys = old.items,
+            });
+        }
+    }
+
+    const entries = try arena.alloc(*const entry_mod.Entry, report.entries.items.len);
+    for (report.entries.items, entries) |*entry_report, *e| e.* = &entry_report.entry;
+
+    if (policy.checksUrldate() and !disabled.contains(.urldate)) {
+        const found = try checkUrldates(arena, entries, policy, today);
+        const severity = rules.Rule.urldate.defaultSeverity();
+        if (found.missing.items.len > 0) {
+            try violations.append(arena, .{
+                .rule = .urldate,
+                .severity = severity,
+                .message = try std.fmt.allocPrint(arena, "{d} web citations have no urldate", .{found.missing.items.len}),
+                .keys = found.missing.items,
+            });
+        }
+        if (found.malformed.items.len > 0) {
+            try violations.append(arena, .{
+                .rule = .urldate,
+                .severity = severity,
+                .message = try std.fmt.allocPrint(arena, "{d} web citations have a urldate not matching {s}", .{ found.malformed.items.len, policy.urldate_format.? }),
+                .keys = found.malformed.items,
+            });
+        }
+        if (found.stale.items.len > 0) {
+            try violations.append(arena, .{
+                .rule = .urldate,
+                .severity = severity,
+                .message = try std.fmt.allocPrint(arena, "{d} web citations were accessed more than {d} months ago", .{ found.stale.items.len, policy.max_urldate_age.? }),
+                .keys = found.stale.items,
+            });
+        }
+    }
+
+    if (policy.field_rules.len > 0 and !disabled.contains(.field_rule)) {
+        for (policy.field_rules) |*rule| {
+            const keys = try field_rules.check(arena, rule, entries);
+            if (keys.len == 0) continue;
+            try violations.append(arena, .{
+                .rule = .field_rule,
+                .severity = rule.severity,
+                .message = try rule.describe(arena),
+                .keys = keys,
+            });
+        }
+    }
+
+    if (!screening.isEmpty()) {
+        for (report.entries.items) |*entry_report| {
+            if (entry_report.status == .known_unmatched or hasMatch(entry_report)) continue;
+            const local = &entry_report.entry;
+            for (try screening_mod.check(arena, screening, local, local)) |d| {
+                if (disabled.contains(d.rule)) continue;
+                const keys = try arena.alloc([]const u8, 1);
+                keys[0] = local.key;
+                try violations.append(arena, .{ .rule = d.rule, .severity = d.severity, .message = d.message, .keys = keys });
+            }
+        }
+    }
+
+    result.violations = violations.items;
+    return result;
+}
+
+fn hasMatch(entry_report: *const report_mod.EntryReport) bool {
+    for (entry_report.validation_results) |result| {
+        if (result.matched_entry != null) return true;
+    }
+    return false;
+}
+
+fn matchedYear(entry_report: *const report_mod.EntryReport) ?i32 {
+    for (entry_report.validation_results) |result| {
+        const matched = result.matched_entry orelse continue;
+        if (matched.year) |y| return y;
+    }
+    return null;
+}
+
+test "age and missing year policies" {
+    const allocator = std.testing.allocator;
+
+    var report = Report.init(allocator);
+    defer report.deinit();
+    var none = [_]entry_mod.ValidationResult{};
+    try report.add(.{ .entry = .{ .key = "old", .entry_type = "article", .year = 1990 }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "new", .entry_type = "article", .year = 2024 }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "undated", .entry_type = "misc" }, .status = .not_found, .validation_results = &none });
+
+    const today = Date{ .year = 2025, .month = 6, .day = 1 };
+    var eval = try evaluate(allocator, &report, .{ .require_year = true, .max_age = 10, .max_old_percent = 40 }, &.{}, today, .initEmpty());
+    defer eval.deinit();
+
+    try std.testing.expectEqual(@as(usize, 2), eval.violations.len);
+    try std.testing.expectEqual(rules.Rule.missing_year, eval.violations[0].rule);
+    try std.testing.expectEqualStrings("undated", eval.violations[0].keys[0]);
+    try std.testing.expectEqual(rules.Rule.old_references, eval.violations[1].rule);
+    try std.testing.expectEqualStrings("old", eval.violations[1].keys[0]);
+
+    var lenient = try evaluate(allocator, &report, .{ .max_age = 10, .max_old_percent = 50 }, &.{}, today, .initEmpty());
+    defer lenient.deinit();
+    try std.testing.expectEqual(@as(usize, 0), lenient.violations.len);
+}
+
+test "urldate policy" {
+    const allocator = std.testing.allocator;
+
+    var report = Report.init(allocator);
+    defer report.deinit();
+    var none = [_]entry_mod.ValidationResult{};
+    try report.add(.{ .entry = .{ .key = "unread", .entry_type = "online", .url = "https://example.org/a" }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "stale", .entry_type = "online", .url = "https://example.org/b", .extra = &.{.{ .name = "urldate", .value = "2023-01-15" }} }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "spelled", .entry_type = "misc", .url = "https://example.org/c", .extra = &.{.{ .name = "urldate", .value = "March 3, 2025" }} }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "fresh", .entry_type = "misc", .url = "https://example.org/d", .extra = &.{.{ .name = "urldate", .value = "2025-05-20" }} }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "paper", .entry_type = "article", .url = "https://example.org/e" }, .status = .not_found, .validation_results = &none });
+
+    const today = Date{ .year = 2025, .month = 6, .day = 1 };
+    const strict = Policy{ .require_urldate = true, .urldate_format = ISO_DATE, .max_urldate_age = 12 };
+    var eval = try evaluate(allocator, &report, strict, &.{}, today, .initEmpty());
+    defer eval.deinit();
+
+    try std.testing.expectEqual(@as(usize, 3), eval.violations.len);
+    try std.testing.expectEqualStrings("unread", eval.violations[0].keys[0]);
+    try std.testing.expectEqualStrings("spelled", eval.violations[1].keys[0]);
+    try std.testing.expectEqualStrings("stale", eval.violations[2].keys[0]);
+    for (eval.violations) |v| try std.testing.expectEqual(@as(usize, 1), v.keys.len);
+
+    var arena = std.heap.ArenaAllocator.init(allocator);
+    defer arena.deinit();
+    const entries = [_]entry_mod.Entry{ report.entries.items[0].entry, report.entries.items[1].entry };
+    var fixes: std.ArrayList(fix.Fix) = .empty;
+    try urldateFixes(arena.allocator(), &entries, strict, today, &fixes);
+    try std.testing.expectEqual(@as(usize, 2), fixes.items.len);
+    try std.testing.expectEqualStrings("2025-06-01", fixes.items[1].value);
+
+    var disabled = rules.RuleSet.initEmpty();
+    disabled.insert(.urldate);
+    var quiet = try evaluate(allocator, &report, strict, &.{}, today, disabled);
+    defer quiet.deinit();
+    try std.testing.expectEqual(@as(usize, 0), quiet.violations.len);
+}
+
+test "venues of unmatched entries are screened" {
+    const allocator = std.testing.allocator;
+
+    var report = Report.init(allocator);
+    defer report.deinit();
+    var none = [_]entry_mod.ValidationResult{};
+    try report.add(.{ .entry = .{ .key = "lost", .entry_type = "article", .venue = "International Journal of Everything" }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "other", .entry_type = "article", .venue = "Nature" }, .status = .not_found, .validation_results = &none });
+
+    const screening = screening_mod.Screening{ .blocked_venues = &.{"International Journal of Everything"} };
+    const today = Date{ .year = 2025, .month = 6, .day = 1 };
+    var eval = try evaluate(allocator, &report, .{}, &screening, today, .initEmpty());
+    defer eval.deinit();
+
+    try std.testing.expectEqual(@as(usize, 1), eval.violations.len);
+    try std.testing.expectEqual(rules.Rule.blocked_venue, eval.violations[0].rule);
+    try std.testing.expectEqualStrings("lost", eval.violations[0].keys[0]);
+}
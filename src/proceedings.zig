@@ -0,0 +1,246 @@
+//! Validators for the PMLR and NeurIPS proceedings sites.
+//!
+//! Volume and page numbers of ML conference papers are often wrong, and
+//! the aggregators frequently lack them. Both sites publish BibTeX for
+//! every paper: PMLR one file per volume, NeurIPS one file per paper,
+//! listed on a page per year. The records keep `volume` and `pages` as
+//! extra fields, where the volume and pages checks compare them.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const scrape = @import("scrape.zig");
+const bibtex = @import("bibtex.zig");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const venues = @import("venues.zig");
+const validators = @import("validators.zig");
+const Entry = entry_mod.Entry;
+const ValidatorError = validators.ValidatorError;
+
+/// Where the proceedings of an entry can be looked up.
+pub const Site = union(enum) {
+    /// PMLR volume number
+    pmlr: u32,
+    /// Year of the NeurIPS conference
+    neurips: i32,
+};
+
+/// The proceedings site that has the entry, if it can be told from its
+/// URL, venue, series, or volume.
+pub fn siteFor(e: *const Entry) ?Site {
+    if (e.url) |url| {
+        if (pmlrVolume(url)) |volume| return .{ .pmlr = volume };
+    }
+    if (e.extraField("volume")) |volume| {
+        const number = std.fmt.parseInt(u32, std.mem.trim(u8, volume, " "), 10) catch null;
+        if (number != null and (namesPmlr(e.venue) or namesPmlr(e.extraField("series")) or namesPmlr(e.publisher))) {
+            return .{ .pmlr = number.? };
+        }
+    }
+
+    const year = e.year orelse return null;
+    if (year < NeurIps.FIRST_YEAR) return null;
+    if (e.url) |url| {
+        if (std.mem.indexOf(u8, url, "papers.nips.cc") != null or std.mem.indexOf(u8, url, "neurips.cc") != null) return .{ .neurips = year };
+    }
+    if (e.venue) |venue| {
+        if (venues.lookup("NeurIPS").?.matches(venue)) return .{ .neurips = year };
+    }
+    return null;
+}
+
+/// The volume in a proceedings.mlr.press URL, e.g. 139 in
+/// "https://proceedings.mlr.press/v139/smith21a.html".
+fn pmlrVolume(url: []const u8) ?u32 {
+    const marker = "proceedings.mlr.press/v";
+    const start = (std.mem.indexOf(u8, url, marker) orelse return null) + marker.len;
+    var end = start;
+    while (end < url.len and std.ascii.isDigit(url[end])) end += 1;
+    return std.fmt.parseInt(u32, url[start..end], 10) catch null;
+}
+
+fn namesPmlr(value: ?[]const u8) bool {
+    const v = value orelse return false;
+    return std.ascii.indexOfIgnoreCase(v, "Machine Learning Research") != null or std.ascii.indexOfIgnoreCase(v, "PMLR") != null;
+}
+
+/// Proceedings of Machine Learning Research.
+pub const Pmlr = struct {
+    allocator: std.mem.Allocator,
+    scraper: scrape.Scraper,
+
+    const BASE_URL = "https://proceedings.mlr.press";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Pmlr {
+        return .{
+            .allocator = allocator,
+            .scraper = scrape.Scraper.init(allocator, client, response_cache),
+        };
+    }
+
+    /// Every paper in a volume, from its BibTeX index. Caller owns the
+    /// entries.
+    pub fn volumeEntries(self: *Pmlr, volume: u32) ![]Entry {
+        var key_buf: [16]u8 = undefined;
+        const key = std.fmt.bufPrint(&key_buf, "v{d}", .{volume}) catch unreachable;
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/{s}/assets/bib/bibliography.bib", .{ BASE_URL, key });
+        defer self.allocator.free(url);
+        const body = try fetch(&self.scraper, "pmlr_volume", key, url);
+        defer self.allocator.free(body);
+
+        return bibtex.parseString(self.allocator, body);
+    }
+};
+
+/// The NeurIPS (formerly NIPS) proceedings at papers.nips.cc.
+pub const NeurIps = struct {
+    allocator: std.mem.Allocator,
+    scraper: scrape.Scraper,
+
+    const BASE_URL = "https://papers.nips.cc/paper_files/paper";
+    /// The first conference, whose proceedings are volume 1.
+    pub const FIRST_YEAR: i32 = 1987;
+    /// Closest listed titles whose BibTeX is fetched.
+    const MAX_RESULTS = 3;
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) NeurIps {
+        return .{
+            .allocator = allocator,
+            .scraper = scrape.Scraper.init(allocator, client, response_cache),
+        };
+    }
+
+    /// Papers of the `year` conference whose titles are closest to `title`.
+    /// Caller owns the entries.
+    pub fn search(self: *NeurIps, year: i32, title: []const u8) ![]Entry {
+        var year_buf: [12]u8 = undefined;
+        const year_key = std.fmt.bufPrint(&year_buf, "{d}", .{year}) catch unreachable;
+        const listing_url = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ BASE_URL, year_key });
+        defer self.allocator.free(listing_url);
+        const listing = try fetch(&self.scraper, "neurips_year", year_key, listing_url);
+        defer self.allocator.free(listing);
+
+        const papers = try closestPapers(self.allocator, listing, title);
+        defer self.allocator.free(papers);
+
+        var entries: std.ArrayList(Entry) = .empty;
+        errdefer {
+            for (entries.items) |*e| e.deinit();
+            entries.deinit(self.allocator);
+        }
+        for (papers) |paper| {
+            const bib_url = try std.fmt.allocPrint(self.allocator, "{s}/{s}/file/{s}-Bibtex.bib", .{ BASE_URL, year_key, paper.hash });
+            defer self.allocator.free(bib_url);
+            // Skip papers whose BibTeX is missing
+            const body = fetch(&self.scraper, "neurips_bib", paper.hash, bib_url) catch continue;
+            defer self.allocator.free(body);
+
+            const parsed = try bibtex.parseString(self.allocator, body);
+            defer self.allocator.free(parsed);
+            if (parsed.len == 0) continue;
+            for (parsed[1..]) |*e| e.deinit();
+            entries.append(self.allocator, parsed[0]) catch |err| {
+                parsed[0].deinit();
+                return err;
+            };
+        }
+        return entries.toOwnedSlice(self.allocator);
+    }
+};
+
+/// A paper on a NeurIPS year page; borrows from the page.
+pub const Paper = struct {
+    title: []const u8,
+    /// Identifies the paper's files, e.g. "3f5ee243547dee91fbd053c1c4a845aa"
+    hash: []const u8,
+};
+
+/// Papers linked from a NeurIPS year page, e.g.
+/// `<a href="/paper_files/paper/2017/hash/3f5e...-Abstract.html">Attention is All you Need</a>`.
+pub fn parseListing(allocator: std.mem.Allocator, html: []const u8) ![]Paper {
+    var papers: std.ArrayList(Paper) = .empty;
+    errdefer papers.deinit(allocator);
+
+    var pos: usize = 0;
+    while (std.mem.indexOfPos(u8, html, pos, "/hash/")) |start| {
+        pos = start + "/hash/".len;
+        const dash = std.mem.indexOfScalarPos(u8, html, pos, '-') orelse break;
+        const hash = html[pos..dash];
+        if (hash.len == 0 or std.mem.indexOfAny(u8, hash, "\"/<> ") != null) continue;
+
+        const text_start = (std.mem.indexOfScalarPos(u8, html, dash, '>') orelse break) + 1;
+        const text_end = std.mem.indexOfPos(u8, html, text_start, "</a>") orelse break;
+        const title = std.mem.trim(u8, html[text_start..text_end], " \t\r\n");
+        pos = text_end;
+        if (title.len == 0 or std.mem.indexOfScalar(u8, title, '<') != null) continue;
+        try papers.append(allocator, .{ .title = title, .hash = hash });
+    }
+    return papers.toOwnedSlice(allocator);
+}
+
+/// Up to `NeurIps.MAX_RESULTS` listed papers with titles close to `title`,
+/// closest first.
+fn closestPapers(allocator: std.mem.Allocator, html: []const u8, title: []const u8) ![]Paper {
+    const papers = try parseListing(allocator, html);
+    defer allocator.free(papers);
+
+    const wanted = try entry_mod.normalizeString(allocator, title);
+    defer allocator.free(wanted);
+
+    const Scored = struct { paper: Paper, score: f64 };
+    var scored: std.ArrayList(Scored) = .empty;
+    defer scored.deinit(allocator);
+    for (papers) |paper| {
+        const norm = try entry_mod.normalizeString(allocator, paper.title);
+        defer allocator.free(norm);
+        const score = try matcher.jaroWinklerSimilarity(allocator, wanted, norm);
+        if (score >= matcher.TITLE_MATCH_THRESHOLD) try scored.append(allocator, .{ .paper = paper, .score = score });
+    }
+    std.sort.pdq(Scored, scored.items, {}, struct {
+        fn higher(_: void, a: Scored, b: Scored) bool {
+            return a.score > b.score;
+        }
+    }.higher);
+
+    const kept = @min(scored.items.len, NeurIps.MAX_RESULTS);
+    const result = try allocator.alloc(Paper, kept);
+    for (scored.items[0..kept], result) |s, *paper| paper.* = s.paper;
+    return result;
+}
+
+/// GET `url` through `scraper`, or read it from the cache under `api` and
+/// `key`. A page robots.txt disallows counts as not found.
+fn fetch(scraper: *scrape.Scraper, api: []const u8, key: []const u8, url: []const u8) ValidatorError![]u8 {
+    return (try scraper.fetch(api, key, url)) orelse ValidatorError.NotFound;
+}
+
+test "proceedings sites and NeurIPS listing" {
+    const allocator = std.testing.allocator;
+
+    const pmlr_url = Entry{ .key = "a", .entry_type = "inproceedings", .url = "https://proceedings.mlr.press/v139/smith21a.html" };
+    try std.testing.expectEqual(Site{ .pmlr = 139 }, siteFor(&pmlr_url).?);
+    const pmlr_series = Entry{
+        .key = "b",
+        .entry_type = "inproceedings",
+        .venue = "Proceedings of the 38th International Conference on Machine Learning",
+        .extra = &.{ .{ .name = "volume", .value = "139" }, .{ .name = "series", .value = "Proceedings of Machine Learning Research" } },
+    };
+    try std.testing.expectEqual(Site{ .pmlr = 139 }, siteFor(&pmlr_series).?);
+    const neurips = Entry{ .key = "c", .entry_type = "inproceedings", .venue = "Advances in Neural Information Processing Systems 30", .year = 2017 };
+    try std.testing.expectEqual(Site{ .neurips = 2017 }, siteFor(&neurips).?);
+    const other = Entry{ .key = "d", .entry_type = "article", .venue = "Nature", .year = 2017 };
+    try std.testing.expect(siteFor(&other) == null);
+
+    const html =
+        \\<ul><li class="conference"><div class="paper-content">
+        \\<a title="paper title" href="/paper_files/paper/2017/hash/3f5ee243547dee91fbd053c1c4a845aa-Abstract.html">Attention is All you Need</a>
+        \\<i>Ashish Vaswani, Noam Shazeer</i></div></li>
+        \\<li><a href="/paper_files/paper/2017/hash/0a1b-Abstract.html">Deep Sets</a></li></ul>
+    ;
+    const papers = try closestPapers(allocator, html, "Attention Is All You Need");
+    defer allocator.free(papers);
+    try std.testing.expectEqual(@as(usize, 1), papers.len);
+    try std.testing.expectEqualStrings("3f5ee243547dee91fbd053c1c4a845aa", papers[0].hash);
+}
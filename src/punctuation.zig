@@ -0,0 +1,211 @@
+//! Typographic punctuation in field values.
+//!
+//! Entries pasted from PDFs and publisher pages are full of curly quotes,
+//! non-breaking spaces, and Unicode dashes. BibTeX on pdfLaTeX sets them
+//! unlike the rest of the bibliography, or fails on them, while biblatex
+//! with Biber on XeLaTeX or LuaLaTeX takes them as they are. With
+//! `fix.engine` in the config, every field is checked against the engine
+//! (BV040), and `--format patch` writes them as LaTeX markup
+//! (`` ``quotes'' ``, `--`, `~`) for `latex`, or the markup as Unicode
+//! characters for `unicode`.
+//! Nothing here needs a lookup.
+
+const std = @import("std");
+const fix = @import("fix.zig");
+const Entry = @import("entry.zig").Entry;
+
+/// The engine the bibliography is typeset with.
+pub const Engine = enum {
+    /// Punctuation is left as written.
+    preserve,
+    /// BibTeX, or biblatex on pdfLaTeX: LaTeX markup only.
+    latex,
+    /// biblatex with Biber on XeLaTeX or LuaLaTeX: Unicode characters.
+    unicode,
+};
+
+const Replacement = struct {
+    unicode: []const u8,
+    latex: []const u8,
+    /// The markup always stands for this character, so `unicode` converts
+    /// it back ("'" is also an apostrophe, "-" a hyphen).
+    reversible: bool = true,
+};
+
+/// Longer markup first, so "---" is not read as "--" and "-".
+const replacements = [_]Replacement{
+    .{ .unicode = "\u{201C}", .latex = "``" },
+    .{ .unicode = "\u{201D}", .latex = "''" },
+    .{ .unicode = "\u{2018}", .latex = "`" },
+    .{ .unicode = "\u{2019}", .latex = "'", .reversible = false },
+    .{ .unicode = "\u{2014}", .latex = "---" },
+    .{ .unicode = "\u{2013}", .latex = "--" },
+    .{ .unicode = "\u{2010}", .latex = "-", .reversible = false },
+    .{ .unicode = "\u{2011}", .latex = "-", .reversible = false },
+    .{ .unicode = "\u{00A0}", .latex = "~" },
+    .{ .unicode = "\u{202F}", .latex = "\\,", .reversible = false },
+    .{ .unicode = "\u{2009}", .latex = "\\,", .reversible = false },
+};
+
+/// Fields holding identifiers or links, never rewritten. `note` and
+/// `howpublished` often hold a bare URL or a `\url{}` with one.
+const verbatim_fields = [_][]const u8{ "url", "doi", "eprint", "file", "pdf", "isbn", "issn", "urldate", "note", "howpublished" };
+
+/// Commands whose first argument is a URL, copied as written.
+const link_commands = [_][]const u8{ "\\url{", "\\href{" };
+
+/// `value` with its punctuation written for `engine`, or null if it
+/// already is.
+pub fn convert(allocator: std.mem.Allocator, value: []const u8, engine: Engine) !?[]const u8 {
+    if (engine == .preserve) return null;
+
+    var out: std.ArrayList(u8) = .empty;
+    errdefer out.deinit(allocator);
+    var changed = false;
+    var i: usize = 0;
+    next: while (i < value.len) {
+        if (linkEnd(value, i)) |end| {
+            try out.appendSlice(allocator, value[i..end]);
+            i = end;
+            continue;
+        }
+        // `\'`, `` \` `` and `\~` are accents, not punctuation
+        const escaped = i > 0 and value[i - 1] == '\\';
+        for (replacements) |r| {
+            if (engine == .unicode and (!r.reversible or escaped)) continue;
+            const from = if (engine == .latex) r.unicode else r.latex;
+            const to = if (engine == .latex) r.latex else r.unicode;
+            if (std.mem.startsWith(u8, value[i..], from)) {
+                try out.appendSlice(allocator, to);
+                i += from.len;
+                changed = true;
+                continue :next;
+            }
+        }
+        try out.append(allocator, value[i]);
+        i += 1;
+    }
+
+    if (!changed) {
+        out.deinit(allocator);
+        return null;
+    }
+    return try out.toOwnedSlice(allocator);
+}
+
+/// End of the URL argument of a `\url{}` or `\href{}` at `start`, or null
+/// if no such command starts there.
+fn linkEnd(value: []const u8, start: usize) ?usize {
+    for (link_commands) |command| {
+        if (!std.mem.startsWith(u8, value[start..], command)) continue;
+        var depth: usize = 1;
+        var i = start + command.len;
+        while (i < value.len) : (i += 1) {
+            switch (value[i]) {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if (depth == 0) return i + 1;
+                },
+                else => {},
+            }
+        }
+        return value.len;
+    }
+    return null;
+}
+
+/// A field of an entry whose punctuation is not written for the engine.
+pub const Change = struct {
+    field: []const u8,
+    value: []const u8,
+    converted: []const u8,
+};
+
+/// The fields of `e` to rewrite for `engine`. Allocates with `arena`.
+pub fn changes(arena: std.mem.Allocator, e: *const Entry, engine: Engine, out: *std.ArrayList(Change)) !void {
+    if (engine == .preserve) return;
+
+    if (e.title) |title| try add(arena, "title", title, engine, out);
+    if (e.authors.len > 0) {
+        const authors = try std.mem.join(arena, " and ", e.authors);
+        try add(arena, "author", authors, engine, out);
+    }
+    if (e.venue) |venue| {
+        if (fix.entryFixField(.venue_variant, e)) |field| try add(arena, field, venue, engine, out);
+    }
+    if (e.publisher) |publisher| try add(arena, "publisher", publisher, engine, out);
+    for (e.extra) |field| {
+        if (rewrites(field.name, engine)) try add(arena, field.name, field.value, engine, out);
+    }
+}
+
+fn add(arena: std.mem.Allocator, field: []const u8, value: []const u8, engine: Engine, out: *std.ArrayList(Change)) !void {
+    const converted = (try convert(arena, value, engine)) orelse return;
+    try out.append(arena, .{ .field = field, .value = value, .converted = converted });
+}
+
+/// Whether the field `name` is written for `engine`.
+fn rewrites(name: []const u8, engine: Engine) bool {
+    for (verbatim_fields) |field| {
+        if (std.ascii.eqlIgnoreCase(name, field)) return false;
+    }
+    // biblatex reads "--" in page ranges itself
+    return engine != .unicode or !std.ascii.eqlIgnoreCase(name, "pages");
+}
+
+/// Fixes writing the punctuation of every entry for `engine`.
+pub fn fixes(arena: std.mem.Allocator, entries: []const Entry, engine: Engine, out: *std.ArrayList(fix.Fix)) !void {
+    for (entries) |*e| {
+        var found: std.ArrayList(Change) = .empty;
+        try changes(arena, e, engine, &found);
+        for (found.items) |change| {
+            try out.append(arena, .{ .key = e.key, .field = change.field, .value = change.converted });
+        }
+    }
+}
+
+/// Write the values of `suggested`, e.g. titles taken from a matched
+/// record, for `engine` too.
+pub fn convertFixes(arena: std.mem.Allocator, suggested: []fix.Fix, engine: Engine) !void {
+    for (suggested) |*f| {
+        if (f.macro or !rewrites(f.field, engine)) continue;
+        if (try convert(arena, f.value, engine)) |converted| f.value = converted;
+    }
+}
+
+test "punctuation for each engine" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    const pasted = "\u{201C}Attention\u{201D} is all you need \u{2014} pages 1\u{2013}10, Jones\u{2019}s\u{00A0}model";
+    try std.testing.expectEqualStrings("``Attention'' is all you need --- pages 1--10, Jones's~model", (try convert(allocator, pasted, .latex)).?);
+    try std.testing.expectEqualStrings("\u{201C}Attention\u{201D} is all you need \u{2014} pages 1\u{2013}10, Jones's\u{00A0}model", (try convert(allocator, "``Attention'' is all you need --- pages 1--10, Jones's~model", .unicode)).?);
+    // Accents and plain ASCII stay
+    try std.testing.expectEqual(@as(?[]const u8, null), try convert(allocator, "Erd\\H{o}s and Pe\\~na's self-attention", .unicode));
+    try std.testing.expectEqual(@as(?[]const u8, null), try convert(allocator, "plain", .latex));
+    try std.testing.expectEqual(@as(?[]const u8, null), try convert(allocator, pasted, .preserve));
+    // Link targets stay, link text does not
+    try std.testing.expectEqualStrings("See \\href{https://example.org/a--b}{pages 1--10} and \\url{https://example.org/c~d}", (try convert(allocator, "See \\href{https://example.org/a--b}{pages 1\u{2013}10} and \\url{https://example.org/c~d}", .latex)).?);
+    try std.testing.expectEqual(@as(?[]const u8, null), try convert(allocator, "\\url{https://example.org/~smith/a--b}", .unicode));
+
+    const e = Entry{
+        .key = "vaswani2017",
+        .entry_type = "inproceedings",
+        .title = "Attention Is All You Need",
+        .venue = "Advances in Neural Information Processing Systems \u{2013} NIPS",
+        .extra = &.{
+            .{ .name = "pages", .value = "5998\u{2013}6008" },
+            .{ .name = "url", .value = "https://example.org/a\u{2013}b" },
+            .{ .name = "note", .value = "Available at https://example.org/a\u{2013}b" },
+        },
+    };
+    var out: std.ArrayList(fix.Fix) = .empty;
+    try fixes(allocator, &.{e}, .latex, &out);
+    try std.testing.expectEqual(@as(usize, 2), out.items.len);
+    try std.testing.expectEqualStrings("booktitle", out.items[0].field);
+    try std.testing.expectEqualStrings("Advances in Neural Information Processing Systems -- NIPS", out.items[0].value);
+    try std.testing.expectEqualStrings("pages", out.items[1].field);
+    try std.testing.expectEqualStrings("5998--6008", out.items[1].value);
+}
@@ -0,0 +1,313 @@
+//! A small regular expression matcher for config-defined checks.
+//!
+//! Supports literals, `.`, character classes (`[a-z0-9_]`, `[^,]`), the
+//! escapes `\d`, `\w`, and `\s`, anchors `^` and `$`, groups with `|`, and
+//! the quantifiers `*`, `+`, and `?`. Patterns are compiled to a small
+//! instruction set and run as a Thompson NFA, so matching takes linear
+//! time whatever the pattern.
+
+const std = @import("std");
+
+pub const Error = error{InvalidPattern} || std.mem.Allocator.Error;
+
+const Class = std.StaticBitSet(256);
+
+const Inst = union(enum) {
+    char: u8,
+    any,
+    class: Class,
+    split: struct { a: usize, b: usize },
+    jmp: usize,
+    bol,
+    eol,
+    match,
+};
+
+const Node = union(enum) {
+    char: u8,
+    any,
+    class: Class,
+    bol,
+    eol,
+    concat: []const usize,
+    alt: [2]usize,
+    star: usize,
+    plus: usize,
+    quest: usize,
+};
+
+pub const Regex = struct {
+    insts: []const Inst,
+    allocator: std.mem.Allocator,
+
+    /// Compile `pattern`, or return `error.InvalidPattern`.
+    pub fn compile(allocator: std.mem.Allocator, pattern: []const u8) Error!Regex {
+        var arena = std.heap.ArenaAllocator.init(allocator);
+        defer arena.deinit();
+
+        var parser = Parser{ .arena = arena.allocator(), .pattern = pattern };
+        const root = try parser.parseAlt();
+        if (parser.pos != pattern.len) return error.InvalidPattern;
+
+        var insts: std.ArrayList(Inst) = .empty;
+        errdefer insts.deinit(allocator);
+        try emit(allocator, &insts, parser.nodes.items, root);
+        try insts.append(allocator, .match);
+        return .{ .insts = try insts.toOwnedSlice(allocator), .allocator = allocator };
+    }
+
+    pub fn deinit(self: *Regex) void {
+        self.allocator.free(self.insts);
+    }
+
+    /// Whether the pattern matches anywhere in `text`; anchor it with `^`
+    /// and `$` to match the whole text.
+    pub fn isMatch(self: *const Regex, text: []const u8) !bool {
+        const n = self.insts.len;
+        const buf = try self.allocator.alloc(usize, 2 * n);
+        defer self.allocator.free(buf);
+        const marks = try self.allocator.alloc(usize, n);
+        defer self.allocator.free(marks);
+        @memset(marks, std.math.maxInt(usize));
+
+        var current: Threads = .{ .pcs = buf[0..n] };
+        var next: Threads = .{ .pcs = buf[n..] };
+        var pos: usize = 0;
+        while (true) : (pos += 1) {
+            // Unanchored: a match may start at any position
+            if (self.addThread(&current, marks, pos, 0, text, pos)) return true;
+            if (pos == text.len) return false;
+
+            next.len = 0;
+            for (current.pcs[0..current.len]) |pc| {
+                const advances = switch (self.insts[pc]) {
+                    .char => |c| text[pos] == c,
+                    .any => text[pos] != '\n',
+                    .class => |class| class.isSet(text[pos]),
+                    else => false,
+                };
+                if (advances and self.addThread(&next, marks, pos + 1, pc + 1, text, pos + 1)) return true;
+            }
+            std.mem.swap(Threads, &current, &next);
+        }
+    }
+
+    /// Add `pc` and the instructions reachable from it without consuming
+    /// input to the threads at `pos`; true if that reaches a match. Marks
+    /// keep each instruction in the list once.
+    fn addThread(self: *const Regex, threads: *Threads, marks: []usize, generation: usize, pc: usize, text: []const u8, pos: usize) bool {
+        if (marks[pc] == generation) return false;
+        marks[pc] = generation;
+        switch (self.insts[pc]) {
+            .match => return true,
+            .jmp => |target| return self.addThread(threads, marks, generation, target, text, pos),
+            .split => |s| {
+                if (self.addThread(threads, marks, generation, s.a, text, pos)) return true;
+                return self.addThread(threads, marks, generation, s.b, text, pos);
+            },
+            .bol => return pos == 0 and self.addThread(threads, marks, generation, pc + 1, text, pos),
+            .eol => return pos == text.len and self.addThread(threads, marks, generation, pc + 1, text, pos),
+            else => {
+                threads.pcs[threads.len] = pc;
+                threads.len += 1;
+                return false;
+            },
+        }
+    }
+};
+
+const Threads = struct {
+    pcs: []usize,
+    len: usize = 0,
+};
+
+const Parser = struct {
+    arena: std.mem.Allocator,
+    pattern: []const u8,
+    pos: usize = 0,
+    nodes: std.ArrayList(Node) = .empty,
+
+    fn add(self: *Parser, node: Node) !usize {
+        try self.nodes.append(self.arena, node);
+        return self.nodes.items.len - 1;
+    }
+
+    fn peek(self: *const Parser) ?u8 {
+        return if (self.pos < self.pattern.len) self.pattern[self.pos] else null;
+    }
+
+    fn parseAlt(self: *Parser) Error!usize {
+        var left = try self.parseConcat();
+        while (self.peek() == '|') {
+            self.pos += 1;
+            const right = try self.parseConcat();
+            left = try self.add(.{ .alt = .{ left, right } });
+        }
+        return left;
+    }
+
+    fn parseConcat(self: *Parser) Error!usize {
+        var items: std.ArrayList(usize) = .empty;
+        while (self.peek()) |c| {
+            if (c == '|' or c == ')') break;
+            try items.append(self.arena, try self.parseRepeat());
+        }
+        return self.add(.{ .concat = items.items });
+    }
+
+    fn parseRepeat(self: *Parser) Error!usize {
+        const atom = try self.parseAtom();
+        const c = self.peek() orelse return atom;
+        const node: Node = switch (c) {
+            '*' => .{ .star = atom },
+            '+' => .{ .plus = atom },
+            '?' => .{ .quest = atom },
+            else => return atom,
+        };
+        self.pos += 1;
+        if (self.peek()) |after| {
+            if (after == '*' or after == '+' or after == '?') return error.InvalidPattern;
+        }
+        return self.add(node);
+    }
+
+    fn parseAtom(self: *Parser) Error!usize {
+        const c = self.pattern[self.pos];
+        self.pos += 1;
+        switch (c) {
+            '(' => {
+                const inner = try self.parseAlt();
+                if (self.peek() != ')') return error.InvalidPattern;
+                self.pos += 1;
+                return inner;
+            },
+            '[' => return self.add(.{ .class = try self.parseClass() }),
+            '.' => return self.add(.any),
+            '^' => return self.add(.bol),
+            '$' => return self.add(.eol),
+            '*', '+', '?' => return error.InvalidPattern,
+            '\\' => {
+                const escaped = self.peek() orelse return error.InvalidPattern;
+                self.pos += 1;
+                if (escapeClass(escaped)) |class| return self.add(.{ .class = class });
+                return self.add(.{ .char = escaped });
+            },
+            else => return self.add(.{ .char = c }),
+        }
+    }
+
+    /// The class after an opening `[`, up to and including its `]`.
+    fn parseClass(self: *Parser) Error!Class {
+        var class = Class.initEmpty();
+        const negated = self.peek() == '^';
+        if (negated) self.pos += 1;
+
+        var first = true;
+        while (true) {
+            const c = self.peek() orelse return error.InvalidPattern;
+            self.pos += 1;
+            // A leading ']' is a literal
+            if (c == ']' and !first) break;
+            first = false;
+
+            var low = c;
+            if (c == '\\') {
+                const escaped = self.peek() orelse return error.InvalidPattern;
+                self.pos += 1;
+                if (escapeClass(escaped)) |escaped_class| {
+                    class.setUnion(escaped_class);
+                    continue;
+                }
+                low = escaped;
+            }
+            if (self.peek() == '-' and self.pos + 1 < self.pattern.len and self.pattern[self.pos + 1] != ']') {
+                const high = self.pattern[self.pos + 1];
+                self.pos += 2;
+                if (high < low) return error.InvalidPattern;
+                class.setRangeValue(.{ .start = low, .end = @as(usize, high) + 1 }, true);
+            } else {
+                class.set(low);
+            }
+        }
+        if (negated) class.toggleAll();
+        return class;
+    }
+};
+
+fn escapeClass(c: u8) ?Class {
+    var class = Class.initEmpty();
+    switch (c) {
+        'd' => class.setRangeValue(.{ .start = '0', .end = '9' + 1 }, true),
+        'w' => {
+            class.setRangeValue(.{ .start = 'a', .end = 'z' + 1 }, true);
+            class.setRangeValue(.{ .start = 'A', .end = 'Z' + 1 }, true);
+            class.setRangeValue(.{ .start = '0', .end = '9' + 1 }, true);
+            class.set('_');
+        },
+        's' => for (" \t\r\n") |space| class.set(space),
+        else => return null,
+    }
+    return class;
+}
+
+fn emit(allocator: std.mem.Allocator, insts: *std.ArrayList(Inst), nodes: []const Node, index: usize) !void {
+    switch (nodes[index]) {
+        .char => |c| try insts.append(allocator, .{ .char = c }),
+        .any => try insts.append(allocator, .any),
+        .class => |class| try insts.append(allocator, .{ .class = class }),
+        .bol => try insts.append(allocator, .bol),
+        .eol => try insts.append(allocator, .eol),
+        .concat => |items| for (items) |item| try emit(allocator, insts, nodes, item),
+        .alt => |branches| {
+            const split = insts.items.len;
+            try insts.append(allocator, .{ .split = .{ .a = split + 1, .b = 0 } });
+            try emit(allocator, insts, nodes, branches[0]);
+            const jump = insts.items.len;
+            try insts.append(allocator, .{ .jmp = 0 });
+            insts.items[split].split.b = insts.items.len;
+            try emit(allocator, insts, nodes, branches[1]);
+            insts.items[jump] = .{ .jmp = insts.items.len };
+        },
+        .star => |inner| {
+            const split = insts.items.len;
+            try insts.append(allocator, .{ .split = .{ .a = split + 1, .b = 0 } });
+            try emit(allocator, insts, nodes, inner);
+            try insts.append(allocator, .{ .jmp = split });
+            insts.items[split].split.b = insts.items.len;
+        },
+        .plus => |inner| {
+            const start = insts.items.len;
+            try emit(allocator, insts, nodes, inner);
+            try insts.append(allocator, .{ .split = .{ .a = start, .b = insts.items.len + 1 } });
+        },
+        .quest => |inner| {
+            const split = insts.items.len;
+            try insts.append(allocator, .{ .split = .{ .a = split + 1, .b = 0 } });
+            try emit(allocator, insts, nodes, inner);
+            insts.items[split].split.b = insts.items.len;
+        },
+    }
+}
+
+test "regular expressions" {
+    const allocator = std.testing.allocator;
+    const cases = [_]struct { pattern: []const u8, text: []const u8, matches: bool }{
+        .{ .pattern = "^[a-z-]+(; [a-z-]+)*$", .text = "deep-learning; vision", .matches = true },
+        .{ .pattern = "^[a-z-]+(; [a-z-]+)*$", .text = "Deep Learning", .matches = false },
+        .{ .pattern = "^10\\.\\d+/", .text = "10.1145/3292500", .matches = true },
+        .{ .pattern = "arxiv|doi", .text = "see doi", .matches = true },
+        .{ .pattern = "^https?://", .text = "ftp://x", .matches = false },
+        .{ .pattern = "^a*$", .text = "", .matches = true },
+        .{ .pattern = "[^,]+,", .text = "no commas", .matches = false },
+        .{ .pattern = "\\w+\\s\\w+", .text = "two words", .matches = true },
+    };
+    for (cases) |case| {
+        var re = try Regex.compile(allocator, case.pattern);
+        defer re.deinit();
+        try std.testing.expectEqual(case.matches, try re.isMatch(case.text));
+    }
+
+    try std.testing.expectError(error.InvalidPattern, Regex.compile(allocator, "(unclosed"));
+    try std.testing.expectError(error.InvalidPattern, Regex.compile(allocator, "a**"));
+    try std.testing.expectError(error.InvalidPattern, Regex.compile(allocator, "[z-a]"));
+}
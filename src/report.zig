@@ -9,6 +9,48 @@ const ApiSource = entry_mod.ApiSource;
 const Discrepancy = entry_mod.Discrepancy;
 const Severity = entry_mod.Severity;
 const ValidationResult = entry_mod.ValidationResult;
+const DiscrepancyField = entry_mod.DiscrepancyField;
+const Rejection = @import("matcher.zig").Rejection;
+const Strategy = @import("plan.zig").Strategy;
+const completeness = @import("completeness.zig");
+const Metrics = @import("metrics.zig").Metrics;
+
+/// Discrepancies below this confidence are marked in the text report.
+const LOW_CONFIDENCE = 0.7;
+
+/// Options controlling the text report.
+pub const PrintOptions = struct {
+    /// Color output and make DOIs and URLs clickable.
+    use_color: bool = false,
+    /// Terminal columns long titles and values are wrapped to.
+    width: usize = 80,
+    /// Only show errors and failures.
+    quiet: bool = false,
+    /// Which entries to list in the open-access section.
+    access: AccessFilter = .none,
+    /// Print the matched remote records beneath each entry.
+    show_matched: bool = false,
+    /// Backend latencies listed in the summary.
+    metrics: ?*const Metrics = null,
+};
+
+/// Entries listed in the open-access section of a report.
+pub const AccessFilter = enum {
+    none,
+    all,
+    /// Only closed-access works and works without access data.
+    closed,
+};
+
+/// Open-access status and license of a cited work.
+pub const Access = struct {
+    oa_status: []const u8,
+    license: ?[]const u8,
+
+    pub fn isClosed(self: Access) bool {
+        return std.mem.eql(u8, self.oa_status, "closed");
+    }
+};
 
 /// Status of a validated entry.
 pub const EntryStatus = union(enum) {
@@ -17,6 +59,113 @@ pub const EntryStatus = union(enum) {
     @"error",
     not_found,
     failed: []const u8,
+    /// Skipped by an `[[ignore]]` in the config, with its reason.
+    known_unmatched: []const u8,
+};
+
+/// Order of entries and discrepancies in the output.
+pub const Order = enum {
+    /// Entries in input order, discrepancies by field.
+    file,
+    /// Errors first, then warnings, not found, failed, and ok entries,
+    /// each in input order; discrepancies by severity, then field.
+    severity,
+};
+
+fn discrepancyBefore(order: Order, a: Discrepancy, b: Discrepancy) bool {
+    if (order == .severity and a.severity != b.severity) return a.severity.order() > b.severity.order();
+    if (a.field != b.field) return @intFromEnum(a.field) < @intFromEnum(b.field);
+    if (a.rule != b.rule) return @intFromEnum(a.rule) < @intFromEnum(b.rule);
+    return std.mem.order(u8, a.message, b.message) == .lt;
+}
+
+fn statusRank(status: EntryStatus) u8 {
+    return switch (status) {
+        .@"error" => 0,
+        .warning => 1,
+        .not_found => 2,
+        .failed => 3,
+        .known_unmatched => 4,
+        .ok => 5,
+    };
+}
+
+fn worseStatus(_: void, a: EntryReport, b: EntryReport) bool {
+    return statusRank(a.status) < statusRank(b.status);
+}
+
+/// Status of an entry from its validation results: the worst discrepancy
+/// severity, or ok with the most confident source. An entry no source
+/// matched is not found, unless a finding made without a match (a wrong
+/// DOI) is an error or warning.
+pub fn statusOf(results: []const ValidationResult) EntryStatus {
+    if (results.len == 0) {
+        return .not_found;
+    }
+
+    var matched = false;
+    var has_errors = false;
+    var has_warnings = false;
+    var best_source: ApiSource = .crossref;
+    var best_confidence: f64 = 0;
+
+    for (results) |result| {
+        if (result.matched_entry != null) matched = true;
+        if (result.confidence > best_confidence) {
+            best_confidence = result.confidence;
+            best_source = result.source;
+        }
+
+        for (result.discrepancies) |d| {
+            if (d.severity == .@"error") has_errors = true;
+            if (d.severity == .warning) has_warnings = true;
+        }
+    }
+
+    if (has_errors) return .@"error";
+    if (has_warnings) return .warning;
+    if (!matched) return .not_found;
+    return .{ .ok = best_source };
+}
+
+/// A search result that failed the match filters, kept to explain why an
+/// entry was not found.
+pub const Candidate = struct {
+    source: ApiSource,
+    title: []const u8,
+    year: ?i32 = null,
+    /// Match score ignoring the filters
+    score: f64,
+    reason: Rejection,
+};
+
+/// One lookup strategy tried for an entry.
+pub const Lookup = struct {
+    strategy: Strategy,
+    outcome: Outcome,
+
+    /// Ordered from worst to best.
+    pub const Outcome = enum {
+        failed,
+        not_found,
+        /// The identifier resolved to a record of a different work.
+        mismatch,
+        matched,
+
+        pub fn describe(self: Outcome) []const u8 {
+            return switch (self) {
+                .failed => "failed",
+                .not_found => "not found",
+                .mismatch => "record of a different work",
+                .matched => "matched",
+            };
+        }
+
+        /// The better of two outcomes for the same strategy.
+        pub fn combine(self: Outcome, other: Outcome) Outcome {
+            return if (@intFromEnum(other) > @intFromEnum(self)) other else self;
+        }
+    };
 };
 
 /// Report for a single bibliography entry.
@@ -24,6 +173,14 @@ pub const EntryReport = struct {
     entry: Entry,
     status: EntryStatus,
     validation_results: []ValidationResult,
+    /// Best rejected candidates of a not-found entry; titles are owned.
+    candidates: []const Candidate = &.{},
+    /// Strategies tried, in order; the last one matched unless the entry
+    /// was not found.
+    lookups: []const Lookup = &.{},
+    /// Wall time of validating the entry; null if its report was restored
+    /// or replayed.
+    elapsed_ms: ?u64 = null,
 
     allocator: ?std.mem.Allocator = null,
 
@@ -36,11 +193,39 @@ pub const EntryReport = struct {
                 result.deinit();
             }
             alloc.free(self.validation_results);
-            if (self.status == .failed) {
-                alloc.free(self.status.failed);
+            for (self.candidates) |c| alloc.free(c.title);
+            if (self.candidates.len > 0) alloc.free(self.candidates);
+            if (self.lookups.len > 0) alloc.free(self.lookups);
+            switch (self.status) {
+                .failed => |reason| alloc.free(reason),
+                .known_unmatched => |reason| alloc.free(reason),
+                else => {},
             }
         }
     }
+
+    /// Access data from the first matched record that has any.
+    pub fn access(self: *const EntryReport) ?Access {
+        for (self.validation_results) |result| {
+            const matched = result.matched_entry orelse continue;
+            const status = matched.oa_status orelse continue;
+            return .{ .oa_status = status, .license = matched.license };
+        }
+        return null;
+    }
+};
+
+/// Wall time of the validated entries of a report.
+pub const EntryTiming = struct {
+    count: u64,
+    total_ms: u64,
+    max_ms: u64,
+    /// Key of the entry that took longest.
+    slowest: []const u8,
+
+    pub fn averageMs(self: EntryTiming) u64 {
+        return self.total_ms / self.count;
+    }
 };
 
 /// Complete validation report.
@@ -104,8 +289,31 @@ pub const Report = struct {
         return count;
     }
 
+    pub fn countKnownUnmatched(self: *const Report) usize {
+        var count: usize = 0;
+        for (self.entries.items) |e| {
+            if (e.status == .known_unmatched) count += 1;
+        }
+        return count;
+    }
+
+    /// Put entries and discrepancies in a deterministic `order`, so
+    /// reports of the same input diff cleanly whatever order the
+    /// validators answered in. Entries are added in input order, so
+    /// `.file` only sorts the discrepancies of each result.
+    pub fn sort(self: *Report, order: Order) void {
+        for (self.entries.items) |*entry_report| {
+            for (entry_report.validation_results) |*result| {
+                std.mem.sort(Discrepancy, result.discrepancies, order, discrepancyBefore);
+            }
+        }
+        if (order == .severity) std.mem.sort(EntryReport, self.entries.items, {}, worseStatus);
+    }
+
     /// Print the report to stdout.
-    pub fn print(self: *const Report, writer: anytype, use_color: bool) !void {
+    pub fn print(self: *const Report, writer: anytype, options: PrintOptions) !void {
+        const use_color = options.use_color;
+
         try writer.writeAll("\n");
         try printStyled(writer, "bibval Report", use_color, .bold);
         try writer.writeAll("\n");
@@ -129,19 +337,101 @@ pub const Report = struct {
         try printColored(writer, failed, use_color, .red);
         try writer.writeAll(" failed, ");
         try printColored(writer, not_found, use_color, .dim);
-        try writer.writeAll(" not found\n\n");
+        try writer.writeAll(" not found");
+        const known_unmatched = self.countKnownUnmatched();
+        if (known_unmatched > 0) {
+            try writer.writeAll(", ");
+            try printColored(writer, known_unmatched, use_color, .dim);
+            try writer.writeAll(" known unmatched");
+        }
+        try writer.writeAll("\n\n");
 
         // Print errors first
-        try self.printSection(writer, .@"error", "ERRORS", use_color, .red);
-        try self.printSection(writer, .failed, "FAILED", use_color, .red);
-        try self.printSection(writer, .warning, "WARNINGS", use_color, .yellow);
-        try self.printNotFoundSection(writer, use_color);
-        try self.printOkSection(writer, use_color);
+        try self.printSection(writer, .@"error", "ERRORS", options, .red);
+        try self.printSection(writer, .failed, "FAILED", options, .red);
+        if (!options.quiet) {
+            try self.printSection(writer, .warning, "WARNINGS", options, .yellow);
+            try self.printNotFoundSection(writer, use_color, options.width);
+            try self.printKnownUnmatchedSection(writer, use_color);
+            try self.printOkSection(writer, options);
+            if (options.access != .none) try self.printAccessSection(writer, options.access, use_color);
+            try self.printSummary(writer, use_color, options.metrics);
+        }
 
         try writer.writeAll("\n");
     }
 
-    fn printSection(self: *const Report, writer: anytype, status_type: std.meta.Tag(EntryStatus), title: []const u8, use_color: bool, color: Color) !void {
+    /// Count distinct discrepancies per field, indexed by severity order.
+    pub fn fieldCounts(self: *const Report) std.EnumArray(DiscrepancyField, [3]usize) {
+        var counts = std.EnumArray(DiscrepancyField, [3]usize).initFill(.{ 0, 0, 0 });
+        for (self.entries.items) |*entry_report| {
+            for (entry_report.validation_results, 0..) |result, i| {
+                for (result.discrepancies, 0..) |d, j| {
+                    if (isDuplicate(entry_report, i, j)) continue;
+                    counts.getPtr(d.field)[d.severity.order()] += 1;
+                }
+            }
+        }
+        return counts;
+    }
+
+    /// Mean and longest wall time of the validated entries, with the
+    /// entry that took longest; null if no entry was timed.
+    pub fn entryTiming(self: *const Report) ?EntryTiming {
+        var timing: EntryTiming = .{ .count = 0, .total_ms = 0, .max_ms = 0, .slowest = "" };
+        for (self.entries.items) |e| {
+            const ms = e.elapsed_ms orelse continue;
+            timing.count += 1;
+            timing.total_ms += ms;
+            if (timing.count == 1 or ms > timing.max_ms) {
+                timing.max_ms = ms;
+                timing.slowest = e.entry.key;
+            }
+        }
+        return if (timing.count == 0) null else timing;
+    }
+
+    fn printSummary(self: *const Report, writer: anytype, use_color: bool, metrics: ?*const Metrics) !void {
+        const matched = self.countOk() + self.countWarnings() + self.countErrors();
+
+        try writer.writeAll("\n");
+        try printStyled(writer, "SUMMARY", use_color, .bold);
+        try writer.writeAll("\n");
+        try writer.print("  {s:<18}{d:>6}\n", .{ "Entries checked", self.entries.items.len });
+        try writer.print("  {s:<18}{d:>6}\n", .{ "Matched", matched });
+        try writer.print("  {s:<18}{d:>6}\n", .{ "Unmatched", self.countNotFound() });
+        try writer.print("  {s:<18}{d:>6}\n", .{ "Failed", self.countFailed() });
+        if (completeness.grade(self)) |g| {
+            try writer.print("  {s:<18}{d:>5}%  ({c})\n", .{ "Completeness", g.percent, g.letter() });
+        }
+        if (self.entryTiming()) |t| {
+            try writer.print("  {s:<18}{d:>4}ms  (longest {d}ms, {s})\n", .{ "Time per entry", t.averageMs(), t.max_ms, shortId(t.slowest) });
+        }
+        try writer.writeAll("\n");
+
+        try writer.print("  {s:<10}{s:>8}{s:>10}{s:>6}\n", .{ "Field", "Errors", "Warnings", "Info" });
+        const counts = self.fieldCounts();
+        for (std.enums.values(DiscrepancyField)) |field| {
+            const c = counts.get(field);
+            // Only custom comparators report other fields
+            if (field == .other and c[0] + c[1] + c[2] == 0) continue;
+            try writer.print("  {s:<10}{d:>8}{d:>10}{d:>6}\n", .{
+                field.name(),
+                c[Severity.@"error".order()],
+                c[Severity.warning.order()],
+                c[Severity.info.order()],
+            });
+        }
+
+        if (metrics) |m| {
+            if (m.totalRequests() == 0) return;
+            try writer.writeAll("\n");
+            try m.print(writer);
+        }
+    }
+
+    fn printSection(self: *const Report, writer: anytype, status_type: std.meta.Tag(EntryStatus), title: []const u8, options: PrintOptions, color: Color) !void {
+        const use_color = options.use_color;
         var matching: std.ArrayList(*const EntryReport) = .empty;
         defer matching.deinit(self.allocator);
 
@@ -153,21 +443,21 @@ pub const Report = struct {
 
         if (matching.items.len == 0) return;
 
-        try printStyled(writer, title, use_color, .bold);
+        try printColored(writer, title, use_color, color);
         try writer.print(" ({d})\n", .{matching.items.len});
 
         for (matching.items) |entry_report| {
             if (status_type == .failed) {
                 try writer.print("  [{s}] {s}\n", .{ shortId(entry_report.entry.key), entry_report.status.failed });
             } else {
-                try printEntryReport(writer, entry_report, use_color);
+                try printEntryReport(writer, entry_report, use_color, options.width);
+                if (options.show_matched) try printMatched(writer, entry_report, use_color, options.width);
             }
         }
         try writer.writeAll("\n");
-        _ = color;
     }
 
-    fn printNotFoundSection(self: *const Report, writer: anytype, use_color: bool) !void {
+    fn printNotFoundSection(self: *const Report, writer: anytype, use_color: bool, width: usize) !void {
         var matching: std.ArrayList(*const EntryReport) = .empty;
         defer matching.deinit(self.allocator);
 
@@ -184,12 +474,44 @@ pub const Report = struct {
 
         for (matching.items) |entry_report| {
             const title = entry_report.entry.title orelse "(no title)";
-            try writer.print("  [{s}] {s}\n", .{ shortId(entry_report.entry.key), truncate(title, 60) });
+            const key = shortId(entry_report.entry.key);
+            try writer.print("  [{s}] ", .{key});
+            try printWrapped(writer, title, key.len + 5, width, 3, use_color, null);
+            try writer.writeAll("\n");
+            try printLookups(writer, entry_report, use_color);
+            // Findings that need no match, such as a suggested archive URL
+            for (entry_report.validation_results, 0..) |result, i| {
+                for (result.discrepancies) |*discrepancy| {
+                    try printDiscrepancy(writer, entry_report, discrepancy, i, use_color, width);
+                }
+            }
+            for (entry_report.candidates) |candidate| {
+                try writer.print("    {d:.2} {s}", .{ candidate.score, truncate(candidate.title, 50) });
+                if (candidate.year) |y| try writer.print(" ({d})", .{y});
+                try writer.print(" via {s}: ", .{candidate.source.name()});
+                try printColored(writer, candidate.reason.describe(), use_color, .dim);
+                try writer.writeAll("\n");
+            }
         }
         try writer.writeAll("\n");
     }
 
-    fn printOkSection(self: *const Report, writer: anytype, use_color: bool) !void {
+    fn printKnownUnmatchedSection(self: *const Report, writer: anytype, use_color: bool) !void {
+        const count = self.countKnownUnmatched();
+        if (count == 0) return;
+
+        try printStyled(writer, "KNOWN UNMATCHED", use_color, .dim);
+        try writer.print(" ({d})\n", .{count});
+        for (self.entries.items) |e| {
+            if (e.status != .known_unmatched) continue;
+            try writer.print("  [{s}] {s}\n", .{ shortId(e.entry.key), e.status.known_unmatched });
+        }
+        try writer.writeAll("\n");
+    }
+
+    fn printOkSection(self: *const Report, writer: anytype, options: PrintOptions) !void {
+        const use_color = options.use_color;
+        const show_matched = options.show_matched;
         var matching: std.ArrayList(*const EntryReport) = .empty;
         defer matching.deinit(self.allocator);
 
@@ -204,12 +526,14 @@ pub const Report = struct {
         try printColored(writer, "OK", use_color, .green);
         try writer.print(" ({d})\n", .{matching.items.len});
 
-        const max_display: usize = 5;
+        // All of them when their records are shown
+        const max_display: usize = if (show_matched) matching.items.len else 5;
         for (matching.items[0..@min(max_display, matching.items.len)]) |entry_report| {
             const source = entry_report.status.ok;
             try writer.print("  [{s}] Validated against ", .{shortId(entry_report.entry.key)});
             try printColored(writer, source.name(), use_color, .green);
             try writer.writeAll("\n");
+            if (show_matched) try printMatched(writer, entry_report, use_color, options.width);
         }
 
         if (matching.items.len > max_display) {
@@ -217,18 +541,135 @@ pub const Report = struct {
         }
     }
 
-    fn printEntryReport(writer: anytype, entry_report: *const EntryReport, use_color: bool) !void {
+    /// List the access status and license of each matched entry. With
+    /// `.closed`, only entries that are not known to be open are listed.
+    fn printAccessSection(self: *const Report, writer: anytype, filter: AccessFilter, use_color: bool) !void {
+        var matching: std.ArrayList(*const EntryReport) = .empty;
+        defer matching.deinit(self.allocator);
+
+        var open: usize = 0;
+        var unknown: usize = 0;
+        for (self.entries.items) |*e| {
+            if (e.status == .not_found or e.status == .failed or e.status == .known_unmatched) continue;
+            if (e.access()) |a| {
+                if (!a.isClosed()) {
+                    open += 1;
+                    if (filter == .closed) continue;
+                }
+            } else {
+                unknown += 1;
+            }
+            try matching.append(self.allocator, e);
+        }
+
+        try writer.writeAll("\n");
+        try printStyled(writer, if (filter == .closed) "CLOSED ACCESS" else "OPEN ACCESS", use_color, .bold);
+        try writer.print(" ({d})\n", .{matching.items.len});
+
+        for (matching.items) |entry_report| {
+            try writer.print("  [{s}] ", .{shortId(entry_report.entry.key)});
+            if (entry_report.access()) |a| {
+                try printColored(writer, a.oa_status, use_color, if (a.isClosed()) .red else .green);
+                try writer.print("  {s}\n", .{a.license orelse "no license"});
+            } else {
+                try printColored(writer, "unknown", use_color, .dim);
+                try writer.writeAll("\n");
+            }
+        }
+
+        const matched = self.countOk() + self.countWarnings() + self.countErrors();
+        try writer.print("  {d} open, {d} closed, {d} unknown\n", .{ open, matched - open - unknown, unknown });
+    }
+
+    /// Print an entry header followed by its distinct discrepancies.
+    fn printEntryReport(writer: anytype, entry_report: *const EntryReport, use_color: bool, width: usize) !void {
         const key = shortId(entry_report.entry.key);
+        try writer.writeAll("  ");
+        try printStyled(writer, key, use_color, .bold);
+        if (entry_report.entry.title) |title| {
+            try writer.writeAll("  ");
+            try printWrapped(writer, title, key.len + 4, width, 3, use_color, .dim);
+        }
+        try writer.writeAll("\n");
+        // Only worth showing when the first strategy did not find it
+        if (entry_report.lookups.len > 1) try printLookups(writer, entry_report, use_color);
+
+        for (entry_report.validation_results, 0..) |result, i| {
+            for (result.discrepancies, 0..) |*discrepancy, j| {
+                if (isDuplicate(entry_report, i, j)) continue;
+                try printDiscrepancy(writer, entry_report, discrepancy, i, use_color, width);
+            }
+        }
+    }
 
+    /// Print every field of the remote records an entry was matched to,
+    /// with the record's ID at its source.
+    fn printMatched(writer: anytype, entry_report: *const EntryReport, use_color: bool, width: usize) !void {
         for (entry_report.validation_results) |result| {
-            for (result.discrepancies) |discrepancy| {
-                try printDiscrepancy(writer, key, &discrepancy, result.source, use_color);
+            const record = result.matched_entry orelse continue;
+            var buf: [64]u8 = undefined;
+            const heading = std.fmt.bufPrint(&buf, "{s} record, score {d:.2}", .{ result.source.name(), result.confidence }) catch "";
+            try writer.writeAll("    ");
+            try printColored(writer, heading, use_color, .blue);
+            try writer.writeAll("\n");
+
+            if (record.key.len > 0) try printRecordField(writer, "id", record.key, use_color, width);
+            try printRecordField(writer, "type", record.entry_type, use_color, width);
+            if (record.title) |v| try printRecordField(writer, "title", v, use_color, width);
+            for (record.authors, 0..) |author, i| {
+                try printRecordField(writer, if (i == 0) "author" else "", author, use_color, width);
+            }
+            if (record.year) |y| {
+                var year_buf: [16]u8 = undefined;
+                try printRecordField(writer, "year", std.fmt.bufPrint(&year_buf, "{d}", .{y}) catch "", use_color, width);
+            }
+            const fields = [_]struct { name: []const u8, value: ?[]const u8 }{
+                .{ .name = "venue", .value = record.venue },
+                .{ .name = "venue id", .value = record.venue_id },
+                .{ .name = "publisher", .value = record.publisher },
+                .{ .name = "doi", .value = record.doi },
+                .{ .name = "arxiv", .value = record.arxiv_id },
+                .{ .name = "isbn", .value = record.isbn },
+                .{ .name = "scopus", .value = record.scopus_eid },
+                .{ .name = "url", .value = record.url },
+            };
+            for (fields) |f| {
+                if (f.value) |v| try printRecordField(writer, f.name, v, use_color, width);
             }
+            for (record.extra) |f| try printRecordField(writer, f.name, f.value, use_color, width);
         }
     }
 
-    fn printDiscrepancy(writer: anytype, key: []const u8, discrepancy: *const Discrepancy, source: ApiSource, use_color: bool) !void {
-        try writer.print("  [{s}] ", .{key});
+    fn printRecordField(writer: anytype, name: []const u8, value: []const u8, use_color: bool, width: usize) !void {
+        var buf: [24]u8 = undefined;
+        const label = std.fmt.bufPrint(&buf, "{s:<11}", .{name}) catch name;
+        try writer.writeAll("      ");
+        try printColored(writer, label, use_color, .dim);
+        if (std.mem.eql(u8, name, "arxiv")) {
+            var url_buf: [128]u8 = undefined;
+            try printLink(writer, value, std.fmt.bufPrint(&url_buf, "https://arxiv.org/abs/{s}", .{value}) catch value, use_color);
+        } else {
+            try printValue(writer, value, 6 + label.len, width, std.math.maxInt(usize), use_color);
+        }
+        try writer.writeAll("\n");
+    }
+
+    /// Print the strategies tried for an entry, e.g. "DOI lookup: not
+    /// found, Title search: matched".
+    fn printLookups(writer: anytype, entry_report: *const EntryReport, use_color: bool) !void {
+        if (entry_report.lookups.len == 0) return;
+        var buf: [256]u8 = undefined;
+        var w = std.Io.Writer.fixed(&buf);
+        for (entry_report.lookups, 0..) |lookup, i| {
+            w.print("{s}{s}: {s}", .{ if (i > 0) ", " else "", lookup.strategy.name(), lookup.outcome.describe() }) catch break;
+        }
+        try writer.writeAll("    ");
+        try printStyled(writer, w.buffered(), use_color, .dim);
+        try writer.writeAll("\n");
+    }
+
+    fn printDiscrepancy(writer: anytype, entry_report: *const EntryReport, discrepancy: *const Discrepancy, first_result: usize, use_color: bool, width: usize) !void {
+        try writer.writeAll("    ");
 
         switch (discrepancy.severity) {
             .@"error" => try printColored(writer, "ERROR", use_color, .red),
@@ -236,15 +677,57 @@ pub const Report = struct {
             .info => try printColored(writer, "INFO", use_color, .blue),
         }
 
-        try writer.print(" {s} (via {s})\n", .{ discrepancy.message, source.name() });
+        try writer.print(" {s} {s} (via ", .{ discrepancy.rule.id(), discrepancy.message });
+
+        // Name every source that reported the same discrepancy
+        var first = true;
+        for (entry_report.validation_results[first_result..]) |result| {
+            if (!containsEquivalent(result.discrepancies, discrepancy)) continue;
+            if (!first) try writer.writeAll(", ");
+            first = false;
+            try writer.writeAll(result.source.name());
+        }
+        try writer.writeAll(")");
+        if (discrepancy.confidence < LOW_CONFIDENCE) {
+            var buf: [32]u8 = undefined;
+            const note = std.fmt.bufPrint(&buf, " low confidence {d:.2}", .{discrepancy.confidence}) catch "";
+            try printColored(writer, note, use_color, .dim);
+        }
+        try writer.writeAll("\n");
 
         if (discrepancy.severity.order() >= Severity.warning.order()) {
-            try writer.print("       Local:  {s}\n", .{truncate(discrepancy.local_value, 60)});
-            try writer.print("       Remote: {s}\n", .{truncate(discrepancy.remote_value, 60)});
+            try writer.writeAll("          Local:  ");
+            try printValue(writer, discrepancy.local_value, 18, width, 3, use_color);
+            try writer.writeAll("\n          Remote: ");
+            try printValue(writer, discrepancy.remote_value, 18, width, 3, use_color);
+            try writer.writeAll("\n");
         }
     }
 };
 
+/// Whether two discrepancies report the same finding.
+fn equivalent(a: *const Discrepancy, b: *const Discrepancy) bool {
+    return a.rule == b.rule and
+        std.mem.eql(u8, a.message, b.message) and
+        std.mem.eql(u8, a.remote_value, b.remote_value);
+}
+
+fn containsEquivalent(discrepancies: []const Discrepancy, target: *const Discrepancy) bool {
+    for (discrepancies) |*d| {
+        if (equivalent(d, target)) return true;
+    }
+    return false;
+}
+
+/// Whether discrepancy `j` of result `i` repeats one reported earlier for the same entry.
+fn isDuplicate(entry_report: *const EntryReport, i: usize, j: usize) bool {
+    const target = &entry_report.validation_results[i].discrepancies[j];
+    for (entry_report.validation_results[0..i]) |result| {
+        if (containsEquivalent(result.discrepancies, target)) return true;
+    }
+    return containsEquivalent(entry_report.validation_results[i].discrepancies[0..j], target);
+}
+
 const Color = enum { red, green, yellow, blue, dim, bold };
 
 fn printColored(writer: anytype, text: anytype, use_color: bool, color: Color) !void {
@@ -280,5 +763,281 @@ fn shortId(id: []const u8) []const u8 {
 }
 
 fn truncate(s: []const u8, max_len: usize) []const u8 {
-    return if (s.len <= max_len) s else s[0..max_len];
+    if (s.len <= max_len) return s;
+    // Never cut a UTF-8 sequence in half
+    var end = max_len;
+    while (end > 0 and s[end] & 0xC0 == 0x80) end -= 1;
+    return s[0..end];
+}
+
+/// Print a value as a link if it is a DOI, URL, or OpenAlex or DBLP ID,
+/// otherwise word-wrapped.
+fn printValue(writer: anytype, value: []const u8, column: usize, width: usize, max_lines: usize, use_color: bool) !void {
+    var buf: [512]u8 = undefined;
+    if (linkTarget(&buf, value)) |url| return printLink(writer, value, url, use_color);
+    try printWrapped(writer, value, column, width, max_lines, use_color, null);
+}
+
+/// Where a value links to: URLs themselves, DOIs at doi.org, and OpenAlex
+/// and DBLP IDs on their sites.
+fn linkTarget(buf: []u8, value: []const u8) ?[]const u8 {
+    if (value.len == 0 or std.mem.indexOfAny(u8, value, " \t\n") != null) return null;
+    if (std.mem.startsWith(u8, value, "https://") or std.mem.startsWith(u8, value, "http://")) return value;
+    if (std.mem.startsWith(u8, value, "10.") and std.mem.indexOfScalar(u8, value, '/') != null) {
+        return std.fmt.bufPrint(buf, "https://doi.org/{s}", .{value}) catch null;
+    }
+    if (std.mem.startsWith(u8, value, "openalex:")) {
+        return std.fmt.bufPrint(buf, "https://openalex.org/{s}", .{value["openalex:".len..]}) catch null;
+    }
+    if (std.mem.startsWith(u8, value, "dblp:")) {
+        return std.fmt.bufPrint(buf, "https://dblp.org/db/{s}", .{value["dblp:".len..]}) catch null;
+    }
+    // Bare OpenAlex IDs: a type letter and a number, e.g. "W2194775991"
+    if (value.len > 5 and std.mem.indexOfScalar(u8, "WSAIPFC", value[0]) != null) {
+        for (value[1..]) |c| {
+            if (!std.ascii.isDigit(c)) return null;
+        }
+        return std.fmt.bufPrint(buf, "https://openalex.org/{s}", .{value}) catch null;
+    }
+    return null;
+}
+
+/// Write `text`, as a terminal hyperlink to `url` when escapes are on.
+fn printLink(writer: anytype, text: []const u8, url: []const u8, use_color: bool) !void {
+    if (!use_color) return writer.writeAll(text);
+    try writer.print("\x1b]8;;{s}\x1b\\{s}\x1b]8;;\x1b\\", .{ url, text });
+}
+
+/// Write `text` wrapped at word boundaries to `width` columns. The text
+/// starts at `column`, and continuation lines are indented to it. Text
+/// beyond `max_lines` lines is cut and marked with "...".
+fn printWrapped(writer: anytype, text: []const u8, column: usize, width: usize, max_lines: usize, use_color: bool, color: ?Color) !void {
+    // Narrow terminals still get a usable line
+    const avail = if (width > column + 20) width - column else 20;
+    var rest = std.mem.trim(u8, text, " ");
+    var lines: usize = 0;
+    while (rest.len > 0) : (lines += 1) {
+        if (lines > 0) {
+            try writer.writeAll("\n");
+            for (0..column) |_| try writer.writeByte(' ');
+        }
+        var end = lineEnd(rest, avail);
+        const cut = lines + 1 == max_lines and end < rest.len;
+        if (cut) end = lineEnd(rest, avail - 3);
+        const line = std.mem.trimRight(u8, rest[0..end], " ");
+        if (color) |c| try printColored(writer, line, use_color, c) else try writer.writeAll(line);
+        if (cut) {
+            try writer.writeAll("...");
+            return;
+        }
+        rest = std.mem.trimLeft(u8, rest[end..], " ");
+    }
+}
+
+/// End of the first line of `text` when lines hold `avail` columns: the
+/// last space that fits, or the end of an overlong first word.
+fn lineEnd(text: []const u8, avail: usize) usize {
+    var columns: usize = 0;
+    var last_space: ?usize = null;
+    for (text, 0..) |c, i| {
+        // Count code points, not bytes
+        if (c & 0xC0 == 0x80) continue;
+        if (columns == avail) {
+            if (c == ' ') return i;
+            return last_space orelse (std.mem.indexOfScalarPos(u8, text, i, ' ') orelse text.len);
+        }
+        if (c == ' ') last_space = i;
+        columns += 1;
+    }
+    return text.len;
+}
+
+/// Write `s` as a JSON string literal, escaping control characters.
+pub fn writeJsonString(writer: anytype, s: []const u8) !void {
+    try writer.writeByte('"');
+    for (s) |c| {
+        switch (c) {
+            '"' => try writer.writeAll("\\\""),
+            '\\' => try writer.writeAll("\\\\"),
+            '\n' => try writer.writeAll("\\n"),
+            '\r' => try writer.writeAll("\\r"),
+            '\t' => try writer.writeAll("\\t"),
+            0x08 => try writer.writeAll("\\b"), // backspace
+            0x0C => try writer.writeAll("\\f"), // form feed
+            else => {
+                if (c < 0x20) {
+                    try writer.print("\\u{x:0>4}", .{c});
+                } else {
+                    try writer.writeByte(c);
+                }
+            },
+        }
+    }
+    try writer.writeByte('"');
+}
+
+test "duplicate discrepancies are counted once" {
+    const year = Discrepancy{
+        .rule = .year_mismatch,
+        .field = .year,
+        .severity = .@"error",
+        .local_value = "2019",
+        .remote_value = "2018",
+        .message = "Year mismatch: 2019 vs 2018",
+    };
+    var dblp = [_]Discrepancy{year};
+    var openalex = [_]Discrepancy{ year, .{
+        .rule = .missing_doi,
+        .field = .doi,
+        .severity = .warning,
+        .local_value = "",
+        .remote_value = "10.1234/pyro",
+        .message = "Missing DOI",
+    } };
+    var results = [_]ValidationResult{
+        .{ .source = .dblp, .confidence = 0.9, .discrepancies = &dblp },
+        .{ .source = .openalex, .confidence = 0.9, .discrepancies = &openalex },
+    };
+
+    var report = Report.init(std.testing.allocator);
+    defer report.entries.deinit(std.testing.allocator);
+    try report.add(.{
+        .entry = .{ .key = "bingham2019", .entry_type = "article" },
+        .status = .@"error",
+        .validation_results = &results,
+    });
+
+    try std.testing.expect(isDuplicate(&report.entries.items[0], 1, 0));
+    try std.testing.expect(!isDuplicate(&report.entries.items[0], 1, 1));
+
+    const counts = report.fieldCounts();
+    try std.testing.expectEqual(@as(usize, 1), counts.get(.year)[Severity.@"error".order()]);
+    try std.testing.expectEqual(@as(usize, 1), counts.get(.doi)[Severity.warning.order()]);
+}
+
+test "matched records are shown" {
+    var results = [_]ValidationResult{.{
+        .source = .dblp,
+        .confidence = 0.97,
+        .matched_entry = .{
+            .key = "https://dblp.org/rec/conf/cvpr/HeZRS16",
+            .entry_type = "inproceedings",
+            .title = "Deep Residual Learning for Image Recognition",
+            .authors = &.{ "Kaiming He", "Xiangyu Zhang" },
+            .year = 2016,
+            .venue = "CVPR",
+        },
+    }};
+
+    var report = Report.init(std.testing.allocator);
+    defer report.entries.deinit(std.testing.allocator);
+    try report.add(.{
+        .entry = .{ .key = "he2016", .entry_type = "inproceedings" },
+        .status = .{ .ok = .dblp },
+        .validation_results = &results,
+    });
+
+    var out: std.Io.Writer.Allocating = .init(std.testing.allocator);
+    defer out.deinit();
+    try report.print(&out.writer, .{ .show_matched = true });
+    const text = out.written();
+    try std.testing.expect(std.mem.indexOf(u8, text, "    DBLP record, score 0.97\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "      id         https://dblp.org/rec/conf/cvpr/HeZRS16\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "      author     Kaiming He\n                 Xiangyu Zhang\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "      venue      CVPR\n") != null);
+}
+
+test "long values wrap and identifiers link" {
+    var out: std.Io.Writer.Allocating = .init(std.testing.allocator);
+    defer out.deinit();
+    try printWrapped(&out.writer, "Deep residual learning for image recognition with very deep networks", 18, 50, 3, false, null);
+    try std.testing.expectEqualStrings(
+        \\Deep residual learning for image
+        \\                  recognition with very deep
+        \\                  networks
+    , out.written());
+
+    out.clearRetainingCapacity();
+    try printWrapped(&out.writer, "one two three four five six seven eight nine ten eleven", 0, 10, 2, false, null);
+    // Lines keep 20 columns however narrow the terminal
+    try std.testing.expectEqualStrings("one two three four\nfive six seven...", out.written());
+
+    var buf: [512]u8 = undefined;
+    try std.testing.expectEqualStrings("https://doi.org/10.1109/CVPR.2016.90", linkTarget(&buf, "10.1109/CVPR.2016.90").?);
+    try std.testing.expectEqualStrings("https://openalex.org/S4306420609", linkTarget(&buf, "openalex:S4306420609").?);
+    try std.testing.expectEqualStrings("https://openreview.net/forum?id=abc", linkTarget(&buf, "https://openreview.net/forum?id=abc").?);
+    try std.testing.expect(linkTarget(&buf, "Deep Residual Learning") == null);
+
+    out.clearRetainingCapacity();
+    try printValue(&out.writer, "10.1109/CVPR.2016.90", 0, 80, 1, true);
+    try std.testing.expectEqualStrings("\x1b]8;;https://doi.org/10.1109/CVPR.2016.90\x1b\\10.1109/CVPR.2016.90\x1b]8;;\x1b\\", out.written());
+}
+
+test "reports sort deterministically" {
+    const allocator = std.testing.allocator;
+    var discrepancies = [_]Discrepancy{
+        .{ .rule = .missing_doi, .field = .doi, .severity = .warning, .local_value = "", .remote_value = "10.1/x", .message = "Missing DOI" },
+        .{ .rule = .title_mismatch, .field = .title, .severity = .info, .local_value = "a", .remote_value = "b", .message = "Title differs" },
+        .{ .rule = .year_mismatch, .field = .year, .severity = .@"error", .local_value = "2019", .remote_value = "2018", .message = "Year mismatch" },
+    };
+    var results = [_]ValidationResult{.{ .source = .crossref, .confidence = 0.9, .discrepancies = &discrepancies }};
+
+    var report = Report.init(allocator);
+    defer report.entries.deinit(allocator);
+    try report.add(.{ .entry = .{ .key = "a", .entry_type = "article" }, .status = .not_found, .validation_results = &.{} });
+    try report.add(.{ .entry = .{ .key = "b", .entry_type = "article" }, .status = .{ .ok = .crossref }, .validation_results = &.{} });
+    try report.add(.{ .entry = .{ .key = "c", .entry_type = "article" }, .status = .@"error", .validation_results = &results });
+
+    report.sort(.file);
+    try std.testing.expectEqualStrings("a", report.entries.items[0].entry.key);
+    try std.testing.expectEqual(DiscrepancyField.title, discrepancies[0].field);
+    try std.testing.expectEqual(DiscrepancyField.year, discrepancies[1].field);
+
+    report.sort(.severity);
+    try std.testing.expectEqualStrings("c", report.entries.items[0].entry.key);
+    try std.testing.expectEqualStrings("a", report.entries.items[1].entry.key);
+    try std.testing.expectEqual(Severity.@"error", discrepancies[0].severity);
+    try std.testing.expectEqual(Severity.info, discrepancies[2].severity);
+}
+
+test "findings without a match leave an entry not found" {
+    var archive = [_]Discrepancy{.{ .rule = .archive_url, .field = .url, .severity = .info, .local_value = "https://example.org", .remote_value = "", .message = "No archived copy is cited" }};
+    var wrong_doi = [_]Discrepancy{.{ .rule = .wrong_doi, .field = .doi, .severity = .@"error", .local_value = "10.1/x", .remote_value = "", .message = "DOI points to a different work" }};
+    const matched = Entry{ .key = "r", .entry_type = "misc" };
+
+    var suggested = [_]ValidationResult{.{ .source = .web, .confidence = 1.0, .discrepancies = &archive }};
+    try std.testing.expect(statusOf(&suggested) == .not_found);
+    var wrong = [_]ValidationResult{.{ .source = .crossref, .confidence = 1.0, .discrepancies = &wrong_doi }};
+    try std.testing.expect(statusOf(&wrong) == .@"error");
+    var found = [_]ValidationResult{.{ .source = .web, .matched_entry = matched, .confidence = 0.9, .discrepancies = &archive }};
+    try std.testing.expectEqual(ApiSource.web, statusOf(&found).ok);
+}
+
+test "closed access lists closed works and works without access data" {
+    const allocator = std.testing.allocator;
+    var open = [_]ValidationResult{.{ .source = .openalex, .confidence = 1.0, .matched_entry = .{ .key = "W1", .entry_type = "article", .oa_status = "gold", .license = "cc-by" } }};
+    var closed = [_]ValidationResult{.{ .source = .openalex, .confidence = 1.0, .matched_entry = .{ .key = "W2", .entry_type = "article", .oa_status = "closed" } }};
+    var unknown = [_]ValidationResult{.{ .source = .dblp, .confidence = 1.0, .matched_entry = .{ .key = "W3", .entry_type = "article" } }};
+
+    var report = Report.init(allocator);
+    defer report.entries.deinit(allocator);
+    try report.add(.{ .entry = .{ .key = "open2020", .entry_type = "article" }, .status = .{ .ok = .openalex }, .validation_results = &open });
+    try report.add(.{ .entry = .{ .key = "closed2015", .entry_type = "article" }, .status = .{ .ok = .openalex }, .validation_results = &closed });
+    try report.add(.{ .entry = .{ .key = "unknown2016", .entry_type = "article" }, .status = .{ .ok = .dblp }, .validation_results = &unknown });
+    try report.add(.{ .entry = .{ .key = "missing", .entry_type = "article" }, .status = .not_found, .validation_results = &.{} });
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try report.print(&out.writer, .{ .access = .closed });
+    try std.testing.expect(std.mem.indexOf(u8, out.written(),
+        \CLOSED ACCESS (2)
+        \  [closed2015] closed  no license
+        \  [unknown2016] unknown
+        \  1 open, 1 closed, 1 unknown
+        \
+    ) != null);
+
+    out.clearRetainingCapacity();
+    try report.print(&out.writer, .{ .access = .all });
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "OPEN ACCESS (3)\n  [open2020] gold  cc-by\n") != null);
 }
@@ -0,0 +1,182 @@
+//! RIS parser.
+//!
+//! Parses RIS records (as exported by publishers and reference managers)
+//! into entries. Each record starts with `TY  - ` and ends with `ER  - `.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Entry = entry_mod.Entry;
+
+/// Whether `content` looks like RIS.
+pub fn looksLikeRis(content: []const u8) bool {
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        const line = std.mem.trim(u8, raw, " \t\r\u{FEFF}");
+        if (line.len == 0) continue;
+        return std.mem.startsWith(u8, line, "TY  -");
+    }
+    return false;
+}
+
+/// Parse a RIS file.
+pub fn parseFile(allocator: std.mem.Allocator, path: []const u8) ![]Entry {
+    const file = try std.fs.cwd().openFile(path, .{});
+    defer file.close();
+
+    const content = try file.readToEndAlloc(allocator, 10 * 1024 * 1024);
+    defer allocator.free(content);
+
+    return try parseString(allocator, content);
+}
+
+/// Parse RIS records.
+pub fn parseString(allocator: std.mem.Allocator, content: []const u8) ![]Entry {
+    var entries: std.ArrayList(Entry) = .empty;
+    errdefer {
+        for (entries.items) |*e| e.deinit();
+        entries.deinit(allocator);
+    }
+
+    var current: ?Entry = null;
+    errdefer if (current) |*c| c.deinit();
+    var authors: std.ArrayList([]const u8) = .empty;
+    defer {
+        for (authors.items) |a| allocator.free(a);
+        authors.deinit(allocator);
+    }
+    var id: ?[]const u8 = null;
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        const line = std.mem.trimRight(u8, raw, " \t\r");
+        if (line.len < 5 or !std.mem.eql(u8, line[2..5], "  -")) continue;
+        const tag = line[0..2];
+        const value = std.mem.trim(u8, line[5..], " \t");
+
+        if (std.mem.eql(u8, tag, "TY")) {
+            // A record missing its ER line ends at the next TY
+            if (current) |*c| {
+                try finish(allocator, c, &authors, id, &entries);
+                current = null;
+            }
+            current = Entry{ .key = "", .entry_type = "", .allocator = allocator };
+            current.?.entry_type = try allocator.dupe(u8, entryType(value));
+            id = null;
+            continue;
+        }
+
+        const e = if (current) |*c| c else continue;
+        if (std.mem.eql(u8, tag, "ER")) {
+            try finish(allocator, e, &authors, id, &entries);
+            current = null;
+        } else if (value.len == 0) {
+            continue;
+        } else if (std.mem.eql(u8, tag, "TI") or std.mem.eql(u8, tag, "T1")) {
+            try replace(allocator, &e.title, value);
+        } else if (std.mem.eql(u8, tag, "AU") or std.mem.eql(u8, tag, "A1")) {
+            try authors.append(allocator, try allocator.dupe(u8, value));
+        } else if (std.mem.eql(u8, tag, "PY") or std.mem.eql(u8, tag, "Y1") or std.mem.eql(u8, tag, "DA")) {
+            // "2020", "2020/05/01/" or "2020///"
+            if (e.year == null and value.len >= 4) e.year = std.fmt.parseInt(i32, value[0..4], 10) catch null;
+        } else if (std.mem.eql(u8, tag, "JO") or std.mem.eql(u8, tag, "JF") or std.mem.eql(u8, tag, "T2") or std.mem.eql(u8, tag, "BT")) {
+            // Prefer the full journal name (JF/T2) over abbreviations
+            if (e.venue == null or std.mem.eql(u8, tag, "JF") or std.mem.eql(u8, tag, "T2")) try replace(allocator, &e.venue, value);
+        } else if (std.mem.eql(u8, tag, "DO")) {
+            try replace(allocator, &e.doi, stripDoiPrefix(value));
+        } else if (std.mem.eql(u8, tag, "UR")) {
+            if (e.url == null) try replace(allocator, &e.url, value);
+            if (e.doi == null) {
+                if (std.mem.indexOf(u8, value, "doi.org/")) |idx| try replace(allocator, &e.doi, value[idx + 8 ..]);
+            }
+        } else if (std.mem.eql(u8, tag, "SN")) {
+            // ISBN or ISSN; only ISBNs are kept
+            var buf: [13]u8 = undefined;
+            if (e.isbn == null) {
+                if (entry_mod.normalizeIsbn(&buf, value)) |isbn| e.isbn = try allocator.dupe(u8, isbn);
+            }
+        } else if (std.mem.eql(u8, tag, "PB")) {
+            try replace(allocator, &e.publisher, value);
+        } else if (std.mem.eql(u8, tag, "ID")) {
+            id = value;
+        }
+    }
+    if (current) |*c| {
+        try finish(allocator, c, &authors, id, &entries);
+        current = null;
+    }
+
+    return entries.toOwnedSlice(allocator);
+}
+
+/// Give a completed record its authors and key and add it to `entries`.
+fn finish(allocator: std.mem.Allocator, e: *Entry, authors: *std.ArrayList([]const u8), id: ?[]const u8, entries: *std.ArrayList(Entry)) !void {
+    e.authors = try authors.toOwnedSlice(allocator);
+    e.key = if (id) |i| try allocator.dupe(u8, i) else try entry_mod.generateKey(allocator, e, entries.items);
+    try entries.append(allocator, e.*);
+}
+
+fn replace(allocator: std.mem.Allocator, field: *?[]const u8, value: []const u8) !void {
+    const copy = try allocator.dupe(u8, value);
+    if (field.*) |old| allocator.free(old);
+    field.* = copy;
+}
+
+fn stripDoiPrefix(doi: []const u8) []const u8 {
+    if (std.mem.indexOf(u8, doi, "doi.org/")) |idx| return doi[idx + 8 ..];
+    return doi;
+}
+
+/// BibTeX entry type for a RIS reference type.
+fn entryType(ty: []const u8) []const u8 {
+    const types = [_][2][]const u8{
+        .{ "JOUR", "article" },
+        .{ "JFULL", "article" },
+        .{ "EJOUR", "article" },
+        .{ "CONF", "inproceedings" },
+        .{ "CPAPER", "inproceedings" },
+        .{ "BOOK", "book" },
+        .{ "EBOOK", "book" },
+        .{ "CHAP", "incollection" },
+        .{ "THES", "phdthesis" },
+        .{ "RPRT", "techreport" },
+    };
+    for (types) |t| {
+        if (std.mem.eql(u8, ty, t[0])) return t[1];
+    }
+    return "misc";
+}
+
+test "parse RIS" {
+    const allocator = std.testing.allocator;
+
+    const entries = try parseString(allocator,
+        \\TY  - JOUR
+        \\AU  - Smith, John
+        \\AU  - Doe, Jane
+        \\TI  - Learning to parse references
+        \\JO  - J. Mach. Learn. Res.
+        \\JF  - Journal of Machine Learning Research
+        \\PY  - 2020///
+        \\DO  - https://doi.org/10.1234/jmlr.2020.42
+        \\ER  -
+        \\
+        \\TY  - CPAPER
+        \\ID  - vaswani17
+        \\TI  - Attention is all you need
+        \\ER  -
+    );
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 2), entries.len);
+    try std.testing.expectEqualStrings("smith2020", entries[0].key);
+    try std.testing.expectEqualStrings("article", entries[0].entry_type);
+    try std.testing.expectEqual(@as(usize, 2), entries[0].authors.len);
+    try std.testing.expectEqualStrings("Journal of Machine Learning Research", entries[0].venue.?);
+    try std.testing.expectEqualStrings("10.1234/jmlr.2020.42", entries[0].doi.?);
+    try std.testing.expectEqual(@as(i32, 2020), entries[0].year.?);
+    try std.testing.expectEqualStrings("vaswani17", entries[1].key);
+    try std.testing.expectEqualStrings("inproceedings", entries[1].entry_type);
+}
@@ -4,12 +4,71 @@
 //! CrossRef, DBLP, arXiv, Semantic Scholar, and OpenAlex.
 
 pub const bibtex = @import("bibtex.zig");
+pub const encoding = @import("encoding.zig");
 pub const entry = @import("entry.zig");
 pub const matcher = @import("matcher.zig");
+pub const cjk = @import("cjk.zig");
+pub const names = @import("names.zig");
+pub const blocking = @import("blocking.zig");
+pub const parallel = @import("parallel.zig");
+pub const duplicates = @import("duplicates.zig");
+pub const consistency = @import("consistency.zig");
+pub const glob = @import("glob.zig");
 pub const http = @import("http.zig");
 pub const cache = @import("cache.zig");
 pub const report = @import("report.zig");
 pub const validators = @import("validators.zig");
+pub const pipeline = @import("pipeline.zig");
+pub const external = @import("external.zig");
+pub const proceedings = @import("proceedings.zig");
+pub const github = @import("github.zig");
+pub const deposits = @import("deposits.zig");
+pub const scrape = @import("scrape.zig");
+pub const markup = @import("markup.zig");
+pub const webpage = @import("webpage.zig");
+pub const wayback = @import("wayback.zig");
+pub const trace = @import("trace.zig");
+pub const progress = @import("progress.zig");
+pub const metrics = @import("metrics.zig");
+pub const plan = @import("plan.zig");
+pub const journal = @import("journal.zig");
+pub const verdicts = @import("verdicts.zig");
+pub const snapshot = @import("snapshot.zig");
+pub const history = @import("history.zig");
+pub const rules = @import("rules.zig");
+pub const fix = @import("fix.zig");
+pub const string_macros = @import("string_macros.zig");
+pub const config = @import("config.zig");
+pub const profiles = @import("profiles.zig");
+pub const venues = @import("venues.zig");
+pub const canonical = @import("canonical.zig");
+pub const iso4 = @import("iso4.zig");
+pub const titlecase = @import("titlecase.zig");
+pub const punctuation = @import("punctuation.zig");
+pub const language = @import("language.zig");
+pub const funders = @import("funders.zig");
+pub const stats = @import("stats.zig");
+pub const completeness = @import("completeness.zig");
+pub const policy = @import("policy.zig");
+pub const field_rules = @import("field_rules.zig");
+pub const regex = @import("regex.zig");
+pub const gates = @import("gates.zig");
+pub const ignores = @import("ignores.zig");
+pub const screening = @import("screening.zig");
+pub const grobid = @import("grobid.zig");
+pub const freetext = @import("freetext.zig");
+pub const ris = @import("ris.zig");
+pub const csl = @import("csl.zig");
+pub const input = @import("input.zig");
+pub const selection = @import("selection.zig");
+pub const workspace = @import("workspace.zig");
+pub const editions = @import("editions.zig");
+pub const zotero = @import("zotero.zig");
+pub const jabref = @import("jabref.zig");
+pub const serve = @import("serve.zig");
+pub const notify = @import("notify.zig");
+pub const cli = @import("cli.zig");
+pub const credentials = @import("credentials.zig");
 
 pub const Entry = entry.Entry;
 pub const Discrepancy = entry.Discrepancy;
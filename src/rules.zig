@@ -0,0 +1,329 @@
+//! Stable identifiers for every check bibval performs.
+//!
+//! Rule IDs (`BV001`, ...) never change meaning once released, so they can
+//! be used to suppress checks and to compare results across runs.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const Severity = entry_mod.Severity;
+const Discrepancy = entry_mod.Discrepancy;
+
+pub const Rule = enum {
+    title_mismatch,
+    title_difference,
+    year_mismatch,
+    missing_doi,
+    author_count,
+    venue_mismatch,
+    unknown_funder,
+    missing_award,
+    missing_year,
+    old_references,
+    blocked_venue,
+    blocked_publisher,
+    unlisted_venue,
+    misplaced_doi,
+    misplaced_arxiv,
+    duplicate_key,
+    duplicate_work,
+    custom_check,
+    volume_mismatch,
+    pages_mismatch,
+    field_rule,
+    title_style,
+    author_variant,
+    venue_variant,
+    first_author,
+    missing_author,
+    wrong_doi,
+    erratum,
+    wrong_edition,
+    workshop_venue,
+    book_edition,
+    unknown_version,
+    doi_granularity,
+    archive_url,
+    urldate,
+    numeric_field,
+    crossmark_update,
+    near_duplicate,
+    title_punctuation,
+    punctuation,
+    language_mismatch,
+    mixed_translation,
+    missing_repository,
+
+    /// Stable identifier, e.g. "BV003".
+    pub fn id(self: Rule) []const u8 {
+        return switch (self) {
+            .title_mismatch => "BV001",
+            .title_difference => "BV002",
+            .year_mismatch => "BV003",
+            .missing_doi => "BV004",
+            .author_count => "BV005",
+            .venue_mismatch => "BV006",
+            .unknown_funder => "BV007",
+            .missing_award => "BV008",
+            .missing_year => "BV009",
+            .old_references => "BV010",
+            .blocked_venue => "BV011",
+            .blocked_publisher => "BV012",
+            .unlisted_venue => "BV013",
+            .misplaced_doi => "BV014",
+            .misplaced_arxiv => "BV015",
+            .duplicate_key => "BV016",
+            .duplicate_work => "BV017",
+            .custom_check => "BV018",
+            .volume_mismatch => "BV019",
+            .pages_mismatch => "BV020",
+            .field_rule => "BV021",
+            .title_style => "BV022",
+            .author_variant => "BV023",
+            .venue_variant => "BV024",
+            .first_author => "BV025",
+            .missing_author => "BV026",
+            .wrong_doi => "BV027",
+            .erratum => "BV028",
+            .wrong_edition => "BV029",
+            .workshop_venue => "BV030",
+            .book_edition => "BV031",
+            .unknown_version => "BV032",
+            .doi_granularity => "BV033",
+            .archive_url => "BV034",
+            .urldate => "BV035",
+            .numeric_field => "BV036",
+            .crossmark_update => "BV037",
+            .near_duplicate => "BV038",
+            .title_punctuation => "BV039",
+            .punctuation => "BV040",
+            .language_mismatch => "BV041",
+            .mixed_translation => "BV042",
+            .missing_repository => "BV043",
+        };
+    }
+
+    /// Human-readable name, e.g. "year-mismatch".
+    pub fn slug(self: Rule) []const u8 {
+        return switch (self) {
+            .title_mismatch => "title-mismatch",
+            .title_difference => "title-difference",
+            .year_mismatch => "year-mismatch",
+            .missing_doi => "missing-doi",
+            .author_count => "author-count",
+            .venue_mismatch => "venue-mismatch",
+            .unknown_funder => "unknown-funder",
+            .missing_award => "missing-award",
+            .missing_year => "missing-year",
+            .old_references => "old-references",
+            .blocked_venue => "blocked-venue",
+            .blocked_publisher => "blocked-publisher",
+            .unlisted_venue => "unlisted-venue",
+            .misplaced_doi => "misplaced-doi",
+            .misplaced_arxiv => "misplaced-arxiv",
+            .duplicate_key => "duplicate-key",
+            .duplicate_work => "duplicate-work",
+            .custom_check => "custom-check",
+            .volume_mismatch => "volume-mismatch",
+            .pages_mismatch => "pages-mismatch",
+            .field_rule => "field-rule",
+            .title_style => "title-style",
+            .author_variant => "author-variant",
+            .venue_variant => "venue-variant",
+            .first_author => "first-author",
+            .missing_author => "missing-author",
+            .wrong_doi => "wrong-doi",
+            .erratum => "erratum",
+            .wrong_edition => "wrong-edition",
+            .workshop_venue => "workshop-venue",
+            .book_edition => "book-edition",
+            .unknown_version => "unknown-version",
+            .doi_granularity => "doi-granularity",
+            .archive_url => "archive-url",
+            .urldate => "urldate",
+            .numeric_field => "numeric-field",
+            .crossmark_update => "crossmark-update",
+            .near_duplicate => "near-duplicate",
+            .title_punctuation => "title-punctuation",
+            .punctuation => "punctuation",
+            .language_mismatch => "language-mismatch",
+            .mixed_translation => "mixed-translation",
+            .missing_repository => "missing-repository",
+        };
+    }
+
+    pub fn description(self: Rule) []const u8 {
+        return switch (self) {
+            .title_mismatch => "Title is significantly different from the matched record",
+            .title_difference => "Title differs slightly from the matched record",
+            .year_mismatch => "Publication year differs from the matched record",
+            .missing_doi => "Entry has no DOI but the matched record does",
+            .author_count => "Number of authors differs from the matched record",
+            .venue_mismatch => "Venue does not name the matched record's known venue",
+            .unknown_funder => "Funder is not in the Crossref Funder Registry (--check-funders)",
+            .missing_award => "Crossref lists an award number missing from the entry (--check-funders)",
+            .missing_year => "Reference has no year (policy.require_year)",
+            .old_references => "Too many references are older than policy.max_age years",
+            .blocked_venue => "Venue is on the screening blocklist",
+            .blocked_publisher => "Publisher is on the screening blocklist",
+            .unlisted_venue => "Venue is not on the screening allowlist",
+            .misplaced_doi => "DOI is given in the url or note field, or not as a bare lowercase DOI",
+            .misplaced_arxiv => "arXiv ID is not given as eprint with archivePrefix",
+            .duplicate_key => "The same key is defined more than once across the input files",
+            .duplicate_work => "The same work is cited under different keys",
+            .custom_check => "Finding of a field comparator registered through the library",
+            .volume_mismatch => "Volume differs from the matched record",
+            .pages_mismatch => "Page range differs from the matched record",
+            .field_rule => "Entry breaks a field rule from the config (policy.rules)",
+            .title_style => "Title capitalization does not follow fix.title_style",
+            .author_variant => "The same author is written differently across entries",
+            .venue_variant => "The same venue is written differently across entries",
+            .first_author => "First author differs from the matched record",
+            .missing_author => "The matched record lists authors the entry leaves out",
+            .wrong_doi => "DOI resolves to a record of a different work",
+            .erratum => "A correction or erratum of the matched work was published",
+            .wrong_edition => "Venue names another edition of the conference the work appeared in",
+            .workshop_venue => "A workshop paper is cited as a main-conference paper, or the reverse",
+            .book_edition => "Book edition, or the year given for it, differs from the matched edition",
+            .unknown_version => "Cited software version is not a tag of the GitHub repository",
+            .doi_granularity => "Zenodo or Figshare DOI names all versions, or another version, of the cited version",
+            .archive_url => "Web citation gives no archived copy; a Wayback Machine snapshot is suggested (--suggest-archives)",
+            .urldate => "Web citation has no urldate, or one in the wrong format or too old (policy.require_urldate)",
+            .numeric_field => "Page range ends before it starts, volume or number is not a number, or year and pages are swapped",
+            .crossmark_update => "Crossmark lists an update to the matched work: a new version, addendum, or retraction (an error)",
+            .near_duplicate => "Two entries have nearly the same title but different DOIs or years, e.g. a preprint and its published version",
+            .title_punctuation => "Title differs from the matched record only in punctuation, dashes, spacing, or capitalization",
+            .punctuation => "Quotes, dashes, or non-breaking spaces are not written for fix.engine (LaTeX markup or Unicode)",
+            .language_mismatch => "The langid or language field names a different language than the title is written in",
+            .mixed_translation => "Title is a translation, but the venue is given in the work's original language",
+            .missing_repository => "Cited GitHub repository does not exist",
+        };
+    }
+
+    /// Config keys that change what the rule reports, comma-separated, or
+    /// "" if none does. Any rule can be turned off with `--disable` or a
+    /// target's `disable`.
+    pub fn configKeys(self: Rule) []const u8 {
+        return switch (self) {
+            .title_mismatch, .title_difference => "matching.similarity.title",
+            .year_mismatch => "matching.max_year_distance",
+            .venue_mismatch => "fix.venue_style, matching.canonical_venues, matching.similarity.venue",
+            .venue_variant => "fix.venue_style",
+            .author_variant, .first_author, .missing_author => "matching.similarity.authors",
+            .missing_year => "policy.require_year",
+            .old_references => "policy.max_age, policy.max_old_percent, policy.old_severity",
+            .urldate => "policy.require_urldate, policy.urldate_format, policy.max_urldate_age",
+            .field_rule => "policy.rules",
+            .blocked_venue => "screening.blocked_venues, screening.blocklist",
+            .blocked_publisher => "screening.blocked_publishers, screening.blocklist",
+            .unlisted_venue => "screening.allowed_venues",
+            .title_style => "fix.title_style",
+            .punctuation => "fix.engine",
+            .missing_doi, .author_count, .unknown_funder, .missing_award, .misplaced_doi, .misplaced_arxiv, .duplicate_key, .duplicate_work, .custom_check, .volume_mismatch, .pages_mismatch, .wrong_doi, .erratum, .wrong_edition, .workshop_venue, .book_edition, .unknown_version, .doi_granularity, .archive_url, .numeric_field, .crossmark_update, .near_duplicate, .title_punctuation, .language_mismatch, .mixed_translation, .missing_repository => "",
+        };
+    }
+
+    pub fn defaultSeverity(self: Rule) Severity {
+        return switch (self) {
+            .title_mismatch, .year_mismatch, .first_author, .wrong_doi, .wrong_edition, .workshop_venue, .book_edition, .missing_repository, .missing_year, .blocked_venue, .blocked_publisher, .duplicate_key => .@"error",
+            .title_difference, .missing_doi, .author_count, .missing_author, .venue_mismatch, .unknown_funder, .old_references, .unlisted_venue, .duplicate_work, .custom_check, .volume_mismatch, .pages_mismatch, .unknown_version, .doi_granularity, .field_rule, .urldate, .numeric_field, .near_duplicate => .warning,
+            .missing_award, .misplaced_doi, .misplaced_arxiv, .erratum, .crossmark_update, .archive_url, .title_style, .title_punctuation, .punctuation, .language_mismatch, .mixed_translation, .author_variant, .venue_variant => .info,
+        };
+    }
+
+    /// How reliably a finding of this rule points at a real error (0.0 to
+    /// 1.0). Fuzzy comparisons are noisier than exact ones, and policy and
+    /// screening rules do not depend on a match at all.
+    pub fn signalQuality(self: Rule) f64 {
+        return switch (self) {
+            .title_mismatch => 0.6,
+            .author_count, .venue_mismatch, .language_mismatch => 0.7,
+            .year_mismatch, .first_author, .missing_author, .wrong_edition, .workshop_venue, .book_edition, .unknown_funder, .custom_check => 0.8,
+            .title_difference, .missing_doi, .missing_award, .unlisted_venue, .volume_mismatch, .pages_mismatch, .unknown_version, .doi_granularity, .title_style, .title_punctuation, .mixed_translation, .author_variant, .venue_variant, .near_duplicate => 0.9,
+            .wrong_doi, .missing_repository, .misplaced_doi, .misplaced_arxiv, .erratum, .crossmark_update, .archive_url, .missing_year, .old_references, .blocked_venue, .blocked_publisher, .duplicate_key, .duplicate_work, .field_rule, .urldate, .numeric_field, .punctuation => 1.0,
+        };
+    }
+
+    /// Look up a rule by ID or name (case-insensitive).
+    pub fn parse(s: []const u8) ?Rule {
+        for (std.enums.values(Rule)) |rule| {
+            if (std.ascii.eqlIgnoreCase(s, rule.id()) or std.ascii.eqlIgnoreCase(s, rule.slug())) return rule;
+        }
+        return null;
+    }
+};
+
+/// Set of rules, e.g. those disabled with `--disable`.
+pub const RuleSet = std.EnumSet(Rule);
+
+/// Drop discrepancies produced by rules in `disabled`. Takes ownership of
+/// `discrepancies` and returns the remainder.
+pub fn removeDisabled(allocator: std.mem.Allocator, discrepancies: []Discrepancy, disabled: RuleSet) ![]Discrepancy {
+    if (disabled.count() == 0 or discrepancies.len == 0) return discrepancies;
+
+    var list = std.ArrayList(Discrepancy).fromOwnedSlice(discrepancies);
+    var i: usize = 0;
+    while (i < list.items.len) {
+        if (disabled.contains(list.items[i].rule)) {
+            var removed = list.orderedRemove(i);
+            removed.deinit();
+        } else {
+            i += 1;
+        }
+    }
+    return list.toOwnedSlice(allocator);
+}
+
+/// Drop discrepancies whose confidence is below `min_confidence`. Takes
+/// ownership of `discrepancies` and returns the remainder.
+pub fn removeBelowConfidence(allocator: std.mem.Allocator, discrepancies: []Discrepancy, min_confidence: f64) ![]Discrepancy {
+    if (min_confidence <= 0 or discrepancies.len == 0) return discrepancies;
+
+    var list = std.ArrayList(Discrepancy).fromOwnedSlice(discrepancies);
+    var i: usize = 0;
+    while (i < list.items.len) {
+        if (list.items[i].confidence < min_confidence) {
+            var removed = list.orderedRemove(i);
+            removed.deinit();
+        } else {
+            i += 1;
+        }
+    }
+    return list.toOwnedSlice(allocator);
+}
+
+/// Print all rules as a table.
+pub fn printRules(writer: anytype) !void {
+    try writer.print("{s:<7}{s:<20}{s:<10}{s}\n", .{ "ID", "NAME", "SEVERITY", "DESCRIPTION" });
+    for (std.enums.values(Rule)) |rule| {
+        try writer.print("{s:<7}{s:<20}{s:<10}{s}\n", .{ rule.id(), rule.slug(), rule.defaultSeverity().name(), rule.description() });
+        const keys = rule.configKeys();
+        if (keys.len > 0) try writer.print("{s:<37}config: {s}\n", .{ "", keys });
+    }
+}
+
+test "Rule.parse accepts IDs and names" {
+    try std.testing.expectEqual(Rule.year_mismatch, Rule.parse("BV003").?);
+    try std.testing.expectEqual(Rule.year_mismatch, Rule.parse("bv003").?);
+    try std.testing.expectEqual(Rule.missing_doi, Rule.parse("missing-doi").?);
+    try std.testing.expect(Rule.parse("BV999") == null);
+}
+
+test "printRules lists config keys" {
+    var out: std.Io.Writer.Allocating = .init(std.testing.allocator);
+    defer out.deinit();
+    try printRules(&out.writer);
+    try std.testing.expect(std.mem.indexOf(u8, out.written(), "config: matching.max_year_distance\n") != null);
+}
+
+test "removeBelowConfidence keeps confident findings" {
+    const allocator = std.testing.allocator;
+
+    const discrepancies = try allocator.alloc(Discrepancy, 2);
+    discrepancies[0] = .{ .rule = .title_mismatch, .field = .title, .severity = .@"error", .local_value = "", .remote_value = "", .message = "", .confidence = 0.45 };
+    discrepancies[1] = .{ .rule = .year_mismatch, .field = .year, .severity = .@"error", .local_value = "", .remote_value = "", .message = "", .confidence = 0.8 };
+
+    const kept = try removeBelowConfidence(allocator, discrepancies, 0.5);
+    defer allocator.free(kept);
+    try std.testing.expectEqual(@as(usize, 1), kept.len);
+    try std.testing.expectEqual(Rule.year_mismatch, kept[0].rule);
+}
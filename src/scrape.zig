@@ -0,0 +1,326 @@
+//! Fetching pages from sites without an API.
+//!
+//! Web pages cited by URL and the PMLR and NeurIPS proceedings are read
+//! from the sites themselves, so they are fetched the way a polite crawler
+//! would: `/robots.txt` is read first and honored (the rules for
+//! `bibval` if it names it, else those for `*`), requests to one host are
+//! spaced by its `Crawl-delay` or at least a second, and proceedings pages
+//! and robots.txt are kept in the response cache for their full lifetime.
+//! As RFC 9309 has it, a
+//! robots.txt that is missing or refused with a 4xx status allows
+//! everything, and a site whose robots.txt cannot be read at all is left
+//! alone.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const validators = @import("validators.zig");
+const ValidatorError = validators.ValidatorError;
+
+/// Product token matched against `User-agent` lines.
+const AGENT = "bibval";
+/// Delay between requests to one host when robots.txt sets none.
+pub const MIN_INTERVAL_MS: u64 = 1000;
+/// Longest `Crawl-delay` waited for; longer ones would stall a run.
+const MAX_CRAWL_DELAY_MS: u64 = 30 * std.time.ms_per_s;
+/// Hosts whose last request is remembered.
+const MAX_HOSTS = 16;
+
+/// Fetches pages, honoring robots.txt, shared by the validators that
+/// read HTML or files off a site.
+pub const Scraper = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    /// Minimum delay between requests to one host in milliseconds.
+    min_interval_ms: u64 = MIN_INTERVAL_MS,
+    /// Last request to each recently visited host.
+    visits: [MAX_HOSTS]Visit = [_]Visit{.{}} ** MAX_HOSTS,
+
+    const Visit = struct {
+        /// Hash of the origin, 0 if the slot is free.
+        host: u64 = 0,
+        at_ms: i64 = 0,
+    };
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Scraper {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+        };
+    }
+
+    /// GET `url`, or read it from the cache under `api` and `key`. Null if
+    /// the site's robots.txt disallows it. Caller owns the body.
+    pub fn fetch(self: *Scraper, api: []const u8, key: []const u8, url: []const u8) ValidatorError!?[]u8 {
+        if (self.response_cache.get(api, key)) |cached| return cached;
+        const body = try self.fetchPage(url) orelse return null;
+        self.response_cache.set(api, key, body) catch {};
+        return body;
+    }
+
+    /// GET `url` without caching the page. Null if the site's robots.txt
+    /// disallows it. Caller owns the body.
+    pub fn fetchPage(self: *Scraper, url: []const u8) ValidatorError!?[]u8 {
+        if (!try self.mayFetch(url)) return null;
+        return self.client.get(url) catch |err| return validatorError(err);
+    }
+
+    /// GET `url` like `fetchPage`, but return a redirect rather than follow
+    /// it, so the caller can check where it leads before fetching that.
+    pub fn fetchUnredirected(self: *Scraper, url: []const u8) ValidatorError!?http.Fetched {
+        if (!try self.mayFetch(url)) return null;
+        return self.client.getUnredirected(url) catch |err| return validatorError(err);
+    }
+
+    /// Whether the site's robots.txt lets bibval fetch `url`; if it does,
+    /// wait out the site's crawl delay.
+    fn mayFetch(self: *Scraper, url: []const u8) ValidatorError!bool {
+        const site = splitUrl(url) orelse return ValidatorError.InvalidUrl;
+        const robots = try self.robotsTxt(site.origin);
+        defer self.allocator.free(robots);
+        if (!allows(robots, site.path)) {
+            if (self.client.tracer) |t| t.event(.debug, "robots", "{s} disallowed", .{url});
+            return false;
+        }
+
+        const delay_ms = if (crawlDelay(robots)) |ms| @min(@max(ms, self.min_interval_ms), MAX_CRAWL_DELAY_MS) else self.min_interval_ms;
+        self.throttle(site.origin, delay_ms);
+        return true;
+    }
+
+    /// The robots.txt of `origin`, empty if it has none.
+    fn robotsTxt(self: *Scraper, origin: []const u8) ValidatorError![]u8 {
+        if (self.response_cache.get("robots", origin)) |cached| return cached;
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/robots.txt", .{origin});
+        defer self.allocator.free(url);
+        self.throttle(origin, self.min_interval_ms);
+        const body = self.client.get(url) catch |err| switch (err) {
+            // "Unavailable" in RFC 9309: no rules, so everything is allowed
+            http.HttpError.NotFound, http.HttpError.Unavailable => try self.allocator.dupe(u8, ""),
+            // "Unreachable" (a server error, no answer, or rate limiting):
+            // the site is left alone
+            else => return validatorError(err),
+        };
+        self.response_cache.set("robots", origin, body) catch {};
+        return body;
+    }
+
+    /// Wait until `interval_ms` has passed since the last request to
+    /// `origin`.
+    fn throttle(self: *Scraper, origin: []const u8, interval_ms: u64) void {
+        const host = std.hash.Wyhash.hash(0, origin) | 1;
+        var slot = &self.visits[0];
+        for (&self.visits) |*visit| {
+            if (visit.host == host) {
+                slot = visit;
+                break;
+            }
+            if (visit.at_ms < slot.at_ms) slot = visit;
+        }
+
+        if (slot.host == host) {
+            const elapsed = std.time.milliTimestamp() - slot.at_ms;
+            const interval: i64 = @intCast(interval_ms);
+            if (elapsed < interval) self.client.wait(@intCast(interval - elapsed));
+        }
+        slot.* = .{ .host = host, .at_ms = std.time.milliTimestamp() };
+    }
+};
+
+fn validatorError(err: http.HttpError) ValidatorError {
+    return switch (err) {
+        http.HttpError.NotFound => ValidatorError.NotFound,
+        http.HttpError.RateLimited => ValidatorError.RateLimited,
+        else => ValidatorError.RequestFailed,
+    };
+}
+
+const Site = struct {
+    /// Scheme and authority, as in "https://example.org".
+    origin: []const u8,
+    /// Path and query, "/" if empty.
+    path: []const u8,
+};
+
+fn splitUrl(url: []const u8) ?Site {
+    const scheme_end = std.mem.indexOf(u8, url, "://") orelse return null;
+    const authority = scheme_end + 3;
+    const end = std.mem.indexOfAnyPos(u8, url, authority, "/?#") orelse url.len;
+    if (end == authority) return null;
+    const rest = url[end..];
+    const path = rest[0 .. std.mem.indexOfScalar(u8, rest, '#') orelse rest.len];
+    return .{ .origin = url[0..end], .path = if (path.len == 0) "/" else path };
+}
+
+/// Whether robots.txt `text` lets bibval fetch `path`. The longest
+/// matching `Allow` or `Disallow` rule decides, `Allow` on a tie.
+pub fn allows(text: []const u8, path: []const u8) bool {
+    var rules = Rules.init(text);
+    var allowed = true;
+    var longest: ?usize = null;
+    while (rules.next()) |d| {
+        const allow = std.ascii.eqlIgnoreCase(d.name, "allow");
+        if (!allow and !std.ascii.eqlIgnoreCase(d.name, "disallow")) continue;
+        // An empty Disallow allows everything
+        if (d.value.len == 0 or !matches(d.value, path)) continue;
+        if (longest) |len| {
+            if (d.value.len < len or (d.value.len == len and !allow)) continue;
+        }
+        longest = d.value.len;
+        allowed = allow;
+    }
+    return allowed;
+}
+
+/// The `Crawl-delay` robots.txt `text` sets for bibval, in milliseconds.
+pub fn crawlDelay(text: []const u8) ?u64 {
+    var rules = Rules.init(text);
+    while (rules.next()) |d| {
+        if (!std.ascii.eqlIgnoreCase(d.name, "crawl-delay")) continue;
+        const secs = std.fmt.parseFloat(f64, d.value) catch continue;
+        if (secs < 0 or !std.math.isFinite(secs)) continue;
+        return @intFromFloat(@min(secs, 86400) * std.time.ms_per_s);
+    }
+    return null;
+}
+
+/// Whether robots.txt path pattern `pattern` matches `path`. `*` matches
+/// any run of characters and a final `$` the end of the path; otherwise
+/// the pattern need only match a prefix. Only the last `*` is backtracked
+/// to, so a pattern with many stars takes at most the product of the two
+/// lengths rather than exponential time.
+fn matches(pattern: []const u8, path: []const u8) bool {
+    const anchored = pattern.len > 0 and pattern[pattern.len - 1] == '$';
+    const pat = if (anchored) pattern[0 .. pattern.len - 1] else pattern;
+    var p: usize = 0;
+    var s: usize = 0;
+    // Pattern position after the last `*`, and the path position it matched up to
+    var star: ?usize = null;
+    var mark: usize = 0;
+    while (s < path.len) {
+        if (p == pat.len and !anchored) return true;
+        if (p < pat.len and pat[p] == '*') {
+            p += 1;
+            star = p;
+            mark = s;
+        } else if (p < pat.len and pat[p] == path[s]) {
+            p += 1;
+            s += 1;
+        } else if (star) |after| {
+            // Let the `*` take one more character
+            mark += 1;
+            s = mark;
+            p = after;
+        } else {
+            return false;
+        }
+    }
+    while (p < pat.len and pat[p] == '*') p += 1;
+    return p == pat.len;
+}
+
+const Directive = struct {
+    name: []const u8,
+    value: []const u8,
+};
+
+/// The directives of the groups that apply to bibval.
+const Rules = struct {
+    lines: std.mem.SplitIterator(u8, .scalar),
+    /// "bibval" if a group names it, else "*".
+    agent: []const u8,
+    /// Whether the previous directive was a `User-agent` line.
+    in_agents: bool = false,
+    applies: bool = false,
+
+    fn init(text: []const u8) Rules {
+        var agent: []const u8 = "*";
+        var lines = std.mem.splitScalar(u8, text, '\n');
+        while (lines.next()) |line| {
+            const d = directive(line) orelse continue;
+            if (std.ascii.eqlIgnoreCase(d.name, "user-agent") and namesAgent(d.value, AGENT)) agent = AGENT;
+        }
+        return .{ .lines = std.mem.splitScalar(u8, text, '\n'), .agent = agent };
+    }
+
+    fn next(self: *Rules) ?Directive {
+        while (self.lines.next()) |line| {
+            const d = directive(line) orelse continue;
+            if (std.ascii.eqlIgnoreCase(d.name, "user-agent")) {
+                // A User-agent line after rules starts a new group
+                if (!self.in_agents) self.applies = false;
+                self.in_agents = true;
+                if (namesAgent(d.value, self.agent)) self.applies = true;
+                continue;
+            }
+            self.in_agents = false;
+            if (self.applies) return d;
+        }
+        return null;
+    }
+};
+
+fn directive(line: []const u8) ?Directive {
+    const content = line[0 .. std.mem.indexOfScalar(u8, line, '#') orelse line.len];
+    const colon = std.mem.indexOfScalar(u8, content, ':') orelse return null;
+    return .{
+        .name = std.mem.trim(u8, content[0..colon], " \t\r"),
+        .value = std.mem.trim(u8, content[colon + 1 ..], " \t\r"),
+    };
+}
+
+fn namesAgent(value: []const u8, agent: []const u8) bool {
+    if (std.mem.eql(u8, agent, "*")) return std.mem.eql(u8, value, "*");
+    if (std.ascii.eqlIgnoreCase(value, agent)) return true;
+    return value.len > agent.len and std.ascii.startsWithIgnoreCase(value, agent) and value[agent.len] == '/';
+}
+
+test "robots.txt rules" {
+    const robots =
+        \\# Crawlers
+        \\User-agent: Googlebot
+        \\User-agent: *
+        \\Disallow: /search
+        \\Disallow: /*.pdf$
+        \\Allow: /search/about
+        \\Crawl-delay: 2.5
+        \\
+        \\User-agent: BadBot
+        \\Disallow: /
+    ;
+    try std.testing.expect(allows(robots, "/paper/123"));
+    try std.testing.expect(!allows(robots, "/search?q=attention"));
+    try std.testing.expect(allows(robots, "/search/about"));
+    try std.testing.expect(!allows(robots, "/files/paper.pdf"));
+    try std.testing.expect(allows(robots, "/files/paper.pdf?download=1"));
+    try std.testing.expectEqual(@as(?u64, 2500), crawlDelay(robots));
+
+    // A group naming bibval replaces the one for every crawler
+    const named =
+        \\User-agent: *
+        \\Disallow: /
+        \\
+        \\User-agent: bibval
+        \\Disallow: /private/
+    ;
+    try std.testing.expect(allows(named, "/v139/assets/bib/bibliography.bib"));
+    try std.testing.expect(!allows(named, "/private/notes"));
+    try std.testing.expectEqual(@as(?u64, null), crawlDelay(named));
+    try std.testing.expect(allows("", "/anything"));
+
+    try std.testing.expect(matches("/a*b*c", "/axxbyyczz"));
+    try std.testing.expect(!matches("/a*b*c$", "/axxbyyczz"));
+    try std.testing.expect(matches("/*$", "/"));
+    try std.testing.expect(!matches("/search", "/sear"));
+    // Many stars against a long path that does not match
+    try std.testing.expect(!matches("/" ++ "*a" ** 20 ++ "b$", "/" ++ "a" ** 2000));
+
+    const site = splitUrl("https://proceedings.mlr.press/v139/smith21a.html#abstract").?;
+    try std.testing.expectEqualStrings("https://proceedings.mlr.press", site.origin);
+    try std.testing.expectEqualStrings("/v139/smith21a.html", site.path);
+    try std.testing.expectEqualStrings("/", splitUrl("https://example.org").?.path);
+    try std.testing.expect(splitUrl("example.org/page") == null);
+}
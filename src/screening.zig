@@ -0,0 +1,152 @@
+//! Venue and publisher screening.
+//!
+//! Flags citations to venues or publishers on a user-supplied blocklist
+//! (e.g. known predatory journals), and optionally to venues missing from
+//! an allowlist. Names are compared against the matched record's canonical
+//! venue as well as the local string, so an abbreviation or alias of a
+//! blocked journal is still caught.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const venues = @import("venues.zig");
+const rules = @import("rules.zig");
+const Entry = entry_mod.Entry;
+const Discrepancy = entry_mod.Discrepancy;
+
+pub const Screening = struct {
+    blocked_venues: []const []const u8 = &.{},
+    blocked_publishers: []const []const u8 = &.{},
+    /// Names read from a blocklist file, matched against both venues and
+    /// publishers.
+    blocklist: []const []const u8 = &.{},
+    /// When non-empty, venues not on this list are reported.
+    allowed_venues: []const []const u8 = &.{},
+
+    pub fn isEmpty(self: *const Screening) bool {
+        return self.blocked_venues.len == 0 and self.blocked_publishers.len == 0 and
+            self.blocklist.len == 0 and self.allowed_venues.len == 0;
+    }
+};
+
+/// Parse a list file: one name per line, `#` starts a comment.
+pub fn parseList(allocator: std.mem.Allocator, content: []const u8) ![]const []const u8 {
+    var names: std.ArrayList([]const u8) = .empty;
+    errdefer names.deinit(allocator);
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |raw| {
+        const line = if (std.mem.indexOfScalar(u8, raw, '#')) |hash| raw[0..hash] else raw;
+        const name = std.mem.trim(u8, line, " \t\r");
+        if (name.len > 0) try names.append(allocator, name);
+    }
+    return names.toOwnedSlice(allocator);
+}
+
+/// Screen an entry's venue and publisher. `remote` is its matched record.
+pub fn check(allocator: std.mem.Allocator, screening: *const Screening, local: *const Entry, remote: *const Entry) ![]Discrepancy {
+    var discrepancies: std.ArrayList(Discrepancy) = .empty;
+    errdefer {
+        for (discrepancies.items) |*d| d.deinit();
+        discrepancies.deinit(allocator);
+    }
+
+    const venue_names = [_]?[]const u8{ remote.venue, local.venue };
+    const display_venue = remote.venue orelse local.venue;
+
+    if (findListed(screening.blocked_venues, &venue_names) orelse findListed(screening.blocklist, &venue_names)) |listed| {
+        try discrepancies.append(allocator, .{
+            .rule = .blocked_venue,
+            .field = .venue,
+            .severity = .@"error",
+            .local_value = try allocator.dupe(u8, local.venue orelse "(none)"),
+            .remote_value = try allocator.dupe(u8, display_venue.?),
+            .message = try std.fmt.allocPrint(allocator, "Venue is on the blocklist: {s}", .{listed}),
+            .allocator = allocator,
+        });
+    } else if (screening.allowed_venues.len > 0 and display_venue != null and
+        findListed(screening.allowed_venues, &venue_names) == null)
+    {
+        try discrepancies.append(allocator, .{
+            .rule = .unlisted_venue,
+            .field = .venue,
+            .severity = .warning,
+            .local_value = try allocator.dupe(u8, local.venue orelse "(none)"),
+            .remote_value = try allocator.dupe(u8, display_venue.?),
+            .message = try std.fmt.allocPrint(allocator, "Venue is not on the allowlist: {s}", .{display_venue.?}),
+            .allocator = allocator,
+        });
+    }
+
+    const publisher_names = [_]?[]const u8{ remote.publisher, local.publisher };
+    if (findListed(screening.blocked_publishers, &publisher_names) orelse findListed(screening.blocklist, &publisher_names)) |listed| {
+        try discrepancies.append(allocator, .{
+            .rule = .blocked_publisher,
+            .field = .publisher,
+            .severity = .@"error",
+            .local_value = try allocator.dupe(u8, local.publisher orelse "(none)"),
+            .remote_value = try allocator.dupe(u8, (remote.publisher orelse local.publisher).?),
+            .message = try std.fmt.allocPrint(allocator, "Publisher is on the blocklist: {s}", .{listed}),
+            .allocator = allocator,
+        });
+    }
+
+    return discrepancies.toOwnedSlice(allocator);
+}
+
+/// The first list entry naming any of `names`.
+fn findListed(list: []const []const u8, names: []const ?[]const u8) ?[]const u8 {
+    for (list) |listed| {
+        for (names) |name| {
+            if (name) |n| {
+                if (sameName(listed, n)) return listed;
+            }
+        }
+    }
+    return null;
+}
+
+/// Whether two names refer to the same venue or publisher, ignoring case
+/// and punctuation, or via the dictionary of known venues.
+fn sameName(a: []const u8, b: []const u8) bool {
+    var a_buf: [256]u8 = undefined;
+    var b_buf: [256]u8 = undefined;
+    const a_norm = venues.normalize(a, &a_buf);
+    if (a_norm.len > 0 and std.mem.eql(u8, a_norm, venues.normalize(b, &b_buf))) return true;
+
+    const known_a = venues.lookup(a) orelse return false;
+    return known_a == venues.lookup(b);
+}
+
+test "blocked and unlisted venues" {
+    const allocator = std.testing.allocator;
+
+    const screening = Screening{
+        .blocked_venues = &.{"Journal of Machine Learning Research"},
+        .blocked_publishers = &.{"Shady Press"},
+    };
+    const local = Entry{ .key = "a", .entry_type = "article", .venue = "JMLR" };
+    const remote = Entry{ .key = "a", .entry_type = "article", .venue = "J. Mach. Learn. Res.", .publisher = "SHADY PRESS." };
+
+    const found = try check(allocator, &screening, &local, &remote);
+    defer {
+        for (found) |*d| d.deinit();
+        allocator.free(found);
+    }
+    try std.testing.expectEqual(@as(usize, 2), found.len);
+    try std.testing.expectEqual(rules.Rule.blocked_venue, found[0].rule);
+    try std.testing.expectEqual(rules.Rule.blocked_publisher, found[1].rule);
+
+    const allow = Screening{ .allowed_venues = &.{"Nature"} };
+    const unlisted = try check(allocator, &allow, &local, &remote);
+    defer {
+        for (unlisted) |*d| d.deinit();
+        allocator.free(unlisted);
+    }
+    try std.testing.expectEqual(@as(usize, 1), unlisted.len);
+    try std.testing.expectEqual(rules.Rule.unlisted_venue, unlisted[0].rule);
+
+    const lines = try parseList(allocator, "# predatory\nShady Press\n\n  Other Journal  # note\n");
+    defer allocator.free(lines);
+    try std.testing.expectEqual(@as(usize, 2), lines.len);
+    try std.testing.expectEqualStrings("Other Journal", lines[1]);
+}
@@ -0,0 +1,149 @@
+//! Choosing which entries to validate.
+//!
+//! Checking one fixed entry should not mean validating the whole
+//! bibliography again. Entries can be picked by key (`--key`), key pattern
+//! (`--key-glob 'smith*'`), entry type (`--type article`), or by whether
+//! they changed since a git revision (`--modified-since main`).
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const glob = @import("glob.zig");
+const input = @import("input.zig");
+const Entry = entry_mod.Entry;
+
+/// Content hashes of entries, as from `Entry.contentHash`.
+pub const Hashes = std.AutoHashMapUnmanaged(u64, void);
+
+pub const Selection = struct {
+    /// Keys to validate. With `key_globs`, an entry matching either is kept.
+    keys: []const []const u8 = &.{},
+    /// Key patterns with `*` and `?`.
+    key_globs: []const []const u8 = &.{},
+    /// Entry types to validate, compared case-insensitively.
+    types: []const []const u8 = &.{},
+    /// Entries as of `--modified-since`; identical entries are skipped.
+    unchanged: ?*const Hashes = null,
+
+    /// Whether nothing narrows the selection.
+    pub fn isEmpty(self: *const Selection) bool {
+        return self.keys.len == 0 and self.key_globs.len == 0 and self.types.len == 0 and self.unchanged == null;
+    }
+
+    pub fn matches(self: *const Selection, e: *const Entry) bool {
+        if (self.keys.len > 0 or self.key_globs.len > 0) {
+            const by_key = for (self.keys) |key| {
+                if (std.mem.eql(u8, e.key, key)) break true;
+            } else for (self.key_globs) |pattern| {
+                if (glob.match(pattern, e.key)) break true;
+            } else false;
+            if (!by_key) return false;
+        }
+        if (self.types.len > 0) {
+            const by_type = for (self.types) |t| {
+                if (std.ascii.eqlIgnoreCase(e.entry_type, t)) break true;
+            } else false;
+            if (!by_type) return false;
+        }
+        if (self.unchanged) |hashes| {
+            if (hashes.contains(e.contentHash())) return false;
+        }
+        return true;
+    }
+};
+
+pub const GitError = error{
+    /// `git` could not be run.
+    GitUnavailable,
+    /// The revision does not name a commit.
+    UnknownRevision,
+};
+
+/// Fail unless `rev` names a commit of the repository around the current
+/// directory.
+pub fn verifyRevision(allocator: std.mem.Allocator, rev: []const u8) !void {
+    const spec = try std.fmt.allocPrint(allocator, "{s}^{{commit}}", .{rev});
+    defer allocator.free(spec);
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = &.{ "git", "rev-parse", "--verify", "--quiet", spec },
+    }) catch return GitError.GitUnavailable;
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+    if (result.term != .Exited or result.term.Exited != 0) return GitError.UnknownRevision;
+}
+
+/// Add the content hashes of the entries `path` held at revision `rev` to
+/// `hashes`. A file that did not exist then adds nothing, so all of its
+/// entries count as modified.
+pub fn addHashesAt(allocator: std.mem.Allocator, rev: []const u8, path: []const u8, hashes: *Hashes) !void {
+    const spec = try revisionSpec(allocator, rev, path);
+    defer allocator.free(spec);
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = &.{ "git", "show", spec },
+        .max_output_bytes = input.MAX_INPUT_SIZE,
+    }) catch return GitError.GitUnavailable;
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+    if (result.term != .Exited or result.term.Exited != 0) return;
+
+    var decoded = try input.decode(allocator, result.stdout, null);
+    defer decoded.deinit();
+    const format = input.fromPath(path) orelse switch (input.detect(decoded.text)) {
+        .freetext => .bibtex,
+        else => |f| f,
+    };
+    const entries = input.parse(allocator, decoded.text, format, null) catch return;
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+    for (entries) |*e| try hashes.put(allocator, e.contentHash(), {});
+}
+
+/// The `git show` argument for `path` at `rev`. A relative path is taken
+/// from the current directory ("./"); git only resolves absolute paths
+/// relative to the repository root, so those are made relative to it.
+fn revisionSpec(allocator: std.mem.Allocator, rev: []const u8, path: []const u8) ![]u8 {
+    if (!std.fs.path.isAbsolute(path)) return std.fmt.allocPrint(allocator, "{s}:./{s}", .{ rev, path });
+
+    const result = std.process.Child.run(.{
+        .allocator = allocator,
+        .argv = &.{ "git", "rev-parse", "--show-toplevel" },
+    }) catch return GitError.GitUnavailable;
+    defer allocator.free(result.stdout);
+    defer allocator.free(result.stderr);
+    if (result.term != .Exited or result.term.Exited != 0) return GitError.GitUnavailable;
+    const root = std.mem.trimRight(u8, result.stdout, "\r\n");
+
+    // The repository root is reported with symlinks resolved
+    const real = std.fs.realpathAlloc(allocator, path) catch try allocator.dupe(u8, path);
+    defer allocator.free(real);
+    const relative = try std.fs.path.relative(allocator, root, real);
+    defer allocator.free(relative);
+    return std.fmt.allocPrint(allocator, "{s}:{s}", .{ rev, relative });
+}
+
+test "selecting entries" {
+    const entries = [_]Entry{
+        .{ .key = "smith2020", .entry_type = "article", .title = "A" },
+        .{ .key = "smith2021", .entry_type = "inproceedings", .title = "B" },
+        .{ .key = "jones2021", .entry_type = "Article", .title = "C" },
+    };
+
+    const by_glob = Selection{ .key_globs = &.{"smith*"}, .types = &.{"article"} };
+    try std.testing.expect(by_glob.matches(&entries[0]));
+    try std.testing.expect(!by_glob.matches(&entries[1]));
+    try std.testing.expect(!by_glob.matches(&entries[2]));
+
+    const by_key = Selection{ .keys = &.{"jones2021"}, .key_globs = &.{"*2020"} };
+    try std.testing.expect(by_key.matches(&entries[0]));
+    try std.testing.expect(by_key.matches(&entries[2]));
+
+    var hashes: Hashes = .empty;
+    defer hashes.deinit(std.testing.allocator);
+    try hashes.put(std.testing.allocator, entries[0].contentHash(), {});
+    const modified = Selection{ .unchanged = &hashes };
+    try std.testing.expect(!modified.matches(&entries[0]));
+    try std.testing.expect(modified.matches(&entries[1]));
+}
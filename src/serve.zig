@@ -0,0 +1,269 @@
+//! Helpers for `bibval serve`.
+//!
+//! One server holds the HTTP client, response cache and validators for all
+//! requests, so a group shares a warm cache and a single upstream request
+//! rate. Requests are handled one at a time; each client is additionally
+//! limited to a number of looked-up entries per minute so that one large
+//! upload cannot starve everyone else. `GET /metrics` exposes request
+//! counts, the cache hit rate, upstream latencies and rate-limit waits to
+//! Prometheus.
+
+const std = @import("std");
+const input = @import("input.zig");
+const metrics = @import("metrics.zig");
+
+pub const DEFAULT_ADDRESS = "127.0.0.1";
+pub const DEFAULT_PORT: u16 = 8080;
+/// Entries (or DOIs) each client may look up per minute.
+pub const DEFAULT_RATE_LIMIT: u32 = 600;
+/// Seconds a client may stay silent while sending its request before the
+/// connection is dropped.
+pub const READ_TIMEOUT_S = 30;
+
+/// Per-client token buckets, refilled continuously up to `per_minute`.
+pub const RateLimiter = struct {
+    allocator: std.mem.Allocator,
+    per_minute: u32,
+    buckets: std.AutoHashMapUnmanaged(u64, Bucket) = .empty,
+
+    const Bucket = struct {
+        tokens: f64,
+        updated_ms: i64,
+    };
+
+    pub const Decision = enum {
+        allow,
+        /// Over the limit now; retry later.
+        wait,
+        /// More than the limit allows in a minute; will never be allowed.
+        too_large,
+    };
+
+    pub fn init(allocator: std.mem.Allocator, per_minute: u32) RateLimiter {
+        return .{ .allocator = allocator, .per_minute = per_minute };
+    }
+
+    pub fn deinit(self: *RateLimiter) void {
+        self.buckets.deinit(self.allocator);
+    }
+
+    /// Take `cost` tokens from the client's bucket if it has them.
+    /// A limit of 0 allows everything.
+    pub fn take(self: *RateLimiter, client: u64, cost: usize, now_ms: i64) !Decision {
+        if (self.per_minute == 0) return .allow;
+        const capacity: f64 = @floatFromInt(self.per_minute);
+        const needed: f64 = @floatFromInt(cost);
+        if (needed > capacity) return .too_large;
+
+        const gop = try self.buckets.getOrPut(self.allocator, client);
+        if (!gop.found_existing) gop.value_ptr.* = .{ .tokens = capacity, .updated_ms = now_ms };
+        const bucket = gop.value_ptr;
+
+        const elapsed: f64 = @floatFromInt(@max(0, now_ms - bucket.updated_ms));
+        bucket.tokens = @min(capacity, bucket.tokens + elapsed * capacity / std.time.ms_per_min);
+        bucket.updated_ms = now_ms;
+
+        if (bucket.tokens < needed) return .wait;
+        bucket.tokens -= needed;
+        return .allow;
+    }
+
+    /// Seconds until the client's bucket holds `cost` tokens.
+    pub fn retryAfter(self: *const RateLimiter, client: u64, cost: usize) u64 {
+        const bucket = self.buckets.get(client) orelse return 0;
+        const missing = @as(f64, @floatFromInt(cost)) - bucket.tokens;
+        if (missing <= 0) return 0;
+        const per_sec = @as(f64, @floatFromInt(self.per_minute)) / 60.0;
+        return @intFromFloat(@ceil(missing / per_sec));
+    }
+};
+
+/// Identify a client by its IP address, ignoring the port.
+pub fn clientId(address: std.net.Address) u64 {
+    return switch (address.any.family) {
+        std.posix.AF.INET => std.hash.Wyhash.hash(4, std.mem.asBytes(&address.in.sa.addr)),
+        std.posix.AF.INET6 => std.hash.Wyhash.hash(6, &address.in6.sa.addr),
+        else => 0,
+    };
+}
+
+/// A request target split into path and query string.
+pub const Target = struct {
+    path: []const u8,
+    query: []const u8,
+
+    pub fn parse(target: []const u8) Target {
+        const q = std.mem.indexOfScalar(u8, target, '?') orelse return .{ .path = target, .query = "" };
+        return .{ .path = target[0..q], .query = target[q + 1 ..] };
+    }
+
+    /// The percent-decoded value of query parameter `name`, allocated
+    /// with `allocator`, or null if it is absent.
+    pub fn param(self: Target, allocator: std.mem.Allocator, name: []const u8) !?[]u8 {
+        var pairs = std.mem.splitScalar(u8, self.query, '&');
+        while (pairs.next()) |pair| {
+            const eq = std.mem.indexOfScalar(u8, pair, '=') orelse pair.len;
+            if (!std.mem.eql(u8, pair[0..eq], name)) continue;
+            const raw = if (eq < pair.len) pair[eq + 1 ..] else "";
+            const buf = try allocator.dupe(u8, raw);
+            defer allocator.free(buf);
+            std.mem.replaceScalar(u8, buf, '+', ' ');
+            return try allocator.dupe(u8, std.Uri.percentDecodeInPlace(buf));
+        }
+        return null;
+    }
+};
+
+/// The endpoints of the server, as counted in its metrics.
+pub const Endpoint = enum {
+    validate,
+    resolve,
+    metrics,
+    other,
+
+    pub fn fromPath(path: []const u8) Endpoint {
+        if (path.len < 2 or path[0] != '/') return .other;
+        return std.meta.stringToEnum(Endpoint, path[1..]) orelse .other;
+    }
+};
+
+/// Requests answered by the server, for `GET /metrics`.
+pub const Stats = struct {
+    allocator: std.mem.Allocator,
+    /// Responses by endpoint and status code.
+    responses: std.AutoArrayHashMapUnmanaged(Response, u64) = .empty,
+    /// Entries looked up for POST /validate and GET /resolve.
+    entries: u64 = 0,
+
+    const Response = struct {
+        endpoint: Endpoint,
+        status: u16,
+    };
+
+    pub fn init(allocator: std.mem.Allocator) Stats {
+        return .{ .allocator = allocator };
+    }
+
+    pub fn deinit(self: *Stats) void {
+        self.responses.deinit(self.allocator);
+    }
+
+    pub fn record(self: *Stats, endpoint: Endpoint, status: std.http.Status) !void {
+        const gop = try self.responses.getOrPut(self.allocator, .{ .endpoint = endpoint, .status = @intFromEnum(status) });
+        if (!gop.found_existing) gop.value_ptr.* = 0;
+        gop.value_ptr.* += 1;
+    }
+};
+
+/// What `GET /metrics` reports besides the server's own counts.
+pub const Upstream = struct {
+    backends: *const metrics.Metrics,
+    cache_hits: u64,
+    cache_misses: u64,
+    /// Time the shared HTTP client spent waiting on upstream rate limits,
+    /// `--request-interval` and retry backoff.
+    waited_ms: u64,
+};
+
+/// Write the server's metrics in the Prometheus text exposition format.
+pub fn writeMetrics(writer: anytype, stats: *const Stats, upstream: Upstream) !void {
+    try writer.writeAll(
+        \\# HELP bibval_http_requests_total Requests answered, by endpoint and status code.
+        \\# TYPE bibval_http_requests_total counter
+        \\
+    );
+    var it = stats.responses.iterator();
+    while (it.next()) |r| {
+        try writer.print("bibval_http_requests_total{{endpoint=\"{s}\",code=\"{d}\"}} {d}\n", .{ @tagName(r.key_ptr.endpoint), r.key_ptr.status, r.value_ptr.* });
+    }
+    try writer.print(
+        \\# HELP bibval_entries_total Entries and DOIs looked up.
+        \\# TYPE bibval_entries_total counter
+        \\bibval_entries_total {d}
+        \\# HELP bibval_cache_hits_total Lookups answered from the response cache.
+        \\# TYPE bibval_cache_hits_total counter
+        \\bibval_cache_hits_total {d}
+        \\# HELP bibval_cache_misses_total Lookups the response cache could not answer.
+        \\# TYPE bibval_cache_misses_total counter
+        \\bibval_cache_misses_total {d}
+        \\# HELP bibval_cache_hit_ratio Share of lookups answered from the response cache.
+        \\# TYPE bibval_cache_hit_ratio gauge
+        \\bibval_cache_hit_ratio {d:.3}
+        \\# HELP bibval_rate_limit_wait_seconds_total Time spent waiting on upstream rate limits and backoff.
+        \\# TYPE bibval_rate_limit_wait_seconds_total counter
+        \\bibval_rate_limit_wait_seconds_total {d}.{d:0>3}
+        \\
+    , .{ stats.entries, upstream.cache_hits, upstream.cache_misses, hitRatio(upstream.cache_hits, upstream.cache_misses), upstream.waited_ms / 1000, upstream.waited_ms % 1000 });
+    try upstream.backends.writePrometheus(writer);
+}
+
+fn hitRatio(hits: u64, misses: u64) f64 {
+    if (hits + misses == 0) return 0;
+    return @as(f64, @floatFromInt(hits)) / @as(f64, @floatFromInt(hits + misses));
+}
+
+/// Input format named by a Content-Type header, if it names one.
+pub fn formatFromContentType(content_type: ?[]const u8) ?input.Format {
+    const value = content_type orelse return null;
+    const semi = std.mem.indexOfScalar(u8, value, ';') orelse value.len;
+    const mime = std.mem.trim(u8, value[0..semi], " ");
+    if (std.ascii.eqlIgnoreCase(mime, "application/x-bibtex") or std.ascii.eqlIgnoreCase(mime, "text/x-bibtex")) return .bibtex;
+    if (std.ascii.eqlIgnoreCase(mime, "application/json") or std.ascii.eqlIgnoreCase(mime, "application/vnd.citationstyles.csl+json")) return .csl_json;
+    if (std.ascii.eqlIgnoreCase(mime, "application/x-research-info-systems")) return .ris;
+    return null;
+}
+
+test "serve helpers" {
+    const allocator = std.testing.allocator;
+
+    const target = Target.parse("/resolve?doi=10.1145%2F3065386&x");
+    try std.testing.expectEqualStrings("/resolve", target.path);
+    const doi = (try target.param(allocator, "doi")).?;
+    defer allocator.free(doi);
+    try std.testing.expectEqualStrings("10.1145/3065386", doi);
+    try std.testing.expect(try target.param(allocator, "title") == null);
+
+    try std.testing.expectEqual(input.Format.csl_json, formatFromContentType("application/json; charset=utf-8").?);
+    try std.testing.expect(formatFromContentType("text/plain") == null);
+
+    var limiter = RateLimiter.init(allocator, 60);
+    defer limiter.deinit();
+    try std.testing.expectEqual(RateLimiter.Decision.allow, try limiter.take(1, 50, 0));
+    try std.testing.expectEqual(RateLimiter.Decision.wait, try limiter.take(1, 20, 0));
+    try std.testing.expectEqual(@as(u64, 10), limiter.retryAfter(1, 20));
+    // Other clients have their own bucket
+    try std.testing.expectEqual(RateLimiter.Decision.allow, try limiter.take(2, 20, 0));
+    // One token per second comes back
+    try std.testing.expectEqual(RateLimiter.Decision.allow, try limiter.take(1, 20, 10_000));
+    try std.testing.expectEqual(RateLimiter.Decision.too_large, try limiter.take(1, 61, 10_000));
+}
+
+test "metrics exposition" {
+    const allocator = std.testing.allocator;
+
+    try std.testing.expectEqual(Endpoint.validate, Endpoint.fromPath("/validate"));
+    try std.testing.expectEqual(Endpoint.other, Endpoint.fromPath("/"));
+    try std.testing.expectEqual(Endpoint.other, Endpoint.fromPath("/other"));
+
+    var stats = Stats.init(allocator);
+    defer stats.deinit();
+    try stats.record(.validate, .ok);
+    try stats.record(.validate, .ok);
+    try stats.record(.resolve, .too_many_requests);
+    stats.entries = 12;
+
+    var backends: metrics.Metrics = .{};
+    backends.event("crossref");
+    backends.request(300, true);
+
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    try writeMetrics(&out.writer, &stats, .{ .backends = &backends, .cache_hits = 3, .cache_misses = 1, .waited_ms = 2500 });
+    const text = out.written();
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_http_requests_total{endpoint=\"validate\",code=\"200\"} 2\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_http_requests_total{endpoint=\"resolve\",code=\"429\"} 1\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_entries_total 12\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_cache_hit_ratio 0.750\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_rate_limit_wait_seconds_total 2.500\n") != null);
+    try std.testing.expect(std.mem.indexOf(u8, text, "\nbibval_backend_requests_total{backend=\"crossref\"} 1\n") != null);
+}
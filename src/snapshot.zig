@@ -0,0 +1,340 @@
+//! Snapshots of matched remote records.
+//!
+//! A snapshot stores every remote record matched during a run, keyed by
+//! citation key. Validating against a committed snapshot instead of the live
+//! APIs gives CI results that do not drift with upstream metadata changes.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const Entry = entry_mod.Entry;
+const ApiSource = entry_mod.ApiSource;
+const Report = report_mod.Report;
+const writeJsonString = report_mod.writeJsonString;
+
+pub const SnapshotError = error{
+    InvalidSnapshot,
+    UnsupportedVersion,
+};
+
+/// Snapshot file format version.
+pub const FORMAT_VERSION: i64 = 1;
+
+/// Snapshot location used when `--out` is not given.
+pub const DEFAULT_PATH = "bibval-snapshot.json";
+
+/// A remote record matched for a local entry.
+pub const Record = struct {
+    source: ApiSource,
+    confidence: f64,
+    entry: Entry,
+};
+
+pub const Snapshot = struct {
+    allocator: std.mem.Allocator,
+    /// Matched records per citation key, in insertion order. A key with no
+    /// records was looked up but not found.
+    records: std.StringArrayHashMapUnmanaged(std.ArrayList(Record)),
+
+    pub fn init(allocator: std.mem.Allocator) Snapshot {
+        return .{
+            .allocator = allocator,
+            .records = .empty,
+        };
+    }
+
+    pub fn deinit(self: *Snapshot) void {
+        for (self.records.keys(), self.records.values()) |key, *list| {
+            for (list.items) |*record| record.entry.deinit();
+            list.deinit(self.allocator);
+            self.allocator.free(key);
+        }
+        self.records.deinit(self.allocator);
+    }
+
+    /// Build a snapshot from the matched entries of a completed run.
+    pub fn fromReport(allocator: std.mem.Allocator, report: *const Report) !Snapshot {
+        var result = Snapshot.init(allocator);
+        errdefer result.deinit();
+
+        for (report.entries.items) |*entry_report| {
+            _ = try result.listFor(entry_report.entry.key);
+            for (entry_report.validation_results) |validation| {
+                const matched = validation.matched_entry orelse continue;
+                var copy = try matched.clone(allocator);
+                errdefer copy.deinit();
+                try result.add(entry_report.entry.key, .{
+                    .source = validation.source,
+                    .confidence = validation.confidence,
+                    .entry = copy,
+                });
+            }
+        }
+
+        return result;
+    }
+
+    /// Add a record for `key`, taking ownership of `record.entry`.
+    pub fn add(self: *Snapshot, key: []const u8, record: Record) !void {
+        const list = try self.listFor(key);
+        try list.append(self.allocator, record);
+    }
+
+    /// Records for `key`, or null if the key is not in the snapshot.
+    pub fn get(self: *const Snapshot, key: []const u8) ?[]const Record {
+        const list = self.records.get(key) orelse return null;
+        return list.items;
+    }
+
+    /// Total number of stored records.
+    pub fn recordCount(self: *const Snapshot) usize {
+        var count: usize = 0;
+        for (self.records.values()) |list| count += list.items.len;
+        return count;
+    }
+
+    fn listFor(self: *Snapshot, key: []const u8) !*std.ArrayList(Record) {
+        if (self.records.getPtr(key)) |list| return list;
+
+        const owned = try self.allocator.dupe(u8, key);
+        errdefer self.allocator.free(owned);
+        try self.records.put(self.allocator, owned, .empty);
+        return self.records.getPtr(owned).?;
+    }
+
+    /// Serialize the snapshot as JSON, one citation key per line.
+    pub fn write(self: *const Snapshot, w: *std.Io.Writer) !void {
+        try w.print("{{\"version\":{d},\"records\":{{", .{FORMAT_VERSION});
+        for (self.records.keys(), self.records.values(), 0..) |key, list, i| {
+            if (i > 0) try w.writeByte(',');
+            try w.writeAll("\n");
+            try writeJsonString(w, key);
+            try w.writeAll(":[");
+            for (list.items, 0..) |*record, j| {
+                if (j > 0) try w.writeByte(',');
+                try w.print("{{\"source\":\"{s}\",\"confidence\":{d},\"entry\":", .{ @tagName(record.source), record.confidence });
+                try writeEntry(w, &record.entry);
+                try w.writeByte('}');
+            }
+            try w.writeByte(']');
+        }
+        try w.writeAll("\n}}\n");
+    }
+
+    /// Write the snapshot to `path`.
+    pub fn save(self: *const Snapshot, dir: std.fs.Dir, path: []const u8) !void {
+        const file = try dir.createFile(path, .{});
+        defer file.close();
+
+        var buf: [4096]u8 = undefined;
+        var file_writer = file.writer(&buf);
+        try self.write(&file_writer.interface);
+        try file_writer.interface.flush();
+    }
+
+    /// Read a snapshot from `path`.
+    pub fn load(allocator: std.mem.Allocator, dir: std.fs.Dir, path: []const u8) !Snapshot {
+        const file = try dir.openFile(path, .{});
+        defer file.close();
+
+        const content = try file.readToEndAlloc(allocator, 100 * 1024 * 1024);
+        defer allocator.free(content);
+
+        return parse(allocator, content);
+    }
+
+    /// Parse a snapshot from its JSON serialization.
+    pub fn parse(allocator: std.mem.Allocator, content: []const u8) !Snapshot {
+        const parsed = std.json.parseFromSlice(std.json.Value, allocator, content, .{}) catch return SnapshotError.InvalidSnapshot;
+        defer parsed.deinit();
+
+        const root = parsed.value;
+        if (root != .object) return SnapshotError.InvalidSnapshot;
+
+        const version = root.object.get("version") orelse return SnapshotError.InvalidSnapshot;
+        if (version != .integer or version.integer != FORMAT_VERSION) return SnapshotError.UnsupportedVersion;
+
+        const records = root.object.get("records") orelse return SnapshotError.InvalidSnapshot;
+        if (records != .object) return SnapshotError.InvalidSnapshot;
+
+        var result = Snapshot.init(allocator);
+        errdefer result.deinit();
+
+        var it = records.object.iterator();
+        while (it.next()) |kv| {
+            const key = kv.key_ptr.*;
+            _ = try result.listFor(key);
+            if (kv.value_ptr.* != .array) continue;
+
+            for (kv.value_ptr.array.items) |item| {
+                if (item != .object) continue;
+                const source = std.meta.stringToEnum(ApiSource, getString(item.object, "source") orelse continue) orelse continue;
+                const confidence: f64 = if (item.object.get("confidence")) |c| switch (c) {
+                    .float => |f| f,
+                    .integer => |n| @as(f64, @floatFromInt(n)),
+                    else => 0.0,
+                } else 0.0;
+
+                var remote = (try parseEntry(allocator, item.object.get("entry") orelse continue)) orelse continue;
+                errdefer remote.deinit();
+                try result.add(key, .{ .source = source, .confidence = confidence, .entry = remote });
+            }
+        }
+
+        return result;
+    }
+};
+
+/// Serialize an entry as a JSON object.
+pub fn writeEntry(w: *std.Io.Writer, e: *const Entry) !void {
+    try w.writeAll("{\"key\":");
+    try writeJsonString(w, e.key);
+    try w.writeAll(",\"entry_type\":");
+    try writeJsonString(w, e.entry_type);
+    try writeOptionalString(w, "title", e.title);
+    try w.writeAll(",\"authors\":[");
+    for (e.authors, 0..) |a, i| {
+        if (i > 0) try w.writeByte(',');
+        try writeJsonString(w, a);
+    }
+    try w.writeByte(']');
+    if (e.year) |y| try w.print(",\"year\":{d}", .{y});
+    try writeOptionalString(w, "venue", e.venue);
+    try writeOptionalString(w, "venue_id", e.venue_id);
+    try writeOptionalString(w, "publisher", e.publisher);
+    try writeOptionalString(w, "doi", e.doi);
+    try writeOptionalString(w, "arxiv_id", e.arxiv_id);
+    try writeOptionalString(w, "scopus_eid", e.scopus_eid);
+    try writeOptionalString(w, "isbn", e.isbn);
+    try writeOptionalString(w, "url", e.url);
+    try writeOptionalString(w, "funding", e.funding);
+    try writeOptionalString(w, "oa_status", e.oa_status);
+    try writeOptionalString(w, "license", e.license);
+    if (e.extra.len > 0) {
+        try w.writeAll(",\"extra\":{");
+        for (e.extra, 0..) |f, i| {
+            if (i > 0) try w.writeByte(',');
+            try writeJsonString(w, f.name);
+            try w.writeByte(':');
+            try writeJsonString(w, f.value);
+        }
+        try w.writeByte('}');
+    }
+    try w.writeByte('}');
+}
+
+/// Parse an entry written by `writeEntry`.
+pub fn parseEntry(allocator: std.mem.Allocator, value: std.json.Value) !?Entry {
+    if (value != .object) return null;
+    const obj = value.object;
+
+    var result = Entry{
+        .key = "",
+        .entry_type = "",
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+
+    result.key = try allocator.dupe(u8, getString(obj, "key") orelse "");
+    result.entry_type = try allocator.dupe(u8, getString(obj, "entry_type") orelse "misc");
+    if (getString(obj, "title")) |t| result.title = try allocator.dupe(u8, t);
+    if (getString(obj, "venue")) |v| result.venue = try allocator.dupe(u8, v);
+    if (getString(obj, "venue_id")) |v| result.venue_id = try allocator.dupe(u8, v);
+    if (getString(obj, "publisher")) |p| result.publisher = try allocator.dupe(u8, p);
+    if (getString(obj, "doi")) |d| result.doi = try allocator.dupe(u8, d);
+    if (getString(obj, "arxiv_id")) |a| result.arxiv_id = try allocator.dupe(u8, a);
+    if (getString(obj, "scopus_eid")) |s| result.scopus_eid = try allocator.dupe(u8, s);
+    if (getString(obj, "isbn")) |i| result.isbn = try allocator.dupe(u8, i);
+    if (getString(obj, "url")) |u| result.url = try allocator.dupe(u8, u);
+    if (getString(obj, "funding")) |f| result.funding = try allocator.dupe(u8, f);
+    if (getString(obj, "oa_status")) |s| result.oa_status = try allocator.dupe(u8, s);
+    if (getString(obj, "license")) |l| result.license = try allocator.dupe(u8, l);
+
+    if (obj.get("year")) |year_val| {
+        if (year_val == .integer) result.year = std.math.cast(i32, year_val.integer);
+    }
+
+    if (obj.get("authors")) |authors_val| {
+        if (authors_val == .array) {
+            var authors: std.ArrayList([]const u8) = .empty;
+            errdefer {
+                for (authors.items) |a| allocator.free(a);
+                authors.deinit(allocator);
+            }
+            for (authors_val.array.items) |a| {
+                if (a == .string) try authors.append(allocator, try allocator.dupe(u8, a.string));
+            }
+            result.authors = try authors.toOwnedSlice(allocator);
+        }
+    }
+
+    if (obj.get("extra")) |extra_val| {
+        if (extra_val == .object) {
+            var extra: std.ArrayList(entry_mod.ExtraField) = .empty;
+            errdefer {
+                for (extra.items) |f| {
+                    allocator.free(f.name);
+                    allocator.free(f.value);
+                }
+                extra.deinit(allocator);
+            }
+            var it = extra_val.object.iterator();
+            while (it.next()) |kv| {
+                if (kv.value_ptr.* != .string) continue;
+                const name = try allocator.dupe(u8, kv.key_ptr.*);
+                errdefer allocator.free(name);
+                const field_value = try allocator.dupe(u8, kv.value_ptr.string);
+                errdefer allocator.free(field_value);
+                try extra.append(allocator, .{ .name = name, .value = field_value });
+            }
+            result.extra = try extra.toOwnedSlice(allocator);
+        }
+    }
+
+    return result;
+}
+
+fn writeOptionalString(w: *std.Io.Writer, name: []const u8, value: ?[]const u8) !void {
+    const v = value orelse return;
+    try w.print(",\"{s}\":", .{name});
+    try writeJsonString(w, v);
+}
+
+fn getString(obj: std.json.ObjectMap, name: []const u8) ?[]const u8 {
+    const value = obj.get(name) orelse return null;
+    return if (value == .string) value.string else null;
+}
+
+test "snapshot round trip" {
+    const allocator = std.testing.allocator;
+
+    var original = Snapshot.init(allocator);
+    defer original.deinit();
+
+    const remote = Entry{
+        .key = "10.1234/example",
+        .entry_type = "journal-article",
+        .title = "A Great Paper",
+        .authors = &.{ "John Smith", "Jane Doe" },
+        .year = 2021,
+        .doi = "10.1234/example",
+    };
+    try original.add("smith2021", .{ .source = .crossref, .confidence = 1.0, .entry = try remote.clone(allocator) });
+    _ = try original.listFor("unmatched2020");
+
+    var aw: std.Io.Writer.Allocating = .init(allocator);
+    defer aw.deinit();
+    try original.write(&aw.writer);
+
+    var restored = try Snapshot.parse(allocator, aw.written());
+    defer restored.deinit();
+
+    const records = restored.get("smith2021").?;
+    try std.testing.expectEqual(@as(usize, 1), records.len);
+    try std.testing.expectEqual(ApiSource.crossref, records[0].source);
+    try std.testing.expectEqualStrings("A Great Paper", records[0].entry.title.?);
+    try std.testing.expectEqual(@as(usize, 2), records[0].entry.authors.len);
+    try std.testing.expectEqual(@as(i32, 2021), records[0].entry.year.?);
+    try std.testing.expectEqual(@as(usize, 0), restored.get("unmatched2020").?.len);
+    try std.testing.expect(restored.get("missing") == null);
+}
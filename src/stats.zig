@@ -0,0 +1,274 @@
+//! Reference list statistics.
+//!
+//! Summarizes a validated bibliography: venue distribution, publication
+//! years, most-cited authors, and how often the manuscript's own authors
+//! are cited. Matched remote records are preferred over local entries since
+//! their metadata is normalized across sources.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const report_mod = @import("report.zig");
+const venues = @import("venues.zig");
+const Entry = entry_mod.Entry;
+const Report = report_mod.Report;
+const EntryReport = report_mod.EntryReport;
+
+/// Number of venues and authors listed by default.
+pub const DEFAULT_TOP: usize = 10;
+
+/// Longest bar in the year histogram.
+const MAX_BAR: usize = 40;
+
+pub const Count = struct {
+    name: []const u8,
+    count: usize,
+};
+
+pub const YearCount = struct {
+    year: i32,
+    count: usize,
+};
+
+pub const Stats = struct {
+    /// Entries analyzed.
+    total: usize = 0,
+    /// Entries with a matched remote record.
+    matched: usize = 0,
+    /// Venues by number of citations, most cited first.
+    venues: []const Count = &.{},
+    /// Entries without a venue.
+    no_venue: usize = 0,
+    /// Citations per year, oldest first.
+    years: []const YearCount = &.{},
+    /// Entries without a year.
+    no_year: usize = 0,
+    /// Authors by number of cited works, most cited first.
+    authors: []const Count = &.{},
+    /// Keys of entries with at least one manuscript author.
+    self_citations: []const []const u8 = &.{},
+
+    arena: std.heap.ArenaAllocator,
+
+    pub fn deinit(self: *Stats) void {
+        self.arena.deinit();
+    }
+
+    /// Share of entries that cite a manuscript author.
+    pub fn selfCitationRate(self: *const Stats) f64 {
+        if (self.total == 0) return 0.0;
+        return @as(f64, @floatFromInt(self.self_citations.len)) / @as(f64, @floatFromInt(self.total));
+    }
+
+    pub fn print(self: *const Stats, writer: anytype, top: usize, manuscript_authors: usize) !void {
+        try writer.writeAll("\nbibval Stats\n");
+        try writer.writeAll("==================================================\n\n");
+        try writer.print("Entries: {d} ({d} matched)\n\n", .{ self.total, self.matched });
+
+        try writer.print("VENUES ({d})\n", .{self.venues.len});
+        for (self.venues[0..@min(top, self.venues.len)]) |v| {
+            try writer.print("  {d:>4}  {d:>3}%  {s}\n", .{ v.count, percent(v.count, self.total), truncate(v.name, 60) });
+        }
+        if (self.venues.len > top) try writer.print("  ... {d} more\n", .{self.venues.len - top});
+        if (self.no_venue > 0) try writer.print("  {d:>4}  {d:>3}%  (no venue)\n", .{ self.no_venue, percent(self.no_venue, self.total) });
+
+        try writer.writeAll("\nYEARS\n");
+        var max_count: usize = 1;
+        for (self.years) |y| max_count = @max(max_count, y.count);
+        for (self.years) |y| {
+            try writer.print("  {d}  {d:>4}  ", .{ y.year, y.count });
+            const width = @max(1, y.count * MAX_BAR / max_count);
+            for (0..width) |_| try writer.writeByte('#');
+            try writer.writeAll("\n");
+        }
+        if (self.no_year > 0) try writer.print("  none  {d:>4}\n", .{self.no_year});
+
+        try writer.writeAll("\nMOST-CITED AUTHORS\n");
+        for (self.authors[0..@min(top, self.authors.len)]) |a| {
+            try writer.print("  {d:>4}  {s}\n", .{ a.count, a.name });
+        }
+
+        if (manuscript_authors > 0) {
+            try writer.print("\nSELF-CITATIONS: {d} of {d} ({d:.1}%)\n", .{
+                self.self_citations.len,
+                self.total,
+                self.selfCitationRate() * 100.0,
+            });
+            for (self.self_citations) |key| try writer.print("  [{s}]\n", .{key});
+        }
+        try writer.writeAll("\n");
+    }
+
+    pub fn writeJson(self: *const Stats, writer: anytype) !void {
+        try writer.print("{{\"total\":{d},\"matched\":{d},\"venues\":[", .{ self.total, self.matched });
+        for (self.venues, 0..) |v, i| {
+            if (i > 0) try writer.writeAll(",");
+            try writer.writeAll("{\"name\":");
+            try report_mod.writeJsonString(writer, v.name);
+            try writer.print(",\"count\":{d}}}", .{v.count});
+        }
+        try writer.writeAll("],\"years\":[");
+        for (self.years, 0..) |y, i| {
+            if (i > 0) try writer.writeAll(",");
+            try writer.print("{{\"year\":{d},\"count\":{d}}}", .{ y.year, y.count });
+        }
+        try writer.writeAll("],\"authors\":[");
+        for (self.authors, 0..) |a, i| {
+            if (i > 0) try writer.writeAll(",");
+            try writer.writeAll("{\"name\":");
+            try report_mod.writeJsonString(writer, a.name);
+            try writer.print(",\"count\":{d}}}", .{a.count});
+        }
+        try writer.writeAll("],\"self_citations\":[");
+        for (self.self_citations, 0..) |key, i| {
+            if (i > 0) try writer.writeAll(",");
+            try report_mod.writeJsonString(writer, key);
+        }
+        try writer.print("],\"self_citation_rate\":{d:.4}}}\n", .{self.selfCitationRate()});
+    }
+};
+
+/// Compute statistics for a report. `manuscript_authors` are the authors of
+/// the citing manuscript, in any name order.
+pub fn compute(allocator: std.mem.Allocator, report: *const Report, manuscript_authors: []const []const u8) !Stats {
+    var stats = Stats{ .arena = std.heap.ArenaAllocator.init(allocator) };
+    errdefer stats.deinit();
+    const arena = stats.arena.allocator();
+
+    var own: std.ArrayList([]const u8) = .empty;
+    for (manuscript_authors) |name| try own.append(arena, try authorKey(arena, name));
+
+    var venue_counts: std.StringArrayHashMapUnmanaged(usize) = .empty;
+    var year_counts: std.AutoArrayHashMapUnmanaged(i32, usize) = .empty;
+    var author_counts: std.StringArrayHashMapUnmanaged(Count) = .empty;
+    var self_citations: std.ArrayList([]const u8) = .empty;
+
+    for (report.entries.items) |*entry_report| {
+        stats.total += 1;
+        const record = bestRecord(entry_report);
+        if (record != &entry_report.entry) stats.matched += 1;
+
+        if (record.venue) |v| {
+            // Count known venues under one name however they are written
+            const name = if (venues.lookup(v)) |known| known.full else v;
+            const gop = try venue_counts.getOrPut(arena, name);
+            gop.value_ptr.* = if (gop.found_existing) gop.value_ptr.* + 1 else 1;
+        } else {
+            stats.no_venue += 1;
+        }
+
+        if (record.year) |y| {
+            const gop = try year_counts.getOrPut(arena, y);
+            gop.value_ptr.* = if (gop.found_existing) gop.value_ptr.* + 1 else 1;
+        } else {
+            stats.no_year += 1;
+        }
+
+        var is_self = false;
+        for (record.authors) |author| {
+            const key = try authorKey(arena, author);
+            if (key.len == 0) continue;
+            const gop = try author_counts.getOrPut(arena, key);
+            if (gop.found_existing) {
+                gop.value_ptr.count += 1;
+            } else {
+                gop.value_ptr.* = .{ .name = author, .count = 1 };
+            }
+            for (own.items) |o| {
+                if (std.mem.eql(u8, o, key)) is_self = true;
+            }
+        }
+        if (is_self) try self_citations.append(arena, entry_report.entry.key);
+    }
+
+    var venue_list: std.ArrayList(Count) = .empty;
+    var venue_it = venue_counts.iterator();
+    while (venue_it.next()) |kv| try venue_list.append(arena, .{ .name = kv.key_ptr.*, .count = kv.value_ptr.* });
+    std.sort.pdq(Count, venue_list.items, {}, moreCited);
+    stats.venues = venue_list.items;
+
+    var year_list: std.ArrayList(YearCount) = .empty;
+    var year_it = year_counts.iterator();
+    while (year_it.next()) |kv| try year_list.append(arena, .{ .year = kv.key_ptr.*, .count = kv.value_ptr.* });
+    std.sort.pdq(YearCount, year_list.items, {}, earlier);
+    stats.years = year_list.items;
+
+    const author_list = try arena.dupe(Count, author_counts.values());
+    std.sort.pdq(Count, author_list, {}, moreCited);
+    stats.authors = author_list;
+
+    stats.self_citations = self_citations.items;
+    return stats;
+}
+
+/// The first matched remote record, or the local entry if none matched.
+fn bestRecord(entry_report: *const EntryReport) *const Entry {
+    for (entry_report.validation_results) |*result| {
+        if (result.matched_entry) |*m| return m;
+    }
+    return &entry_report.entry;
+}
+
+/// Identify an author by last name and first initial, so that "Smith,
+/// John", "John Smith", and "J. Smith" count as the same person.
+fn authorKey(allocator: std.mem.Allocator, name: []const u8) ![]const u8 {
+    var last: []const u8 = name;
+    var first: []const u8 = "";
+    if (std.mem.indexOfScalar(u8, name, ',')) |comma| {
+        last = name[0..comma];
+        first = name[comma + 1 ..];
+    } else if (std.mem.lastIndexOfScalar(u8, std.mem.trimRight(u8, name, " "), ' ')) |space| {
+        first = name[0..space];
+        last = name[space + 1 ..];
+    }
+
+    const last_norm = try entry_mod.normalizeString(allocator, last);
+    defer allocator.free(last_norm);
+    const first_norm = try entry_mod.normalizeString(allocator, first);
+    defer allocator.free(first_norm);
+
+    if (first_norm.len == 0) return allocator.dupe(u8, last_norm);
+    return std.fmt.allocPrint(allocator, "{s} {c}", .{ last_norm, first_norm[0] });
+}
+
+fn moreCited(_: void, a: Count, b: Count) bool {
+    if (a.count != b.count) return a.count > b.count;
+    return std.mem.lessThan(u8, a.name, b.name);
+}
+
+fn earlier(_: void, a: YearCount, b: YearCount) bool {
+    return a.year < b.year;
+}
+
+fn percent(part: usize, whole: usize) usize {
+    return if (whole == 0) 0 else part * 100 / whole;
+}
+
+fn truncate(s: []const u8, max_len: usize) []const u8 {
+    return if (s.len <= max_len) s else s[0..max_len];
+}
+
+test "venue, year, and self-citation counts" {
+    const allocator = std.testing.allocator;
+
+    var report = Report.init(allocator);
+    defer report.deinit();
+    var none = [_]entry_mod.ValidationResult{};
+
+    const jmlr = [_][]const u8{ "Smith, John", "Jane Doe" };
+    const other = [_][]const u8{"A. Turing"};
+    try report.add(.{ .entry = .{ .key = "a", .entry_type = "article", .venue = "JMLR", .year = 2020, .authors = &jmlr }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "b", .entry_type = "article", .venue = "Journal of Machine Learning Research", .year = 2020, .authors = &other }, .status = .not_found, .validation_results = &none });
+    try report.add(.{ .entry = .{ .key = "c", .entry_type = "misc", .year = 2018, .authors = &.{"J. Smith"} }, .status = .not_found, .validation_results = &none });
+
+    var stats = try compute(allocator, &report, &.{"John Smith"});
+    defer stats.deinit();
+
+    try std.testing.expectEqual(@as(usize, 1), stats.venues.len);
+    try std.testing.expectEqual(@as(usize, 2), stats.venues[0].count);
+    try std.testing.expectEqual(@as(usize, 1), stats.no_venue);
+    try std.testing.expectEqual(@as(i32, 2018), stats.years[0].year);
+    try std.testing.expectEqual(@as(usize, 2), stats.years[1].count);
+    try std.testing.expectEqualStrings("Smith, John", stats.authors[0].name);
+    try std.testing.expectEqual(@as(usize, 2), stats.authors[0].count);
+    try std.testing.expectEqual(@as(usize, 2), stats.self_citations.len);
+}
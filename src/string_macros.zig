@@ -0,0 +1,240 @@
+//! `@string` macros for repeated venues.
+//!
+//! A bibliography citing twenty NeurIPS papers spells the venue twenty
+//! times, and fixing it means twenty edits. With `--string-macros`, patch
+//! output defines one `@string` per venue used by several entries and
+//! makes the entries refer to it. Macros the file already defines are
+//! reused, and venue fixes are factored with their new value.
+
+const std = @import("std");
+const bibtex = @import("bibtex.zig");
+const fix = @import("fix.zig");
+const venues = @import("venues.zig");
+const Fix = fix.Fix;
+const Edit = fix.Edit;
+
+/// Entries that must share a venue for it to get a macro of its own.
+pub const MIN_USES: usize = 2;
+
+/// Words left out of generated macro names.
+const minor_words = [_][]const u8{ "a", "an", "the", "of", "on", "in", "for", "and", "&", "de", "der", "und" };
+
+/// BibTeX's predefined month macros, never generated.
+const months = [_][]const u8{ "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec" };
+
+/// Like `fix.editsFor`, with repeated venues (after `fixes`) written as
+/// macro references and the new `@string` definitions inserted before the
+/// first entry. Allocates with `arena`.
+pub fn editsFor(arena: std.mem.Allocator, content: []const u8, fixes: []const Fix) ![]Edit {
+    const entries = try bibtex.parseString(arena, content);
+    const existing = try bibtex.parseMacros(arena, content);
+
+    // The venue each entry ends up with, and the field holding it
+    const Venue = struct { key: []const u8, field: []const u8, value: []const u8 };
+    var planned: std.ArrayList(Venue) = .empty;
+    var uses: std.StringArrayHashMapUnmanaged(usize) = .empty;
+    for (entries) |*e| {
+        const span = bibtex.findEntry(content, e.key) orelse continue;
+        const field = venueField(content, span, e.entry_type) orelse continue;
+        if (bibtex.findField(content, span, field)) |value_span| {
+            // Already a macro reference
+            if (value_span.style == .bare and !isNumber(content[value_span.value.start..value_span.value.end])) continue;
+        }
+        const value = fixedValue(fixes, e.key, field) orelse e.venue orelse continue;
+        if (value.len == 0) continue;
+        try planned.append(arena, .{ .key = e.key, .field = field, .value = value });
+        const gop = try uses.getOrPut(arena, value);
+        gop.value_ptr.* = if (gop.found_existing) gop.value_ptr.* + 1 else 1;
+    }
+
+    var taken: std.StringHashMapUnmanaged(void) = .empty;
+    for (existing) |m| try taken.put(arena, m.name, {});
+    for (months) |month| try taken.put(arena, month, {});
+
+    // Macro name for each factored value; new ones are defined in order
+    var names: std.StringHashMapUnmanaged([]const u8) = .empty;
+    var definitions: std.Io.Writer.Allocating = .init(arena);
+    var it = uses.iterator();
+    while (it.next()) |kv| {
+        const value = kv.key_ptr.*;
+        const defined = for (existing) |m| {
+            if (std.mem.eql(u8, m.value, value)) break m.name;
+        } else null;
+        if (defined) |name| {
+            try names.put(arena, value, name);
+            continue;
+        }
+        if (kv.value_ptr.* < MIN_USES) continue;
+        const name = try macroName(arena, value, &taken);
+        try names.put(arena, value, name);
+        try definitions.writer.print("@string{{{s} = {{{s}}}}}\n", .{ name, value });
+    }
+
+    var rewritten: std.ArrayList(Fix) = .empty;
+    for (fixes) |f| {
+        const replaced = for (planned.items) |p| {
+            if (std.mem.eql(u8, p.key, f.key) and std.mem.eql(u8, p.field, f.field) and names.contains(p.value)) break true;
+        } else false;
+        if (!replaced) try rewritten.append(arena, f);
+    }
+    for (planned.items) |p| {
+        const name = names.get(p.value) orelse continue;
+        try rewritten.append(arena, .{ .key = p.key, .field = p.field, .value = name, .macro = true });
+    }
+
+    const edits = try fix.editsFor(arena, content, rewritten.items);
+    if (definitions.written().len == 0) return edits;
+
+    try definitions.writer.writeAll("\n");
+    const at = firstEntry(content);
+    const all = try arena.alloc(Edit, edits.len + 1);
+    all[0] = .{ .start = at, .end = at, .text = definitions.written() };
+    @memcpy(all[1..], edits);
+    return all;
+}
+
+/// The venue field an entry uses, or the one it would get.
+fn venueField(content: []const u8, span: bibtex.Span, entry_type: []const u8) ?[]const u8 {
+    if (bibtex.findField(content, span, "journal") != null) return "journal";
+    if (bibtex.findField(content, span, "booktitle") != null) return "booktitle";
+    return fix.venueField(entry_type);
+}
+
+fn fixedValue(fixes: []const Fix, key: []const u8, field: []const u8) ?[]const u8 {
+    for (fixes) |f| {
+        if (std.mem.eql(u8, f.key, key) and std.mem.eql(u8, f.field, field)) return f.value;
+    }
+    return null;
+}
+
+/// A short lowercase name: the venue's acronym if it has one ("neurips"),
+/// otherwise the initials of its main words ("jasr" for "Journal of
+/// Applied Statistics Research"), made unique with a number.
+fn macroName(arena: std.mem.Allocator, value: []const u8, taken: *std.StringHashMapUnmanaged(void)) ![]const u8 {
+    var base: std.ArrayList(u8) = .empty;
+    if (venues.lookup(value)) |known| {
+        for (known.aliases) |alias| {
+            if (isWord(alias)) {
+                for (alias) |c| try base.append(arena, std.ascii.toLower(c));
+                break;
+            }
+        }
+    }
+    if (base.items.len == 0) {
+        var words = std.mem.tokenizeAny(u8, value, " \t\r\n-/");
+        while (words.next()) |word| {
+            if (isMinor(word)) continue;
+            for (word) |c| {
+                if (std.ascii.isAlphabetic(c)) {
+                    try base.append(arena, std.ascii.toLower(c));
+                    break;
+                }
+            }
+        }
+    }
+    // A single-word venue ("Nature") is its own name
+    if (base.items.len < 2) {
+        base.clearRetainingCapacity();
+        for (value) |c| {
+            if (std.ascii.isAlphabetic(c) and base.items.len < 12) try base.append(arena, std.ascii.toLower(c));
+        }
+    }
+    if (base.items.len == 0) try base.appendSlice(arena, "venue");
+
+    var name: []const u8 = base.items;
+    var n: usize = 2;
+    while (taken.contains(name)) : (n += 1) {
+        name = try std.fmt.allocPrint(arena, "{s}{d}", .{ base.items, n });
+    }
+    try taken.put(arena, name, {});
+    return name;
+}
+
+/// Offset of the first entry that is not a `@string`, `@preamble`, or
+/// `@comment`; the end of the content if there is none.
+fn firstEntry(content: []const u8) usize {
+    var pos: usize = 0;
+    while (std.mem.indexOfScalarPos(u8, content, pos, '@')) |at| {
+        pos = at + 1;
+        var end = pos;
+        while (end < content.len and std.ascii.isAlphabetic(content[end])) end += 1;
+        const entry_type = content[pos..end];
+        if (entry_type.len == 0) continue;
+        if (std.ascii.eqlIgnoreCase(entry_type, "string") or
+            std.ascii.eqlIgnoreCase(entry_type, "preamble") or
+            std.ascii.eqlIgnoreCase(entry_type, "comment")) continue;
+        return at;
+    }
+    return content.len;
+}
+
+fn isWord(text: []const u8) bool {
+    for (text) |c| {
+        if (!std.ascii.isAlphabetic(c)) return false;
+    }
+    return text.len > 0;
+}
+
+fn isMinor(word: []const u8) bool {
+    for (minor_words) |minor| {
+        if (std.ascii.eqlIgnoreCase(word, minor)) return true;
+    }
+    return false;
+}
+
+fn isNumber(text: []const u8) bool {
+    for (text) |c| {
+        if (!std.ascii.isDigit(c)) return false;
+    }
+    return text.len > 0;
+}
+
+test "repeated venues become macros" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    const bib =
+        \\@string{jasr = {Journal of Applied Statistics Research}}
+        \\
+        \\@inproceedings{a,
+        \\  booktitle = {Advances in Neural Information Processing Systems},
+        \\}
+        \\@inproceedings{b,
+        \\  booktitle = {NIPS},
+        \\}
+        \\@article{c,
+        \\  journal = {Journal of Applied Statistics Research},
+        \\}
+        \\@article{d,
+        \\  journal = jasr,
+        \\}
+        \\
+    ;
+    const fixes = [_]Fix{.{ .key = "b", .field = "booktitle", .value = "Advances in Neural Information Processing Systems" }};
+    const edits = try editsFor(allocator, bib, &fixes);
+    const fixed = try fix.apply(allocator, bib, edits);
+    try std.testing.expectEqualStrings(
+        \\@string{jasr = {Journal of Applied Statistics Research}}
+        \\
+        \\@string{neurips = {Advances in Neural Information Processing Systems}}
+        \\
+        \\@inproceedings{a,
+        \\  booktitle = neurips,
+        \\}
+        \\@inproceedings{b,
+        \\  booktitle = neurips,
+        \\}
+        \\@article{c,
+        \\  journal = jasr,
+        \\}
+        \\@article{d,
+        \\  journal = jasr,
+        \\}
+        \\
+    , fixed);
+
+    // Macro references are read back as the venue they stand for
+    const entries = try bibtex.parseString(allocator, fixed);
+    try std.testing.expectEqualStrings("Journal of Applied Statistics Research", entries[3].venue.?);
+}
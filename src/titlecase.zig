@@ -0,0 +1,222 @@
+//! Title capitalization styles.
+//!
+//! Reference lists mix "Attention Is All You Need" with "Deep residual
+//! learning for image recognition". With `fix.title_style` set, every
+//! matched entry's title is checked against the chosen style and a fix
+//! that only changes letter case is suggested. Whether a capitalized word
+//! is a proper noun ("Bayesian", "Markov") cannot be told from the word
+//! alone, so the matched record's title decides: words it capitalizes in
+//! the middle of a sentence-case title keep their capital.
+//!
+//! Words in braces, LaTeX commands, acronyms, and words with inner
+//! capitals or digits ("BERT", "LeCun", "ResNet-50") are never changed.
+
+const std = @import("std");
+const entry_mod = @import("entry.zig");
+const matcher = @import("matcher.zig");
+const Entry = entry_mod.Entry;
+const Discrepancy = entry_mod.Discrepancy;
+
+pub const Style = enum {
+    /// Titles are not checked.
+    preserve,
+    /// "Deep residual learning for image recognition"
+    sentence,
+    /// "Deep Residual Learning for Image Recognition"
+    title,
+};
+
+/// Articles, conjunctions, and short prepositions, lowercase in title case
+/// unless they start the title or a subtitle.
+const minor_words = [_][]const u8{ "a", "an", "the", "and", "but", "or", "nor", "for", "so", "yet", "as", "at", "by", "in", "of", "off", "on", "per", "to", "up", "via", "vs", "from", "into", "with" };
+
+pub const sentence_case_comparator = matcher.FieldComparator{ .name = "title-style", .compareFn = compareSentenceCase };
+pub const title_case_comparator = matcher.FieldComparator{ .name = "title-style", .compareFn = compareTitleCase };
+
+/// `base` with the title style check for `style` added.
+pub fn withStyle(allocator: std.mem.Allocator, base: []const matcher.FieldComparator, style: Style) ![]const matcher.FieldComparator {
+    const check = switch (style) {
+        .preserve => return base,
+        .sentence => sentence_case_comparator,
+        .title => title_case_comparator,
+    };
+    return std.mem.concat(allocator, matcher.FieldComparator, &.{ base, &.{check} });
+}
+
+fn compareSentenceCase(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    return compareStyle(allocator, local, remote, .sentence);
+}
+
+fn compareTitleCase(_: ?*anyopaque, allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry) std.mem.Allocator.Error!?Discrepancy {
+    return compareStyle(allocator, local, remote, .title);
+}
+
+fn compareStyle(allocator: std.mem.Allocator, local: *const Entry, remote: *const Entry, style: Style) std.mem.Allocator.Error!?Discrepancy {
+    const title = local.title orelse return null;
+    const restyled = try restyle(allocator, title, style, remote.title);
+    if (std.mem.eql(u8, restyled, title)) {
+        allocator.free(restyled);
+        return null;
+    }
+    errdefer allocator.free(restyled);
+
+    return .{
+        .rule = .title_style,
+        .field = .title,
+        .severity = .info,
+        .local_value = try allocator.dupe(u8, title),
+        .remote_value = restyled,
+        .message = try std.fmt.allocPrint(allocator, "Title is not in {s} case", .{@tagName(style)}),
+        .allocator = allocator,
+    };
+}
+
+/// `title` in `style`, changing only letter case. Words capitalized in
+/// the middle of `reference` count as proper nouns.
+pub fn restyle(allocator: std.mem.Allocator, title: []const u8, style: Style, reference: ?[]const u8) ![]u8 {
+    const out = try allocator.dupe(u8, title);
+    if (style == .preserve) return out;
+
+    var proper_buf: [64][]const u8 = undefined;
+    const proper = properNouns(reference orelse "", &proper_buf);
+
+    var words: std.ArrayList([2]usize) = .empty;
+    defer words.deinit(allocator);
+    var start: ?usize = null;
+    var depth: usize = 0;
+    for (title, 0..) |c, i| {
+        switch (c) {
+            '{' => depth += 1,
+            '}' => depth -|= 1,
+            else => {},
+        }
+        // Spaces inside braces do not end a word
+        if (c == ' ' and depth == 0) {
+            if (start) |s| try words.append(allocator, .{ s, i });
+            start = null;
+        } else if (start == null) {
+            start = i;
+        }
+    }
+    if (start) |s| try words.append(allocator, .{ s, title.len });
+
+    var starts_sentence = true;
+    for (words.items, 0..) |span, n| {
+        const word = out[span[0]..span[1]];
+        const last = n + 1 == words.items.len;
+        if (!isProtected(word)) {
+            var parts = std.mem.splitScalar(u8, word, '-');
+            var first_part = true;
+            while (parts.next()) |part| {
+                // The part as a mutable slice of `out`
+                const offset = @intFromPtr(part.ptr) - @intFromPtr(out.ptr);
+                restylePart(out[offset .. offset + part.len], style, proper, starts_sentence and first_part, last);
+                first_part = false;
+            }
+        }
+        const end = std.mem.trimRight(u8, word, "\"')}");
+        starts_sentence = end.len > 0 and std.mem.indexOfScalar(u8, ":.?!", end[end.len - 1]) != null;
+    }
+    return out;
+}
+
+fn restylePart(part: []u8, style: Style, proper: []const []const u8, starts_sentence: bool, last: bool) void {
+    // Leading punctuation such as an opening quote or parenthesis
+    const letters_start = for (part, 0..) |c, i| {
+        if (std.ascii.isAlphanumeric(c) or c >= 0x80) break i;
+    } else return;
+    var letters_end = letters_start;
+    while (letters_end < part.len and (std.ascii.isAlphanumeric(part[letters_end]) or part[letters_end] >= 0x80 or part[letters_end] == '\'')) letters_end += 1;
+    const core = part[letters_start..letters_end];
+    if (!std.ascii.isAlphabetic(core[0]) or keepsCase(core)) return;
+
+    for (proper) |noun| {
+        if (std.ascii.eqlIgnoreCase(noun, core)) {
+            // In sentence case a proper noun keeps its capital
+            if (style == .sentence or !starts_sentence) {
+                @memcpy(core, noun);
+                if (style == .title) core[0] = std.ascii.toUpper(core[0]);
+                return;
+            }
+        }
+    }
+
+    const capitalize = switch (style) {
+        .preserve => return,
+        .sentence => starts_sentence,
+        .title => starts_sentence or last or !isMinor(core),
+    };
+    core[0] = if (capitalize) std.ascii.toUpper(core[0]) else std.ascii.toLower(core[0]);
+}
+
+/// Capitals after the first letter, digits, or a single capital other than
+/// "A": acronyms, product names, and numerals keep their case.
+fn keepsCase(core: []const u8) bool {
+    for (core[1..]) |c| {
+        if (std.ascii.isUpper(c) or std.ascii.isDigit(c)) return true;
+    }
+    return core.len == 1 and std.ascii.isUpper(core[0]) and core[0] != 'A';
+}
+
+/// Braced for BibTeX, or containing a LaTeX command or math.
+fn isProtected(word: []const u8) bool {
+    return std.mem.indexOfAny(u8, word, "{}\\$") != null;
+}
+
+fn isMinor(core: []const u8) bool {
+    for (minor_words) |minor| {
+        if (std.ascii.eqlIgnoreCase(core, minor)) return true;
+    }
+    return false;
+}
+
+/// Words capitalized in the middle of `reference`, unless it is itself in
+/// title case and so says nothing about proper nouns.
+fn properNouns(reference: []const u8, buf: *[64][]const u8) []const []const u8 {
+    var capitalized: usize = 0;
+    var lowercase: usize = 0;
+    var count: usize = 0;
+    var starts_sentence = true;
+    var words = std.mem.tokenizeScalar(u8, reference, ' ');
+    while (words.next()) |word| {
+        const core = std.mem.trim(u8, word, "\"'()[]{},.:;?!");
+        const mid_sentence = !starts_sentence;
+        starts_sentence = word.len > 0 and std.mem.indexOfScalar(u8, ":.?!", word[word.len - 1]) != null;
+        if (core.len == 0 or !std.ascii.isAlphabetic(core[0]) or !mid_sentence) continue;
+        if (std.ascii.isUpper(core[0])) {
+            capitalized += 1;
+            if (count < buf.len) {
+                buf[count] = core;
+                count += 1;
+            }
+        } else if (!isMinor(core)) {
+            lowercase += 1;
+        }
+    }
+    if (capitalized > lowercase) return &.{};
+    return buf[0..count];
+}
+
+test "title styles" {
+    const allocator = std.testing.allocator;
+    const cases = [_]struct { title: []const u8, style: Style, reference: ?[]const u8, expected: []const u8 }{
+        .{ .title = "Deep Residual Learning for Image Recognition", .style = .sentence, .reference = null, .expected = "Deep residual learning for image recognition" },
+        .{ .title = "deep residual learning for image recognition", .style = .title, .reference = null, .expected = "Deep Residual Learning for Image Recognition" },
+        .{ .title = "BERT: Pre-Training of Deep Bidirectional Transformers", .style = .sentence, .reference = null, .expected = "BERT: Pre-training of deep bidirectional transformers" },
+        .{ .title = "A Bayesian Approach to Markov Models", .style = .sentence, .reference = "A Bayesian approach to Markov models", .expected = "A Bayesian approach to Markov models" },
+        .{ .title = "The {ImageNet} challenge: on the state of the art", .style = .title, .reference = null, .expected = "The {ImageNet} Challenge: On the State of the Art" },
+        .{ .title = "Training ResNet-50 in one hour", .style = .title, .reference = null, .expected = "Training ResNet-50 in One Hour" },
+    };
+    for (cases) |case| {
+        const restyled = try restyle(allocator, case.title, case.style, case.reference);
+        defer allocator.free(restyled);
+        try std.testing.expectEqualStrings(case.expected, restyled);
+    }
+
+    const local = Entry{ .key = "he2016", .entry_type = "inproceedings", .title = "Deep Residual Learning for Image Recognition" };
+    const remote = Entry{ .key = "r", .entry_type = "inproceedings", .title = "Deep Residual Learning for Image Recognition" };
+    var found = (try sentence_case_comparator.compare(allocator, &local, &remote)).?;
+    defer found.deinit();
+    try std.testing.expectEqual(@import("rules.zig").Rule.title_style, found.rule);
+    try std.testing.expect(try title_case_comparator.compare(allocator, &local, &remote) == null);
+}
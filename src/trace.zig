@@ -0,0 +1,172 @@
+//! Structured tracing for the validation pipeline.
+//!
+//! Events go to stderr as plain lines (`-v`, `-vv`) or as one JSON object
+//! per line (`--log-json`). Every event carries the key of the entry being
+//! validated, so the lookups behind a single bad match can be followed.
+
+const std = @import("std");
+const report = @import("report.zig");
+const Progress = @import("progress.zig").Progress;
+const Metrics = @import("metrics.zig").Metrics;
+const ApiSource = @import("entry.zig").ApiSource;
+
+/// Verbosity levels, selected with repeated `-v` flags.
+pub const Level = enum(u8) {
+    off = 0,
+    /// Per-entry and per-validator events (`-v`).
+    info = 1,
+    /// Individual HTTP requests and responses (`-vv`).
+    debug = 2,
+
+    pub fn name(self: Level) []const u8 {
+        return switch (self) {
+            .off => "off",
+            .info => "info",
+            .debug => "debug",
+        };
+    }
+};
+
+/// How a backend's lookup ended, for events that report it.
+pub const Outcome = enum {
+    failed,
+};
+
+/// What an event marks, for the progress line.
+const Kind = enum {
+    note,
+    lookup,
+    failed,
+};
+
+/// Tracer shared by the pipeline and the HTTP client.
+pub const Tracer = struct {
+    level: Level = .off,
+    json: bool = false,
+    /// Key of the entry currently being validated.
+    entry_key: ?[]const u8 = null,
+    /// Progress line fed with every event, whatever the level.
+    progress: ?*Progress = null,
+    /// Backend metrics, told which backend each lookup is for.
+    metrics: ?*Metrics = null,
+    /// Where events are written; stderr when null.
+    out: ?*std.Io.Writer = null,
+
+    pub fn enabled(self: *const Tracer, level: Level) bool {
+        return level != .off and @intFromEnum(level) <= @intFromEnum(self.level);
+    }
+
+    /// Emit an event if `level` is enabled.
+    pub fn event(self: *const Tracer, level: Level, scope: []const u8, comptime fmt: []const u8, args: anytype) void {
+        self.emit(level, scope, .note, fmt, args);
+    }
+
+    /// Emit the start of a backend's lookup, counted on the progress line.
+    /// Other events of the backend, such as how well its record matched,
+    /// are emitted with `event`.
+    pub fn lookup(self: *const Tracer, scope: []const u8, comptime fmt: []const u8, args: anytype) void {
+        self.emit(.info, scope, .lookup, fmt, args);
+    }
+
+    /// Emit the failure of a backend's lookup with `err`.
+    pub fn lookupFailed(self: *const Tracer, scope: []const u8, err: anyerror) void {
+        self.emit(.info, scope, .failed, "lookup failed: {s}", .{@errorName(err)});
+    }
+
+    fn emit(self: *const Tracer, level: Level, scope: []const u8, kind: Kind, comptime fmt: []const u8, args: anytype) void {
+        if (self.progress) |p| {
+            if (kind == .lookup) p.lookup(scope) else p.event(scope);
+        }
+        if (self.metrics) |m| m.event(scope);
+        if (!self.enabled(level)) return;
+
+        // Messages carry whole URLs and response bodies, so nothing is cut
+        // to fit a buffer
+        const allocator = std.heap.page_allocator;
+        const msg = std.fmt.allocPrint(allocator, fmt, args) catch return;
+        defer allocator.free(msg);
+
+        var line: std.Io.Writer.Allocating = .init(allocator);
+        defer line.deinit();
+        self.writeEvent(&line.writer, level, scope, if (kind == .failed) .failed else null, msg) catch return;
+        if (self.out) |out| {
+            out.writeAll(line.written()) catch {};
+        } else {
+            std.debug.print("{s}", .{line.written()});
+        }
+    }
+
+    fn writeEvent(self: *const Tracer, w: *std.Io.Writer, level: Level, scope: []const u8, outcome: ?Outcome, msg: []const u8) !void {
+        if (self.json) {
+            try self.writeJsonEvent(w, level, scope, outcome, msg);
+            try w.writeByte('\n');
+        } else if (self.entry_key) |key| {
+            try w.print("  [{s}] {s}: {s}\n", .{ key, scope, msg });
+        } else {
+            try w.print("  {s}: {s}\n", .{ scope, msg });
+        }
+    }
+
+    fn writeJsonEvent(self: *const Tracer, w: *std.Io.Writer, level: Level, scope: []const u8, outcome: ?Outcome, msg: []const u8) !void {
+        try w.print("{{\"ts\":{d},\"level\":\"{s}\",\"scope\":", .{ std.time.milliTimestamp(), level.name() });
+        try report.writeJsonString(w, scope);
+        try w.writeAll(",\"entry\":");
+        if (self.entry_key) |key| {
+            try report.writeJsonString(w, key);
+        } else {
+            try w.writeAll("null");
+        }
+        if (outcome) |o| try w.print(",\"outcome\":\"{s}\"", .{@tagName(o)});
+        try w.writeAll(",\"msg\":");
+        try report.writeJsonString(w, msg);
+        try w.writeByte('}');
+    }
+};
+
+test "Tracer level gating" {
+    const quiet = Tracer{};
+    try std.testing.expect(!quiet.enabled(.info));
+
+    const verbose = Tracer{ .level = .info };
+    try std.testing.expect(verbose.enabled(.info));
+    try std.testing.expect(!verbose.enabled(.debug));
+
+    const very_verbose = Tracer{ .level = .debug };
+    try std.testing.expect(very_verbose.enabled(.debug));
+}
+
+test "JSON events are written whole" {
+    const allocator = std.testing.allocator;
+    const tracer = Tracer{ .level = .debug, .json = true, .entry_key = "smith2020" };
+
+    const msg = try allocator.alloc(u8, 4096);
+    defer allocator.free(msg);
+    @memset(msg, 'x');
+
+    var line: std.Io.Writer.Allocating = .init(allocator);
+    defer line.deinit();
+    try tracer.writeJsonEvent(&line.writer, .debug, "http", null, msg);
+
+    const parsed = try std.json.parseFromSlice(std.json.Value, allocator, line.written(), .{});
+    defer parsed.deinit();
+    try std.testing.expectEqual(@as(usize, 4096), parsed.value.object.get("msg").?.string.len);
+}
+
+test "only the starts of lookups count as lookups" {
+    var progress = Progress.init(1, 120);
+    progress.last_draw_ms = std.math.maxInt(i64);
+    const tracer = Tracer{ .progress = &progress };
+
+    // A DOI matched on the first try
+    tracer.lookup("crossref", "DOI lookup {s}", .{"10.1234/x"});
+    tracer.event(.info, "crossref", "identifier record title similarity {d:.2}", .{0.98});
+    try std.testing.expectEqual(@as(usize, 1), progress.lookups.get(.crossref));
+
+    tracer.lookup("web", "page {s}", .{"http://localhost/"});
+    tracer.event(.debug, "web", "{s} is not a public address", .{"http://localhost/"});
+    tracer.lookup("openalex", "title search", .{});
+    tracer.lookupFailed("openalex", error.Timeout);
+    try std.testing.expectEqual(@as(usize, 1), progress.lookups.get(.web));
+    try std.testing.expectEqual(@as(usize, 1), progress.lookups.get(.openalex));
+    try std.testing.expectEqual(ApiSource.openalex, progress.active.?);
+}
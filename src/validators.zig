@@ -3,6 +3,7 @@
 const std = @import("std");
 const http = @import("http.zig");
 const cache = @import("cache.zig");
+const markup = @import("markup.zig");
 const entry_mod = @import("entry.zig");
 const Entry = entry_mod.Entry;
 const ApiSource = entry_mod.ApiSource;
@@ -18,19 +19,20 @@ pub const ValidatorError = error{
     InvalidUrl,
 };
 
-const USER_AGENT = "bibval/0.1.0 (https://github.com/evil-mind-evil-sword/bibval)";
+pub const USER_AGENT = "bibval/0.1.0 (https://github.com/evil-mind-evil-sword/bibval)";
 
 pub const CrossRef = struct {
     allocator: std.mem.Allocator,
-    client: http.Client,
+    client: *http.Client,
     response_cache: *cache.Cache,
 
     const BASE_URL = "https://api.crossref.org/works";
+    const FUNDERS_URL = "https://api.crossref.org/funders";
 
-    pub fn init(allocator: std.mem.Allocator, response_cache: *cache.Cache) CrossRef {
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) CrossRef {
         return .{
             .allocator = allocator,
-            .client = http.Client.init(allocator, USER_AGENT),
+            .client = client,
             .response_cache = response_cache,
         };
     }
@@ -75,6 +77,67 @@ pub const CrossRef = struct {
         return try parseSearchResults(self.allocator, body);
     }
 
+    /// Search the Crossref Funder Registry and return the primary and
+    /// alternative names of each matching funder. Caller owns the result.
+    pub fn searchFunders(self: *CrossRef, name: []const u8) ![][]u8 {
+        if (self.response_cache.get("crossref_funder", name)) |cached| {
+            defer self.allocator.free(cached);
+            return try parseFunderNames(self.allocator, cached);
+        }
+
+        const encoded = try http.urlEncode(self.allocator, name);
+        defer self.allocator.free(encoded);
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?query={s}&rows=5", .{ FUNDERS_URL, encoded });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        self.response_cache.set("crossref_funder", name, body) catch {};
+        return try parseFunderNames(self.allocator, body);
+    }
+
+    fn parseFunderNames(allocator: std.mem.Allocator, json_body: []const u8) ![][]u8 {
+        const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
+        defer parsed.deinit();
+
+        const root = parsed.value;
+        if (root != .object) return &.{};
+
+        const message = root.object.get("message") orelse return &.{};
+        if (message != .object) return &.{};
+
+        const items = message.object.get("items") orelse return &.{};
+        if (items != .array) return &.{};
+
+        var names: std.ArrayList([]u8) = .empty;
+        errdefer {
+            for (names.items) |n| allocator.free(n);
+            names.deinit(allocator);
+        }
+
+        for (items.array.items) |item| {
+            if (item != .object) continue;
+            if (item.object.get("name")) |name_val| {
+                if (name_val == .string) try names.append(allocator, try allocator.dupe(u8, name_val.string));
+            }
+            if (item.object.get("alt-names")) |alt_val| {
+                if (alt_val != .array) continue;
+                for (alt_val.array.items) |alt| {
+                    if (alt == .string) try names.append(allocator, try allocator.dupe(u8, alt.string));
+                }
+            }
+        }
+
+        return names.toOwnedSlice(allocator);
+    }
+
     fn parseWork(allocator: std.mem.Allocator, json_body: []const u8) !?Entry {
         const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return null;
         defer parsed.deinit();
@@ -138,7 +201,7 @@ pub const CrossRef = struct {
         if (work.get("title")) |title_val| {
             if (title_val == .array and title_val.array.items.len > 0) {
                 if (title_val.array.items[0] == .string) {
-                    result.title = try allocator.dupe(u8, title_val.array.items[0].string);
+                    result.title = try markup.plainText(allocator, title_val.array.items[0].string);
                 }
             }
         }
@@ -163,36 +226,679 @@ pub const CrossRef = struct {
         if (work.get("container-title")) |venue_val| {
             if (venue_val == .array and venue_val.array.items.len > 0) {
                 if (venue_val.array.items[0] == .string) {
-                    result.venue = try allocator.dupe(u8, venue_val.array.items[0].string);
+                    result.venue = try markup.plainText(allocator, venue_val.array.items[0].string);
+                }
+            }
+        }
+
+        if (work.get("publisher")) |publisher_val| {
+            if (publisher_val == .string) {
+                result.publisher = try markup.plainText(allocator, publisher_val.string);
+            }
+        }
+
+        const date_fields = [_][]const u8{ "published", "published-print", "published-online" };
+        for (date_fields) |field_name| {
+            if (work.get(field_name)) |date_val| {
+                if (date_val == .object) {
+                    if (date_val.object.get("date-parts")) |parts| {
+                        if (parts == .array and parts.array.items.len > 0) {
+                            if (parts.array.items[0] == .array and parts.array.items[0].array.items.len > 0) {
+                                if (parts.array.items[0].array.items[0] == .integer) {
+                                    result.year = @intCast(parts.array.items[0].array.items[0].integer);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if (work.get("type")) |type_val| {
+            if (type_val == .string) {
+                const new_type = try allocator.dupe(u8, type_val.string);
+                allocator.free(result.entry_type);
+                result.entry_type = new_type;
+            }
+        }
+
+        // Funders as "Name (award, award); Name"
+        if (work.get("funder")) |funder_val| {
+            if (funder_val == .array and funder_val.array.items.len > 0) {
+                var funding: std.ArrayList(u8) = .empty;
+                defer funding.deinit(allocator);
+                for (funder_val.array.items) |funder| {
+                    if (funder != .object) continue;
+                    const name_val = funder.object.get("name") orelse continue;
+                    if (name_val != .string) continue;
+
+                    if (funding.items.len > 0) try funding.appendSlice(allocator, "; ");
+                    try funding.appendSlice(allocator, name_val.string);
+
+                    const awards = funder.object.get("award") orelse continue;
+                    if (awards != .array or awards.array.items.len == 0) continue;
+                    try funding.appendSlice(allocator, " (");
+                    for (awards.array.items, 0..) |award, i| {
+                        if (award != .string) continue;
+                        if (i > 0) try funding.appendSlice(allocator, ", ");
+                        try funding.appendSlice(allocator, award.string);
+                    }
+                    try funding.append(allocator, ')');
+                }
+                if (funding.items.len > 0) result.funding = try funding.toOwnedSlice(allocator);
+            }
+        }
+
+        const errata = try correctionDois(allocator, work);
+        defer allocator.free(errata);
+        const updates = try updateNotices(allocator, work);
+        defer allocator.free(updates);
+
+        // Translated works give the title in the original language too
+        var original_title: []const u8 = "";
+        defer allocator.free(original_title);
+        if (work.get("original-title")) |original_val| {
+            if (original_val == .array and original_val.array.items.len > 0 and original_val.array.items[0] == .string) {
+                original_title = try markup.plainText(allocator, original_val.array.items[0].string);
+            }
+        }
+
+        // ACM gives article numbers instead of pages for many papers
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "volume", .value = stringField(work, "volume") },
+            .{ .name = "number", .value = stringField(work, "issue") },
+            .{ .name = "pages", .value = stringField(work, "page") },
+            .{ .name = "articleno", .value = stringField(work, "article-number") },
+            .{ .name = "edition", .value = stringField(work, "edition-number") },
+            .{ .name = "erratum", .value = errata },
+            .{ .name = "updates", .value = updates },
+            .{ .name = "language", .value = stringField(work, "language") },
+            .{ .name = "origtitle", .value = original_title },
+        });
+
+        return result;
+    }
+
+    /// DOIs of the corrections of a work, comma-separated: its
+    /// `is-corrected-by` and `has-erratum` relations and the correction and
+    /// erratum notices in `updated-by`.
+    fn correctionDois(allocator: std.mem.Allocator, work: std.json.ObjectMap) ![]u8 {
+        var list: std.ArrayList(u8) = .empty;
+        errdefer list.deinit(allocator);
+
+        if (work.get("relation")) |relation| {
+            if (relation == .object) {
+                for ([_][]const u8{ "is-corrected-by", "has-erratum" }) |kind| {
+                    const related = relation.object.get(kind) orelse continue;
+                    if (related != .array) continue;
+                    for (related.array.items) |item| {
+                        if (item != .object) continue;
+                        if (!std.ascii.eqlIgnoreCase(stringField(item.object, "id-type"), "doi")) continue;
+                        try appendListed(allocator, &list, stringField(item.object, "id"));
+                    }
+                }
+            }
+        }
+
+        if (work.get("updated-by")) |updates| {
+            if (updates == .array) {
+                for (updates.array.items) |update| {
+                    if (update != .object) continue;
+                    if (!isCorrection(stringField(update.object, "type"))) continue;
+                    try appendListed(allocator, &list, stringField(update.object, "DOI"));
+                }
+            }
+        }
+        return list.toOwnedSlice(allocator);
+    }
+
+    /// The other Crossmark notices in `updated-by`, as "type DOI" pairs:
+    /// "new_version 10.1/abc, retraction 10.1/def".
+    fn updateNotices(allocator: std.mem.Allocator, work: std.json.ObjectMap) ![]u8 {
+        var list: std.ArrayList(u8) = .empty;
+        errdefer list.deinit(allocator);
+
+        if (work.get("updated-by")) |updates| {
+            if (updates == .array) {
+                for (updates.array.items) |update| {
+                    if (update != .object) continue;
+                    const kind = stringField(update.object, "type");
+                    const doi = stringField(update.object, "DOI");
+                    if (kind.len == 0 or doi.len == 0 or isCorrection(kind)) continue;
+                    if (list.items.len > 0) try list.appendSlice(allocator, ", ");
+                    try list.print(allocator, "{s} {s}", .{ kind, doi });
+                }
+            }
+        }
+        return list.toOwnedSlice(allocator);
+    }
+
+    fn isCorrection(kind: []const u8) bool {
+        for ([_][]const u8{ "correction", "erratum", "corrigendum" }) |name| {
+            if (std.mem.eql(u8, kind, name)) return true;
+        }
+        return false;
+    }
+
+    fn appendListed(allocator: std.mem.Allocator, list: *std.ArrayList(u8), doi: []const u8) !void {
+        if (doi.len == 0 or std.ascii.indexOfIgnoreCase(list.items, doi) != null) return;
+        if (list.items.len > 0) try list.appendSlice(allocator, ", ");
+        try list.appendSlice(allocator, doi);
+    }
+};
+
+/// A string member of a JSON object, or "" if it is absent.
+fn stringField(obj: std.json.ObjectMap, name: []const u8) []const u8 {
+    const value = obj.get(name) orelse return "";
+    return if (value == .string) value.string else "";
+}
+
+/// Owned copies of the fields with a non-empty value.
+pub fn extraFields(allocator: std.mem.Allocator, fields: []const entry_mod.ExtraField) ![]const entry_mod.ExtraField {
+    var list: std.ArrayList(entry_mod.ExtraField) = .empty;
+    errdefer {
+        for (list.items) |f| {
+            allocator.free(f.name);
+            allocator.free(f.value);
+        }
+        list.deinit(allocator);
+    }
+    for (fields) |f| {
+        if (f.value.len == 0) continue;
+        const name = try allocator.dupe(u8, f.name);
+        errdefer allocator.free(name);
+        const value = try allocator.dupe(u8, f.value);
+        errdefer allocator.free(value);
+        try list.append(allocator, .{ .name = name, .value = value });
+    }
+    if (list.items.len == 0) return &.{};
+    return list.toOwnedSlice(allocator);
+}
+
+/// ACM Digital Library records, found through Crossref by ACM's member ID.
+pub const Acm = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+
+    const BASE_URL = "https://api.crossref.org/works";
+    /// Crossref member ID of the Association for Computing Machinery.
+    const MEMBER_ID = 320;
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) Acm {
+        return .{
+            .allocator = allocator,
+            .client = client,
+        };
+    }
+
+    pub fn searchByTitle(self: *Acm, title: []const u8) ![]Entry {
+        const encoded = try http.urlEncode(self.allocator, title);
+        defer self.allocator.free(encoded);
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?query.title={s}&filter=member:{d}&rows=5", .{ BASE_URL, encoded, MEMBER_ID });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        return try CrossRef.parseSearchResults(self.allocator, body);
+    }
+};
+
+/// IEEE Xplore Metadata API; needs an API key.
+pub const Ieee = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    api_key: []const u8,
+
+    const BASE_URL = "https://ieeexploreapi.ieee.org/api/v1/search/articles";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache, api_key: []const u8) Ieee {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+            .api_key = api_key,
+        };
+    }
+
+    /// Whether IEEE is the registrant of a DOI.
+    pub fn isIeeeDoi(doi: []const u8) bool {
+        return std.mem.startsWith(u8, doi, "10.1109/");
+    }
+
+    pub fn searchByDoi(self: *Ieee, doi: []const u8) !?Entry {
+        if (self.response_cache.get("ieee_doi", doi)) |cached| {
+            defer self.allocator.free(cached);
+            return try firstArticle(self.allocator, cached);
+        }
+
+        const encoded = try http.urlEncode(self.allocator, doi);
+        defer self.allocator.free(encoded);
+        const body = try self.get("doi", encoded);
+        defer self.allocator.free(body);
+
+        self.response_cache.set("ieee_doi", doi, body) catch {};
+        return try firstArticle(self.allocator, body);
+    }
+
+    pub fn searchByTitle(self: *Ieee, title: []const u8) ![]Entry {
+        const encoded = try http.urlEncode(self.allocator, title);
+        defer self.allocator.free(encoded);
+        const body = try self.get("article_title", encoded);
+        defer self.allocator.free(body);
+        return try parseArticles(self.allocator, body);
+    }
+
+    fn get(self: *Ieee, param: []const u8, value: []const u8) ![]u8 {
+        // Request traces show the URL with the key's value cut out
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?{s}={s}&format=json&max_records=5&apikey={s}", .{ BASE_URL, param, value, self.api_key });
+        defer self.allocator.free(url);
+
+        return self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+    }
+
+    fn firstArticle(allocator: std.mem.Allocator, json_body: []const u8) !?Entry {
+        const entries = try parseArticles(allocator, json_body);
+        defer allocator.free(entries);
+        if (entries.len == 0) return null;
+        for (entries[1..]) |*e| e.deinit();
+        return entries[0];
+    }
+
+    pub fn parseArticles(allocator: std.mem.Allocator, json_body: []const u8) ![]Entry {
+        const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
+        defer parsed.deinit();
+
+        const root = parsed.value;
+        if (root != .object) return &.{};
+        const articles = root.object.get("articles") orelse return &.{};
+        if (articles != .array) return &.{};
+
+        var entries: std.ArrayList(Entry) = .empty;
+        errdefer {
+            for (entries.items) |*e| e.deinit();
+            entries.deinit(allocator);
+        }
+
+        for (articles.array.items) |article| {
+            if (article != .object) continue;
+            if (articleToEntry(allocator, article.object)) |e| {
+                try entries.append(allocator, e);
+            } else |_| {}
+        }
+
+        return entries.toOwnedSlice(allocator);
+    }
+
+    fn articleToEntry(allocator: std.mem.Allocator, article: std.json.ObjectMap) !Entry {
+        const conference = std.mem.indexOf(u8, stringField(article, "content_type"), "Conference") != null;
+        var result = Entry{
+            .key = "",
+            .entry_type = try allocator.dupe(u8, if (conference) "inproceedings" else "article"),
+            .allocator = allocator,
+        };
+        errdefer result.deinit();
+
+        result.key = try allocator.dupe(u8, stringField(article, "article_number"));
+        if (stringField(article, "title").len > 0) result.title = try markup.plainText(allocator, stringField(article, "title"));
+        if (stringField(article, "doi").len > 0) result.doi = try allocator.dupe(u8, stringField(article, "doi"));
+        if (stringField(article, "publication_title").len > 0) result.venue = try markup.plainText(allocator, stringField(article, "publication_title"));
+        if (stringField(article, "publisher").len > 0) result.publisher = try markup.plainText(allocator, stringField(article, "publisher"));
+
+        if (article.get("publication_year")) |year_val| {
+            switch (year_val) {
+                .integer => |y| result.year = std.math.cast(i32, y),
+                .string => |s| result.year = std.fmt.parseInt(i32, s, 10) catch null,
+                else => {},
+            }
+        }
+
+        if (article.get("authors")) |authors_obj| {
+            if (authors_obj == .object) {
+                if (authors_obj.object.get("authors")) |list| {
+                    if (list == .array) {
+                        var authors: std.ArrayList([]const u8) = .empty;
+                        defer authors.deinit(allocator);
+                        errdefer for (authors.items) |a| allocator.free(a);
+                        for (list.array.items) |author| {
+                            if (author != .object) continue;
+                            const name = stringField(author.object, "full_name");
+                            if (name.len > 0) try authors.append(allocator, try allocator.dupe(u8, name));
+                        }
+                        result.authors = try authors.toOwnedSlice(allocator);
+                    }
+                }
+            }
+        }
+
+        const start_page = stringField(article, "start_page");
+        const end_page = stringField(article, "end_page");
+        const pages = if (start_page.len > 0 and end_page.len > 0 and !std.mem.eql(u8, start_page, end_page))
+            try std.fmt.allocPrint(allocator, "{s}--{s}", .{ start_page, end_page })
+        else
+            try allocator.dupe(u8, start_page);
+        defer allocator.free(pages);
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "volume", .value = stringField(article, "volume") },
+            .{ .name = "number", .value = stringField(article, "issue") },
+            .{ .name = "pages", .value = pages },
+        });
+
+        return result;
+    }
+};
+
+/// Springer Nature Meta API; needs an API key.
+pub const Springer = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    api_key: []const u8,
+
+    const BASE_URL = "https://api.springernature.com/meta/v2/json";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache, api_key: []const u8) Springer {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+            .api_key = api_key,
+        };
+    }
+
+    /// Whether a DOI was registered by Springer, Nature, or BMC.
+    pub fn isSpringerDoi(doi: []const u8) bool {
+        for ([_][]const u8{ "10.1007/", "10.1038/", "10.1186/" }) |prefix| {
+            if (std.mem.startsWith(u8, doi, prefix)) return true;
+        }
+        return false;
+    }
+
+    pub fn searchByDoi(self: *Springer, doi: []const u8) !?Entry {
+        if (self.response_cache.get("springer_doi", doi)) |cached| {
+            defer self.allocator.free(cached);
+            return try firstRecord(self.allocator, cached);
+        }
+
+        const query = try std.fmt.allocPrint(self.allocator, "doi:{s}", .{doi});
+        defer self.allocator.free(query);
+        const body = try self.get(query);
+        defer self.allocator.free(body);
+
+        self.response_cache.set("springer_doi", doi, body) catch {};
+        return try firstRecord(self.allocator, body);
+    }
+
+    pub fn searchByTitle(self: *Springer, title: []const u8) ![]Entry {
+        // Quotes would make the search exact, which typos defeat
+        const query = try std.fmt.allocPrint(self.allocator, "title:{s}", .{title});
+        defer self.allocator.free(query);
+        const body = try self.get(query);
+        defer self.allocator.free(body);
+        return try parseRecords(self.allocator, body);
+    }
+
+    fn get(self: *Springer, query: []const u8) ![]u8 {
+        const encoded = try http.urlEncode(self.allocator, query);
+        defer self.allocator.free(encoded);
+        // Request traces show the URL with the key's value cut out
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?q={s}&p=5&api_key={s}", .{ BASE_URL, encoded, self.api_key });
+        defer self.allocator.free(url);
+
+        return self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+    }
+
+    fn firstRecord(allocator: std.mem.Allocator, json_body: []const u8) !?Entry {
+        const entries = try parseRecords(allocator, json_body);
+        defer allocator.free(entries);
+        if (entries.len == 0) return null;
+        for (entries[1..]) |*e| e.deinit();
+        return entries[0];
+    }
+
+    pub fn parseRecords(allocator: std.mem.Allocator, json_body: []const u8) ![]Entry {
+        const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
+        defer parsed.deinit();
+
+        const root = parsed.value;
+        if (root != .object) return &.{};
+        const records = root.object.get("records") orelse return &.{};
+        if (records != .array) return &.{};
+
+        var entries: std.ArrayList(Entry) = .empty;
+        errdefer {
+            for (entries.items) |*e| e.deinit();
+            entries.deinit(allocator);
+        }
+
+        for (records.array.items) |record| {
+            if (record != .object) continue;
+            if (recordToEntry(allocator, record.object)) |e| {
+                try entries.append(allocator, e);
+            } else |_| {}
+        }
+
+        return entries.toOwnedSlice(allocator);
+    }
+
+    fn recordToEntry(allocator: std.mem.Allocator, record: std.json.ObjectMap) !Entry {
+        const chapter = std.mem.indexOf(u8, stringField(record, "contentType"), "Chapter") != null;
+        var result = Entry{
+            .key = "",
+            .entry_type = try allocator.dupe(u8, if (chapter) "incollection" else "article"),
+            .allocator = allocator,
+        };
+        errdefer result.deinit();
+
+        const doi = stringField(record, "doi");
+        if (doi.len > 0) {
+            result.doi = try allocator.dupe(u8, doi);
+            result.key = try allocator.dupe(u8, doi);
+        }
+        if (stringField(record, "title").len > 0) result.title = try markup.plainText(allocator, stringField(record, "title"));
+        if (stringField(record, "publicationName").len > 0) result.venue = try markup.plainText(allocator, stringField(record, "publicationName"));
+        if (stringField(record, "publisher").len > 0) result.publisher = try markup.plainText(allocator, stringField(record, "publisher"));
+
+        // "2017-05-28"
+        const date = stringField(record, "publicationDate");
+        if (date.len >= 4) result.year = std.fmt.parseInt(i32, date[0..4], 10) catch null;
+
+        if (record.get("creators")) |creators| {
+            if (creators == .array) {
+                var authors: std.ArrayList([]const u8) = .empty;
+                defer authors.deinit(allocator);
+                errdefer for (authors.items) |a| allocator.free(a);
+                for (creators.array.items) |creator| {
+                    if (creator != .object) continue;
+                    // "LeCun, Yann"
+                    const name = stringField(creator.object, "creator");
+                    if (name.len > 0) try authors.append(allocator, try allocator.dupe(u8, name));
                 }
+                result.authors = try authors.toOwnedSlice(allocator);
             }
         }
 
-        const date_fields = [_][]const u8{ "published", "published-print", "published-online" };
-        for (date_fields) |field_name| {
-            if (work.get(field_name)) |date_val| {
-                if (date_val == .object) {
-                    if (date_val.object.get("date-parts")) |parts| {
-                        if (parts == .array and parts.array.items.len > 0) {
-                            if (parts.array.items[0] == .array and parts.array.items[0].array.items.len > 0) {
-                                if (parts.array.items[0].array.items[0] == .integer) {
-                                    result.year = @intCast(parts.array.items[0].array.items[0].integer);
-                                    break;
-                                }
-                            }
-                        }
-                    }
+        const start_page = stringField(record, "startingPage");
+        const end_page = stringField(record, "endingPage");
+        const pages = if (start_page.len > 0 and end_page.len > 0 and !std.mem.eql(u8, start_page, end_page))
+            try std.fmt.allocPrint(allocator, "{s}--{s}", .{ start_page, end_page })
+        else
+            try allocator.dupe(u8, start_page);
+        defer allocator.free(pages);
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "volume", .value = stringField(record, "volume") },
+            .{ .name = "number", .value = stringField(record, "number") },
+            .{ .name = "pages", .value = pages },
+        });
+
+        return result;
+    }
+};
+
+/// Elsevier Scopus Search API; needs an API key with Scopus access.
+pub const Scopus = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    api_key: []const u8,
+
+    const BASE_URL = "https://api.elsevier.com/content/search/scopus";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache, api_key: []const u8) Scopus {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+            .api_key = api_key,
+        };
+    }
+
+    /// The record with a Scopus EID, e.g. "2-s2.0-85012345678".
+    pub fn searchByEid(self: *Scopus, eid: []const u8) !?Entry {
+        if (self.response_cache.get("scopus_eid", eid)) |cached| {
+            defer self.allocator.free(cached);
+            return try firstEntry(self.allocator, cached);
+        }
+
+        const query = try std.fmt.allocPrint(self.allocator, "EID({s})", .{eid});
+        defer self.allocator.free(query);
+        const body = try self.get(query, 1);
+        defer self.allocator.free(body);
+
+        self.response_cache.set("scopus_eid", eid, body) catch {};
+        return try firstEntry(self.allocator, body);
+    }
+
+    pub fn searchByTitle(self: *Scopus, title: []const u8) ![]Entry {
+        // Scopus query syntax treats these as operators
+        const cleaned = try self.allocator.dupe(u8, title);
+        defer self.allocator.free(cleaned);
+        for (cleaned) |*c| {
+            if (std.mem.indexOfScalar(u8, "(){}\"", c.*) != null) c.* = ' ';
+        }
+        const query = try std.fmt.allocPrint(self.allocator, "TITLE({s})", .{cleaned});
+        defer self.allocator.free(query);
+        const body = try self.get(query, 5);
+        defer self.allocator.free(body);
+        return try parseResults(self.allocator, body);
+    }
+
+    fn get(self: *Scopus, query: []const u8, count: usize) ![]u8 {
+        const encoded = try http.urlEncode(self.allocator, query);
+        defer self.allocator.free(encoded);
+        // The complete view lists every author rather than the first
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?query={s}&count={d}&view=COMPLETE&httpAccept=application/json", .{ BASE_URL, encoded, count });
+        defer self.allocator.free(url);
+
+        // Sent as a header, so the key is in no URL
+        const headers = [_]std.http.Header{.{ .name = "X-ELS-APIKey", .value = self.api_key }};
+        return self.client.getWithHeaders(url, &headers) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+    }
+
+    fn firstEntry(allocator: std.mem.Allocator, json_body: []const u8) !?Entry {
+        const entries = try parseResults(allocator, json_body);
+        defer allocator.free(entries);
+        if (entries.len == 0) return null;
+        for (entries[1..]) |*e| e.deinit();
+        return entries[0];
+    }
+
+    pub fn parseResults(allocator: std.mem.Allocator, json_body: []const u8) ![]Entry {
+        const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
+        defer parsed.deinit();
+
+        const root = parsed.value;
+        if (root != .object) return &.{};
+        const results = root.object.get("search-results") orelse return &.{};
+        if (results != .object) return &.{};
+        const items = results.object.get("entry") orelse return &.{};
+        if (items != .array) return &.{};
+
+        var entries: std.ArrayList(Entry) = .empty;
+        errdefer {
+            for (entries.items) |*e| e.deinit();
+            entries.deinit(allocator);
+        }
+
+        for (items.array.items) |item| {
+            // An empty result set is a single {"error": "Result set was empty"}
+            if (item != .object or item.object.get("error") != null) continue;
+            if (itemToEntry(allocator, item.object)) |e| {
+                try entries.append(allocator, e);
+            } else |_| {}
+        }
+
+        return entries.toOwnedSlice(allocator);
+    }
+
+    fn itemToEntry(allocator: std.mem.Allocator, item: std.json.ObjectMap) !Entry {
+        const conference = std.mem.eql(u8, stringField(item, "prism:aggregationType"), "Conference Proceeding");
+        var result = Entry{
+            .key = "",
+            .entry_type = try allocator.dupe(u8, if (conference) "inproceedings" else "article"),
+            .allocator = allocator,
+        };
+        errdefer result.deinit();
+
+        const eid = stringField(item, "eid");
+        if (eid.len > 0) {
+            result.scopus_eid = try allocator.dupe(u8, eid);
+            result.key = try allocator.dupe(u8, eid);
+        }
+        if (stringField(item, "dc:title").len > 0) result.title = try markup.plainText(allocator, stringField(item, "dc:title"));
+        if (stringField(item, "prism:doi").len > 0) result.doi = try allocator.dupe(u8, stringField(item, "prism:doi"));
+        if (stringField(item, "prism:publicationName").len > 0) result.venue = try markup.plainText(allocator, stringField(item, "prism:publicationName"));
+
+        // "2017-05-28"
+        const date = stringField(item, "prism:coverDate");
+        if (date.len >= 4) result.year = std.fmt.parseInt(i32, date[0..4], 10) catch null;
+
+        // Without the complete view only the first author is listed, which
+        // would read as a short author list, so authors are left out then
+        if (item.get("author")) |author_list| {
+            if (author_list == .array) {
+                var authors: std.ArrayList([]const u8) = .empty;
+                defer authors.deinit(allocator);
+                errdefer for (authors.items) |a| allocator.free(a);
+                for (author_list.array.items) |author| {
+                    if (author != .object) continue;
+                    // "LeCun Y."
+                    const name = stringField(author.object, "authname");
+                    if (name.len > 0) try authors.append(allocator, try allocator.dupe(u8, name));
                 }
+                result.authors = try authors.toOwnedSlice(allocator);
             }
         }
 
-        if (work.get("type")) |type_val| {
-            if (type_val == .string) {
-                const new_type = try allocator.dupe(u8, type_val.string);
-                allocator.free(result.entry_type);
-                result.entry_type = new_type;
-            }
-        }
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "volume", .value = stringField(item, "prism:volume") },
+            .{ .name = "number", .value = stringField(item, "prism:issueIdentifier") },
+            .{ .name = "pages", .value = stringField(item, "prism:pageRange") },
+            .{ .name = "articleno", .value = stringField(item, "article-number") },
+        });
 
         return result;
     }
@@ -200,14 +906,14 @@ pub const CrossRef = struct {
 
 pub const Dblp = struct {
     allocator: std.mem.Allocator,
-    client: http.Client,
+    client: *http.Client,
 
     const BASE_URL = "https://dblp.org/search/publ/api";
 
-    pub fn init(allocator: std.mem.Allocator) Dblp {
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) Dblp {
         return .{
             .allocator = allocator,
-            .client = http.Client.init(allocator, USER_AGENT),
+            .client = client,
         };
     }
 
@@ -278,7 +984,7 @@ pub const Dblp = struct {
                 if (std.mem.endsWith(u8, title, ".")) {
                     title = title[0 .. title.len - 1];
                 }
-                result.title = try allocator.dupe(u8, title);
+                result.title = try markup.plainText(allocator, title);
             }
         }
 
@@ -296,7 +1002,16 @@ pub const Dblp = struct {
 
         if (info.get("venue")) |venue_val| {
             if (venue_val == .string) {
-                result.venue = try allocator.dupe(u8, venue_val.string);
+                result.venue = try markup.plainText(allocator, venue_val.string);
+            }
+        }
+
+        // "conf/nips/VaswaniSPUJGKP17" is in the stream "conf/nips"
+        if (info.get("key")) |key_val| {
+            if (key_val == .string) {
+                if (std.mem.lastIndexOfScalar(u8, key_val.string, '/')) |slash| {
+                    result.venue_id = try std.fmt.allocPrint(allocator, "dblp:{s}", .{key_val.string[0..slash]});
+                }
             }
         }
 
@@ -353,14 +1068,14 @@ pub const Dblp = struct {
 
 pub const SemanticScholar = struct {
     allocator: std.mem.Allocator,
-    client: http.Client,
+    client: *http.Client,
 
     const BASE_URL = "https://api.semanticscholar.org/graph/v1";
 
-    pub fn init(allocator: std.mem.Allocator) SemanticScholar {
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) SemanticScholar {
         return .{
             .allocator = allocator,
-            .client = http.Client.init(allocator, USER_AGENT),
+            .client = client,
         };
     }
 
@@ -382,6 +1097,30 @@ pub const SemanticScholar = struct {
         return try parseResults(self.allocator, body);
     }
 
+    /// Look up a single paper by arXiv ID. Versions ("v2") are dropped,
+    /// since the API only knows the paper.
+    pub fn searchByArxiv(self: *SemanticScholar, arxiv_id: []const u8) !?Entry {
+        const id = withoutVersion(arxiv_id);
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/paper/arXiv:{s}?fields=title,authors,year,venue,externalIds", .{ BASE_URL, id });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, body, .{}) catch return null;
+        defer parsed.deinit();
+
+        if (parsed.value != .object) return null;
+        return try paperToEntry(self.allocator, parsed.value.object);
+    }
+
     fn parseResults(allocator: std.mem.Allocator, json_body: []const u8) ![]Entry {
         const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
         defer parsed.deinit();
@@ -424,7 +1163,7 @@ pub const SemanticScholar = struct {
 
         if (paper.get("title")) |title_val| {
             if (title_val == .string) {
-                result.title = try allocator.dupe(u8, title_val.string);
+                result.title = try markup.plainText(allocator, title_val.string);
             }
         }
 
@@ -436,7 +1175,7 @@ pub const SemanticScholar = struct {
 
         if (paper.get("venue")) |venue_val| {
             if (venue_val == .string and venue_val.string.len > 0) {
-                result.venue = try allocator.dupe(u8, venue_val.string);
+                result.venue = try markup.plainText(allocator, venue_val.string);
             }
         }
 
@@ -477,14 +1216,14 @@ pub const SemanticScholar = struct {
 
 pub const OpenAlex = struct {
     allocator: std.mem.Allocator,
-    client: http.Client,
+    client: *http.Client,
 
     const BASE_URL = "https://api.openalex.org/works";
 
-    pub fn init(allocator: std.mem.Allocator) OpenAlex {
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) OpenAlex {
         return .{
             .allocator = allocator,
-            .client = http.Client.init(allocator, USER_AGENT),
+            .client = client,
         };
     }
 
@@ -506,6 +1245,34 @@ pub const OpenAlex = struct {
         return try parseResults(self.allocator, body);
     }
 
+    /// Look up a single work by DOI.
+    pub fn searchByDoi(self: *OpenAlex, doi: []const u8) !?Entry {
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/doi:{s}", .{ BASE_URL, doi });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, body, .{}) catch return null;
+        defer parsed.deinit();
+
+        if (parsed.value != .object) return null;
+        return try workToEntry(self.allocator, parsed.value.object);
+    }
+
+    /// "https://openalex.org/S4306420609" as "openalex:S4306420609".
+    pub fn openAlexSourceId(allocator: std.mem.Allocator, url: []const u8) ![]u8 {
+        const slash = std.mem.lastIndexOfScalar(u8, url, '/');
+        const id = if (slash) |i| url[i + 1 ..] else url;
+        return std.fmt.allocPrint(allocator, "openalex:{s}", .{id});
+    }
+
     fn parseResults(allocator: std.mem.Allocator, json_body: []const u8) ![]Entry {
         const parsed = std.json.parseFromSlice(std.json.Value, allocator, json_body, .{}) catch return &.{};
         defer parsed.deinit();
@@ -548,7 +1315,7 @@ pub const OpenAlex = struct {
 
         if (work.get("title")) |title_val| {
             if (title_val == .string) {
-                result.title = try allocator.dupe(u8, title_val.string);
+                result.title = try markup.plainText(allocator, title_val.string);
             }
         }
 
@@ -588,6 +1355,543 @@ pub const OpenAlex = struct {
             }
         }
 
+        if (work.get("primary_location")) |location| {
+            if (location == .object) {
+                if (location.object.get("source")) |source| {
+                    if (source == .object) {
+                        if (source.object.get("display_name")) |name| {
+                            if (name == .string) result.venue = try markup.plainText(allocator, name.string);
+                        }
+                        if (source.object.get("id")) |id| {
+                            if (id == .string) result.venue_id = try openAlexSourceId(allocator, id.string);
+                        }
+                        if (source.object.get("host_organization_name")) |name| {
+                            if (name == .string) result.publisher = try allocator.dupe(u8, name.string);
+                        }
+                        // Conference proceedings, workshops included, are sources of type "conference"
+                        if (std.mem.eql(u8, stringField(source.object, "type"), "conference")) {
+                            const new_type = try allocator.dupe(u8, "inproceedings");
+                            allocator.free(result.entry_type);
+                            result.entry_type = new_type;
+                        }
+                    }
+                }
+            }
+        }
+
+        if (work.get("open_access")) |oa_val| {
+            if (oa_val == .object) {
+                if (oa_val.object.get("oa_status")) |status| {
+                    if (status == .string) {
+                        result.oa_status = try allocator.dupe(u8, status.string);
+                    }
+                }
+            }
+        }
+
+        // Prefer the license of the best open copy over the publisher's
+        for ([_][]const u8{ "best_oa_location", "primary_location" }) |location_name| {
+            const location = work.get(location_name) orelse continue;
+            if (location != .object) continue;
+            const license = location.object.get("license") orelse continue;
+            if (license == .string) {
+                result.license = try allocator.dupe(u8, license.string);
+                break;
+            }
+        }
+
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "language", .value = stringField(work, "language") },
+        });
+
+        return result;
+    }
+};
+
+pub const OpenLibrary = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+
+    const BASE_URL = "https://openlibrary.org/api/books";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) OpenLibrary {
+        return .{
+            .allocator = allocator,
+            .client = client,
+        };
+    }
+
+    /// Look up a book by ISBN.
+    pub fn searchByIsbn(self: *OpenLibrary, isbn: []const u8) !?Entry {
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?bibkeys=ISBN:{s}&format=json&jscmd=data", .{ BASE_URL, isbn });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, body, .{}) catch return null;
+        defer parsed.deinit();
+
+        // Keyed by the requested bibkey; empty when the ISBN is unknown
+        if (parsed.value != .object) return null;
+        var books = parsed.value.object.iterator();
+        const book = books.next() orelse return null;
+        if (book.value_ptr.* != .object) return null;
+        return try bookToEntry(self.allocator, book.value_ptr.object, isbn);
+    }
+
+    fn bookToEntry(allocator: std.mem.Allocator, book: std.json.ObjectMap, isbn: []const u8) !Entry {
+        var result = Entry{
+            .key = "",
+            .entry_type = try allocator.dupe(u8, "book"),
+            .allocator = allocator,
+        };
+        errdefer result.deinit();
+        result.isbn = try allocator.dupe(u8, isbn);
+
+        if (book.get("key")) |key_val| {
+            if (key_val == .string) result.key = try allocator.dupe(u8, key_val.string);
+        }
+
+        result.title = try bookTitle(allocator, book.get("title"), book.get("subtitle"));
+
+        if (book.get("authors")) |authors_val| {
+            if (authors_val == .array) {
+                var authors: std.ArrayList([]const u8) = .empty;
+                defer authors.deinit(allocator);
+                for (authors_val.array.items) |author| {
+                    if (author != .object) continue;
+                    if (author.object.get("name")) |name| {
+                        if (name == .string) {
+                            try authors.append(allocator, try allocator.dupe(u8, name.string));
+                        }
+                    }
+                }
+                result.authors = try authors.toOwnedSlice(allocator);
+            }
+        }
+
+        if (book.get("publishers")) |publishers| {
+            if (publishers == .array and publishers.array.items.len > 0) {
+                const first = publishers.array.items[0];
+                if (first == .object) {
+                    if (first.object.get("name")) |name| {
+                        if (name == .string) result.publisher = try allocator.dupe(u8, name.string);
+                    }
+                }
+            }
+        }
+
+        if (book.get("publish_date")) |date| {
+            if (date == .string) result.year = yearIn(date.string);
+        }
+
+        result.extra = try extraFields(allocator, &.{
+            .{ .name = "edition", .value = stringField(book, "edition_name") },
+        });
+
+        return result;
+    }
+};
+
+pub const GoogleBooks = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+
+    const BASE_URL = "https://www.googleapis.com/books/v1/volumes";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) GoogleBooks {
+        return .{
+            .allocator = allocator,
+            .client = client,
+        };
+    }
+
+    /// Look up a book by ISBN.
+    pub fn searchByIsbn(self: *GoogleBooks, isbn: []const u8) !?Entry {
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?q=isbn:{s}", .{ BASE_URL, isbn });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, body, .{}) catch return null;
+        defer parsed.deinit();
+
+        if (parsed.value != .object) return null;
+        const items = parsed.value.object.get("items") orelse return null;
+        if (items != .array or items.array.items.len == 0) return null;
+        const volume = items.array.items[0];
+        if (volume != .object) return null;
+        const info = volume.object.get("volumeInfo") orelse return null;
+        if (info != .object) return null;
+
+        var result = Entry{
+            .key = "",
+            .entry_type = try self.allocator.dupe(u8, "book"),
+            .allocator = self.allocator,
+        };
+        errdefer result.deinit();
+        result.isbn = try self.allocator.dupe(u8, isbn);
+
+        if (volume.object.get("id")) |id_val| {
+            if (id_val == .string) result.key = try self.allocator.dupe(u8, id_val.string);
+        }
+
+        result.title = try bookTitle(self.allocator, info.object.get("title"), info.object.get("subtitle"));
+
+        if (info.object.get("authors")) |authors_val| {
+            if (authors_val == .array) {
+                var authors: std.ArrayList([]const u8) = .empty;
+                defer authors.deinit(self.allocator);
+                for (authors_val.array.items) |name| {
+                    if (name == .string) {
+                        try authors.append(self.allocator, try self.allocator.dupe(u8, name.string));
+                    }
+                }
+                result.authors = try authors.toOwnedSlice(self.allocator);
+            }
+        }
+
+        if (info.object.get("publisher")) |publisher| {
+            if (publisher == .string) result.publisher = try self.allocator.dupe(u8, publisher.string);
+        }
+
+        if (info.object.get("publishedDate")) |date| {
+            if (date == .string) result.year = yearIn(date.string);
+        }
+
+        return result;
+    }
+};
+
+/// "Title: Subtitle", as a book is usually cited.
+fn bookTitle(allocator: std.mem.Allocator, title: ?std.json.Value, subtitle: ?std.json.Value) !?[]const u8 {
+    const main = title orelse return null;
+    if (main != .string) return null;
+    if (subtitle) |sub| {
+        if (sub == .string and sub.string.len > 0) {
+            return try std.fmt.allocPrint(allocator, "{s}: {s}", .{ main.string, sub.string });
+        }
+    }
+    return try allocator.dupe(u8, main.string);
+}
+
+/// The first four-digit year in a free-form date such as "March 2009".
+fn yearIn(date: []const u8) ?i32 {
+    var i: usize = 0;
+    while (i + 4 <= date.len) : (i += 1) {
+        const digits = date[i .. i + 4];
+        const starts_run = i == 0 or !std.ascii.isDigit(date[i - 1]);
+        const ends_run = i + 4 == date.len or !std.ascii.isDigit(date[i + 4]);
+        if (!starts_run or !ends_run) continue;
+        return std.fmt.parseInt(i32, digits, 10) catch continue;
+    }
+    return null;
+}
+
+/// An arXiv ID without its version suffix ("v2").
+fn withoutVersion(arxiv_id: []const u8) []const u8 {
+    const v = std.mem.lastIndexOfScalar(u8, arxiv_id, 'v') orelse return arxiv_id;
+    if (v > 0 and v + 1 < arxiv_id.len and std.ascii.isDigit(arxiv_id[v - 1]) and std.ascii.isDigit(arxiv_id[v + 1])) return arxiv_id[0..v];
+    return arxiv_id;
+}
+
+pub const Arxiv = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+
+    const BASE_URL = "https://export.arxiv.org/api/query";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client) Arxiv {
+        return .{
+            .allocator = allocator,
+            .client = client,
+        };
+    }
+
+    /// Look up a preprint by arXiv ID.
+    pub fn searchById(self: *Arxiv, arxiv_id: []const u8) !?Entry {
+        const url = try std.fmt.allocPrint(self.allocator, "{s}?id_list={s}", .{ BASE_URL, arxiv_id });
+        defer self.allocator.free(url);
+
+        const body = self.client.get(url) catch |err| {
+            return switch (err) {
+                http.HttpError.NotFound => null,
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        defer self.allocator.free(body);
+
+        return try parseFeed(self.allocator, body);
+    }
+
+    /// The first entry of an Atom feed from the arXiv API. Unknown IDs
+    /// come back as an entry whose id is an error URL.
+    fn parseFeed(allocator: std.mem.Allocator, feed: []const u8) !?Entry {
+        const start = std.mem.indexOf(u8, feed, "<entry>") orelse return null;
+        const end = std.mem.indexOfPos(u8, feed, start, "</entry>") orelse return null;
+        const item = feed[start..end];
+
+        const id = xmlElement(item, "id") orelse return null;
+        if (std.mem.indexOf(u8, id, "/api/errors") != null) return null;
+
+        var result = Entry{
+            .key = "",
+            .entry_type = try allocator.dupe(u8, "article"),
+            .allocator = allocator,
+        };
+        errdefer result.deinit();
+
+        // "http://arxiv.org/abs/1706.03762v7"
+        const abs = std.mem.indexOf(u8, id, "/abs/");
+        result.key = try allocator.dupe(u8, if (abs) |a| id[a + 5 ..] else id);
+        result.arxiv_id = try allocator.dupe(u8, withoutVersion(result.key));
+
+        if (xmlElement(item, "title")) |title| result.title = try xmlText(allocator, title);
+        if (xmlElement(item, "published")) |date| result.year = yearIn(date);
+        if (xmlElement(item, "arxiv:doi")) |doi| result.doi = try xmlText(allocator, doi);
+        if (xmlAttribute(item, "arxiv:primary_category", "term")) |class| result.primary_class = try allocator.dupe(u8, class);
+
+        var authors: std.ArrayList([]const u8) = .empty;
+        defer authors.deinit(allocator);
+        errdefer for (authors.items) |a| allocator.free(a);
+        var rest = item;
+        while (std.mem.indexOf(u8, rest, "<author>")) |pos| {
+            rest = rest[pos + "<author>".len ..];
+            const name = xmlElement(rest, "name") orelse continue;
+            try authors.append(allocator, try xmlText(allocator, name));
+        }
+        result.authors = try authors.toOwnedSlice(allocator);
+
         return result;
     }
+
+    /// Content of the first `<tag>` element in `xml`.
+    fn xmlElement(xml: []const u8, tag: []const u8) ?[]const u8 {
+        var search: usize = 0;
+        while (std.mem.indexOfPos(u8, xml, search, "<")) |open| {
+            search = open + 1;
+            const after = open + 1 + tag.len;
+            if (after >= xml.len or !std.mem.eql(u8, xml[open + 1 .. after], tag)) continue;
+            if (xml[after] != '>' and xml[after] != ' ') continue;
+            const content_start = (std.mem.indexOfScalarPos(u8, xml, after, '>') orelse return null) + 1;
+            if (xml[content_start - 2] == '/') return "";
+            var close_buf: [64]u8 = undefined;
+            const close = std.fmt.bufPrint(&close_buf, "</{s}>", .{tag}) catch return null;
+            const content_end = std.mem.indexOfPos(u8, xml, content_start, close) orelse return null;
+            return xml[content_start..content_end];
+        }
+        return null;
+    }
+
+    /// Value of attribute `name` on the first `<tag>` element in `xml`.
+    fn xmlAttribute(xml: []const u8, tag: []const u8, name: []const u8) ?[]const u8 {
+        var open_buf: [64]u8 = undefined;
+        const open = std.fmt.bufPrint(&open_buf, "<{s} ", .{tag}) catch return null;
+        const start = std.mem.indexOf(u8, xml, open) orelse return null;
+        const end = std.mem.indexOfScalarPos(u8, xml, start, '>') orelse return null;
+        const attrs = xml[start + open.len .. end];
+
+        var attr_buf: [64]u8 = undefined;
+        const needle = std.fmt.bufPrint(&attr_buf, "{s}=\"", .{name}) catch return null;
+        const pos = std.mem.indexOf(u8, attrs, needle) orelse return null;
+        const value_start = pos + needle.len;
+        const value_end = std.mem.indexOfScalarPos(u8, attrs, value_start, '"') orelse return null;
+        return attrs[value_start..value_end];
+    }
+
+    /// Element text with entities decoded and line breaks collapsed.
+    fn xmlText(allocator: std.mem.Allocator, raw: []const u8) ![]const u8 {
+        var out: std.ArrayList(u8) = .empty;
+        errdefer out.deinit(allocator);
+
+        var i: usize = 0;
+        var space = false;
+        while (i < raw.len) : (i += 1) {
+            const c = raw[i];
+            if (std.ascii.isWhitespace(c)) {
+                space = out.items.len > 0;
+                continue;
+            }
+            if (space) try out.append(allocator, ' ');
+            space = false;
+
+            if (c == '&') {
+                const entities = [_][2][]const u8{ .{ "&amp;", "&" }, .{ "&lt;", "<" }, .{ "&gt;", ">" }, .{ "&quot;", "\"" }, .{ "&apos;", "'" } };
+                const decoded = for (entities) |entity| {
+                    if (std.mem.startsWith(u8, raw[i..], entity[0])) break entity;
+                } else null;
+                if (decoded) |entity| {
+                    try out.appendSlice(allocator, entity[1]);
+                    i += entity[0].len - 1;
+                    continue;
+                }
+            }
+            try out.append(allocator, c);
+        }
+        return out.toOwnedSlice(allocator);
+    }
 };
+
+test "arXiv Atom feed" {
+    const allocator = std.testing.allocator;
+
+    var e = (try Arxiv.parseFeed(allocator,
+        \\<feed xmlns="http://www.w3.org/2005/Atom">
+        \\  <title type="html">ArXiv Query</title>
+        \\  <entry>
+        \\    <id>http://arxiv.org/abs/1706.03762v7</id>
+        \\    <published>2017-06-12T17:57:34Z</published>
+        \\    <title>Attention Is All You
+        \\  Need</title>
+        \\    <author><name>Ashish Vaswani</name></author>
+        \\    <author><name>Noam Shazeer</name></author>
+        \\    <arxiv:doi xmlns:arxiv="http://arxiv.org/schemas/atom">10.48550/arXiv.1706.03762</arxiv:doi>
+        \\    <arxiv:primary_category xmlns:arxiv="http://arxiv.org/schemas/atom" term="cs.CL" scheme="http://arxiv.org/schemas/atom"/>
+        \\  </entry>
+        \\</feed>
+    )).?;
+    defer e.deinit();
+
+    try std.testing.expectEqualStrings("Attention Is All You Need", e.title.?);
+    try std.testing.expectEqual(@as(?i32, 2017), e.year);
+    try std.testing.expectEqual(@as(usize, 2), e.authors.len);
+    try std.testing.expectEqualStrings("Noam Shazeer", e.authors[1]);
+    try std.testing.expectEqualStrings("1706.03762", e.arxiv_id.?);
+    try std.testing.expectEqualStrings("10.48550/arXiv.1706.03762", e.doi.?);
+    try std.testing.expectEqualStrings("cs.CL", e.primary_class.?);
+
+    try std.testing.expect(try Arxiv.parseFeed(allocator, "<feed><entry><id>http://arxiv.org/api/errors#incorrect_id_format_for_1234</id></entry></feed>") == null);
+    try std.testing.expectEqual(@as(?i32, 2009), yearIn("March 2009"));
+}
+
+test "IEEE Xplore articles" {
+    const allocator = std.testing.allocator;
+
+    const entries = try Ieee.parseArticles(allocator,
+        \\{"total_records":1,"articles":[{"article_number":"7780459","doi":"10.1109/CVPR.2016.90",
+        \\"title":"Deep Residual Learning for Image Recognition","publisher":"IEEE",
+        \\"content_type":"Conferences","publication_title":"2016 IEEE Conference on Computer Vision and Pattern Recognition (CVPR)",
+        \\"publication_year":2016,"start_page":"770","end_page":"778",
+        \\"authors":{"authors":[{"full_name":"Kaiming He","author_order":1},{"full_name":"Xiangyu Zhang","author_order":2}]}}]}
+    );
+    defer {
+        for (entries) |*e| e.deinit();
+        allocator.free(entries);
+    }
+
+    try std.testing.expectEqual(@as(usize, 1), entries.len);
+    const e = entries[0];
+    try std.testing.expectEqualStrings("inproceedings", e.entry_type);
+    try std.testing.expectEqualStrings("10.1109/CVPR.2016.90", e.doi.?);
+    try std.testing.expectEqual(@as(?i32, 2016), e.year);
+    try std.testing.expectEqualStrings("Xiangyu Zhang", e.authors[1]);
+    try std.testing.expectEqualStrings("770--778", e.extraField("pages").?);
+    try std.testing.expect(e.extraField("volume") == null);
+    try std.testing.expect(Ieee.isIeeeDoi(e.doi.?));
+}
+
+test "Springer Nature and Scopus records" {
+    const allocator = std.testing.allocator;
+
+    const springer = try Springer.parseRecords(allocator,
+        \\{"records":[{"contentType":"Article","title":"Deep learning","creators":[{"creator":"LeCun, Yann"},{"creator":"Bengio, Yoshua"}],
+        \\"publicationName":"Nature","doi":"10.1038/nature14539","publisher":"Nature","publicationDate":"2015-05-28",
+        \\"volume":"521","number":"7553","startingPage":"436","endingPage":"444"}]}
+    );
+    defer {
+        for (springer) |*e| e.deinit();
+        allocator.free(springer);
+    }
+    try std.testing.expectEqual(@as(usize, 1), springer.len);
+    try std.testing.expectEqual(@as(?i32, 2015), springer[0].year);
+    try std.testing.expectEqualStrings("Bengio, Yoshua", springer[0].authors[1]);
+    try std.testing.expectEqualStrings("436--444", springer[0].extraField("pages").?);
+    try std.testing.expect(Springer.isSpringerDoi(springer[0].doi.?));
+
+    const scopus = try Scopus.parseResults(allocator,
+        \\{"search-results":{"entry":[{"eid":"2-s2.0-84930630277","dc:title":"Deep learning","prism:publicationName":"Nature",
+        \\"prism:coverDate":"2015-05-28","prism:doi":"10.1038/nature14539","prism:volume":"521","prism:pageRange":"436-444",
+        \\"prism:aggregationType":"Journal","author":[{"authname":"LeCun Y."},{"authname":"Bengio Y."},{"authname":"Hinton G."}]}]}}
+    );
+    defer {
+        for (scopus) |*e| e.deinit();
+        allocator.free(scopus);
+    }
+    try std.testing.expectEqual(@as(usize, 1), scopus.len);
+    try std.testing.expectEqualStrings("2-s2.0-84930630277", scopus[0].scopus_eid.?);
+    try std.testing.expectEqual(@as(usize, 3), scopus[0].authors.len);
+    try std.testing.expectEqualStrings("436-444", scopus[0].extraField("pages").?);
+
+    const empty = try Scopus.parseResults(allocator,
+        \\{"search-results":{"opensearch:totalResults":"0","entry":[{"@_fa":"true","error":"Result set was empty"}]}}
+    );
+    defer allocator.free(empty);
+    try std.testing.expectEqual(@as(usize, 0), empty.len);
+}
+
+test "OpenAlex open-access status and license" {
+    const allocator = std.testing.allocator;
+
+    const works = try OpenAlex.parseResults(allocator,
+        \\{"results":[{"id":"https://openalex.org/W2194775991","title":"Deep Residual Learning for Image Recognition",
+        \\"open_access":{"is_oa":true,"oa_status":"green"},
+        \\"primary_location":{"license":null,"source":{"display_name":"CVPR"}},
+        \\"best_oa_location":{"license":"cc-by","landing_page_url":"https://arxiv.org/abs/1512.03385"}},
+        \\{"id":"https://openalex.org/W1","title":"Deep learning","open_access":{"is_oa":false,"oa_status":"closed"},
+        \\"primary_location":{"license":"publisher-specific-oa"},"best_oa_location":null},
+        \\{"id":"https://openalex.org/W2","title":"No access data"}]}
+    );
+    defer {
+        for (works) |*e| e.deinit();
+        allocator.free(works);
+    }
+
+    try std.testing.expectEqual(@as(usize, 3), works.len);
+    try std.testing.expectEqualStrings("green", works[0].oa_status.?);
+    try std.testing.expectEqualStrings("cc-by", works[0].license.?);
+    // Without an open copy, the publisher's license
+    try std.testing.expectEqualStrings("closed", works[1].oa_status.?);
+    try std.testing.expectEqualStrings("publisher-specific-oa", works[1].license.?);
+    try std.testing.expect(works[2].oa_status == null);
+    try std.testing.expect(works[2].license == null);
+}
+
+test "CrossRef corrections" {
+    const allocator = std.testing.allocator;
+
+    var work = (try CrossRef.parseWork(allocator,
+        \\{"status":"ok","message":{"DOI":"10.1038/nature14539","title":["Deep learning"],"type":"journal-article",
+        \\"relation":{"is-corrected-by":[{"id-type":"doi","id":"10.1038/nature14540","asserted-by":"object"}]},
+        \\"updated-by":[{"DOI":"10.1038/nature14540","type":"correction"},{"DOI":"10.1038/nature99999","type":"retraction"}]}}
+    )).?;
+    defer work.deinit();
+    try std.testing.expectEqualStrings("10.1038/nature14540", work.extraField("erratum").?);
+    try std.testing.expectEqualStrings("retraction 10.1038/nature99999", work.extraField("updates").?);
+}
+
+test "CrossRef translated titles" {
+    const allocator = std.testing.allocator;
+
+    var translated = (try CrossRef.parseWork(allocator,
+        \\{"status":"ok","message":{"DOI":"10.1000/weber","title":["The Protestant Ethic and the Spirit of Capitalism"],
+        \\"original-title":["Die protestantische Ethik und der <i>Geist</i> des Kapitalismus"],"language":"de"}}
+    )).?;
+    defer translated.deinit();
+    try std.testing.expectEqualStrings("Die protestantische Ethik und der Geist des Kapitalismus", translated.extraField("origtitle").?);
+    try std.testing.expectEqualStrings("de", translated.extraField("language").?);
+}
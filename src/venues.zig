@@ -0,0 +1,296 @@
+//! Dictionary of well-known venues and their standard abbreviations.
+
+const std = @import("std");
+
+/// Preferred form of a venue name.
+pub const Style = enum {
+    /// Keep whichever form the local entry already uses.
+    preserve,
+    full,
+    abbreviated,
+};
+
+pub const Venue = struct {
+    /// Canonical full name.
+    full: []const u8,
+    /// Standard (ISO 4) abbreviation.
+    abbrev: []const u8,
+    /// Other common names, such as acronyms.
+    aliases: []const []const u8 = &.{},
+
+    /// Whether `name` refers to this venue. Proceedings titles that contain
+    /// the full name (e.g. "Proceedings of the 36th International
+    /// Conference on Machine Learning") also match.
+    pub fn matches(self: *const Venue, name: []const u8) bool {
+        var buf: [256]u8 = undefined;
+        const norm = normalize(name, &buf);
+        if (norm.len == 0) return false;
+
+        var full_buf: [256]u8 = undefined;
+        const full_norm = normalize(self.full, &full_buf);
+        if (std.mem.indexOf(u8, norm, full_norm) != null) return true;
+        return self.isNamed(norm);
+    }
+
+    /// The form of the name to suggest for `style`. With `.preserve`,
+    /// `local` decides which form is used.
+    pub fn preferred(self: *const Venue, style: Style, local: ?[]const u8) []const u8 {
+        return switch (style) {
+            .full => self.full,
+            .abbreviated => self.abbrev,
+            .preserve => if (local != null and looksAbbreviated(local.?)) self.abbrev else self.full,
+        };
+    }
+
+    fn isNamed(self: *const Venue, norm: []const u8) bool {
+        var buf: [256]u8 = undefined;
+        if (std.mem.eql(u8, norm, normalize(self.full, &buf))) return true;
+        if (std.mem.eql(u8, norm, normalize(self.abbrev, &buf))) return true;
+        for (self.aliases) |alias| {
+            if (std.mem.eql(u8, norm, normalize(alias, &buf))) return true;
+        }
+        return false;
+    }
+};
+
+pub const known = [_]Venue{
+    .{ .full = "Nature", .abbrev = "Nature" },
+    .{ .full = "Science", .abbrev = "Science" },
+    .{ .full = "Cell", .abbrev = "Cell" },
+    .{ .full = "The Lancet", .abbrev = "Lancet" },
+    .{ .full = "Bioinformatics", .abbrev = "Bioinformatics" },
+    .{ .full = "Proceedings of the National Academy of Sciences", .abbrev = "Proc. Natl. Acad. Sci. U.S.A.", .aliases = &.{ "PNAS", "Proceedings of the National Academy of Sciences of the United States of America" } },
+    .{ .full = "Physical Review Letters", .abbrev = "Phys. Rev. Lett.", .aliases = &.{"PRL"} },
+    .{ .full = "Nucleic Acids Research", .abbrev = "Nucleic Acids Res." },
+    .{ .full = "Journal of Machine Learning Research", .abbrev = "J. Mach. Learn. Res.", .aliases = &.{"JMLR"} },
+    .{ .full = "Machine Learning", .abbrev = "Mach. Learn." },
+    .{ .full = "Neural Computation", .abbrev = "Neural Comput." },
+    .{ .full = "Neural Networks", .abbrev = "Neural Netw." },
+    .{ .full = "Artificial Intelligence", .abbrev = "Artif. Intell." },
+    .{ .full = "Advances in Neural Information Processing Systems", .abbrev = "Adv. Neural Inf. Process. Syst.", .aliases = &.{ "NeurIPS", "NIPS" } },
+    .{ .full = "International Conference on Machine Learning", .abbrev = "Int. Conf. Mach. Learn.", .aliases = &.{"ICML"} },
+    .{ .full = "International Conference on Learning Representations", .abbrev = "Int. Conf. Learn. Represent.", .aliases = &.{"ICLR"} },
+    .{ .full = "AAAI Conference on Artificial Intelligence", .abbrev = "AAAI Conf. Artif. Intell.", .aliases = &.{"AAAI"} },
+    .{ .full = "IEEE Conference on Computer Vision and Pattern Recognition", .abbrev = "IEEE Conf. Comput. Vis. Pattern Recognit.", .aliases = &.{"CVPR"} },
+    .{ .full = "IEEE Transactions on Pattern Analysis and Machine Intelligence", .abbrev = "IEEE Trans. Pattern Anal. Mach. Intell.", .aliases = &.{"TPAMI"} },
+    .{ .full = "Communications of the ACM", .abbrev = "Commun. ACM", .aliases = &.{"CACM"} },
+    .{ .full = "Journal of the ACM", .abbrev = "J. ACM", .aliases = &.{"JACM"} },
+    .{ .full = "ACM Transactions on Programming Languages and Systems", .abbrev = "ACM Trans. Program. Lang. Syst.", .aliases = &.{"TOPLAS"} },
+    .{ .full = "Proceedings of the ACM on Programming Languages", .abbrev = "Proc. ACM Program. Lang.", .aliases = &.{"PACMPL"} },
+    .{ .full = "Journal of the American Statistical Association", .abbrev = "J. Am. Stat. Assoc.", .aliases = &.{"JASA"} },
+    .{ .full = "The Annals of Statistics", .abbrev = "Ann. Stat." },
+    .{ .full = "Journal of Statistical Software", .abbrev = "J. Stat. Softw.", .aliases = &.{"JSS"} },
+};
+
+/// Find the venue named exactly `name` (full name, abbreviation, or alias).
+pub fn lookup(name: []const u8) ?*const Venue {
+    var buf: [256]u8 = undefined;
+    const norm = normalize(name, &buf);
+    if (norm.len == 0) return null;
+
+    for (&known) |*venue| {
+        if (venue.isNamed(norm)) return venue;
+    }
+    return null;
+}
+
+/// Year and number of one edition of a conference, as its name gives them:
+/// "ICML 2019", "Proceedings of the 36th International Conference on
+/// Machine Learning".
+pub const Edition = struct {
+    year: ?i32 = null,
+    /// 36 for "36th"
+    number: ?u32 = null,
+};
+
+pub fn edition(name: []const u8) Edition {
+    var result = Edition{};
+    var it = std.mem.tokenizeAny(u8, name, " ,.;:()'-/");
+    while (it.next()) |token| {
+        if (yearToken(token)) |y| {
+            result.year = y;
+        } else if (ordinalToken(token)) |n| {
+            result.number = n;
+        }
+    }
+    return result;
+}
+
+/// Whether a venue is a workshop rather than a main conference or journal.
+pub fn isWorkshop(name: []const u8) bool {
+    var buf: [256]u8 = undefined;
+    var it = std.mem.tokenizeScalar(u8, normalize(name, &buf), ' ');
+    while (it.next()) |word| {
+        if (std.mem.eql(u8, word, "workshop") or std.mem.eql(u8, word, "workshops")) return true;
+    }
+    return false;
+}
+
+/// Whether `workshop` names a workshop held at `conference`, such as "ICML
+/// 2020 Workshop on Uncertainty" at the International Conference on
+/// Machine Learning.
+pub fn workshopOf(workshop: []const u8, conference: []const u8) bool {
+    if (!isWorkshop(workshop) or isWorkshop(conference)) return false;
+    var conf_buf: [256]u8 = undefined;
+    const conf_key = seriesName(conference, &conf_buf);
+    if (conf_key.len == 0) return false;
+    var ws_buf: [256]u8 = undefined;
+    const ws_norm = normalize(workshop, &ws_buf);
+
+    const venue = knownSeries(conf_key) orelse return std.mem.indexOf(u8, ws_norm, conf_key) != null;
+    if (venue.matches(workshop)) return true;
+    // Workshops usually go by the conference's acronym
+    var name_buf: [256]u8 = undefined;
+    if (containsWord(ws_norm, normalize(venue.abbrev, &name_buf))) return true;
+    for (venue.aliases) |alias| {
+        if (containsWord(ws_norm, normalize(alias, &name_buf))) return true;
+    }
+    return false;
+}
+
+fn containsWord(text: []const u8, word: []const u8) bool {
+    if (word.len == 0) return false;
+    var it = std.mem.tokenizeScalar(u8, text, ' ');
+    while (it.next()) |w| {
+        if (std.mem.eql(u8, w, word)) return true;
+    }
+    return false;
+}
+
+/// Whether two venue names are editions of the same series, such as
+/// "ICML 2019" and "Proceedings of the 37th International Conference on
+/// Machine Learning". A workshop is not in the series of its conference.
+pub fn sameSeries(a: []const u8, b: []const u8) bool {
+    if (isWorkshop(a) != isWorkshop(b)) return false;
+    var a_buf: [256]u8 = undefined;
+    var b_buf: [256]u8 = undefined;
+    const a_key = seriesName(a, &a_buf);
+    const b_key = seriesName(b, &b_buf);
+    if (a_key.len == 0 or b_key.len == 0) return false;
+    const a_known = knownSeries(a_key);
+    const b_known = knownSeries(b_key);
+    if (a_known != null or b_known != null) return a_known == b_known;
+    return std.mem.eql(u8, a_key, b_key);
+}
+
+/// The known venue a series name refers to. "International Conference on
+/// Machine Learning" contains "Machine Learning" too, so the longest
+/// matching name wins.
+fn knownSeries(name: []const u8) ?*const Venue {
+    if (lookup(name)) |venue| return venue;
+    var best: ?*const Venue = null;
+    for (&known) |*venue| {
+        if (venue.matches(name) and (best == null or venue.full.len > best.?.full.len)) best = venue;
+    }
+    return best;
+}
+
+/// The normalized name without years, ordinals, and a leading
+/// "proceedings of the".
+fn seriesName(name: []const u8, buf: []u8) []const u8 {
+    var norm_buf: [256]u8 = undefined;
+    var it = std.mem.tokenizeScalar(u8, normalize(name, &norm_buf), ' ');
+    var len: usize = 0;
+    while (it.next()) |word| {
+        if (yearToken(word) != null or ordinalToken(word) != null) continue;
+        const filler = [_][]const u8{ "proceedings", "proc", "of", "the", "annual" };
+        const leading = len == 0 and for (filler) |f| {
+            if (std.mem.eql(u8, word, f)) break true;
+        } else false;
+        if (leading or len + word.len + 1 > buf.len) continue;
+        if (len > 0) {
+            buf[len] = ' ';
+            len += 1;
+        }
+        @memcpy(buf[len..][0..word.len], word);
+        len += word.len;
+    }
+    return buf[0..len];
+}
+
+fn yearToken(token: []const u8) ?i32 {
+    if (token.len != 4) return null;
+    const y = std.fmt.parseInt(i32, token, 10) catch return null;
+    return if (y >= 1900 and y < 2100) y else null;
+}
+
+/// 36 for "36th", "1st", "2nd", "3rd".
+fn ordinalToken(token: []const u8) ?u32 {
+    if (token.len < 3) return null;
+    const suffix = token[token.len - 2 ..];
+    const suffixes = [_][]const u8{ "st", "nd", "rd", "th" };
+    const is_ordinal = for (suffixes) |s| {
+        if (std.ascii.eqlIgnoreCase(suffix, s)) break true;
+    } else false;
+    if (!is_ordinal) return null;
+    return std.fmt.parseInt(u32, token[0 .. token.len - 2], 10) catch null;
+}
+
+/// Whether a venue name is written in abbreviated form ("J. Mach. Learn. Res.").
+pub fn looksAbbreviated(name: []const u8) bool {
+    for (name, 0..) |c, i| {
+        if (c == '.' and (i + 1 == name.len or name[i + 1] == ' ')) return true;
+    }
+    return false;
+}
+
+/// Lowercase, drop punctuation and a leading "the", and collapse spaces.
+/// Names longer than `buf` are truncated.
+pub fn normalize(name: []const u8, buf: []u8) []const u8 {
+    var len: usize = 0;
+    var pending_space = false;
+    for (name) |c| {
+        if (std.ascii.isAlphanumeric(c)) {
+            if (pending_space and len > 0 and len < buf.len) {
+                buf[len] = ' ';
+                len += 1;
+            }
+            pending_space = false;
+            if (len < buf.len) {
+                buf[len] = std.ascii.toLower(c);
+                len += 1;
+            }
+        } else {
+            pending_space = true;
+        }
+    }
+    const result = buf[0..len];
+    return if (std.mem.startsWith(u8, result, "the ")) result[4..] else result;
+}
+
+test "venue lookup and matching" {
+    const jmlr = lookup("J. Mach. Learn. Res.").?;
+    try std.testing.expectEqualStrings("Journal of Machine Learning Research", jmlr.full);
+    try std.testing.expect(lookup("JMLR") == jmlr);
+    try std.testing.expect(lookup("Journal of Unknown Results") == null);
+
+    const icml = lookup("International Conference on Machine Learning").?;
+    try std.testing.expect(icml.matches("Proceedings of the 36th International Conference on Machine Learning"));
+    try std.testing.expect(icml.matches("ICML"));
+    try std.testing.expect(!icml.matches("NeurIPS"));
+
+    try std.testing.expectEqualStrings("J. Mach. Learn. Res.", jmlr.preferred(.preserve, "Mach. Learn."));
+    try std.testing.expectEqualStrings("Journal of Machine Learning Research", jmlr.preferred(.preserve, "Machine Learning"));
+    try std.testing.expectEqualStrings("J. Mach. Learn. Res.", jmlr.preferred(.abbreviated, null));
+}
+
+test "conference series and editions" {
+    try std.testing.expect(sameSeries("ICML 2019", "Proceedings of the 37th International Conference on Machine Learning"));
+    try std.testing.expect(sameSeries("Proc. of the 12th Symposium on Graph Drawing", "Symposium on Graph Drawing 2005"));
+    try std.testing.expect(!sameSeries("ICML 2020 Workshop on Uncertainty", "ICML 2020"));
+    try std.testing.expect(!sameSeries("ICML 2019", "NeurIPS 2019"));
+    try std.testing.expect(!sameSeries("International Conference on Machine Learning", "Machine Learning"));
+
+    const e = edition("Proceedings of the 36th International Conference on Machine Learning, ICML 2019");
+    try std.testing.expectEqual(@as(?i32, 2019), e.year);
+    try std.testing.expectEqual(@as(?u32, 36), e.number);
+    try std.testing.expect(isWorkshop("NeurIPS Workshop on Deep Learning"));
+}
+
+test "workshops of a conference" {
+    try std.testing.expect(workshopOf("ICML 2020 Workshop on Uncertainty and Robustness", "International Conference on Machine Learning"));
+    try std.testing.expect(workshopOf("NeurIPS 2019 Workshop on Bayesian Deep Learning", "Advances in Neural Information Processing Systems 32"));
+    try std.testing.expect(workshopOf("Workshop on Graph Learning at the Web Conference", "The Web Conference 2021"));
+    try std.testing.expect(!workshopOf("ICML 2020 Workshop on Uncertainty", "NeurIPS"));
+    try std.testing.expect(!workshopOf("ICML 2020", "ICML 2019"));
+}
@@ -0,0 +1,167 @@
+//! Cached verdicts for unchanged entries.
+//!
+//! The response cache saves the API round trips, but every entry is still
+//! matched and compared on each run. A verdict is the finished report of
+//! one entry, cached under a hash of everything that decides it: the
+//! entry's own fields, the validator version, and the settings of the run.
+//! Editing an entry changes only its own hash, so only that entry is
+//! validated again; changing a threshold, the enabled backends, or a
+//! setting of the config file or its profile, or upgrading bibval, changes
+//! every hash at once.
+//!
+//! Verdicts are stored with the API responses and expire with them.
+
+const std = @import("std");
+const cache_mod = @import("cache.zig");
+const entry_mod = @import("entry.zig");
+const journal = @import("journal.zig");
+const matcher = @import("matcher.zig");
+const plan = @import("plan.zig");
+const report_mod = @import("report.zig");
+const rules = @import("rules.zig");
+const Cache = cache_mod.Cache;
+const Entry = entry_mod.Entry;
+const EntryReport = report_mod.EntryReport;
+
+/// Validator version. Bump whenever matching or a check changes what a
+/// run reports, so verdicts from older versions are not reused.
+pub const VERSION: u32 = 2;
+
+/// Cache namespace of verdict records.
+const API = "verdict";
+
+/// Run settings that change verdicts.
+pub const Settings = struct {
+    backends: plan.Backends,
+    disabled: rules.RuleSet,
+    min_confidence: f64,
+    check_funders: bool,
+    open_access: bool,
+    matching: *const matcher.MatcherConfig,
+    /// `Config.fingerprint` of the config, which holds the remaining settings.
+    config: u64 = 0,
+
+    /// Hash of the settings and the validator version.
+    pub fn fingerprint(self: *const Settings) u64 {
+        var hasher = std.hash.Wyhash.init(VERSION);
+        std.hash.autoHash(&hasher, self.backends);
+        std.hash.autoHash(&hasher, self.disabled.bits.mask);
+        hasher.update(std.mem.asBytes(&self.min_confidence));
+        std.hash.autoHash(&hasher, self.check_funders);
+        std.hash.autoHash(&hasher, self.open_access);
+        std.hash.autoHash(&hasher, self.matching.max_year_distance);
+        hashMetric(&hasher, self.matching.title_similarity);
+        hashMetric(&hasher, self.matching.author_similarity);
+        hasher.update(std.mem.asBytes(&self.matching.weights));
+        std.hash.autoHash(&hasher, self.matching.doi_boost);
+        std.hash.autoHash(&hasher, self.matching.filters);
+        for (self.matching.comparators) |c| {
+            hasher.update(c.name);
+            hasher.update(&[_]u8{0});
+        }
+        std.hash.autoHash(&hasher, self.config);
+        return hasher.final();
+    }
+
+    /// Metrics are hashed by name, since function addresses change from
+    /// one build to the next.
+    fn hashMetric(hasher: *std.hash.Wyhash, metric: matcher.Similarity) void {
+        for (matcher.metric_names) |m| {
+            if (m.metric.compareFn == metric.compareFn) return hasher.update(m.name);
+        }
+        hasher.update("custom");
+    }
+};
+
+pub const Verdicts = struct {
+    allocator: std.mem.Allocator,
+    cache: *Cache,
+    fingerprint: u64,
+
+    pub fn init(allocator: std.mem.Allocator, cache: *Cache, settings: Settings) Verdicts {
+        return .{ .allocator = allocator, .cache = cache, .fingerprint = settings.fingerprint() };
+    }
+
+    /// The cached report of `local`, or null if it has none under the
+    /// current settings or was edited since.
+    pub fn restore(self: *Verdicts, local: *const Entry) !?EntryReport {
+        var buf: [48]u8 = undefined;
+        const cached = self.cache.get(API, self.query(&buf, local)) orelse return null;
+        defer self.allocator.free(cached);
+
+        const parsed = std.json.parseFromSlice(std.json.Value, self.allocator, cached, .{}) catch return null;
+        defer parsed.deinit();
+
+        return journal.parseRecord(self.allocator, parsed.value, local);
+    }
+
+    /// Cache the report of a validated entry. Reports with a failed
+    /// lookup are not cached, so the next run tries them again.
+    pub fn store(self: *Verdicts, entry_report: *const EntryReport) !void {
+        switch (entry_report.status) {
+            .failed, .known_unmatched => return,
+            else => {},
+        }
+        for (entry_report.lookups) |lookup| {
+            if (lookup.outcome == .failed) return;
+        }
+
+        var aw: std.Io.Writer.Allocating = .init(self.allocator);
+        defer aw.deinit();
+        try journal.writeRecord(&aw.writer, entry_report);
+
+        var buf: [48]u8 = undefined;
+        self.cache.set(API, self.query(&buf, &entry_report.entry), aw.written()) catch {};
+    }
+
+    fn query(self: *const Verdicts, buf: *[48]u8, local: *const Entry) []const u8 {
+        return std.fmt.bufPrint(buf, "{x:0>16}:{x:0>16}", .{ self.fingerprint, local.contentHash() }) catch unreachable;
+    }
+};
+
+test "verdicts follow entry content and settings" {
+    const allocator = std.testing.allocator;
+
+    var tmp = std.testing.tmpDir(.{});
+    defer tmp.cleanup();
+    const dir = try tmp.dir.realpathAlloc(allocator, ".");
+    var cache = Cache{ .cache_dir = dir, .enabled = true, .allocator = allocator };
+    defer cache.deinit();
+
+    const matching = matcher.MatcherConfig{};
+    const settings = Settings{ .backends = .{}, .disabled = .initEmpty(), .min_confidence = 0.0, .check_funders = false, .open_access = false, .matching = &matching };
+    var verdicts = Verdicts.init(allocator, &cache, settings);
+
+    const local = Entry{ .key = "smith2021", .entry_type = "article", .title = "A Great Paper", .year = 2021 };
+    var results = [_]entry_mod.ValidationResult{.{ .source = .crossref, .confidence = 0.95 }};
+    try verdicts.store(&.{ .entry = local, .status = .{ .ok = .crossref }, .validation_results = &results });
+
+    var restored = (try verdicts.restore(&local)).?;
+    defer restored.deinit();
+    try std.testing.expect(restored.status == .ok);
+
+    // Editing the entry invalidates its verdict
+    const edited = Entry{ .key = "smith2021", .entry_type = "article", .title = "A Great Paper", .year = 2020 };
+    try std.testing.expect((try verdicts.restore(&edited)) == null);
+
+    // So does changing a setting
+    var stricter = settings;
+    stricter.min_confidence = 0.8;
+    var other = Verdicts.init(allocator, &cache, stricter);
+    try std.testing.expect((try other.restore(&local)) == null);
+
+    var weighted_matching = matching;
+    weighted_matching.weights.title = 0.5;
+    var weighted = settings;
+    weighted.matching = &weighted_matching;
+    try std.testing.expect(weighted.fingerprint() != settings.fingerprint());
+
+    // A report with a failed lookup is not kept, even if another matched
+    const partial = Entry{ .key = "doe2020", .entry_type = "article", .title = "Another Paper", .year = 2020 };
+    var lookups = [_]report_mod.Lookup{
+        .{ .strategy = .doi_lookup, .outcome = .failed },
+        .{ .strategy = .title_search, .outcome = .matched },
+    };
+    try verdicts.store(&.{ .entry = partial, .status = .{ .ok = .crossref }, .validation_results = &results, .lookups = &lookups });
+    try std.testing.expect((try verdicts.restore(&partial)) == null);
+}
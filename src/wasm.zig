@@ -0,0 +1,142 @@
+//! WebAssembly exports for validating in the browser.
+//!
+//! Built by `zig build wasm` into `zig-out/bin/bibval.wasm` from the
+//! `bibval-core` module, so it has no network access: the host fetches
+//! remote records itself (e.g. from `bibval serve`'s `/resolve`, or a
+//! stored snapshot) and passes them in the snapshot format.
+//!
+//! Strings cross the boundary as pointer and length pairs in linear
+//! memory. Each call returns the length of its JSON result, which stays at
+//! `bibval_result()` until the next call:
+//!
+//!     const ptr = bibval_alloc(len);           // copy UTF-8 input here
+//!     const out_len = bibval_parse(ptr, len);  // then read out_len bytes
+//!     const out = bibval_result();             // from out
+//!     bibval_free(ptr, len);
+//!
+//! Failures are returned as `{"error":"Name"}`.
+
+const std = @import("std");
+const core = @import("bibval-core");
+
+const allocator = std.heap.wasm_allocator;
+
+/// Holds the current result and everything computed for it.
+var result_arena = std.heap.ArenaAllocator.init(allocator);
+var result: []const u8 = "";
+
+/// Allocate `len` bytes for an input string.
+export fn bibval_alloc(len: usize) ?[*]u8 {
+    const buf = allocator.alloc(u8, len) catch return null;
+    return buf.ptr;
+}
+
+/// Free an input string allocated with `bibval_alloc`.
+export fn bibval_free(ptr: [*]u8, len: usize) void {
+    allocator.free(ptr[0..len]);
+}
+
+/// The result of the last call.
+export fn bibval_result() [*]const u8 {
+    return result.ptr;
+}
+
+/// Parse a BibTeX, CSL-JSON or RIS reference list (detected from the
+/// content) into a JSON array of entries.
+export fn bibval_parse(ptr: [*]const u8, len: usize) usize {
+    return respond(parse, .{ptr[0..len]});
+}
+
+/// Compare a reference list with remote records in the snapshot format
+/// (`{"version":1,"records":{"key":[{"source","confidence","entry"}]}}`).
+/// Returns each entry's status, discrepancies and suggested fixes, and the
+/// BibTeX with the fixes applied.
+export fn bibval_check(ptr: [*]const u8, len: usize, snapshot_ptr: [*]const u8, snapshot_len: usize) usize {
+    return respond(check, .{ ptr[0..len], snapshot_ptr[0..snapshot_len] });
+}
+
+fn respond(comptime func: anytype, args: anytype) usize {
+    _ = result_arena.reset(.retain_capacity);
+    const arena = result_arena.allocator();
+    var out: std.Io.Writer.Allocating = .init(arena);
+    @call(.auto, func, .{ arena, &out.writer } ++ args) catch |err| {
+        out.clearRetainingCapacity();
+        out.writer.print("{{\"error\":\"{s}\"}}", .{@errorName(err)}) catch {};
+    };
+    result = out.written();
+    return result.len;
+}
+
+fn parseEntries(arena: std.mem.Allocator, raw: []const u8) !struct { text: []const u8, format: core.input.Format, entries: []core.Entry } {
+    const decoded = try core.input.decode(arena, raw, null);
+    const format = core.input.detect(decoded.text);
+    return .{ .text = decoded.text, .format = format, .entries = try core.input.parse(arena, decoded.text, format, null) };
+}
+
+fn parse(arena: std.mem.Allocator, w: *std.Io.Writer, raw: []const u8) !void {
+    const parsed = try parseEntries(arena, raw);
+    try w.writeByte('[');
+    for (parsed.entries, 0..) |*e, i| {
+        if (i > 0) try w.writeByte(',');
+        try core.snapshot.writeEntry(w, e);
+    }
+    try w.writeByte(']');
+}
+
+fn check(arena: std.mem.Allocator, w: *std.Io.Writer, raw: []const u8, snapshot_json: []const u8) !void {
+    const parsed = try parseEntries(arena, raw);
+    const snap = try core.snapshot.Snapshot.parse(arena, snapshot_json);
+
+    var fixes: std.ArrayList(core.fix.Fix) = .empty;
+    try w.writeAll("{\"entries\":[");
+    for (parsed.entries, 0..) |*local, i| {
+        var results: std.ArrayList(core.ValidationResult) = .empty;
+        for (snap.get(local.key) orelse &.{}) |*record| {
+            try results.append(arena, .{
+                .source = record.source,
+                .matched_entry = record.entry,
+                .confidence = record.confidence,
+                .discrepancies = try core.matcher.compareEntries(arena, local, &record.entry),
+            });
+        }
+        const entry_report = core.report.EntryReport{
+            .entry = local.*,
+            .status = core.report.statusOf(results.items),
+            .validation_results = results.items,
+        };
+
+        if (i > 0) try w.writeByte(',');
+        try w.writeAll("{\"key\":");
+        try core.report.writeJsonString(w, local.key);
+        try w.print(",\"status\":\"{s}\",\"discrepancies\":[", .{@tagName(entry_report.status)});
+        var first = true;
+        for (results.items) |r| {
+            for (r.discrepancies) |d| {
+                if (!first) try w.writeByte(',');
+                first = false;
+                const confidence = r.confidence * d.rule.signalQuality() * r.source.reliability();
+                try w.print("{{\"rule\":\"{s}\",\"rule_name\":\"{s}\",\"source\":\"{s}\",\"field\":\"{s}\",\"severity\":\"{s}\",\"confidence\":{d:.2},\"message\":", .{ d.rule.id(), d.rule.slug(), @tagName(r.source), d.field.name(), d.severity.name(), confidence });
+                try core.report.writeJsonString(w, d.message);
+                try w.writeByte('}');
+            }
+        }
+        try w.writeAll("],\"fixes\":[");
+        const entry_fixes = fixes.items.len;
+        try core.fix.suggest(arena, &entry_report, .{}, &fixes);
+        for (fixes.items[entry_fixes..], 0..) |f, j| {
+            if (j > 0) try w.writeByte(',');
+            try w.print("{{\"field\":\"{s}\",\"value\":", .{f.field});
+            try core.report.writeJsonString(w, f.value);
+            try w.writeByte('}');
+        }
+        try w.writeAll("]}");
+    }
+    try w.writeAll("],\"fixed\":");
+    if (parsed.format == .bibtex) {
+        const edits = try core.fix.editsFor(arena, parsed.text, fixes.items);
+        try core.report.writeJsonString(w, try core.fix.apply(arena, parsed.text, edits));
+    } else {
+        try w.writeAll("null");
+    }
+    try w.writeByte('}');
+}
@@ -0,0 +1,138 @@
+//! Wayback Machine snapshots of cited web pages.
+//!
+//! Web pages move and disappear; a citation that gives an archived copy
+//! along with the live URL stays checkable. For web citations without one,
+//! the snapshot closest to the entry's `urldate` (the date the page was
+//! read) is suggested as an `archiveurl` field, and with `--archive-urls`
+//! pages that were never archived are saved with Save Page Now.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const entry_mod = @import("entry.zig");
+const validators = @import("validators.zig");
+const ignores = @import("ignores.zig");
+const Entry = entry_mod.Entry;
+const ValidatorError = validators.ValidatorError;
+
+/// BibTeX field the snapshot is written to. Not a standard field, so
+/// styles that do not know it leave it out.
+pub const FIELD = "archiveurl";
+
+/// Whether an entry already cites an archived copy: an archive field, or
+/// a URL on an archive.
+pub fn isArchived(e: *const Entry) bool {
+    for ([_][]const u8{ FIELD, "archive_url", "archived" }) |name| {
+        if (e.extraField(name) != null) return true;
+    }
+    const url = e.url orelse return false;
+    for ([_][]const u8{ "web.archive.org/", "archive.today/", "archive.ph/", "archive.is/", "webcitation.org/" }) |host| {
+        if (std.ascii.indexOfIgnoreCase(url, host) != null) return true;
+    }
+    return false;
+}
+
+/// The Wayback Machine availability API and Save Page Now.
+pub const Wayback = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    response_cache: *cache.Cache,
+    /// Archive pages that have no snapshot (--archive-urls)
+    save: bool,
+
+    const AVAILABLE_URL = "https://archive.org/wayback/available";
+    const WEB_URL = "https://web.archive.org/web";
+    const SAVE_URL = "https://web.archive.org/save";
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache, save: bool) Wayback {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .response_cache = response_cache,
+            .save = save,
+        };
+    }
+
+    /// The snapshot of `url` closest to `date`, or the newest one. Null if
+    /// the page was never archived. Caller owns the URL.
+    pub fn snapshot(self: *Wayback, url: []const u8, date: ?ignores.Date) !?[]u8 {
+        const encoded = try http.urlEncode(self.allocator, url);
+        defer self.allocator.free(encoded);
+        const query = if (date) |d|
+            try std.fmt.allocPrint(self.allocator, "url={s}&timestamp={d:0>4}{d:0>2}{d:0>2}", .{ encoded, @as(u32, @intCast(@max(0, d.year))), d.month, d.day })
+        else
+            try std.fmt.allocPrint(self.allocator, "url={s}", .{encoded});
+        defer self.allocator.free(query);
+
+        const body = if (self.response_cache.get("wayback", query)) |cached| cached else blk: {
+            const request = try std.fmt.allocPrint(self.allocator, "{s}?{s}", .{ AVAILABLE_URL, query });
+            defer self.allocator.free(request);
+            const fetched = self.client.get(request) catch |err| {
+                return switch (err) {
+                    http.HttpError.RateLimited => ValidatorError.RateLimited,
+                    else => ValidatorError.RequestFailed,
+                };
+            };
+            self.response_cache.set("wayback", query, fetched) catch {};
+            break :blk fetched;
+        };
+        defer self.allocator.free(body);
+        return parseAvailability(self.allocator, body);
+    }
+
+    /// Archive `url` with Save Page Now. Returns a URL that resolves to
+    /// the newest snapshot; caller owns it.
+    pub fn archive(self: *Wayback, url: []const u8) ![]u8 {
+        const request = try std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ SAVE_URL, url });
+        defer self.allocator.free(request);
+        const body = self.client.get(request) catch |err| {
+            return switch (err) {
+                http.HttpError.RateLimited => ValidatorError.RateLimited,
+                else => ValidatorError.RequestFailed,
+            };
+        };
+        self.allocator.free(body);
+        return std.fmt.allocPrint(self.allocator, "{s}/{s}", .{ WEB_URL, url });
+    }
+};
+
+/// The closest snapshot in an availability response, with an https URL.
+pub fn parseAvailability(allocator: std.mem.Allocator, json: []const u8) !?[]u8 {
+    const parsed = std.json.parseFromSlice(std.json.Value, allocator, json, .{}) catch return null;
+    defer parsed.deinit();
+    if (parsed.value != .object) return null;
+
+    const snapshots = parsed.value.object.get("archived_snapshots") orelse return null;
+    if (snapshots != .object) return null;
+    const closest = snapshots.object.get("closest") orelse return null;
+    if (closest != .object) return null;
+
+    if (closest.object.get("available")) |available| {
+        if (available != .bool or !available.bool) return null;
+    }
+    const url = closest.object.get("url") orelse return null;
+    if (url != .string) return null;
+    if (std.mem.startsWith(u8, url.string, "http://")) {
+        return try std.fmt.allocPrint(allocator, "https://{s}", .{url.string["http://".len..]});
+    }
+    return try allocator.dupe(u8, url.string);
+}
+
+test "Wayback snapshots" {
+    const allocator = std.testing.allocator;
+
+    const found = (try parseAvailability(allocator,
+        \\{"url": "example.org/post", "archived_snapshots": {"closest": {"status": "200", "available": true,
+        \\ "url": "http://web.archive.org/web/20210304000000/https://example.org/post", "timestamp": "20210304000000"}}}
+    )).?;
+    defer allocator.free(found);
+    try std.testing.expectEqualStrings("https://web.archive.org/web/20210304000000/https://example.org/post", found);
+    try std.testing.expect((try parseAvailability(allocator,
+        \\{"url": "example.org/new", "archived_snapshots": {}}
+    )) == null);
+
+    const live = Entry{ .key = "a", .entry_type = "online", .url = "https://example.org/post" };
+    try std.testing.expect(!isArchived(&live));
+    const archived = Entry{ .key = "b", .entry_type = "online", .url = "https://example.org/post", .extra = &.{.{ .name = "archiveurl", .value = "https://web.archive.org/web/2021/https://example.org/post" }} };
+    try std.testing.expect(isArchived(&archived));
+}
@@ -0,0 +1,373 @@
+//! Validator for web pages cited by URL.
+//!
+//! Blog posts, standards, and documentation are cited as `@misc` or
+//! `@online` entries with nothing but a URL to look them up by. Most such
+//! pages describe themselves in `<meta>` tags: Highwire Press
+//! (`citation_title`, `citation_author`, `citation_date`), Dublin Core
+//! (`DC.title`, `DC.creator`, `DC.date`), or OpenGraph (`og:title`). The
+//! page's record is built from the first of these it has, Highwire first.
+
+const std = @import("std");
+const http = @import("http.zig");
+const cache = @import("cache.zig");
+const scrape = @import("scrape.zig");
+const markup = @import("markup.zig");
+const entry_mod = @import("entry.zig");
+const validators = @import("validators.zig");
+const Entry = entry_mod.Entry;
+const ValidatorError = validators.ValidatorError;
+
+/// Redirects followed from a cited URL before giving up on it.
+const MAX_REDIRECTS = 5;
+
+/// Whether an entry is a web page to look up by its URL: a `@misc` or
+/// `@online` entry with a URL and no DOI.
+pub fn isWebEntry(e: *const Entry) bool {
+    if (e.url == null or e.doi != null) return false;
+    const types = [_][]const u8{ "misc", "online", "electronic", "www" };
+    for (types) |t| {
+        if (std.ascii.eqlIgnoreCase(e.entry_type, t)) return true;
+    }
+    return false;
+}
+
+/// Fetches pages and reads their metadata.
+pub const Web = struct {
+    allocator: std.mem.Allocator,
+    scraper: scrape.Scraper,
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, response_cache: *cache.Cache) Web {
+        return .{
+            .allocator = allocator,
+            .scraper = scrape.Scraper.init(allocator, client, response_cache),
+        };
+    }
+
+    /// The record of the page at `url`, or null if it has no title metadata,
+    /// the site's robots.txt disallows fetching it, or its host is not on
+    /// the public internet. Only the metadata of a page is cached.
+    pub fn searchByUrl(self: *Web, url: []const u8) !?Entry {
+        if (self.scraper.response_cache.get("web_page", url)) |cached| {
+            defer self.allocator.free(cached);
+            return parsePage(self.allocator, cached, url);
+        }
+
+        const body = try self.fetchPublic(url) orelse return null;
+        defer self.allocator.free(body);
+
+        const metadata = try metadataTags(self.allocator, body);
+        defer self.allocator.free(metadata);
+        self.scraper.response_cache.set("web_page", url, metadata) catch {};
+        return parsePage(self.allocator, metadata, url);
+    }
+
+    /// The page at `url`, following redirects only to public hosts. Null
+    /// if it is not found or leads off the public internet.
+    fn fetchPublic(self: *Web, url: []const u8) !?[]u8 {
+        var target = try self.allocator.dupe(u8, url);
+        defer self.allocator.free(target);
+        var redirects: usize = 0;
+        while (true) : (redirects += 1) {
+            // A cited URL is fetched for whoever submitted the references,
+            // so neither it nor a redirect from it may reach into the
+            // network bibval runs in
+            if (self.scraper.client.transport == null and !isPublicUrl(self.allocator, target)) {
+                if (self.scraper.client.tracer) |t| t.event(.debug, "web", "{s} is not a public address", .{target});
+                return null;
+            }
+
+            const fetched = (self.scraper.fetchUnredirected(target) catch |err| switch (err) {
+                ValidatorError.NotFound => return null,
+                else => return err,
+            }) orelse return null;
+            switch (fetched) {
+                .body => |body| return body,
+                .redirect => |location| {
+                    defer self.allocator.free(location);
+                    if (redirects >= MAX_REDIRECTS) return null;
+                    const next = try http.resolveUrl(self.allocator, target, location);
+                    self.allocator.free(target);
+                    target = next;
+                },
+            }
+        }
+    }
+};
+
+/// Whether every address the host of `url` resolves to is public: not
+/// private, loopback, or link-local.
+fn isPublicUrl(allocator: std.mem.Allocator, url: []const u8) bool {
+    const uri = std.Uri.parse(url) catch return false;
+    var host_buf: [256]u8 = undefined;
+    const host = (uri.host orelse return false).toRaw(&host_buf) catch return false;
+    const port = uri.port orelse if (std.ascii.eqlIgnoreCase(uri.scheme, "http")) @as(u16, 80) else 443;
+    const list = std.net.getAddressList(allocator, host, port) catch return false;
+    defer list.deinit();
+    if (list.addrs.len == 0) return false;
+    for (list.addrs) |address| {
+        if (!isPublicAddress(address)) return false;
+    }
+    return true;
+}
+
+fn isPublicAddress(address: std.net.Address) bool {
+    return switch (address.any.family) {
+        std.posix.AF.INET => isPublicIp4(@bitCast(address.in.sa.addr)),
+        std.posix.AF.INET6 => {
+            const bytes = address.in6.sa.addr;
+            // IPv4-mapped, ::ffff:a.b.c.d
+            if (std.mem.allEqual(u8, bytes[0..10], 0) and bytes[10] == 0xff and bytes[11] == 0xff) return isPublicIp4(bytes[12..16].*);
+            // Unspecified and loopback, :: and ::1
+            if (std.mem.allEqual(u8, bytes[0..15], 0)) return false;
+            // Link-local fe80::/10 and unique local fc00::/7
+            if (bytes[0] == 0xfe and bytes[1] & 0xc0 == 0x80) return false;
+            return bytes[0] & 0xfe != 0xfc;
+        },
+        else => false,
+    };
+}
+
+fn isPublicIp4(bytes: [4]u8) bool {
+    return switch (bytes[0]) {
+        // "This network", private, and loopback
+        0, 10, 127 => false,
+        // Shared address space of carrier-grade NAT
+        100 => bytes[1] & 0xc0 != 64,
+        169 => bytes[1] != 254,
+        172 => bytes[1] & 0xf0 != 16,
+        192 => bytes[1] != 168,
+        else => true,
+    };
+}
+
+/// Metadata schemes, in order of preference.
+const Scheme = enum { highwire, dublin_core, opengraph };
+
+const Field = enum { title, author, date, publisher };
+
+/// The scheme and field a `<meta>` name or property stands for.
+fn classify(name: []const u8) ?struct { Scheme, Field } {
+    const table = [_]struct { []const u8, Scheme, Field }{
+        .{ "citation_title", .highwire, .title },
+        .{ "citation_author", .highwire, .author },
+        .{ "citation_date", .highwire, .date },
+        .{ "citation_publication_date", .highwire, .date },
+        .{ "citation_online_date", .highwire, .date },
+        .{ "citation_publisher", .highwire, .publisher },
+        .{ "dc.title", .dublin_core, .title },
+        .{ "dcterms.title", .dublin_core, .title },
+        .{ "dc.creator", .dublin_core, .author },
+        .{ "dcterms.creator", .dublin_core, .author },
+        .{ "dc.date", .dublin_core, .date },
+        .{ "dcterms.date", .dublin_core, .date },
+        .{ "dcterms.issued", .dublin_core, .date },
+        .{ "dc.publisher", .dublin_core, .publisher },
+        .{ "dcterms.publisher", .dublin_core, .publisher },
+        .{ "og:title", .opengraph, .title },
+        .{ "article:author", .opengraph, .author },
+        .{ "article:published_time", .opengraph, .date },
+        .{ "og:site_name", .opengraph, .publisher },
+    };
+    for (table) |row| {
+        if (std.ascii.eqlIgnoreCase(name, row[0])) return .{ row[1], row[2] };
+    }
+    return null;
+}
+
+/// The `<meta>` tags of a page in one of the schemes.
+const MetaTags = struct {
+    html: []const u8,
+    pos: usize = 0,
+
+    const Tag = struct {
+        /// The whole tag, from `<meta` to `>`.
+        raw: []const u8,
+        scheme: Scheme,
+        field: Field,
+        content: []const u8,
+    };
+
+    fn next(self: *MetaTags) ?Tag {
+        while (std.ascii.indexOfIgnoreCasePos(self.html, self.pos, "<meta")) |start| {
+            const end = std.mem.indexOfScalarPos(u8, self.html, start, '>') orelse return null;
+            self.pos = end + 1;
+            const tag = self.html[start + "<meta".len .. end];
+            const name = attribute(tag, "name") orelse attribute(tag, "property") orelse continue;
+            const content = attribute(tag, "content") orelse continue;
+            const kind = classify(name) orelse continue;
+            return .{ .raw = self.html[start .. end + 1], .scheme = kind[0], .field = kind[1], .content = content };
+        }
+        return null;
+    }
+};
+
+/// The `<meta>` tags of `html` that `parsePage` reads, one per line: what
+/// is kept of a page in the response cache.
+pub fn metadataTags(allocator: std.mem.Allocator, html: []const u8) ![]u8 {
+    var out: std.Io.Writer.Allocating = .init(allocator);
+    defer out.deinit();
+    var tags = MetaTags{ .html = html };
+    while (tags.next()) |tag| {
+        try out.writer.writeAll(tag.raw);
+        try out.writer.writeByte('\n');
+    }
+    return out.toOwnedSlice();
+}
+
+/// Build a `misc` record from the metadata of an HTML page. Null if the
+/// page gives no title in any scheme.
+pub fn parsePage(allocator: std.mem.Allocator, html: []const u8, url: []const u8) !?Entry {
+    var arena_state = std.heap.ArenaAllocator.init(allocator);
+    defer arena_state.deinit();
+    const arena = arena_state.allocator();
+
+    // Values per scheme and field, in page order
+    var values: [3][4]std.ArrayList([]const u8) = undefined;
+    for (&values) |*row| {
+        for (row) |*list| list.* = .empty;
+    }
+
+    var tags = MetaTags{ .html = html };
+    while (tags.next()) |tag| {
+        const text = std.mem.trim(u8, try markup.decodeEntities(arena, tag.content), " \t\r\n");
+        if (text.len == 0) continue;
+        try values[@intFromEnum(tag.scheme)][@intFromEnum(tag.field)].append(arena, text);
+    }
+
+    const title = first(&values, .title) orelse return null;
+
+    var result = Entry{
+        .key = "",
+        .entry_type = try allocator.dupe(u8, "misc"),
+        .allocator = allocator,
+    };
+    errdefer result.deinit();
+    result.title = try allocator.dupe(u8, title);
+    result.url = try allocator.dupe(u8, url);
+
+    for (values) |row| {
+        const authors = row[@intFromEnum(Field.author)].items;
+        if (authors.len == 0) continue;
+        const owned = try allocator.alloc([]const u8, authors.len);
+        for (owned, 0..) |*slot, i| {
+            slot.* = allocator.dupe(u8, authors[i]) catch |err| {
+                for (owned[0..i]) |a| allocator.free(a);
+                allocator.free(owned);
+                return err;
+            };
+        }
+        result.authors = owned;
+        break;
+    }
+
+    if (first(&values, .date)) |date| result.year = leadingYear(date);
+    if (first(&values, .publisher)) |publisher| result.publisher = try allocator.dupe(u8, publisher);
+    return result;
+}
+
+/// The first value of a field, from the most preferred scheme that has one.
+fn first(values: *const [3][4]std.ArrayList([]const u8), field: Field) ?[]const u8 {
+    for (values) |row| {
+        const list = row[@intFromEnum(field)].items;
+        if (list.len > 0) return list[0];
+    }
+    return null;
+}
+
+/// The year a date starts with: 2019 for "2019/05/01", "2019-05-01T10:00Z",
+/// or "2019".
+fn leadingYear(date: []const u8) ?i32 {
+    if (date.len < 4) return null;
+    if (date.len > 4 and std.ascii.isDigit(date[4])) return null;
+    return std.fmt.parseInt(i32, date[0..4], 10) catch null;
+}
+
+/// The value of attribute `name` in the inside of a tag, quoted or not.
+fn attribute(tag: []const u8, name: []const u8) ?[]const u8 {
+    var i: usize = 0;
+    while (i < tag.len) {
+        while (i < tag.len and (std.ascii.isWhitespace(tag[i]) or tag[i] == '/')) i += 1;
+        const name_start = i;
+        while (i < tag.len and tag[i] != '=' and !std.ascii.isWhitespace(tag[i]) and tag[i] != '/') i += 1;
+        const attr = tag[name_start..i];
+        if (attr.len == 0) {
+            i += 1;
+            continue;
+        }
+        while (i < tag.len and std.ascii.isWhitespace(tag[i])) i += 1;
+        if (i >= tag.len or tag[i] != '=') continue;
+        i += 1;
+        while (i < tag.len and std.ascii.isWhitespace(tag[i])) i += 1;
+
+        var value: []const u8 = "";
+        if (i < tag.len and (tag[i] == '"' or tag[i] == '\'')) {
+            const quote = tag[i];
+            const value_end = std.mem.indexOfScalarPos(u8, tag, i + 1, quote) orelse tag.len;
+            value = tag[i + 1 .. value_end];
+            i = @min(value_end + 1, tag.len);
+        } else {
+            const value_start = i;
+            while (i < tag.len and !std.ascii.isWhitespace(tag[i])) i += 1;
+            value = tag[value_start..i];
+        }
+        if (std.ascii.eqlIgnoreCase(attr, name)) return value;
+    }
+    return null;
+}
+
+test "web page metadata" {
+    const allocator = std.testing.allocator;
+
+    const highwire =
+        \\<html><head>
+        \\<meta property="og:title" content="A Post | Example Blog">
+        \\<meta name="citation_title" content="Attention &amp; Memory">
+        \\<meta name="citation_author" content="Doe, Jane">
+        \\<meta name="citation_author" content="Roe, Richard">
+        \\<meta name=citation_date content="2019/05/01" />
+        \\</head></html>
+    ;
+    var page = (try parsePage(allocator, highwire, "https://example.org/post")).?;
+    defer page.deinit();
+    try std.testing.expectEqualStrings("Attention & Memory", page.title.?);
+    try std.testing.expectEqual(@as(usize, 2), page.authors.len);
+    try std.testing.expectEqualStrings("Roe, Richard", page.authors[1]);
+    try std.testing.expectEqual(@as(?i32, 2019), page.year);
+
+    const opengraph =
+        \\<meta property='og:title' content='RFC 9110: HTTP Semantics'>
+        \\<meta property="article:published_time" content="2022-06-01T00:00:00Z">
+    ;
+    var og = (try parsePage(allocator, opengraph, "https://example.org/rfc")).?;
+    defer og.deinit();
+    try std.testing.expectEqualStrings("RFC 9110: HTTP Semantics", og.title.?);
+    try std.testing.expectEqual(@as(?i32, 2022), og.year);
+    try std.testing.expectEqual(@as(usize, 0), og.authors.len);
+
+    try std.testing.expect((try parsePage(allocator, "<html><title>x</title></html>", "https://example.org")) == null);
+
+    // Only the tags read are cached, and read back the same
+    const kept = try metadataTags(allocator, "<html><head><meta charset=\"utf-8\">" ++ highwire ++ "<body>" ++ "x" ** 4096 ++ "</body>");
+    defer allocator.free(kept);
+    try std.testing.expect(kept.len < highwire.len);
+    var cached = (try parsePage(allocator, kept, "https://example.org/post")).?;
+    defer cached.deinit();
+    try std.testing.expectEqualStrings("Attention & Memory", cached.title.?);
+    try std.testing.expectEqual(@as(usize, 2), cached.authors.len);
+
+    const online = Entry{ .key = "a", .entry_type = "online", .url = "https://example.org" };
+    try std.testing.expect(isWebEntry(&online));
+    const article = Entry{ .key = "b", .entry_type = "article", .url = "https://example.org" };
+    try std.testing.expect(!isWebEntry(&article));
+}
+
+test "only public addresses are fetched" {
+    for ([_][]const u8{ "10.1.2.3", "127.0.0.1", "169.254.169.254", "172.20.0.1", "192.168.1.1", "100.64.0.1", "0.0.0.0", "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1" }) |ip| {
+        try std.testing.expect(!isPublicAddress(try std.net.Address.parseIp(ip, 443)));
+    }
+    for ([_][]const u8{ "93.184.216.34", "172.32.0.1", "2606:2800:220:1::1", "::ffff:93.184.216.34" }) |ip| {
+        try std.testing.expect(isPublicAddress(try std.net.Address.parseIp(ip, 443)));
+    }
+    try std.testing.expect(!isPublicUrl(std.testing.allocator, "https://127.0.0.1/admin"));
+    try std.testing.expect(!isPublicUrl(std.testing.allocator, "not a url"));
+}
@@ -0,0 +1,121 @@
+//! Per-file settings for repositories with several bibliographies.
+//!
+//! A thesis repository may keep `books.bib`, checked against Open Library
+//! and Google Books only, next to `papers.bib`, checked against Crossref
+//! and DBLP. Each `[[targets]]` table of the config names the files it
+//! applies to and overrides the backends, thresholds, disabled rules, and
+//! ignored keys for their entries:
+//!
+//! ```toml
+//! [[targets]]
+//! name = "books"
+//! files = ["books.bib"]
+//! validators = ["open_library", "google_books"]
+//! ignore = ["lecture_notes_2019"]
+//! ```
+//!
+//! An entry uses the first target matching its file, and the settings of
+//! the command line and the rest of the config otherwise.
+
+const std = @import("std");
+const glob = @import("glob.zig");
+const ignores = @import("ignores.zig");
+const policy = @import("policy.zig");
+const rules = @import("rules.zig");
+const entry_mod = @import("entry.zig");
+const ApiSource = entry_mod.ApiSource;
+const Entry = entry_mod.Entry;
+
+pub const Target = struct {
+    /// Shown with the target's ignored entries.
+    name: []const u8 = "",
+    /// Paths or patterns with `*` and `?`. A pattern without `/` matches
+    /// the file name in any directory.
+    files: []const []const u8,
+    /// Backends queried for the target's entries; null keeps the run's.
+    validators: ?std.EnumSet(ApiSource) = null,
+    min_confidence: ?f64 = null,
+    max_year_distance: ?i32 = null,
+    /// Rules disabled in addition to the run's.
+    disabled: rules.RuleSet = .initEmpty(),
+    /// Entries not expected to be found.
+    ignores: []const ignores.Ignore = &.{},
+
+    pub fn matches(self: *const Target, path: []const u8) bool {
+        for (self.files) |pattern| {
+            if (glob.match(pattern, path)) return true;
+            if (std.mem.indexOfScalar(u8, pattern, '/') == null and glob.match(pattern, std.fs.path.basename(path))) return true;
+        }
+        return false;
+    }
+};
+
+/// Index of the first target of `targets` that applies to `path`.
+pub fn forFile(targets: []const Target, path: ?[]const u8) ?usize {
+    const p = path orelse return null;
+    for (targets, 0..) |*target, i| {
+        if (target.matches(p)) return i;
+    }
+    return null;
+}
+
+/// Whether `rule` is disabled for the entry keyed `key`: in `disabled`,
+/// the run's set, or by the target of the entry's file.
+pub fn isDisabled(targets: []const Target, entries: []const Entry, disabled: rules.RuleSet, rule: rules.Rule, key: []const u8) bool {
+    if (disabled.contains(rule)) return true;
+    for (entries) |*e| {
+        if (!std.mem.eql(u8, e.key, key)) continue;
+        const t = forFile(targets, e.source_file) orelse return false;
+        return targets[t].disabled.contains(rule);
+    }
+    return false;
+}
+
+/// Drop the violations of `evaluation` whose entries all have the
+/// violated rule disabled by their target.
+pub fn dropDisabled(evaluation: *policy.Evaluation, targets: []const Target, entries: []const Entry) !void {
+    if (targets.len == 0) return;
+    var kept: std.ArrayList(policy.Violation) = .empty;
+    for (evaluation.violations) |v| {
+        const dropped = for (v.keys) |key| {
+            if (!isDisabled(targets, entries, .initEmpty(), v.rule, key)) break false;
+        } else v.keys.len > 0;
+        if (!dropped) try kept.append(evaluation.arena.allocator(), v);
+    }
+    evaluation.violations = kept.items;
+}
+
+test "targets apply by file" {
+    const targets = [_]Target{
+        .{ .files = &.{"books.bib"}, .validators = .initMany(&.{ .open_library, .google_books }) },
+        .{ .files = &.{"chapters/*.bib"} },
+    };
+    try std.testing.expectEqual(@as(?usize, 0), forFile(&targets, "books.bib"));
+    try std.testing.expectEqual(@as(?usize, 0), forFile(&targets, "refs/books.bib"));
+    try std.testing.expectEqual(@as(?usize, 1), forFile(&targets, "chapters/intro.bib"));
+    try std.testing.expectEqual(@as(?usize, null), forFile(&targets, "papers.bib"));
+    try std.testing.expectEqual(@as(?usize, null), forFile(&targets, null));
+}
+
+test "rules disabled by a target" {
+    var disabled: rules.RuleSet = .initEmpty();
+    disabled.insert(.duplicate_work);
+    const targets = [_]Target{.{ .files = &.{"books.bib"}, .disabled = disabled }};
+    const entries = [_]Entry{
+        .{ .key = "knuth1984", .entry_type = "book", .source_file = "books.bib" },
+        .{ .key = "knuth1984a", .entry_type = "book", .source_file = "books.bib" },
+        .{ .key = "lamport1978", .entry_type = "article", .source_file = "papers.bib" },
+    };
+    try std.testing.expect(isDisabled(&targets, &entries, .initEmpty(), .duplicate_work, "knuth1984"));
+    try std.testing.expect(!isDisabled(&targets, &entries, .initEmpty(), .duplicate_work, "lamport1978"));
+
+    var evaluation = policy.Evaluation{ .arena = std.heap.ArenaAllocator.init(std.testing.allocator) };
+    defer evaluation.deinit();
+    evaluation.violations = &.{
+        .{ .rule = .duplicate_work, .severity = .warning, .message = "same work", .keys = &.{ "knuth1984", "knuth1984a" } },
+        .{ .rule = .duplicate_work, .severity = .warning, .message = "same work", .keys = &.{ "knuth1984", "lamport1978" } },
+    };
+    try dropDisabled(&evaluation, &targets, &entries);
+    try std.testing.expectEqual(@as(usize, 1), evaluation.violations.len);
+    try std.testing.expectEqualStrings("lamport1978", evaluation.violations[0].keys[1]);
+}
@@ -0,0 +1,341 @@
+//! Zotero library access.
+//!
+//! Reads items from a running Zotero instance through its local API
+//! (Zotero 7, read-only) or from the Zotero web API, and writes suggested
+//! corrections back as item updates through the web API. Citation keys
+//! set by Better BibTeX are used as entry keys when present.
+
+const std = @import("std");
+const http = @import("http.zig");
+const entry_mod = @import("entry.zig");
+const fix = @import("fix.zig");
+const report = @import("report.zig");
+const Entry = entry_mod.Entry;
+
+pub const ZoteroError = error{
+    ServiceUnavailable,
+    InvalidResponse,
+    /// The update was rejected, e.g. because the item changed since it was read.
+    UpdateFailed,
+};
+
+/// Local API of the Zotero desktop app for the user's own library.
+pub const DEFAULT_URL = "http://localhost:23119/api/users/0";
+
+/// Items requested per page.
+const PAGE_SIZE = 100;
+
+/// Where an entry came from in the library, for writing fixes back.
+pub const Item = struct {
+    /// Citation key of the entry.
+    key: []const u8,
+    /// Zotero item key, e.g. "ABCD2345".
+    item_key: []const u8,
+    version: i64,
+    item_type: []const u8,
+    /// The item's date as entered, e.g. "2015-05-28", so a year fix keeps
+    /// its month and day.
+    date: []const u8 = "",
+};
+
+/// Entries read from Zotero. All memory is owned by the arena.
+pub const Library = struct {
+    entries: []Entry = &.{},
+    items: []Item = &.{},
+    arena: std.heap.ArenaAllocator,
+
+    pub fn deinit(self: *Library) void {
+        self.arena.deinit();
+    }
+
+    pub fn find(self: *const Library, key: []const u8) ?*const Item {
+        for (self.items) |*item| {
+            if (std.mem.eql(u8, item.key, key)) return item;
+        }
+        return null;
+    }
+};
+
+pub const Zotero = struct {
+    allocator: std.mem.Allocator,
+    client: *http.Client,
+    /// Library root, e.g. DEFAULT_URL or "https://api.zotero.org/users/12345".
+    base_url: []const u8,
+    /// Web API key; required for private libraries and for writing. Sent
+    /// in the `Zotero-API-Key` header, never in the URL.
+    api_key: ?[]const u8 = null,
+
+    key_header: [1]std.http.Header = undefined,
+
+    pub fn init(allocator: std.mem.Allocator, client: *http.Client, base_url: []const u8, api_key: ?[]const u8) Zotero {
+        return .{
+            .allocator = allocator,
+            .client = client,
+            .base_url = std.mem.trimRight(u8, base_url, "/"),
+            .api_key = api_key,
+            .key_header = .{.{ .name = "Zotero-API-Key", .value = api_key orelse "" }},
+        };
+    }
+
+    /// Read all top-level items of the library.
+    pub fn fetchLibrary(self: *Zotero) !Library {
+        var library = Library{ .arena = std.heap.ArenaAllocator.init(self.allocator) };
+        errdefer library.deinit();
+        const arena = library.arena.allocator();
+
+        var entries: std.ArrayList(Entry) = .empty;
+        var items: std.ArrayList(Item) = .empty;
+
+        var start: usize = 0;
+        while (true) : (start += PAGE_SIZE) {
+            const url = try std.fmt.allocPrint(self.allocator, "{s}/items/top?format=json&limit={d}&start={d}", .{ self.base_url, PAGE_SIZE, start });
+            defer self.allocator.free(url);
+
+            const body = self.client.getWithHeaders(url, self.headers()) catch return ZoteroError.ServiceUnavailable;
+            defer self.allocator.free(body);
+
+            const count = try parseItems(arena, body, &entries, &items);
+            if (count < PAGE_SIZE) break;
+        }
+
+        library.entries = entries.items;
+        library.items = items.items;
+        return library;
+    }
+
+    /// Set fields of an item. Fails if the item changed since it was read.
+    pub fn updateItem(self: *Zotero, item: *const Item, fixes: []const fix.Fix) !usize {
+        var body: std.Io.Writer.Allocating = .init(self.allocator);
+        defer body.deinit();
+        const w = &body.writer;
+
+        var changed: usize = 0;
+        try w.print("{{\"version\":{d}", .{item.version});
+        for (fixes) |f| {
+            const field = zoteroField(f.field, item.item_type) orelse continue;
+            try w.print(",\"{s}\":", .{field});
+            if (std.mem.eql(u8, field, "date")) {
+                const date = try withYear(self.allocator, item.date, f.value);
+                defer self.allocator.free(date);
+                try report.writeJsonString(w, date);
+            } else {
+                try report.writeJsonString(w, f.value);
+            }
+            changed += 1;
+        }
+        try w.writeAll("}");
+        if (changed == 0) return 0;
+
+        const url = try std.fmt.allocPrint(self.allocator, "{s}/items/{s}", .{ self.base_url, item.item_key });
+        defer self.allocator.free(url);
+
+        const response = self.client.patch(url, "application/json", body.written(), self.headers()) catch return ZoteroError.UpdateFailed;
+        self.allocator.free(response);
+        return changed;
+    }
+
+    fn headers(self: *const Zotero) []const std.http.Header {
+        return if (self.api_key != null) &self.key_header else &.{};
+    }
+};
+
+/// Parse a page of items in Zotero's JSON format, appending entries and
+/// items. Returns the number of items on the page, including skipped
+/// attachments and notes.
+pub fn parseItems(arena: std.mem.Allocator, body: []const u8, entries: *std.ArrayList(Entry), items: *std.ArrayList(Item)) !usize {
+    // Entries borrow strings from the parsed value, so copy them out of `body`
+    const parsed = std.json.parseFromSlice(std.json.Value, arena, body, .{ .allocate = .alloc_always }) catch return ZoteroError.InvalidResponse;
+    if (parsed.value != .array) return ZoteroError.InvalidResponse;
+
+    for (parsed.value.array.items) |value| {
+        if (value != .object) continue;
+        const data = value.object.get("data") orelse continue;
+        if (data != .object) continue;
+
+        const item_type = getString(data.object, "itemType") orelse continue;
+        if (std.mem.eql(u8, item_type, "attachment") or std.mem.eql(u8, item_type, "note")) continue;
+
+        var result = try itemToEntry(arena, data.object, item_type);
+        result.key = citationKey(data.object) orelse getString(value.object, "key") orelse
+            try entry_mod.generateKey(arena, &result, entries.items);
+        try entries.append(arena, result);
+
+        try items.append(arena, .{
+            .key = result.key,
+            .item_key = getString(value.object, "key") orelse "",
+            .version = if (value.object.get("version")) |v| (if (v == .integer) v.integer else 0) else 0,
+            .item_type = item_type,
+            .date = getString(data.object, "date") orelse "",
+        });
+    }
+    return parsed.value.array.items.len;
+}
+
+/// Entry for an item's data. Strings borrow from the parsed JSON, which
+/// lives in the same arena.
+fn itemToEntry(arena: std.mem.Allocator, data: std.json.ObjectMap, item_type: []const u8) !Entry {
+    var result = Entry{ .key = "", .entry_type = entryType(item_type) };
+
+    result.title = getString(data, "title");
+    result.doi = getString(data, "DOI");
+    result.url = getString(data, "url");
+    result.publisher = getString(data, "publisher");
+    result.venue = getString(data, "publicationTitle") orelse getString(data, "proceedingsTitle") orelse
+        getString(data, "bookTitle") orelse getString(data, "conferenceName");
+
+    // "2020-05-01", "May 2020", "2020"
+    if (getString(data, "date")) |date| result.year = firstYear(date);
+
+    if (data.get("creators")) |creators| {
+        if (creators == .array) {
+            var authors: std.ArrayList([]const u8) = .empty;
+            for (creators.array.items) |creator| {
+                if (creator != .object) continue;
+                const role = getString(creator.object, "creatorType") orelse "author";
+                if (!std.mem.eql(u8, role, "author")) continue;
+                if (getString(creator.object, "name")) |name| {
+                    try authors.append(arena, name);
+                } else if (getString(creator.object, "lastName")) |last| {
+                    const first = getString(creator.object, "firstName") orelse "";
+                    try authors.append(arena, if (first.len > 0)
+                        try std.fmt.allocPrint(arena, "{s}, {s}", .{ last, first })
+                    else
+                        last);
+                }
+            }
+            result.authors = authors.items;
+        }
+    }
+
+    return result;
+}
+
+/// The Better BibTeX citation key: the `citationKey` field, or a
+/// "Citation Key:" line in Extra on older versions.
+fn citationKey(data: std.json.ObjectMap) ?[]const u8 {
+    if (getString(data, "citationKey")) |key| return key;
+    const extra = getString(data, "extra") orelse return null;
+    var lines = std.mem.splitScalar(u8, extra, '\n');
+    while (lines.next()) |line| {
+        const prefix = "Citation Key:";
+        if (std.ascii.startsWithIgnoreCase(line, prefix)) {
+            const key = std.mem.trim(u8, line[prefix.len..], " \t\r");
+            if (key.len > 0) return key;
+        }
+    }
+    return null;
+}
+
+fn firstYear(date: []const u8) ?i32 {
+    const start = yearStart(date) orelse return null;
+    return std.fmt.parseInt(i32, date[start .. start + 4], 10) catch null;
+}
+
+/// Where the first four-digit run of a date starts.
+fn yearStart(date: []const u8) ?usize {
+    var i: usize = 0;
+    while (i + 4 <= date.len) : (i += 1) {
+        const is_year = for (date[i .. i + 4]) |c| {
+            if (!std.ascii.isDigit(c)) break false;
+        } else true;
+        if (is_year) return i;
+    }
+    return null;
+}
+
+/// `date` with its year replaced by `year`, keeping month and day:
+/// "2015-05-28" becomes "2016-05-28". Just `year` if the date has none.
+fn withYear(allocator: std.mem.Allocator, date: []const u8, year: []const u8) ![]u8 {
+    const start = yearStart(date) orelse return allocator.dupe(u8, year);
+    return std.mem.concat(allocator, u8, &.{ date[0..start], year, date[start + 4 ..] });
+}
+
+/// A non-empty string field; Zotero sends unset fields as "".
+fn getString(obj: std.json.ObjectMap, name: []const u8) ?[]const u8 {
+    const value = obj.get(name) orelse return null;
+    return if (value == .string and value.string.len > 0) value.string else null;
+}
+
+/// BibTeX entry type for a Zotero item type.
+fn entryType(item_type: []const u8) []const u8 {
+    const types = [_][2][]const u8{
+        .{ "journalArticle", "article" },
+        .{ "magazineArticle", "article" },
+        .{ "conferencePaper", "inproceedings" },
+        .{ "book", "book" },
+        .{ "bookSection", "incollection" },
+        .{ "thesis", "phdthesis" },
+        .{ "report", "techreport" },
+    };
+    for (types) |t| {
+        if (std.mem.eql(u8, item_type, t[0])) return t[1];
+    }
+    return "misc";
+}
+
+/// Zotero field a BibTeX fix is written to, or null if the item type has
+/// no such field.
+fn zoteroField(bibtex_field: []const u8, item_type: []const u8) ?[]const u8 {
+    if (std.mem.eql(u8, bibtex_field, "title")) return "title";
+    if (std.mem.eql(u8, bibtex_field, "year")) return "date";
+    if (std.mem.eql(u8, bibtex_field, "doi")) {
+        const has_doi = std.mem.eql(u8, item_type, "journalArticle") or
+            std.mem.eql(u8, item_type, "conferencePaper") or std.mem.eql(u8, item_type, "preprint");
+        return if (has_doi) "DOI" else null;
+    }
+    if (std.mem.eql(u8, bibtex_field, "journal") or std.mem.eql(u8, bibtex_field, "booktitle")) {
+        if (std.mem.eql(u8, item_type, "journalArticle") or std.mem.eql(u8, item_type, "magazineArticle")) return "publicationTitle";
+        if (std.mem.eql(u8, item_type, "conferencePaper")) return "proceedingsTitle";
+        if (std.mem.eql(u8, item_type, "bookSection")) return "bookTitle";
+    }
+    return null;
+}
+
+test "parse Zotero items" {
+    var arena = std.heap.ArenaAllocator.init(std.testing.allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    var entries: std.ArrayList(Entry) = .empty;
+    var items: std.ArrayList(Item) = .empty;
+    const count = try parseItems(allocator,
+        \\[{"key": "ABCD2345", "version": 7, "data": {"itemType": "journalArticle",
+        \\  "title": "Deep learning", "publicationTitle": "Nature", "date": "2015-05-28",
+        \\  "DOI": "10.1038/nature14539", "extra": "Citation Key: lecun2015deep",
+        \\  "creators": [{"creatorType": "author", "firstName": "Yann", "lastName": "LeCun"},
+        \\               {"creatorType": "editor", "name": "Someone Else"}]}},
+        \\ {"key": "EFGH6789", "version": 3, "data": {"itemType": "attachment", "title": "PDF"}},
+        \\ {"key": "JKLM2345", "version": 9, "data": {"itemType": "conferencePaper",
+        \\  "title": "Attention is all you need", "date": "December 2017", "citationKey": ""}}]
+    , &entries, &items);
+
+    try std.testing.expectEqual(@as(usize, 3), count);
+    try std.testing.expectEqual(@as(usize, 2), entries.items.len);
+    try std.testing.expectEqualStrings("lecun2015deep", entries.items[0].key);
+    try std.testing.expectEqualStrings("article", entries.items[0].entry_type);
+    try std.testing.expectEqual(@as(usize, 1), entries.items[0].authors.len);
+    try std.testing.expectEqualStrings("LeCun, Yann", entries.items[0].authors[0]);
+    try std.testing.expectEqual(@as(i32, 2015), entries.items[0].year.?);
+    try std.testing.expectEqualStrings("JKLM2345", entries.items[1].key);
+    try std.testing.expectEqual(@as(i32, 2017), entries.items[1].year.?);
+    try std.testing.expectEqual(@as(i64, 9), items.items[1].version);
+
+    try std.testing.expectEqualStrings("2015-05-28", items.items[0].date);
+
+    try std.testing.expectEqualStrings("proceedingsTitle", zoteroField("booktitle", "conferencePaper").?);
+    try std.testing.expectEqual(@as(?[]const u8, null), zoteroField("doi", "book"));
+}
+
+test "year fixes keep the month and day" {
+    const allocator = std.testing.allocator;
+    const cases = [_][3][]const u8{
+        .{ "2015-05-28", "2016", "2016-05-28" },
+        .{ "December 2017", "2018", "December 2018" },
+        .{ "", "2020", "2020" },
+    };
+    for (cases) |case| {
+        const date = try withYear(allocator, case[0], case[1]);
+        defer allocator.free(date);
+        try std.testing.expectEqualStrings(case[2], date);
+    }
+}